@@ -0,0 +1,35 @@
+// Compares Vector3::angle() (atan2 of the cross and dot products) with
+// Vector3::angle_unit() (chord length and asin) on random unit vectors.
+//
+// Run with `cargo bench --bench angle`.
+
+mod common;
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+fn bench_angle(c: &mut Criterion) {
+    let points = common::random_points(1024, 1);
+    let pairs: Vec<_> = points.iter().zip(points.iter().rev()).collect();
+
+    let mut group = c.benchmark_group("vector3_angle");
+    group.bench_function("angle", |b| {
+        b.iter(|| {
+            for (a, b) in &pairs {
+                black_box(black_box(*a).angle(b));
+            }
+        })
+    });
+    group.bench_function("angle_unit", |b| {
+        b.iter(|| {
+            for (a, b) in &pairs {
+                black_box(black_box(*a).angle_unit(b));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_angle);
+criterion_main!(benches);
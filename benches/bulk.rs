@@ -0,0 +1,44 @@
+// Measures how the rayon-based bulk conversion scales with the number of
+// threads on 1M random points, against the sequential loop.
+//
+// Run with `cargo bench --features rayon --bench bulk`.
+
+mod common;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use s2shell::{bulk::cell_ids_from_points, s2::S2CellId};
+
+const NUM_POINTS: usize = 1_000_000;
+
+fn bench_bulk(c: &mut Criterion) {
+    let points = common::random_points(NUM_POINTS, 4);
+    let level = S2CellId::MAX_LEVEL;
+
+    let mut group = c.benchmark_group("bulk");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(NUM_POINTS as u64));
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            points
+                .iter()
+                .map(|p| S2CellId::from_point(p).parent_at_level(level))
+                .collect::<Vec<_>>()
+        })
+    });
+    let max_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut threads = 1;
+    while threads <= max_threads {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        group.bench_with_input(BenchmarkId::new("threads", threads), &threads, |b, _| {
+            b.iter(|| pool.install(|| cell_ids_from_points(&points, level)))
+        });
+        threads *= 2;
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_bulk);
+criterion_main!(benches);
@@ -0,0 +1,39 @@
+// Benchmarks for converting points to cell ids.
+//
+// Run with `cargo bench --bench cell_id`.
+
+mod common;
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use s2shell::s2::{S2CellId, S2LatLng};
+
+/// Compares S2CellId::from_lat_lngs() with converting each point to a leaf
+/// cell id and taking its parent.
+fn bench_from_lat_lngs(c: &mut Criterion) {
+    let points: Vec<S2LatLng> = common::random_points(10_000, 2)
+        .iter()
+        .map(S2LatLng::from_point)
+        .collect();
+
+    let mut group = c.benchmark_group("from_lat_lngs");
+    group.throughput(Throughput::Elements(points.len() as u64));
+    for level in [10, S2CellId::MAX_LEVEL] {
+        group.bench_with_input(BenchmarkId::new("scalar", level), &level, |b, &level| {
+            b.iter(|| {
+                black_box(&points)
+                    .iter()
+                    .map(|ll| S2CellId::from_lat_lng(ll).parent_at_level(level))
+                    .collect::<Vec<_>>()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("batch", level), &level, |b, &level| {
+            b.iter(|| S2CellId::from_lat_lngs(black_box(&points), level))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_from_lat_lngs);
+criterion_main!(benches);
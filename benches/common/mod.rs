@@ -0,0 +1,22 @@
+// Shared helpers for the benchmarks. Inputs are generated from a fixed seed so
+// that every run benchmarks the same data.
+
+#![allow(dead_code)]
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use s2shell::s2::S2Point;
+
+/// Returns "n" random unit-length points.
+pub fn random_points(n: usize, seed: u64) -> Vec<S2Point> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..n)
+        .map(|_| {
+            S2Point::new(
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+            )
+            .normalize()
+        })
+        .collect()
+}
@@ -0,0 +1,43 @@
+// Measures the per-call cost of the Hilbert curve lookup tables in
+// S2CellId::to_face_ij_orientation() and S2CellId::from_face_ij().
+//
+// The tables are computed at compile time, so each call is a plain indexed
+// load with no initialization check.
+//
+// Run with `cargo bench --bench lookup_tables`.
+
+mod common;
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use s2shell::s2::S2CellId;
+
+fn bench_lookup_tables(c: &mut Criterion) {
+    let ids: Vec<S2CellId> = common::random_points(1024, 3)
+        .iter()
+        .map(S2CellId::from_point)
+        .collect();
+    let coords: Vec<_> = ids.iter().map(|id| id.to_face_ij_orientation()).collect();
+
+    let mut group = c.benchmark_group("lookup_tables");
+    group.throughput(Throughput::Elements(ids.len() as u64));
+    group.bench_function("to_face_ij_orientation", |b| {
+        b.iter(|| {
+            for id in black_box(&ids) {
+                black_box(id.to_face_ij_orientation());
+            }
+        })
+    });
+    group.bench_function("from_face_ij", |b| {
+        b.iter(|| {
+            for &(face, i, j, _) in black_box(&coords) {
+                black_box(S2CellId::from_face_ij(face, i, j));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_lookup_tables);
+criterion_main!(benches);
@@ -0,0 +1,55 @@
+// Compares evaluating sin() and cos() separately with S1Angle::sin_cos() and
+// with stepping a cached TrigPair using the angle addition formulas, over a
+// loop of 10 million angles.
+//
+// Run with `cargo bench --bench trig`.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use s2shell::s1::{S1Angle, TrigPair};
+
+const ITERATIONS: u64 = 10_000_000;
+const STEP: f64 = 1e-6;
+
+fn bench_trig(c: &mut Criterion) {
+    let mut group = c.benchmark_group("trig");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(ITERATIONS));
+    group.bench_function("separate", |b| {
+        b.iter(|| {
+            let mut total = 0.0;
+            for i in 0..ITERATIONS {
+                let a = S1Angle::from_radians(black_box(i as f64 * STEP));
+                total += a.sin() * a.cos();
+            }
+            total
+        })
+    });
+    group.bench_function("sin_cos", |b| {
+        b.iter(|| {
+            let mut total = 0.0;
+            for i in 0..ITERATIONS {
+                let (sin, cos) = S1Angle::from_radians(black_box(i as f64 * STEP)).sin_cos();
+                total += sin * cos;
+            }
+            total
+        })
+    });
+    group.bench_function("cached_trig_pair", |b| {
+        let step = TrigPair::from(S1Angle::from_radians(STEP));
+        b.iter(|| {
+            let mut total = 0.0;
+            let mut pair = TrigPair::from(S1Angle::zero());
+            for _ in 0..ITERATIONS {
+                total += pair.sin * pair.cos;
+                pair = pair.sum(black_box(&step));
+            }
+            total
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_trig);
+criterion_main!(benches);
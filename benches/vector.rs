@@ -0,0 +1,70 @@
+// Quantifies the cost of the accurate (FMA and TwoProduct based) vector
+// products relative to the default fast versions, on random unit vectors.
+//
+// Run with `cargo bench --bench vector`.
+
+mod common;
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use s2shell::util::math::Vector3;
+
+fn bench_products(c: &mut Criterion) {
+    let points = common::random_points(1024, 5);
+    let triples: Vec<_> = points
+        .iter()
+        .zip(points.iter().skip(1))
+        .zip(points.iter().skip(2))
+        .map(|((a, b), c)| (*a, *b, *c))
+        .collect();
+
+    let mut group = c.benchmark_group("vector3_products");
+    group.throughput(Throughput::Elements(triples.len() as u64));
+    group.bench_function("dot_prod", |b| {
+        b.iter(|| {
+            for (x, y, _) in black_box(&triples) {
+                black_box(x.dot_prod(y));
+            }
+        })
+    });
+    group.bench_function("dot_prod_accurate", |b| {
+        b.iter(|| {
+            for (x, y, _) in black_box(&triples) {
+                black_box(x.dot_prod_accurate(y));
+            }
+        })
+    });
+    group.bench_function("cross_prod", |b| {
+        b.iter(|| {
+            for (x, y, _) in black_box(&triples) {
+                black_box(x.cross_prod(y));
+            }
+        })
+    });
+    group.bench_function("cross_prod_accurate", |b| {
+        b.iter(|| {
+            for (x, y, _) in black_box(&triples) {
+                black_box(x.cross_prod_accurate(y));
+            }
+        })
+    });
+    group.bench_function("triple_product", |b| {
+        b.iter(|| {
+            for (x, y, z) in black_box(&triples) {
+                black_box(x.dot_prod(&y.cross_prod(z)));
+            }
+        })
+    });
+    group.bench_function("scalar_triple_product", |b| {
+        b.iter(|| {
+            for (x, y, z) in black_box(&triples) {
+                black_box(Vector3::scalar_triple_product(x, y, z));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_products);
+criterion_main!(benches);
@@ -0,0 +1,52 @@
+/*
+ * C interface to the s2shell cell id operations.
+ *
+ * Build the library with the "ffi" feature, e.g.
+ *
+ *     cargo rustc --release --features ffi --crate-type cdylib
+ *
+ * Cell ids are passed as their 64-bit integer value. The invalid id 0 is
+ * returned on error. No function unwinds into the caller.
+ */
+
+#ifndef S2SHELL_H_
+#define S2SHELL_H_
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Returns the id of the cell at "level" containing the given point in
+ * degrees, or 0 if the arguments are out of range. */
+uint64_t s2_cellid_from_latlng_degrees(double lat, double lng, int32_t level);
+
+/* Writes the center of the cell in degrees. Returns false if the id is not
+ * valid or a pointer is null. */
+bool s2_cellid_to_latlng_degrees(uint64_t id, double *out_lat, double *out_lng);
+
+/* Returns the ancestor of the cell at "level", or 0 on error. */
+uint64_t s2_cellid_parent(uint64_t id, int32_t level);
+
+/* Returns true if cell "a" contains cell "b". */
+bool s2_cellid_contains(uint64_t a, uint64_t b);
+
+/* Returns the level of the cell, or -1 if the id is not valid. */
+int32_t s2_cellid_level(uint64_t id);
+
+/* Writes the NUL-terminated token of the cell to "buf" if it fits, and
+ * returns the token length excluding the terminator (1 to 16). Returns 0
+ * only if an internal error occurred. */
+size_t s2_cellid_to_token(uint64_t id, char *buf, size_t buf_len);
+
+/* Decodes a token of "len" bytes. Returns 0 if the token is malformed. */
+uint64_t s2_cellid_from_token(const char *ptr, size_t len);
+
+#ifdef __cplusplus
+} /* extern "C" */
+#endif
+
+#endif /* S2SHELL_H_ */
@@ -0,0 +1,159 @@
+//! Conversion of large batches of points to cell ids using all available
+//! cores, enabled by the `rayon` feature.
+//!
+//! The work is spread over rayon's global thread pool, but the results are
+//! deterministic: they are identical to converting each point sequentially
+//! with S2CellId::from_point(), regardless of the number of threads.
+
+use rayon::prelude::*;
+
+use crate::s2::{S2CellId, S2LatLng, S2Point};
+
+/// Returns the cell at the given level containing each point, in the same
+/// order as "points".  The points do not need to be normalized.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::bulk::cell_ids_from_points;
+/// use s2shell::s2::{S2CellId, S2Point};
+///
+/// let points = [S2Point::new(1.0, 0.0, 0.0), S2Point::new(0.0, 0.0, -1.0)];
+/// let ids = cell_ids_from_points(&points, 0);
+/// assert_eq!(ids, [S2CellId::from_face(0), S2CellId::from_face(5)]);
+/// ```
+pub fn cell_ids_from_points(points: &[S2Point], level: i32) -> Vec<S2CellId> {
+    debug_assert!((0..=S2CellId::MAX_LEVEL).contains(&level));
+    points
+        .par_iter()
+        .map(|p| S2CellId::from_point(p).parent_at_level(level))
+        .collect()
+}
+
+/// Returns the cell at the given level containing each (latitude, longitude)
+/// pair in degrees, in the same order as "coords".  As with
+/// S2LatLng::from_degrees(), the coordinates must be normalized.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::bulk::cell_ids_from_latlng_degrees;
+/// use s2shell::s2::{S2CellId, S2LatLng};
+///
+/// let ids = cell_ids_from_latlng_degrees(&[(40.7550976, -73.9830029)], 10);
+/// assert_eq!(ids, [S2CellId::from_token("89c259")]);
+/// ```
+pub fn cell_ids_from_latlng_degrees(coords: &[(f64, f64)], level: i32) -> Vec<S2CellId> {
+    debug_assert!((0..=S2CellId::MAX_LEVEL).contains(&level));
+    coords
+        .par_iter()
+        .map(|&(lat, lng)| {
+            S2CellId::from_lat_lng(&S2LatLng::from_degrees(lat, lng)).parent_at_level(level)
+        })
+        .collect()
+}
+
+/// Like cell_ids_from_points(), but returns the cell ids in sorted order
+/// together with the permutation that sorts them: the k-th cell id contains
+/// points[permutation[k]].  Points in the same cell keep their input order,
+/// so the permutation can be used to reorder any per-point payload.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::bulk::sorted_cell_ids_from_points;
+/// use s2shell::s2::{S2CellId, S2Point};
+///
+/// let points = [
+///     S2Point::new(0.0, 0.0, -1.0),
+///     S2Point::new(1.0, 0.0, 0.0),
+///     S2Point::new(0.0, 0.0, -2.0),
+/// ];
+/// let (ids, permutation) = sorted_cell_ids_from_points(&points, 0);
+/// assert_eq!(ids, [0, 5, 5].map(S2CellId::from_face));
+/// assert_eq!(permutation, [1, 0, 2]);
+/// ```
+pub fn sorted_cell_ids_from_points(points: &[S2Point], level: i32) -> (Vec<S2CellId>, Vec<usize>) {
+    let mut pairs: Vec<(S2CellId, usize)> = cell_ids_from_points(points, level)
+        .into_par_iter()
+        .enumerate()
+        .map(|(k, id)| (id, k))
+        .collect();
+    // The indices are distinct, so an unstable sort is deterministic.
+    pairs.par_sort_unstable();
+    pairs.into_par_iter().unzip()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::s2::s2testing::random_points;
+
+    #[test]
+    fn test_cell_ids_from_points() {
+        let mut rng = StdRng::seed_from_u64(27);
+        let points = random_points(&mut rng, 100_000);
+        for level in [0, 1, 10, S2CellId::MAX_LEVEL] {
+            let expected: Vec<S2CellId> = points
+                .iter()
+                .map(|p| S2CellId::from_point(p).parent_at_level(level))
+                .collect();
+            assert_eq!(cell_ids_from_points(&points, level), expected);
+        }
+        assert!(cell_ids_from_points(&[], 10).is_empty());
+    }
+
+    #[test]
+    fn test_cell_ids_from_latlng_degrees() {
+        let mut rng = StdRng::seed_from_u64(28);
+        let lls: Vec<S2LatLng> = random_points(&mut rng, 100_000)
+            .iter()
+            .map(S2LatLng::from_point)
+            .collect();
+        let coords: Vec<(f64, f64)> = lls
+            .iter()
+            .map(|ll| (ll.lat().degrees(), ll.lng().degrees()))
+            .collect();
+        let expected = S2CellId::from_lat_lngs(
+            &coords
+                .iter()
+                .map(|&(lat, lng)| S2LatLng::from_degrees(lat, lng))
+                .collect::<Vec<_>>(),
+            12,
+        );
+        assert_eq!(cell_ids_from_latlng_degrees(&coords, 12), expected);
+    }
+
+    #[test]
+    fn test_sorted_cell_ids_from_points() {
+        let mut rng = StdRng::seed_from_u64(29);
+        // Repeat every point so that some cells contain several points.
+        let mut points = random_points(&mut rng, 50_000);
+        points.extend_from_within(..);
+        for level in [0, 5, S2CellId::MAX_LEVEL] {
+            let unsorted = cell_ids_from_points(&points, level);
+            let (ids, permutation) = sorted_cell_ids_from_points(&points, level);
+            assert_eq!(ids.len(), points.len());
+            assert!(ids.windows(2).all(|w| w[0] <= w[1]));
+
+            // The permutation is a bijection that maps each sorted id back to
+            // its point, and ties are broken by input order.
+            let mut seen = vec![false; points.len()];
+            for (k, &index) in permutation.iter().enumerate() {
+                assert!(!seen[index]);
+                seen[index] = true;
+                assert_eq!(ids[k], unsorted[index]);
+                if k > 0 && ids[k - 1] == ids[k] {
+                    assert!(permutation[k - 1] < index);
+                }
+            }
+
+            // Sequential reference.
+            let mut expected: Vec<usize> = (0..points.len()).collect();
+            expected.sort_by_key(|&k| unsorted[k]);
+            assert_eq!(permutation, expected);
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! A C interface to the S2CellId operations, enabled by the "ffi" feature.
+//!
+//! Cell ids are passed across the boundary as their 64-bit integer value.
+//! The invalid id 0 (S2CellId::none()) is returned on error, for example
+//! when an argument is out of range. No function unwinds into the caller:
+//! any panic is caught at the boundary and reported as an error.
+//!
+//! The declarations for C are in `include/s2shell.h`. To link against the
+//! library from C, build it as a shared or static library, e.g.
+//!
+//! ```text
+//! cargo rustc --release --features ffi --crate-type cdylib
+//! ```
+
+use core::{ffi::c_char, slice, str};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use crate::s2::S2CellId;
+
+// Runs "f", returning "default" if it panics.
+fn guard<T>(default: T, f: impl FnOnce() -> T) -> T {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(default)
+}
+
+/// Returns the id of the cell at the given level containing the given
+/// latitude and longitude in degrees, or 0 if the latitude is outside
+/// [-90, 90], either coordinate is not finite, or the level is outside
+/// [0, 30]. Longitudes outside [-180, 180] are wrapped.
+#[no_mangle]
+pub extern "C" fn s2_cellid_from_latlng_degrees(lat: f64, lng: f64, level: i32) -> u64 {
+    guard(0, || {
+        S2CellId::from_latlng_degrees_at_level(lat, lng, level).id()
+    })
+}
+
+/// Writes the latitude and longitude in degrees of the center of the cell
+/// to "out_lat" and "out_lng". Returns false, leaving the outputs
+/// unchanged, if the id is not valid or either pointer is null.
+///
+/// # Safety
+///
+/// Non-null pointers must be valid for writing an f64.
+#[no_mangle]
+pub unsafe extern "C" fn s2_cellid_to_latlng_degrees(
+    id: u64,
+    out_lat: *mut f64,
+    out_lng: *mut f64,
+) -> bool {
+    guard(false, || {
+        let id = S2CellId::new(id);
+        if !id.is_valid() || out_lat.is_null() || out_lng.is_null() {
+            return false;
+        }
+        let (lat, lng) = id.to_latlng_degrees();
+        // SAFETY: the caller guarantees that non-null pointers are valid.
+        unsafe {
+            *out_lat = lat;
+            *out_lng = lng;
+        }
+        true
+    })
+}
+
+/// Returns the ancestor of the cell at the given level, or 0 if the id is
+/// not valid or the level is outside [0, level of the cell].
+#[no_mangle]
+pub extern "C" fn s2_cellid_parent(id: u64, level: i32) -> u64 {
+    guard(0, || {
+        let id = S2CellId::new(id);
+        if !id.is_valid() || !(0..=id.level()).contains(&level) {
+            return 0;
+        }
+        id.parent_at_level(level).id()
+    })
+}
+
+/// Returns true if the cell "a" contains the cell "b". Returns false if
+/// either id is not valid.
+#[no_mangle]
+pub extern "C" fn s2_cellid_contains(a: u64, b: u64) -> bool {
+    guard(false, || {
+        let (a, b) = (S2CellId::new(a), S2CellId::new(b));
+        a.is_valid() && b.is_valid() && a.contains(&b)
+    })
+}
+
+/// Returns the level of the cell (0 for face cells, 30 for leaf cells), or
+/// -1 if the id is not valid.
+#[no_mangle]
+pub extern "C" fn s2_cellid_level(id: u64) -> i32 {
+    guard(-1, || {
+        let id = S2CellId::new(id);
+        if id.is_valid() {
+            id.level()
+        } else {
+            -1
+        }
+    })
+}
+
+/// Writes the token of the cell (see S2CellId::to_token) to "buf" as a
+/// NUL-terminated string, and returns the length of the token excluding the
+/// terminator. As with snprintf(), nothing is written if "buf" is null or
+/// "buf_len" is too small to hold the token and its terminator; the return
+/// value can then be used to size the buffer. Tokens are between 1 and 16
+/// characters long, so 0 is only returned if a panic was caught.
+///
+/// # Safety
+///
+/// If "buf" is non-null, it must be valid for writing "buf_len" bytes.
+#[no_mangle]
+pub unsafe extern "C" fn s2_cellid_to_token(id: u64, buf: *mut c_char, buf_len: usize) -> usize {
+    guard(0, || {
+        let token = S2CellId::new(id).to_token();
+        if !buf.is_null() && buf_len > token.len() {
+            // SAFETY: the caller guarantees that "buf" is valid for writing
+            // "buf_len" bytes, which we have checked is enough.
+            let out = unsafe { slice::from_raw_parts_mut(buf.cast::<u8>(), buf_len) };
+            out[..token.len()].copy_from_slice(token.as_bytes());
+            out[token.len()] = 0;
+        }
+        token.len()
+    })
+}
+
+/// Decodes the token of "len" bytes at "ptr" (which need not be
+/// NUL-terminated). Returns 0 if the pointer is null or the token is
+/// malformed.
+///
+/// # Safety
+///
+/// If "ptr" is non-null, it must be valid for reading "len" bytes.
+#[no_mangle]
+pub unsafe extern "C" fn s2_cellid_from_token(ptr: *const c_char, len: usize) -> u64 {
+    guard(0, || {
+        if ptr.is_null() {
+            return 0;
+        }
+        // SAFETY: the caller guarantees that "ptr" is valid for reading "len"
+        // bytes.
+        let bytes = unsafe { slice::from_raw_parts(ptr.cast::<u8>(), len) };
+        match str::from_utf8(bytes) {
+            Ok(token) => S2CellId::from_token(token).id(),
+            Err(_) => 0,
+        }
+    })
+}
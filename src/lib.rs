@@ -1,4 +1,13 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![allow(dead_code, unused_variables)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "rayon")]
+pub mod bulk;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 pub mod r1;
 pub mod r2;
 pub mod s1;
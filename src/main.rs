@@ -0,0 +1,109 @@
+// A small command line tool for inspecting cells:
+//
+//   s2shell token <id>                 cell with the given 64-bit id
+//   s2shell latlng <lat> <lng> [level] cell containing a point (in degrees)
+//   s2shell cell <token>               cell with the given token
+//
+// Each command prints the cell's id, token, level, center and vertices.
+
+use std::{env, process::ExitCode};
+
+use s2shell::s2::{S2Cell, S2CellId, S2LatLng};
+
+const USAGE: &str = "\
+usage: s2shell <command> <args>
+
+commands:
+  token <id>                  describe the cell with the given 64-bit id
+                              (decimal, or hex with a 0x prefix)
+  latlng <lat> <lng> [level]  describe the cell at the given level (default
+                              30) containing the point, in degrees
+  cell <token>                describe the cell with the given token";
+
+fn parse_id(arg: &str) -> Result<S2CellId, String> {
+    let id = match arg.strip_prefix("0x").or_else(|| arg.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => arg.parse(),
+    };
+    id.map(S2CellId::new)
+        .map_err(|_| format!("invalid cell id: {arg:?}"))
+}
+
+fn parse_degrees(arg: &str, name: &str, limit: f64) -> Result<f64, String> {
+    match arg.parse::<f64>() {
+        Ok(x) if x.abs() <= limit => Ok(x),
+        _ => Err(format!("invalid {name}: {arg:?}")),
+    }
+}
+
+fn parse_level(arg: &str) -> Result<i32, String> {
+    match arg.parse::<i32>() {
+        Ok(level) if (0..=S2CellId::MAX_LEVEL).contains(&level) => Ok(level),
+        _ => Err(format!(
+            "invalid level: {arg:?} (must be 0..={})",
+            S2CellId::MAX_LEVEL
+        )),
+    }
+}
+
+// Parses the command line (without the program name) into a cell id.
+fn parse_args(args: &[String]) -> Result<S2CellId, String> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let id = match args.as_slice() {
+        ["token", id] => parse_id(id)?,
+        ["latlng", lat, lng, rest @ ..] if rest.len() <= 1 => {
+            let lat = parse_degrees(lat, "latitude", 90.0)?;
+            let lng = parse_degrees(lng, "longitude", 180.0)?;
+            let level = rest
+                .first()
+                .map_or(Ok(S2CellId::MAX_LEVEL), |l| parse_level(l))?;
+            S2CellId::from_lat_lng(&S2LatLng::from_degrees(lat, lng)).parent_at_level(level)
+        }
+        ["cell", token] => {
+            let id = S2CellId::from_token(token);
+            if !id.is_valid() {
+                return Err(format!("invalid cell token: {token:?}"));
+            }
+            id
+        }
+        _ => return Err(USAGE.to_string()),
+    };
+    if !id.is_valid() {
+        return Err(format!("not a valid cell: {id}"));
+    }
+    Ok(id)
+}
+
+fn describe(id: S2CellId) -> String {
+    let cell = S2Cell::new(id);
+    let mut out = format!(
+        "id:       0x{:016x} ({})\ntoken:    {}\ncell:     {}\nlevel:    {}\ncenter:   {}\n",
+        id.id(),
+        id.id(),
+        id.to_token(),
+        id,
+        id.level(),
+        id.to_lat_lng(),
+    );
+    for k in 0..4 {
+        out += &format!(
+            "vertex {k}: {}\n",
+            S2LatLng::from_point(&cell.get_vertex(k))
+        );
+    }
+    out
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match parse_args(&args) {
+        Ok(id) => {
+            print!("{}", describe(id));
+            ExitCode::SUCCESS
+        }
+        Err(message) => {
+            eprintln!("{message}");
+            ExitCode::from(2)
+        }
+    }
+}
@@ -1,4 +1,7 @@
-use std::ops::{Index, IndexMut};
+use core::{
+    fmt,
+    ops::{Index, IndexMut},
+};
 
 use crate::util::math::Vector2;
 
@@ -8,7 +11,7 @@ use crate::util::math::Vector2;
 ///
 /// This class is intended to be copied by value as desired.  It uses
 /// the default copy constructor and assignment operator.
-#[derive(Debug, Copy, Clone, Default)]
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
 pub struct R1Interval {
     bounds: Vector2<f64>,
 }
@@ -21,6 +24,11 @@ impl R1Interval {
         }
     }
 
+    /// Returns the empty interval.
+    pub fn empty() -> R1Interval {
+        R1Interval::new(1.0, 0.0)
+    }
+
     pub fn from_point(p: f64) -> R1Interval {
         R1Interval::new(p, p)
     }
@@ -78,12 +86,68 @@ impl R1Interval {
         p > self.lo() && p < self.hi()
     }
 
-    pub fn contains_interval(y: &R1Interval) -> bool {
-        todo!()
+    /// Returns the closest point in the interval to the given point "p".
+    /// The interval must be non-empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::r1::R1Interval;
+    ///
+    /// let x = R1Interval::new(0.1, 0.4);
+    /// assert_eq!(x.project(0.3), 0.3);
+    /// assert_eq!(x.project(-7.0), 0.1);
+    /// assert_eq!(x.project(0.6), 0.4);
+    /// ```
+    pub fn project(&self, p: f64) -> f64 {
+        debug_assert!(!self.is_empty());
+        self.lo().max(self.hi().min(p))
+    }
+
+    /// Returns true if this interval contains the interval "y".
+    pub fn contains_interval(&self, y: &R1Interval) -> bool {
+        if y.is_empty() {
+            return true;
+        }
+        y.lo() >= self.lo() && y.hi() <= self.hi()
+    }
+
+    /// Returns true if the interior of this interval contains the entire
+    /// interval "y" (including its boundary).
+    pub fn interior_contains_interval(&self, y: &R1Interval) -> bool {
+        if y.is_empty() {
+            return true;
+        }
+        y.lo() > self.lo() && y.hi() < self.hi()
     }
 
-    pub fn interior_contains_interval(y: &R1Interval) -> bool {
-        todo!()
+    /// Return an interval that has been expanded on each side by the given
+    /// distance "margin". If "margin" is negative, then shrink the interval on
+    /// each side by "margin" instead. The resulting interval may be empty. Any
+    /// expansion of an empty interval remains empty.
+    pub fn expanded(&self, margin: f64) -> R1Interval {
+        if self.is_empty() {
+            return *self;
+        }
+        R1Interval::new(self.lo() - margin, self.hi() + margin)
+    }
+
+    /// Return the smallest interval that contains this interval and the
+    /// given interval "y".
+    pub fn union(&self, y: &R1Interval) -> R1Interval {
+        if self.is_empty() {
+            return *y;
+        }
+        if y.is_empty() {
+            return *self;
+        }
+        R1Interval::new(self.lo().min(y.lo()), self.hi().max(y.hi()))
+    }
+
+    /// Return the intersection of this interval with the given interval.
+    /// Empty intervals do not need to be special-cased.
+    pub fn intersection(&self, y: &R1Interval) -> R1Interval {
+        R1Interval::new(self.lo().max(y.lo()), self.hi().min(y.hi()))
     }
 
     pub fn intersects(&self, y: &R1Interval) -> bool {
@@ -112,3 +176,19 @@ impl IndexMut<usize> for R1Interval {
         &mut self.bounds[index]
     }
 }
+
+/// Formats the interval as "[lo, hi]".
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::r1::R1Interval;
+///
+/// assert_eq!(R1Interval::new(-1.5, 2.0).to_string(), "[-1.5, 2]");
+/// assert_eq!(R1Interval::empty().to_string(), "[1, 0]");
+/// ```
+impl fmt::Display for R1Interval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}, {}]", self.lo(), self.hi())
+    }
+}
@@ -15,7 +15,7 @@
 
 // Original Author: ericv@google.com (Eric Veach)
 
-use crate::util::math::Vector2;
+use crate::{r1::R1Interval, util::math::Vector2};
 
 pub type R2Point = Vector2<f64>;
 
@@ -30,8 +30,332 @@ impl R2Edge {
     pub fn new(v0: &R2Point, v1: &R2Point) -> R2Edge {
         R2Edge { v0: *v0, v1: *v1 }
     }
+
+    pub fn v0(&self) -> R2Point {
+        self.v0
+    }
+
+    pub fn v1(&self) -> R2Point {
+        self.v1
+    }
+
+    /// Return true if this edge and the given edge have at least one point in
+    /// common. Edges are closed, so touching at an endpoint or overlapping
+    /// along a common line both count as intersecting.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::r2::{R2Edge, R2Point};
+    ///
+    /// let a = R2Edge::new(&R2Point::new(0.0, 0.0), &R2Point::new(1.0, 1.0));
+    /// let b = R2Edge::new(&R2Point::new(0.0, 1.0), &R2Point::new(1.0, 0.0));
+    /// let c = R2Edge::new(&R2Point::new(2.0, 0.0), &R2Point::new(2.0, 1.0));
+    /// assert!(a.intersects(&b));
+    /// assert!(!a.intersects(&c));
+    /// ```
+    pub fn intersects(&self, other: &R2Edge) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// Return a point common to this edge and the given edge, or `None` if the
+    /// edges do not intersect. If the edges cross at a single point, that
+    /// point is returned. If the edges are collinear and overlap, the returned
+    /// point is the first endpoint (of either edge) that lies on the other
+    /// edge.
+    pub fn intersection(&self, other: &R2Edge) -> Option<R2Point> {
+        let (a, b, c, d) = (self.v0, self.v1, other.v0, other.v1);
+        let ab = b - a;
+        let cd = d - c;
+        let ac = c - a;
+        let denom = ab.cross_prod(&cd);
+        if denom == 0.0 {
+            // The edges are parallel. They can only intersect if they are also
+            // collinear, in which case we look for an endpoint of one edge that
+            // lies on the other.
+            if ab.cross_prod(&ac) != 0.0 || cd.cross_prod(&ac) != 0.0 {
+                return None;
+            }
+            return [c, d]
+                .into_iter()
+                .find(|p| self.contains_collinear(p))
+                .or_else(|| [a, b].into_iter().find(|p| other.contains_collinear(p)));
+        }
+        // Solve a + s * ab = c + t * cd for the parameters s and t, and check
+        // that both lie within [0, 1].
+        let s = ac.cross_prod(&cd) / denom;
+        let t = ac.cross_prod(&ab) / denom;
+        if !(0.0..=1.0).contains(&s) || !(0.0..=1.0).contains(&t) {
+            return None;
+        }
+        Some(a + ab * s)
+    }
+
+    /// Clip this edge to the given rectangle, returning the portion of the
+    /// edge that lies inside it (including its boundary), or `None` if the
+    /// edge does not intersect the rectangle. The clipped endpoints keep the
+    /// direction of the original edge.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::{
+    ///     r1::R1Interval,
+    ///     r2::{R2Edge, R2Point, R2Rect},
+    /// };
+    ///
+    /// let rect = R2Rect::new(R1Interval::new(-1.0, 1.0), R1Interval::new(-1.0, 1.0));
+    /// let edge = R2Edge::new(&R2Point::new(-2.0, 0.0), &R2Point::new(2.0, 0.0));
+    /// let clipped = edge.clip_to_rect(&rect).unwrap();
+    /// assert_eq!(clipped.v0(), R2Point::new(-1.0, 0.0));
+    /// assert_eq!(clipped.v1(), R2Point::new(1.0, 0.0));
+    /// ```
+    pub fn clip_to_rect(&self, clip: &R2Rect) -> Option<R2Edge> {
+        // Compute the bounding rectangle of the edge, clip it, and then extract
+        // the new endpoints from the clipped bound.
+        let (a, b) = (self.v0, self.v1);
+        let mut bound = R2Rect::from_point_pair(&a, &b);
+        if !clip_edge_bound(&a, &b, clip, &mut bound) {
+            return None;
+        }
+        let ai = (a[0] > b[0]) as i32;
+        let aj = (a[1] > b[1]) as i32;
+        Some(R2Edge::new(
+            &bound.get_vertex_ij(ai, aj),
+            &bound.get_vertex_ij(1 - ai, 1 - aj),
+        ))
+    }
+
+    // Return true if "p" lies within the bounding box of this edge. This is
+    // only meaningful when "p" is known to be collinear with the edge.
+    fn contains_collinear(&self, p: &R2Point) -> bool {
+        R2Rect::from_point_pair(&self.v0, &self.v1).contains(p)
+    }
+}
+
+/// Given a value x that is some linear combination of a and b, return the
+/// value x1 that is the same linear combination of a1 and b1. This function
+/// makes the following guarantees:
+///  - If x == a, then x1 = a1 (exactly).
+///  - If x == b, then x1 = b1 (exactly).
+///  - If a <= x <= b and a1 <= b1, then a1 <= x1 <= b1 (even if a1 == b1).
+///
+/// This requires that a != b, except that if a == b then a1 is returned.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::r2::interpolate_f64;
+///
+/// assert_eq!(interpolate_f64(0.25, 0.0, 1.0, 10.0, 20.0), 12.5);
+/// assert_eq!(interpolate_f64(1.0, 0.0, 1.0, 10.0, 20.0), 20.0);
+/// ```
+pub fn interpolate_f64(x: f64, a: f64, b: f64, a1: f64, b1: f64) -> f64 {
+    // To get results that are accurate near both A and B, we interpolate
+    // starting from the closer of the two points.
+    if a == b {
+        return a1;
+    }
+    if (a - x).abs() <= (b - x).abs() {
+        a1 + (b1 - a1) * (x - a) / (b - a)
+    } else {
+        b1 + (a1 - b1) * (x - b) / (a - b)
+    }
+}
+
+// Given an edge AB and a rectangle "clip", shrink "bound" (which must be a
+// bound of AB) so that it bounds the portion of AB that lies within "clip".
+// Returns false if AB does not intersect "clip".
+fn clip_edge_bound(a: &R2Point, b: &R2Point, clip: &R2Rect, bound: &mut R2Rect) -> bool {
+    // "diag" indicates which diagonal of the bounding box is spanned by AB: it
+    // is 0 if AB has positive slope, and 1 if AB has negative slope. This is
+    // used to determine which interval endpoints need to be updated each time
+    // the edge is clipped.
+    let diag = ((a[0] > b[0]) != (a[1] > b[1])) as usize;
+    let [mut x, mut y] = [bound[0], bound[1]];
+    let ok = clip_bound_axis(a[0], b[0], &mut x, a[1], b[1], &mut y, diag, &clip[0])
+        && clip_bound_axis(a[1], b[1], &mut y, a[0], b[0], &mut x, diag, &clip[1]);
+    *bound = R2Rect::new(x, y);
+    ok
+}
+
+// Clip the bounding interval "bound0" of one axis to "clip0", updating the
+// interval "bound1" of the other axis accordingly.
+#[allow(clippy::too_many_arguments)]
+fn clip_bound_axis(
+    a0: f64,
+    b0: f64,
+    bound0: &mut R1Interval,
+    a1: f64,
+    b1: f64,
+    bound1: &mut R1Interval,
+    diag: usize,
+    clip0: &R1Interval,
+) -> bool {
+    if bound0.lo() < clip0.lo() {
+        if bound0.hi() < clip0.lo() {
+            return false;
+        }
+        bound0[0] = clip0.lo();
+        if !update_endpoint(bound1, diag, interpolate_f64(clip0.lo(), a0, b0, a1, b1)) {
+            return false;
+        }
+    }
+    if bound0.hi() > clip0.hi() {
+        if bound0.lo() > clip0.hi() {
+            return false;
+        }
+        bound0[1] = clip0.hi();
+        if !update_endpoint(
+            bound1,
+            1 - diag,
+            interpolate_f64(clip0.hi(), a0, b0, a1, b1),
+        ) {
+            return false;
+        }
+    }
+    true
+}
+
+// Move the given endpoint ("end" = 0 for lo, 1 for hi) of "bound" inward to
+// "value". Returns false if the interval would become empty.
+fn update_endpoint(bound: &mut R1Interval, end: usize, value: f64) -> bool {
+    if end == 0 {
+        if bound.hi() < value {
+            return false;
+        }
+        if bound.lo() < value {
+            bound[0] = value;
+        }
+    } else {
+        if bound.lo() > value {
+            return false;
+        }
+        if bound.hi() > value {
+            bound[1] = value;
+        }
+    }
+    true
 }
 
 pub mod r2rect;
 
 pub use r2rect::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(x0: f64, y0: f64, x1: f64, y1: f64) -> R2Edge {
+        R2Edge::new(&R2Point::new(x0, y0), &R2Point::new(x1, y1))
+    }
+
+    fn unit_rect() -> R2Rect {
+        R2Rect::new(R1Interval::new(0.0, 1.0), R1Interval::new(0.0, 1.0))
+    }
+
+    #[test]
+    fn test_edge_intersection() {
+        // Proper crossing.
+        let a = edge(0.0, 0.0, 2.0, 2.0);
+        let b = edge(0.0, 2.0, 2.0, 0.0);
+        assert_eq!(a.intersection(&b), Some(R2Point::new(1.0, 1.0)));
+        assert!(a.intersects(&b) && b.intersects(&a));
+
+        // Touching at an endpoint.
+        let c = edge(2.0, 2.0, 3.0, 0.0);
+        assert_eq!(a.intersection(&c), Some(R2Point::new(2.0, 2.0)));
+
+        // Disjoint, including when the supporting lines cross.
+        let d = edge(3.0, 0.0, 4.0, -1.0);
+        assert!(!a.intersects(&d));
+        assert!(!a.intersects(&edge(1.0, 0.0, 3.0, 2.0)));
+
+        // Collinear overlapping and collinear disjoint.
+        let e = edge(1.0, 1.0, 3.0, 3.0);
+        assert_eq!(a.intersection(&e), Some(R2Point::new(1.0, 1.0)));
+        assert!(!a.intersects(&edge(3.0, 3.0, 4.0, 4.0)));
+
+        // Collinear with one edge containing the other.
+        let f = edge(-1.0, -1.0, 5.0, 5.0);
+        assert_eq!(a.intersection(&f), Some(R2Point::new(0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_interpolate_f64() {
+        // Endpoints are reproduced exactly.
+        assert_eq!(interpolate_f64(0.1, 0.1, 0.7, 3.0, -5.0), 3.0);
+        assert_eq!(interpolate_f64(0.7, 0.1, 0.7, 3.0, -5.0), -5.0);
+        // Degenerate input returns a1.
+        assert_eq!(interpolate_f64(0.3, 0.5, 0.5, 3.0, 4.0), 3.0);
+        // Extrapolation works in both directions.
+        assert_eq!(interpolate_f64(2.0, 0.0, 1.0, 0.0, 10.0), 20.0);
+        assert_eq!(interpolate_f64(-1.0, 0.0, 1.0, 0.0, 10.0), -10.0);
+        // Results stay within [a1, b1] even when a1 == b1.
+        for i in 0..=10 {
+            let x = i as f64 / 10.0;
+            assert_eq!(interpolate_f64(x, 0.0, 1.0, 0.3, 0.3), 0.3);
+        }
+    }
+
+    #[test]
+    fn test_clip_to_rect() {
+        let rect = unit_rect();
+
+        // Entirely inside: unchanged.
+        let inside = edge(0.25, 0.5, 0.75, 0.25);
+        assert_eq!(inside.clip_to_rect(&rect), Some(inside));
+        assert!(rect.intersects_edge(&inside));
+
+        // Entirely outside, both with a disjoint bound and with an overlapping
+        // bound that misses the corner.
+        let outside = edge(2.0, 0.0, 3.0, 1.0);
+        assert_eq!(outside.clip_to_rect(&rect), None);
+        assert!(!rect.intersects_edge(&outside));
+        let near_corner = edge(0.5, 1.75, 1.75, 0.5);
+        assert_eq!(near_corner.clip_to_rect(&rect), None);
+        assert!(!rect.intersects_edge(&near_corner));
+
+        // Crossing through the rectangle keeps the edge direction.
+        let crossing = edge(1.5, 0.25, -0.5, 0.75);
+        assert_eq!(
+            crossing.clip_to_rect(&rect),
+            Some(edge(1.0, 0.375, 0.0, 0.625))
+        );
+
+        // Passing exactly through a corner.
+        let corner = edge(0.5, 1.5, 1.5, 0.5);
+        assert_eq!(corner.clip_to_rect(&rect), Some(edge(1.0, 1.0, 1.0, 1.0)));
+        assert!(rect.intersects_edge(&corner));
+
+        // Collinear with a boundary edge.
+        let boundary = edge(-1.0, 0.0, 2.0, 0.0);
+        assert_eq!(boundary.clip_to_rect(&rect), Some(edge(0.0, 0.0, 1.0, 0.0)));
+        assert!(rect.intersects_edge(&boundary));
+        let beside = edge(-1.0, 1.5, 2.0, 1.5);
+        assert_eq!(beside.clip_to_rect(&rect), None);
+        assert!(!rect.intersects_edge(&beside));
+    }
+
+    #[test]
+    fn test_clip_to_rect_matches_intersects_edge() {
+        let rect = R2Rect::new(R1Interval::new(-0.25, 0.5), R1Interval::new(-0.125, 0.75));
+        // Use dyadic coordinates so that edges through the rectangle corners are
+        // computed exactly.
+        let coords = [-1.0, -0.25, -0.125, 0.0, 0.5, 0.75, 1.5];
+        for &x0 in &coords {
+            for &y0 in &coords {
+                for &x1 in &coords {
+                    for &y1 in &coords {
+                        let e = edge(x0, y0, x1, y1);
+                        let clipped = e.clip_to_rect(&rect);
+                        assert_eq!(clipped.is_some(), rect.intersects_edge(&e), "{e:?}");
+                        if let Some(c) = clipped {
+                            assert!(rect.contains(&c.v0()) && rect.contains(&c.v1()));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
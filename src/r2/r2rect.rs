@@ -1,6 +1,12 @@
-use std::ops::{Index, IndexMut};
+use core::{
+    fmt,
+    ops::{Index, IndexMut},
+};
 
-use crate::r1::R1Interval;
+use crate::{
+    r1::R1Interval,
+    r2::{R2Edge, R2Point},
+};
 
 /// An R2Rect represents a closed axis-aligned rectangle in the (x,y) plane.
 #[derive(Debug, Copy, Clone, Default)]
@@ -8,6 +14,159 @@ pub struct R2Rect {
     bounds: [R1Interval; 2],
 }
 
+impl R2Rect {
+    /// Construct a rectangle from the given intervals in x and y. The two
+    /// intervals must either be both empty or both non-empty.
+    pub fn new(x: R1Interval, y: R1Interval) -> R2Rect {
+        R2Rect { bounds: [x, y] }
+    }
+
+    /// Construct a rectangle from the given lower-left and upper-right points.
+    pub fn from_lo_hi(lo: &R2Point, hi: &R2Point) -> R2Rect {
+        R2Rect::new(
+            R1Interval::new(lo.x(), hi.x()),
+            R1Interval::new(lo.y(), hi.y()),
+        )
+    }
+
+    /// Construct the minimal bounding rectangle containing the two given
+    /// points. This is equivalent to starting with an empty rectangle and
+    /// calling add_point() twice.
+    pub fn from_point_pair(p1: &R2Point, p2: &R2Point) -> R2Rect {
+        R2Rect::new(
+            R1Interval::from_point_pair(p1.x(), p2.x()),
+            R1Interval::from_point_pair(p1.y(), p2.y()),
+        )
+    }
+
+    pub fn x(&self) -> &R1Interval {
+        &self.bounds[0]
+    }
+
+    pub fn y(&self) -> &R1Interval {
+        &self.bounds[1]
+    }
+
+    pub fn lo(&self) -> R2Point {
+        R2Point::new(self.x().lo(), self.y().lo())
+    }
+
+    pub fn hi(&self) -> R2Point {
+        R2Point::new(self.x().hi(), self.y().hi())
+    }
+
+    /// Return true if the rectangle is empty, i.e. it contains no points at all.
+    pub fn is_empty(&self) -> bool {
+        self.x().is_empty()
+    }
+
+    /// Return the k-th vertex of the rectangle (k = 0,1,2,3) in CCW order.
+    /// Vertex 0 is in the lower-left corner. For convenience, the argument is
+    /// reduced modulo 4 to the range [0..3].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::{
+    ///     r1::R1Interval,
+    ///     r2::{R2Point, R2Rect},
+    /// };
+    ///
+    /// let rect = R2Rect::new(R1Interval::new(0.0, 1.0), R1Interval::new(2.0, 3.0));
+    /// assert_eq!(rect.get_vertex(0), R2Point::new(0.0, 2.0));
+    /// assert_eq!(rect.get_vertex(1), R2Point::new(1.0, 2.0));
+    /// assert_eq!(rect.get_vertex(2), R2Point::new(1.0, 3.0));
+    /// assert_eq!(rect.get_vertex(3), R2Point::new(0.0, 3.0));
+    /// ```
+    pub fn get_vertex(&self, k: i32) -> R2Point {
+        // Twiddle bits to return the points in CCW order (lower left, lower right,
+        // upper right, upper left).
+        let j = (k >> 1) & 1;
+        self.get_vertex_ij(j ^ (k & 1), j)
+    }
+
+    /// Return the vertex in direction "i" along the x-axis (0=left, 1=right) and
+    /// direction "j" along the y-axis (0=down, 1=up).
+    pub fn get_vertex_ij(&self, i: i32, j: i32) -> R2Point {
+        R2Point::new(self.bounds[0][i as usize], self.bounds[1][j as usize])
+    }
+
+    /// Return the center of the rectangle in (x,y)-space.
+    pub fn get_center(&self) -> R2Point {
+        R2Point::new(self.x().get_center(), self.y().get_center())
+    }
+
+    /// Return true if the rectangle contains the given point. Note that
+    /// rectangles are closed regions, i.e. they contain their boundary.
+    pub fn contains(&self, p: &R2Point) -> bool {
+        self.x().contains(p.x()) && self.y().contains(p.y())
+    }
+
+    /// Return true if this rectangle and the given other rectangle have any
+    /// points in common.
+    pub fn intersects(&self, other: &R2Rect) -> bool {
+        self.x().intersects(other.x()) && self.y().intersects(other.y())
+    }
+
+    /// Return true if the given edge intersects this rectangle (including its
+    /// boundary).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::{
+    ///     r1::R1Interval,
+    ///     r2::{R2Edge, R2Point, R2Rect},
+    /// };
+    ///
+    /// let rect = R2Rect::new(R1Interval::new(0.0, 1.0), R1Interval::new(0.0, 1.0));
+    /// let edge = R2Edge::new(&R2Point::new(-0.5, 0.25), &R2Point::new(0.5, 1.25));
+    /// assert!(rect.intersects_edge(&edge));
+    ///
+    /// let edge = R2Edge::new(&R2Point::new(-1.0, 0.5), &R2Point::new(0.5, 2.0));
+    /// assert!(!rect.intersects_edge(&edge));
+    /// ```
+    pub fn intersects_edge(&self, edge: &R2Edge) -> bool {
+        let (a, b) = (edge.v0(), edge.v1());
+        // First check whether the bound of AB intersects this rectangle.
+        if !self.intersects(&R2Rect::from_point_pair(&a, &b)) {
+            return false;
+        }
+
+        // Otherwise AB intersects the rectangle if and only if all four
+        // vertices do not lie on the same side of the extended line AB. We test
+        // this by finding the two vertices with minimum and maximum projections
+        // onto the normal of AB, and computing their dot products with the
+        // edge normal.
+        let n = (b - a).ortho();
+        let i = (n[0] >= 0.0) as i32;
+        let j = (n[1] >= 0.0) as i32;
+        let max = n.dot_prod(&(self.get_vertex_ij(i, j) - a));
+        let min = n.dot_prod(&(self.get_vertex_ij(1 - i, 1 - j) - a));
+        max >= 0.0 && min <= 0.0
+    }
+
+    /// Return a rectangle that has been expanded on each side in the x-direction
+    /// by margin and on each side in the y-direction by margin. If the margin
+    /// is negative, then shrink the interval on the corresponding sides
+    /// instead. The resulting rectangle may be empty. Any expansion of an empty
+    /// rectangle remains empty.
+    pub fn expanded(&self, margin: f64) -> R2Rect {
+        let xx = self.x().expanded(margin);
+        let yy = self.y().expanded(margin);
+        if xx.is_empty() || yy.is_empty() {
+            return R2Rect::empty();
+        }
+        R2Rect::new(xx, yy)
+    }
+
+    /// The canonical empty rectangle. Use is_empty() to test for empty
+    /// rectangles, since they have more than one representation.
+    pub fn empty() -> R2Rect {
+        R2Rect::new(R1Interval::empty(), R1Interval::empty())
+    }
+}
+
 impl Index<usize> for R2Rect {
     type Output = R1Interval;
 
@@ -21,3 +180,27 @@ impl IndexMut<usize> for R2Rect {
         &mut self.bounds[index]
     }
 }
+
+/// Formats the rectangle as "[Lo[x, y], Hi[x, y]]".
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::{r1::R1Interval, r2::R2Rect};
+///
+/// let rect = R2Rect::new(R1Interval::new(0.0, 0.5), R1Interval::new(-1.0, 1.0));
+/// assert_eq!(rect.to_string(), "[Lo[0, -1], Hi[0.5, 1]]");
+/// ```
+impl fmt::Display for R2Rect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (lo, hi) = (self.lo(), self.hi());
+        write!(
+            f,
+            "[Lo[{}, {}], Hi[{}, {}]]",
+            lo.x(),
+            lo.y(),
+            hi.x(),
+            hi.y()
+        )
+    }
+}
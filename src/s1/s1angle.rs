@@ -15,7 +15,10 @@
 
 // Original Author: ericv@google.com (Eric Veach)
 
-use std::f64::consts::PI;
+use core::f64::consts::PI;
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
 
 use crate::{s1::S1ChordAngle, s2::s2point::S2Point};
 
@@ -61,6 +64,11 @@ impl S1Angle {
 
     /// Creates an S1Angle from two S2Point's.
     ///
+    /// This uses Vector3::angle(), which does not require the points to be
+    /// unit length and is accurate for all angles, including nearly antipodal
+    /// points (which s2::distance relies on). If both points are known to be
+    /// unit length and not nearly antipodal, Vector3::angle_unit() is cheaper.
+    ///
     /// # Examples
     ///
     /// ```
@@ -92,6 +100,62 @@ impl S1Angle {
         (180.0 / PI) * self.radians
     }
 
+    /// Returns the sine of the angle.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s1::S1Angle;
+    ///
+    /// assert!((S1Angle::from_degrees(90.0).sin() - 1.0).abs() < 1e-15);
+    /// assert!((S1Angle::from_degrees(30.0).sin() - 0.5).abs() < 1e-15);
+    /// ```
+    pub fn sin(&self) -> f64 {
+        self.radians.sin()
+    }
+
+    /// Returns the cosine of the angle.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s1::S1Angle;
+    ///
+    /// assert!(S1Angle::from_degrees(90.0).cos().abs() < 1e-15);
+    /// assert_eq!(S1Angle::zero().cos(), 1.0);
+    /// ```
+    pub fn cos(&self) -> f64 {
+        self.radians.cos()
+    }
+
+    /// Returns the tangent of the angle.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s1::S1Angle;
+    ///
+    /// assert!((S1Angle::from_degrees(45.0).tan() - 1.0).abs() < 1e-15);
+    /// ```
+    pub fn tan(&self) -> f64 {
+        self.radians.tan()
+    }
+
+    /// Returns the sine and cosine of the angle. This is the same as calling
+    /// sin() and cos() separately, but may be faster on some platforms.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s1::S1Angle;
+    ///
+    /// let angle = S1Angle::from_degrees(30.0);
+    /// assert_eq!(angle.sin_cos(), (angle.sin(), angle.cos()));
+    /// ```
+    pub fn sin_cos(&self) -> (f64, f64) {
+        self.radians.sin_cos()
+    }
+
     pub fn abs(self) -> S1Angle {
         S1Angle::from_radians(self.radians.abs())
     }
@@ -113,7 +177,11 @@ impl S1Angle {
     /// assert_eq!(angle.normalize().degrees(), 180.0);
     /// ```
     pub fn normalize(self) -> S1Angle {
-        let mut radians = self.radians.rem_euclid(2.0 * PI);
+        // Equivalent to rem_euclid(), which is not available without std.
+        let mut radians = self.radians % (2.0 * PI);
+        if radians < 0.0 {
+            radians += 2.0 * PI;
+        }
         if radians > PI {
             radians -= 2.0 * PI;
         }
@@ -121,6 +189,60 @@ impl S1Angle {
     }
 }
 
+/// The sine and cosine of an angle, computed once so that they can be cached
+/// by code that needs both (e.g. in inner loops) and combined to obtain the
+/// sine and cosine of sums and differences of angles without calling any
+/// further trigonometric functions.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s1::{S1Angle, TrigPair};
+///
+/// let a = TrigPair::from(S1Angle::from_degrees(50.0));
+/// let b = TrigPair::from(S1Angle::from_degrees(40.0));
+/// let sum = a.sum(&b);
+/// assert!((sum.sin - 1.0).abs() <= 1e-15);
+/// assert!(sum.cos.abs() <= 1e-15);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TrigPair {
+    pub sin: f64,
+    pub cos: f64,
+}
+
+impl TrigPair {
+    /// Returns the sine and cosine of (a + b), where "self" and "other" hold
+    /// the sine and cosine of "a" and "b" respectively.
+    pub fn sum(&self, other: &TrigPair) -> TrigPair {
+        TrigPair {
+            sin: self.sin * other.cos + self.cos * other.sin,
+            cos: self.cos * other.cos - self.sin * other.sin,
+        }
+    }
+
+    /// Returns the sine and cosine of (a - b), where "self" and "other" hold
+    /// the sine and cosine of "a" and "b" respectively.
+    pub fn difference(&self, other: &TrigPair) -> TrigPair {
+        TrigPair {
+            sin: self.sin * other.cos - self.cos * other.sin,
+            cos: self.cos * other.cos + self.sin * other.sin,
+        }
+    }
+
+    /// Returns the tangent of the angle.
+    pub fn tan(&self) -> f64 {
+        self.sin / self.cos
+    }
+}
+
+impl From<S1Angle> for TrigPair {
+    fn from(angle: S1Angle) -> TrigPair {
+        let (sin, cos) = angle.sin_cos();
+        TrigPair { sin, cos }
+    }
+}
+
 impl From<S1ChordAngle> for S1Angle {
     fn from(value: S1ChordAngle) -> S1Angle {
         if value.is_negative() {
@@ -132,3 +254,64 @@ impl From<S1ChordAngle> for S1Angle {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_definition() {
+        // S1Angle has a single definition in s1::s1angle; the s1 re-export and
+        // the type used by S1ChordAngle are the same type.
+        fn same(a: crate::s1::s1angle::S1Angle) -> crate::s1::S1Angle {
+            a
+        }
+        let angle = same(S1Angle::from_degrees(60.0));
+        let chord = S1ChordAngle::from(angle);
+        assert!((S1Angle::from(chord).degrees() - 60.0).abs() < 1e-13);
+
+        let a = S2Point::new(1.0, 0.0, 0.0);
+        let b = S2Point::new(0.0, 1.0, 0.0);
+        let angle: crate::s1::S1Angle = S1Angle::from_points(&a, &b);
+        assert_eq!(angle.degrees(), 90.0);
+    }
+
+    #[test]
+    fn test_trigonometry() {
+        let right = S1Angle::from_degrees(90.0);
+        assert!((right.sin() - 1.0).abs() < 1e-15);
+        assert!(right.cos().abs() < 1e-15);
+        for degrees in [-135.0, -30.0, 0.0, 12.5, 60.0, 179.0] {
+            let angle = S1Angle::from_degrees(degrees);
+            assert_eq!(angle.sin(), angle.radians().sin());
+            assert_eq!(angle.cos(), angle.radians().cos());
+            assert_eq!(angle.tan(), angle.radians().tan());
+        }
+    }
+
+    #[test]
+    fn test_sin_cos() {
+        // sin_cos() agrees exactly with separate calls, and sums and
+        // differences of cached pairs agree with the direct computation up
+        // to rounding (which includes the error in adding the radians).
+        const MAX_ERROR: f64 = 1e-14;
+        for i in -36..=36 {
+            let a = S1Angle::from_degrees(10.0 * i as f64 + 0.3);
+            assert_eq!(a.sin_cos(), (a.sin(), a.cos()));
+            let pa = TrigPair::from(a);
+            for j in -36..=36 {
+                let b = S1Angle::from_degrees(7.0 * j as f64 - 0.1);
+                let pb = TrigPair::from(b);
+                let sum = S1Angle::from_radians(a.radians() + b.radians());
+                let diff = S1Angle::from_radians(a.radians() - b.radians());
+                assert!((pa.sum(&pb).sin - sum.sin()).abs() <= MAX_ERROR);
+                assert!((pa.sum(&pb).cos - sum.cos()).abs() <= MAX_ERROR);
+                assert!((pa.difference(&pb).sin - diff.sin()).abs() <= MAX_ERROR);
+                assert!((pa.difference(&pb).cos - diff.cos()).abs() <= MAX_ERROR);
+            }
+            if a.cos().abs() > 1e-3 {
+                assert!((pa.tan() - a.tan()).abs() <= MAX_ERROR * a.tan().abs().max(1.0));
+            }
+        }
+    }
+}
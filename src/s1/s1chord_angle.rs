@@ -15,10 +15,12 @@
 
 // Original Author: ericv@google.com (Eric Veach)
 
-use crate::{
-    s1::S1Angle,
-    s2::{is_unit_length, S2Point},
-};
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+use core::ops::{Add, Sub};
+
+use crate::{s1::S1Angle, s2::S2Point};
 
 /// S1ChordAngle represents the angle subtended by a chord (i.e., the straight
 /// line segment connecting two points on the sphere). Its representation
@@ -106,7 +108,7 @@ use crate::{
 ///      ~= 1.5e-8
 ///
 /// which is 9.5 cm on the Earth's surface.
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
 pub struct S1ChordAngle {
     length2: f64,
 }
@@ -117,8 +119,8 @@ impl S1ChordAngle {
     }
 
     pub fn from_points(x: &S2Point, y: &S2Point) -> S1ChordAngle {
-        debug_assert!(is_unit_length(x));
-        debug_assert!(is_unit_length(y));
+        x.assert_unit_length();
+        y.assert_unit_length();
         let length2 = (x - y).norm2().min(4.0);
         let angle = S1ChordAngle::new(length2);
         debug_assert!(angle.is_valid());
@@ -189,7 +191,12 @@ impl S1ChordAngle {
     /// because of the S1Angle conversion these methods are relatively expensive,
     /// so the results should be cached if they are needed inside loops.
     pub fn degrees(&self) -> f64 {
-        S1Angle::from(*self).radians()
+        S1Angle::from(*self).degrees()
+    }
+
+    /// Return true if the angle is zero.
+    pub fn is_zero(&self) -> bool {
+        self.length2() == 0.0
     }
 
     pub fn is_negative(&self) -> bool {
@@ -204,10 +211,180 @@ impl S1ChordAngle {
         self.length2
     }
 
+    /// Return true if the angle is negative or infinity.
+    pub fn is_special(&self) -> bool {
+        self.is_negative() || self.is_infinity()
+    }
+
     /// Return true if the internal representation is valid.  Negative() and
     /// Infinity() are both considered valid.
     pub fn is_valid(&self) -> bool {
-        todo!()
+        (self.length2() >= 0.0 && self.length2() <= 4.0) || self.is_special()
+    }
+
+    /// Return the square of the sine of the angle. This is cheaper than
+    /// computing the sine and squaring it since the chord angle
+    /// representation does not require a trigonometric function call.
+    pub fn sin2(&self) -> f64 {
+        debug_assert!(!self.is_special());
+        // Let "a" be the (non-squared) chord length, and let A be the corresponding
+        // half-angle (a = 2*sin(A)).  The formula below can be derived from:
+        //   sin(2*A) = 2 * sin(A) * cos(A)
+        //   cos^2(A) = 1 - sin^2(A)
+        // This is much faster than converting to an angle and computing its sine.
+        self.length2() * (1.0 - 0.25 * self.length2())
+    }
+
+    /// Returns the sine of the angle. Like sin2(), this is computed directly
+    /// from the squared chord length rather than by converting to an S1Angle.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s1::S1ChordAngle;
+    ///
+    /// assert_eq!(S1ChordAngle::right().sin(), 1.0);
+    /// assert!((S1ChordAngle::from_degrees(30.0).sin() - 0.5).abs() <= 1e-15);
+    /// ```
+    pub fn sin(&self) -> f64 {
+        self.sin2().sqrt()
+    }
+
+    /// Returns the cosine of the angle, computed directly from the squared
+    /// chord length using the law of cosines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s1::S1ChordAngle;
+    ///
+    /// assert_eq!(S1ChordAngle::straight().cos(), -1.0);
+    /// assert!((S1ChordAngle::from_degrees(60.0).cos() - 0.5).abs() <= 1e-15);
+    /// ```
+    pub fn cos(&self) -> f64 {
+        // cos(2*A) = cos^2(A) - sin^2(A) = 1 - 2*sin^2(A)
+        debug_assert!(!self.is_special());
+        1.0 - 0.5 * self.length2()
+    }
+
+    /// Returns the tangent of the angle.
+    pub fn tan(&self) -> f64 {
+        self.sin() / self.cos()
+    }
+
+    /// Return the maximum error in length2() for the from_points()
+    /// constructor, assuming that the points are normalized to within the
+    /// bounds guaranteed by S2Point::normalize(). (The error is defined with
+    /// respect to the true distance after the points are projected to lie
+    /// exactly on the sphere.)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s1::S1ChordAngle;
+    ///
+    /// assert_eq!(
+    ///     S1ChordAngle::right().get_s2point_constructor_max_error(),
+    ///     9.0 * f64::EPSILON + 16.0 * f64::EPSILON * f64::EPSILON
+    /// );
+    /// ```
+    pub fn get_s2point_constructor_max_error(&self) -> f64 {
+        // There is a relative error of 2.5 * DBL_EPSILON when computing the
+        // squared distance, plus a relative error of 2 * DBL_EPSILON and an
+        // absolute error of (16 * DBL_EPSILON**2) because the lengths of the
+        // input points may differ from 1 by up to (2 * DBL_EPSILON) each.
+        // (This is the maximum error in normalize().)
+        4.5 * f64::EPSILON * self.length2 + 16.0 * f64::EPSILON * f64::EPSILON
+    }
+
+    /// Modify the length of the angle by the given "error" in length2(), as
+    /// returned by the various error bound methods. The result is clamped to
+    /// the range [0, 4], and special values are returned unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s1::S1ChordAngle;
+    ///
+    /// let angle = S1ChordAngle::right();
+    /// assert_eq!(angle.plus_error(0.5).length2(), 2.5);
+    /// assert_eq!(angle.plus_error(-3.0), S1ChordAngle::zero());
+    /// assert!(S1ChordAngle::infinity().plus_error(1.0).is_infinity());
+    /// ```
+    pub fn plus_error(&self, error: f64) -> S1ChordAngle {
+        // If angle is special, don't change it. Otherwise clamp it to the
+        // valid range.
+        if self.is_special() {
+            return *self;
+        }
+        S1ChordAngle::new((self.length2 + error).clamp(0.0, 4.0))
+    }
+}
+
+impl Add for S1ChordAngle {
+    type Output = S1ChordAngle;
+
+    /// Adds two chord angles, clamping the result to at most 180 degrees.
+    /// This is much more efficient than converting the chord angles to
+    /// S1Angles and adding those, since it requires only one square root plus
+    /// a few additions and multiplications.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s1::S1ChordAngle;
+    ///
+    /// let sum = S1ChordAngle::right() + S1ChordAngle::right();
+    /// assert_eq!(sum, S1ChordAngle::straight());
+    /// let sum = S1ChordAngle::from_degrees(30.0) + S1ChordAngle::from_degrees(60.0);
+    /// assert!((sum.length2() - S1ChordAngle::right().length2()).abs() < 1e-15);
+    /// ```
+    fn add(self, other: S1ChordAngle) -> S1ChordAngle {
+        debug_assert!(!self.is_special());
+        debug_assert!(!other.is_special());
+
+        // Optimization for the common case where "other" is an error
+        // tolerance parameter that happens to be set to zero.
+        let (a2, b2) = (self.length2(), other.length2());
+        if b2 == 0.0 {
+            return self;
+        }
+
+        // Clamp the angle sum to at most 180 degrees.
+        if a2 + b2 >= 4.0 {
+            return S1ChordAngle::straight();
+        }
+
+        // Let "a" and "b" be the (non-squared) chord lengths, and let c = a+b.
+        // Let A, B, and C be the corresponding half-angles (a = 2*sin(A), etc).
+        // Then the formula below can be derived from c = 2 * sin(A+B) and the
+        // relationships   sin(A+B) = sin(A)*cos(B) + sin(B)*cos(A)
+        //                 cos(X) = sqrt(1 - sin^2(X)) .
+        let x = a2 * (1.0 - 0.25 * b2); // is_valid() => non-negative
+        let y = b2 * (1.0 - 0.25 * a2); // is_valid() => non-negative
+        S1ChordAngle::new((x + y + 2.0 * (x * y).sqrt()).min(4.0))
+    }
+}
+
+impl Sub for S1ChordAngle {
+    type Output = S1ChordAngle;
+
+    /// Subtracts one chord angle from another, clamping the result to be
+    /// non-negative.
+    fn sub(self, other: S1ChordAngle) -> S1ChordAngle {
+        // See comments in add().
+        debug_assert!(!self.is_special());
+        debug_assert!(!other.is_special());
+        let (a2, b2) = (self.length2(), other.length2());
+        if b2 == 0.0 {
+            return self;
+        }
+        if a2 <= b2 {
+            return S1ChordAngle::zero();
+        }
+        let x = a2 * (1.0 - 0.25 * b2);
+        let y = b2 * (1.0 - 0.25 * a2);
+        S1ChordAngle::new((x + y - 2.0 * (x * y).sqrt()).max(0.0))
     }
 }
 
@@ -216,12 +393,82 @@ impl From<S1Angle> for S1ChordAngle {
     /// as follows: Infinity() is mapped to Infinity(), negative angles are
     /// mapped to Negative(), and finite angles larger than Pi are mapped to
     /// Straight().
-
+    ///
     /// Note that this operation is relatively expensive and should be avoided.
     /// To use S1ChordAngle effectively, you should structure your code so that
     /// input arguments are converted to S1ChordAngles at the beginning of your
     /// algorithm, and results are converted back to S1Angles only at the end.
     fn from(value: S1Angle) -> S1ChordAngle {
-        todo!()
+        if value.radians() < 0.0 {
+            S1ChordAngle::negative()
+        } else if value == S1Angle::infinity() {
+            S1ChordAngle::infinity()
+        } else {
+            // The chord length is 2 * sin(angle / 2).
+            let length = 2.0 * (0.5 * value.radians().min(core::f64::consts::PI)).sin();
+            S1ChordAngle::new(length * length)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_radians_and_degrees() {
+        assert_eq!(S1ChordAngle::zero().degrees(), 0.0);
+        assert!((S1ChordAngle::right().degrees() - 90.0).abs() <= 1e-13);
+        assert!((S1ChordAngle::straight().degrees() - 180.0).abs() <= 1e-13);
+        assert!((S1ChordAngle::from_degrees(45.0).degrees() - 45.0).abs() <= 1e-13);
+        let angle = S1ChordAngle::from_degrees(60.0);
+        assert!((angle.degrees() - angle.radians().to_degrees()).abs() <= 1e-13);
+    }
+
+    #[test]
+    fn test_trigonometry() {
+        // The direct formulas agree with converting to an S1Angle first. The
+        // sine loses accuracy near 180 degrees, where S1ChordAngle itself
+        // cannot represent angles precisely.
+        for i in 0..=180 {
+            let angle = S1Angle::from_degrees(i as f64);
+            let chord = S1ChordAngle::from(angle);
+            assert!((chord.sin() - angle.sin()).abs() <= 1e-13);
+            assert!((chord.cos() - angle.cos()).abs() <= 4.0 * f64::EPSILON);
+            assert!((chord.sin2() - angle.sin().powi(2)).abs() <= 4.0 * f64::EPSILON);
+            if i != 90 {
+                let tan = angle.tan();
+                assert!((chord.tan() - tan).abs() <= 1e-13 * tan.abs().max(1.0));
+            }
+        }
+        assert_eq!(S1ChordAngle::zero().sin(), 0.0);
+        assert_eq!(S1ChordAngle::zero().cos(), 1.0);
+        assert_eq!(S1ChordAngle::right().cos(), 0.0);
+        assert_eq!(S1ChordAngle::straight().sin(), 0.0);
+    }
+
+    #[test]
+    fn test_plus_error() {
+        assert_eq!(
+            S1ChordAngle::negative().plus_error(5.0),
+            S1ChordAngle::negative()
+        );
+        assert_eq!(
+            S1ChordAngle::infinity().plus_error(-5.0),
+            S1ChordAngle::infinity()
+        );
+        assert_eq!(
+            S1ChordAngle::straight().plus_error(5.0),
+            S1ChordAngle::straight()
+        );
+        assert_eq!(S1ChordAngle::zero().plus_error(-5.0), S1ChordAngle::zero());
+        assert_eq!(
+            S1ChordAngle::from_length2(1.0).plus_error(0.25),
+            S1ChordAngle::from_length2(1.25)
+        );
+        assert_eq!(
+            S1ChordAngle::from_length2(1.0).plus_error(-0.25),
+            S1ChordAngle::from_length2(0.75)
+        );
     }
 }
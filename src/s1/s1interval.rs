@@ -0,0 +1,563 @@
+// Copyright 2005 Google Inc. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS-IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+// Original Author: ericv@google.com (Eric Veach)
+
+use core::{f64::consts::PI, ops::Index};
+
+use crate::util::math::{remainder, Vector2};
+
+/// An S1Interval represents a closed interval on a unit circle (also known
+/// as a 1-dimensional sphere). It is capable of representing the empty
+/// interval (containing no points), the full interval (containing all
+/// points), and zero-length intervals (containing a single point).
+///
+/// Points are represented by the angle they make with the positive x-axis in
+/// the range [-Pi, Pi]. An interval is represented by its lower and upper
+/// bounds (both inclusive, since the interval is closed). The lower bound may
+/// be greater than the upper bound, in which case the interval is "inverted"
+/// (i.e. it passes through the point (-1, 0)).
+///
+/// Note that the point (-1, 0) has two valid representations, Pi and -Pi.
+/// The normalized representation of this point internally is Pi, so that
+/// endpoints of normal intervals are in the range (-Pi, Pi]. However, we take
+/// advantage of the point -Pi to construct two special intervals: the Full()
+/// interval is [-Pi, Pi], and the Empty() interval is [Pi, -Pi].
+///
+/// This class is intended to be copied by value as desired.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct S1Interval {
+    bounds: Vector2<f64>,
+}
+
+impl S1Interval {
+    /// Constructs an interval with the given endpoints. Both endpoints must be
+    /// in the range -Pi to Pi inclusive. The value -Pi is converted internally
+    /// to Pi except for the full() and empty() intervals.
+    pub fn new(lo: f64, hi: f64) -> S1Interval {
+        let mut result = S1Interval::new_unchecked(lo, hi);
+        if lo == -PI && hi != PI {
+            result.bounds[0] = PI;
+        }
+        if hi == -PI && lo != PI {
+            result.bounds[1] = PI;
+        }
+        debug_assert!(result.is_valid());
+        result
+    }
+
+    /// Internal constructor that assumes that both arguments are in the
+    /// correct range, i.e. normalization from -Pi to Pi is already done.
+    fn new_unchecked(lo: f64, hi: f64) -> S1Interval {
+        S1Interval {
+            bounds: Vector2::new(lo, hi),
+        }
+    }
+
+    /// Returns the empty interval.
+    pub fn empty() -> S1Interval {
+        S1Interval::new_unchecked(PI, -PI)
+    }
+
+    /// Returns the full interval.
+    pub fn full() -> S1Interval {
+        S1Interval::new_unchecked(-PI, PI)
+    }
+
+    /// Convenience method to construct an interval containing a single point.
+    pub fn from_point(p: f64) -> S1Interval {
+        let p = if p == -PI { PI } else { p };
+        S1Interval::new_unchecked(p, p)
+    }
+
+    /// Convenience method to construct the minimal interval containing the
+    /// two given points. This is equivalent to starting with an empty
+    /// interval and calling add_point() twice, but it is more efficient.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s1::S1Interval;
+    ///
+    /// // The shorter way around the circle is chosen.
+    /// let interval = S1Interval::from_point_pair(3.0, -3.0);
+    /// assert!(interval.is_inverted());
+    /// assert_eq!((interval.lo(), interval.hi()), (3.0, -3.0));
+    /// ```
+    pub fn from_point_pair(p1: f64, p2: f64) -> S1Interval {
+        debug_assert!(p1.abs() <= PI);
+        debug_assert!(p2.abs() <= PI);
+        let p1 = if p1 == -PI { PI } else { p1 };
+        let p2 = if p2 == -PI { PI } else { p2 };
+        if positive_distance(p1, p2) <= PI {
+            S1Interval::new_unchecked(p1, p2)
+        } else {
+            S1Interval::new_unchecked(p2, p1)
+        }
+    }
+
+    /// The low bound of the interval.
+    pub fn lo(&self) -> f64 {
+        self.bounds[0]
+    }
+
+    /// The high bound of the interval.
+    pub fn hi(&self) -> f64 {
+        self.bounds[1]
+    }
+
+    pub fn bounds(&self) -> &Vector2<f64> {
+        &self.bounds
+    }
+
+    /// An interval is valid if neither bound exceeds Pi in absolute value,
+    /// and the value -Pi appears only in the empty() and full() intervals.
+    pub fn is_valid(&self) -> bool {
+        self.lo().abs() <= PI
+            && self.hi().abs() <= PI
+            && !(self.lo() == -PI && self.hi() != PI)
+            && !(self.hi() == -PI && self.lo() != PI)
+    }
+
+    /// Return true if the interval contains all points on the unit circle.
+    pub fn is_full(&self) -> bool {
+        self.lo() == -PI && self.hi() == PI
+    }
+
+    /// Return true if the interval is empty, i.e. it contains no points.
+    pub fn is_empty(&self) -> bool {
+        self.lo() == PI && self.hi() == -PI
+    }
+
+    /// Return true if lo() > hi(). (This is true for empty intervals.)
+    pub fn is_inverted(&self) -> bool {
+        self.lo() > self.hi()
+    }
+
+    /// Return the midpoint of the interval. For full and empty intervals,
+    /// the result is arbitrary.
+    pub fn get_center(&self) -> f64 {
+        let center = 0.5 * (self.lo() + self.hi());
+        if !self.is_inverted() {
+            return center;
+        }
+        // Return the center in the range (-Pi, Pi].
+        if center <= 0.0 {
+            center + PI
+        } else {
+            center - PI
+        }
+    }
+
+    /// Return the midpoint of the complement of the interval. For full and
+    /// empty intervals, the result is arbitrary. For a singleton interval
+    /// (containing a single point), the result is its antipodal point on S1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s1::S1Interval;
+    /// use std::f64::consts::{FRAC_PI_2, PI};
+    ///
+    /// assert_eq!(
+    ///     S1Interval::new(0.0, FRAC_PI_2).get_complement_center(),
+    ///     -3.0 * PI / 4.0
+    /// );
+    /// assert_eq!(
+    ///     S1Interval::from_point(FRAC_PI_2).get_complement_center(),
+    ///     -FRAC_PI_2
+    /// );
+    /// ```
+    pub fn get_complement_center(&self) -> f64 {
+        if self.lo() != self.hi() {
+            return S1Interval::new_unchecked(self.hi(), self.lo()).get_center();
+        }
+        // Singleton.
+        if self.hi() <= 0.0 {
+            self.hi() + PI
+        } else {
+            self.hi() - PI
+        }
+    }
+
+    /// Return the length of the interval. The length of an empty interval
+    /// is negative.
+    pub fn get_length(&self) -> f64 {
+        let length = self.hi() - self.lo();
+        if length >= 0.0 {
+            return length;
+        }
+        let length = length + 2.0 * PI;
+        // Empty intervals have a negative length.
+        if length > 0.0 {
+            length
+        } else {
+            -1.0
+        }
+    }
+
+    /// Return true if the interval (which is closed) contains the point "p".
+    /// Requires: |p| <= Pi.
+    pub fn contains(&self, p: f64) -> bool {
+        debug_assert!(p.abs() <= PI);
+        let p = if p == -PI { PI } else { p };
+        self.fast_contains(p)
+    }
+
+    /// Return true if the interior of the interval contains the point "p".
+    /// Requires: |p| <= Pi.
+    pub fn interior_contains(&self, p: f64) -> bool {
+        debug_assert!(p.abs() <= PI);
+        let p = if p == -PI { PI } else { p };
+        if self.is_inverted() {
+            p > self.lo() || p < self.hi()
+        } else {
+            (p > self.lo() && p < self.hi()) || self.is_full()
+        }
+    }
+
+    /// Return the closest point in the interval to the given point "p". The
+    /// interval must be non-empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s1::S1Interval;
+    /// use std::f64::consts::PI;
+    ///
+    /// // An interval that wraps around from 3*Pi/4 to -3*Pi/4.
+    /// let x = S1Interval::new(0.75 * PI, -0.75 * PI);
+    /// assert_eq!(x.project(-PI), PI);
+    /// assert_eq!(x.project(0.5 * PI), 0.75 * PI);
+    /// assert_eq!(x.project(-0.5 * PI), -0.75 * PI);
+    /// ```
+    pub fn project(&self, p: f64) -> f64 {
+        debug_assert!(!self.is_empty());
+        debug_assert!(p.abs() <= PI);
+        let p = if p == -PI { PI } else { p };
+        if self.fast_contains(p) {
+            return p;
+        }
+        // Compute distance from p to each endpoint.
+        let dlo = positive_distance(p, self.lo());
+        let dhi = positive_distance(self.hi(), p);
+        if dlo < dhi {
+            self.lo()
+        } else {
+            self.hi()
+        }
+    }
+
+    /// Return true if the interval contains the given interval "y". Works
+    /// for empty, full, and singleton intervals.
+    pub fn contains_interval(&self, y: &S1Interval) -> bool {
+        // It might be helpful to compare the structure of these tests to
+        // the simpler contains(f64) method above.
+        if self.is_inverted() {
+            if y.is_inverted() {
+                return y.lo() >= self.lo() && y.hi() <= self.hi();
+            }
+            (y.lo() >= self.lo() || y.hi() <= self.hi()) && !self.is_empty()
+        } else {
+            if y.is_inverted() {
+                return self.is_full() || y.is_empty();
+            }
+            y.lo() >= self.lo() && y.hi() <= self.hi()
+        }
+    }
+
+    /// Return true if the two intervals contain any points in common. Note
+    /// that the point +/-Pi has two representations, so the intervals [-Pi,-3]
+    /// and [2,Pi] intersect, for example.
+    pub fn intersects(&self, y: &S1Interval) -> bool {
+        if self.is_empty() || y.is_empty() {
+            return false;
+        }
+        if self.is_inverted() {
+            // Every non-empty inverted interval contains Pi.
+            return y.is_inverted() || y.lo() <= self.hi() || y.hi() >= self.lo();
+        }
+        if y.is_inverted() {
+            return y.lo() <= self.hi() || y.hi() >= self.lo();
+        }
+        y.lo() <= self.hi() && y.hi() >= self.lo()
+    }
+
+    /// Return an interval that has been expanded on each side by the given
+    /// distance "margin". If "margin" is negative, then shrink the interval on
+    /// each side by "margin" instead. The resulting interval may be empty or
+    /// full. Any expansion (positive or negative) of a full interval remains
+    /// full, and any expansion of an empty interval remains empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s1::S1Interval;
+    ///
+    /// // Expanding across the antimeridian produces an inverted interval.
+    /// let interval = S1Interval::new(2.0, 3.0).expanded(0.5);
+    /// assert!(interval.is_inverted());
+    /// assert!(interval.contains(-3.0));
+    /// ```
+    pub fn expanded(&self, margin: f64) -> S1Interval {
+        if margin >= 0.0 {
+            if self.is_empty() {
+                return *self;
+            }
+            // Check whether this interval will be full after expansion,
+            // allowing for a 1-bit rounding error when computing each endpoint.
+            if self.get_length() + 2.0 * margin + 2.0 * f64::EPSILON >= 2.0 * PI {
+                return S1Interval::full();
+            }
+        } else {
+            if self.is_full() {
+                return *self;
+            }
+            // Check whether this interval will be empty after expansion,
+            // allowing for a 1-bit rounding error when computing each endpoint.
+            if self.get_length() + 2.0 * margin - 2.0 * f64::EPSILON <= 0.0 {
+                return S1Interval::empty();
+            }
+        }
+        let mut result = S1Interval::new_unchecked(
+            remainder(self.lo() - margin, 2.0 * PI),
+            remainder(self.hi() + margin, 2.0 * PI),
+        );
+        if result.lo() <= -PI {
+            result.bounds[0] = PI;
+        }
+        result
+    }
+
+    /// Return the smallest interval that contains this interval and the
+    /// given interval "y".
+    pub fn union(&self, y: &S1Interval) -> S1Interval {
+        // The y.is_full() case is handled correctly in all cases by the code
+        // below, but can follow three separate code paths depending on whether
+        // this interval is inverted, is non-inverted but contains Pi, or neither.
+        if y.is_empty() {
+            return *self;
+        }
+        if self.fast_contains(y.lo()) {
+            if self.fast_contains(y.hi()) {
+                // Either this interval contains y, or the union of the two
+                // intervals is the Full() interval.
+                if self.contains_interval(y) {
+                    return *self; // is_full() code path
+                }
+                return S1Interval::full();
+            }
+            return S1Interval::new_unchecked(self.lo(), y.hi());
+        }
+        if self.fast_contains(y.hi()) {
+            return S1Interval::new_unchecked(y.lo(), self.hi());
+        }
+
+        // This interval contains neither endpoint of y. This means that either
+        // y contains all of this interval, or the two intervals are disjoint.
+        if self.is_empty() || y.fast_contains(self.lo()) {
+            return *y;
+        }
+
+        // Check which pair of endpoints are closer together.
+        let dlo = positive_distance(y.hi(), self.lo());
+        let dhi = positive_distance(self.hi(), y.lo());
+        if dlo < dhi {
+            S1Interval::new_unchecked(y.lo(), self.hi())
+        } else {
+            S1Interval::new_unchecked(self.lo(), y.hi())
+        }
+    }
+
+    /// Return true if the interval (which is closed) contains the point "p".
+    /// Skips the normalization of "p" from -Pi to Pi.
+    fn fast_contains(&self, p: f64) -> bool {
+        if self.is_inverted() {
+            (p >= self.lo() || p <= self.hi()) && !self.is_empty()
+        } else {
+            p >= self.lo() && p <= self.hi()
+        }
+    }
+}
+
+impl Default for S1Interval {
+    /// The default constructor creates an empty interval.
+    fn default() -> S1Interval {
+        S1Interval::empty()
+    }
+}
+
+impl Index<usize> for S1Interval {
+    type Output = f64;
+
+    /// The recommended style is to use `lo()` and `hi()` whenever possible,
+    /// but these methods are useful when the endpoint to be selected is not constant.
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.bounds[index]
+    }
+}
+
+/// Compute the distance from "a" to "b" in the range [0, 2*Pi). This is
+/// equivalent to (remainder(b - a - Pi, 2 * Pi) + Pi), except that it is
+/// more numerically stable (it does not lose precision for very small
+/// positive distances).
+fn positive_distance(a: f64, b: f64) -> f64 {
+    let d = b - a;
+    if d >= 0.0 {
+        return d;
+    }
+    // We want to ensure that if b == Pi and a == (-Pi + eps), the return
+    // result is approximately 2*Pi and not zero.
+    (b + PI) - (a - PI)
+}
+
+#[cfg(test)]
+mod tests {
+    use core::f64::consts::FRAC_PI_2;
+
+    use super::*;
+
+    #[test]
+    fn test_constructors_and_accessors() {
+        let empty = S1Interval::empty();
+        let full = S1Interval::full();
+        assert!(empty.is_valid() && empty.is_empty() && empty.is_inverted());
+        assert!(full.is_valid() && full.is_full() && !full.is_inverted());
+        assert!(empty.get_length() < 0.0);
+        assert_eq!(full.get_length(), 2.0 * PI);
+        assert_eq!(S1Interval::default(), empty);
+
+        // -Pi is converted to Pi except for the full interval.
+        assert_eq!(S1Interval::new(-PI, 0.0).lo(), PI);
+        assert_eq!(S1Interval::new(0.0, -PI).hi(), PI);
+        assert_eq!(S1Interval::from_point(-PI).lo(), PI);
+
+        let mid = S1Interval::new(-1.0, 2.0);
+        assert_eq!(mid.get_center(), 0.5);
+        assert_eq!(mid.get_length(), 3.0);
+        let inverted = S1Interval::new(3.0, -3.0);
+        assert!(inverted.is_inverted());
+        assert_eq!(inverted.get_center(), PI);
+        assert!((inverted.get_length() - (2.0 * PI - 6.0)).abs() < 1e-15);
+        assert_eq!(mid.get_complement_center(), 0.5 - PI);
+        assert_eq!(inverted.get_complement_center(), 0.0);
+        assert_eq!(
+            S1Interval::new(0.0, FRAC_PI_2).get_complement_center(),
+            -3.0 * PI / 4.0
+        );
+        assert_eq!(S1Interval::from_point(PI).get_complement_center(), 0.0);
+        assert_eq!(
+            S1Interval::from_point(-FRAC_PI_2).get_complement_center(),
+            FRAC_PI_2
+        );
+    }
+
+    #[test]
+    fn test_contains() {
+        let mid = S1Interval::new(-1.0, 2.0);
+        assert!(mid.contains(0.0) && mid.contains(-1.0) && mid.contains(2.0));
+        assert!(!mid.contains(3.0));
+        assert!(!mid.interior_contains(2.0) && mid.interior_contains(1.0));
+
+        let inverted = S1Interval::new(3.0, -3.0);
+        assert!(inverted.contains(PI) && inverted.contains(-PI));
+        assert!(!inverted.contains(0.0));
+        assert!(S1Interval::full().interior_contains(PI));
+        assert!(!S1Interval::empty().contains(PI));
+        assert!(!S1Interval::empty().contains(0.0));
+    }
+
+    #[test]
+    fn test_interval_ops() {
+        let empty = S1Interval::empty();
+        let full = S1Interval::full();
+        let quad12 = S1Interval::new(0.0, PI);
+        let quad23 = S1Interval::new(FRAC_PI_2, -FRAC_PI_2);
+        let quad1 = S1Interval::new(0.0, FRAC_PI_2);
+        let quad3 = S1Interval::new(PI, -FRAC_PI_2);
+        let mid12 = S1Interval::from_point(FRAC_PI_2);
+
+        assert!(full.contains_interval(&empty) && full.contains_interval(&quad23));
+        assert!(empty.contains_interval(&empty) && !empty.contains_interval(&mid12));
+        assert!(quad12.contains_interval(&quad1) && quad12.contains_interval(&mid12));
+        assert!(!quad12.contains_interval(&quad23) && !quad1.contains_interval(&quad12));
+        assert!(quad23.contains_interval(&quad3) && quad23.contains_interval(&mid12));
+        assert!(!quad23.contains_interval(&full) && !quad3.contains_interval(&quad23));
+
+        assert!(!full.intersects(&empty) && !empty.intersects(&empty));
+        assert!(full.intersects(&quad3) && quad12.intersects(&quad23));
+        assert!(quad1.intersects(&mid12) && !quad1.intersects(&quad3));
+        assert!(quad23.intersects(&quad3) && quad3.intersects(&quad12));
+        // Pi and -Pi are the same point.
+        assert!(S1Interval::new(-PI, -3.0).intersects(&S1Interval::new(2.0, PI)));
+
+        assert_eq!(empty.union(&empty), empty);
+        assert_eq!(quad1.union(&empty), quad1);
+        assert_eq!(quad12.union(&quad3), S1Interval::new(0.0, -FRAC_PI_2));
+        assert_eq!(quad3.union(&quad1), S1Interval::new(PI, FRAC_PI_2));
+        assert_eq!(quad23.union(&quad1), S1Interval::new(0.0, -FRAC_PI_2));
+        assert_eq!(
+            quad12.union(&S1Interval::new(-FRAC_PI_2, 0.0)).lo(),
+            -FRAC_PI_2
+        );
+        assert!(quad12
+            .union(&quad23)
+            .union(&S1Interval::new(-2.0, 0.5))
+            .is_full());
+    }
+
+    #[test]
+    fn test_from_point_pair() {
+        assert_eq!(
+            S1Interval::from_point_pair(-PI, PI),
+            S1Interval::from_point(PI)
+        );
+        assert_eq!(
+            S1Interval::from_point_pair(1.0, -1.0),
+            S1Interval::new(-1.0, 1.0)
+        );
+        assert_eq!(
+            S1Interval::from_point_pair(-3.0, 3.0),
+            S1Interval::new(3.0, -3.0)
+        );
+    }
+
+    #[test]
+    fn test_expanded() {
+        assert_eq!(S1Interval::empty().expanded(1.0), S1Interval::empty());
+        assert_eq!(S1Interval::full().expanded(-1.0), S1Interval::full());
+        assert_eq!(
+            S1Interval::new(-1.0, 1.0).expanded(-1.0),
+            S1Interval::empty()
+        );
+        assert_eq!(S1Interval::new(-1.0, 1.0).expanded(PI), S1Interval::full());
+        assert_eq!(
+            S1Interval::new(-1.0, 1.0).expanded(0.5),
+            S1Interval::new(-1.5, 1.5)
+        );
+        assert_eq!(
+            S1Interval::new(-1.0, 1.0).expanded(-0.5),
+            S1Interval::new(-0.5, 0.5)
+        );
+
+        // Expansion across +/- Pi wraps around.
+        let expanded = S1Interval::new(PI - 0.1, PI).expanded(0.2);
+        assert!(expanded.is_inverted());
+        assert!((expanded.lo() - (PI - 0.3)).abs() < 1e-15);
+        assert!((expanded.hi() - (-PI + 0.2)).abs() < 1e-15);
+        let point = S1Interval::from_point(PI).expanded(0.1);
+        assert!(point.contains(PI) && point.contains(-PI + 0.05));
+    }
+}
@@ -0,0 +1,478 @@
+//! Domains of the conversions between the cell coordinate systems.
+//!
+//! The coordinate systems themselves are described in `s2/mod.rs`. Each
+//! scalar conversion between them is defined on a fixed domain:
+//!
+//! | Conversion         | Domain                 | Range                   | Out of range |
+//! |--------------------|------------------------|-------------------------|--------------|
+//! | [`st_to_uv`]       | `s` in `[0,1]`         | `u` in `[-1,1]`         | debug_assert |
+//! | [`uv_to_st`]       | `u` in `[-1,1]`        | `s` in `[0,1]`          | debug_assert |
+//! | [`siti_to_st`]     | `si` in `[0,MAX_SITI]` | `s` in `[0,1]`          | debug_assert |
+//! | [`st_to_siti`]     | `s` in `[0,1]`         | `si` in `[0,MAX_SITI]`  | debug_assert |
+//! | [`ij_to_st_min`]   | `i` in `[0,LIMIT_IJ]`  | `s` in `[0,1]`          | debug_assert |
+//! | [`st_to_ij`]       | any `s`                | `i` in `[0,LIMIT_IJ-1]` | clamps       |
+//!
+//! In release builds the asserting functions do not check their arguments:
+//! `st_to_uv` and `uv_to_st` extrapolate the projection, `siti_to_st` and
+//! `ij_to_st_min` scale linearly, and `st_to_siti` panics for values that do
+//! not round to a `u32`. The same domains apply to [`st_to_uv_with`] and
+//! [`uv_to_st_with`].
+//!
+//! Values that come from outside the library (or from arithmetic that may
+//! drift slightly past a face edge) should instead go through the
+//! `*_clamped` variants in this module, which clamp the argument to the
+//! domain first and are therefore total. [`st_to_ij`] already clamps its
+//! result (a leaf cell index must always be valid), so it has no separate
+//! variant. NaN is not a valid input to any of these functions.
+//!
+//! None of the conversions wrap: a position past the edge of a face belongs
+//! to a neighboring face, which is handled by `S2CellId::from_face_ij_wrap`
+//! rather than by the scalar conversions.
+//!
+//! All of the conversions are available in `no_std` builds, with or without
+//! the `alloc` feature.
+//!
+//! Leaf cell indices and (si,ti) values are both plain integers, but they
+//! differ by a factor of two, so passing one where the other is expected
+//! silently produces a point in the wrong place. The conversions therefore
+//! take and return the [`IjCoord`] and [`SiTiCoord`] wrappers. Each of them
+//! also has a `*_raw` variant that works with bare integers, for code that
+//! already does its own bookkeeping. The `*_clamped` variants use the
+//! wrappers too; a wrapper may hold any value, and clamping it is exactly
+//! their job.
+//!
+//! The rounding errors of the conversions are given by the `MAX_*_ERROR`
+//! constants in `s2/mod.rs`.
+//!
+//! [`st_to_uv`]: crate::s2::st_to_uv
+//! [`uv_to_st`]: crate::s2::uv_to_st
+//! [`siti_to_st`]: crate::s2::siti_to_st
+//! [`st_to_siti`]: crate::s2::st_to_siti
+//! [`ij_to_st_min`]: crate::s2::ij_to_st_min
+//! [`st_to_ij`]: crate::s2::st_to_ij
+//! [`st_to_uv_with`]: crate::s2::st_to_uv_with
+//! [`uv_to_st_with`]: crate::s2::uv_to_st_with
+
+use core::ops::{Add, AddAssign, BitAnd, Deref, Neg, Sub};
+
+use crate::s2::{
+    ij_to_st_min_raw, siti_to_st_raw, st_to_siti_raw, st_to_uv, uv_to_st, LIMIT_IJ, MAX_SITI,
+};
+
+/// The i- or j-index of a leaf cell (see s2/mod.rs). Valid leaf cells have
+/// indices in the range [0..LIMIT_IJ-1], and the conversion functions also
+/// accept LIMIT_IJ itself as the upper edge of the last leaf cell.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{ij_to_st_min, st_to_ij, IjCoord};
+///
+/// let i = st_to_ij(0.5);
+/// assert_eq!(i, IjCoord(IjCoord::LIMIT.into_inner() / 2));
+/// assert_eq!(
+///     ij_to_st_min(i + IjCoord(1)),
+///     0.5 + 1.0 / IjCoord::LIMIT.into_inner() as f64
+/// );
+/// assert_eq!(*i, 1 << 29);
+/// ```
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IjCoord(pub i32);
+
+impl IjCoord {
+    /// The maximum index of a valid leaf cell plus one (see LIMIT_IJ).
+    pub const LIMIT: IjCoord = IjCoord(LIMIT_IJ);
+
+    /// Returns the wrapped index.
+    pub const fn into_inner(self) -> i32 {
+        self.0
+    }
+}
+
+impl Deref for IjCoord {
+    type Target = i32;
+
+    fn deref(&self) -> &i32 {
+        &self.0
+    }
+}
+
+impl From<IjCoord> for i32 {
+    fn from(i: IjCoord) -> i32 {
+        i.0
+    }
+}
+
+impl Add for IjCoord {
+    type Output = IjCoord;
+
+    fn add(self, other: IjCoord) -> IjCoord {
+        IjCoord(self.0 + other.0)
+    }
+}
+
+impl Sub for IjCoord {
+    type Output = IjCoord;
+
+    fn sub(self, other: IjCoord) -> IjCoord {
+        IjCoord(self.0 - other.0)
+    }
+}
+
+impl AddAssign for IjCoord {
+    fn add_assign(&mut self, other: IjCoord) {
+        self.0 += other.0;
+    }
+}
+
+impl Neg for IjCoord {
+    type Output = IjCoord;
+
+    fn neg(self) -> IjCoord {
+        IjCoord(-self.0)
+    }
+}
+
+// Cell sizes are powers of two, so "i & -size" rounds "i" down to the
+// lower edge of the cell of that size containing it.
+impl BitAnd for IjCoord {
+    type Output = IjCoord;
+
+    fn bitand(self, other: IjCoord) -> IjCoord {
+        IjCoord(self.0 & other.0)
+    }
+}
+
+/// An si- or ti-coordinate (see s2/mod.rs). These are twice as fine as leaf
+/// cell indices, so that the centers and edges of all cells (including leaf
+/// cells) have integer coordinates. Valid values are in the range
+/// [0..MAX_SITI].
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{siti_to_st, st_to_siti, IjCoord, SiTiCoord};
+///
+/// let si = st_to_siti(0.25);
+/// assert_eq!(si, SiTiCoord(SiTiCoord::MAX.into_inner() / 4));
+/// assert_eq!(
+///     siti_to_st(si + SiTiCoord(1)),
+///     0.25 + 1.0 / SiTiCoord::MAX.into_inner() as f64
+/// );
+/// assert_eq!(si.to_ij(), IjCoord(IjCoord::LIMIT.into_inner() / 4));
+/// ```
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SiTiCoord(pub u32);
+
+impl SiTiCoord {
+    /// The maximum value of an si- or ti-coordinate (see MAX_SITI).
+    pub const MAX: SiTiCoord = SiTiCoord(MAX_SITI);
+
+    /// Returns the wrapped value.
+    pub const fn into_inner(self) -> u32 {
+        self.0
+    }
+
+    /// Returns the index of the leaf cell whose (closed) range of
+    /// si-coordinates starts at or just before this one, i.e. si / 2. For
+    /// the center of any cell this is a leaf cell contained by that cell.
+    pub const fn to_ij(self) -> IjCoord {
+        IjCoord((self.0 >> 1) as i32)
+    }
+}
+
+impl Deref for SiTiCoord {
+    type Target = u32;
+
+    fn deref(&self) -> &u32 {
+        &self.0
+    }
+}
+
+impl From<SiTiCoord> for u32 {
+    fn from(si: SiTiCoord) -> u32 {
+        si.0
+    }
+}
+
+impl Add for SiTiCoord {
+    type Output = SiTiCoord;
+
+    fn add(self, other: SiTiCoord) -> SiTiCoord {
+        SiTiCoord(self.0 + other.0)
+    }
+}
+
+impl Sub for SiTiCoord {
+    type Output = SiTiCoord;
+
+    fn sub(self, other: SiTiCoord) -> SiTiCoord {
+        SiTiCoord(self.0 - other.0)
+    }
+}
+
+/// Like st_to_uv(), but first clamps "s" to the range [0,1], so the result
+/// is always in the range [-1,1].
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::st_to_uv_clamped;
+///
+/// assert_eq!(st_to_uv_clamped(-0.25), -1.0);
+/// assert_eq!(st_to_uv_clamped(0.5), 0.0);
+/// assert_eq!(st_to_uv_clamped(1.25), 1.0);
+/// ```
+pub fn st_to_uv_clamped(s: f64) -> f64 {
+    st_to_uv(s.clamp(0.0, 1.0))
+}
+
+/// Like uv_to_st(), but first clamps "u" to the range [-1,1], so the result
+/// is always in the range [0,1].
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::uv_to_st_clamped;
+///
+/// assert_eq!(uv_to_st_clamped(-1.5), 0.0);
+/// assert_eq!(uv_to_st_clamped(0.0), 0.5);
+/// assert_eq!(uv_to_st_clamped(1.5), 1.0);
+/// ```
+pub fn uv_to_st_clamped(u: f64) -> f64 {
+    uv_to_st(u.clamp(-1.0, 1.0))
+}
+
+/// Like siti_to_st(), but values greater than MAX_SITI are treated as
+/// MAX_SITI, so the result is always in the range [0,1].
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{siti_to_st_clamped, SiTiCoord, MAX_SITI};
+///
+/// assert_eq!(siti_to_st_clamped(SiTiCoord(MAX_SITI / 2)), 0.5);
+/// assert_eq!(siti_to_st_clamped(SiTiCoord(u32::MAX)), 1.0);
+/// ```
+pub fn siti_to_st_clamped(si: SiTiCoord) -> f64 {
+    siti_to_st_raw(si.0.min(MAX_SITI))
+}
+
+/// Like st_to_siti(), but first clamps "s" to the range [0,1], so the result
+/// is always in the range [0..MAX_SITI].
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{st_to_siti_clamped, SiTiCoord, MAX_SITI};
+///
+/// assert_eq!(st_to_siti_clamped(-0.5), SiTiCoord(0));
+/// assert_eq!(st_to_siti_clamped(0.25), SiTiCoord(MAX_SITI / 4));
+/// assert_eq!(st_to_siti_clamped(2.0), SiTiCoord::MAX);
+/// ```
+pub fn st_to_siti_clamped(s: f64) -> SiTiCoord {
+    SiTiCoord(st_to_siti_raw(s.clamp(0.0, 1.0)))
+}
+
+/// Like ij_to_st_min(), but first clamps "i" to the range [0..LIMIT_IJ], so
+/// the result is always in the range [0,1].
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{ij_to_st_min_clamped, IjCoord, LIMIT_IJ};
+///
+/// assert_eq!(ij_to_st_min_clamped(IjCoord(-1)), 0.0);
+/// assert_eq!(ij_to_st_min_clamped(IjCoord(LIMIT_IJ / 4)), 0.25);
+/// assert_eq!(ij_to_st_min_clamped(IjCoord(i32::MAX)), 1.0);
+/// ```
+pub fn ij_to_st_min_clamped(i: IjCoord) -> f64 {
+    ij_to_st_min_raw(i.0.clamp(0, LIMIT_IJ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::s2::{
+        ij_to_st_min, siti_to_st, st_to_ij, st_to_ij_raw, st_to_siti, st_to_uv_with, uv_to_st_with,
+        Projection,
+    };
+
+    // Values just past each end of [0,1] and [-1,1].
+    const BELOW_0: f64 = -f64::MIN_POSITIVE;
+    const ABOVE_1: f64 = 1.0 + f64::EPSILON;
+    const BELOW_MINUS_1: f64 = -1.0 - f64::EPSILON;
+
+    #[test]
+    fn test_st_boundaries() {
+        // (s, st_to_uv, st_to_siti, st_to_ij), where None means that s is
+        // outside the domain of the function.
+        let cases = [
+            (0.0, Some((-1.0, SiTiCoord(0))), IjCoord(0)),
+            (
+                0.5,
+                Some((0.0, SiTiCoord(MAX_SITI / 2))),
+                IjCoord(LIMIT_IJ / 2),
+            ),
+            (
+                1.0,
+                Some((1.0, SiTiCoord::MAX)),
+                IjCoord::LIMIT - IjCoord(1),
+            ),
+            (BELOW_0, None, IjCoord(0)),
+            (ABOVE_1, None, IjCoord::LIMIT - IjCoord(1)),
+            (-1.0, None, IjCoord(0)),
+            (2.0, None, IjCoord::LIMIT - IjCoord(1)),
+            (f64::NEG_INFINITY, None, IjCoord(0)),
+            (f64::INFINITY, None, IjCoord::LIMIT - IjCoord(1)),
+        ];
+        for (s, expected, ij) in cases {
+            let clamped = s.clamp(0.0, 1.0);
+            if let Some((uv, siti)) = expected {
+                assert_eq!(st_to_uv(s), uv, "{s}");
+                assert_eq!(st_to_siti(s), siti, "{s}");
+            }
+            assert_eq!(st_to_uv_clamped(s), st_to_uv(clamped), "{s}");
+            assert_eq!(st_to_siti_clamped(s), st_to_siti(clamped), "{s}");
+            assert_eq!(st_to_ij(s), ij, "{s}");
+            assert_eq!(st_to_ij_raw(s), *ij, "{s}");
+        }
+        assert_eq!(st_to_uv_clamped(BELOW_0), -1.0);
+        assert_eq!(st_to_uv_clamped(ABOVE_1), 1.0);
+        assert_eq!(st_to_siti_clamped(BELOW_0), SiTiCoord(0));
+        assert_eq!(st_to_siti_clamped(ABOVE_1), SiTiCoord::MAX);
+    }
+
+    #[test]
+    fn test_uv_boundaries() {
+        let cases = [
+            (-1.0, Some(0.0)),
+            (0.0, Some(0.5)),
+            (1.0, Some(1.0)),
+            (BELOW_MINUS_1, None),
+            (ABOVE_1, None),
+            (f64::NEG_INFINITY, None),
+            (f64::INFINITY, None),
+        ];
+        for (u, st) in cases {
+            if let Some(st) = st {
+                assert_eq!(uv_to_st(u), st, "{u}");
+                assert_eq!(uv_to_st_clamped(u), st, "{u}");
+            }
+            assert_eq!(uv_to_st_clamped(u), uv_to_st(u.clamp(-1.0, 1.0)), "{u}");
+        }
+        assert_eq!(uv_to_st_clamped(BELOW_MINUS_1), 0.0);
+        assert_eq!(uv_to_st_clamped(ABOVE_1), 1.0);
+    }
+
+    #[test]
+    fn test_siti_boundaries() {
+        let cases = [
+            (0, Some(0.0)),
+            (MAX_SITI / 2, Some(0.5)),
+            (MAX_SITI, Some(1.0)),
+            (MAX_SITI + 1, None),
+            (u32::MAX, None),
+        ];
+        for (si, st) in cases {
+            if let Some(st) = st {
+                assert_eq!(siti_to_st(SiTiCoord(si)), st, "{si}");
+                assert_eq!(siti_to_st_raw(si), st, "{si}");
+                assert_eq!(st_to_siti(st), SiTiCoord(si), "{si}");
+                assert_eq!(st_to_siti_raw(st), si, "{si}");
+            }
+            assert_eq!(siti_to_st_clamped(SiTiCoord(si)), st.unwrap_or(1.0), "{si}");
+        }
+    }
+
+    #[test]
+    fn test_ij_boundaries() {
+        let cases = [
+            (0, Some(0.0)),
+            (LIMIT_IJ / 2, Some(0.5)),
+            (LIMIT_IJ - 1, Some(1.0 - 1.0 / LIMIT_IJ as f64)),
+            (LIMIT_IJ, Some(1.0)),
+            (-1, None),
+            (LIMIT_IJ + 1, None),
+            (i32::MIN, None),
+            (i32::MAX, None),
+        ];
+        for (i, st) in cases {
+            match st {
+                Some(st) => {
+                    assert_eq!(ij_to_st_min(IjCoord(i)), st, "{i}");
+                    assert_eq!(ij_to_st_min_raw(i), st, "{i}");
+                    assert_eq!(ij_to_st_min_clamped(IjCoord(i)), st, "{i}");
+                    // The leaf cell index past the end maps back to the last
+                    // valid one.
+                    assert_eq!(st_to_ij(st), IjCoord(i.min(LIMIT_IJ - 1)), "{i}");
+                }
+                None => {
+                    let expected = if i < 0 { 0.0 } else { 1.0 };
+                    assert_eq!(ij_to_st_min_clamped(IjCoord(i)), expected, "{i}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_newtypes() {
+        assert_eq!(IjCoord::LIMIT.into_inner(), LIMIT_IJ);
+        assert_eq!(*SiTiCoord::MAX, MAX_SITI);
+        assert_eq!(i32::from(IjCoord(7)), 7);
+        assert_eq!(u32::from(SiTiCoord(7)), 7);
+        assert_eq!(IjCoord(5) + IjCoord(3), IjCoord(8));
+        assert_eq!(IjCoord(5) - IjCoord(7), IjCoord(-2));
+        assert_eq!(SiTiCoord(5) - SiTiCoord(3), SiTiCoord(2));
+        assert!(IjCoord(1) < IjCoord(2) && SiTiCoord(1) < SiTiCoord(2));
+        assert_eq!(IjCoord(13) & -IjCoord(4), IjCoord(12));
+        let mut i = IjCoord(1);
+        i += IjCoord(2);
+        assert_eq!(i, IjCoord(3));
+
+        // The center of every leaf cell has an odd si-value, and the leaf
+        // cell it maps back to is the original one.
+        for i in [0, 1, LIMIT_IJ / 2, LIMIT_IJ - 1] {
+            let si = SiTiCoord(2 * i as u32 + 1);
+            assert_eq!(si.to_ij(), IjCoord(i));
+            assert_eq!(st_to_ij(siti_to_st(si)), IjCoord(i));
+            assert_eq!(ij_to_st_min(IjCoord(i)), siti_to_st(si - SiTiCoord(1)));
+        }
+        assert_eq!(SiTiCoord::MAX.to_ij(), IjCoord::LIMIT);
+    }
+
+    #[test]
+    fn test_clamped_matches_base_in_domain() {
+        for k in 0..=1000 {
+            let s = k as f64 / 1000.0;
+            assert_eq!(st_to_uv_clamped(s), st_to_uv(s));
+            assert_eq!(st_to_siti_clamped(s), st_to_siti(s));
+            let u = 2.0 * s - 1.0;
+            assert_eq!(uv_to_st_clamped(u), uv_to_st(u));
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn test_base_functions_assert_domain() {
+        extern crate std;
+        use std::panic::catch_unwind;
+
+        let out_of_range = [
+            catch_unwind(|| st_to_uv(BELOW_0)),
+            catch_unwind(|| st_to_uv(ABOVE_1)),
+            catch_unwind(|| uv_to_st(BELOW_MINUS_1)),
+            catch_unwind(|| uv_to_st(ABOVE_1)),
+            catch_unwind(|| siti_to_st(SiTiCoord::MAX + SiTiCoord(1))),
+            catch_unwind(|| *st_to_siti(BELOW_0) as f64),
+            catch_unwind(|| *st_to_siti(ABOVE_1) as f64),
+            catch_unwind(|| ij_to_st_min(IjCoord(-1))),
+            catch_unwind(|| ij_to_st_min(IjCoord::LIMIT + IjCoord(1))),
+            catch_unwind(|| st_to_uv_with(Projection::Linear, ABOVE_1)),
+            catch_unwind(|| uv_to_st_with(Projection::Tan, BELOW_MINUS_1)),
+        ];
+        for (k, result) in out_of_range.into_iter().enumerate() {
+            assert!(result.is_err(), "case {k} did not panic");
+        }
+    }
+}
@@ -0,0 +1,343 @@
+//! The recommended ways to measure and compare distances between points.
+//!
+//! | Function                   | Result         | Worst-case error                        |
+//! |----------------------------|----------------|-----------------------------------------|
+//! | [`angle_between_points`]   | `S1Angle`      | ~1e-15 radians at all distances         |
+//! | [`chord_distance`]         | `S1ChordAngle` | 4.5 * DBL_EPSILON relative in length2   |
+//! | [`compare_distance`]       | `Ordering`     | exact unless the distances agree to ~29 digits |
+//!
+//! Use [`angle_between_points`] when the distance itself is needed (e.g. to
+//! convert to meters), and [`chord_distance`] when distances only need to be
+//! compared or accumulated against a limit, since it avoids trigonometric
+//! functions. Note that the chord length changes very slowly near 180
+//! degrees, so converting a chord angle back to an angle loses accuracy there
+//! (up to about 1e-7 radians, i.e. about 60cm on the Earth).
+//!
+//! When the ordering of two distances matters (e.g. when sorting or
+//! deduplicating neighbors), use [`compare_distance`] rather than comparing
+//! the results of either function: it returns the same result as the exact
+//! distances would, and it is antisymmetric, so it never contradicts itself
+//! when its arguments are swapped.
+//!
+//! S2LatLng::get_distance() converts both arguments to points and uses
+//! [`angle_between_points`]. Avoid mixing these results with distances from
+//! the Haversine formula, which loses about half of its significant digits
+//! for nearly antipodal points.
+
+use core::{cmp::Ordering, f64::consts::FRAC_1_SQRT_2};
+
+use crate::{
+    s1::{S1Angle, S1ChordAngle},
+    s2::S2Point,
+    util::math::exact_float::DoubleDouble,
+};
+
+/// Returns the angle between two points, computed as atan2(|a x b|, a.b).
+/// Unlike formulas based on acos() or asin(), this is accurate to within a
+/// few times DBL_EPSILON (about 1e-15 radians) for all angles, including
+/// nearly coincident and nearly antipodal points. The points do not need
+/// to be unit length.
+///
+/// # Examples
+///
+/// ```
+/// use core::f64::consts::PI;
+/// use s2shell::s2::{distance::angle_between_points, S2Point};
+///
+/// let a = S2Point::new(1.0, 0.0, 0.0);
+/// let b = S2Point::new(-1.0, 1e-9, 0.0);
+/// assert_eq!(angle_between_points(&a, &b).radians(), PI - 1e-9);
+/// ```
+pub fn angle_between_points(a: &S2Point, b: &S2Point) -> S1Angle {
+    S1Angle::from_points(a, b)
+}
+
+/// Returns the distance between two unit-length points as a chord angle.
+/// The squared chord length has a relative error of at most
+/// 4.5 * DBL_EPSILON (see S1ChordAngle::get_s2point_constructor_max_error()),
+/// which corresponds to a relative angle error of about 2.25 * DBL_EPSILON
+/// for small distances but grows to about 1e-7 radians near 180 degrees.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s1::S1ChordAngle;
+/// use s2shell::s2::{distance::chord_distance, S2Point};
+///
+/// let a = S2Point::new(1.0, 0.0, 0.0);
+/// let b = S2Point::new(0.0, 1.0, 0.0);
+/// assert_eq!(chord_distance(&a, &b), S1ChordAngle::right());
+/// ```
+pub fn chord_distance(a: &S2Point, b: &S2Point) -> S1ChordAngle {
+    S1ChordAngle::from_points(a, b)
+}
+
+/// Compares the distance from X to A with the distance from X to B, i.e.
+/// returns Less if A is closer to X than B. All points should be unit
+/// length.
+///
+/// The comparison is first made using chord angles, and only when the two
+/// distances are too close to be separated within their error bounds is it
+/// repeated in double-double precision, taking the lengths of the points
+/// into account. The result is therefore correct unless the exact
+/// distances agree to about 29 significant digits. Such near-ties (including
+/// exact ties) are broken by comparing A and B lexicographically, so that
+/// swapping A and B always reverses the result, and Equal is returned only
+/// if A == B.
+///
+/// # Examples
+///
+/// ```
+/// use core::cmp::Ordering;
+/// use s2shell::s2::{distance::compare_distance, S2Point};
+///
+/// // Both points are at distance 180 degrees to within double precision,
+/// // but "a" is slightly closer to the antipode of "x".
+/// let x = S2Point::new(1.0, 0.0, 0.0);
+/// let a = S2Point::new(-1.0, 1e-9, 0.0);
+/// let b = S2Point::new(-1.0, 2e-9, 0.0);
+/// assert_eq!(compare_distance(&x, &a, &b), Ordering::Greater);
+/// assert_eq!(compare_distance(&x, &b, &a), Ordering::Less);
+/// ```
+pub fn compare_distance(x: &S2Point, a: &S2Point, b: &S2Point) -> Ordering {
+    if a == b {
+        return Ordering::Equal;
+    }
+    let ax = chord_distance(x, a);
+    let bx = chord_distance(x, b);
+    // Besides the rounding errors in the chord lengths, the difference also
+    // includes the difference between the squared norms of "a" and "b", which
+    // may each differ from 1 by up to 5 * DBL_EPSILON (see is_unit_length()).
+    let error = ax.get_s2point_constructor_max_error()
+        + bx.get_s2point_constructor_max_error()
+        + 10.0 * f64::EPSILON;
+    let diff = ax.length2() - bx.length2();
+    if diff > error {
+        return Ordering::Greater;
+    }
+    if diff < -error {
+        return Ordering::Less;
+    }
+    match compare_distances_dd(x, a, b) {
+        Ordering::Equal if lexicographic_lt(a, b) => Ordering::Greater,
+        Ordering::Equal => Ordering::Less,
+        order => order,
+    }
+}
+
+// Bounds on the error of the double-double values computed below, for points
+// whose squared norms are at most 1 + 5 * DBL_EPSILON. Each double-double
+// operation has a relative error of at most a few times 2**-104 (about
+// 5e-32), and the dot products have magnitude at most about 1.
+const DOT_PROD_ERROR: f64 = 1e-30;
+const COS_CMP_ERROR: f64 = 1e-29;
+const SIN2_CMP_RELATIVE_ERROR: f64 = 1e-29;
+
+// Compares the distances AX and BX in double-double precision, taking the
+// lengths of A and B into account. Returns Equal if the distances are too
+// close to call.
+fn compare_distances_dd(x: &S2Point, a: &S2Point, b: &S2Point) -> Ordering {
+    // AX < BX iff (x.a)/|a| > (x.b)/|b|. First compare the signs of the
+    // cosines.
+    let cos_ax = dot_prod(x, a);
+    let cos_bx = dot_prod(x, b);
+    let sign_ax = sign_with_error(cos_ax, DOT_PROD_ERROR);
+    let sign_bx = sign_with_error(cos_bx, DOT_PROD_ERROR);
+    if sign_ax != sign_bx {
+        return sign_bx.cmp(&sign_ax);
+    }
+    if cos_ax.hi().abs() > FRAC_1_SQRT_2 {
+        // Both distances are less than about 45 degrees or more than about
+        // 135 degrees, where the cosine is too flat to separate nearby
+        // distances. Instead compare the squared sines, which are smaller
+        // for points closer to X (or to -X if the cosines are negative).
+        let sin2_ax = cross_prod_norm2(x, a) * dot_prod(b, b);
+        let sin2_bx = cross_prod_norm2(x, b) * dot_prod(a, a);
+        let error = SIN2_CMP_RELATIVE_ERROR * (sin2_ax.hi() + sin2_bx.hi());
+        return (sign_ax * sign_with_error(sin2_ax - sin2_bx, error)).cmp(&0);
+    }
+    // Otherwise compare the squared cosines, scaled by the squared norms.
+    let cmp = cos_bx * cos_bx * dot_prod(a, a) - cos_ax * cos_ax * dot_prod(b, b);
+    (sign_ax * sign_with_error(cmp, COS_CMP_ERROR)).cmp(&0)
+}
+
+// Returns true if "a" is lexicographically smaller than "b", comparing the
+// x, y and z coordinates in that order.
+fn lexicographic_lt(a: &S2Point, b: &S2Point) -> bool {
+    (a.x(), a.y(), a.z()) < (b.x(), b.y(), b.z())
+}
+
+fn cross_prod_norm2(a: &S2Point, b: &S2Point) -> DoubleDouble {
+    let x = DoubleDouble::from_product(a.y(), b.z()) - DoubleDouble::from_product(a.z(), b.y());
+    let y = DoubleDouble::from_product(a.z(), b.x()) - DoubleDouble::from_product(a.x(), b.z());
+    let z = DoubleDouble::from_product(a.x(), b.y()) - DoubleDouble::from_product(a.y(), b.x());
+    x * x + y * y + z * z
+}
+
+fn dot_prod(a: &S2Point, b: &S2Point) -> DoubleDouble {
+    DoubleDouble::from_product(a.x(), b.x())
+        + DoubleDouble::from_product(a.y(), b.y())
+        + DoubleDouble::from_product(a.z(), b.z())
+}
+
+fn sign_with_error(value: DoubleDouble, error: f64) -> i32 {
+    if value.hi() > error {
+        1
+    } else if value.hi() < -error {
+        -1
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::f64::consts::PI;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::s2::s2testing::random_point;
+    use crate::s2::S2LatLng;
+
+    fn assert_close(actual: f64, expected: f64, max_error: f64) {
+        assert!(
+            (actual - expected).abs() <= max_error,
+            "{actual:e} != {expected:e}"
+        );
+    }
+
+    #[test]
+    fn test_angle_between_points() {
+        // Reference values were computed from the exact input coordinates
+        // with 50 digits of precision and rounded to the nearest double.
+        let cases = [
+            ((1.0, 0.0, 0.0), (1.0, 1e-15, 0.0), 1e-15),
+            ((0.6, 0.8, 0.0), (0.6, 0.8, 3e-12), 3e-12),
+            ((1.0, 0.0, 0.0), (-1.0, 1e-9, 0.0), 3.141592652589793),
+            ((0.6, 0.8, 0.0), (-0.6, -0.8, 3e-10), 3.141592653289793),
+        ];
+        for ((ax, ay, az), (bx, by, bz), expected) in cases {
+            let a = S2Point::new(ax, ay, az);
+            let b = S2Point::new(bx, by, bz);
+            let max_error = 2.0 * f64::EPSILON * expected;
+            assert_close(angle_between_points(&a, &b).radians(), expected, max_error);
+            assert_close(angle_between_points(&b, &a).radians(), expected, max_error);
+        }
+    }
+
+    #[test]
+    fn test_chord_distance() {
+        // Small distances keep their relative accuracy.
+        let a = S2Point::new(0.6, 0.8, 0.0);
+        let b = S2Point::new(0.6, 0.8, 3e-12);
+        let d = S1Angle::from(chord_distance(&a, &b)).radians();
+        assert_close(d, 3e-12, 1e-15 * 3e-12);
+
+        // Near 180 degrees the chord length is insensitive to the distance,
+        // but the error stays within the documented bound.
+        let a = S2Point::new(1.0, 0.0, 0.0);
+        let b = S2Point::new(-1.0, 1e-9, 0.0);
+        let d = S1Angle::from(chord_distance(&a, &b)).radians();
+        assert_close(d, 3.141592652589793, 1e-7);
+
+        let mut rng = StdRng::seed_from_u64(39);
+        for _ in 0..1000 {
+            let a = random_point(&mut rng);
+            let b = random_point(&mut rng);
+            let chord = chord_distance(&a, &b);
+            assert_eq!(chord, chord_distance(&b, &a));
+            let expected = S1ChordAngle::from(angle_between_points(&a, &b));
+            assert!(
+                (chord.length2() - expected.length2()).abs()
+                    <= chord.get_s2point_constructor_max_error() + 4.0 * f64::EPSILON
+            );
+        }
+    }
+
+    #[test]
+    fn test_latlng_distance_near_antipodes() {
+        // The absolute error is small at all distances. (The Haversine formula
+        // gives 3.1415926019706566 for the first case, an error of about
+        // 4e-10.)
+        let cases = [
+            ((0.3, -2.0), (-0.3 + 1e-9, 1.1415926), 3.141592602383743),
+            ((0.0, 0.0), (0.0, PI - 1e-7), PI - 1e-7),
+            ((0.5, 0.25), (0.5, 0.25 + 1e-12), 8.775631482545551e-13),
+        ];
+        for ((lat1, lng1), (lat2, lng2), expected) in cases {
+            let a = S2LatLng::from_radians(lat1, lng1);
+            let b = S2LatLng::from_radians(lat2, lng2);
+            assert_close(a.get_distance(&b).radians(), expected, 4.0 * f64::EPSILON);
+            assert_eq!(
+                a.get_distance(&b),
+                angle_between_points(&a.to_point(), &b.to_point())
+            );
+        }
+    }
+
+    #[test]
+    fn test_compare_distance_hard_cases() {
+        let x = S2Point::new(1.0, 0.0, 0.0);
+
+        // Both points are 90 degrees from X to within double precision, and
+        // their chord lengths are both exactly 2.
+        let a = S2Point::new(1e-17, 1.0, 0.0);
+        let b = S2Point::new(0.0, 1.0, 0.0);
+        assert_eq!(chord_distance(&x, &a), chord_distance(&x, &b));
+        assert_eq!(compare_distance(&x, &a, &b), Ordering::Less);
+        assert_eq!(compare_distance(&x, &b, &a), Ordering::Greater);
+
+        // Nearly antipodal points whose dot products with X are both exactly
+        // -1; only their lengths distinguish them.
+        let a = S2Point::new(-1.0, 1e-9, 0.0);
+        let b = S2Point::new(-1.0, 0.0, 2e-9);
+        assert_eq!(x.dot_prod(&a), x.dot_prod(&b));
+        assert_eq!(compare_distance(&x, &a, &b), Ordering::Greater);
+        assert_eq!(compare_distance(&x, &b, &a), Ordering::Less);
+
+        // Nearly coincident points.
+        let a = S2Point::new(1.0, 1e-20, 0.0);
+        let b = S2Point::new(1.0, 0.0, -2e-20);
+        assert_eq!(compare_distance(&x, &a, &b), Ordering::Less);
+        assert_eq!(compare_distance(&x, &x, &a), Ordering::Less);
+
+        // Exact ties are broken lexicographically (the smaller point is
+        // treated as farther away), and only identical points are Equal.
+        let a = S2Point::new(0.0, 1.0, 0.0);
+        let b = S2Point::new(0.0, 0.0, 1.0);
+        assert_eq!(compare_distance(&x, &a, &b), Ordering::Less);
+        assert_eq!(compare_distance(&x, &b, &a), Ordering::Greater);
+        assert_eq!(compare_distance(&x, &a, &a), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_compare_distance_consistency() {
+        let mut rng = StdRng::seed_from_u64(40);
+        for _ in 0..10000 {
+            let x = random_point(&mut rng);
+            let a = random_point(&mut rng);
+            // Choose "b" near "a", or near the reflection of "a" through X
+            // (so that the distances are nearly equal), or near -X.
+            let base = match rng.gen_range(0..3) {
+                0 => a,
+                1 => (x * (2.0 * x.dot_prod(&a)) - a).normalize(),
+                _ => -x,
+            };
+            let scale = 1e-15 * 2f64.powi(rng.gen_range(0..40));
+            let b = (base + random_point(&mut rng) * scale).normalize();
+
+            let ab = compare_distance(&x, &a, &b);
+            assert_eq!(ab, compare_distance(&x, &b, &a).reverse());
+            assert_eq!(ab == Ordering::Equal, a == b);
+
+            // The result agrees with the angles whenever they are separated
+            // by more than their error.
+            let da = angle_between_points(&x, &a).radians();
+            let db = angle_between_points(&x, &b).radians();
+            if (da - db).abs() > 1e-14 {
+                assert_eq!(ab, da.partial_cmp(&db).unwrap(), "{x:?} {a:?} {b:?}");
+            }
+        }
+    }
+}
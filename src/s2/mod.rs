@@ -15,23 +15,83 @@
 
 // Original Author: ericv@google.com (Eric Veach)
 
+use core::f64::consts::{FRAC_2_PI, FRAC_PI_2, FRAC_PI_4};
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
 use num_traits::ToPrimitive;
 
 use crate::r2::R2Point;
 
+pub mod coords;
+pub mod distance;
+#[cfg(feature = "alloc")]
+pub mod s2buffered_polyline;
 pub mod s2cap;
 pub mod s2cell;
 pub mod s2cell_id;
+#[cfg(feature = "alloc")]
+pub mod s2cell_union;
+pub mod s2centroids;
+pub mod s2contains_point;
+pub mod s2earth;
+pub mod s2edge_clipping;
+pub mod s2edge_crossings;
+pub mod s2edge_distances;
+#[cfg(feature = "alloc")]
+pub mod s2edge_vector_shape;
+pub mod s2latlng;
 pub mod s2latlng_rect;
+pub mod s2latlng_rect_bounder;
+#[cfg(feature = "alloc")]
+pub mod s2loop;
+pub mod s2measures;
+pub mod s2metrics;
 pub mod s2point;
+#[cfg(feature = "alloc")]
+pub mod s2point_compression;
+#[cfg(feature = "alloc")]
+pub mod s2polyline;
+pub mod s2predicates;
 pub mod s2region;
+#[cfg(feature = "alloc")]
+pub mod s2region_coverer;
+pub mod s2shape;
+#[cfg(test)]
+pub(crate) mod s2testing;
+pub mod snap_functions;
 
+pub use coords::*;
+#[cfg(feature = "alloc")]
+pub use s2buffered_polyline::*;
 pub use s2cap::*;
 pub use s2cell::*;
 pub use s2cell_id::*;
+#[cfg(feature = "alloc")]
+pub use s2cell_union::S2CellUnion;
+pub use s2contains_point::*;
+pub use s2edge_clipping::*;
+pub use s2edge_crossings::*;
+pub use s2edge_distances::*;
+#[cfg(feature = "alloc")]
+pub use s2edge_vector_shape::*;
+pub use s2latlng::*;
 pub use s2latlng_rect::*;
+#[cfg(feature = "alloc")]
+pub use s2loop::*;
+pub use s2measures::*;
+pub use s2metrics::{AreaMetric, LengthMetric, Metric};
 pub use s2point::*;
+#[cfg(feature = "alloc")]
+pub use s2point_compression::*;
+#[cfg(feature = "alloc")]
+pub use s2polyline::*;
+pub use s2predicates::*;
 pub use s2region::*;
+#[cfg(feature = "alloc")]
+pub use s2region_coverer::*;
+pub use s2shape::*;
+pub use snap_functions::*;
 
 // This file contains documentation of the various coordinate systems used
 // throughout the library. Most importantly, S2 defines a framework for
@@ -118,6 +178,44 @@ pub use s2region::*;
 /// magnitude less than two.
 pub const MAX_XYZ_TO_UV_ERROR: f64 = 0.5 * f64::EPSILON;
 
+/// The maximum absolute error in U/V coordinates when converting from S/T
+/// with st_to_uv().
+///
+/// For s >= 0.5 the result is (1/3) * (4*s*s - 1). The product s*s is at
+/// most 1, so it is rounded by at most 0.25*DBL_EPSILON, which becomes
+/// DBL_EPSILON after scaling by 4 (exactly). The subtraction is exact when
+/// 4*s*s <= 2 (Sterbenz) and otherwise rounds by at most DBL_EPSILON.
+/// Dividing this 2*DBL_EPSILON by 3 and adding the error of the constant
+/// 1/3 (at most 0.25*DBL_EPSILON after scaling by 4*s*s - 1 <= 3) and the
+/// final rounding (0.25*DBL_EPSILON) gives 7/6*DBL_EPSILON.
+///
+/// For s < 0.5 the result is (1/3) * (1 - 4*(1-s)*(1-s)). Now 1-s itself
+/// is rounded by up to 0.25*DBL_EPSILON, which contributes 2*DBL_EPSILON to
+/// 4*(1-s)^2 <= 4, and the product and the subtraction each round by at most
+/// DBL_EPSILON. Dividing by 3 and adding the same two terms as above gives
+/// 11/6*DBL_EPSILON, which we round up to 2*DBL_EPSILON.
+pub const MAX_ST_TO_UV_ERROR: f64 = 2.0 * f64::EPSILON;
+
+/// The maximum absolute error in S/T coordinates when converting from U/V
+/// with uv_to_st().
+///
+/// The argument of the square root, 1 + 3*|u| <= 4, accumulates at most
+/// DBL_EPSILON from the product and DBL_EPSILON from the sum. The square
+/// root halves this error (its derivative is at most 1/2 on [1,4]) and adds
+/// a rounding error of 0.5*DBL_EPSILON, and the final multiplication by 0.5
+/// halves the total to 0.75*DBL_EPSILON. For u < 0 the final subtraction
+/// from 1 is exact (Sterbenz), so the same bound applies.
+pub const MAX_UV_TO_ST_ERROR: f64 = 0.75 * f64::EPSILON;
+
+/// The maximum absolute error in S/T coordinates when converting from si/ti
+/// with siti_to_st(). The conversion is exact, since every valid si-value
+/// is an integer of at most 32 bits and the scale factor is a power of two.
+pub const MAX_SITI_TO_ST_ERROR: f64 = 0.0;
+
+/// The maximum absolute error in S/T coordinates when converting from i/j
+/// with ij_to_st_min(). As with siti_to_st(), the conversion is exact.
+pub const MAX_IJ_TO_ST_ERROR: f64 = 0.0;
+
 /// This is the number of levels needed to specify a leaf cell. This
 /// constant is defined here so that the S2::Metric class and the conversion
 /// functions below can be implemented without including s2cell_id.h. Please
@@ -132,33 +230,156 @@ pub const LIMIT_IJ: i32 = 1 << MAX_CELL_LEVEL; // == S2CellId::MAX_SIZE
 /// values is [0..MAX_SITI].
 pub const MAX_SITI: u32 = 1 << (MAX_CELL_LEVEL + 1);
 
+/// The transformation used to map (s,t)-coordinates to (u,v)-coordinates.
+/// This library always uses `Projection::Quadratic` (see `st_to_uv` and
+/// `uv_to_st`); the other projections are available through `st_to_uv_with`
+/// and `uv_to_st_with` for experimentation.
+///
+/// Here are some comparisons of the three projections:
+///
+/// ```text
+///                Area    Edge    Diag
+///               Ratio   Ratio   Ratio
+/// Linear:      5.200   2.117   2.959
+/// Tan:         1.414   1.414   1.704
+/// Quadratic:   2.082   1.802   1.932
+/// ```
+///
+/// The area ratio is the ratio of the largest to smallest cell area at a
+/// given level, and similarly for the edge and diagonal ratios.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub enum Projection {
+    /// This is the fastest transformation, but also produces the least
+    /// uniform cell sizes.
+    Linear,
+    /// Transforming the coordinates via atan() makes the cell sizes more
+    /// uniform. The areas vary by a maximum ratio of 1.4 as opposed to a
+    /// maximum ratio of 5.2. However, each call to atan() is about as
+    /// expensive as all of the other calculations combined when converting
+    /// from points to cell ids, i.e. it reduces performance by a factor of 3.
+    Tan,
+    /// This is an approximation of the tangent projection that is much faster
+    /// and produces cells that are almost as uniform in size. It is about 3
+    /// times faster than the tangent projection for converting cell ids to
+    /// points or vice versa. Cell areas vary by a maximum ratio of about 2.1.
+    #[default]
+    Quadratic,
+}
+
+/// Convert an s- or t-value to the corresponding u- or v-value using the
+/// given projection. The argument must be in the range [0,1]; see
+/// st_to_uv_clamped() for arbitrary values.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{st_to_uv, st_to_uv_with, Projection};
+///
+/// assert_eq!(st_to_uv_with(Projection::Linear, 0.75), 0.5);
+/// assert_eq!(st_to_uv_with(Projection::Quadratic, 0.3), st_to_uv(0.3));
+/// ```
+pub fn st_to_uv_with(projection: Projection, s: f64) -> f64 {
+    debug_assert!((0.0..=1.0).contains(&s), "s out of range: {s}");
+
+    match projection {
+        Projection::Linear => 2.0 * s - 1.0,
+        Projection::Tan => {
+            // Unfortunately, tan(FRAC_PI_4) is slightly less than 1.0. This
+            // isn't due to a flaw in the implementation of tan(), it's because
+            // the derivative of tan(x) at x=pi/4 is 2, and it happens that the
+            // two adjacent floating point numbers on either side of the
+            // infinite-precision value of pi/4 have tangents that are slightly
+            // below and slightly above 1.0 when rounded to the nearest
+            // double-precision result.
+            let u = (FRAC_PI_2 * s - FRAC_PI_4).tan();
+            u + (1.0 / (1_i64 << 53) as f64) * u
+        }
+        Projection::Quadratic => {
+            if s >= 0.5 {
+                (1.0 / 3.0) * (4.0 * s * s - 1.0)
+            } else {
+                (1.0 / 3.0) * (1.0 - 4.0 * (1.0 - s) * (1.0 - s))
+            }
+        }
+    }
+}
+
+/// The inverse of `st_to_uv_with` for the given projection. Note that it is
+/// not always true that uv_to_st_with(p, st_to_uv_with(p, x)) == x due to
+/// numerical errors. The argument must be in the range [-1,1]; see
+/// uv_to_st_clamped() for arbitrary values.
+pub fn uv_to_st_with(projection: Projection, u: f64) -> f64 {
+    debug_assert!((-1.0..=1.0).contains(&u), "u out of range: {u}");
+
+    match projection {
+        Projection::Linear => 0.5 * (u + 1.0),
+        Projection::Tan => FRAC_2_PI * (u.atan() + FRAC_PI_4),
+        Projection::Quadratic => {
+            if u >= 0.0 {
+                0.5 * (1.0 + 3.0 * u).sqrt()
+            } else {
+                1.0 - 0.5 * (1.0 - 3.0 * u).sqrt()
+            }
+        }
+    }
+}
+
 /// Convert an s- or t-value to the corresponding u- or v-value. This is
 /// a non-linear transformation from [0,1] to [-1,1] that attempts to
-/// make the cell sizes more uniform.
+/// make the cell sizes more uniform. The argument must be in the range
+/// [0,1]; see st_to_uv_clamped() for arbitrary values.
 pub fn st_to_uv(s: f64) -> f64 {
-    if s >= 0.5 {
-        (1.0 / 3.0) * (4.0 * s * s - 1.0)
-    } else {
-        (1.0 / 3.0) * (1.0 - 4.0 * (1.0 - s) * (1.0 - s))
-    }
+    st_to_uv_with(Projection::Quadratic, s)
 }
 
 /// The inverse of the STtoUV transformation. Note that it is not always
-/// true that UVtoST(STtoUV(x)) == x due to numerical errors.
+/// true that UVtoST(STtoUV(x)) == x due to numerical errors. Exhaustive
+/// sampling of [0,1] shows that the round trip error is at most
+/// 0.75 * DBL_EPSILON in (s,t)-space, and at most DBL_EPSILON in (u,v)-space
+/// for the opposite round trip.
+///
+/// The argument must be in the range [-1,1]; see uv_to_st_clamped() for
+/// arbitrary values.
 pub fn uv_to_st(u: f64) -> f64 {
-    if u >= 0.0 {
-        0.5 * (1.0 + 3.0 * u).sqrt()
-    } else {
-        1.0 - 0.5 * (1.0 - 3.0 * u).sqrt()
-    }
+    uv_to_st_with(Projection::Quadratic, u)
+}
+
+/// Convert (u,v) coordinates on a cube face to the corresponding (s,t)
+/// coordinates on the same face. This applies uv_to_st() to each coordinate.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::{
+///     r2::R2Point,
+///     s2::{face_st_to_uv, face_uv_to_st},
+/// };
+///
+/// let st = face_uv_to_st(&R2Point::new(0.0, 1.0));
+/// assert_eq!(st, R2Point::new(0.5, 1.0));
+/// assert_eq!(face_st_to_uv(&st), R2Point::new(0.0, 1.0));
+/// ```
+pub fn face_uv_to_st(uv: &R2Point) -> R2Point {
+    R2Point::new(uv_to_st(uv.x()), uv_to_st(uv.y()))
+}
+
+/// Convert (s,t) coordinates on a cube face to the corresponding (u,v)
+/// coordinates on the same face. This is the inverse of face_uv_to_st().
+pub fn face_st_to_uv(st: &R2Point) -> R2Point {
+    R2Point::new(st_to_uv(st.x()), st_to_uv(st.y()))
 }
 
 /// Convert the i- or j-index of a leaf cell to the minimum corresponding s-
 /// or t-value contained by that cell. The argument must be in the range
 /// [0..2**30], i.e. up to one position beyond the normal range of valid leaf
-/// cell indices.
-pub fn ij_to_st_min(i: i32) -> f64 {
-    debug_assert!((0..=LIMIT_IJ).contains(&i));
+/// cell indices. See ij_to_st_min_clamped() for arbitrary values.
+pub fn ij_to_st_min(i: IjCoord) -> f64 {
+    ij_to_st_min_raw(i.into_inner())
+}
+
+/// Like ij_to_st_min(), but takes a bare integer index.
+pub fn ij_to_st_min_raw(i: i32) -> f64 {
+    debug_assert!((0..=LIMIT_IJ).contains(&i), "i out of range: {i}");
 
     (1.0 / LIMIT_IJ as f64) * (i as f64)
 }
@@ -170,26 +391,47 @@ pub fn ij_to_st_min(i: i32) -> f64 {
 ///
 /// # Examples
 /// ```
-/// use s2shell::s2::{st_to_ij, LIMIT_IJ};
+/// use s2shell::s2::{st_to_ij, IjCoord, LIMIT_IJ};
 ///
 /// let s = 0.5_f64;
-/// assert_eq!(st_to_ij(s), LIMIT_IJ / 2);
+/// assert_eq!(st_to_ij(s), IjCoord(LIMIT_IJ / 2));
 /// ```
-pub fn st_to_ij(s: f64) -> i32 {
+pub fn st_to_ij(s: f64) -> IjCoord {
+    IjCoord(st_to_ij_raw(s))
+}
+
+/// Like st_to_ij(), but returns a bare integer index.
+pub fn st_to_ij_raw(s: f64) -> i32 {
     let index = (LIMIT_IJ as f64 * s - 0.5).round() as i32;
     index.clamp(0, LIMIT_IJ - 1)
 }
 
-/// Convert an si- or ti-value to the corresponding s- or t-value.
-pub fn siti_to_st(si: u32) -> f64 {
-    debug_assert!(si < MAX_SITI);
+/// Convert an si- or ti-value to the corresponding s- or t-value. The
+/// argument must be in the range [0..MAX_SITI]; see siti_to_st_clamped() for
+/// arbitrary values.
+pub fn siti_to_st(si: SiTiCoord) -> f64 {
+    siti_to_st_raw(si.into_inner())
+}
+
+/// Like siti_to_st(), but takes a bare integer coordinate.
+pub fn siti_to_st_raw(si: u32) -> f64 {
+    debug_assert!(si <= MAX_SITI, "si out of range: {si}");
 
     (1.0 / MAX_SITI as f64) * (si as f64)
 }
 
 /// Return the si- or ti-coordinate that is nearest to the given s- or
-/// t-value. The result may be outside the range of valid (si,ti)-values.
-pub fn st_to_siti(s: f64) -> u32 {
+/// t-value. The argument must be in the range [0,1], so that the result is
+/// in the range [0..MAX_SITI]; see st_to_siti_clamped() for arbitrary
+/// values.
+pub fn st_to_siti(s: f64) -> SiTiCoord {
+    SiTiCoord(st_to_siti_raw(s))
+}
+
+/// Like st_to_siti(), but returns a bare integer coordinate.
+pub fn st_to_siti_raw(s: f64) -> u32 {
+    debug_assert!((0.0..=1.0).contains(&s), "s out of range: {s}");
+
     (s * MAX_SITI as f64)
         .round()
         .to_u32()
@@ -211,8 +453,111 @@ pub fn face_uv_to_xyz(face: i32, u: f64, v: f64) -> S2Point {
     }
 }
 
-pub fn face_uv_to_xyz_from_r2point(face: i8, uv: &R2Point) -> S2Point {
-    todo!()
+pub fn face_uv_to_xyz_from_r2point(face: i32, uv: &R2Point) -> S2Point {
+    face_uv_to_xyz(face, uv.x(), uv.y())
+}
+
+/// Given a *valid* face for the given point p (meaning that dot product of p
+/// with the face normal is positive), return the corresponding u and v
+/// values (which may lie outside the range [-1,1]).
+pub fn valid_face_xyz_to_uv(face: i32, p: &S2Point) -> R2Point {
+    debug_assert!(p.dot_prod(&get_norm(face)) > 0.0);
+    match face {
+        0 => R2Point::new(p[1] / p[0], p[2] / p[0]),
+        1 => R2Point::new(-p[0] / p[1], p[2] / p[1]),
+        2 => R2Point::new(-p[0] / p[2], -p[1] / p[2]),
+        3 => R2Point::new(p[2] / p[0], p[1] / p[0]),
+        4 => R2Point::new(p[2] / p[1], -p[0] / p[1]),
+        5 => R2Point::new(-p[1] / p[2], -p[0] / p[2]),
+        _ => panic!("invalid face: {face}"),
+    }
+}
+
+/// Convert a direction vector (not necessarily unit length) to
+/// (face, u, v) coordinates.
+pub fn xyz_to_face_uv(p: &S2Point) -> (i32, f64, f64) {
+    let face = get_face(p);
+    let uv = valid_face_xyz_to_uv(face, p);
+    (face, uv.x(), uv.y())
+}
+
+/// Convert a direction vector (not necessarily unit length) to (u, v)
+/// coordinates on the given face. If the dot product of the point p with the
+/// given face normal is not positive, returns `None`.
+pub fn face_xyz_to_uv(face: i32, p: &S2Point) -> Option<R2Point> {
+    if face < 3 {
+        if p[face as usize] <= 0.0 {
+            return None;
+        }
+    } else if p[(face - 3) as usize] >= 0.0 {
+        return None;
+    }
+    Some(valid_face_xyz_to_uv(face, p))
+}
+
+/// Return the right-handed normal (not necessarily unit length) for an
+/// edge in the direction of the positive v-axis at the given u-value on
+/// the given face. (This vector is perpendicular to the plane through
+/// the sphere origin that contains the given edge.)
+#[rustfmt::skip]
+pub fn get_u_norm(face: i32, u: f64) -> S2Point {
+    match face {
+        0 => S2Point::new(   u, -1.0,  0.0),
+        1 => S2Point::new( 1.0,    u,  0.0),
+        2 => S2Point::new( 1.0,  0.0,    u),
+        3 => S2Point::new(  -u,  0.0,  1.0),
+        4 => S2Point::new( 0.0,   -u,  1.0),
+        5 => S2Point::new( 0.0, -1.0,   -u),
+        _ => panic!("invalid face: {face}"),
+    }
+}
+
+/// Return the right-handed normal (not necessarily unit length) for an
+/// edge in the direction of the positive u-axis at the given v-value on
+/// the given face.
+#[rustfmt::skip]
+pub fn get_v_norm(face: i32, v: f64) -> S2Point {
+    match face {
+        0 => S2Point::new(  -v,  0.0,  1.0),
+        1 => S2Point::new( 0.0,   -v,  1.0),
+        2 => S2Point::new( 0.0, -1.0,   -v),
+        3 => S2Point::new(   v, -1.0,  0.0),
+        4 => S2Point::new( 1.0,    v,  0.0),
+        5 => S2Point::new( 1.0,  0.0,    v),
+        _ => panic!("invalid face: {face}"),
+    }
+}
+
+/// Return the unit-length normal for the given face.
+pub fn get_norm(face: i32) -> S2Point {
+    get_uvw_axis(face, 2)
+}
+
+/// Return the u-, v-, or w-axis (axis 0, 1, or 2) for the given face.
+#[rustfmt::skip]
+pub fn get_uvw_axis(face: i32, axis: i32) -> S2Point {
+    const FACE_UVW_AXES: [[[f64; 3]; 3]; 6] = [
+        [[ 0.0,  1.0,  0.0], [ 0.0,  0.0,  1.0], [ 1.0,  0.0,  0.0]],
+        [[-1.0,  0.0,  0.0], [ 0.0,  0.0,  1.0], [ 0.0,  1.0,  0.0]],
+        [[-1.0,  0.0,  0.0], [ 0.0, -1.0,  0.0], [ 0.0,  0.0,  1.0]],
+        [[ 0.0,  0.0, -1.0], [ 0.0, -1.0,  0.0], [-1.0,  0.0,  0.0]],
+        [[ 0.0,  0.0, -1.0], [ 1.0,  0.0,  0.0], [ 0.0, -1.0,  0.0]],
+        [[ 0.0,  1.0,  0.0], [ 1.0,  0.0,  0.0], [ 0.0,  0.0, -1.0]],
+    ];
+    let [x, y, z] = FACE_UVW_AXES[face as usize][axis as usize];
+    S2Point::new(x, y, z)
+}
+
+/// Return the given point P transformed into the (u,v,w) coordinate system
+/// of the given face. The resulting point W is not necessarily unit length,
+/// and its w-coordinate is positive only if P lies on the given face's
+/// hemisphere.
+pub fn face_xyz_to_uvw(face: i32, p: &S2Point) -> S2Point {
+    S2Point::new(
+        p.dot_prod(&get_uvw_axis(face, 0)),
+        p.dot_prod(&get_uvw_axis(face, 1)),
+        p.dot_prod(&get_uvw_axis(face, 2)),
+    )
 }
 
 /// Return the face containing the given direction vector. (For points on
@@ -226,10 +571,245 @@ pub fn get_face(p: &S2Point) -> i32 {
     }
 }
 
-pub fn face_siti_to_xyz(face: i32, si: u32, ti: u32) -> S2Point {
+/// Convert (face, si, ti) coordinates to a direction vector (not necessarily
+/// unit length).
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{face_siti_to_xyz, S2Point, SiTiCoord};
+///
+/// let half = SiTiCoord(SiTiCoord::MAX.into_inner() / 2);
+/// assert_eq!(face_siti_to_xyz(2, half, half), S2Point::new(0.0, 0.0, 1.0));
+/// ```
+pub fn face_siti_to_xyz(face: i32, si: SiTiCoord, ti: SiTiCoord) -> S2Point {
     let u: f64 = st_to_uv(siti_to_st(si));
     let v: f64 = st_to_uv(siti_to_st(ti));
     face_uv_to_xyz(face, u, v)
 }
 
+/// Like face_siti_to_xyz(), but takes bare integer coordinates.
+pub fn face_siti_to_xyz_raw(face: i32, si: u32, ti: u32) -> S2Point {
+    face_siti_to_xyz(face, SiTiCoord(si), SiTiCoord(ti))
+}
+
+/// Convert a direction vector (not necessarily unit length) to
+/// (face, si, ti) coordinates and, if p is exactly equal to the center of a
+/// cell, return the level of this cell (-1 otherwise).
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{face_siti_to_xyz, xyz_to_face_siti, SiTiCoord, MAX_SITI};
+///
+/// // The center of face 2 is a level 0 cell center.
+/// let half = SiTiCoord(MAX_SITI / 2);
+/// let p = face_siti_to_xyz(2, half, half).normalize();
+/// assert_eq!(xyz_to_face_siti(&p), (2, half, half, 0));
+/// ```
+pub fn xyz_to_face_siti(p: &S2Point) -> (i32, SiTiCoord, SiTiCoord, i32) {
+    let (face, u, v) = xyz_to_face_uv(p);
+    let si = st_to_siti(uv_to_st(u));
+    let ti = st_to_siti(uv_to_st(v));
+
+    // If the levels corresponding to si,ti are not equal, then p is not a cell
+    // center. The si,ti values 0 and MAX_SITI need to be handled specially
+    // because they do not correspond to cell centers at any valid level; they
+    // are mapped to level -1 by the code below.
+    let level = MAX_CELL_LEVEL - (*si | MAX_SITI).trailing_zeros() as i32;
+    if level < 0 || level != MAX_CELL_LEVEL - (*ti | MAX_SITI).trailing_zeros() as i32 {
+        return (face, si, ti, -1);
+    }
+    debug_assert!(level <= MAX_CELL_LEVEL);
+
+    // In infinite precision, this test could be changed to ST == SiTi. However,
+    // due to rounding errors, uv_to_st(xyz_to_face_uv(face_uv_to_xyz(st_to_uv(...))))
+    // is not idempotent. On the other hand, the center is computed exactly the
+    // same way p was originally computed (if it is indeed the center of an
+    // S2Cell): the comparison can be exact.
+    let center = face_siti_to_xyz(face, si, ti).normalize();
+    (face, si, ti, if *p == center { level } else { -1 })
+}
+
+/// Like xyz_to_face_siti(), but returns bare integer coordinates.
+pub fn xyz_to_face_siti_raw(p: &S2Point) -> (i32, u32, u32, i32) {
+    let (face, si, ti, level) = xyz_to_face_siti(p);
+    (face, si.into_inner(), ti.into_inner(), level)
+}
+
 mod internal;
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::{s2::s2testing::random_cell_id, util::math::exact_float::DoubleDouble};
+
+    const PROJECTIONS: [Projection; 3] =
+        [Projection::Linear, Projection::Tan, Projection::Quadratic];
+
+    #[test]
+    fn test_projection_round_trip() {
+        for projection in PROJECTIONS {
+            assert_eq!(st_to_uv_with(projection, 0.0), -1.0);
+            assert_eq!(st_to_uv_with(projection, 1.0), 1.0);
+            assert_eq!(uv_to_st_with(projection, -1.0), 0.0);
+            assert_eq!(uv_to_st_with(projection, 1.0), 1.0);
+            for i in 0..=1000 {
+                let x = i as f64 / 1000.0;
+                let u = 2.0 * x - 1.0;
+                assert!(
+                    (uv_to_st_with(projection, st_to_uv_with(projection, x)) - x).abs() < 1e-15
+                );
+                assert!(
+                    (st_to_uv_with(projection, uv_to_st_with(projection, u)) - u).abs() < 1e-15
+                );
+            }
+        }
+        assert_eq!(Projection::default(), Projection::Quadratic);
+    }
+
+    #[test]
+    fn test_st_uv_round_trip() {
+        // Sample [0,1] densely, including both endpoints and the midpoint
+        // where the quadratic transform switches branches.
+        const N: i32 = 1 << 20;
+        let mut max_st_error = 0.0_f64;
+        let mut max_uv_error = 0.0_f64;
+        for i in 0..=N {
+            let s = i as f64 / N as f64;
+            max_st_error = max_st_error.max((uv_to_st(st_to_uv(s)) - s).abs());
+            let u = 2.0 * s - 1.0;
+            max_uv_error = max_uv_error.max((st_to_uv(uv_to_st(u)) - u).abs());
+        }
+        assert!(max_st_error < 2.0 * f64::EPSILON, "{}", max_st_error);
+        assert!(max_uv_error < 2.0 * f64::EPSILON, "{}", max_uv_error);
+
+        // The endpoints and the midpoint are exact.
+        for (s, u) in [(0.0, -1.0), (0.5, 0.0), (1.0, 1.0)] {
+            assert_eq!(st_to_uv(s), u);
+            assert_eq!(uv_to_st(u), s);
+        }
+
+        // Leaf cell boundaries also survive the round trip.
+        for i in [
+            0,
+            1,
+            12345,
+            LIMIT_IJ / 2 - 1,
+            LIMIT_IJ / 2,
+            LIMIT_IJ - 1,
+            LIMIT_IJ,
+        ] {
+            let s = ij_to_st_min(IjCoord(i));
+            assert!((uv_to_st(st_to_uv(s)) - s).abs() < 2.0 * f64::EPSILON);
+        }
+
+        let uv = R2Point::new(-0.25, 0.75);
+        let round_trip = face_st_to_uv(&face_uv_to_st(&uv));
+        assert!((round_trip.x() - uv.x()).abs() < 2.0 * f64::EPSILON);
+        assert!((round_trip.y() - uv.y()).abs() < 2.0 * f64::EPSILON);
+    }
+
+    #[test]
+    fn test_conversion_error_constants() {
+        // Compare against the conversions evaluated in double-double
+        // precision. For st_to_uv() we check 3*u against (4*s*s - 1) or
+        // (1 - 4*(1-s)^2), and for uv_to_st() we check the square of the
+        // value under the square root, which scales the error by 8*s or
+        // 8*(1-s) (both at least 4).
+        let mut rng = StdRng::seed_from_u64(61);
+        let samples = (0..=1 << 16)
+            .map(|i| i as f64 / (1 << 16) as f64)
+            .chain((0..1 << 20).map(|_| rng.gen::<f64>()));
+        let mut max_st_to_uv = 0.0_f64;
+        let mut max_uv_to_st = 0.0_f64;
+        for s in samples {
+            let u = st_to_uv(s);
+            let exact = if s >= 0.5 {
+                DoubleDouble::from_product(s, s) * 4.0 - DoubleDouble::from_f64(1.0)
+            } else {
+                let t = DoubleDouble::from_sum(1.0, -s);
+                DoubleDouble::from_f64(1.0) - t * t * 4.0
+            };
+            let error = (DoubleDouble::from_f64(u) * 3.0 - exact).hi().abs() / 3.0;
+            max_st_to_uv = max_st_to_uv.max(error);
+
+            let u = 2.0 * s - 1.0;
+            let st = uv_to_st(u);
+            let (root, slope) = if u >= 0.0 {
+                (DoubleDouble::from_f64(2.0 * st), 8.0 * st)
+            } else {
+                let t = DoubleDouble::from_sum(1.0, -st);
+                (t * 2.0, 8.0 * (1.0 - st))
+            };
+            let arg = DoubleDouble::from_f64(1.0) + DoubleDouble::from_product(3.0, u.abs());
+            let error = (root * root - arg).hi().abs() / slope;
+            max_uv_to_st = max_uv_to_st.max(error);
+        }
+        assert!(max_st_to_uv <= MAX_ST_TO_UV_ERROR, "{max_st_to_uv}");
+        assert!(max_uv_to_st <= MAX_UV_TO_ST_ERROR, "{max_uv_to_st}");
+
+        // The si/ti and i/j conversions are exact, i.e. they invert exactly.
+        for k in 0..1000 {
+            let si = SiTiCoord(rng.gen_range(0..=MAX_SITI));
+            let st = siti_to_st(si);
+            assert_eq!(st * MAX_SITI as f64, *si as f64 + MAX_SITI_TO_ST_ERROR);
+            let i = IjCoord(rng.gen_range(0..=LIMIT_IJ));
+            let st = ij_to_st_min(i);
+            assert_eq!(st * LIMIT_IJ as f64, *i as f64 + MAX_IJ_TO_ST_ERROR, "{k}");
+        }
+    }
+
+    #[test]
+    fn test_siti_raw_variants() {
+        let mut rng = StdRng::seed_from_u64(62);
+        for _ in 0..1000 {
+            let id = random_cell_id(&mut rng);
+            let (face, si, ti) = id.get_center_siti();
+            assert_eq!(id.get_center_siti_raw(), (face, *si as i32, *ti as i32));
+            let p = face_siti_to_xyz(face, si, ti);
+            assert_eq!(face_siti_to_xyz_raw(face, *si, *ti), p);
+            let center = p.normalize();
+            assert_eq!(xyz_to_face_siti(&center), (face, si, ti, id.level()));
+            assert_eq!(xyz_to_face_siti_raw(&center), (face, *si, *ti, id.level()));
+        }
+    }
+
+    /// Returns the ratio of the largest to the smallest area of the cells in
+    /// an n x n grid in (s,t)-space on face 0, using the given projection.
+    fn area_ratio(projection: Projection, n: i32) -> f64 {
+        // The area of the spherical triangle ABC (with unit-length vertices).
+        fn triangle_area(a: &S2Point, b: &S2Point, c: &S2Point) -> f64 {
+            let det = a.dot_prod(&b.cross_prod(c)).abs();
+            2.0 * det.atan2(1.0 + a.dot_prod(b) + b.dot_prod(c) + c.dot_prod(a))
+        }
+        let point = |i: i32, j: i32| {
+            let u = st_to_uv_with(projection, i as f64 / n as f64);
+            let v = st_to_uv_with(projection, j as f64 / n as f64);
+            face_uv_to_xyz(0, u, v).normalize()
+        };
+        let (mut min_area, mut max_area) = (f64::INFINITY, 0.0_f64);
+        for i in 0..n {
+            for j in 0..n {
+                let (p00, p10) = (point(i, j), point(i + 1, j));
+                let (p11, p01) = (point(i + 1, j + 1), point(i, j + 1));
+                let area = triangle_area(&p00, &p10, &p11) + triangle_area(&p00, &p11, &p01);
+                min_area = min_area.min(area);
+                max_area = max_area.max(area);
+            }
+        }
+        max_area / min_area
+    }
+
+    #[test]
+    fn test_projection_area_uniformity() {
+        let linear = area_ratio(Projection::Linear, 64);
+        let tan = area_ratio(Projection::Tan, 64);
+        let quadratic = area_ratio(Projection::Quadratic, 64);
+        assert!(tan < quadratic && quadratic < linear);
+        assert!(tan < 1.5, "{}", tan);
+        assert!(linear > 5.0, "{}", linear);
+    }
+}
@@ -0,0 +1,192 @@
+use alloc::vec::Vec;
+
+use crate::{
+    s1::{S1Angle, S1ChordAngle},
+    s2::{
+        s2edge_crossings::crossing_sign,
+        s2edge_distances::{get_update_min_distance_max_error, update_min_distance},
+        S2Cap, S2Cell, S2CellId, S2LatLngRect, S2Point, S2Polyline, S2Region,
+    },
+};
+
+/// An S2Region consisting of all points within a given distance of a
+/// polyline (the polyline "buffered" by that distance). This is the usual
+/// region for geofencing a route, and it can be passed to S2RegionCoverer to
+/// obtain the cells along the path.
+///
+/// Point containment is exact up to the error in the edge distance
+/// computations. The cell predicates are conservative: contains_cell() may
+/// return false for cells that are actually contained, and may_intersect_cell()
+/// may return true for cells that are slightly farther than the buffer
+/// distance.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s1::S1Angle;
+/// use s2shell::s2::{S2BufferedPolyline, S2LatLng, S2Polyline, S2Region};
+///
+/// let line = S2Polyline::new(vec![
+///     S2LatLng::from_degrees(0.0, 0.0).to_point(),
+///     S2LatLng::from_degrees(0.0, 10.0).to_point(),
+/// ]);
+/// let region = S2BufferedPolyline::new(line, S1Angle::from_degrees(1.0));
+/// assert!(region.contains_point(&S2LatLng::from_degrees(0.9, 5.0).to_point()));
+/// assert!(!region.contains_point(&S2LatLng::from_degrees(1.1, 5.0).to_point()));
+/// ```
+#[derive(Debug, Clone)]
+pub struct S2BufferedPolyline {
+    polyline: S2Polyline,
+    radius: S1ChordAngle,
+}
+
+impl S2BufferedPolyline {
+    /// Creates the region of all points within "distance" of the polyline.
+    /// The distance must be non-negative.
+    pub fn new(polyline: S2Polyline, distance: S1Angle) -> S2BufferedPolyline {
+        debug_assert!(distance.radians() >= 0.0);
+        S2BufferedPolyline {
+            polyline,
+            radius: S1ChordAngle::from(distance),
+        }
+    }
+
+    /// Returns the underlying polyline.
+    pub fn polyline(&self) -> &S2Polyline {
+        &self.polyline
+    }
+
+    /// Returns the buffer distance.
+    pub fn radius(&self) -> S1ChordAngle {
+        self.radius
+    }
+
+    // Returns the minimum distance from the given cell to the polyline.
+    fn get_distance_to_cell(&self, cell: &S2Cell) -> S1ChordAngle {
+        let vertices = self.polyline.vertices();
+        if vertices.is_empty() {
+            return S1ChordAngle::infinity();
+        }
+        let cell_vertices = [
+            cell.get_vertex(0),
+            cell.get_vertex(1),
+            cell.get_vertex(2),
+            cell.get_vertex(3),
+        ];
+        let mut min_dist = S1ChordAngle::infinity();
+        for v in vertices {
+            let dist = cell.get_distance(v);
+            if dist < min_dist {
+                min_dist = dist;
+            }
+        }
+        if min_dist == S1ChordAngle::zero() {
+            return min_dist;
+        }
+        // No vertex lies inside the cell, so an edge is either disjoint from
+        // the cell or crosses its boundary. In the first case the closest
+        // pair of points is either an edge endpoint (handled above) or a cell
+        // vertex.
+        for edge in vertices.windows(2) {
+            let (a, b) = (&edge[0], &edge[1]);
+            for k in 0..4 {
+                if a != b
+                    && crossing_sign(a, b, &cell_vertices[k], &cell_vertices[(k + 1) & 3]) >= 0
+                {
+                    return S1ChordAngle::zero();
+                }
+                update_min_distance(&cell_vertices[k], a, b, &mut min_dist);
+            }
+        }
+        min_dist
+    }
+}
+
+impl S2Region for S2BufferedPolyline {
+    fn get_cap_bound(&self) -> S2Cap {
+        let cap = self.polyline.get_rect_bound().get_cap_bound();
+        if cap.is_empty() {
+            return cap;
+        }
+        S2Cap::new(cap.center(), cap.radius() + self.radius)
+    }
+
+    fn get_rect_bound(&self) -> S2LatLngRect {
+        self.get_cap_bound().get_rect_bound()
+    }
+
+    fn get_cell_union_bound(&self, cell_ids: &mut Vec<S2CellId>) {
+        self.get_cap_bound().get_cell_union_bound(cell_ids)
+    }
+
+    fn contains_cell(&self, cell: &S2Cell) -> bool {
+        // Every point of the cell is within the cell's bounding cap radius of
+        // its center, so it suffices to check that the center is close enough
+        // to the polyline (allowing for the error in the distance).
+        let cap = cell.get_cap_bound();
+        let dist = self.polyline.get_distance(&cap.center());
+        if dist.is_infinity() {
+            return false;
+        }
+        let max_dist = dist.plus_error(get_update_min_distance_max_error(dist)) + cap.radius();
+        max_dist <= self.radius && max_dist < S1ChordAngle::straight()
+    }
+
+    fn may_intersect_cell(&self, cell: &S2Cell) -> bool {
+        let dist = self.get_distance_to_cell(cell);
+        !dist.is_infinity()
+            && dist.plus_error(-get_update_min_distance_max_error(dist)) <= self.radius
+    }
+
+    fn contains_point(&self, point: &S2Point) -> bool {
+        self.polyline.get_distance(point) <= self.radius
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::s2::s2testing::{make_polyline, sample_near_polyline};
+    use crate::s2::S2LatLng;
+
+    #[test]
+    fn test_buffered_contains_point() {
+        let line = make_polyline(&[(0.0, 0.0), (0.0, 10.0)]);
+        let region = S2BufferedPolyline::new(line, S1Angle::from_degrees(1.0));
+        assert!(region.contains_point(&S2LatLng::from_degrees(0.0, 5.0).to_point()));
+        assert!(region.contains_point(&S2LatLng::from_degrees(-0.99, 0.0).to_point()));
+        assert!(region.contains_point(&S2LatLng::from_degrees(0.0, 10.99).to_point()));
+        assert!(!region.contains_point(&S2LatLng::from_degrees(0.0, 11.01).to_point()));
+        assert!(!region.contains_point(&S2LatLng::from_degrees(-1.01, 5.0).to_point()));
+    }
+
+    #[test]
+    fn test_buffered_cell_predicates_consistent() {
+        // contains_cell() implies that the whole cell is within the buffer,
+        // and !may_intersect_cell() implies that no part of it is.
+        let mut rng = StdRng::seed_from_u64(59);
+        let line = make_polyline(&[(0.0, 0.0), (3.0, 4.0), (2.0, 8.0), (-1.0, 9.0)]);
+        let region = S2BufferedPolyline::new(line, S1Angle::from_degrees(0.5));
+        for _ in 0..2000 {
+            let p = sample_near_polyline(region.polyline(), S1Angle::from_degrees(2.0), &mut rng);
+            let level = rng.gen_range(6..12);
+            let cell = S2Cell::new(S2CellId::from_point(&p).parent_at_level(level));
+            let points = [
+                cell.get_center(),
+                cell.get_vertex(0),
+                cell.get_vertex(1),
+                cell.get_vertex(2),
+                cell.get_vertex(3),
+            ];
+            if region.contains_cell(&cell) {
+                assert!(region.may_intersect_cell(&cell));
+                assert!(points.iter().all(|v| region.contains_point(v)));
+            }
+            if !region.may_intersect_cell(&cell) {
+                assert!(!points.iter().any(|v| region.contains_point(v)));
+            }
+        }
+    }
+}
@@ -15,10 +15,1162 @@
 
 // Original Author: ericv@google.com (Eric Veach)
 
-use crate::{s1::S1ChordAngle, s2::S2Point};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::{
+    f64::consts::{FRAC_PI_2, PI},
+    fmt,
+};
 
-#[derive(Debug)]
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+#[cfg(feature = "alloc")]
+use crate::s2::{s2metrics::MIN_WIDTH, S2CellId};
+use crate::{
+    r1::R1Interval,
+    s1::{S1Angle, S1ChordAngle, S1Interval},
+    s2::{
+        is_unit_length, s2edge_distances::interpolate_at_distance, S2Cell, S2LatLng, S2LatLngRect,
+        S2Point, S2Region,
+    },
+    util::math::remainder,
+};
+
+/// S2Cap represents a disc-shaped region defined by a center and radius.
+/// Technically this shape is called a "spherical cap" (rather than disc)
+/// because it is not planar; the cap represents a portion of the sphere that
+/// has been cut off by a plane. The boundary of the cap is the circle defined
+/// by the intersection of the sphere and the plane. For containment purposes,
+/// the cap is a closed set, i.e. it contains its boundary.
+///
+/// For the most part, you can use a spherical cap wherever you would use a
+/// disc in planar geometry. The radius of the cap is measured along the
+/// surface of the sphere (rather than the straight-line distance through the
+/// interior). Thus a cap of radius Pi/2 is a hemisphere, and a cap of radius
+/// Pi covers the entire sphere.
+///
+/// A cap can also be defined by its center point and height. The height is
+/// simply the distance from the center point to the cutoff plane. There is
+/// also support for empty and full caps, which contain no points and all
+/// points respectively.
+///
+/// This class is intended to be copied by value as desired.
+#[derive(Debug, Copy, Clone)]
 pub struct S2Cap {
     center: S2Point,
     radius: S1ChordAngle,
 }
+
+impl S2Cap {
+    /// Constructs a cap with the given center and radius. A negative radius
+    /// yields an empty cap; a radius of 180 degrees or more yields a full cap
+    /// (containing the entire sphere). "center" should be unit length.
+    pub fn new(center: S2Point, radius: S1ChordAngle) -> S2Cap {
+        let cap = S2Cap { center, radius };
+        debug_assert!(cap.is_valid());
+        cap
+    }
+
+    /// Constructs a cap with the given center and radius, where the radius is
+    /// given as an S1Angle. Angles larger than 180 degrees are clamped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::{
+    ///     s1::S1Angle,
+    ///     s2::{S2Cap, S2Point, S2Region},
+    /// };
+    ///
+    /// let cap = S2Cap::from_center_angle(S2Point::new(0.0, 0.0, 1.0), S1Angle::from_degrees(10.0));
+    /// assert!(cap.contains_point(&S2Point::new(0.0, 0.0, 1.0)));
+    /// assert!(!cap.contains_point(&S2Point::new(1.0, 0.0, 0.0)));
+    /// ```
+    pub fn from_center_angle(center: S2Point, radius: S1Angle) -> S2Cap {
+        // The "min" calculation is necessary to handle S1Angle::infinity().
+        let radius = if radius.radians() > core::f64::consts::PI {
+            S1Angle::from_radians(core::f64::consts::PI)
+        } else {
+            radius
+        };
+        S2Cap::new(center, S1ChordAngle::from(radius))
+    }
+
+    /// Convenience function that creates a cap containing a single point. This
+    /// method is more efficient that the S2Cap constructor.
+    pub fn from_point(center: S2Point) -> S2Cap {
+        S2Cap::new(center, S1ChordAngle::zero())
+    }
+
+    /// Returns a cap with the given center and height (see comments above). A
+    /// negative height yields an empty cap; a height of 2 or more yields a full
+    /// cap. "center" should be unit length.
+    pub fn from_center_height(center: S2Point, height: f64) -> S2Cap {
+        S2Cap::new(center, S1ChordAngle::from_length2(2.0 * height))
+    }
+
+    /// Returns an empty cap, i.e. a cap that contains no points.
+    pub fn empty() -> S2Cap {
+        S2Cap::new(S2Point::new(1.0, 0.0, 0.0), S1ChordAngle::negative())
+    }
+
+    /// Returns a full cap, i.e. a cap that contains all points.
+    pub fn full() -> S2Cap {
+        S2Cap::new(S2Point::new(1.0, 0.0, 0.0), S1ChordAngle::straight())
+    }
+
+    pub fn center(&self) -> S2Point {
+        self.center
+    }
+
+    pub fn radius(&self) -> S1ChordAngle {
+        self.radius
+    }
+
+    /// Returns the cap radius as an S1Angle. (Note that the cap angle is stored
+    /// internally as an S1ChordAngle, so this method requires a trigonometric
+    /// operation and may yield a slightly different result than the value
+    /// passed to from_center_angle().)
+    pub fn get_radius(&self) -> S1Angle {
+        S1Angle::from(self.radius)
+    }
+
+    /// Returns the height of the cap, i.e. the distance from the center point
+    /// to the cutoff plane.
+    pub fn height(&self) -> f64 {
+        0.5 * self.radius.length2()
+    }
+
+    /// We allow negative heights (to represent empty caps) but heights are
+    /// normalized so that they do not exceed 2.
+    pub fn is_valid(&self) -> bool {
+        is_unit_length(&self.center) && self.radius.length2() <= 4.0
+    }
+
+    /// Returns true if the cap is empty, i.e. it contains no points.
+    pub fn is_empty(&self) -> bool {
+        self.radius.is_negative()
+    }
+
+    /// Returns true if the cap is full, i.e. it contains all points.
+    pub fn is_full(&self) -> bool {
+        self.radius.length2() == 4.0
+    }
+
+    /// Returns the area of the cap on the unit sphere, in steradians. Empty
+    /// caps have zero area, and the full cap has area 4*Pi.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{S2Cap, S2Point};
+    ///
+    /// let hemisphere = S2Cap::from_center_height(S2Point::new(0.0, 0.0, 1.0), 1.0);
+    /// assert_eq!(hemisphere.get_area(), 2.0 * std::f64::consts::PI);
+    /// assert_eq!(S2Cap::empty().get_area(), 0.0);
+    /// ```
+    pub fn get_area(&self) -> f64 {
+        2.0 * PI * self.height().max(0.0)
+    }
+
+    /// Returns the length of the boundary of the cap on the unit sphere,
+    /// i.e. the circumference of a circle of radius sin(radius). Empty and
+    /// full caps have no boundary and return zero.
+    pub fn get_perimeter(&self) -> S1Angle {
+        if self.is_empty() {
+            return S1Angle::zero();
+        }
+        S1Angle::from_radians(2.0 * PI * self.radius.sin())
+    }
+
+    /// Returns the true centroid of the cap multiplied by its surface area
+    /// (see s2centroids.h in the C++ library for details on centroids). The
+    /// result lies on the ray from the origin through the cap's center, but
+    /// it is not unit length. Note that if you just want the "surface
+    /// centroid", i.e. the normalized result, then it is much simpler just to
+    /// call center().
+    ///
+    /// The reason for multiplying the result by the cap area is to make it
+    /// easier to compute the centroid of more complicated shapes. The
+    /// centroid of a union of disjoint regions can be computed simply by
+    /// adding their centroids together. Caps with zero area have a zero
+    /// centroid.
+    pub fn get_centroid(&self) -> S2Point {
+        // From symmetry, the centroid of the cap must be somewhere on the line
+        // from the origin to the center of the cap on the surface of the
+        // sphere. When a sphere is divided into slices of constant thickness
+        // by a set of parallel planes, all slices have the same surface area.
+        // This implies that the radial component of the centroid is simply
+        // the midpoint of the range of radial distances spanned by the cap.
+        // That is easily computed from the cap height.
+        if self.is_empty() {
+            return S2Point::zero();
+        }
+        let r = 1.0 - 0.5 * self.height();
+        self.center * (self.get_area() * r)
+    }
+
+    /// Increases the cap height if necessary to include the given point. If the
+    /// cap is empty then the center is set to the given point, but otherwise
+    /// the center is not changed. "p" should be unit length.
+    pub fn add_point(&mut self, p: &S2Point) {
+        // Compute the squared chord length, then convert it into a height.
+        debug_assert!(is_unit_length(p));
+        if self.is_empty() {
+            self.center = *p;
+            self.radius = S1ChordAngle::zero();
+        } else {
+            // After calling cap.add_point(p), cap.contains_point(p) must be
+            // true. However we don't need to do anything special to achieve
+            // this because contains_point() does exactly the same distance
+            // calculation that we do here.
+            let dist = S1ChordAngle::from_points(&self.center, p);
+            if dist > self.radius {
+                self.radius = dist;
+            }
+        }
+    }
+
+    /// Returns a cap that contains all points within a given distance of this
+    /// cap. Note that any expansion of the empty cap is still empty.
+    ///
+    /// REQUIRES: distance >= 0
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::{
+    ///     s1::S1Angle,
+    ///     s2::{S2Cap, S2LatLng, S2Region},
+    /// };
+    ///
+    /// let center = S2LatLng::from_degrees(0.0, 0.0).to_point();
+    /// let cap = S2Cap::from_center_angle(center, S1Angle::from_degrees(5.0));
+    /// let expanded = cap.expanded(S1Angle::from_degrees(2.0));
+    /// assert!((expanded.get_radius().degrees() - 7.0).abs() < 1e-13);
+    /// assert!(expanded.contains_point(&S2LatLng::from_degrees(6.5, 0.0).to_point()));
+    /// assert!(S2Cap::empty()
+    ///     .expanded(S1Angle::from_degrees(2.0))
+    ///     .is_empty());
+    /// ```
+    pub fn expanded(&self, distance: S1Angle) -> S2Cap {
+        debug_assert!(distance.radians() >= 0.0);
+        if self.is_empty() {
+            return S2Cap::empty();
+        }
+        S2Cap::new(self.center, self.radius + S1ChordAngle::from(distance))
+    }
+
+    /// Like expanded(), but the new radius is computed as an S1Angle, i.e. it
+    /// is min(get_radius() + distance, Pi). expanded() adds the distance to
+    /// the S1ChordAngle radius instead, which is faster but accumulates more
+    /// rounding error. The expansion of the empty cap is still empty, and the
+    /// expansion of the full cap is still full.
+    ///
+    /// REQUIRES: distance >= 0
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::{
+    ///     s1::S1Angle,
+    ///     s2::{S2Cap, S2LatLng},
+    /// };
+    ///
+    /// let center = S2LatLng::from_degrees(48.85, 2.35).to_point();
+    /// let cap = S2Cap::from_center_angle(center, S1Angle::from_degrees(10.0));
+    /// let buffered = cap.expanded_by_distance(S1Angle::from_degrees(5.0));
+    /// assert!((buffered.get_radius().degrees() - 15.0).abs() < 1e-13);
+    /// assert!(cap
+    ///     .expanded_by_distance(S1Angle::from_degrees(175.0))
+    ///     .is_full());
+    /// ```
+    pub fn expanded_by_distance(&self, distance: S1Angle) -> S2Cap {
+        debug_assert!(distance.radians() >= 0.0);
+        if self.is_empty() {
+            return S2Cap::empty();
+        }
+        S2Cap::from_center_angle(
+            self.center,
+            S1Angle::from_radians(self.get_radius().radians() + distance.radians()),
+        )
+    }
+
+    /// Returns the complement of the interior of the cap. A cap and its
+    /// complement have the same boundary but do not share any interior points.
+    /// The complement operator is not a bijection because the complement of a
+    /// singleton cap (containing a single point) is the same as the complement
+    /// of an empty cap.
+    pub fn complement(&self) -> S2Cap {
+        // The complement of a full cap is an empty cap, not a singleton.
+        // Also make sure that the complement of an empty cap is full.
+        if self.is_full() {
+            return S2Cap::empty();
+        }
+        if self.is_empty() {
+            return S2Cap::full();
+        }
+        S2Cap::new(
+            -self.center,
+            S1ChordAngle::from_length2(4.0 - self.radius.length2()),
+        )
+    }
+
+    /// Returns true if and only if this cap contains the given other cap (in a
+    /// set containment sense, e.g. every cap contains the empty cap).
+    pub fn contains(&self, other: &S2Cap) -> bool {
+        if self.is_full() || other.is_empty() {
+            return true;
+        }
+        let distance = S1Angle::from_points(&self.center, &other.center);
+        self.get_radius().radians() >= distance.radians() + other.get_radius().radians()
+    }
+
+    /// Returns true if there are any points that are contained by both this
+    /// cap and the given other cap.
+    pub fn intersects(&self, other: &S2Cap) -> bool {
+        if self.is_empty() || other.is_empty() {
+            return false;
+        }
+        let distance = S1Angle::from_points(&self.center, &other.center);
+        self.get_radius().radians() + other.get_radius().radians() >= distance.radians()
+    }
+
+    /// Returns the smallest cap which encloses this cap and "other".
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::{
+    ///     s1::S1Angle,
+    ///     s2::{S2Cap, S2LatLng},
+    /// };
+    ///
+    /// let a = S2Cap::from_center_angle(
+    ///     S2LatLng::from_degrees(0.0, 0.0).to_point(),
+    ///     S1Angle::from_degrees(10.0),
+    /// );
+    /// let b = S2Cap::from_center_angle(
+    ///     S2LatLng::from_degrees(0.0, 40.0).to_point(),
+    ///     S1Angle::from_degrees(10.0),
+    /// );
+    /// let union = a.union(&b);
+    /// assert!(union.contains(&a) && union.contains(&b));
+    /// assert!((union.get_radius().degrees() - 30.0).abs() < 1e-10);
+    /// ```
+    pub fn union(&self, other: &S2Cap) -> S2Cap {
+        if self.radius < other.radius {
+            return other.union(self);
+        }
+        if self.is_full() || other.is_empty() {
+            return *self;
+        }
+        // This calculation would be more efficient using S1ChordAngles.
+        let this_radius = self.get_radius().radians();
+        let other_radius = other.get_radius().radians();
+        let distance = S1Angle::from_points(&self.center, &other.center).radians();
+        if this_radius >= distance + other_radius {
+            return *self;
+        }
+        // The center of the result lies on the great circle through the two
+        // centers, halfway between the two far sides of the caps.
+        let result_radius = 0.5 * (distance + this_radius + other_radius);
+        let result_center = interpolate_at_distance(
+            S1Angle::from_radians(0.5 * (distance - this_radius + other_radius)),
+            &self.center,
+            &other.center,
+        );
+        S2Cap::from_center_angle(result_center, S1Angle::from_radians(result_radius))
+    }
+
+    /// Returns a cap that contains the intersection of this cap and "other",
+    /// or None if the caps have no points in common.
+    ///
+    /// Unlike the union, the intersection of two caps is not a cap in general
+    /// (it is usually a lens-shaped region), so the result is a bound. It is
+    /// the smallest bounding cap except when the centers are nearly antipodal,
+    /// in which case the smaller of the two caps is returned. If one cap
+    /// contains the other then the smaller cap is returned exactly.
+    pub fn intersection(&self, other: &S2Cap) -> Option<S2Cap> {
+        if !self.intersects(other) {
+            return None;
+        }
+        if self.contains(other) {
+            return Some(*other);
+        }
+        if other.contains(self) {
+            return Some(*self);
+        }
+        let smaller = if self.radius <= other.radius {
+            *self
+        } else {
+            *other
+        };
+
+        // The boundary circles of the two caps meet at two points P1 and P2
+        // (possibly coincident). Writing P = x*A + y*B + z*N, where A and B
+        // are the cap centers and N is the unit normal of the plane through
+        // them, the conditions P.A = cos(ra) and P.B = cos(rb) determine x and
+        // y, and the midpoint of P1 and P2 is x*A + y*B.
+        let normal = self.center.cross_prod(&other.center);
+        if normal.norm2() <= f64::EPSILON * f64::EPSILON {
+            // The centers are (nearly) antipodal, so the intersection is a
+            // band around the common axis rather than a lens.
+            return Some(smaller);
+        }
+        let cos_a = self.get_radius().cos();
+        let cos_b = other.get_radius().cos();
+        let g = self.center.dot_prod(&other.center);
+        let x = (cos_a - g * cos_b) / (1.0 - g * g);
+        let y = (cos_b - g * cos_a) / (1.0 - g * g);
+        let mid = self.center * x + other.center * y;
+        let z2 = (1.0 - (x * cos_a + y * cos_b)).max(0.0);
+        if mid.norm2() == 0.0 {
+            return Some(smaller);
+        }
+        let lens_cap = S2Cap::from_center_angle(
+            mid.normalize(),
+            S1Angle::from_radians(z2.sqrt().atan2(mid.norm())),
+        );
+
+        // The cap whose diameter is P1P2 contains the whole intersection if
+        // and only if it also contains the two points where the intersection
+        // meets the great circle through the centers. Otherwise the
+        // intersection contains more than half of the boundary of one of the
+        // caps, and the smaller cap is the best bound available.
+        let near_a = interpolate_at_distance(self.get_radius(), &self.center, &other.center);
+        let near_b = interpolate_at_distance(other.get_radius(), &other.center, &self.center);
+        if lens_cap.radius < smaller.radius
+            && lens_cap.contains_point(&near_a)
+            && lens_cap.contains_point(&near_b)
+        {
+            Some(lens_cap)
+        } else {
+            Some(smaller)
+        }
+    }
+
+    /// Returns true if the cap intersects "cell", given that the cap does
+    /// not contain any of the cell vertices (supplied in "vertices", an array
+    /// of length 4).
+    fn intersects_cell(&self, cell: &S2Cell, vertices: &[S2Point; 4]) -> bool {
+        // If the cap is a hemisphere or larger, the cell and the complement of
+        // the cap are both convex. Therefore the cap intersects the cell if and
+        // only if the cap does not contain any cell vertex.
+        if self.radius >= S1ChordAngle::right() {
+            return false;
+        }
+
+        // We need to check for empty caps due to the center check just below.
+        if self.is_empty() {
+            return false;
+        }
+
+        // Optimization: return true if the cell contains the cap center. (This
+        // allows half of the edge checks below to be skipped.)
+        if cell.contains_point(&self.center) {
+            return true;
+        }
+
+        // At this point we know that the cell does not contain the cap center,
+        // and the cap does not contain any cell vertex. The only way that they
+        // can intersect is if the cap intersects the interior of some edge.
+        let sin2_angle = self.radius.sin2();
+        for k in 0..4 {
+            let edge = cell.get_edge_raw(k);
+            let dot = self.center.dot_prod(&edge);
+            if dot > 0.0 {
+                // The center is in the interior half-space defined by the edge. We
+                // don't need to consider these edges, since if the cap intersects
+                // this edge then it also intersects the edge on the opposite side
+                // of the cell (because we know the center is not contained with
+                // the cell).
+                continue;
+            }
+            // The norm2() factor is necessary because "edge" is not normalized.
+            if dot * dot > sin2_angle * edge.norm2() {
+                return false; // Entire cap is on the exterior side of this edge.
+            }
+            // Otherwise, the great circle containing this edge intersects the
+            // interior of the cap. We just need to check whether the point of
+            // closest approach occurs between the two edge endpoints.
+            let dir = edge.cross_prod(&self.center);
+            if dir.dot_prod(&vertices[k as usize]) < 0.0
+                && dir.dot_prod(&vertices[((k + 1) & 3) as usize]) > 0.0
+            {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl S2Region for S2Cap {
+    fn get_cap_bound(&self) -> S2Cap {
+        *self
+    }
+
+    fn get_rect_bound(&self) -> S2LatLngRect {
+        if self.is_empty() {
+            return S2LatLngRect::empty();
+        }
+
+        // Convert the center to a (lat,lng) pair, and compute the cap angle.
+        let center_ll = S2LatLng::from_point(&self.center);
+        let cap_angle = self.radius.radians();
+
+        let mut all_longitudes = false;
+        let mut lat = [0.0; 2];
+        let mut lng = [-PI, PI];
+
+        // Check whether cap includes the south pole.
+        lat[0] = center_ll.lat().radians() - cap_angle;
+        if lat[0] <= -FRAC_PI_2 {
+            lat[0] = -FRAC_PI_2;
+            all_longitudes = true;
+        }
+        // Check whether cap includes the north pole.
+        lat[1] = center_ll.lat().radians() + cap_angle;
+        if lat[1] >= FRAC_PI_2 {
+            lat[1] = FRAC_PI_2;
+            all_longitudes = true;
+        }
+        if !all_longitudes {
+            // Compute the range of longitudes covered by the cap. We use the
+            // law of sines for spherical triangles. Consider the triangle ABC
+            // where A is the north pole, B is the center of the cap, and C is
+            // the point of tangency between the cap boundary and a line of
+            // longitude. Then C is a right angle, and letting a,b,c denote the
+            // sides opposite A,B,C, we have sin(a)/sin(A) = sin(c)/sin(C), or
+            // sin(A) = sin(a)/sin(c). Here "a" is the cap angle, and "c" is the
+            // colatitude (90 degrees minus the latitude). This formula also
+            // works for negative latitudes.
+            //
+            // The formula for sin(a) follows from the relationship
+            // h = 1 - cos(a).
+            let sin_a = self.radius.sin();
+            let sin_c = center_ll.lat().cos();
+            // If sin_a > sin_c then the cap contains a pole up to rounding
+            // error (which also keeps the asin() argument in range), so we
+            // conservatively return the full longitude range.
+            if sin_a <= sin_c {
+                let angle_a = (sin_a / sin_c).asin();
+                lng[0] = remainder(center_ll.lng().radians() - angle_a, 2.0 * PI);
+                lng[1] = remainder(center_ll.lng().radians() + angle_a, 2.0 * PI);
+            }
+        }
+        S2LatLngRect::from_lat_lng(
+            R1Interval::new(lat[0], lat[1]),
+            S1Interval::new(lng[0], lng[1]),
+        )
+    }
+
+    #[cfg(feature = "alloc")]
+    fn get_cell_union_bound(&self, cell_ids: &mut Vec<S2CellId>) {
+        cell_ids.clear();
+        if self.is_empty() {
+            return;
+        }
+
+        // Find the maximum level such that the cap contains at most one cell
+        // vertex and such that S2CellId::append_vertex_neighbors() can be
+        // called.
+        let level = MIN_WIDTH.get_level_for_min_value(self.get_radius().radians()) - 1;
+
+        // If level < 0, then more than three face cells are required.
+        if level < 0 {
+            cell_ids.extend((0..6).map(S2CellId::from_face));
+        } else {
+            // The covering consists of the 4 cells at the given level that
+            // share the cell vertex that is closest to the cap center.
+            S2CellId::from_point(&self.center).append_vertex_neighbors(level, cell_ids);
+        }
+    }
+
+    fn contains_cell(&self, cell: &S2Cell) -> bool {
+        // If the cap does not contain all cell vertices, return false.
+        let vertices: [S2Point; 4] = core::array::from_fn(|k| cell.get_vertex(k as i32));
+        if !vertices.iter().all(|v| self.contains_point(v)) {
+            return false;
+        }
+        // Otherwise, return true if the complement of the cap does not intersect
+        // the cell. (This test is slightly conservative, because technically we
+        // want complement().interior_intersects() here.)
+        !self.complement().intersects_cell(cell, &vertices)
+    }
+
+    fn may_intersect_cell(&self, cell: &S2Cell) -> bool {
+        // If the cap contains any cell vertex, return true.
+        let vertices: [S2Point; 4] = core::array::from_fn(|k| cell.get_vertex(k as i32));
+        if vertices.iter().any(|v| self.contains_point(v)) {
+            return true;
+        }
+        self.intersects_cell(cell, &vertices)
+    }
+
+    /// The point "p" should be a unit-length vector.
+    fn contains_point(&self, point: &S2Point) -> bool {
+        debug_assert!(is_unit_length(point));
+        S1ChordAngle::from_points(&self.center, point) <= self.radius
+    }
+}
+
+/// Formats the cap as "center=lat,lng radius=deg", with the center and the
+/// radius in degrees. The radius of an empty cap is negative.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::{
+///     s1::S1Angle,
+///     s2::{S2Cap, S2LatLng},
+/// };
+///
+/// let center = S2LatLng::from_degrees(10.0, 20.0).to_point();
+/// let cap = S2Cap::from_center_angle(center, S1Angle::from_degrees(5.0));
+/// assert_eq!(
+///     cap.to_string(),
+///     "center=10.000000,20.000000 radius=5.000000"
+/// );
+/// ```
+impl fmt::Display for S2Cap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "center={} radius={:.6}",
+            S2LatLng::from_point(&self.center),
+            self.get_radius().degrees()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec};
+    use core::f64::consts::FRAC_PI_4;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::s2::s2testing::random_point;
+
+    // Maximum allowable error for latitudes and longitudes measured in
+    // degrees.
+    const DEGREE_EPS: f64 = 1e-13;
+
+    fn get_lat_lng_point(lat_degrees: f64, lng_degrees: f64) -> S2Point {
+        S2LatLng::from_degrees(lat_degrees, lng_degrees).to_point()
+    }
+
+    fn assert_near(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() <= DEGREE_EPS,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_get_rect_bound() {
+        // Empty and full caps.
+        assert!(S2Cap::empty().get_rect_bound().is_empty());
+        assert!(S2Cap::full().get_rect_bound().is_full());
+
+        // Cap that includes the south pole.
+        let rect =
+            S2Cap::from_center_angle(get_lat_lng_point(-45.0, 57.0), S1Angle::from_degrees(50.0))
+                .get_rect_bound();
+        assert_near(rect.lat_lo().degrees(), -90.0);
+        assert_near(rect.lat_hi().degrees(), 5.0);
+        assert!(rect.lng().is_full());
+
+        // Cap that is tangent to the north pole.
+        let rect = S2Cap::from_center_angle(
+            S2Point::new(1.0, 0.0, 1.0).normalize(),
+            S1Angle::from_radians(FRAC_PI_4 + 1e-16),
+        )
+        .get_rect_bound();
+        assert_near(rect.lat().lo(), 0.0);
+        assert_near(rect.lat().hi(), FRAC_PI_2);
+        assert!(rect.lng().is_full());
+
+        let rect = S2Cap::from_center_angle(
+            S2Point::new(1.0, 0.0, 1.0).normalize(),
+            S1Angle::from_degrees(45.0 + 5e-15),
+        )
+        .get_rect_bound();
+        assert_near(rect.lat_lo().degrees(), 0.0);
+        assert_near(rect.lat_hi().degrees(), 90.0);
+        assert!(rect.lng().is_full());
+
+        // The eastern hemisphere.
+        let rect = S2Cap::from_center_angle(
+            S2Point::new(0.0, 1.0, 0.0),
+            S1Angle::from_radians(FRAC_PI_2 + 2e-16),
+        )
+        .get_rect_bound();
+        assert_near(rect.lat_lo().degrees(), -90.0);
+        assert_near(rect.lat_hi().degrees(), 90.0);
+        assert!(rect.lng().is_full());
+
+        // A cap centered on the equator.
+        let rect =
+            S2Cap::from_center_angle(get_lat_lng_point(0.0, 50.0), S1Angle::from_degrees(20.0))
+                .get_rect_bound();
+        assert_near(rect.lat_lo().degrees(), -20.0);
+        assert_near(rect.lat_hi().degrees(), 20.0);
+        assert_near(rect.lng_lo().degrees(), 30.0);
+        assert_near(rect.lng_hi().degrees(), 70.0);
+
+        // A cap centered on the north pole.
+        let rect =
+            S2Cap::from_center_angle(get_lat_lng_point(90.0, 123.0), S1Angle::from_degrees(10.0))
+                .get_rect_bound();
+        assert_near(rect.lat_lo().degrees(), 80.0);
+        assert_near(rect.lat_hi().degrees(), 90.0);
+        assert!(rect.lng().is_full());
+
+        // A cap that straddles the 180 degree meridian.
+        let rect =
+            S2Cap::from_center_angle(get_lat_lng_point(0.0, 175.0), S1Angle::from_degrees(10.0))
+                .get_rect_bound();
+        assert!(rect.is_inverted());
+        assert_near(rect.lng_lo().degrees(), 165.0);
+        assert_near(rect.lng_hi().degrees(), -175.0);
+
+        // A cap centered on the south pole.
+        let rect =
+            S2Cap::from_center_angle(get_lat_lng_point(-90.0, 0.0), S1Angle::from_degrees(10.0))
+                .get_rect_bound();
+        assert_near(rect.lat_lo().degrees(), -90.0);
+        assert_near(rect.lat_hi().degrees(), -80.0);
+        assert!(rect.lng().is_full());
+
+        // A cap that is just large enough to contain the north pole.
+        let rect =
+            S2Cap::from_center_angle(get_lat_lng_point(89.0, 0.0), S1Angle::from_degrees(1.0))
+                .get_rect_bound();
+        assert_eq!(rect.lat_hi().radians(), FRAC_PI_2);
+        assert!(rect.lng().is_full());
+
+        // Tiny caps at high latitudes span a longitude range of
+        // 2 * asin(sin(radius) / cos(lat)), which is much wider than the radius.
+        for (lat, radius) in [(89.0, 1e-3), (89.99, 1e-5), (-89.999, 1e-7), (60.0, 1e-10)] {
+            let cap = S2Cap::from_center_angle(
+                get_lat_lng_point(lat, 20.0),
+                S1Angle::from_degrees(radius),
+            );
+            let rect = cap.get_rect_bound();
+            assert!(!rect.lng().is_full());
+            assert_near(rect.lat_lo().degrees(), lat - radius);
+            assert_near(rect.lat_hi().degrees(), lat + radius);
+            let half_width = (radius.to_radians().sin() / lat.to_radians().cos()).asin();
+            assert!(
+                (rect.lng().get_length() - 2.0 * half_width).abs() <= 1e-13 * half_width.max(1.0)
+            );
+            assert!(rect.lng().get_length() > 2.0 * radius.to_radians());
+        }
+    }
+
+    #[test]
+    fn test_get_rect_bound_random() {
+        // Every point contained by the cap must be contained by its bound, and
+        // for caps that stay away from the poles the bound is not much larger
+        // than the cap itself.
+        let mut rng = StdRng::seed_from_u64(44);
+        for _ in 0..10000 {
+            let center = random_point(&mut rng);
+            let radius = S1Angle::from_radians(PI * 2f64.powf(-rng.gen_range(0.0..40.0)));
+            let cap = S2Cap::from_center_angle(center, radius);
+            let rect = cap.get_rect_bound();
+
+            let x = center.ortho();
+            let y = center.cross_prod(&x);
+            for i in 0..10 {
+                let theta = rng.gen_range(0.0..2.0 * PI);
+                let r = if i % 2 == 0 {
+                    radius.radians()
+                } else {
+                    radius.radians() * rng.gen_range(0.0..1.0)
+                };
+                let p =
+                    (center * r.cos() + (x * theta.cos() + y * theta.sin()) * r.sin()).normalize();
+                if cap.contains_point(&p) {
+                    assert!(rect.contains_point(&p), "{:?} {:?}", cap, p);
+                }
+            }
+
+            let center_lat = S2LatLng::from_point(&center).lat().radians();
+            if center_lat.abs() + radius.radians() < PI / 3.0 && radius.radians() > 1e-6 {
+                assert!(rect.area() <= 3.0 * cap.get_area(), "{:?}", cap);
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_rect_bound_contains_cap() {
+        // Every point inside a cap (including its boundary) must be contained
+        // by the cap's bounding rectangle.
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..200 {
+            let center = S2Point::new(
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+            )
+            .normalize();
+            let radius = S1Angle::from_radians(rng.gen_range(0.0..PI));
+            let cap = S2Cap::from_center_angle(center, radius);
+            let rect = cap.get_rect_bound();
+            assert!(rect.contains_point(&center));
+            let u = center.ortho();
+            let v = center.cross_prod(&u);
+            for k in 0..32 {
+                // A point on the boundary, pulled slightly inside the cap.
+                let theta = 2.0 * PI * k as f64 / 32.0;
+                let r = radius.radians() * (1.0 - 1e-9);
+                let dir = u * theta.cos() + v * theta.sin();
+                let p = (center * r.cos() + dir * r.sin()).normalize();
+                assert!(cap.contains_point(&p));
+                assert!(rect.contains_point(&p), "{:?} {:?}", cap, p);
+            }
+        }
+    }
+
+    #[test]
+    fn test_expanded() {
+        assert!(S2Cap::empty()
+            .expanded(S1Angle::from_radians(2.0))
+            .is_empty());
+        assert!(S2Cap::full().expanded(S1Angle::from_radians(2.0)).is_full());
+
+        let cap50 =
+            S2Cap::from_center_angle(S2Point::new(1.0, 0.0, 0.0), S1Angle::from_degrees(50.0));
+        let cap51 =
+            S2Cap::from_center_angle(S2Point::new(1.0, 0.0, 0.0), S1Angle::from_degrees(51.0));
+        let same = cap50.expanded(S1Angle::from_radians(0.0));
+        assert_eq!(same.center(), cap50.center());
+        assert_eq!(same.radius(), cap50.radius());
+        assert_near(
+            cap50
+                .expanded(S1Angle::from_degrees(1.0))
+                .get_radius()
+                .degrees(),
+            cap51.get_radius().degrees(),
+        );
+        assert!(!cap50.expanded(S1Angle::from_degrees(129.99)).is_full());
+        assert!(cap50.expanded(S1Angle::from_degrees(130.01)).is_full());
+
+        let mut rng = StdRng::seed_from_u64(50);
+        for _ in 0..100 {
+            let center = random_point(&mut rng);
+            let radius = S1Angle::from_radians(rng.gen_range(0.0..1.0));
+            let distance = S1Angle::from_radians(rng.gen_range(0.0..1.0));
+            let cap = S2Cap::from_center_angle(center, radius);
+            let expanded = cap.expanded(distance);
+            assert!(expanded.contains(&cap));
+
+            // Every point of the original cap (including its boundary) is in
+            // the expanded cap, and so is every point within "distance" of it.
+            let x = center.ortho();
+            let y = center.cross_prod(&x);
+            for _ in 0..10 {
+                let theta = rng.gen_range(0.0..2.0 * PI);
+                let dir = x * theta.cos() + y * theta.sin();
+                let r = radius.radians();
+                let p = (center * r.cos() + dir * r.sin()).normalize();
+                if cap.contains_point(&p) {
+                    assert!(expanded.contains_point(&p));
+                }
+                let r = (radius.radians() + distance.radians()) * (1.0 - 1e-12);
+                let q = (center * r.cos() + dir * r.sin()).normalize();
+                assert!(expanded.contains_point(&q));
+            }
+
+            // The cell union bound of the expanded cap covers its center.
+            let mut cell_ids = Vec::new();
+            expanded.get_cell_union_bound(&mut cell_ids);
+            assert!(cell_ids.len() <= 6);
+            assert!(cell_ids
+                .iter()
+                .any(|&id| S2Cell::new(id).contains_point(&center)));
+        }
+    }
+
+    #[test]
+    fn test_expanded_by_distance() {
+        let distance = S1Angle::from_degrees(10.0);
+        assert!(S2Cap::empty().expanded_by_distance(distance).is_empty());
+        assert!(S2Cap::empty()
+            .expanded_by_distance(S1Angle::from_radians(PI))
+            .is_empty());
+        assert!(S2Cap::full().expanded_by_distance(distance).is_full());
+        assert!(S2Cap::full()
+            .expanded_by_distance(S1Angle::zero())
+            .is_full());
+
+        // A singleton cap is not empty, and grows to a cap of the given
+        // radius.
+        let center = S2Point::new(0.0, 0.0, 1.0);
+        let point = S2Cap::from_point(center);
+        assert!(!point.is_empty());
+        let expanded = point.expanded_by_distance(distance);
+        assert_eq!(expanded.center(), center);
+        assert_eq!(
+            expanded.radius(),
+            S2Cap::from_center_angle(center, distance).radius()
+        );
+
+        // The radius is min(radius + distance, Pi).
+        let cap170 = S2Cap::from_center_angle(center, S1Angle::from_degrees(170.0));
+        let expanded = cap170.expanded_by_distance(distance);
+        assert!(expanded.is_full());
+        assert_eq!(expanded.get_radius().radians(), PI);
+        assert!(!cap170
+            .expanded_by_distance(S1Angle::from_degrees(9.99))
+            .is_full());
+
+        let mut rng = StdRng::seed_from_u64(71);
+        for _ in 0..100 {
+            let center = random_point(&mut rng);
+            let radius = S1Angle::from_radians(rng.gen_range(0.0..PI));
+            let distance = S1Angle::from_radians(rng.gen_range(0.0..PI));
+            let cap = S2Cap::from_center_angle(center, radius);
+            let expanded = cap.expanded_by_distance(distance);
+            assert!(expanded.contains(&cap));
+            assert!(
+                (expanded.get_radius().radians() - (radius.radians() + distance.radians()).min(PI))
+                    .abs()
+                    <= 1e-13
+            );
+            assert_eq!(expanded.center(), cap.center());
+        }
+    }
+
+    #[test]
+    fn test_union() {
+        // Two caps which have the same center but one has a larger radius.
+        let a = S2Cap::from_center_angle(get_lat_lng_point(50.0, 10.0), S1Angle::from_degrees(0.2));
+        let b = S2Cap::from_center_angle(get_lat_lng_point(50.0, 10.0), S1Angle::from_degrees(0.3));
+        assert!(b.contains(&a));
+        assert_eq!(b.union(&a).radius(), b.radius());
+        assert_eq!(a.union(&b).radius(), b.radius());
+
+        // Two caps where one is the full cap or the empty cap.
+        assert!(a.union(&S2Cap::full()).is_full());
+        assert_eq!(a.union(&S2Cap::empty()).radius(), a.radius());
+
+        // Two disjoint caps whose centers are 3 degrees apart along the
+        // equator. The union is centered halfway between them, with a radius
+        // of half the distance between the centers plus the common radius.
+        let e = S2Cap::from_center_angle(get_lat_lng_point(0.0, 0.0), S1Angle::from_degrees(1.0));
+        let f = S2Cap::from_center_angle(get_lat_lng_point(0.0, 3.0), S1Angle::from_degrees(1.0));
+        let ef = e.union(&f);
+        assert!((ef.get_radius().degrees() - 2.5).abs() < 1e-10);
+        let expected_center = get_lat_lng_point(0.0, 1.5);
+        assert!(S1Angle::from_points(&ef.center(), &expected_center).degrees() < 1e-10);
+
+        // Two caps with the same center, where the union is the larger one.
+        let c = S2Cap::from_center_angle(get_lat_lng_point(51.0, 11.0), S1Angle::from_degrees(1.5));
+        let d = S2Cap::from_center_angle(get_lat_lng_point(51.0, 11.0), S1Angle::from_degrees(0.1));
+        assert!(c.union(&d).contains(&c) && c.union(&d).contains(&d));
+
+        // Two caps that are far apart, whose union is full.
+        let g = S2Cap::from_center_angle(get_lat_lng_point(0.0, 0.0), S1Angle::from_degrees(100.0));
+        let h =
+            S2Cap::from_center_angle(get_lat_lng_point(0.0, 180.0), S1Angle::from_degrees(100.0));
+        assert!(g.union(&h).is_full());
+
+        // The union of random caps contains both caps.
+        let mut rng = StdRng::seed_from_u64(2);
+        for _ in 0..100 {
+            let x = S2Cap::from_center_angle(
+                random_point(&mut rng),
+                S1Angle::from_radians(rng.gen_range(0.0..1.5)),
+            );
+            let y = S2Cap::from_center_angle(
+                random_point(&mut rng),
+                S1Angle::from_radians(rng.gen_range(0.0..1.5)),
+            );
+            let union = x.union(&y);
+            let slack = S2Cap::from_center_angle(
+                union.center(),
+                S1Angle::from_radians(union.get_radius().radians() + 1e-13),
+            );
+            assert!(slack.contains(&x) && slack.contains(&y));
+        }
+    }
+
+    #[test]
+    fn test_intersection() {
+        let a = S2Cap::from_center_angle(get_lat_lng_point(0.0, 0.0), S1Angle::from_degrees(10.0));
+        let b = S2Cap::from_center_angle(get_lat_lng_point(0.0, 30.0), S1Angle::from_degrees(10.0));
+        assert!(a.intersection(&b).is_none());
+        assert!(a.intersection(&S2Cap::empty()).is_none());
+        assert_eq!(a.intersection(&S2Cap::full()).unwrap().radius(), a.radius());
+
+        // Nested caps.
+        let small =
+            S2Cap::from_center_angle(get_lat_lng_point(1.0, 1.0), S1Angle::from_degrees(2.0));
+        assert_eq!(a.intersection(&small).unwrap().radius(), small.radius());
+        assert_eq!(small.intersection(&a).unwrap().radius(), small.radius());
+
+        // Two equal caps overlapping symmetrically: the boundary circles cross
+        // on the meridian halfway between the centers.
+        let c = S2Cap::from_center_angle(get_lat_lng_point(0.0, 15.0), S1Angle::from_degrees(10.0));
+        let lens = a.intersection(&c).unwrap();
+        assert!(
+            S1Angle::from_points(&lens.center(), &get_lat_lng_point(0.0, 7.5)).degrees() < 1e-10
+        );
+        assert!(lens.radius() < a.radius());
+
+        // Random caps: the result bounds sample points in the intersection.
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..100 {
+            let x = S2Cap::from_center_angle(
+                random_point(&mut rng),
+                S1Angle::from_radians(rng.gen_range(0.0..2.0)),
+            );
+            let y = S2Cap::from_center_angle(
+                random_point(&mut rng),
+                S1Angle::from_radians(rng.gen_range(0.0..2.0)),
+            );
+            let Some(result) = x.intersection(&y) else {
+                assert!(!x.intersects(&y));
+                continue;
+            };
+            assert!(result.radius() <= x.radius() && result.radius() <= y.radius());
+            let slack = S2Cap::from_center_angle(
+                result.center(),
+                S1Angle::from_radians(result.get_radius().radians() + 1e-12),
+            );
+            for _ in 0..200 {
+                let p = random_point(&mut rng);
+                if x.contains_point(&p) && y.contains_point(&p) {
+                    assert!(slack.contains_point(&p), "{:?} {:?} {:?}", x, y, p);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            S2Cap::full().to_string(),
+            "center=0.000000,0.000000 radius=180.000000"
+        );
+        // The empty cap has a radius of -1 radian.
+        assert_eq!(
+            S2Cap::empty().to_string(),
+            "center=0.000000,0.000000 radius=-57.295780"
+        );
+        let cap = S2Cap::from_point(S2Point::new(0.0, 0.0, -1.0));
+        assert_eq!(
+            cap.to_string(),
+            "center=-90.000000,0.000000 radius=0.000000"
+        );
+    }
+
+    #[test]
+    fn test_area_perimeter_and_centroid() {
+        let north = S2Point::new(0.0, 0.0, 1.0);
+        assert_eq!(S2Cap::empty().get_area(), 0.0);
+        assert_eq!(S2Cap::from_point(north).get_area(), 0.0);
+        assert_eq!(S2Cap::full().get_area(), 4.0 * PI);
+        let hemisphere = S2Cap::from_center_height(north, 1.0);
+        assert_eq!(hemisphere.get_area(), 2.0 * PI);
+
+        assert_eq!(S2Cap::empty().get_perimeter(), S1Angle::zero());
+        assert_eq!(S2Cap::full().get_perimeter(), S1Angle::zero());
+        assert!((hemisphere.get_perimeter().radians() - 2.0 * PI).abs() <= 1e-15);
+        let cap = S2Cap::from_center_angle(north, S1Angle::from_degrees(30.0));
+        assert!((cap.get_perimeter().radians() - PI).abs() <= 1e-15);
+
+        // The centroid of a hemisphere is at half the radius, scaled by its
+        // area; the full sphere and empty caps have zero centroid.
+        assert_eq!(S2Cap::empty().get_centroid(), S2Point::zero());
+        assert_eq!(S2Cap::full().get_centroid(), S2Point::zero());
+        assert!((hemisphere.get_centroid() - north * PI).norm() <= 1e-15);
+
+        // A cap and its complement together make up the full sphere.
+        let mut rng = StdRng::seed_from_u64(18);
+        for _ in 0..100 {
+            let center =
+                get_lat_lng_point(rng.gen_range(-90.0..90.0), rng.gen_range(-180.0..180.0));
+            let cap = S2Cap::from_center_height(center, rng.gen_range(0.0..2.0));
+            let complement = cap.complement();
+            assert!((cap.get_area() + complement.get_area() - 4.0 * PI).abs() <= 1e-14);
+            assert!((cap.get_centroid() + complement.get_centroid()).norm() <= 1e-14);
+        }
+    }
+
+    #[test]
+    fn test_get_cell_union_bound() {
+        let mut cell_ids = vec![S2CellId::from_face(0)];
+        S2Cap::empty().get_cell_union_bound(&mut cell_ids);
+        assert!(cell_ids.is_empty());
+        S2Cap::full().get_cell_union_bound(&mut cell_ids);
+        assert_eq!(
+            cell_ids,
+            (0..6).map(S2CellId::from_face).collect::<Vec<_>>()
+        );
+
+        let mut rng = StdRng::seed_from_u64(23);
+        for _ in 0..200 {
+            let center = S2Point::new(
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+            )
+            .normalize();
+            let radius = PI * 2f64.powf(-rng.gen_range(0.0..30.0));
+            let radius_angle = S1Angle::from_radians(radius);
+            S2Cap::from_center_angle(center, radius_angle).get_cell_union_bound(&mut cell_ids);
+
+            // Unless the cap is very large, the covering consists of at most 4
+            // cells at the same level, the first of which contains the center.
+            if cell_ids.len() == 6 {
+                assert!(cell_ids.iter().all(|id| id.is_face()));
+            } else {
+                assert!(cell_ids.len() <= 4);
+                let level = cell_ids[0].level();
+                assert!(cell_ids.iter().all(|id| id.level() == level));
+                assert!(cell_ids[0].contains(&S2CellId::from_point(&center)));
+            }
+
+            // Points sampled from the cap, including its boundary, are all
+            // contained by the covering.
+            for _ in 0..20 {
+                let target = S2Point::new(
+                    rng.gen_range(-1.0..1.0),
+                    rng.gen_range(-1.0..1.0),
+                    rng.gen_range(-1.0..1.0),
+                )
+                .normalize();
+                let dist = S1Angle::from_radians(radius * rng.gen_range(0.0..=1.0));
+                let p = interpolate_at_distance(dist, &center, &target);
+                assert!(cell_ids
+                    .iter()
+                    .any(|id| S2Cell::new(*id).contains_point(&p)));
+            }
+
+            // Shrinking the cap never makes the covering coarser.
+            let mut smaller = Vec::new();
+            S2Cap::from_center_angle(center, S1Angle::from_radians(0.5 * radius))
+                .get_cell_union_bound(&mut smaller);
+            assert!(smaller[0].level() >= cell_ids[0].level());
+        }
+
+        // Small caps are covered at deeper levels.
+        let center = S2Point::new(1.0, 0.1, -0.2).normalize();
+        let mut levels = Vec::new();
+        for radius in [1.0, 1e-1, 1e-2, 1e-3, 1e-4] {
+            S2Cap::from_center_angle(center, S1Angle::from_radians(radius))
+                .get_cell_union_bound(&mut cell_ids);
+            levels.push(cell_ids[0].level());
+        }
+        assert!(levels.windows(2).all(|w| w[0] < w[1]), "{:?}", levels);
+    }
+}
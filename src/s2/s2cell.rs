@@ -15,9 +15,28 @@
 
 // Original Author: ericv@google.com (Eric Veach)
 
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::{
+    f64::consts::{FRAC_PI_2, FRAC_PI_4},
+    fmt,
+};
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
 use crate::{
+    r1::R1Interval,
     r2::R2Rect,
-    s2::{face_uv_to_xyz, S2CellId, S2Point},
+    s1::{S1ChordAngle, S1Interval},
+    s2::{
+        area, face_uv_to_xyz, face_uv_to_xyz_from_r2point, face_xyz_to_uv, face_xyz_to_uvw,
+        get_u_norm, get_uvw_axis, get_v_norm,
+        internal::{POS_TO_IJ, POS_TO_ORIENTATION},
+        s2metrics::AVG_AREA,
+        update_max_distance, update_min_distance, IjCoord, S2Cap, S2CellId, S2LatLng, S2LatLngRect,
+        S2Point, S2Region, MAX_XYZ_TO_UV_ERROR,
+    },
 };
 
 /// An S2Cell is an S2Region object that represents a cell. Unlike S2CellId's,
@@ -62,8 +81,16 @@ impl S2Cell {
         }
     }
 
+    /// Returns the cell corresponding to the given S2 cube face.
     pub fn from_face(face: i32) -> Self {
-        todo!()
+        S2Cell::new(S2CellId::from_face(face))
+    }
+
+    /// Returns a cell given its face (range 0..5), Hilbert curve position
+    /// within that face (an unsigned integer with S2CellId::POS_BITS bits),
+    /// and level (range 0..MAX_LEVEL).
+    pub fn from_face_pos_level(face: i32, pos: u64, level: i32) -> Self {
+        S2Cell::new(S2CellId::from_face_pos_level(face, pos, level))
     }
 
     pub fn id(&self) -> S2CellId {
@@ -88,14 +115,55 @@ impl S2Cell {
 
     /// This is equivalent to the S2CellId method, but has a more efficient
     /// implementation since the level has been precomputed.
-    pub fn get_size_ij(&self) -> i32 {
+    pub fn get_size_ij(&self) -> IjCoord {
         S2CellId::get_size_ij_at_level(self.level())
     }
 
     /// This is equivalent to the S2CellId method, but has a more efficient
     /// implementation since the level has been precomputed.
     pub fn get_size_st(&self) -> f64 {
-        todo!()
+        S2CellId::get_size_st_at_level(self.level())
+    }
+
+    /// Returns the average area for cells at the given level.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use core::f64::consts::PI;
+    /// use s2shell::s2::S2Cell;
+    ///
+    /// assert_eq!(S2Cell::get_average_area(0), 4.0 * PI / 6.0);
+    /// assert_eq!(S2Cell::get_average_area(1), 4.0 * PI / 24.0);
+    /// ```
+    pub fn get_average_area(level: i32) -> f64 {
+        AVG_AREA.get_value(level)
+    }
+
+    /// Returns the area of this cell as accurately as possible.  This method is
+    /// more expensive but it is accurate to 6 digits of precision even for leaf
+    /// cells (whose area is approximately 1e-18).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{S2Cell, S2CellId};
+    ///
+    /// let cell = S2Cell::new(S2CellId::from_token("89c259"));
+    /// let ratio = cell.exact_area() / S2Cell::get_average_area(cell.level());
+    /// assert!(ratio > 0.5 && ratio < 2.0);
+    /// ```
+    pub fn exact_area(&self) -> f64 {
+        // There is a straightforward mathematical formula for the exact surface
+        // area (based on 4 calls to asin), but as the cell size gets small this
+        // formula has too much cancellation error.  So instead we compute the
+        // area as the sum of two triangles (which is very accurate at all cell
+        // levels).
+        let v0 = self.get_vertex(0);
+        let v1 = self.get_vertex(1);
+        let v2 = self.get_vertex(2);
+        let v3 = self.get_vertex(3);
+        area(&v0, &v1, &v2) + area(&v0, &v2, &v3)
     }
 
     /// Returns the k-th vertex of the cell (k = 0,1,2,3).  Vertices are returned
@@ -103,11 +171,1237 @@ impl S2Cell {
     /// plane).  The points returned by GetVertexRaw are not normalized.
     /// For convenience, the argument is reduced modulo 4 to the range [0..3].
     pub fn get_vertex(&self, k: i32) -> S2Point {
-        todo!()
+        self.get_vertex_raw(k).normalize()
+    }
+
+    pub fn get_vertex_raw(&self, k: i32) -> S2Point {
+        face_uv_to_xyz_from_r2point(self.face, &self.uv.get_vertex(k))
+    }
+
+    /// Returns the boundary of the cell as a closed ring of unit-length
+    /// points, with "points_per_edge" additional points interpolated along
+    /// each edge. The ring starts at get_vertex(0), visits the vertices in CCW
+    /// order, and ends with a copy of its first point, so it has
+    /// 4 * (points_per_edge + 1) + 1 points. This is useful for rendering
+    /// cells or for approximate containment tests by sampling.
+    ///
+    /// The intermediate points are spaced evenly in (u,v)-space. Since lines
+    /// of constant u or v are great circles, they lie on the cell edges.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{S2Cell, S2CellId};
+    ///
+    /// let cell = S2Cell::new(S2CellId::from_face(2).child(1));
+    /// let boundary = cell.get_boundary(3);
+    /// assert_eq!(boundary.len(), 17);
+    /// assert_eq!(boundary[0], cell.get_vertex(0));
+    /// assert_eq!(boundary[4], cell.get_vertex(1));
+    /// assert_eq!(boundary.first(), boundary.last());
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn get_boundary(&self, points_per_edge: usize) -> Vec<S2Point> {
+        let num_segments = points_per_edge + 1;
+        let mut boundary = Vec::with_capacity(4 * num_segments + 1);
+        for k in 0..4 {
+            let a = self.uv.get_vertex(k);
+            let b = self.uv.get_vertex(k + 1);
+            for i in 0..num_segments {
+                // Edges 0 and 2 have constant v, and edges 1 and 3 have constant
+                // u. Only the varying coordinate is interpolated, so that every
+                // point lies exactly on the edge, and i == 0 yields the vertex.
+                let t = i as f64 / num_segments as f64;
+                let (u, v) = if k % 2 == 0 {
+                    ((1.0 - t) * a.x() + t * b.x(), a.y())
+                } else {
+                    (a.x(), (1.0 - t) * a.y() + t * b.y())
+                };
+                boundary.push(face_uv_to_xyz(self.face, u, v).normalize());
+            }
+        }
+        boundary.push(boundary[0]);
+        boundary
+    }
+
+    /// Returns the inward-facing normal of the great circle passing through the
+    /// edge from vertex k to vertex k+1 (mod 4). The normals returned by
+    /// `get_edge_raw` are not necessarily unit length. For convenience, the
+    /// argument is reduced modulo 4 to the range [0..3].
+    pub fn get_edge(&self, k: i32) -> S2Point {
+        self.get_edge_raw(k).normalize()
+    }
+
+    pub fn get_edge_raw(&self, k: i32) -> S2Point {
+        match k & 3 {
+            0 => get_v_norm(self.face, self.uv[1][0]),  // Bottom
+            1 => get_u_norm(self.face, self.uv[0][1]),  // Right
+            2 => -get_v_norm(self.face, self.uv[1][1]), // Top
+            _ => -get_u_norm(self.face, self.uv[0][0]), // Left
+        }
+    }
+
+    /// Returns the four direct children of this cell in traversal order (the
+    /// order defined by S2CellId::child_begin()). This is equivalent to
+    /// calling S2Cell::new() on each child id, but is faster because the
+    /// children's (u,v) bounds are computed by splitting this cell's bounds
+    /// at its center. Requires that this is not a leaf cell.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::S2Cell;
+    ///
+    /// let face = S2Cell::from_face(2);
+    /// for (pos, child) in face.subcells().iter().enumerate() {
+    ///     assert_eq!(child.id(), face.id().child(pos as i32));
+    ///     assert_eq!(child.level(), 1);
+    /// }
+    /// ```
+    pub fn subcells(&self) -> [S2Cell; 4] {
+        debug_assert!(!self.is_leaf());
+
+        // Compute the cell midpoint in uv-space.
+        let uv_mid = self.id.get_center_uv();
+
+        // Create four children with the appropriate bounds.
+        let mut id = self.id.child_begin();
+        core::array::from_fn(|pos| {
+            // We want to split the cell in half in "u" and "v". To decide which
+            // side to set equal to the midpoint value, we look at the cell's
+            // (i,j) position within its parent. The index for "i" is in bit 1
+            // of ij.
+            let ij = POS_TO_IJ[self.orientation as usize][pos];
+            let i = (ij >> 1) as usize;
+            let j = (ij & 1) as usize;
+            let mut uv = R2Rect::default();
+            uv[0][i] = self.uv[0][i];
+            uv[0][1 - i] = uv_mid[0];
+            uv[1][j] = self.uv[1][j];
+            uv[1][1 - j] = uv_mid[1];
+            let child = S2Cell {
+                id,
+                face: self.face,
+                level: self.level + 1,
+                orientation: self.orientation ^ POS_TO_ORIENTATION[pos],
+                uv,
+            };
+            id = id.next();
+            child
+        })
+    }
+
+    /// Returns the direction vector corresponding to the center in (s,t)-space
+    /// of the given cell. This is the point at which the cell is divided into
+    /// four subcells; it is not necessarily the centroid of the cell in
+    /// (u,v)-space or (x,y,z)-space. The point returned by get_center_raw is
+    /// not necessarily unit length.
+    pub fn get_center(&self) -> S2Point {
+        self.get_center_raw().normalize()
+    }
+
+    pub fn get_center_raw(&self) -> S2Point {
+        self.id.to_point_raw()
+    }
+
+    /// Returns a bounding latitude-longitude rectangle that contains the cell.
+    /// The bound is conservative, i.e. it may be slightly larger than the
+    /// smallest possible bounding rectangle, but only by rounding error: for
+    /// cells below level 0 the extreme latitudes and longitudes are attained
+    /// at the vertices, and the face cells are handled explicitly (the
+    /// equatorial faces bulge to +/-45 degrees latitude at their edge
+    /// midpoints).
+    pub fn get_rect_bound(&self) -> S2LatLngRect {
+        if self.level > 0 {
+            // Except for cells at level 0, the latitude and longitude extremes
+            // are attained at the vertices. Furthermore, the latitude range is
+            // determined by one pair of diagonally opposite vertices and the
+            // longitude range is determined by the other pair.
+            //
+            // We first determine which corner (i,j) of the cell has the largest
+            // absolute latitude. To maximize latitude, we want to find the
+            // point in the cell that has the largest absolute z-coordinate and
+            // the smallest absolute x- and y-coordinates. To do this we look at
+            // each coordinate (u and v), and determine whether we want to
+            // minimize or maximize that coordinate based on the axis direction
+            // and the cell's (u,v) quadrant.
+            let u = self.uv[0][0] + self.uv[0][1];
+            let v = self.uv[1][0] + self.uv[1][1];
+            let i = if get_uvw_axis(self.face, 0).z() == 0.0 {
+                (u < 0.0) as usize
+            } else {
+                (u > 0.0) as usize
+            };
+            let j = if get_uvw_axis(self.face, 1).z() == 0.0 {
+                (v < 0.0) as usize
+            } else {
+                (v > 0.0) as usize
+            };
+            let lat = R1Interval::from_point_pair(
+                self.get_latitude(i, j),
+                self.get_latitude(1 - i, 1 - j),
+            );
+            let lng = S1Interval::from_point_pair(
+                self.get_longitude(i, 1 - j),
+                self.get_longitude(1 - i, j),
+            );
+
+            // We grow the bounds slightly to make sure that the bounding
+            // rectangle contains S2LatLng(P) for any point P inside the loop L
+            // defined by the four *normalized* vertices. Note that normalization
+            // of a vector can change its direction by up to 0.5 * DBL_EPSILON
+            // radians, and it is not enough just to add normalize() calls to
+            // the code above because the latitude/longitude ranges are not
+            // necessarily determined by diagonally opposite vertex pairs after
+            // normalization.
+            //
+            // We would like to bound the amount by which the latitude/longitude
+            // of a contained point P can exceed the bounds computed above. In
+            // the case of longitude, the normalization error can change the
+            // direction of rounding leading to a maximum difference in
+            // longitude of 2 * DBL_EPSILON. Similarly, the maximum difference
+            // in latitude is DBL_EPSILON (since the extreme latitude can be
+            // attained at an edge interior), for a total of 2 * DBL_EPSILON in
+            // both directions.
+            return S2LatLngRect::from_lat_lng(lat, lng)
+                .expanded(&S2LatLng::from_radians(
+                    2.0 * f64::EPSILON,
+                    2.0 * f64::EPSILON,
+                ))
+                .polar_closure();
+        }
+
+        // The 4 cells around the equator extend to +/-45 degrees latitude at
+        // the midpoints of their top and bottom edges. The two cells covering
+        // the poles extend down to +/-35.26 degrees at their vertices. The
+        // maximum error in this calculation is 0.5 * DBL_EPSILON.
+        let pole_min_lat = (1.0_f64 / 3.0).sqrt().asin() - 0.5 * f64::EPSILON;
+
+        let bound = match self.face {
+            0 => S2LatLngRect::from_lat_lng(
+                R1Interval::new(-FRAC_PI_4, FRAC_PI_4),
+                S1Interval::new(-FRAC_PI_4, FRAC_PI_4),
+            ),
+            1 => S2LatLngRect::from_lat_lng(
+                R1Interval::new(-FRAC_PI_4, FRAC_PI_4),
+                S1Interval::new(FRAC_PI_4, 3.0 * FRAC_PI_4),
+            ),
+            2 => S2LatLngRect::from_lat_lng(
+                R1Interval::new(pole_min_lat, FRAC_PI_2),
+                S1Interval::full(),
+            ),
+            3 => S2LatLngRect::from_lat_lng(
+                R1Interval::new(-FRAC_PI_4, FRAC_PI_4),
+                S1Interval::new(3.0 * FRAC_PI_4, -3.0 * FRAC_PI_4),
+            ),
+            4 => S2LatLngRect::from_lat_lng(
+                R1Interval::new(-FRAC_PI_4, FRAC_PI_4),
+                S1Interval::new(-3.0 * FRAC_PI_4, -FRAC_PI_4),
+            ),
+            _ => S2LatLngRect::from_lat_lng(
+                R1Interval::new(-FRAC_PI_2, -pole_min_lat),
+                S1Interval::full(),
+            ),
+        };
+        // Finally, we expand the bound to account for the error when a point P
+        // is converted to an S2LatLng to test for containment. (The bound
+        // should be large enough so that it contains the computed S2LatLng of
+        // any contained point, not just the infinite-precision version.) We
+        // don't need to expand longitude because longitude is calculated via
+        // a single call to atan2(), which is guaranteed to be
+        // semi-monotonic.
+        bound.expanded(&S2LatLng::from_radians(f64::EPSILON, 0.0))
+    }
+
+    /// Returns the latitude of the cell vertex given by (i,j), where "i" and
+    /// "j" are either 0 or 1.
+    fn get_latitude(&self, i: usize, j: usize) -> f64 {
+        let p = face_uv_to_xyz(self.face, self.uv[0][i], self.uv[1][j]);
+        S2LatLng::latitude(&p).radians()
+    }
+
+    /// Returns the longitude of the cell vertex given by (i,j), where "i" and
+    /// "j" are either 0 or 1.
+    fn get_longitude(&self, i: usize, j: usize) -> f64 {
+        let p = face_uv_to_xyz(self.face, self.uv[0][i], self.uv[1][j]);
+        S2LatLng::longitude(&p).radians()
+    }
+
+    /// Returns the bounds of this cell in (u,v)-space.
+    pub fn get_bound_uv(&self) -> &R2Rect {
+        &self.uv
+    }
+
+    /// Returns true if the cell contains the given point "p". Note that unlike
+    /// S2Loop/S2Polygon, S2Cells are considered to be closed sets. This means
+    /// that points along an S2Cell edge (or at a vertex) belong to the adjacent
+    /// cell(s) as well.
+    ///
+    /// If instead you want every point to be contained by exactly one S2Cell,
+    /// you will need to convert the S2Cells to S2Loops (which implement point
+    /// containment this way).
+    ///
+    /// The point "p" does not need to be normalized.
+    ///
+    /// This is equivalent to testing that "p" is on the inner side of the
+    /// great circles through all four edges (see get_edge()), but is done in
+    /// (u,v)-space since that is both faster and allows the bound to be
+    /// expanded slightly to account for the error in S2CellId::from_point().
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{S2Cell, S2CellId, S2Point};
+    ///
+    /// let cell = S2Cell::new(S2CellId::from_face(1).child(2));
+    /// assert!(cell.contains_point(&cell.get_center()));
+    /// // Cells are closed, so they contain their vertices.
+    /// assert!(cell.contains_point(&cell.get_vertex(3)));
+    /// assert!(!cell.contains_point(&S2Point::new(0.0, -1.0, 0.0)));
+    /// ```
+    pub fn contains_point(&self, p: &S2Point) -> bool {
+        // We can't just call xyz_to_face_uv, because for points that lie on the
+        // boundary between two faces (i.e. u or v is +1/-1) we need to return
+        // true for both adjacent cells.
+        match face_xyz_to_uv(self.face, p) {
+            // Expand the (u,v) bound to ensure that
+            //
+            //   S2Cell::new(S2CellId::from_point(p)).contains_point(p)
+            //
+            // is always true. To do this, we need to account for the error when
+            // converting from (u,v) coordinates to (s,t) coordinates. At least in
+            // the case of S2_QUADRATIC_PROJECTION, the total error is at most
+            // MAX_XYZ_TO_UV_ERROR.
+            Some(uv) => self.uv.expanded(MAX_XYZ_TO_UV_ERROR).contains(&uv),
+            None => false,
+        }
     }
 
-    fn get_vertex_raw(&self, k: i32) -> S2Point {
-        todo!()
-        // face_uv_to_xyz(self.face, self.uv.get_vertex(k));
+    /// Returns the distance from the cell to the given point. Returns zero if
+    /// the point is inside the cell. The point should be unit length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s1::{S1Angle, S1ChordAngle};
+    /// use s2shell::s2::{S2Cell, S2Point};
+    ///
+    /// let cell = S2Cell::from_face(0);
+    /// assert_eq!(
+    ///     cell.get_distance(&S2Point::new(1.0, 0.0, 0.0)),
+    ///     S1ChordAngle::zero()
+    /// );
+    /// // The north pole is 45 degrees from the top edge of face 0.
+    /// let d = S1Angle::from(cell.get_distance(&S2Point::new(0.0, 0.0, 1.0)));
+    /// assert!((d.degrees() - 45.0).abs() < 1e-13);
+    /// ```
+    pub fn get_distance(&self, target: &S2Point) -> S1ChordAngle {
+        self.get_distance_internal(target, true)
+    }
+
+    /// Returns the distance from the cell boundary to the given point. The
+    /// point should be unit length. Unlike the distance to the cell itself,
+    /// this is positive for points in the interior of the cell; for points
+    /// outside the cell the two distances are the same.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s1::S1Angle;
+    /// use s2shell::s2::{S2Cell, S2Point};
+    ///
+    /// // The center of a face is 45 degrees from the middle of each edge.
+    /// let cell = S2Cell::from_face(0);
+    /// let d = S1Angle::from(cell.get_distance_to_boundary(&S2Point::new(1.0, 0.0, 0.0)));
+    /// assert!((d.degrees() - 45.0).abs() < 1e-13);
+    /// ```
+    pub fn get_distance_to_boundary(&self, target: &S2Point) -> S1ChordAngle {
+        self.get_distance_internal(target, false)
+    }
+
+    /// Returns the maximum distance from the cell (including its interior) to
+    /// the given point. The point should be unit length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s1::S1ChordAngle;
+    /// use s2shell::s2::{S2Cell, S2Point};
+    ///
+    /// let cell = S2Cell::from_face(0);
+    /// // The face center is farthest from the four face vertices.
+    /// let center = S2Point::new(1.0, 0.0, 0.0);
+    /// let vertex = S2Point::new(1.0, 1.0, 1.0).normalize();
+    /// let d = cell.get_max_distance(&center);
+    /// assert!((d.length2() - S1ChordAngle::from_points(&center, &vertex).length2()).abs() < 1e-15);
+    /// // The cell contains the antipode of (-1, 0, 0).
+    /// assert_eq!(
+    ///     cell.get_max_distance(&S2Point::new(-1.0, 0.0, 0.0)),
+    ///     S1ChordAngle::straight()
+    /// );
+    /// ```
+    pub fn get_max_distance(&self, target: &S2Point) -> S1ChordAngle {
+        // First check the 4 cell vertices. If all are within the hemisphere
+        // centered around target, the max distance will be to one of these
+        // vertices.
+        let target_uvw = face_xyz_to_uvw(self.face, target);
+        let max_dist = max_distance([
+            self.vertex_chord_dist(&target_uvw, 0, 0),
+            self.vertex_chord_dist(&target_uvw, 1, 0),
+            self.vertex_chord_dist(&target_uvw, 0, 1),
+            self.vertex_chord_dist(&target_uvw, 1, 1),
+        ]);
+        if max_dist <= S1ChordAngle::right() {
+            return max_dist;
+        }
+
+        // Otherwise, find the minimum distance d_min to the antipodal point and
+        // the maximum distance will be pi - d_min.
+        S1ChordAngle::straight() - self.get_distance(&-*target)
+    }
+
+    /// Returns the minimum distance from the cell to the given target cell.
+    /// Returns zero if the two cells intersect.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::S2Cell;
+    ///
+    /// let cell = S2Cell::from_face(0);
+    /// assert!(cell.get_distance_to_cell(&cell).is_zero());
+    /// // Opposite faces are closest at their corners, e.g. (1,1,1) and (-1,1,1).
+    /// let opposite = S2Cell::from_face(3);
+    /// let expected = (1.0f64 / 3.0).acos();
+    /// assert!((opposite.get_distance_to_cell(&cell).radians() - expected).abs() < 1e-15);
+    /// ```
+    pub fn get_distance_to_cell(&self, target: &S2Cell) -> S1ChordAngle {
+        // If the cells intersect, the distance is zero. We use the (u,v)
+        // ranges rather than S2CellId::intersects() so that cells that only
+        // share a boundary are also considered to intersect.
+        if self.face == target.face && self.uv.intersects(&target.uv) {
+            return S1ChordAngle::zero();
+        }
+
+        // Otherwise, the minimum distance always occurs between a vertex of
+        // one cell and an edge of the other cell (including the edge
+        // endpoints). This represents a total of 32 possible (vertex, edge)
+        // pairs.
+        let va: [S2Point; 4] = core::array::from_fn(|k| self.get_vertex(k as i32));
+        let vb: [S2Point; 4] = core::array::from_fn(|k| target.get_vertex(k as i32));
+        let mut min_dist = S1ChordAngle::infinity();
+        for i in 0..4 {
+            for j in 0..4 {
+                update_min_distance(&va[i], &vb[j], &vb[(j + 1) & 3], &mut min_dist);
+                update_min_distance(&vb[i], &va[j], &va[(j + 1) & 3], &mut min_dist);
+            }
+        }
+        min_dist
+    }
+
+    /// Returns the maximum distance from the cell (including its interior) to
+    /// the given target cell. Returns a straight angle if the cell contains a
+    /// point antipodal to some point of the target.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::{s1::S1ChordAngle, s2::S2Cell};
+    ///
+    /// let cell = S2Cell::from_face(0);
+    /// assert_eq!(
+    ///     cell.get_max_distance_to_cell(&S2Cell::from_face(3)),
+    ///     S1ChordAngle::straight()
+    /// );
+    /// ```
+    pub fn get_max_distance_to_cell(&self, target: &S2Cell) -> S1ChordAngle {
+        // Need to check the antipodal target for intersection with the cell.
+        // The antipodal (u,v) rectangle is the transpose of the original one,
+        // interpreted within the opposite face. If it intersects, the distance
+        // is S1ChordAngle::straight().
+        let opposite_face = if target.face >= 3 {
+            target.face - 3
+        } else {
+            target.face + 3
+        };
+        let opposite_uv = R2Rect::new(target.uv[1], target.uv[0]);
+        if self.face == opposite_face && self.uv.intersects(&opposite_uv) {
+            return S1ChordAngle::straight();
+        }
+
+        // Otherwise, the maximum distance always occurs between a vertex of
+        // one cell and an edge of the other cell (including the edge
+        // endpoints). This represents a total of 32 possible (vertex, edge)
+        // pairs.
+        let va: [S2Point; 4] = core::array::from_fn(|k| self.get_vertex(k as i32));
+        let vb: [S2Point; 4] = core::array::from_fn(|k| target.get_vertex(k as i32));
+        let mut max_dist = S1ChordAngle::negative();
+        for i in 0..4 {
+            for j in 0..4 {
+                update_max_distance(&va[i], &vb[j], &vb[(j + 1) & 3], &mut max_dist);
+                update_max_distance(&vb[i], &va[j], &va[(j + 1) & 3], &mut max_dist);
+            }
+        }
+        max_dist
+    }
+
+    // Returns the distance from the given point to the cell, or to its
+    // boundary if "to_interior" is false.
+    fn get_distance_internal(&self, target_xyz: &S2Point, to_interior: bool) -> S1ChordAngle {
+        // All calculations are done in the (u,v,w) coordinates of this cell's
+        // face.
+        let target = face_xyz_to_uvw(self.face, target_xyz);
+
+        // Compute dot products with all four upward or rightward-facing edge
+        // normals. "dirIJ" is the dot product for the edge corresponding to
+        // axis I, endpoint J. For example, dir01 is the right edge of the cell
+        // (i.e., the edge corresponding to the maximum u value).
+        let dir00 = target[0] - target[2] * self.uv[0][0];
+        let dir01 = target[0] - target[2] * self.uv[0][1];
+        let dir10 = target[1] - target[2] * self.uv[1][0];
+        let dir11 = target[1] - target[2] * self.uv[1][1];
+        let mut inside = true;
+        if dir00 < 0.0 {
+            inside = false; // Target is to the left of the cell
+            if self.v_edge_is_closest(&target, 0) {
+                return edge_distance(-dir00, self.uv[0][0]);
+            }
+        }
+        if dir01 > 0.0 {
+            inside = false; // Target is to the right of the cell
+            if self.v_edge_is_closest(&target, 1) {
+                return edge_distance(dir01, self.uv[0][1]);
+            }
+        }
+        if dir10 < 0.0 {
+            inside = false; // Target is below the cell
+            if self.u_edge_is_closest(&target, 0) {
+                return edge_distance(-dir10, self.uv[1][0]);
+            }
+        }
+        if dir11 > 0.0 {
+            inside = false; // Target is above the cell
+            if self.u_edge_is_closest(&target, 1) {
+                return edge_distance(dir11, self.uv[1][1]);
+            }
+        }
+        if inside {
+            if to_interior {
+                return S1ChordAngle::zero();
+            }
+            // Although you might think of S2Cells as rectangles, they are
+            // actually arbitrary quadrilaterals after they are projected onto
+            // the sphere. Therefore the simplest approach is just to find the
+            // minimum distance to any of the four edges.
+            return min_distance([
+                edge_distance(-dir00, self.uv[0][0]),
+                edge_distance(dir01, self.uv[0][1]),
+                edge_distance(-dir10, self.uv[1][0]),
+                edge_distance(dir11, self.uv[1][1]),
+            ]);
+        }
+        // Otherwise, the closest point is one of the four cell vertices. Note
+        // that it is *not* trivial to narrow down the candidates based on the
+        // edge sign tests above, because (1) the edges don't meet at right
+        // angles and (2) there are points on the far side of the sphere that
+        // are both above *and* below the cell, etc.
+        min_distance([
+            self.vertex_chord_dist(&target, 0, 0),
+            self.vertex_chord_dist(&target, 1, 0),
+            self.vertex_chord_dist(&target, 0, 1),
+            self.vertex_chord_dist(&target, 1, 1),
+        ])
+    }
+
+    // Returns the squared chord distance from point P to corner vertex (i,j).
+    fn vertex_chord_dist(&self, p: &S2Point, i: usize, j: usize) -> S1ChordAngle {
+        let vertex = S2Point::new(self.uv[0][i], self.uv[1][j], 1.0).normalize();
+        S1ChordAngle::from_points(p, &vertex)
+    }
+
+    // Given a point P and either the lower or upper edge of the S2Cell
+    // (specified by setting "v_end" to 0 or 1 respectively), returns true if P
+    // is closer to the interior of that edge than it is to either endpoint.
+    fn u_edge_is_closest(&self, p: &S2Point, v_end: usize) -> bool {
+        let u0 = self.uv[0][0];
+        let u1 = self.uv[0][1];
+        let v = self.uv[1][v_end];
+        // These are the normals to the planes that are perpendicular to the
+        // edge and pass through one of its two endpoints.
+        let dir0 = S2Point::new(v * v + 1.0, -u0 * v, -u0);
+        let dir1 = S2Point::new(v * v + 1.0, -u1 * v, -u1);
+        p.dot_prod(&dir0) > 0.0 && p.dot_prod(&dir1) < 0.0
+    }
+
+    // Given a point P and either the left or right edge of the S2Cell
+    // (specified by setting "u_end" to 0 or 1 respectively), returns true if P
+    // is closer to the interior of that edge than it is to either endpoint.
+    fn v_edge_is_closest(&self, p: &S2Point, u_end: usize) -> bool {
+        let v0 = self.uv[1][0];
+        let v1 = self.uv[1][1];
+        let u = self.uv[0][u_end];
+        // See comments above.
+        let dir0 = S2Point::new(-u * v0, u * u + 1.0, -v0);
+        let dir1 = S2Point::new(-u * v1, u * u + 1.0, -v1);
+        p.dot_prod(&dir0) > 0.0 && p.dot_prod(&dir1) < 0.0
+    }
+}
+
+// Returns the smallest of the given distances.
+fn min_distance(distances: [S1ChordAngle; 4]) -> S1ChordAngle {
+    distances
+        .into_iter()
+        .fold(S1ChordAngle::infinity(), |a, b| if b < a { b } else { a })
+}
+
+// Returns the largest of the given distances.
+fn max_distance(distances: [S1ChordAngle; 4]) -> S1ChordAngle {
+    distances
+        .into_iter()
+        .fold(S1ChordAngle::negative(), |a, b| if b > a { b } else { a })
+}
+
+// Given the dot product of a point P with the normal of a u- or v-edge at the
+// given coordinate value, returns the distance from P to that edge.
+fn edge_distance(dir_ij: f64, uv: f64) -> S1ChordAngle {
+    // Let P by the target point and let R be the closest point on the given
+    // edge AB. The desired distance PR can be expressed as PR^2 = PQ^2 + QR^2
+    // where Q is the point P projected onto the plane through the great circle
+    // through AB. We can compute the distance PQ^2 perpendicular to the plane
+    // from "dir_ij" (the dot product of the target point P with the edge
+    // normal) and the squared length the edge normal (1 + uv**2).
+    let pq2 = (dir_ij * dir_ij) / (1.0 + uv * uv);
+
+    // We can compute the distance QR as (1 - OQ) where O is the sphere origin,
+    // and we can compute OQ^2 = 1 - PQ^2 using the Pythagorean theorem.
+    // (This calculation loses accuracy as angle POQ approaches Pi/2.)
+    let qr = 1.0 - (1.0 - pq2).sqrt();
+    S1ChordAngle::from_length2(pq2 + qr * qr)
+}
+
+impl S2Region for S2Cell {
+    fn get_cap_bound(&self) -> S2Cap {
+        // Use the cell center in (u,v)-space as the cap axis. This vector is
+        // very close to get_center() and faster to compute. Neither one of
+        // these vectors yields the bounding cap with minimal surface area, but
+        // they are both pretty close.
+        let center = face_uv_to_xyz_from_r2point(self.face, &self.uv.get_center()).normalize();
+        let mut cap = S2Cap::from_point(center);
+        for k in 0..4 {
+            cap.add_point(&self.get_vertex(k));
+        }
+        cap
+    }
+
+    fn get_rect_bound(&self) -> S2LatLngRect {
+        S2Cell::get_rect_bound(self)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn get_cell_union_bound(&self, cell_ids: &mut Vec<S2CellId>) {
+        cell_ids.clear();
+        cell_ids.push(self.id);
+    }
+
+    fn contains_cell(&self, cell: &S2Cell) -> bool {
+        self.id.contains(&cell.id)
+    }
+
+    fn may_intersect_cell(&self, cell: &S2Cell) -> bool {
+        self.id.intersects(&cell.id)
+    }
+
+    fn contains_point(&self, point: &S2Point) -> bool {
+        S2Cell::contains_point(self, point)
+    }
+}
+
+/// Returns the cell with the given id, as in [`S2Cell::new`]. The id must be
+/// valid.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{S2Cell, S2CellId};
+///
+/// let id = S2CellId::from_face(4).child(2);
+/// let cell = S2Cell::from(id);
+/// assert_eq!((cell.face(), cell.level()), (4, 1));
+/// ```
+impl From<S2CellId> for S2Cell {
+    fn from(id: S2CellId) -> S2Cell {
+        S2Cell::new(id)
+    }
+}
+
+/// Returns the id of the cell.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{S2Cell, S2CellId};
+///
+/// let cell = S2Cell::from_face(5);
+/// assert_eq!(S2CellId::from(&cell), S2CellId::from_face(5));
+/// ```
+impl From<&S2Cell> for S2CellId {
+    fn from(cell: &S2Cell) -> S2CellId {
+        cell.id()
+    }
+}
+
+/// Formats the cell as its S2CellId followed by its level and the lat/lng of
+/// its center in degrees, e.g. "3/02 level=2 center=...".
+impl fmt::Display for S2Cell {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} level={} center={}",
+            self.id,
+            self.level,
+            S2LatLng::from_point(&self.get_center())
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use core::f64::consts::PI;
+
+    use approx::assert_relative_eq;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::s2::s2testing::{random_cell_id, random_point};
+    use crate::{
+        s1::S1Angle,
+        s2::{
+            interpolate, is_unit_length,
+            s2metrics::{MAX_AREA, MIN_AREA},
+        },
+        util::math::remainder,
+    };
+
+    #[test]
+    fn test_face_cells() {
+        for face in 0..S2CellId::NUM_FACES {
+            let cell = S2Cell::new(S2CellId::from_face(face));
+            assert_eq!(cell.face(), face);
+            assert_eq!(cell.level(), 0);
+            assert_eq!(cell.get_size_ij(), IjCoord(1 << 30));
+            assert_eq!(cell.get_size_st(), 1.0);
+            let uv = cell.get_bound_uv();
+            assert_eq!((uv[0].lo(), uv[0].hi()), (-1.0, 1.0));
+            assert_eq!((uv[1].lo(), uv[1].hi()), (-1.0, 1.0));
+        }
+    }
+
+    #[test]
+    fn test_leaf_cells() {
+        let id = S2CellId::from_face(4).child_begin_at_level(S2CellId::MAX_LEVEL);
+        let cell = S2Cell::new(id);
+        assert!(cell.is_leaf());
+        assert_eq!(cell.level(), S2CellId::MAX_LEVEL);
+        assert_eq!(cell.get_size_ij(), IjCoord(1));
+        let uv = cell.get_bound_uv();
+        assert!(uv[0].get_length() > 0.0);
+        assert_relative_eq!(uv[0].lo(), -1.0);
+
+        let last = S2CellId::from_face(1)
+            .child_end_at_level(S2CellId::MAX_LEVEL)
+            .prev();
+        assert!(S2Cell::new(last).is_leaf());
+    }
+
+    #[test]
+    fn test_exact_area() {
+        // The face cells tile the sphere.
+        let total: f64 = (0..6)
+            .map(|face| S2Cell::from_face(face).exact_area())
+            .sum();
+        assert!((total - 4.0 * PI).abs() <= 1e-14);
+
+        // The children of a cell partition it, and the area of a cell is within
+        // the range given by the area metrics.
+        let mut rng = StdRng::seed_from_u64(31);
+        for _ in 0..200 {
+            let cell = S2Cell::new(random_cell_id(&mut rng));
+            let (id, level) = (cell.id(), cell.level());
+            let area = cell.exact_area();
+            if !cell.is_leaf() {
+                let children: f64 = cell.subcells().iter().map(S2Cell::exact_area).sum();
+                // The area of small cells is computed from nearly degenerate
+                // triangles, so the relative error grows as the cells shrink.
+                assert!((children / area).ln().abs() <= 1e-6_f64.ln_1p(), "{id}");
+            }
+
+            let average = S2Cell::get_average_area(level);
+            assert!(area >= MIN_AREA.get_value(level) * (1.0 - 1e-14), "{id}");
+            assert!(area <= MAX_AREA.get_value(level) * (1.0 + 1e-14), "{id}");
+            assert!(area / average > 0.5 && area / average < 2.0, "{id}");
+        }
+    }
+
+    #[test]
+    fn test_get_average_area() {
+        assert_eq!(S2Cell::get_average_area(0), 4.0 * PI / 6.0);
+        for level in 1..=S2CellId::MAX_LEVEL {
+            assert_eq!(
+                S2Cell::get_average_area(level),
+                0.25 * S2Cell::get_average_area(level - 1)
+            );
+        }
+    }
+
+    // Returns a random cell and a target point for testing point-to-cell
+    // distances. Half of the targets are within twice the cell's (s,t)-size of
+    // its center, so that points inside, on, and just outside the cell are well
+    // represented; the rest are uniformly distributed over the sphere.
+    fn random_cell_and_target(rng: &mut StdRng) -> (S2Cell, S2Point) {
+        let cell = S2Cell::new(random_cell_id(rng));
+        let target = if rng.gen_bool(0.5) {
+            let radius = rng.gen_range(0.0..2.0) * cell.get_size_st();
+            (cell.get_center() + random_point(rng) * radius).normalize()
+        } else {
+            random_point(rng)
+        };
+        (cell, target)
+    }
+
+    #[test]
+    fn test_get_distance_to_boundary() {
+        let mut rng = StdRng::seed_from_u64(33);
+        for _ in 0..1000 {
+            let (cell, target) = random_cell_and_target(&mut rng);
+
+            // Compare against the minimum distance to the four edges.
+            let mut expected = S1ChordAngle::infinity();
+            for k in 0..4 {
+                update_min_distance(
+                    &target,
+                    &cell.get_vertex(k),
+                    &cell.get_vertex(k + 1),
+                    &mut expected,
+                );
+            }
+            let actual = cell.get_distance_to_boundary(&target);
+            assert!(
+                (S1Angle::from(actual).radians() - S1Angle::from(expected).radians()).abs()
+                    <= 1e-12,
+                "{cell:?} {target:?}"
+            );
+        }
+
+        // The distance from a vertex to the boundary is zero.
+        let cell = S2Cell::new(S2CellId::from_token("89c259"));
+        for k in 0..4 {
+            let d = cell.get_distance_to_boundary(&cell.get_vertex(k));
+            assert!(S1Angle::from(d).radians() <= 1e-15);
+        }
+        // The center of a cell is strictly inside.
+        assert!(cell.get_distance_to_boundary(&cell.get_center()) > S1ChordAngle::zero());
+    }
+
+    // Returns points sampled along the boundary of the cell, along with an
+    // upper bound on the chord distance from any boundary point to the
+    // closest sample.
+    fn sample_boundary(cell: &S2Cell) -> (Vec<S2Point>, f64) {
+        const SAMPLES_PER_EDGE: usize = 16;
+        let mut points = Vec::new();
+        let mut max_gap: f64 = 0.0;
+        for k in 0..4 {
+            let a = cell.get_vertex(k);
+            let b = cell.get_vertex(k + 1);
+            for i in 0..SAMPLES_PER_EDGE {
+                points.push(interpolate(i as f64 / SAMPLES_PER_EDGE as f64, &a, &b));
+            }
+            // Every boundary point is within half a sample spacing (measured
+            // along the edge, which bounds the chord distance) of a sample.
+            let spacing = S1Angle::from_points(&a, &b).radians() / SAMPLES_PER_EDGE as f64;
+            max_gap = max_gap.max(0.5 * spacing);
+        }
+        (points, max_gap)
+    }
+
+    #[test]
+    fn test_get_rect_bound_matches_sampled_boundary() {
+        // The bound contains every sampled boundary point, and each side of
+        // the bound is attained (up to rounding) by some sample. Sampling
+        // includes the vertices and edge midpoints, which is where the
+        // extremes occur.
+        const MAX_ERROR: f64 = 1e-14;
+        let mut rng = StdRng::seed_from_u64(25);
+        let cells = (0..S2CellId::NUM_FACES)
+            .map(S2Cell::from_face)
+            .chain((0..500).map(|_| S2Cell::new(random_cell_id(&mut rng))));
+        for cell in cells {
+            let bound = cell.get_rect_bound();
+            let (points, _) = sample_boundary(&cell);
+            let lls: Vec<S2LatLng> = points.iter().map(S2LatLng::from_point).collect();
+            for ll in &lls {
+                assert!(bound.contains_latlng(ll), "{cell}: {ll}");
+            }
+
+            let near_lat = |lat: f64| {
+                lls.iter()
+                    .any(|ll| (ll.lat().radians() - lat).abs() <= MAX_ERROR)
+            };
+            let contains_pole = cell.contains_point(&S2Point::new(0.0, 0.0, 1.0))
+                || cell.contains_point(&S2Point::new(0.0, 0.0, -1.0));
+            if contains_pole {
+                // The bound extends to the pole, and all longitudes.
+                assert!(bound.lng().is_full());
+                assert!(
+                    bound.lat_lo().radians() == -FRAC_PI_2 || near_lat(bound.lat_lo().radians())
+                );
+                assert!(
+                    bound.lat_hi().radians() == FRAC_PI_2 || near_lat(bound.lat_hi().radians())
+                );
+                continue;
+            }
+            assert!(near_lat(bound.lat_lo().radians()), "{cell}");
+            assert!(near_lat(bound.lat_hi().radians()), "{cell}");
+            let near_lng = |lng: f64| {
+                lls.iter()
+                    .any(|ll| remainder(ll.lng().radians() - lng, 2.0 * PI).abs() <= MAX_ERROR)
+            };
+            assert!(near_lng(bound.lng_lo().radians()), "{cell}");
+            assert!(near_lng(bound.lng_hi().radians()), "{cell}");
+        }
+    }
+
+    #[test]
+    fn test_get_distance_to_point() {
+        let mut rng = StdRng::seed_from_u64(35);
+        for _ in 0..1000 {
+            let (cell, target) = random_cell_and_target(&mut rng);
+
+            // The distance is zero for points inside the cell, and otherwise
+            // the minimum distance to the four edges.
+            let mut expected = S1ChordAngle::infinity();
+            if cell.contains_point(&target) {
+                expected = S1ChordAngle::zero();
+            } else {
+                for k in 0..4 {
+                    update_min_distance(
+                        &target,
+                        &cell.get_vertex(k),
+                        &cell.get_vertex(k + 1),
+                        &mut expected,
+                    );
+                }
+            }
+            let actual = cell.get_distance(&target);
+            assert!(
+                (S1Angle::from(actual).radians() - S1Angle::from(expected).radians()).abs()
+                    <= 1e-12,
+                "{cell:?} {target:?}"
+            );
+            // Outside the cell, the distance to the cell and to its boundary
+            // agree.
+            if actual > S1ChordAngle::zero() {
+                assert_eq!(actual, cell.get_distance_to_boundary(&target));
+            }
+        }
+
+        let cell = S2Cell::new(S2CellId::from_token("89c259"));
+        assert_eq!(cell.get_distance(&cell.get_center()), S1ChordAngle::zero());
+        // The point of the cell farthest from its center is a vertex, so the
+        // antipode of the center is 180 degrees minus that distance away.
+        let center = cell.get_center();
+        let max_vertex_angle = (0..4)
+            .map(|k| center.angle(&cell.get_vertex(k)))
+            .fold(0.0, f64::max);
+        let d = S1Angle::from(cell.get_distance(&-center));
+        assert!((d.radians() - (PI - max_vertex_angle)).abs() <= 1e-12);
+    }
+
+    #[test]
+    fn test_get_max_distance_to_point() {
+        let mut rng = StdRng::seed_from_u64(38);
+        for _ in 0..1000 {
+            let (cell, target) = random_cell_and_target(&mut rng);
+
+            // If the cell contains the antipode, the distance is 180 degrees.
+            // Otherwise the farthest point lies on the boundary, so the
+            // distance is the maximum distance to the four edges.
+            let mut expected = S1ChordAngle::negative();
+            if cell.contains_point(&-target) {
+                expected = S1ChordAngle::straight();
+            } else {
+                for k in 0..4 {
+                    update_max_distance(
+                        &target,
+                        &cell.get_vertex(k),
+                        &cell.get_vertex(k + 1),
+                        &mut expected,
+                    );
+                }
+            }
+            let actual = cell.get_max_distance(&target);
+            assert!(
+                (S1Angle::from(actual).radians() - S1Angle::from(expected).radians()).abs()
+                    <= 1e-12,
+                "{cell:?} {target:?}"
+            );
+            assert!(actual >= cell.get_distance(&target));
+        }
+
+        // The farthest point from a vertex of a small cell is the opposite
+        // vertex.
+        let cell = S2Cell::new(S2CellId::from_token("89c259"));
+        for k in 0..4 {
+            let expected = S1ChordAngle::from_points(&cell.get_vertex(k), &cell.get_vertex(k + 2));
+            let actual = cell.get_max_distance(&cell.get_vertex(k));
+            assert!((actual.length2() - expected.length2()).abs() <= 1e-15);
+        }
+    }
+
+    #[test]
+    fn test_get_distance_to_cell() {
+        // The computed distances have an error of at most a few dozen
+        // DBL_EPSILON (see GetUpdateMinDistanceMaxError() in the C++ library),
+        // whereas the sampled distances exceed (or fall short of) the true
+        // ones by at most the sampling gaps of the two cells.
+        const MAX_ERROR: f64 = 1e-14;
+        let mut rng = StdRng::seed_from_u64(12);
+        for iter in 0..300 {
+            let a = S2Cell::new(random_cell_id(&mut rng));
+            // Half of the targets are chosen near the first cell so that the
+            // distances are small and the cells sometimes touch.
+            let b = if iter % 2 == 0 {
+                S2Cell::new(random_cell_id(&mut rng))
+            } else {
+                let offset = S2Point::new(
+                    rng.gen_range(-1.0..1.0),
+                    rng.gen_range(-1.0..1.0),
+                    rng.gen_range(-1.0..1.0),
+                ) * 10f64.powi(-rng.gen_range(1..9));
+                let p = (a.get_vertex(rng.gen_range(0..4)) + offset).normalize();
+                let level = rng.gen_range(0..=S2CellId::MAX_LEVEL);
+                S2Cell::new(S2CellId::from_point(&p).parent_at_level(level))
+            };
+            let (pa, gap_a) = sample_boundary(&a);
+            let (pb, gap_b) = sample_boundary(&b);
+            let mut sampled_min: f64 = f64::INFINITY;
+            let mut sampled_max: f64 = 0.0;
+            for x in &pa {
+                for y in &pb {
+                    let d = (x - y).norm();
+                    sampled_min = sampled_min.min(d);
+                    sampled_max = sampled_max.max(d);
+                }
+            }
+            let gap = gap_a + gap_b;
+
+            let min_dist = a.get_distance_to_cell(&b);
+            assert_eq!(min_dist, b.get_distance_to_cell(&a));
+            let min_dist = min_dist.length2().sqrt();
+            assert!(min_dist <= sampled_min + MAX_ERROR, "{a:?} {b:?}");
+            if min_dist > 0.0 {
+                assert!(sampled_min <= min_dist + gap + MAX_ERROR, "{a:?} {b:?}");
+            }
+
+            let max_dist = a.get_max_distance_to_cell(&b);
+            assert_eq!(max_dist, b.get_max_distance_to_cell(&a));
+            let max_dist = max_dist.length2().sqrt();
+            assert!(max_dist >= sampled_max - MAX_ERROR, "{a:?} {b:?}");
+            if max_dist < 2.0 {
+                assert!(max_dist <= sampled_max + gap + MAX_ERROR, "{a:?} {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_distance_to_cell_special_cases() {
+        let cell =
+            S2Cell::new(S2CellId::from_point(&S2Point::new(1.0, 2.0, 3.0)).parent_at_level(10));
+        // A cell is at distance zero from itself and from its neighbors.
+        assert!(cell.get_distance_to_cell(&cell).is_zero());
+        let mut neighbors = Vec::new();
+        cell.id().append_all_neighbors(12, &mut neighbors);
+        for neighbor in neighbors {
+            assert!(cell.get_distance_to_cell(&S2Cell::new(neighbor)).is_zero());
+        }
+        // A cell that contains a point antipodal to the target is at maximum
+        // distance a straight angle, even though no vertex/edge pair is.
+        let antipodal = S2Cell::new(S2CellId::from_point(&-cell.get_center()).parent_at_level(5));
+        assert_eq!(
+            cell.get_max_distance_to_cell(&antipodal),
+            S1ChordAngle::straight()
+        );
+        assert_eq!(
+            antipodal.get_max_distance_to_cell(&cell),
+            S1ChordAngle::straight()
+        );
+        // The maximum distance across a face cell is attained between
+        // opposite vertices, e.g. (1,1,1) and (1,-1,-1), whose angle is
+        // acos(-1/3).
+        let face = S2Cell::from_face(0);
+        let max_dist = S1Angle::from(face.get_max_distance_to_cell(&face)).radians();
+        assert!((max_dist - (-1.0f64 / 3.0).acos()).abs() <= 1e-14);
+        // Adjacent faces contain antipodal corner points.
+        assert_eq!(
+            face.get_max_distance_to_cell(&S2Cell::from_face(1)),
+            S1ChordAngle::straight()
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        // Some face centers have negative zero coordinates, which determine
+        // the sign of the longitude (atan2(-0, -1) == -180 degrees).
+        let golden = [
+            "0/ level=0 center=0.000000,0.000000",
+            "1/ level=0 center=0.000000,90.000000",
+            "2/ level=0 center=90.000000,-180.000000",
+            "3/ level=0 center=-0.000000,-180.000000",
+            "4/ level=0 center=-0.000000,-90.000000",
+            "5/ level=0 center=-90.000000,0.000000",
+        ];
+        for (face, expected) in golden.iter().enumerate() {
+            assert_eq!(S2Cell::from_face(face as i32).to_string(), *expected);
+        }
+        let leaf = S2Cell::new(S2CellId::from_point(&S2Point::new(1.0, 0.0, 0.0)));
+        assert_eq!(
+            leaf.to_string(),
+            "0/200000000000000000000000000000 level=30 center=0.000000,0.000000"
+        );
+    }
+
+    #[test]
+    fn test_subcells() {
+        let mut rng = StdRng::seed_from_u64(19);
+        let mut cells: Vec<S2Cell> = (0..S2CellId::NUM_FACES).map(S2Cell::from_face).collect();
+        for _ in 0..200 {
+            cells.push(S2Cell::new(random_cell_id(&mut rng)));
+        }
+        for cell in cells.iter().filter(|cell| !cell.is_leaf()) {
+            let children = cell.subcells();
+            let uv = cell.get_bound_uv();
+            let mut lo = [f64::INFINITY; 2];
+            let mut hi = [f64::NEG_INFINITY; 2];
+            for (pos, child) in children.iter().enumerate() {
+                // Each subcell matches the cell constructed from its id.
+                let expected = S2Cell::new(cell.id().child(pos as i32));
+                assert_eq!(child.id(), expected.id());
+                assert_eq!(child.face(), cell.face());
+                assert_eq!(child.level(), cell.level() + 1);
+                assert_eq!(child.orientation(), expected.orientation());
+                let child_uv = child.get_bound_uv();
+                for d in 0..2 {
+                    assert_eq!(child_uv[d], expected.get_bound_uv()[d]);
+                    // The child shares one boundary with the parent and the
+                    // other is the parent's center.
+                    assert!(child_uv[d].lo() == uv[d].lo() || child_uv[d].hi() == uv[d].hi());
+                }
+                for d in 0..2 {
+                    lo[d] = lo[d].min(child_uv[d].lo());
+                    hi[d] = hi[d].max(child_uv[d].hi());
+                }
+            }
+            // The subcells cover the parent exactly, and since each one is a
+            // distinct quadrant split at the same center they do not overlap.
+            for d in 0..2 {
+                assert_eq!((lo[d], hi[d]), (uv[d].lo(), uv[d].hi()));
+            }
+            let quadrants: Vec<_> = children
+                .iter()
+                .map(|c| (c.get_bound_uv()[0].lo(), c.get_bound_uv()[1].lo()))
+                .collect();
+            for (k, q) in quadrants.iter().enumerate() {
+                assert!(!quadrants[..k].contains(q));
+            }
+        }
+    }
+
+    #[test]
+    fn test_contains_point() {
+        let mut rng = StdRng::seed_from_u64(21);
+        for _ in 0..500 {
+            let cell = S2Cell::new(random_cell_id(&mut rng));
+            // The center and the vertices (which are on the boundary) are
+            // contained, as is the unnormalized center.
+            assert!(cell.contains_point(&cell.get_center()));
+            assert!(cell.contains_point(&(cell.get_center() * 3.0)));
+            for k in 0..4 {
+                assert!(cell.contains_point(&cell.get_vertex(k)));
+                assert!(cell.contains_point(&cell.get_vertex_raw(k)));
+            }
+            // Every point is contained by the leaf cell that from_point()
+            // returns for it.
+            let p = cell.get_vertex(rng.gen_range(0..4));
+            assert!(S2Cell::new(S2CellId::from_point(&p)).contains_point(&p));
+
+            // Points just outside each edge are not contained. We step away
+            // from the midpoint of the edge along the edge normal by an amount
+            // much larger than the containment tolerance.
+            let size = S1Angle::from_points(&cell.get_vertex(0), &cell.get_vertex(2)).radians();
+            for k in 0..4 {
+                let mid = (cell.get_vertex(k) + cell.get_vertex(k + 1)).normalize();
+                let outside = (mid - cell.get_edge(k) * (0.01 * size).max(1e-13)).normalize();
+                assert!(!cell.contains_point(&outside), "{cell:?} {k}");
+                let inside = (mid + cell.get_edge(k) * (0.01 * size).max(1e-13)).normalize();
+                assert!(cell.contains_point(&inside), "{cell:?} {k}");
+            }
+            // The antipodal center is never contained.
+            assert!(!cell.contains_point(&-cell.get_center()));
+        }
+    }
+
+    #[test]
+    fn test_contains_point_matches_edge_normals() {
+        // Away from cell boundaries, containment agrees with testing the point
+        // against the four inward-facing edge normals.
+        let mut rng = StdRng::seed_from_u64(22);
+        for _ in 0..100 {
+            let leaf = S2Cell::new(random_cell_id(&mut rng))
+                .id()
+                .child_begin_at_level(S2CellId::MAX_LEVEL);
+            let cell = S2Cell::new(leaf.parent_at_level(rng.gen_range(0..8)));
+            for _ in 0..100 {
+                let p = S2Point::new(
+                    rng.gen_range(-1.0..1.0),
+                    rng.gen_range(-1.0..1.0),
+                    rng.gen_range(-1.0..1.0),
+                )
+                .normalize();
+                let dots: Vec<f64> = (0..4).map(|k| cell.get_edge(k).dot_prod(&p)).collect();
+                if dots.iter().any(|d| d.abs() < 1e-12) {
+                    continue;
+                }
+                let inside = dots.iter().all(|&d| d > 0.0) && p.dot_prod(&cell.get_center()) > 0.0;
+                assert_eq!(cell.contains_point(&p), inside, "{cell:?} {p:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_boundary() {
+        let mut rng = StdRng::seed_from_u64(75);
+        for _ in 0..100 {
+            let cell = S2Cell::new(random_cell_id(&mut rng));
+            for points_per_edge in [0, 1, 5] {
+                let boundary = cell.get_boundary(points_per_edge);
+                let n = points_per_edge + 1;
+                assert_eq!(boundary.len(), 4 * n + 1);
+                assert_eq!(boundary.first(), boundary.last());
+                for k in 0..4 {
+                    assert_eq!(boundary[k * n], cell.get_vertex(k as i32));
+                    // Every point of the k-th edge lies on its great circle,
+                    // and on the cell up to rounding errors.
+                    for p in &boundary[k * n..=(k + 1) * n] {
+                        assert!(is_unit_length(p));
+                        assert!(cell.get_edge(k as i32).dot_prod(p).abs() <= 1e-15);
+                        assert!(S1Angle::from(cell.get_distance(p)).radians() <= 1e-15);
+                    }
+                }
+            }
+        }
+        assert!(S2Cell::from_face(0)
+            .get_boundary(0)
+            .windows(2)
+            .all(|w| w[0] != w[1]));
     }
 }
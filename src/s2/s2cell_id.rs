@@ -15,16 +15,23 @@
 
 // Original Author: ericv@google.com (Eric Veach)
 
-use lazy_static::lazy_static;
-use num_traits::ToPrimitive;
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+use core::{fmt, iter::FusedIterator, ops::RangeInclusive, str::FromStr};
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
 
 use crate::{
-    r2::R2Rect,
+    r1::R1Interval,
+    r2::{R2Point, R2Rect},
+    s1::S1Angle,
     s2::{
-        face_siti_to_xyz, ij_to_st_min,
+        face_siti_to_xyz, face_uv_to_xyz, ij_to_st_min, ij_to_st_min_clamped,
         internal::{INVERT_MASK, POS_TO_IJ, POS_TO_ORIENTATION, SWAP_MASK},
+        s2metrics::{MAX_AREA, MAX_EDGE, MIN_EDGE},
         s2point::S2Point,
-        st_to_uv,
+        siti_to_st, st_to_ij, st_to_uv, uv_to_st, xyz_to_face_uv, IjCoord, S2LatLng, SiTiCoord,
     },
 };
 
@@ -60,7 +67,23 @@ use crate::{
 /// (although not all methods enforce this).
 ///
 /// This class is intended to be copied by value as desired.
-#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+///
+/// # Storing ids as signed integers
+///
+/// Systems that only have signed 64-bit integers (e.g. Postgres BIGINT or
+/// Java longs) can store cell ids using one of two mappings, which are NOT
+/// interchangeable:
+///
+///  - `to_i64_bits()` reinterprets the bits of the id. This is what most
+///    other S2 implementations store, so use it when exchanging ids with
+///    them. However it does not preserve ordering: ids on faces 4 and 5 have
+///    the top bit set, so they become negative and sort before face 0.
+///
+///  - `to_signed_ordered()` flips the top bit instead, so that comparing the
+///    signed values gives the same result as comparing the cell ids. Use it
+///    whenever the database sorts or range-scans the values, e.g. with
+///    `signed_range_min()` and `signed_range_max()` in a "BETWEEN" query.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct S2CellId {
     id: u64,
 }
@@ -79,51 +102,54 @@ pub struct S2CellId {
 const LOOKUP_BITS: i32 = 4;
 const LOOKUP_TABLE_SIZE: usize = 1 << (2 * LOOKUP_BITS + 2);
 
-lazy_static! {
-    static ref LOOKUP_TABLES: ([u16; LOOKUP_TABLE_SIZE], [u16; LOOKUP_TABLE_SIZE]) =
-        init_lookup_tables();
-    static ref LOOKUP_POS: &'static [u16; LOOKUP_TABLE_SIZE] = &LOOKUP_TABLES.0;
-    static ref LOOKUP_IJ: &'static [u16; LOOKUP_TABLE_SIZE] = &LOOKUP_TABLES.1;
-}
+/// The tables are computed at compile time, so accessing them is free of
+/// any initialization checks.
+const LOOKUP_TABLES: ([u16; LOOKUP_TABLE_SIZE], [u16; LOOKUP_TABLE_SIZE]) = init_lookup_tables();
+static LOOKUP_POS: [u16; LOOKUP_TABLE_SIZE] = LOOKUP_TABLES.0;
+static LOOKUP_IJ: [u16; LOOKUP_TABLE_SIZE] = LOOKUP_TABLES.1;
 
-#[rustfmt::skip]
-fn init_lookup_tables() -> ([u16; LOOKUP_TABLE_SIZE], [u16; LOOKUP_TABLE_SIZE]) {
+const fn init_lookup_tables() -> ([u16; LOOKUP_TABLE_SIZE], [u16; LOOKUP_TABLE_SIZE]) {
     let mut lookup_pos = [0u16; LOOKUP_TABLE_SIZE];
     let mut lookup_ij = [0u16; LOOKUP_TABLE_SIZE];
 
-    // I'm leaving this with too many arguments because I'm trying to
-    // replicate the original library's implementation as closely as I
-    // can because I don't understand this enough to make this iterative.
-    #[allow(clippy::too_many_arguments)]
-    fn init_lookup_cell(
-        level: i32, i: i32, j: i32, orig_orientation: i32, pos: i32, orientation: i32,
-        lookup_pos: &mut [u16; LOOKUP_TABLE_SIZE], lookup_ij: &mut [u16; LOOKUP_TABLE_SIZE]
-    ) {
-        if level == LOOKUP_BITS {
-            let ij: i32 = (i << LOOKUP_BITS) + j;
-            lookup_pos[((ij << 2) + orig_orientation) as usize] = ((pos << 2) + orientation) as u16;
-            lookup_ij[((pos << 2) + orig_orientation) as usize] = ((ij << 2) + orientation) as u16;
-        } else {
-            let level = level + 1;
-            let i = i << 1;
-            let j = j << 1;
-            let pos = pos << 2;
-            let r: [i32; 4] = POS_TO_IJ[orientation as usize];
-            init_lookup_cell(level, i + (r[0] >> 1), j + (r[0] & 1), orig_orientation, pos,
-                orientation ^ POS_TO_ORIENTATION[0], lookup_pos, lookup_ij);
-            init_lookup_cell(level, i + (r[1] >> 1), j + (r[1] & 1), orig_orientation, pos + 1,
-                orientation ^ POS_TO_ORIENTATION[1], lookup_pos, lookup_ij);
-            init_lookup_cell(level, i + (r[2] >> 1), j + (r[2] & 1), orig_orientation, pos + 2,
-                orientation ^ POS_TO_ORIENTATION[2], lookup_pos, lookup_ij);
-            init_lookup_cell(level, i + (r[3] >> 1), j + (r[3] & 1), orig_orientation, pos + 3,
-                orientation ^ POS_TO_ORIENTATION[3], lookup_pos, lookup_ij);
-        }
-    }
-
-    init_lookup_cell(0, 0, 0, 0, 0, 0, &mut lookup_pos, &mut lookup_ij);
-    init_lookup_cell(0, 0, 0, SWAP_MASK, 0, SWAP_MASK, &mut lookup_pos, &mut lookup_ij);
-    init_lookup_cell(0, 0, 0, INVERT_MASK, 0, INVERT_MASK, &mut lookup_pos, &mut lookup_ij);
-    init_lookup_cell(0, 0, 0, SWAP_MASK | INVERT_MASK, 0, SWAP_MASK | INVERT_MASK, &mut lookup_pos, &mut lookup_ij);
+    // Each top-level orientation is expanded depth-first into its 4^LOOKUP_BITS
+    // subcells, using an explicit stack of (level, i, j, pos, orientation)
+    // entries. Every node pushes its four children, so the stack never holds
+    // more than 3 entries per level plus the one being expanded.
+    const STACK_SIZE: usize = 3 * LOOKUP_BITS as usize + 1;
+    let mut stack = [[0i32; 5]; STACK_SIZE];
+
+    let mut orig_orientation = 0;
+    while orig_orientation < 4 {
+        stack[0] = [0, 0, 0, 0, orig_orientation];
+        let mut len = 1;
+        while len > 0 {
+            len -= 1;
+            let [level, i, j, pos, orientation] = stack[len];
+            if level == LOOKUP_BITS {
+                let ij = (i << LOOKUP_BITS) + j;
+                lookup_pos[((ij << 2) + orig_orientation) as usize] =
+                    ((pos << 2) + orientation) as u16;
+                lookup_ij[((pos << 2) + orig_orientation) as usize] =
+                    ((ij << 2) + orientation) as u16;
+                continue;
+            }
+            let r = POS_TO_IJ[orientation as usize];
+            let mut k = 0;
+            while k < 4 {
+                stack[len] = [
+                    level + 1,
+                    (i << 1) + (r[k] >> 1),
+                    (j << 1) + (r[k] & 1),
+                    (pos << 2) + k as i32,
+                    orientation ^ POS_TO_ORIENTATION[k],
+                ];
+                len += 1;
+                k += 1;
+            }
+        }
+        orig_orientation += 1;
+    }
 
     (lookup_pos, lookup_ij)
 }
@@ -157,6 +183,53 @@ impl S2CellId {
         self.id
     }
 
+    /// Returns the bits of the cell id reinterpreted as an i64. This mapping
+    /// does NOT preserve ordering; see to_signed_ordered().
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::S2CellId;
+    ///
+    /// let id = S2CellId::from_face(5);
+    /// assert!(id.to_i64_bits() < 0);
+    /// assert!(id.to_i64_bits() < S2CellId::from_face(0).to_i64_bits());
+    /// assert_eq!(S2CellId::from_i64_bits(id.to_i64_bits()), id);
+    /// ```
+    pub fn to_i64_bits(&self) -> i64 {
+        self.id as i64
+    }
+
+    /// The inverse of to_i64_bits().
+    pub fn from_i64_bits(id: i64) -> S2CellId {
+        S2CellId::new(id as u64)
+    }
+
+    /// Returns an i64 whose numeric ordering is the same as the ordering of
+    /// cell ids (i.e. their order along the Hilbert curve). This is done by
+    /// flipping the top bit, so S2CellId::none() maps to i64::MIN and
+    /// S2CellId::sentinel() maps to i64::MAX.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::S2CellId;
+    ///
+    /// let a = S2CellId::from_face(0);
+    /// let b = S2CellId::from_face(5);
+    /// assert!(a < b);
+    /// assert!(a.to_signed_ordered() < b.to_signed_ordered());
+    /// assert_eq!(S2CellId::from_signed_ordered(b.to_signed_ordered()), b);
+    /// ```
+    pub fn to_signed_ordered(&self) -> i64 {
+        (self.id ^ (1 << 63)) as i64
+    }
+
+    /// The inverse of to_signed_ordered().
+    pub fn from_signed_ordered(id: i64) -> S2CellId {
+        S2CellId::new((id as u64) ^ (1 << 63))
+    }
+
     /// Which cube face this cell belongs to, in the range 0..5.
     pub fn face(&self) -> i32 {
         (self.id >> S2CellId::POS_BITS) as i32
@@ -165,13 +238,77 @@ impl S2CellId {
     /// The position of the cell center along the Hilbert curve over this face,
     /// in the range 0..(2**kPosBits-1).
     pub fn pos(&self) -> u64 {
-        self.id & (0_u64 >> S2CellId::FACE_BITS)
+        self.id & (!0_u64 >> S2CellId::FACE_BITS)
     }
 
+    /// Return the subdivision level of the cell (range 0..MAX_LEVEL).
     pub fn level(&self) -> i32 {
+        // We can't just S2_DCHECK(is_valid()) because we want level() to be
+        // defined for end-iterators, i.e. S2CellId::End(kLevel). However there is
+        // no good way to define S2CellId::None().level(), so we do prohibit that.
         debug_assert!(self.id != 0_u64);
-        todo!()
-        // S2CellId::MAX_LEVEL - (Bt
+
+        // A special case for leaf cells is not worthwhile.
+        S2CellId::MAX_LEVEL - (self.id.trailing_zeros() >> 1) as i32
+    }
+
+    /// Returns the minimum level such that every cell at that level has all
+    /// of its edges no longer than "angle", based on the MAX_EDGE metric. If
+    /// even leaf cells are too large, returns MAX_LEVEL.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::{
+    ///     s1::S1Angle,
+    ///     s2::{s2metrics::MAX_EDGE, S2CellId},
+    /// };
+    ///
+    /// let angle = S1Angle::from_degrees(1.0);
+    /// let level = S2CellId::level_for_max_edge_length(angle);
+    /// assert!(MAX_EDGE.get_value(level) <= angle.radians());
+    /// assert!(MAX_EDGE.get_value(level - 1) > angle.radians());
+    /// ```
+    pub fn level_for_max_edge_length(angle: S1Angle) -> i32 {
+        MAX_EDGE.get_level_for_max_value(angle.radians())
+    }
+
+    /// Returns the maximum level such that every cell at that level has all
+    /// of its edges at least as long as "angle", based on the MIN_EDGE
+    /// metric. If even face cells are too small, returns 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::{
+    ///     s1::S1Angle,
+    ///     s2::{s2metrics::MIN_EDGE, S2CellId},
+    /// };
+    ///
+    /// let angle = S1Angle::from_degrees(1.0);
+    /// let level = S2CellId::level_for_min_edge_length(angle);
+    /// assert!(MIN_EDGE.get_value(level) >= angle.radians());
+    /// assert!(MIN_EDGE.get_value(level + 1) < angle.radians());
+    /// ```
+    pub fn level_for_min_edge_length(angle: S1Angle) -> i32 {
+        MIN_EDGE.get_level_for_min_value(angle.radians())
+    }
+
+    /// Returns the minimum level such that every cell at that level has an
+    /// area of at most "steradians", based on the MAX_AREA metric. If even
+    /// leaf cells are too large, returns MAX_LEVEL.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{s2metrics::MAX_AREA, S2CellId};
+    ///
+    /// let level = S2CellId::level_for_max_area(1e-6);
+    /// assert!(MAX_AREA.get_value(level) <= 1e-6);
+    /// assert!(MAX_AREA.get_value(level - 1) > 1e-6);
+    /// ```
+    pub fn level_for_max_area(steradians: f64) -> i32 {
+        MAX_AREA.get_level_for_max_value(steradians)
     }
 
     /// Returns an invalid cell id.
@@ -193,192 +330,3017 @@ impl S2CellId {
         S2CellId::new(u64::MAX)
     }
 
+    /// Return the cell corresponding to a given S2 cube face.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::s2cell_id::S2CellId;
+    ///
+    /// let face = S2CellId::from_face(3);
+    /// assert_eq!(face.face(), 3);
+    /// assert_eq!(face.level(), 0);
+    /// assert!(face.is_face());
+    /// ```
     pub fn from_face(face: i32) -> S2CellId {
-        todo!()
+        debug_assert!((0..S2CellId::NUM_FACES).contains(&face));
+        S2CellId::new(((face as u64) << S2CellId::POS_BITS) + S2CellId::lsb_for_level(0))
     }
 
-    pub fn from_face_pos_level(face: i8, pos: u64, level: i8) -> S2CellId {
-        todo!()
+    /// Return a cell given its face (range 0..5), Hilbert curve position within
+    /// that face (an unsigned integer with S2CellId::POS_BITS bits), and level
+    /// (range 0..MAX_LEVEL). The given position will be modified to correspond
+    /// to the Hilbert curve position at the center of the returned cell. This
+    /// is a static function rather than a constructor in order to indicate what
+    /// the arguments represent.
+    pub fn from_face_pos_level(face: i32, pos: u64, level: i32) -> S2CellId {
+        let cell = S2CellId::new(((face as u64) << S2CellId::POS_BITS) + (pos | 1));
+        cell.parent_at_level(level)
     }
 
-    fn to_point_raw(self) -> S2Point {
-        let (face, si, ti) = self.get_center_siti();
-        face_siti_to_xyz(face, si.to_u32().unwrap(), ti.to_u32().unwrap())
+    /// Construct a leaf cell containing the given point "p". Usually there is
+    /// exactly one such cell, but for points along the edge of a cell, any
+    /// adjacent cell may be (deterministically) chosen. This is because
+    /// S2CellIds are considered to be closed sets. The returned cell will
+    /// always contain the given point, i.e.
+    ///
+    ///   S2Cell::new(S2CellId::from_point(&p)).contains_point(&p)
+    ///
+    /// is always true. The point "p" does not need to be normalized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{s2cell_id::S2CellId, S2Point};
+    ///
+    /// let id = S2CellId::from_point(&S2Point::new(1.0, 0.0, 0.0));
+    /// assert!(id.is_leaf());
+    /// assert_eq!(id.face(), 0);
+    /// ```
+    pub fn from_point(p: &S2Point) -> S2CellId {
+        let (face, u, v) = xyz_to_face_uv(p);
+        S2CellId::from_face_ij(face, st_to_ij(uv_to_st(u)), st_to_ij(uv_to_st(v)))
     }
 
-    // pub fn get_center_st() -> R2Point {
-    //     todo!()
-    // }
+    /// Return the leaf cell containing the given normalized S2LatLng.
+    pub fn from_lat_lng(ll: &S2LatLng) -> S2CellId {
+        S2CellId::from_point(&ll.to_point())
+    }
 
-    pub fn get_size_st(&self) -> f64 {
-        S2CellId::get_size_st_at_level(self.level())
+    /// Returns the leaf cell containing the given latitude and longitude in
+    /// degrees. This is a convenience wrapper around
+    /// from_latlng_degrees_at_level(); see there for the handling of invalid
+    /// arguments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{S2CellId, S2LatLng};
+    ///
+    /// let id = S2CellId::from_latlng_degrees(40.7128, -74.0060);
+    /// assert!(id.is_leaf());
+    /// assert_eq!(
+    ///     id,
+    ///     S2CellId::from_lat_lng(&S2LatLng::from_degrees(40.7128, -74.0060))
+    /// );
+    /// assert_eq!(S2CellId::from_latlng_degrees(91.0, 0.0), S2CellId::none());
+    /// ```
+    pub fn from_latlng_degrees(lat: f64, lng: f64) -> S2CellId {
+        S2CellId::from_latlng_degrees_at_level(lat, lng, S2CellId::MAX_LEVEL)
     }
 
-    pub fn get_size_st_at_level(level: i32) -> f64 {
-        ij_to_st_min(S2CellId::get_size_ij_at_level(level))
+    /// Returns the cell at the given level containing the given latitude and
+    /// longitude in degrees. Longitudes outside [-180, 180] are wrapped.
+    /// Returns S2CellId::none() if either coordinate is not finite, the
+    /// latitude is outside [-90, 90], or the level is outside
+    /// [0, S2CellId::MAX_LEVEL].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::S2CellId;
+    ///
+    /// let id = S2CellId::from_latlng_degrees_at_level(40.7550976, -73.9830029, 10);
+    /// assert_eq!(id.to_token(), "89c259");
+    /// assert_eq!(
+    ///     S2CellId::from_latlng_degrees_at_level(40.7550976, 286.0169971, 10),
+    ///     id
+    /// );
+    /// assert!(!S2CellId::from_latlng_degrees_at_level(0.0, 0.0, 31).is_valid());
+    /// ```
+    pub fn from_latlng_degrees_at_level(lat: f64, lng: f64, level: i32) -> S2CellId {
+        if !lat.is_finite() || !lng.is_finite() || !(-90.0..=90.0).contains(&lat) {
+            return S2CellId::none();
+        }
+        if !(0..=S2CellId::MAX_LEVEL).contains(&level) {
+            return S2CellId::none();
+        }
+        let ll = S2LatLng::from_degrees(lat, lng).normalized();
+        S2CellId::from_lat_lng(&ll).parent_at_level(level)
     }
 
-    // pub fn get_bound_st() -> R2Rect {
-    //     todo!()
-    // }
+    /// Parses the face/path form produced by to_debug_string() (e.g.
+    /// "3/210210"), as the FromStr implementation does, but returns None
+    /// rather than an error if the string is malformed: if it is empty, the
+    /// face is not in 0..5, a child position is not in 0..3, or there are
+    /// more than 30 child positions. A face followed by an empty path (e.g.
+    /// "3/") is the face cell. This is the inverse of to_debug_string() for
+    /// all valid cells.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::S2CellId;
+    ///
+    /// let id = S2CellId::from_debug_string("3/210").unwrap();
+    /// assert_eq!(id, S2CellId::from_face(3).child(2).child(1).child(0));
+    /// assert_eq!(
+    ///     S2CellId::from_debug_string("3/"),
+    ///     Some(S2CellId::from_face(3))
+    /// );
+    /// assert_eq!(S2CellId::from_debug_string("3/4"), None);
+    /// ```
+    pub fn from_debug_string(s: &str) -> Option<S2CellId> {
+        s.parse().ok()
+    }
 
-    // pub fn get_center_uv(&self) -> R2Point {
-    //     todo!()
-    // }
+    /// Return the cells at the given level containing each of the given
+    /// normalized S2LatLngs, in the same order. This produces the same result
+    /// as calling `S2CellId::from_lat_lng(ll).parent_at_level(level)` for each
+    /// point, but is faster for levels below MAX_LEVEL since it only computes
+    /// the Hilbert curve position bits that survive truncation to "level".
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{S2CellId, S2LatLng};
+    ///
+    /// let points = [
+    ///     S2LatLng::from_degrees(37.8, -122.4),
+    ///     S2LatLng::from_degrees(-33.9, 151.2),
+    /// ];
+    /// let ids = S2CellId::from_lat_lngs(&points, 10);
+    /// assert_eq!(
+    ///     ids[1],
+    ///     S2CellId::from_lat_lng(&points[1]).parent_at_level(10)
+    /// );
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn from_lat_lngs(points: &[S2LatLng], level: i32) -> Vec<S2CellId> {
+        debug_assert!((0..=S2CellId::MAX_LEVEL).contains(&level));
+        points
+            .iter()
+            .map(|ll| {
+                let (face, u, v) = xyz_to_face_uv(&ll.to_point());
+                let i = st_to_ij(uv_to_st(u));
+                let j = st_to_ij(uv_to_st(v));
+                S2CellId::from_face_ij_at_level(face, i, j, level)
+            })
+            .collect()
+    }
 
-    // pub fn get_bound_uv(&self) -> R2Rect {
-    //     todo!()
-    // }
+    /// Return a leaf cell given its cube face (range 0..5) and i- and
+    /// j-coordinates (see s2/mod.rs). This is the inverse of
+    /// to_face_ij_orientation().
+    ///
+    /// REQUIRES: 0 <= face <= 5, and i and j are in [0, MAX_SIZE).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{IjCoord, S2CellId};
+    ///
+    /// let leaf = S2CellId::from_token("89c25a31").child_begin_at_level(S2CellId::MAX_LEVEL);
+    /// let (face, i, j, _) = leaf.to_face_ij_orientation();
+    /// assert_eq!(S2CellId::from_face_ij(face, i, j), leaf);
+    /// assert_eq!(
+    ///     S2CellId::from_face_ij(0, IjCoord(0), IjCoord(0)),
+    ///     S2CellId::from_face(0).child_begin_at_level(S2CellId::MAX_LEVEL)
+    /// );
+    /// ```
+    pub fn from_face_ij(face: i32, i: IjCoord, j: IjCoord) -> S2CellId {
+        debug_assert!((0..S2CellId::NUM_FACES).contains(&face));
+        debug_assert!(
+            (IjCoord(0)..IjCoord::LIMIT).contains(&i),
+            "i out of range: {}",
+            *i
+        );
+        debug_assert!(
+            (IjCoord(0)..IjCoord::LIMIT).contains(&j),
+            "j out of range: {}",
+            *j
+        );
+        S2CellId::from_face_ij_at_level(face, i, j, S2CellId::MAX_LEVEL)
+    }
 
-    // pub fn expanded_by_distance_uv(&self, uv: &R2Rect, distance: S1Angle) -> R2Rect {
-    //     todo!()
-    // }
+    /// Like from_face_ij(), but returns the ancestor of the leaf cell at the
+    /// given level. Lookup table iterations that only affect the position
+    /// bits below "level" are skipped.
+    fn from_face_ij_at_level(face: i32, i: IjCoord, j: IjCoord, level: i32) -> S2CellId {
+        // Note that this value gets shifted one bit to the left at the end
+        // of the function.
+        let mut n: u64 = (face as u64) << (S2CellId::POS_BITS - 1);
 
-    pub fn get_center_siti(&self) -> (i32, i32, i32) {
-        // First we compute the discrete (i,j) coordinates of a leaf cell contained
-        // within the given cell.  Given that cells are represented by the Hilbert
-        // curve position corresponding at their center, it turns out that the cell
-        // returned by ToFaceIJOrientation is always one of two leaf cells closest
-        // to the center of the cell (unless the given cell is a leaf cell itself,
-        // in which case there is only one possibility).
-        //
-        // Given a cell of size s >= 2 (i.e. not a leaf cell), and letting (imin,
-        // jmin) be the coordinates of its lower left-hand corner, the leaf cell
-        // returned by ToFaceIJOrientation() is either (imin + s/2, jmin + s/2)
-        // (imin + s/2 - 1, jmin + s/2 - 1).  The first case is the one we want.
-        // We can distinguish these two cases by looking at the low bit of "i" or
-        // "j".  In the second case the low bit is one, unless s == 2 (i.e. the
-        // level just above leaf cells) in which case the low bit is zero.
+        // Alternating faces have opposite Hilbert curve orientations; this
+        // is necessary in order for all faces to have a right-handed
+        // coordinate system.
+        let mut bits = face & SWAP_MASK;
+
+        // Each iteration maps 4 bits of "i" and "j" into 8 bits of the Hilbert
+        // curve position. The lookup table transforms a 10-bit key of the form
+        // "iiiijjjjoo" to a 10-bit value of the form "ppppppppoo", where the
+        // letters [ijpo] denote bits of "i", "j", Hilbert curve position, and
+        // Hilbert curve orientation respectively.
         //
-        // In the code below, the expression ((i ^ (int(id_) >> 2)) & 1) is true
-        // if we are in the second case described above.
-        let (face, i, j, _) = self.to_face_ij_orientation();
-        let delta: i32 = if self.is_leaf() {
-            1
-        } else if (i ^ (self.id.to_i32().unwrap() >> 2)) & 1 != 0 {
-            2
-        } else {
-            0
-        };
-        let psi = 2 * i + delta;
-        let pti = 2 * j + delta;
+        // Iteration "k" handles bits [4k, 4k+4) of "i" and "j", while a cell
+        // at "level" only depends on the top "level" of their 30 bits.
+        let mask = (1 << LOOKUP_BITS) - 1;
+        let k_min = (S2CellId::MAX_LEVEL - level) / LOOKUP_BITS;
+        for k in (k_min..8).rev() {
+            bits += ((*i >> (k * LOOKUP_BITS)) & mask) << (LOOKUP_BITS + 2);
+            bits += ((*j >> (k * LOOKUP_BITS)) & mask) << 2;
+            bits = LOOKUP_POS[bits as usize] as i32;
+            n |= ((bits >> 2) as u64) << (k * 2 * LOOKUP_BITS);
+            bits &= SWAP_MASK | INVERT_MASK;
+        }
 
-        (face, psi, pti)
+        let leaf = S2CellId::new(n * 2 + 1);
+        if level == S2CellId::MAX_LEVEL {
+            leaf
+        } else {
+            leaf.parent_at_level(level)
+        }
     }
 
-    /// Return the edge length of this cell in (i,j)-space.
-    pub fn get_size_ij(&self) -> i32 {
-        S2CellId::get_size_ij_at_level(self.level())
+    /// Returns the first cell in an iteration over all cells at the given
+    /// level (in Hilbert curve order).
+    pub fn begin(level: i32) -> S2CellId {
+        S2CellId::from_face(0).child_begin_at_level(level)
     }
 
-    /// Like `get_size_ij`, but return the size of cells at the given level.
-    pub fn get_size_ij_at_level(level: i32) -> i32 {
-        debug_assert!(level > 0);
-        debug_assert!(level < S2CellId::MAX_LEVEL);
-        1 << (S2CellId::MAX_LEVEL - level)
+    /// Returns the cell id just past the last cell at the given level. Note
+    /// that the result is not a valid cell id.
+    pub fn end(level: i32) -> S2CellId {
+        S2CellId::from_face(5).child_end_at_level(level)
     }
 
-    /// Return true if id() represents a valid cell.
+    /// Returns a lazy iterator over all cells at the given level in Hilbert
+    /// curve order, starting on face 0 and ending on face 5. There are
+    /// `6 * 4^level` such cells.
     ///
-    /// All methods require is_valid() to be true unless otherwise specified
-    /// (although not all methods enforce this).
-    pub fn is_valid(&self) -> bool {
-        self.face() < S2CellId::NUM_FACES && (self.lsb() & 0x1555555555555555) != 0
+    /// This is equivalent to `cells_at_level()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::s2cell_id::S2CellId;
+    ///
+    /// assert_eq!(S2CellId::all_cells_at_level(0).count(), 6);
+    /// assert_eq!(S2CellId::all_cells_at_level(2).count(), 6 * 16);
+    /// ```
+    pub fn all_cells_at_level(level: i32) -> S2CellIdIter {
+        S2CellId::cells_at_level(level)
     }
 
-    /// Return true if this is a leaf cell (more efficient than checking
-    /// whether level() == kMaxLevel).
-    pub fn is_leaf(&self) -> bool {
-        (self.id & 1) != 0
+    /// Returns an iterator over all cells at the given level in Hilbert curve
+    /// order across all six faces, i.e. the cells in the range
+    /// [begin(level), end(level)).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::s2cell_id::S2CellId;
+    ///
+    /// let cells = S2CellId::cells_at_level(1);
+    /// assert_eq!(cells.len(), 24);
+    /// assert_eq!(cells.rev().next(), Some(S2CellId::end(1).prev()));
+    /// ```
+    pub fn cells_at_level(level: i32) -> S2CellIdIter {
+        debug_assert!((0..=S2CellId::MAX_LEVEL).contains(&level));
+        S2CellIdIter::new(S2CellId::begin(level), S2CellId::end(level))
     }
 
-    /// Return true if this is a top-level face cell (more efficient than
-    /// checking whether level() == 0).
-    pub fn is_face(&self) -> bool {
-        (self.id & (self.lsb_for_level(0) - 1)) == 0
+    /// Returns an iterator over all cells at the given level on the given
+    /// face, in Hilbert curve order. There are `4^level` such cells. This is
+    /// equivalent to filtering `cells_at_level()` by face, but only visits
+    /// the cells on that face.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::s2cell_id::S2CellId;
+    ///
+    /// let cells = S2CellId::iter_along_face(3, 2);
+    /// assert_eq!(cells.len(), 16);
+    /// assert!(cells.clone().all(|id| id.face() == 3 && id.level() == 2));
+    /// assert_eq!(
+    ///     cells.clone().next(),
+    ///     Some(S2CellId::from_face(3).child_begin_at_level(2))
+    /// );
+    /// ```
+    pub fn iter_along_face(face: i32, level: i32) -> S2CellIdIter {
+        debug_assert!((0..S2CellId::NUM_FACES).contains(&face));
+        debug_assert!((0..=S2CellId::MAX_LEVEL).contains(&level));
+        let id = S2CellId::from_face(face);
+        S2CellIdIter::new(id.child_begin_at_level(level), id.child_end_at_level(level))
     }
 
-    /// Converts this cell ID to face, i, j, and orientation.
+    /// Returns an iterator over the four children of this cell, in Hilbert
+    /// curve order. The cell must not be a leaf cell.
     ///
     /// # Examples
     ///
     /// ```
     /// use s2shell::s2::s2cell_id::S2CellId;
     ///
-    /// let cell_id = S2CellId::new(0x1234567890ABCDEF);
-    /// let (face, i, j, orientation) = cell_id.to_face_ij_orientation();
-    /// assert!(face >= 0 && face < 6);
-    /// assert!(orientation >= 0 && orientation < 4);
+    /// let id = S2CellId::from_face(3);
+    /// assert!(id.children_iter().eq((0..4).map(|k| id.child(k))));
     /// ```
-    pub fn to_face_ij_orientation(&self) -> (i32, i32, i32, i32) {
-        let (mut i, mut j) = (0, 0);
-        let face = self.face();
-        let mut bits = face & SWAP_MASK;
-
-        fn get_bits(k: i32, id: u64, bits: &mut i32, i: &mut i32, j: &mut i32, lookup_ij: &[u16]) {
-            let nbits = if k == 7 {
-                S2CellId::MAX_LEVEL - 7 * LOOKUP_BITS
-            } else {
-                LOOKUP_BITS
-            };
-            *bits += (((id >> (k * 2 * LOOKUP_BITS + 1)) & ((1 << (2 * nbits)) - 1)) as i32) << 2;
-            *bits = lookup_ij[*bits as usize] as i32;
-            *i += (*bits >> (LOOKUP_BITS + 2)) << (k * LOOKUP_BITS);
-            *j += ((*bits >> 2) & ((1 << LOOKUP_BITS) - 1)) << (k * LOOKUP_BITS);
-            *bits &= SWAP_MASK | INVERT_MASK;
-        }
+    pub fn children_iter(&self) -> S2CellIdIter {
+        S2CellIdIter::new(self.child_begin(), self.child_end())
+    }
 
-        for k in (0..8).rev() {
-            get_bits(k, self.id, &mut bits, &mut i, &mut j, &LOOKUP_IJ[..]);
-        }
+    /// Returns the range of leaf cell ids contained by this cell, i.e.
+    /// `range_min().id()..=range_max().id()`. This is convenient for looking
+    /// up cells in stores indexed by 64-bit cell id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::s2cell_id::S2CellId;
+    ///
+    /// let id = S2CellId::from_face(1).child(2);
+    /// assert!(id.leaf_range().contains(&id.id()));
+    /// assert!(!id.leaf_range().contains(&id.next().id()));
+    /// ```
+    pub fn leaf_range(&self) -> RangeInclusive<u64> {
+        self.range_min().id()..=self.range_max().id()
+    }
 
-        debug_assert_eq!(0, POS_TO_ORIENTATION[2]);
-        debug_assert_eq!(SWAP_MASK, POS_TO_ORIENTATION[0]);
-        let orientation = if self.lsb() & 0x1111111111111110 != 0 {
-            bits ^ SWAP_MASK
-        } else {
-            bits
-        };
+    /// Return the direction vector corresponding to the center of the given
+    /// cell. The vector returned by to_point_raw is not necessarily unit
+    /// length. This method returns the same result as S2Cell::get_center().
+    pub fn to_point(&self) -> S2Point {
+        self.to_point_raw().normalize()
+    }
 
-        (face, i, j, orientation)
+    /// Like to_point(), but the result is not necessarily unit length.
+    pub fn to_point_raw(&self) -> S2Point {
+        let (face, si, ti) = self.get_center_siti();
+        face_siti_to_xyz(face, si, ti)
     }
 
-    /// Return the lowest-numbered bit that is on for this cell id, which is
-    /// equal to (uint64_t{1} << (2 * (kMaxLevel - level))).  So for example,
-    /// a.lsb() <= b.lsb() if and only if a.level() >= b.level(), but the
-    /// first test is more efficient.
-    pub fn lsb(&self) -> u64 {
-        self.id & (self.id.wrapping_neg() + 1)
+    /// Return the S2LatLng corresponding to the center of the given cell.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::S2CellId;
+    ///
+    /// let id = S2CellId::from_face(2);
+    /// assert_eq!(id.to_lat_lng().lat().degrees(), 90.0);
+    /// ```
+    pub fn to_lat_lng(&self) -> S2LatLng {
+        S2LatLng::from_point(&self.to_point_raw())
     }
 
-    pub fn lsb_for_level(&self, level: i32) -> u64 {
-        1_u64 << (2 * (S2CellId::MAX_LEVEL - level))
+    /// Returns the latitude and longitude of the center of the cell, in
+    /// degrees. The cell must be valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::S2CellId;
+    ///
+    /// let (lat, lng) = S2CellId::from_token("89c259").to_latlng_degrees();
+    /// assert!((lat - 40.7550976).abs() < 1e-7);
+    /// assert!((lng + 73.9830029).abs() < 1e-7);
+    /// ```
+    pub fn to_latlng_degrees(&self) -> (f64, f64) {
+        debug_assert!(self.is_valid());
+        let ll = self.to_lat_lng();
+        (ll.lat().degrees(), ll.lng().degrees())
     }
 
-    /// Return the bound in (u,v)-space for the cell at the given level containing
-    /// the leaf cell with the given (i,j)-coordinates.
-    pub fn ij_level_to_bound_uv(i: i32, j: i32, level: i32) -> R2Rect {
-        let cell_size = S2CellId::get_size_ij_at_level(level);
-        let mut bound = R2Rect::default();
-        for d in 0..2 {
-            let ij = if d == 0 { i } else { j };
-            let ij_lo = ij & -cell_size;
-            let ij_hi = ij_lo + cell_size;
-            bound[d][0] = st_to_uv(ij_to_st_min(ij_lo));
-            bound[d][1] = st_to_uv(ij_to_st_min(ij_hi));
+    /// Returns a human-readable summary of the cell for debugging, consisting
+    /// of its face, level, token and center. Invalid cells are formatted as
+    /// by the Display implementation. The summary cannot be parsed back; use
+    /// to_debug_string() for a form that from_debug_string() accepts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::S2CellId;
+    ///
+    /// let id = S2CellId::from_face(4).child(1);
+    /// assert_eq!(
+    ///     id.debug_string(),
+    ///     "face 4, level 1, token 8c, center 21.037511,-67.380135"
+    /// );
+    /// assert_eq!(S2CellId::none().debug_string(), "Invalid: 0000000000000000");
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn debug_string(&self) -> String {
+        if !self.is_valid() {
+            return alloc::format!("{self}");
         }
-        bound
+        alloc::format!(
+            "face {}, level {}, token {}, center {}",
+            self.face(),
+            self.level(),
+            self.to_token(),
+            self.to_lat_lng()
+        )
     }
-}
 
-impl From<S2CellId> for S2Point {
-    fn from(val: S2CellId) -> S2Point {
-        val.to_point_raw().normalize()
-    }
+    /// Returns the face/path form of the cell, i.e. its face followed by a
+    /// slash and the child position at each level (e.g. "3/02" for
+    /// `S2CellId::from_face(3).child(0).child(2)`). This is the same string as
+    /// the Display implementation, and from_debug_string() parses it back.
+    /// Invalid cells are formatted as "Invalid: " followed by their id in
+    /// hexadecimal, which from_debug_string() rejects.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::S2CellId;
+    ///
+    /// let id = S2CellId::from_face(3).child(0).child(2);
+    /// assert_eq!(id.to_debug_string(), "3/02");
+    /// assert_eq!(S2CellId::from_debug_string(&id.to_debug_string()), Some(id));
+    /// assert_eq!(S2CellId::from_face(5).to_debug_string(), "5/");
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn to_debug_string(&self) -> String {
+        alloc::format!("{self}")
+    }
+
+    /// Returns a compact, stable string representation of the cell id: the
+    /// id in lowercase hex with trailing zeros removed. Tokens preserve the
+    /// ordering of the ids when compared lexicographically. The invalid id
+    /// S2CellId::none() is encoded as "X".
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::S2CellId;
+    ///
+    /// assert_eq!(S2CellId::from_face(3).to_token(), "7");
+    /// assert_eq!(S2CellId::none().to_token(), "X");
+    /// let id = S2CellId::from_face(3).child(1).child(2);
+    /// assert_eq!(S2CellId::from_token(&id.to_token()), id);
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn to_token(&self) -> String {
+        if self.id == 0 {
+            return String::from("X");
+        }
+        let num_zero_digits = self.id.trailing_zeros() as usize / 4;
+        let mut token = alloc::format!("{:016x}", self.id);
+        token.truncate(16 - num_zero_digits);
+        token
+    }
+
+    /// Decodes a token produced by to_token(). Returns S2CellId::none() if
+    /// the token is malformed (too long or containing a character other
+    /// than a hex digit). Both upper and lower case hex digits are accepted.
+    pub fn from_token(token: &str) -> S2CellId {
+        if token.is_empty() || token.len() > 16 {
+            return S2CellId::none();
+        }
+        let mut id: u64 = 0;
+        for c in token.chars() {
+            match c.to_digit(16) {
+                Some(d) => id = (id << 4) | d as u64,
+                None => return S2CellId::none(),
+            }
+        }
+        S2CellId::new(id << (4 * (16 - token.len())))
+    }
+
+    /// Return the center of the cell in (s,t) coordinates (see s2coords.h).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::{r2::R2Point, s2::S2CellId};
+    ///
+    /// assert_eq!(
+    ///     S2CellId::from_face(2).get_center_st(),
+    ///     R2Point::new(0.5, 0.5)
+    /// );
+    /// assert_eq!(
+    ///     S2CellId::from_face(2).child(0).get_center_st(),
+    ///     R2Point::new(0.25, 0.25)
+    /// );
+    /// ```
+    pub fn get_center_st(&self) -> R2Point {
+        let (_, si, ti) = self.get_center_siti();
+        R2Point::new(siti_to_st(si), siti_to_st(ti))
+    }
+
+    /// Return the edge length of this cell in (s,t)-space.
+    pub fn get_size_st(&self) -> f64 {
+        S2CellId::get_size_st_at_level(self.level())
+    }
+
+    /// Return the edge length in (s,t)-space of cells at the given level,
+    /// from 1.0 for face cells (level 0) down to 2**-30 for leaf cells.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::S2CellId;
+    ///
+    /// assert_eq!(S2CellId::get_size_st_at_level(0), 1.0);
+    /// assert_eq!(S2CellId::get_size_st_at_level(3), 0.125);
+    /// ```
+    pub fn get_size_st_at_level(level: i32) -> f64 {
+        ij_to_st_min(S2CellId::get_size_ij_at_level(level))
+    }
+
+    /// Return the bounds of this cell in (s,t)-space.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::{r2::R2Point, s2::S2CellId};
+    ///
+    /// let bound = S2CellId::from_face(0).child(1).get_bound_st();
+    /// assert_eq!(bound.lo(), R2Point::new(0.0, 0.5));
+    /// assert_eq!(bound.hi(), R2Point::new(0.5, 1.0));
+    /// ```
+    pub fn get_bound_st(&self) -> R2Rect {
+        let center = self.get_center_st();
+        let half_size = 0.5 * self.get_size_st();
+        R2Rect::from_lo_hi(
+            &R2Point::new(center.x() - half_size, center.y() - half_size),
+            &R2Point::new(center.x() + half_size, center.y() + half_size),
+        )
+    }
+
+    /// Return the center of the cell in (u,v) coordinates (see s2coords.h).
+    /// Note that the center of the cell is defined as the point at which it
+    /// is recursively subdivided into four children; in general, it is not
+    /// at the midpoint of the (u,v) rectangle covered by the cell.
+    pub fn get_center_uv(&self) -> R2Point {
+        let center = self.get_center_st();
+        R2Point::new(st_to_uv(center.x()), st_to_uv(center.y()))
+    }
+
+    /// Return the bounds of this cell in (u,v)-space.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::{r1::R1Interval, s2::S2CellId};
+    ///
+    /// let bound = S2CellId::from_face(2).get_bound_uv();
+    /// assert_eq!(*bound.x(), R1Interval::new(-1.0, 1.0));
+    /// assert_eq!(*bound.y(), R1Interval::new(-1.0, 1.0));
+    /// ```
+    pub fn get_bound_uv(&self) -> R2Rect {
+        let (_, i, j, _) = self.to_face_ij_orientation();
+        S2CellId::ij_level_to_bound_uv(i, j, self.level())
+    }
+
+    /// Expand a rectangle in (u,v)-space so that it contains all points within
+    /// the given distance of the boundary, and return the smallest such
+    /// rectangle. If the distance is negative, then instead shrink this
+    /// rectangle so that it excludes all points within the given absolute
+    /// distance of the boundary.
+    ///
+    /// Distances are measured *on the sphere*, not in (u,v)-space. For
+    /// example, you can use this method to expand the (u,v)-bound of an
+    /// S2CellId so that it contains all points within 5km of the original
+    /// cell. You can then test whether a point lies within the expanded
+    /// bounds using face_xyz_to_uv().
+    ///
+    /// Limitations:
+    ///
+    ///  - Because the rectangle is drawn on one of the six cube-face planes
+    ///    (i.e., {x,y,z} = +/-1), it can cover at most one hemisphere. This
+    ///    limits the maximum amount that a rectangle can be expanded. For
+    ///    example, S2CellId bounds can be expanded safely by at most 45 degrees
+    ///    (about 5000 km on the Earth's surface).
+    ///
+    ///  - The implementation is not exact for negative distances. The
+    ///    resulting rectangle will exclude all points within the given
+    ///    distance of the boundary but may be slightly smaller than necessary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::{s1::S1Angle, s2::S2CellId};
+    ///
+    /// let id = S2CellId::from_face(0).child(3).child(1);
+    /// let uv = id.get_bound_uv();
+    /// let expanded = S2CellId::expanded_by_distance_uv(&uv, S1Angle::from_degrees(1.0));
+    /// assert!(expanded.contains(&id.get_center_uv()));
+    /// assert!(expanded.x().lo() < uv.x().lo() && expanded.x().hi() > uv.x().hi());
+    /// assert!(expanded.y().lo() < uv.y().lo() && expanded.y().hi() > uv.y().hi());
+    /// ```
+    pub fn expanded_by_distance_uv(uv: &R2Rect, distance: S1Angle) -> R2Rect {
+        // Expand each of the four sides of the rectangle just enough to include
+        // all points within the given distance of that side. (The rectangle may
+        // be expanded by a different amount in (u,v)-space on each side.)
+        let (u0, u1, v0, v1) = (uv[0].lo(), uv[0].hi(), uv[1].lo(), uv[1].hi());
+        let max_u = u0.abs().max(u1.abs());
+        let max_v = v0.abs().max(v1.abs());
+        let sin_dist = distance.radians().sin();
+        R2Rect::new(
+            R1Interval::new(
+                expand_endpoint(u0, max_v, -sin_dist),
+                expand_endpoint(u1, max_v, sin_dist),
+            ),
+            R1Interval::new(
+                expand_endpoint(v0, max_u, -sin_dist),
+                expand_endpoint(v1, max_u, sin_dist),
+            ),
+        )
+    }
+
+    /// Return the (face, si, ti) coordinates of the center of the cell. Note
+    /// that although (si,ti) coordinates span the range [0,2**31] in general,
+    /// the cell center coordinates are always in the range [1,2**31-1] and
+    /// therefore can be represented using a signed 32-bit integer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{S2CellId, SiTiCoord};
+    ///
+    /// let (face, si, ti) = S2CellId::from_face(3).get_center_siti();
+    /// assert_eq!((face, si, ti), (3, SiTiCoord(1 << 30), SiTiCoord(1 << 30)));
+    /// ```
+    pub fn get_center_siti(&self) -> (i32, SiTiCoord, SiTiCoord) {
+        // First we compute the discrete (i,j) coordinates of a leaf cell contained
+        // within the given cell.  Given that cells are represented by the Hilbert
+        // curve position corresponding at their center, it turns out that the cell
+        // returned by ToFaceIJOrientation is always one of two leaf cells closest
+        // to the center of the cell (unless the given cell is a leaf cell itself,
+        // in which case there is only one possibility).
+        //
+        // Given a cell of size s >= 2 (i.e. not a leaf cell), and letting (imin,
+        // jmin) be the coordinates of its lower left-hand corner, the leaf cell
+        // returned by ToFaceIJOrientation() is either (imin + s/2, jmin + s/2)
+        // (imin + s/2 - 1, jmin + s/2 - 1).  The first case is the one we want.
+        // We can distinguish these two cases by looking at the low bit of "i" or
+        // "j".  In the second case the low bit is one, unless s == 2 (i.e. the
+        // level just above leaf cells) in which case the low bit is zero.
+        //
+        // In the code below, the expression ((i ^ (int(id_) >> 2)) & 1) is true
+        // if we are in the second case described above.
+        let (face, IjCoord(i), IjCoord(j), _) = self.to_face_ij_orientation();
+        let delta: i32 = if self.is_leaf() {
+            1
+        } else if (i ^ (self.id >> 2) as i32) & 1 != 0 {
+            2
+        } else {
+            0
+        };
+        let psi = 2 * i + delta;
+        let pti = 2 * j + delta;
+
+        (face, SiTiCoord(psi as u32), SiTiCoord(pti as u32))
+    }
+
+    /// Like get_center_siti(), but returns bare integer coordinates.
+    pub fn get_center_siti_raw(&self) -> (i32, i32, i32) {
+        let (face, si, ti) = self.get_center_siti();
+        (face, si.into_inner() as i32, ti.into_inner() as i32)
+    }
+
+    /// Return the edge length of this cell in (i,j)-space.
+    pub fn get_size_ij(&self) -> IjCoord {
+        S2CellId::get_size_ij_at_level(self.level())
+    }
+
+    /// Like `get_size_ij`, but return the size of cells at the given level.
+    pub fn get_size_ij_at_level(level: i32) -> IjCoord {
+        debug_assert!((0..=S2CellId::MAX_LEVEL).contains(&level));
+        IjCoord(1 << (S2CellId::MAX_LEVEL - level))
+    }
+
+    /// Return true if id() represents a valid cell.
+    ///
+    /// All methods require is_valid() to be true unless otherwise specified
+    /// (although not all methods enforce this).
+    pub fn is_valid(&self) -> bool {
+        self.face() < S2CellId::NUM_FACES && (self.lsb() & 0x1555555555555555) != 0
+    }
+
+    /// Return true if this is a leaf cell (more efficient than checking
+    /// whether level() == kMaxLevel).
+    pub fn is_leaf(&self) -> bool {
+        (self.id & 1) != 0
+    }
+
+    /// Return true if this is a top-level face cell (more efficient than
+    /// checking whether level() == 0).
+    pub fn is_face(&self) -> bool {
+        (self.id & (S2CellId::lsb_for_level(0) - 1)) == 0
+    }
+
+    /// Return the minimum cell id contained within this cell, i.e. the first
+    /// leaf cell in its range.
+    pub fn range_min(&self) -> S2CellId {
+        S2CellId::new(self.id - (self.lsb() - 1))
+    }
+
+    /// Return the maximum cell id contained within this cell, i.e. the last
+    /// leaf cell in its range.
+    pub fn range_max(&self) -> S2CellId {
+        S2CellId::new(self.id + (self.lsb() - 1))
+    }
+
+    /// Returns range_min() in the to_signed_ordered() representation. The
+    /// descendants of this cell are exactly the ids whose signed values are
+    /// in [signed_range_min(), signed_range_max()], e.g. as used in a SQL
+    /// "BETWEEN" query.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::S2CellId;
+    ///
+    /// let parent = S2CellId::from_face(4).child(2);
+    /// let child = parent.child(3).child(1).to_signed_ordered();
+    /// assert!((parent.signed_range_min()..=parent.signed_range_max()).contains(&child));
+    /// ```
+    pub fn signed_range_min(&self) -> i64 {
+        self.range_min().to_signed_ordered()
+    }
+
+    /// Returns range_max() in the to_signed_ordered() representation.
+    pub fn signed_range_max(&self) -> i64 {
+        self.range_max().to_signed_ordered()
+    }
+
+    /// Return true if the given cell is contained within this one.
+    pub fn contains(&self, other: &S2CellId) -> bool {
+        debug_assert!(self.is_valid());
+        debug_assert!(other.is_valid());
+        other >= &self.range_min() && other <= &self.range_max()
+    }
+
+    /// Return true if the given cell intersects this one.
+    pub fn intersects(&self, other: &S2CellId) -> bool {
+        debug_assert!(self.is_valid());
+        debug_assert!(other.is_valid());
+        other.range_min() <= self.range_max() && other.range_max() >= self.range_min()
+    }
+
+    /// Returns how this cell is positioned relative to "other": whether the
+    /// two cells are the same, one contains the other, their boundaries touch
+    /// along an edge or at isolated points, or they are disjoint. The result
+    /// is exact and correctly handles cells on different cube faces.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{CellRelation, S2CellId};
+    ///
+    /// let face0 = S2CellId::from_face(0);
+    /// assert_eq!(face0.relationship(&face0.child(1)), CellRelation::ParentOf);
+    /// assert_eq!(face0.child(1).relationship(&face0), CellRelation::ChildOf);
+    /// assert_eq!(
+    ///     face0.child(0).relationship(&face0.child(1)),
+    ///     CellRelation::EdgeNeighbor
+    /// );
+    /// assert_eq!(
+    ///     face0.child(0).relationship(&face0.child(2)),
+    ///     CellRelation::VertexNeighbor
+    /// );
+    ///
+    /// // Adjacent faces share an edge, while opposite faces are disjoint.
+    /// assert_eq!(
+    ///     face0.relationship(&S2CellId::from_face(1)),
+    ///     CellRelation::EdgeNeighbor
+    /// );
+    /// assert_eq!(
+    ///     face0.relationship(&S2CellId::from_face(3)),
+    ///     CellRelation::Disjoint
+    /// );
+    /// ```
+    pub fn relationship(&self, other: &S2CellId) -> CellRelation {
+        debug_assert!(self.is_valid());
+        debug_assert!(other.is_valid());
+        if self == other {
+            return CellRelation::Same;
+        }
+        if self.contains(other) {
+            return CellRelation::ParentOf;
+        }
+        if other.contains(self) {
+            return CellRelation::ChildOf;
+        }
+
+        // Cells that do not contain each other have disjoint interiors, so we
+        // only need to determine how their closed (i,j) rectangles touch.
+        let (face, rect) = self.get_closed_ij_rect();
+        let (other_face, other_rect) = other.get_closed_ij_rect();
+        let other_rect = if other_face == face {
+            other_rect
+        } else {
+            match clip_to_face_edge(other_face, &other_rect, face) {
+                Some(r) => r,
+                None => return CellRelation::Disjoint,
+            }
+        };
+        let di = rect[1].min(other_rect[1]) - rect[0].max(other_rect[0]);
+        let dj = rect[3].min(other_rect[3]) - rect[2].max(other_rect[2]);
+        if di < 0 || dj < 0 {
+            CellRelation::Disjoint
+        } else {
+            debug_assert!(di == 0 || dj == 0);
+            if di > 0 || dj > 0 {
+                CellRelation::EdgeNeighbor
+            } else {
+                CellRelation::VertexNeighbor
+            }
+        }
+    }
+
+    /// Returns true if the boundaries of this cell and "other" have a segment
+    /// of positive length in common while their interiors are disjoint. For
+    /// cells at the same level, this means that they share two vertices.
+    pub fn shares_edge_with(&self, other: &S2CellId) -> bool {
+        self.relationship(other) == CellRelation::EdgeNeighbor
+    }
+
+    /// Returns true if the boundaries of this cell and "other" intersect while
+    /// their interiors are disjoint, i.e. the cells are edge or vertex
+    /// neighbors. For cells at the same level, this means that they share at
+    /// least one vertex.
+    pub fn shares_vertex_with(&self, other: &S2CellId) -> bool {
+        matches!(
+            self.relationship(other),
+            CellRelation::EdgeNeighbor | CellRelation::VertexNeighbor
+        )
+    }
+
+    /// Returns the face of this cell and the closed range of leaf-cell
+    /// coordinates that it spans, as [i_lo, i_hi, j_lo, j_hi]. The upper
+    /// bounds may equal MAX_SIZE.
+    fn get_closed_ij_rect(&self) -> (i32, [i64; 4]) {
+        let (face, i, j, _) = self.to_face_ij_orientation();
+        let size = self.get_size_ij();
+        let (i, j) = ((i & -size).0 as i64, (j & -size).0 as i64);
+        let size = size.0 as i64;
+        (face, [i, i + size, j, j + size])
+    }
+
+    /// Return the cell at the previous level (i.e. the immediate parent).
+    /// Requires that this is not a face cell.
+    pub fn parent(&self) -> S2CellId {
+        debug_assert!(self.is_valid());
+        debug_assert!(!self.is_face());
+        let new_lsb = self.lsb() << 2;
+        S2CellId::new((self.id & new_lsb.wrapping_neg()) | new_lsb)
+    }
+
+    /// Return the cell at the given level, which must be no greater than the
+    /// current level.
+    pub fn parent_at_level(&self, level: i32) -> S2CellId {
+        debug_assert!(self.is_valid());
+        debug_assert!((0..=self.level()).contains(&level));
+        let new_lsb = S2CellId::lsb_for_level(level);
+        S2CellId::new((self.id & new_lsb.wrapping_neg()) | new_lsb)
+    }
+
+    /// Return the child position (0..3) of this cell's ancestor at the given
+    /// level within its parent. For example, child_position(1) returns the
+    /// position of this cell's level-1 ancestor within its top-level face
+    /// cell. Requires 1 <= level <= self.level().
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::S2CellId;
+    ///
+    /// let id = S2CellId::from_face(3).child(2).child(1);
+    /// assert_eq!(id.child_position(1), 2);
+    /// assert_eq!(id.child_position(2), 1);
+    /// ```
+    pub fn child_position(&self, level: i32) -> i32 {
+        debug_assert!(self.is_valid());
+        debug_assert!((1..=self.level()).contains(&level));
+        ((self.id >> (2 * (S2CellId::MAX_LEVEL - level) + 1)) & 3) as i32
+    }
+
+    /// Return the immediate child of this cell at the given traversal order
+    /// position (in the range 0 to 3). This cell must not be a leaf cell.
+    pub fn child(&self, position: i32) -> S2CellId {
+        debug_assert!(self.is_valid());
+        debug_assert!(!self.is_leaf());
+        debug_assert!((0..4).contains(&position));
+        // To change the level, we need to move the least-significant bit two
+        // positions downward. We do this by subtracting (4 * new_lsb) and adding
+        // new_lsb. Then to advance to the given child cell, we add
+        // (2 * position * new_lsb).
+        let new_lsb = self.lsb() >> 2;
+        S2CellId::new(
+            self.id
+                .wrapping_add((2 * position as u64 + 1).wrapping_mul(new_lsb))
+                .wrapping_sub(4 * new_lsb),
+        )
+    }
+
+    /// Return the first child in a traversal of the children of this cell (in
+    /// Hilbert curve order). This cell must not be a leaf cell.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::s2cell_id::S2CellId;
+    ///
+    /// let face = S2CellId::from_face(0);
+    /// let mut id = face.child_begin();
+    /// while id != face.child_end() {
+    ///     assert_eq!(id.parent(), face);
+    ///     id = id.next();
+    /// }
+    /// ```
+    pub fn child_begin(&self) -> S2CellId {
+        debug_assert!(self.is_valid());
+        debug_assert!(!self.is_leaf());
+        let old_lsb = self.lsb();
+        S2CellId::new(self.id - old_lsb + (old_lsb >> 2))
+    }
+
+    /// Return the first cell in a traversal of the descendants of this cell at
+    /// the given level, which must be no less than the current level.
+    pub fn child_begin_at_level(&self, level: i32) -> S2CellId {
+        debug_assert!(self.is_valid());
+        debug_assert!((self.level()..=S2CellId::MAX_LEVEL).contains(&level));
+        S2CellId::new(self.id - self.lsb() + S2CellId::lsb_for_level(level))
+    }
+
+    /// Return the cell id just past the last child of this cell. Note that the
+    /// result is generally not a valid cell id; it is only suitable for use as
+    /// the end of an iteration that starts at `child_begin()`.
+    pub fn child_end(&self) -> S2CellId {
+        debug_assert!(self.is_valid());
+        debug_assert!(!self.is_leaf());
+        let old_lsb = self.lsb();
+        S2CellId::new(self.id.wrapping_add(old_lsb).wrapping_add(old_lsb >> 2))
+    }
+
+    /// Like `child_end()`, but for descendants at the given level.
+    pub fn child_end_at_level(&self, level: i32) -> S2CellId {
+        debug_assert!(self.is_valid());
+        debug_assert!((self.level()..=S2CellId::MAX_LEVEL).contains(&level));
+        S2CellId::new(
+            self.id
+                .wrapping_add(self.lsb())
+                .wrapping_add(S2CellId::lsb_for_level(level)),
+        )
+    }
+
+    /// Return the next cell at the same level along the Hilbert curve. Works
+    /// correctly when advancing from one face to the next, but does *not* wrap
+    /// around from the last face to the first or vice versa.
+    pub fn next(&self) -> S2CellId {
+        S2CellId::new(self.id.wrapping_add(self.lsb() << 1))
+    }
+
+    /// Return the previous cell at the same level along the Hilbert curve.
+    /// Works correctly when advancing from one face to the next, but does *not*
+    /// wrap around from the last face to the first or vice versa.
+    pub fn prev(&self) -> S2CellId {
+        S2CellId::new(self.id.wrapping_sub(self.lsb() << 1))
+    }
+
+    /// Return the level of the lowest common ancestor of this cell and
+    /// `other`, that is, the maximum level such that
+    /// `parent_at_level(level) == other.parent_at_level(level)`. Returns -1 if
+    /// the two cells do not have any common ancestor (i.e., they are on
+    /// different faces).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::s2cell_id::S2CellId;
+    ///
+    /// let a = S2CellId::from_face(0).child(1).child(2);
+    /// let b = S2CellId::from_face(0).child(1).child(3);
+    /// assert_eq!(a.get_common_ancestor_level(b), 1);
+    /// assert_eq!(a.get_common_ancestor_level(S2CellId::from_face(1)), -1);
+    /// ```
+    pub fn get_common_ancestor_level(&self, other: S2CellId) -> i32 {
+        // Basically we find the first bit position at which the two S2CellIds
+        // differ and convert that to a level. The max() below is necessary for
+        // the case where one S2CellId is a descendant of the other.
+        let bits = (self.id ^ other.id).max(self.lsb().max(other.lsb()));
+        debug_assert!(bits != 0); // Because lsb() is non-zero.
+
+        // Compute the position of the most significant bit, and then map
+        // {0} -> 30, {1,2} -> 29, {3,4} -> 28, ... , {59,60} -> 0, {61,62,63} -> -1.
+        let msb = 63 - bits.leading_zeros() as i32;
+        (60 - msb).max(-1) >> 1
+    }
+
+    /// Converts this cell ID to face, i, j, and orientation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::s2cell_id::S2CellId;
+    ///
+    /// let cell_id = S2CellId::new(0x1234567890ABCDEF);
+    /// let (face, i, j, orientation) = cell_id.to_face_ij_orientation();
+    /// assert!(face >= 0 && face < 6);
+    /// assert!(orientation >= 0 && orientation < 4);
+    /// ```
+    pub fn to_face_ij_orientation(&self) -> (i32, IjCoord, IjCoord, i32) {
+        let (mut i, mut j) = (0, 0);
+        let face = self.face();
+        let mut bits = face & SWAP_MASK;
+
+        fn get_bits(k: i32, id: u64, bits: &mut i32, i: &mut i32, j: &mut i32, lookup_ij: &[u16]) {
+            let nbits = if k == 7 {
+                S2CellId::MAX_LEVEL - 7 * LOOKUP_BITS
+            } else {
+                LOOKUP_BITS
+            };
+            *bits += (((id >> (k * 2 * LOOKUP_BITS + 1)) & ((1 << (2 * nbits)) - 1)) as i32) << 2;
+            *bits = lookup_ij[*bits as usize] as i32;
+            *i += (*bits >> (LOOKUP_BITS + 2)) << (k * LOOKUP_BITS);
+            *j += ((*bits >> 2) & ((1 << LOOKUP_BITS) - 1)) << (k * LOOKUP_BITS);
+            *bits &= SWAP_MASK | INVERT_MASK;
+        }
+
+        for k in (0..8).rev() {
+            get_bits(k, self.id, &mut bits, &mut i, &mut j, &LOOKUP_IJ[..]);
+        }
+
+        debug_assert_eq!(0, POS_TO_ORIENTATION[2]);
+        debug_assert_eq!(SWAP_MASK, POS_TO_ORIENTATION[0]);
+        let orientation = if self.lsb() & 0x1111111111111110 != 0 {
+            bits ^ SWAP_MASK
+        } else {
+            bits
+        };
+
+        (face, IjCoord(i), IjCoord(j), orientation)
+    }
+
+    /// Return the lowest-numbered bit that is on for this cell id, which is
+    /// equal to (uint64_t{1} << (2 * (kMaxLevel - level))).  So for example,
+    /// a.lsb() <= b.lsb() if and only if a.level() >= b.level(), but the
+    /// first test is more efficient.
+    ///
+    /// This is the usual two's-complement idiom `id & -id`, so it is also
+    /// well-defined for invalid ids: none() has no bits set and returns 0,
+    /// while sentinel() returns 1 (the same as a leaf cell).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::S2CellId;
+    ///
+    /// assert_eq!(S2CellId::from_face(0).lsb(), S2CellId::lsb_for_level(0));
+    /// assert_eq!(S2CellId::none().lsb(), 0);
+    /// assert_eq!(S2CellId::sentinel().lsb(), 1);
+    /// ```
+    pub fn lsb(&self) -> u64 {
+        self.id & self.id.wrapping_neg()
+    }
+
+    /// Return the lowest-numbered bit that is on for cells at the given level.
+    pub fn lsb_for_level(level: i32) -> u64 {
+        debug_assert!((0..=S2CellId::MAX_LEVEL).contains(&level));
+        1_u64 << (2 * (S2CellId::MAX_LEVEL - level))
+    }
+
+    /// Return the bound in (u,v)-space for the cell at the given level containing
+    /// the leaf cell with the given (i,j)-coordinates. Coordinates outside the
+    /// face are clamped to its boundary (see s2::coords), so the result is
+    /// always contained by [-1,1]x[-1,1].
+    pub fn ij_level_to_bound_uv(i: IjCoord, j: IjCoord, level: i32) -> R2Rect {
+        let cell_size = S2CellId::get_size_ij_at_level(level);
+        let mut bound = R2Rect::default();
+        for d in 0..2 {
+            let ij = if d == 0 { i } else { j };
+            let ij_lo = ij & -cell_size;
+            let ij_hi = IjCoord(ij_lo.saturating_add(*cell_size));
+            bound[d][0] = st_to_uv(ij_to_st_min_clamped(ij_lo));
+            bound[d][1] = st_to_uv(ij_to_st_min_clamped(ij_hi));
+        }
+        bound
+    }
+
+    /// Append all neighbors of this cell at the given level to "output". Two
+    /// cells X and Y are neighbors if their boundaries intersect but their
+    /// interiors do not. In particular, two cells that intersect at a single
+    /// point are neighbors. Neighbors that are already present in "output"
+    /// are not appended again.
+    ///
+    /// Requires: nbr_level >= self.level().
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::s2cell_id::S2CellId;
+    ///
+    /// // A face cell is adjacent to the four faces that it shares an edge with.
+    /// let mut output = Vec::new();
+    /// S2CellId::from_face(0).append_all_neighbors(0, &mut output);
+    /// assert_eq!(output.len(), 4);
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn append_all_neighbors(&self, nbr_level: i32, output: &mut Vec<S2CellId>) {
+        debug_assert!(nbr_level >= self.level());
+        debug_assert!(nbr_level <= S2CellId::MAX_LEVEL);
+        let (face, i, j, _) = self.to_face_ij_orientation();
+
+        // Find the coordinates of the lower left-hand leaf cell. We need to
+        // normalize (i,j) to a known position within the cell because
+        // nbr_level may be larger than this cell's level.
+        let size = self.get_size_ij();
+        let (i, j) = (i & -size, j & -size);
+
+        let nbr_size = S2CellId::get_size_ij_at_level(nbr_level);
+        debug_assert!(nbr_size <= size);
+
+        // We compute the top-bottom, left-right, and diagonal neighbors in one
+        // pass. The loop test is at the end of the loop to avoid 32-bit overflow.
+        let mut neighbors = Vec::new();
+        let mut k = -nbr_size;
+        loop {
+            let same_face;
+            if k < IjCoord(0) {
+                same_face = j + k >= IjCoord(0);
+            } else if k >= size {
+                same_face = j + k < IjCoord::LIMIT;
+            } else {
+                same_face = true;
+                // Top and bottom neighbors.
+                neighbors.push(
+                    S2CellId::from_face_ij_same(face, i + k, j - nbr_size, j - size >= IjCoord(0))
+                        .parent_at_level(nbr_level),
+                );
+                neighbors.push(
+                    S2CellId::from_face_ij_same(face, i + k, j + size, j + size < IjCoord::LIMIT)
+                        .parent_at_level(nbr_level),
+                );
+            }
+            // Left, right, and diagonal neighbors.
+            neighbors.push(
+                S2CellId::from_face_ij_same(
+                    face,
+                    i - nbr_size,
+                    j + k,
+                    same_face && i - size >= IjCoord(0),
+                )
+                .parent_at_level(nbr_level),
+            );
+            neighbors.push(
+                S2CellId::from_face_ij_same(
+                    face,
+                    i + size,
+                    j + k,
+                    same_face && i + size < IjCoord::LIMIT,
+                )
+                .parent_at_level(nbr_level),
+            );
+            if k >= size {
+                break;
+            }
+            k += nbr_size;
+        }
+
+        // Cells adjacent to a face vertex can produce the same neighbor more
+        // than once.
+        neighbors.sort();
+        neighbors.dedup();
+        neighbors.retain(|id| !output.contains(id));
+        output.extend(neighbors);
+    }
+
+    /// Append the 4 cells at the given level that share the cell vertex
+    /// closest to this cell to "output". (Normally these are neighbors, but
+    /// the cell vertex may be one of the 8 cube vertices, in which case only
+    /// 3 cells are appended.) The first cell appended is always the parent
+    /// of this cell at the given level.
+    ///
+    /// Requires: level < self.level(), so that we can determine which vertex
+    /// this cell is closest to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{s2cell_id::S2CellId, S2Point};
+    ///
+    /// let id = S2CellId::from_point(&S2Point::new(1.0, 0.1, -0.2));
+    /// let mut output = Vec::new();
+    /// id.append_vertex_neighbors(5, &mut output);
+    /// assert_eq!(output.len(), 4);
+    /// assert_eq!(output[0], id.parent_at_level(5));
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn append_vertex_neighbors(&self, level: i32, output: &mut Vec<S2CellId>) {
+        debug_assert!(level >= 0);
+        debug_assert!(level < self.level());
+        let (face, i, j, _) = self.to_face_ij_orientation();
+
+        // Determine the i- and j-offsets to the closest neighboring cell in
+        // each direction. This involves looking at the next bit of "i" and "j"
+        // to determine which quadrant of self.parent_at_level(level) this cell
+        // lies in.
+        let halfsize = S2CellId::get_size_ij_at_level(level + 1);
+        let size = halfsize + halfsize;
+        let (ioffset, isame) = if i & halfsize != IjCoord(0) {
+            (size, i + size < IjCoord::LIMIT)
+        } else {
+            (-size, i - size >= IjCoord(0))
+        };
+        let (joffset, jsame) = if j & halfsize != IjCoord(0) {
+            (size, j + size < IjCoord::LIMIT)
+        } else {
+            (-size, j - size >= IjCoord(0))
+        };
+
+        output.push(self.parent_at_level(level));
+        output
+            .push(S2CellId::from_face_ij_same(face, i + ioffset, j, isame).parent_at_level(level));
+        output
+            .push(S2CellId::from_face_ij_same(face, i, j + joffset, jsame).parent_at_level(level));
+        // If the i- and j-edge neighbors are *both* on a different face, then
+        // this vertex only has three neighbors (it is one of the 8 cube
+        // vertices).
+        if isame || jsame {
+            output.push(
+                S2CellId::from_face_ij_same(face, i + ioffset, j + joffset, isame && jsame)
+                    .parent_at_level(level),
+            );
+        }
+    }
+
+    /// Return the number of descendants of this cell at the given level,
+    /// i.e. 4^(level - self.level()). A cell is its own only descendant at
+    /// its own level.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::s2cell_id::S2CellId;
+    ///
+    /// let id = S2CellId::from_face(1).child(3);
+    /// assert_eq!(id.count_descendants_at_level(1), 1);
+    /// assert_eq!(id.count_descendants_at_level(4), 64);
+    /// assert_eq!(
+    ///     S2CellId::from_face(0).count_descendants_at_level(30),
+    ///     1 << 60
+    /// );
+    /// ```
+    pub fn count_descendants_at_level(&self, level: i32) -> u64 {
+        debug_assert!(self.is_valid());
+        debug_assert!((self.level()..=S2CellId::MAX_LEVEL).contains(&level));
+        1 << (2 * (level - self.level()))
+    }
+
+    /// Append all descendants of this cell at the given level to "output", in
+    /// Hilbert curve order. This appends count_descendants_at_level(level)
+    /// cells, so callers expanding large cells to fine levels should prefer
+    /// try_append_children_at_level().
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::s2cell_id::S2CellId;
+    ///
+    /// let id = S2CellId::from_face(2);
+    /// let mut output = Vec::new();
+    /// id.append_children_at_level(2, &mut output);
+    /// assert_eq!(output.len(), 16);
+    /// assert_eq!(output[0], id.child_begin_at_level(2));
+    /// assert!(output.windows(2).all(|w| w[0].next() == w[1]));
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn append_children_at_level(&self, level: i32, output: &mut Vec<S2CellId>) {
+        // Cap the up-front reservation so that a huge level difference does not
+        // abort on one enormous allocation before any cells are appended. For
+        // the same reason the cells are pushed one at a time, since extend()
+        // would reserve space for all of them based on the iterator's length.
+        const MAX_RESERVE: u64 = 1 << 16;
+        output.reserve(self.count_descendants_at_level(level).min(MAX_RESERVE) as usize);
+        for id in S2CellIdIter::new(
+            self.child_begin_at_level(level),
+            self.child_end_at_level(level),
+        ) {
+            output.push(id);
+        }
+    }
+
+    /// Like append_children_at_level(), but returns an error (and leaves
+    /// "output" unchanged) if the number of descendants exceeds "max_cells".
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::s2cell_id::S2CellId;
+    ///
+    /// let id = S2CellId::from_face(2);
+    /// let mut output = Vec::new();
+    /// assert!(id
+    ///     .try_append_children_at_level(20, 1000, &mut output)
+    ///     .is_err());
+    /// assert!(output.is_empty());
+    /// assert!(id
+    ///     .try_append_children_at_level(3, 1000, &mut output)
+    ///     .is_ok());
+    /// assert_eq!(output.len(), 64);
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn try_append_children_at_level(
+        &self,
+        level: i32,
+        max_cells: usize,
+        output: &mut Vec<S2CellId>,
+    ) -> Result<(), TooManyCellsError> {
+        let count = self.count_descendants_at_level(level);
+        if count > max_cells as u64 {
+            return Err(TooManyCellsError { count, max_cells });
+        }
+        self.append_children_at_level(level, output);
+        Ok(())
+    }
+
+    /// Return a leaf cell given its cube face (range 0..5) and i- and
+    /// j-coordinates, which may be outside the range 0..MAX_SIZE-1 by at most
+    /// one. In that case the coordinates are wrapped onto the adjacent face.
+    fn from_face_ij_wrap(face: i32, i: IjCoord, j: IjCoord) -> S2CellId {
+        // Convert i and j to the coordinates of a leaf cell just beyond the
+        // boundary of this face. This prevents 32-bit overflow in the case
+        // of finding the neighbors of a face cell.
+        let i = *i.clamp(IjCoord(-1), IjCoord::LIMIT);
+        let j = *j.clamp(IjCoord(-1), IjCoord::LIMIT);
+
+        // We want to wrap these coordinates onto the appropriate adjacent face.
+        // The easiest way to do this is to convert the (i,j) coordinates to
+        // (x,y,z) (which yields a point outside the normal face boundary), and
+        // then call xyz_to_face_uv() to project back onto the correct face.
+        //
+        // The code below converts (i,j) to (si,ti), and then (si,ti) to (u,v)
+        // using the linear projection (u=2*s-1 and v=2*t-1). (The code further
+        // below converts back using the inverse projection, s=0.5*(u+1) and
+        // t=0.5*(v+1). Any projection would work here, so we use the simplest.)
+        // We also clamp the (u,v) coordinates so that the point is barely
+        // outside the [-1,1]x[-1,1] face rectangle, since otherwise the
+        // reprojection step (which divides by the new z coordinate) might
+        // change the other coordinates enough so that we end up in the wrong
+        // leaf cell.
+        const SCALE: f64 = 1.0 / S2CellId::MAX_SIZE as f64;
+        const LIMIT: f64 = 1.0 + f64::EPSILON;
+        let u = (SCALE * (2 * (i - S2CellId::MAX_SIZE / 2) + 1) as f64).clamp(-LIMIT, LIMIT);
+        let v = (SCALE * (2 * (j - S2CellId::MAX_SIZE / 2) + 1) as f64).clamp(-LIMIT, LIMIT);
+
+        // Find the leaf cell coordinates on the adjacent face, and convert
+        // them to a cell id at the appropriate level.
+        let (face, u, v) = xyz_to_face_uv(&face_uv_to_xyz(face, u, v));
+        S2CellId::from_face_ij(face, st_to_ij(0.5 * (u + 1.0)), st_to_ij(0.5 * (v + 1.0)))
+    }
+
+    /// Like `from_face_ij`, but wraps the coordinates onto the adjacent face
+    /// when "same_face" is false.
+    fn from_face_ij_same(face: i32, i: IjCoord, j: IjCoord, same_face: bool) -> S2CellId {
+        if same_face {
+            S2CellId::from_face_ij(face, i, j)
+        } else {
+            S2CellId::from_face_ij_wrap(face, i, j)
+        }
+    }
+}
+
+// Return the expanded "u" coordinate of an edge of a (u,v)-rectangle whose
+// maximum |v| is "max_v", moving it by the sine of the expansion distance.
+fn expand_endpoint(u: f64, max_v: f64, sin_dist: f64) -> f64 {
+    // This is based on solving a spherical right triangle, similar to the
+    // calculation in S2Cap::get_rect_bound().
+    let sin_u_shift = sin_dist * ((1.0 + u * u + max_v * max_v) / (1.0 + u * u)).sqrt();
+    let cos_u_shift = (1.0 - sin_u_shift * sin_u_shift).sqrt();
+    // The following is an expansion of tan(atan(u) + asin(sin_u_shift)).
+    (cos_u_shift * u + sin_u_shift) / (cos_u_shift - sin_u_shift * u)
+}
+
+/// Expand a set of cells into the sorted, deduplicated list of cells at the
+/// given level that they intersect. Cells coarser than "level" are replaced
+/// by all of their descendants at that level, and finer cells by their
+/// ancestor at that level. The input may be unsorted and may contain
+/// overlapping cells.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::s2cell_id::{expand_to_level, S2CellId};
+///
+/// let parent = S2CellId::from_face(0).child(1);
+/// // The grandchild is already covered by its ancestor.
+/// let ids = expand_to_level(&[parent.child(2).child(0), parent], 2);
+/// assert_eq!(ids, parent.children_iter().collect::<Vec<_>>());
+/// ```
+#[cfg(feature = "alloc")]
+pub fn expand_to_level(ids: &[S2CellId], level: i32) -> Vec<S2CellId> {
+    debug_assert!((0..=S2CellId::MAX_LEVEL).contains(&level));
+    // Each cell expands to a contiguous range of cells at "level". Sorting
+    // and merging these ranges (rather than the expanded cells) keeps the
+    // output sorted and free of duplicates.
+    let mut ranges: Vec<(S2CellId, S2CellId)> = ids
+        .iter()
+        .map(|id| {
+            debug_assert!(id.is_valid());
+            let id = id.parent_at_level(level.min(id.level()));
+            (id.child_begin_at_level(level), id.child_end_at_level(level))
+        })
+        .collect();
+    ranges.sort_unstable();
+
+    let mut output = Vec::new();
+    // The first cell that has not been appended yet.
+    let mut next = S2CellId::none();
+    for (begin, end) in ranges {
+        let begin = begin.max(next);
+        if begin < end {
+            output.extend(S2CellIdIter::new(begin, end));
+            next = end;
+        }
+    }
+    output
+}
+
+/// An iterator over a contiguous range of cells at the same level, in Hilbert
+/// curve order. The range is half-open: `begin` is the first cell returned and
+/// `end` is the cell just past the last one (which may be an invalid cell id,
+/// e.g. the result of `S2CellId::end()`).
+///
+/// Created by `S2CellId::cells_at_level()`, `S2CellId::iter_along_face()` and
+/// `S2CellId::children_iter()`.
+#[derive(Debug, Clone)]
+pub struct S2CellIdIter {
+    front: u64,
+    back: u64,
+    step: u64,
+}
+
+impl S2CellIdIter {
+    /// Returns an iterator over the cells in the range [begin, end). Both
+    /// arguments must be at the same level, and "begin" must be valid.
+    pub fn new(begin: S2CellId, end: S2CellId) -> S2CellIdIter {
+        debug_assert!(begin.is_valid());
+        debug_assert!(begin <= end);
+        debug_assert_eq!(begin.lsb(), end.lsb());
+        // Advancing a cell to the next cell at the same level adds twice its
+        // lowest set bit, which is what S2CellId::next() does.
+        let step = begin.lsb() << 1;
+        // An id without any bits set (such as S2CellId::none()) has no level,
+        // so return an empty iterator rather than one that never advances.
+        let back = if step == 0 { begin.id() } else { end.id() };
+        S2CellIdIter {
+            front: begin.id(),
+            back,
+            step,
+        }
+    }
+}
+
+impl Iterator for S2CellIdIter {
+    type Item = S2CellId;
+
+    fn next(&mut self) -> Option<S2CellId> {
+        if self.front == self.back {
+            return None;
+        }
+        let id = S2CellId::new(self.front);
+        self.front += self.step;
+        Some(id)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for S2CellIdIter {
+    fn next_back(&mut self) -> Option<S2CellId> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= self.step;
+        Some(S2CellId::new(self.back))
+    }
+}
+
+impl ExactSizeIterator for S2CellIdIter {
+    fn len(&self) -> usize {
+        if self.front == self.back {
+            return 0;
+        }
+        ((self.back - self.front) / self.step) as usize
+    }
+}
+
+impl FusedIterator for S2CellIdIter {}
+
+/// Returns the leaf cell containing the given point, as in
+/// [`S2CellId::from_point`].
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{S2CellId, S2LatLng, S2Point};
+///
+/// let p = S2Point::new(0.0, 0.0, 1.0);
+/// let id: S2CellId = p.into();
+/// assert_eq!(id, S2CellId::from_point(&p));
+/// // The center of a leaf cell maps back to the same cell.
+/// assert_eq!(S2CellId::from(S2Point::from(id)), id);
+/// assert_eq!(S2CellId::from(S2LatLng::from_point(&p)).face(), 2);
+/// ```
+impl From<S2Point> for S2CellId {
+    fn from(p: S2Point) -> S2CellId {
+        S2CellId::from_point(&p)
+    }
+}
+
+/// Returns the leaf cell containing the given normalized S2LatLng, as in
+/// [`S2CellId::from_lat_lng`].
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{S2CellId, S2LatLng};
+///
+/// let ll = S2LatLng::from_degrees(-33.9, 18.4);
+/// let id = S2CellId::from(ll);
+/// assert!(id.is_leaf());
+/// assert_eq!(id, S2CellId::from_point(&ll.to_point()));
+/// ```
+impl From<S2LatLng> for S2CellId {
+    fn from(ll: S2LatLng) -> S2CellId {
+        S2CellId::from_lat_lng(&ll)
+    }
+}
+
+/// Returns the center of the cell as a unit-length point. This normalizes
+/// the result of [`S2CellId::to_point_raw`] so that it can be passed directly
+/// to methods that require unit-length points. The center of a leaf cell
+/// converts back to the same cell, while the center of any other cell
+/// converts back to one of the leaf cells adjacent to its center.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{S2CellId, S2Point};
+///
+/// let p = S2Point::from(S2CellId::from_face(0));
+/// assert_eq!(p, S2Point::new(1.0, 0.0, 0.0));
+/// ```
+impl From<S2CellId> for S2Point {
+    fn from(val: S2CellId) -> S2Point {
+        val.to_point()
+    }
+}
+
+/// Returns the center of the cell, as in [`S2CellId::to_lat_lng`].
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{S2CellId, S2LatLng};
+///
+/// let ll = S2LatLng::from(S2CellId::from_face(2));
+/// assert_eq!(ll.lat().degrees(), 90.0);
+/// ```
+impl From<S2CellId> for S2LatLng {
+    fn from(id: S2CellId) -> S2LatLng {
+        id.to_lat_lng()
+    }
+}
+
+/// Formats the cell as its face followed by the child positions of its
+/// ancestors, e.g. "3/02" for the child 2 of the child 0 of face 3. The
+/// level of the cell is the number of child positions. Invalid cell ids are
+/// formatted as "Invalid: " followed by the id in hex.
+///
+/// The output can be parsed back with [`str::parse`].
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::S2CellId;
+///
+/// let id = S2CellId::from_face(3).child(0).child(2);
+/// assert_eq!(id.to_string(), "3/02");
+/// assert_eq!("3/02".parse::<S2CellId>(), Ok(id));
+/// assert_eq!(S2CellId::none().to_string(), "Invalid: 0000000000000000");
+/// ```
+impl fmt::Display for S2CellId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.is_valid() {
+            return write!(f, "Invalid: {:016x}", self.id);
+        }
+        write!(f, "{}/", self.face())?;
+        for level in 1..=self.level() {
+            write!(f, "{}", self.child_position(level))?;
+        }
+        Ok(())
+    }
+}
+
+/// The relationship between two cells, as returned by
+/// [`S2CellId::relationship`]. The variants describe the first cell relative
+/// to the second one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CellRelation {
+    /// The two cells are identical.
+    Same,
+    /// The first cell is a proper descendant of the second one.
+    ChildOf,
+    /// The first cell is a proper ancestor of the second one.
+    ParentOf,
+    /// The cells do not intersect, not even along their boundaries.
+    Disjoint,
+    /// The interiors are disjoint, but the boundaries share a segment of
+    /// positive length.
+    EdgeNeighbor,
+    /// The interiors are disjoint, and the boundaries only touch at isolated
+    /// points.
+    VertexNeighbor,
+}
+
+// The following functions convert points on the boundary of a face between
+// leaf-cell (i,j) coordinates and cube-space (x,y,z) coordinates without any
+// rounding. Each cube coordinate is represented by the leaf-cell coordinate
+// corresponding to its value, so that -1 maps to 0, +1 maps to MAX_SIZE, and
+// negation maps k to MAX_SIZE - k. This is exact because the (u,v) to (s,t)
+// transformation is symmetric about the origin. See face_uv_to_xyz() for the
+// axis conventions.
+
+#[rustfmt::skip]
+fn face_ij_to_cube(face: i32, i: i64, j: i64) -> [i64; 3] {
+    const L: i64 = S2CellId::MAX_SIZE as i64;
+    match face {
+        0 => [    L,     i,     j],
+        1 => [L - i,     L,     j],
+        2 => [L - i, L - j,     L],
+        3 => [    0, L - j, L - i],
+        4 => [    j,     0, L - i],
+        5 => [    j,     i,     0],
+        _ => panic!("invalid face: {face}"),
+    }
+}
+
+/// The inverse of face_ij_to_cube(). Requires that "p" lies on the given
+/// face, i.e. that its coordinate along the face axis is exactly +/-1.
+#[rustfmt::skip]
+fn cube_to_face_ij(face: i32, p: &[i64; 3]) -> (i64, i64) {
+    const L: i64 = S2CellId::MAX_SIZE as i64;
+    match face {
+        0 => (    p[1],     p[2]),
+        1 => (L - p[0],     p[2]),
+        2 => (L - p[0], L - p[1]),
+        3 => (L - p[2], L - p[1]),
+        4 => (L - p[2],     p[0]),
+        5 => (    p[1],     p[0]),
+        _ => panic!("invalid face: {face}"),
+    }
 }
 
-// impl Into<S2LatLng> for S2CellId {
-//     fn into(self) -> S2LatLng {
-//         todo!()
-//     }
-// }
+/// Given the closed (i,j) rectangle of a cell on "other_face", returns the
+/// part of it that lies on the boundary of "face" expressed in the (i,j)
+/// coordinates of "face", or None if the cell does not touch "face". Since
+/// the cell is convex in (i,j) space, this part is the bounding rectangle of
+/// the cell vertices that lie on "face" (a segment or a single point).
+fn clip_to_face_edge(other_face: i32, rect: &[i64; 4], face: i32) -> Option<[i64; 4]> {
+    let axis = (face % 3) as usize;
+    let axis_value = if face < 3 {
+        S2CellId::MAX_SIZE as i64
+    } else {
+        0
+    };
+    let mut result: Option<[i64; 4]> = None;
+    for (i, j) in [
+        (rect[0], rect[2]),
+        (rect[1], rect[2]),
+        (rect[0], rect[3]),
+        (rect[1], rect[3]),
+    ] {
+        let p = face_ij_to_cube(other_face, i, j);
+        if p[axis] != axis_value {
+            continue;
+        }
+        let (i, j) = cube_to_face_ij(face, &p);
+        result = Some(match result {
+            None => [i, i, j, j],
+            Some(r) => [r[0].min(i), r[1].max(i), r[2].min(j), r[3].max(j)],
+        });
+    }
+    result
+}
+
+/// The error returned when parsing an S2CellId from a string fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseS2CellIdError(());
+
+impl fmt::Display for ParseS2CellIdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(
+            "invalid S2CellId: expected a face (0-5), '/', and up to 30 child positions (0-3)",
+        )
+    }
+}
+
+impl core::error::Error for ParseS2CellIdError {}
+
+/// The error returned by [`S2CellId::try_append_children_at_level`] when a
+/// cell has more descendants at the requested level than the caller allows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TooManyCellsError {
+    /// The number of descendants at the requested level.
+    pub count: u64,
+    /// The maximum number of cells the caller allowed.
+    pub max_cells: usize,
+}
+
+impl fmt::Display for TooManyCellsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "expansion would produce {} cells, more than the limit of {}",
+            self.count, self.max_cells
+        )
+    }
+}
+
+impl core::error::Error for TooManyCellsError {}
+
+impl FromStr for S2CellId {
+    type Err = ParseS2CellIdError;
+
+    /// Parses the format produced by Display, e.g. "3/02".
+    fn from_str(s: &str) -> Result<S2CellId, ParseS2CellIdError> {
+        let bytes = s.as_bytes();
+        if bytes.len() < 2 || bytes.len() - 2 > S2CellId::MAX_LEVEL as usize || bytes[1] != b'/' {
+            return Err(ParseS2CellIdError(()));
+        }
+        let face = match bytes[0] {
+            b @ b'0'..=b'5' => (b - b'0') as i32,
+            _ => return Err(ParseS2CellIdError(())),
+        };
+        let mut id = S2CellId::from_face(face);
+        for &b in &bytes[2..] {
+            match b {
+                b'0'..=b'3' => id = id.child((b - b'0') as i32),
+                _ => return Err(ParseS2CellIdError(())),
+            }
+        }
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, string::ToString};
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::{
+        r1::R1Interval,
+        s1::S1ChordAngle,
+        s2::s2testing::{random_cell_id, random_cell_id_at_level},
+        s2::{face_xyz_to_uv, s2edge_distances, s2metrics::MAX_DIAG, S2Cell},
+    };
+
+    #[test]
+    fn test_from_debug_string() {
+        let mut rng = StdRng::seed_from_u64(32);
+        for _ in 0..100 {
+            let id = random_cell_id(&mut rng);
+            let s = id.to_debug_string();
+            assert_eq!(s.len(), 2 + id.level() as usize);
+            assert_eq!(S2CellId::from_debug_string(&s), Some(id));
+        }
+        for face in 0..S2CellId::NUM_FACES {
+            let id = S2CellId::from_face(face);
+            assert_eq!(S2CellId::from_debug_string(&id.to_debug_string()), Some(id));
+        }
+        assert_eq!(
+            S2CellId::from_debug_string("5/"),
+            Some(S2CellId::from_face(5))
+        );
+        assert_eq!(
+            S2CellId::from_debug_string("2/301"),
+            Some(S2CellId::from_face(2).child(3).child(0).child(1))
+        );
+        // The deepest valid path is a leaf cell.
+        let leaf = "1/".to_string() + &"3".repeat(S2CellId::MAX_LEVEL as usize);
+        let id = S2CellId::from_debug_string(&leaf).unwrap();
+        assert!(id.is_leaf());
+        assert_eq!(id.to_debug_string(), leaf);
+        // Invalid cells have a debug string, but it does not parse.
+        for id in [S2CellId::none(), S2CellId::sentinel()] {
+            assert_eq!(S2CellId::from_debug_string(&id.to_debug_string()), None);
+        }
+        for bad in [
+            "",
+            "3",
+            "3/0124",
+            "6/0",
+            "9/",
+            "a/0",
+            "-1/0",
+            "33/0",
+            "3-012",
+            "3/01 ",
+            "1/0000000000000000000000000000000",
+        ] {
+            assert!(bad.parse::<S2CellId>().is_err(), "{bad:?}");
+            assert_eq!(S2CellId::from_debug_string(bad), None, "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn test_latlng_degrees() {
+        for (lat, lng) in [
+            (40.7128, -74.0060),
+            (-90.0, 0.0),
+            (90.0, 180.0),
+            (0.0, -180.0),
+        ] {
+            let id = S2CellId::from_latlng_degrees(lat, lng);
+            let ll = S2LatLng::from_degrees(lat, lng);
+            assert_eq!(id, S2CellId::from_lat_lng(&ll));
+            assert!(id.is_leaf());
+            for level in [0, 12, S2CellId::MAX_LEVEL] {
+                assert_eq!(
+                    S2CellId::from_latlng_degrees_at_level(lat, lng, level),
+                    id.parent_at_level(level)
+                );
+            }
+
+            // The center of a leaf cell is within one leaf cell width.
+            let (center_lat, center_lng) = id.to_latlng_degrees();
+            let center = S2LatLng::from_degrees(center_lat, center_lng);
+            assert!(center.get_distance(&ll).radians() <= MAX_DIAG.get_value(30));
+        }
+
+        // Longitudes wrap.
+        let id = S2CellId::from_latlng_degrees_at_level(10.0, 20.0, 20);
+        assert_eq!(S2CellId::from_latlng_degrees_at_level(10.0, 380.0, 20), id);
+        assert_eq!(S2CellId::from_latlng_degrees_at_level(10.0, -340.0, 20), id);
+
+        // Invalid arguments.
+        for (lat, lng, level) in [
+            (90.000001, 0.0, 10),
+            (-91.0, 0.0, 10),
+            (f64::NAN, 0.0, 10),
+            (0.0, f64::NAN, 10),
+            (0.0, f64::INFINITY, 10),
+            (0.0, 0.0, -1),
+            (0.0, 0.0, S2CellId::MAX_LEVEL + 1),
+        ] {
+            assert_eq!(
+                S2CellId::from_latlng_degrees_at_level(lat, lng, level),
+                S2CellId::none(),
+                "({lat}, {lng}) at level {level}"
+            );
+        }
+        assert_eq!(S2CellId::from_latlng_degrees(100.0, 0.0), S2CellId::none());
+    }
+
+    #[test]
+    fn test_get_common_ancestor_level() {
+        // Two identical cell ids.
+        assert_eq!(
+            0,
+            S2CellId::from_face(0).get_common_ancestor_level(S2CellId::from_face(0))
+        );
+        assert_eq!(
+            30,
+            S2CellId::from_face(0)
+                .child_begin_at_level(30)
+                .get_common_ancestor_level(S2CellId::from_face(0).child_begin_at_level(30))
+        );
+
+        // One cell id is a descendant of the other.
+        assert_eq!(
+            0,
+            S2CellId::from_face(0)
+                .child_begin_at_level(30)
+                .get_common_ancestor_level(S2CellId::from_face(0))
+        );
+        assert_eq!(
+            0,
+            S2CellId::from_face(5)
+                .get_common_ancestor_level(S2CellId::from_face(5).child_end_at_level(30).prev())
+        );
+
+        // Two cells that have no common ancestor.
+        assert_eq!(
+            -1,
+            S2CellId::from_face(0).get_common_ancestor_level(S2CellId::from_face(5))
+        );
+        assert_eq!(
+            -1,
+            S2CellId::from_face(2)
+                .child_begin_at_level(30)
+                .get_common_ancestor_level(S2CellId::from_face(3).child_end_at_level(20))
+        );
+
+        // Two cells that have a common ancestor distinct from both of them.
+        assert_eq!(
+            8,
+            S2CellId::from_face(5)
+                .child_begin_at_level(9)
+                .next()
+                .child_begin_at_level(15)
+                .get_common_ancestor_level(
+                    S2CellId::from_face(5)
+                        .child_begin_at_level(9)
+                        .child_begin_at_level(20)
+                )
+        );
+        assert_eq!(
+            1,
+            S2CellId::from_face(0)
+                .child_begin_at_level(2)
+                .child_begin_at_level(30)
+                .get_common_ancestor_level(
+                    S2CellId::from_face(0)
+                        .child_begin_at_level(2)
+                        .next()
+                        .next()
+                        .child_begin_at_level(5)
+                )
+        );
+    }
+
+    #[test]
+    fn test_level_for_metrics() {
+        let mut prev = (0, 0, 0);
+        for i in 0..200 {
+            // Decreasing values from about 2 radians down to 1e-12.
+            let value = 2.0 * 0.87_f64.powi(i);
+            let angle = S1Angle::from_radians(value);
+
+            let max_level = S2CellId::level_for_max_edge_length(angle);
+            assert!(max_level == S2CellId::MAX_LEVEL || MAX_EDGE.get_value(max_level) <= value);
+            assert!(max_level == 0 || MAX_EDGE.get_value(max_level - 1) > value);
+
+            let min_level = S2CellId::level_for_min_edge_length(angle);
+            assert!(min_level == 0 || MIN_EDGE.get_value(min_level) >= value);
+            assert!(min_level == S2CellId::MAX_LEVEL || MIN_EDGE.get_value(min_level + 1) < value);
+            // Cells small enough to satisfy the max bound are never larger
+            // than the cells that satisfy the min bound.
+            assert!(min_level <= max_level);
+
+            let area = value * value;
+            let area_level = S2CellId::level_for_max_area(area);
+            assert!(area_level == S2CellId::MAX_LEVEL || MAX_AREA.get_value(area_level) <= area);
+            assert!(area_level == 0 || MAX_AREA.get_value(area_level - 1) > area);
+
+            // Smaller values never yield coarser levels.
+            assert!(max_level >= prev.0 && min_level >= prev.1 && area_level >= prev.2);
+            prev = (max_level, min_level, area_level);
+        }
+        assert_eq!(
+            S2CellId::level_for_max_edge_length(S1Angle::zero()),
+            S2CellId::MAX_LEVEL
+        );
+        assert_eq!(S2CellId::level_for_max_area(10.0), 0);
+    }
+
+    // Returns the number of vertices of "b" that lie on the boundary of "a",
+    // which distinguishes edge neighbors (2) from vertex neighbors (1) when
+    // "b" is no larger than "a".
+    fn num_vertices_on_boundary(a: S2CellId, b: S2CellId) -> usize {
+        let (a, b) = (S2Cell::new(a), S2Cell::new(b));
+        (0..4)
+            .filter(|&k| {
+                let v = b.get_vertex(k);
+                (0..4).any(|e| {
+                    let dist = s2edge_distances::get_distance(
+                        &v,
+                        &a.get_vertex(e),
+                        &a.get_vertex((e + 1) & 3),
+                    );
+                    dist.radians() < 1e-14
+                })
+            })
+            .count()
+    }
+
+    fn check_relationship(a: S2CellId, b: S2CellId, expected: CellRelation) {
+        assert_eq!(a.relationship(&b), expected, "{a:?} {b:?}");
+        let reverse = match expected {
+            CellRelation::ChildOf => CellRelation::ParentOf,
+            CellRelation::ParentOf => CellRelation::ChildOf,
+            other => other,
+        };
+        assert_eq!(b.relationship(&a), reverse, "{b:?} {a:?}");
+        assert_eq!(
+            a.shares_edge_with(&b),
+            expected == CellRelation::EdgeNeighbor
+        );
+        assert_eq!(
+            a.shares_vertex_with(&b),
+            matches!(
+                expected,
+                CellRelation::EdgeNeighbor | CellRelation::VertexNeighbor
+            )
+        );
+    }
+
+    #[test]
+    fn test_relationship() {
+        let face = S2CellId::from_face(4);
+        let id = face.child(3).child(0);
+        check_relationship(id, id, CellRelation::Same);
+        check_relationship(face, id, CellRelation::ParentOf);
+        check_relationship(id.child(2).child(1), id, CellRelation::ChildOf);
+
+        // The children of a cell are numbered along a Hilbert curve, so
+        // consecutive children share an edge while children 0 and 2 only
+        // touch at the center of the parent.
+        for k in 0..3 {
+            check_relationship(id.child(k), id.child(k + 1), CellRelation::EdgeNeighbor);
+        }
+        check_relationship(id.child(0), id.child(2), CellRelation::VertexNeighbor);
+        check_relationship(id.child(1), id.child(3), CellRelation::VertexNeighbor);
+
+        // Cells at different levels.
+        check_relationship(
+            id.child(0),
+            id.child(1).child(0),
+            CellRelation::EdgeNeighbor,
+        );
+        check_relationship(
+            face.child(0),
+            face.child(3).child(3),
+            CellRelation::Disjoint,
+        );
+
+        // Faces: each face touches four others along an edge and is disjoint
+        // from its opposite face.
+        for f in 0..S2CellId::NUM_FACES {
+            for g in 0..S2CellId::NUM_FACES {
+                let expected = if f == g {
+                    CellRelation::Same
+                } else if (f + 3) % 6 == g {
+                    CellRelation::Disjoint
+                } else {
+                    CellRelation::EdgeNeighbor
+                };
+                check_relationship(S2CellId::from_face(f), S2CellId::from_face(g), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_relationship_across_faces() {
+        // Cells along the edge between faces 0 and 1 (u = 1 on face 0).
+        let level = 6;
+        let size = S2CellId::get_size_ij_at_level(level);
+        let last = IjCoord::LIMIT - IjCoord(1);
+        for j in [IjCoord(0), IjCoord(5 * size.0), IjCoord::LIMIT - size] {
+            let id = S2CellId::from_face_ij(0, last, j).parent_at_level(level);
+            let mut neighbors = Vec::new();
+            id.append_all_neighbors(level, &mut neighbors);
+            let across: Vec<_> = neighbors.iter().filter(|n| n.face() != 0).collect();
+            assert!(!across.is_empty());
+            for n in across {
+                let expected = match num_vertices_on_boundary(id, *n) {
+                    2 => CellRelation::EdgeNeighbor,
+                    1 => CellRelation::VertexNeighbor,
+                    count => panic!("{id:?} {n:?} {count}"),
+                };
+                check_relationship(id, *n, expected);
+            }
+        }
+
+        // The three cells that meet at a cube vertex each share an edge with
+        // the other two.
+        let corner = S2CellId::from_face_ij(0, last, last);
+        let mut cells = Vec::new();
+        corner.append_vertex_neighbors(level, &mut cells);
+        assert_eq!(cells.len(), 3);
+        for a in &cells {
+            for b in &cells {
+                let expected = if a == b {
+                    CellRelation::Same
+                } else {
+                    CellRelation::EdgeNeighbor
+                };
+                check_relationship(*a, *b, expected);
+            }
+        }
+        // Only three cells meet at a cube vertex rather than four, so a corner
+        // cell has 7 neighbors rather than 8.
+        let mut neighbors = Vec::new();
+        cells[0].append_all_neighbors(level, &mut neighbors);
+        assert_eq!(neighbors.len(), 7);
+    }
+
+    #[test]
+    fn test_relationship_brute_force() {
+        let mut rng = StdRng::seed_from_u64(72);
+        for _ in 0..200 {
+            let level = rng.gen_range(0..16);
+            let id = random_cell_id_at_level(&mut rng, level);
+            let nbr_level = level + rng.gen_range(0..3);
+            let mut neighbors = Vec::new();
+            id.append_all_neighbors(nbr_level, &mut neighbors);
+            for n in &neighbors {
+                let expected = match num_vertices_on_boundary(id, *n) {
+                    2 => CellRelation::EdgeNeighbor,
+                    1 => CellRelation::VertexNeighbor,
+                    count => panic!("{id:?} {n:?} {count}"),
+                };
+                check_relationship(id, *n, expected);
+            }
+            // Cells in the next ring out are disjoint from "id".
+            for n in &neighbors {
+                let mut ring = Vec::new();
+                n.append_all_neighbors(nbr_level, &mut ring);
+                for r in ring {
+                    if !neighbors.contains(&r) && !id.intersects(&r) {
+                        assert_eq!(num_vertices_on_boundary(id, r), 0);
+                        check_relationship(id, r, CellRelation::Disjoint);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_all_cells_at_level() {
+        for level in 0..=4 {
+            let cells: Vec<S2CellId> = S2CellId::all_cells_at_level(level).collect();
+            assert_eq!(cells.len(), 6 << (2 * level));
+            assert!(cells.iter().all(|id| id.is_valid() && id.level() == level));
+            assert!(cells.windows(2).all(|w| w[0] < w[1]));
+            assert_eq!(cells[0], S2CellId::from_face(0).child_begin_at_level(level));
+            assert_eq!(
+                *cells.last().unwrap(),
+                S2CellId::from_face(5).child_end_at_level(level).prev()
+            );
+        }
+
+        // Cells at the next level are the children of the cells at this level,
+        // in the same order.
+        let children: Vec<S2CellId> = S2CellId::all_cells_at_level(3)
+            .flat_map(|id| (0..4).map(move |k| id.child(k)))
+            .collect();
+        assert!(children.into_iter().eq(S2CellId::all_cells_at_level(4)));
+
+        // The iterator is lazy, so taking a prefix of the leaf cells is cheap.
+        let leaves: Vec<S2CellId> = S2CellId::all_cells_at_level(30).take(3).collect();
+        assert_eq!(leaves[0], S2CellId::from_face(0).range_min());
+        assert_eq!(leaves[2], leaves[0].next().next());
+    }
+
+    #[test]
+    fn test_iter_along_face() {
+        for level in 0..=4 {
+            let all: Vec<S2CellId> = S2CellId::all_cells_at_level(level).collect();
+            for face in 0..S2CellId::NUM_FACES {
+                let cells = S2CellId::iter_along_face(face, level);
+                assert_eq!(cells.len(), 1 << (2 * level));
+                assert!(cells
+                    .clone()
+                    .eq(all.iter().copied().filter(|id| id.face() == face)));
+                let mut prev = S2CellId::from_face(face).child_end_at_level(level);
+                for id in cells.rev() {
+                    assert_eq!(id, prev.prev());
+                    prev = id;
+                }
+            }
+        }
+        // Leaf cells are enumerated lazily.
+        let last = S2CellId::iter_along_face(5, 30).next_back().unwrap();
+        assert_eq!(last, S2CellId::from_face(5).range_max());
+    }
+
+    #[test]
+    fn test_get_size_at_level() {
+        assert_eq!(S2CellId::get_size_ij_at_level(0), IjCoord(1 << 30));
+        assert_eq!(S2CellId::get_size_ij_at_level(0), IjCoord::LIMIT);
+        assert_eq!(S2CellId::get_size_ij_at_level(29), IjCoord(2));
+        assert_eq!(S2CellId::get_size_ij_at_level(30), IjCoord(1));
+        assert_eq!(S2CellId::get_size_st_at_level(0), 1.0);
+        assert_eq!(S2CellId::get_size_st_at_level(30), 1.0 / (1 << 30) as f64);
+
+        // Sizes halve exactly at each level, and the (s,t) size is consistent
+        // with the (i,j) size.
+        for level in 1..=S2CellId::MAX_LEVEL {
+            assert_eq!(
+                S2CellId::get_size_ij_at_level(level).0 * 2,
+                S2CellId::get_size_ij_at_level(level - 1).0
+            );
+            assert_eq!(
+                S2CellId::get_size_st_at_level(level) * 2.0,
+                S2CellId::get_size_st_at_level(level - 1)
+            );
+            assert_eq!(
+                S2CellId::get_size_st_at_level(level),
+                S2CellId::get_size_ij_at_level(level).0 as f64 / S2CellId::MAX_SIZE as f64
+            );
+        }
+
+        let face = S2CellId::from_face(2);
+        assert_eq!(face.get_size_ij(), IjCoord(1 << 30));
+        assert_eq!(face.get_size_st(), 1.0);
+        let leaf = face.child_begin_at_level(30);
+        assert_eq!(leaf.get_size_ij(), IjCoord(1));
+    }
+
+    #[test]
+    fn test_lsb_for_level() {
+        assert_eq!(S2CellId::lsb_for_level(0), 1 << 60);
+        assert_eq!(S2CellId::lsb_for_level(30), 1);
+        for level in 0..=S2CellId::MAX_LEVEL {
+            let id = S2CellId::from_face(1).child_begin_at_level(level);
+            assert_eq!(id.lsb(), S2CellId::lsb_for_level(level));
+        }
+    }
+
+    #[test]
+    fn test_append_all_neighbors() {
+        // A cell in the interior of a face has 8 neighbors at its own level.
+        let id = S2CellId::from_point(&S2Point::new(1.0, 0.1, -0.2)).parent_at_level(10);
+        let mut output = Vec::new();
+        id.append_all_neighbors(10, &mut output);
+        assert_eq!(output.len(), 8);
+        for nbr in &output {
+            assert_eq!(nbr.level(), 10);
+            assert_ne!(*nbr, id);
+            assert_eq!(nbr.face(), id.face());
+        }
+
+        // Calling it again appends nothing since all neighbors are present.
+        id.append_all_neighbors(10, &mut output);
+        assert_eq!(output.len(), 8);
+
+        // At a deeper level there are 4 neighbors along each edge plus the 4
+        // diagonal neighbors, and all of them touch the original cell.
+        let mut output = Vec::new();
+        id.append_all_neighbors(12, &mut output);
+        assert_eq!(output.len(), 4 * 4 + 4);
+        for nbr in &output {
+            assert!(!id.intersects(nbr));
+            assert!(output.iter().filter(|x| *x == nbr).count() == 1);
+        }
+
+        // The leaf cell at a face corner has only 7 neighbors, since only three
+        // faces meet at a cube vertex.
+        let corner = S2CellId::from_face_ij(1, IjCoord(0), IjCoord(0));
+        let mut output = Vec::new();
+        corner.append_all_neighbors(30, &mut output);
+        assert_eq!(output.len(), 7);
+        assert!(output.iter().filter(|x| x.face() != 1).count() == 4);
+
+        // Each face cell is adjacent to all faces except its antipodal face.
+        for face in 0..6 {
+            let mut output = Vec::new();
+            S2CellId::from_face(face).append_all_neighbors(0, &mut output);
+            let mut faces: Vec<i32> = output.iter().map(|x| x.face()).collect();
+            faces.sort();
+            let expected: Vec<i32> = (0..6)
+                .filter(|f| *f != face && *f != (face + 3) % 6)
+                .collect();
+            assert_eq!(faces, expected);
+        }
+    }
+
+    #[test]
+    fn test_append_children_at_level() {
+        let face = S2CellId::from_face(3);
+        let mut output = Vec::new();
+        face.append_children_at_level(5, &mut output);
+        assert_eq!(output.len(), 1024);
+        assert_eq!(face.count_descendants_at_level(5), 1024);
+        assert!(output.windows(2).all(|w| w[0] < w[1]));
+        assert!(output.iter().all(|id| id.level() == 5 && face.contains(id)));
+        assert_eq!(output[0], face.child_begin_at_level(5));
+        assert_eq!(output[1023].next(), face.child_end_at_level(5));
+
+        // Appending preserves existing contents.
+        face.append_children_at_level(3, &mut output);
+        assert_eq!(output.len(), 1024 + 64);
+        // A cell at the target level is its own descendant.
+        let leaf = S2CellId::from_point(&S2Point::new(1.0, 2.0, 3.0));
+        let mut output = Vec::new();
+        leaf.append_children_at_level(S2CellId::MAX_LEVEL, &mut output);
+        assert_eq!(output, [leaf]);
+
+        let err = face
+            .try_append_children_at_level(S2CellId::MAX_LEVEL, 1 << 20, &mut output)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            TooManyCellsError {
+                count: 1 << 60,
+                max_cells: 1 << 20
+            }
+        );
+        assert_eq!(output, [leaf]);
+        assert!(face
+            .try_append_children_at_level(5, 1024, &mut output)
+            .is_ok());
+        assert_eq!(output.len(), 1025);
+    }
+
+    #[test]
+    fn test_expand_to_level() {
+        // Reference implementation: expand everything, then sort and dedup.
+        fn expand_slow(ids: &[S2CellId], level: i32) -> Vec<S2CellId> {
+            let mut output = Vec::new();
+            for id in ids {
+                id.parent_at_level(level.min(id.level()))
+                    .append_children_at_level(level, &mut output);
+            }
+            output.sort();
+            output.dedup();
+            output
+        }
+
+        let face = S2CellId::from_face(1);
+        assert_eq!(expand_to_level(&[face], 5).len(), 1024);
+        assert!(expand_to_level(&[], 5).is_empty());
+
+        // Overlapping inputs, including a duplicate, a descendant before and
+        // after its ancestor, and a cell finer than the target level.
+        let parent = face.child(2);
+        let ids = [
+            parent.child(0),
+            parent.child(1).child(3),
+            parent,
+            parent.child(3).child(1).child(2),
+            parent.child(3).child(1).child(2),
+            face.child(3).child(0).child(0).child(0),
+        ];
+        let expected = expand_slow(&ids, 3);
+        assert_eq!(expected.len(), 16 + 1);
+        assert_eq!(expand_to_level(&ids, 3), expected);
+
+        // The last cell of the last face expands up to S2CellId::end().
+        let last = S2CellId::from_face(5).child(3);
+        assert_eq!(
+            expand_to_level(&[last, last.child(3)], 2),
+            expand_slow(&[last], 2)
+        );
+
+        // Random overlapping and unsorted inputs.
+        let mut rng = StdRng::seed_from_u64(37);
+        for _ in 0..200 {
+            let ids: Vec<S2CellId> = (0..rng.gen_range(0..10))
+                .map(|_| {
+                    let level = rng.gen_range(0..7);
+                    random_cell_id_at_level(&mut rng, level)
+                })
+                .collect();
+            let level = rng.gen_range(0..7);
+            assert_eq!(expand_to_level(&ids, level), expand_slow(&ids, level));
+        }
+    }
+
+    #[test]
+    fn test_append_vertex_neighbors() {
+        // The four cells share a vertex, which is within the cell's parent.
+        let id = S2CellId::from_point(&S2Point::new(1.0, 0.1, -0.2));
+        let mut output = Vec::new();
+        id.append_vertex_neighbors(10, &mut output);
+        assert_eq!(output.len(), 4);
+        assert_eq!(output[0], id.parent_at_level(10));
+        let mut sorted = output.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(sorted.len(), 4);
+        for nbr in &output[1..] {
+            assert_eq!(nbr.level(), 10);
+            assert!(!nbr.intersects(&output[0]));
+        }
+
+        // A cube vertex is shared by only three face cells.
+        let corner = S2CellId::from_face_ij(1, IjCoord(0), IjCoord(0));
+        let mut output = Vec::new();
+        corner.append_vertex_neighbors(0, &mut output);
+        let mut faces: Vec<i32> = output.iter().map(|x| x.face()).collect();
+        faces.sort();
+        faces.dedup();
+        assert_eq!(faces.len(), 3);
+        assert!(faces.contains(&1));
+        assert!(output.iter().all(|x| x.is_face()));
+    }
+
+    #[test]
+    fn test_common_ancestor_matches_parent() {
+        let a = S2CellId::from_face(4).child(2).child(1).child(3).child(0);
+        let b = S2CellId::from_face(4).child(2).child(1).child(0);
+        let level = a.get_common_ancestor_level(b);
+        assert_eq!(level, 2);
+        assert_eq!(a.parent_at_level(level), b.parent_at_level(level));
+        assert_ne!(a.parent_at_level(level + 1), b.parent_at_level(level + 1));
+    }
+
+    #[test]
+    fn test_cell_id_iterators() {
+        assert_eq!(S2CellId::cells_at_level(1).count(), 24);
+        assert_eq!(S2CellId::cells_at_level(0).len(), 6);
+
+        // size_hint() is exact at every step, from either end.
+        let mut iter = S2CellId::cells_at_level(2);
+        let mut expected = 6 * 16;
+        while expected > 0 {
+            assert_eq!(iter.size_hint(), (expected, Some(expected)));
+            if expected % 2 == 0 {
+                iter.next().unwrap();
+            } else {
+                iter.next_back().unwrap();
+            }
+            expected -= 1;
+        }
+        assert_eq!(iter.size_hint(), (0, Some(0)));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+
+        // Reversing the children iterator yields children 3..0.
+        let id = S2CellId::from_face(4).child(1).child(2);
+        let reversed: Vec<S2CellId> = id.children_iter().rev().collect();
+        assert_eq!(
+            reversed,
+            (0..4).rev().map(|k| id.child(k)).collect::<Vec<_>>()
+        );
+
+        // The children of the last cell on face 5 are handled correctly even
+        // though the end of the range lies past the last valid cell id.
+        let last = S2CellId::end(3).prev();
+        assert_eq!(last.children_iter().len(), 4);
+        assert!(last.children_iter().eq((0..4).map(|k| last.child(k))));
+
+        let leaf_range = id.leaf_range();
+        assert_eq!(*leaf_range.start(), id.range_min().id());
+        assert_eq!(*leaf_range.end(), id.range_max().id());
+        let leaf = S2CellId::from_face(0).child_begin_at_level(S2CellId::MAX_LEVEL);
+        assert_eq!(leaf.leaf_range(), leaf.id()..=leaf.id());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "begin.is_valid()")]
+    fn test_cell_id_iter_rejects_invalid_begin() {
+        S2CellIdIter::new(S2CellId::none(), S2CellId::none());
+    }
+
+    #[test]
+    #[cfg(not(debug_assertions))]
+    fn test_cell_id_iter_invalid_begin_is_empty() {
+        let mut iter = S2CellIdIter::new(S2CellId::none(), S2CellId::none());
+        assert_eq!(iter.len(), 0);
+        assert_eq!(iter.next(), None);
+    }
+
+    // Returns the (x,y) coordinates of the "d"-th cell of a Hilbert curve over
+    // a 2**bits x 2**bits grid, using the classic iterative construction that
+    // builds the coordinates one bit at a time from the lowest level up. The
+    // curve starts at (0,0) and ends at (2**bits - 1, 0), which is S2's
+    // canonical orientation with x = i and y = j. This is independent of the
+    // POS_TO_IJ and POS_TO_ORIENTATION tables, so it can be used to check them.
+    fn hilbert_d2xy(bits: i32, d: u64) -> (u64, u64) {
+        let (mut x, mut y) = (0, 0);
+        let mut t = d;
+        for level in 0..bits {
+            let s = 1_u64 << level;
+            let rx = (t >> 1) & 1;
+            let ry = (t ^ rx) & 1;
+            // Rotate the sub-curve built so far into the next quadrant.
+            if ry == 0 {
+                if rx == 1 {
+                    x = s - 1 - x;
+                    y = s - 1 - y;
+                }
+                core::mem::swap(&mut x, &mut y);
+            }
+            x += s * rx;
+            y += s * ry;
+            t >>= 2;
+        }
+        (x, y)
+    }
+
+    // Returns the S2 orientation of a sub-curve that enters its cell at the
+    // given corner and leaves it at the other given corner, where corners are
+    // (i,j) pairs of 0 or 1 in the cell's own coordinates. The canonical curve
+    // runs from (0,0) to (1,0), swapping i and j makes it end at (0,1), and
+    // inverting i and j makes it start at (1,1).
+    fn hilbert_orientation(entry: (u64, u64), exit: (u64, u64)) -> i32 {
+        assert!(entry == (0, 0) || entry == (1, 1), "{entry:?}");
+        assert!(exit == (1, 0) || exit == (0, 1), "{exit:?}");
+        let invert = entry == (1, 1);
+        let swap = (exit == (0, 1)) != invert;
+        (if swap { SWAP_MASK } else { 0 }) | (if invert { INVERT_MASK } else { 0 })
+    }
+
+    #[test]
+    fn test_lookup_tables() {
+        // The tables baked in at compile time must match the same construction
+        // performed at runtime.
+        let (lookup_pos, lookup_ij) = core::hint::black_box(init_lookup_tables)();
+        assert_eq!(LOOKUP_POS, lookup_pos);
+        assert_eq!(LOOKUP_IJ, lookup_ij);
+
+        // They must also agree with a Hilbert curve built independently of the
+        // position tables. Each table cell covers a 2x2 block of the next finer
+        // level, and the first and last cells of that block give the corners
+        // where the curve enters and leaves it, and therefore its orientation.
+        for orientation in 0..4 {
+            for pos in 0..(1 << (2 * LOOKUP_BITS)) {
+                let fine: Vec<(u64, u64)> = (0..4)
+                    .map(|k| {
+                        let (mut x, mut y) = hilbert_d2xy(LOOKUP_BITS + 1, 4 * pos as u64 + k);
+                        if orientation & SWAP_MASK != 0 {
+                            core::mem::swap(&mut x, &mut y);
+                        }
+                        if orientation & INVERT_MASK != 0 {
+                            let max = (1 << (LOOKUP_BITS + 1)) - 1;
+                            (x, y) = (max - x, max - y);
+                        }
+                        (x, y)
+                    })
+                    .collect();
+                let ij = (((fine[0].0 >> 1) << LOOKUP_BITS) + (fine[0].1 >> 1)) as usize;
+                let sub_orientation = hilbert_orientation(
+                    (fine[0].0 & 1, fine[0].1 & 1),
+                    (fine[3].0 & 1, fine[3].1 & 1),
+                );
+                let k = (ij << 2) + orientation as usize;
+                assert_eq!(
+                    LOOKUP_POS[k] as i32,
+                    (pos << 2) + sub_orientation,
+                    "lookup_pos[{k}]"
+                );
+                let k = ((pos << 2) + orientation) as usize;
+                assert_eq!(
+                    LOOKUP_IJ[k] as i32,
+                    ((ij as i32) << 2) + sub_orientation,
+                    "lookup_ij[{k}]"
+                );
+            }
+        }
+
+        // The two tables are inverses of each other (ignoring the orientation
+        // bits of the result).
+        for ij in 0..(1 << (2 * LOOKUP_BITS)) {
+            for orientation in 0..4 {
+                let pos = LOOKUP_POS[(ij << 2) + orientation] as usize;
+                let back = LOOKUP_IJ[((pos >> 2) << 2) + orientation] as usize;
+                assert_eq!(back >> 2, ij);
+                assert_eq!(back & 3, pos & 3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_lat_lngs() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let points: Vec<S2LatLng> = (0..5000)
+            .map(|_| {
+                S2LatLng::from_degrees(rng.gen_range(-90.0..=90.0), rng.gen_range(-180.0..180.0))
+            })
+            .collect();
+        for level in 0..=S2CellId::MAX_LEVEL {
+            let batch = S2CellId::from_lat_lngs(&points, level);
+            assert_eq!(batch.len(), points.len());
+            for (id, ll) in batch.iter().zip(&points) {
+                assert_eq!(*id, S2CellId::from_lat_lng(ll).parent_at_level(level));
+            }
+        }
+        assert!(S2CellId::from_lat_lngs(&[], 10).is_empty());
+    }
+
+    #[test]
+    fn test_display_and_from_str() {
+        for face in 0..S2CellId::NUM_FACES {
+            let id = S2CellId::from_face(face);
+            assert_eq!(id.to_string(), format!("{face}/"));
+            assert_eq!(id.to_string().parse::<S2CellId>(), Ok(id));
+        }
+        let leaf = S2CellId::from_point(&S2Point::new(1.0, 0.0, 0.0));
+        assert_eq!(leaf.to_string(), "0/200000000000000000000000000000");
+        assert_eq!(
+            S2CellId::sentinel().to_string(),
+            "Invalid: ffffffffffffffff"
+        );
+
+        let mut rng = StdRng::seed_from_u64(16);
+        for _ in 0..100 {
+            let id = random_cell_id(&mut rng);
+            let s = id.to_string();
+            assert_eq!(s.len(), 2 + id.level() as usize);
+            assert_eq!(s.parse::<S2CellId>(), Ok(id));
+        }
+
+        for s in [
+            "",
+            "0",
+            "6/",
+            "0-",
+            "/0",
+            "0/4",
+            "0/01x",
+            "1/0000000000000000000000000000000",
+        ] {
+            assert!(s.parse::<S2CellId>().is_err(), "{s:?}");
+        }
+    }
+
+    #[test]
+    fn test_conversions() {
+        let mut rng = StdRng::seed_from_u64(17);
+        for _ in 0..1000 {
+            let id = random_cell_id_at_level(&mut rng, S2CellId::MAX_LEVEL);
+            let p = S2Point::from(id);
+            assert!(p.is_unit_length());
+            assert_eq!(S2CellId::from(p), id);
+            assert_eq!(S2CellId::from(p), S2CellId::from_point(&p));
+            let ll = S2LatLng::from_point(&p);
+            assert_eq!(S2CellId::from(ll), S2CellId::from_lat_lng(&ll));
+        }
+    }
+
+    #[test]
+    fn test_from_face_ij_round_trip() {
+        let mut rng = StdRng::seed_from_u64(36);
+        for _ in 0..10000 {
+            let leaf = random_cell_id_at_level(&mut rng, S2CellId::MAX_LEVEL);
+            let (f, i, j, _) = leaf.to_face_ij_orientation();
+            assert_eq!(S2CellId::from_face_ij(f, i, j), leaf);
+        }
+
+        // The corners of every face.
+        let (first, last) = (IjCoord(0), IjCoord::LIMIT - IjCoord(1));
+        for face in 0..S2CellId::NUM_FACES {
+            for (i, j) in [(first, first), (last, first), (first, last), (last, last)] {
+                let leaf = S2CellId::from_face_ij(face, i, j);
+                assert!(leaf.is_leaf());
+                let (f, ii, jj, _) = leaf.to_face_ij_orientation();
+                assert_eq!((f, ii, jj), (face, i, j));
+            }
+            assert_eq!(
+                S2CellId::from_face_ij(face, first, first),
+                S2CellId::from_face(face).child_begin_at_level(S2CellId::MAX_LEVEL)
+            );
+        }
+    }
+
+    #[test]
+    fn test_tokens() {
+        assert_eq!(S2CellId::none().to_token(), "X");
+        assert_eq!(S2CellId::from_token("X"), S2CellId::none());
+        for face in 0..S2CellId::NUM_FACES {
+            let id = S2CellId::from_face(face);
+            assert_eq!(id.to_token(), format!("{:x}", 2 * face + 1));
+        }
+        let leaf = S2CellId::from_point(&S2Point::new(1.0, 0.0, 0.0));
+        assert_eq!(leaf.to_token(), "1000000000000001");
+
+        // Tokens round trip and preserve ordering.
+        let mut rng = StdRng::seed_from_u64(20);
+        let mut ids = Vec::new();
+        for _ in 0..1000 {
+            let id = random_cell_id(&mut rng);
+            assert_eq!(S2CellId::from_token(&id.to_token()), id);
+            assert_eq!(S2CellId::from_token(&id.to_token().to_uppercase()), id);
+            ids.push(id);
+        }
+        ids.sort();
+        let tokens: Vec<String> = ids.iter().map(S2CellId::to_token).collect();
+        assert!(tokens.windows(2).all(|w| w[0] <= w[1]));
+
+        // Malformed tokens.
+        for token in ["", "12345678901234567", "3g", "1 ", "+1", "\u{e9}"] {
+            assert_eq!(S2CellId::from_token(token), S2CellId::none(), "{token:?}");
+        }
+    }
+
+    #[test]
+    fn test_get_center_st_and_uv() {
+        // Face cells are centered at the middle of the face in both (s,t) and
+        // (u,v) coordinates.
+        for face in 0..S2CellId::NUM_FACES {
+            let id = S2CellId::from_face(face);
+            assert_eq!(id.get_center_st(), R2Point::new(0.5, 0.5));
+            assert_eq!(id.get_center_uv(), R2Point::new(0.0, 0.0));
+        }
+
+        let mut rng = StdRng::seed_from_u64(24);
+        for _ in 0..1000 {
+            let level = rng.gen_range(0..S2CellId::MAX_LEVEL);
+            let id = random_cell_id_at_level(&mut rng, level);
+
+            // The center projects to the point returned by to_point_raw().
+            let uv = id.get_center_uv();
+            let st = id.get_center_st();
+            assert_eq!(uv, R2Point::new(st_to_uv(st.x()), st_to_uv(st.y())));
+            let p = face_uv_to_xyz(id.face(), uv[0], uv[1]);
+            assert!((p - id.to_point_raw()).norm() <= 1e-15);
+
+            // The center of a non-leaf cell is the vertex shared by its
+            // children, and lies within the bound of the cell.
+            let (_, i, j, _) = id.to_face_ij_orientation();
+            let bound = S2CellId::ij_level_to_bound_uv(i, j, level);
+            assert!(bound.contains(&uv));
+
+            let size = id.get_size_st();
+            let bound_st = id.get_bound_st();
+            assert_eq!(bound_st.get_center(), st);
+            assert_eq!(bound_st.hi() - bound_st.lo(), R2Point::new(size, size));
+            for k in 0..4 {
+                let child = id.child(k).get_center_st();
+                assert_eq!((child - st).abs(), R2Point::new(0.25 * size, 0.25 * size));
+            }
+        }
+
+        // Leaf coordinates past the face edge are clamped to it.
+        for level in 0..=S2CellId::MAX_LEVEL {
+            let outside = S2CellId::ij_level_to_bound_uv(IjCoord(-1), IjCoord::LIMIT, level);
+            assert_eq!((outside.x().lo(), outside.x().hi()), (-1.0, -1.0));
+            assert_eq!((outside.y().lo(), outside.y().hi()), (1.0, 1.0));
+        }
+    }
+
+    #[test]
+    fn test_get_bound_uv() {
+        let full = R1Interval::new(-1.0, 1.0);
+        for face in 0..S2CellId::NUM_FACES {
+            let bound = S2CellId::from_face(face).get_bound_uv();
+            assert_eq!(*bound.x(), full);
+            assert_eq!(*bound.y(), full);
+        }
+
+        let mut rng = StdRng::seed_from_u64(43);
+        for _ in 0..1000 {
+            let id = random_cell_id(&mut rng);
+
+            let uv = id.get_bound_uv();
+            assert!(uv.contains(&id.get_center_uv()));
+            assert!(id.get_bound_st().contains(&id.get_center_st()));
+            let cell_uv = *S2Cell::new(id).get_bound_uv();
+            let st = id.get_bound_st();
+            for d in 0..2 {
+                assert_eq!(uv[d], cell_uv[d]);
+                assert_eq!(uv[d].lo(), st_to_uv(st[d].lo()));
+                assert_eq!(uv[d].hi(), st_to_uv(st[d].hi()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_expanded_by_distance_uv() {
+        let max_dist_degrees = 10.0;
+        let mut rng = StdRng::seed_from_u64(46);
+        for _ in 0..100 {
+            let level = rng.gen_range(3..S2CellId::MAX_LEVEL);
+            let id = random_cell_id_at_level(&mut rng, level);
+            let uv = id.get_bound_uv();
+
+            // Expanding by zero is a no-op.
+            let same = S2CellId::expanded_by_distance_uv(&uv, S1Angle::zero());
+            assert_eq!((same[0], same[1]), (uv[0], uv[1]));
+
+            let radius = S1Angle::from_degrees(rng.gen_range(0.0..max_dist_degrees));
+            let expanded = S2CellId::expanded_by_distance_uv(&uv, radius);
+            assert!(expanded.x().lo() < uv.x().lo() && expanded.x().hi() > uv.x().hi());
+            assert!(expanded.y().lo() < uv.y().lo() && expanded.y().hi() > uv.y().hi());
+
+            // Points within "radius" of the cell are inside the expanded bound,
+            // and the corners of the expanded bound are at least "radius" away.
+            let cell = S2Cell::new(id);
+            let inner = S1ChordAngle::from(S1Angle::from_radians(radius.radians() * (1.0 - 1e-12)));
+            let center = id.to_point();
+            let (x, y) = (center.ortho(), center.cross_prod(&center.ortho()));
+            for _ in 0..10 {
+                let r = rng.gen_range(0.0..2.0 * radius.radians() + 1e-9);
+                let theta = rng.gen_range(0.0..2.0 * core::f64::consts::PI);
+                let p =
+                    (center * r.cos() + (x * theta.cos() + y * theta.sin()) * r.sin()).normalize();
+                let Some(p_uv) = face_xyz_to_uv(id.face(), &p) else {
+                    continue;
+                };
+                if cell.get_distance(&p) < inner {
+                    assert!(expanded.contains(&p_uv));
+                }
+            }
+            for k in 0..4 {
+                let corner = expanded.get_vertex(k);
+                let p = face_uv_to_xyz(id.face(), corner.x(), corner.y()).normalize();
+                assert!(cell.get_distance(&p) >= inner);
+            }
+        }
+    }
+
+    #[test]
+    fn test_signed_mappings() {
+        assert_eq!(S2CellId::none().to_signed_ordered(), i64::MIN);
+        assert_eq!(S2CellId::sentinel().to_signed_ordered(), i64::MAX);
+        assert_eq!(S2CellId::none().to_i64_bits(), 0);
+        assert_eq!(S2CellId::sentinel().to_i64_bits(), -1);
+
+        // Sample ids at all levels spanning all six faces.
+        let mut rng = StdRng::seed_from_u64(55);
+        let mut ids: Vec<S2CellId> = (0..1000).map(|_| random_cell_id(&mut rng)).collect();
+        ids.extend((0..S2CellId::NUM_FACES).map(S2CellId::from_face));
+        ids.extend([S2CellId::none(), S2CellId::sentinel()]);
+        assert!((0..S2CellId::NUM_FACES).all(|face| ids.iter().any(|id| id.face() == face)));
+        ids.sort();
+
+        for id in &ids {
+            assert_eq!(S2CellId::from_signed_ordered(id.to_signed_ordered()), *id);
+            assert_eq!(S2CellId::from_i64_bits(id.to_i64_bits()), *id);
+        }
+        for w in ids.windows(2) {
+            assert_eq!(
+                w[0].to_signed_ordered().cmp(&w[1].to_signed_ordered()),
+                w[0].cmp(&w[1])
+            );
+        }
+
+        // Reinterpreting the bits puts faces 4 and 5 before face 0.
+        assert!(S2CellId::from_face(4).to_i64_bits() < S2CellId::from_face(0).to_i64_bits());
+
+        // The signed ranges contain exactly the descendants of each cell.
+        for w in ids.windows(2) {
+            let (a, b) = (w[0], w[1]);
+            if !a.is_valid() || !b.is_valid() {
+                continue;
+            }
+            let range = a.signed_range_min()..=a.signed_range_max();
+            assert_eq!(range.contains(&b.to_signed_ordered()), a.contains(&b));
+            assert!(range.contains(&a.to_signed_ordered()));
+            assert_eq!(a.signed_range_min(), a.range_min().to_signed_ordered());
+            assert_eq!(a.signed_range_max(), a.range_max().to_signed_ordered());
+        }
+    }
+
+    #[test]
+    fn test_to_lat_lng() {
+        // The center of the leaf cell containing a point is within one leaf
+        // cell width of it.
+        let max_error = MAX_DIAG.get_value(S2CellId::MAX_LEVEL);
+        for (lat, lng) in [(40.7484, -73.9857), (-33.8568, 151.2153), (90.0, 0.0)] {
+            let ll = S2LatLng::from_degrees(lat, lng);
+            let center = S2CellId::from_lat_lng(&ll).to_lat_lng();
+            assert!(center.is_valid());
+            assert!(center.get_distance(&ll).radians() <= max_error, "{ll}");
+        }
+
+        let mut rng = StdRng::seed_from_u64(26);
+        for _ in 0..1000 {
+            let id = random_cell_id(&mut rng);
+            let center = id.to_lat_lng();
+            assert_eq!(
+                S2CellId::from_lat_lng(&center).parent_at_level(id.level()),
+                id
+            );
+            let ll = S2LatLng::from_point(&S2Point::from(id));
+            assert!(ll.get_distance(&center).radians() <= 1e-15);
+        }
+    }
+
+    #[test]
+    fn test_debug_string() {
+        let id = S2CellId::from_token("89c259");
+        assert_eq!(
+            id.debug_string(),
+            "face 4, level 10, token 89c259, center 40.755098,-73.983003"
+        );
+        assert_eq!(
+            S2CellId::from_face(0).debug_string(),
+            "face 0, level 0, token 1, center 0.000000,0.000000"
+        );
+        assert_eq!(
+            S2CellId::sentinel().debug_string(),
+            "Invalid: ffffffffffffffff"
+        );
+    }
+
+    #[test]
+    fn test_conversion_round_trips() {
+        let mut rng = StdRng::seed_from_u64(65);
+        for _ in 0..1000 {
+            let leaf = random_cell_id_at_level(&mut rng, S2CellId::MAX_LEVEL);
+            let id = leaf.parent_at_level(rng.gen_range(0..=S2CellId::MAX_LEVEL));
+
+            // id -> S2Cell -> id is the identity.
+            let cell = S2Cell::from(id);
+            assert_eq!(S2CellId::from(&cell), id);
+
+            // Leaf id -> point -> id is the identity.
+            assert_eq!(S2CellId::from(S2Point::from(leaf)), leaf);
+
+            // Any other id -> point -> id yields a descendant leaf.
+            let descendant = S2CellId::from(S2Point::from(id));
+            assert!(descendant.is_leaf());
+            assert!(id.contains(&descendant));
+
+            // The same holds when going through the cell center as an S2LatLng.
+            let center = S2LatLng::from(id);
+            let normalized = S2LatLng::try_from(S2Point::from(id)).unwrap();
+            assert!(center.get_distance(&normalized).radians() <= 1e-15);
+            assert!(id.contains(&S2CellId::from(center)));
+            assert!(cell.contains_point(&S2Point::from(center)));
+        }
+    }
+
+    #[test]
+    fn test_lsb() {
+        // Every single-bit id is its own lowest set bit.
+        for shift in 0..64 {
+            let id = S2CellId::new(1 << shift);
+            assert_eq!(id.lsb(), 1 << shift);
+        }
+        let mut rng = StdRng::seed_from_u64(67);
+        for _ in 0..10000 {
+            let id = S2CellId::new(rng.gen::<u64>() | 1 << rng.gen_range(0..64));
+            assert_eq!(id.lsb(), 1 << id.id().trailing_zeros());
+        }
+        for _ in 0..1000 {
+            let id = random_cell_id(&mut rng);
+            assert_eq!(id.lsb(), S2CellId::lsb_for_level(id.level()));
+        }
+        assert_eq!(S2CellId::none().lsb(), 0);
+        assert_eq!(S2CellId::sentinel().lsb(), 1);
+    }
+
+    #[test]
+    fn test_is_valid_mask() {
+        // A cell id is valid iff its face is in range and its lowest set bit
+        // is an even position within the position bits (i.e. it marks the
+        // end of a whole level).
+        fn reference(id: u64) -> bool {
+            let tz = id.trailing_zeros() as i32;
+            (id >> S2CellId::POS_BITS) < 6 && tz < S2CellId::POS_BITS && tz % 2 == 0
+        }
+        let mut rng = StdRng::seed_from_u64(68);
+        for _ in 0..10000 {
+            let id = rng.gen::<u64>() >> rng.gen_range(0..64);
+            assert_eq!(S2CellId::new(id).is_valid(), reference(id), "{id:#x}");
+        }
+        for shift in 0..64 {
+            let id = 1 << shift;
+            assert_eq!(S2CellId::new(id).is_valid(), reference(id), "{id:#x}");
+        }
+        assert!(!S2CellId::none().is_valid());
+        assert!(!S2CellId::sentinel().is_valid());
+    }
+
+    #[test]
+    fn test_to_face_ij_orientation_reference() {
+        // Compute the result from the classic Hilbert curve construction in
+        // hilbert_d2xy() rather than from the position tables, and compare
+        // against the lookup-table implementation.
+        fn reference(id: S2CellId) -> (i32, IjCoord, IjCoord, i32) {
+            let face = id.face();
+            // Odd-numbered faces use the curve with i and j swapped.
+            let to_face = |(x, y): (u64, u64)| {
+                if face & SWAP_MASK != 0 {
+                    (y, x)
+                } else {
+                    (x, y)
+                }
+            };
+            let pos_mask = (1_u64 << S2CellId::POS_BITS) - 1;
+
+            // The bits of "id" give the position of a leaf cell along the curve
+            // (for non-leaf cells, one of the leaf cells nearest the center).
+            let (i, j) = to_face(hilbert_d2xy(S2CellId::MAX_LEVEL, (id.id() & pos_mask) >> 1));
+
+            // The orientation is given by where the curve enters and leaves the
+            // cell. Use a curve one level finer than leaf cells, so that leaf
+            // cells span 2x2 curve cells too.
+            let first = ((id.range_min().id() & pos_mask) >> 1) << 2;
+            let last = (((id.range_max().id() & pos_mask) >> 1) << 2) + 3;
+            let (x0, y0) = to_face(hilbert_d2xy(S2CellId::MAX_LEVEL + 1, first));
+            let (x1, y1) = to_face(hilbert_d2xy(S2CellId::MAX_LEVEL + 1, last));
+            let half = S2CellId::MAX_LEVEL - id.level();
+            let orientation = hilbert_orientation(
+                ((x0 >> half) & 1, (y0 >> half) & 1),
+                ((x1 >> half) & 1, (y1 >> half) & 1),
+            );
+            (face, IjCoord(i as i32), IjCoord(j as i32), orientation)
+        }
+        let mut rng = StdRng::seed_from_u64(69);
+        for _ in 0..10000 {
+            let id = random_cell_id(&mut rng);
+            assert_eq!(id.to_face_ij_orientation(), reference(id), "{id:?}");
+        }
+        for face in 0..S2CellId::NUM_FACES {
+            let id = S2CellId::from_face(face);
+            assert_eq!(id.to_face_ij_orientation(), reference(id));
+        }
+    }
+}
@@ -0,0 +1,465 @@
+// Copyright 2005 Google Inc. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS-IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+// Original Author: ericv@google.com (Eric Veach)
+
+use alloc::vec::Vec;
+
+use crate::s2::{S2CellId, S2Point};
+
+/// An S2CellUnion is a region consisting of cells of various sizes. Typically
+/// a cell union is used to approximate some other shape. There is a tradeoff
+/// between the accuracy of the approximation and how many cells are used.
+/// Unlike polygons, cells have a fixed hierarchical structure. This makes
+/// them more suitable for optimizations based on preprocessing.
+///
+/// An S2CellUnion is represented as a vector of sorted, non-overlapping
+/// S2CellIds. By default the vector is also "normalized", meaning that
+/// groups of 4 child cells have been replaced by their parent cell whenever
+/// possible. S2CellUnions are not required to be normalized, but certain
+/// operations will return different results if they are not (e.g., `contains`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct S2CellUnion {
+    cell_ids: Vec<S2CellId>,
+}
+
+impl S2CellUnion {
+    /// Constructs a cell union with the given S2CellIds, then calls
+    /// `normalize()` to sort them, remove duplicates, and merge cells when
+    /// possible.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{S2CellId, S2CellUnion};
+    ///
+    /// let parent = S2CellId::from_face(1).child(2);
+    /// let union = S2CellUnion::new((0..4).map(|k| parent.child(k)).collect());
+    /// assert_eq!(union.cell_ids(), &[parent]);
+    /// ```
+    pub fn new(cell_ids: Vec<S2CellId>) -> S2CellUnion {
+        let mut union = S2CellUnion { cell_ids };
+        union.normalize();
+        union
+    }
+
+    /// Constructs a cell union from S2CellIds that have already been
+    /// normalized (typically because they were extracted from another
+    /// S2CellUnion).
+    pub fn from_normalized(cell_ids: Vec<S2CellId>) -> S2CellUnion {
+        let union = S2CellUnion { cell_ids };
+        debug_assert!(union.is_normalized());
+        union
+    }
+
+    /// Constructs a cell union from a vector of sorted, non-overlapping
+    /// S2CellIds. Unlike the other constructors, this one does not require
+    /// that groups of 4 child cells have been replaced by their parent cell.
+    pub fn from_verbatim(cell_ids: Vec<S2CellId>) -> S2CellUnion {
+        let union = S2CellUnion { cell_ids };
+        debug_assert!(union.is_valid());
+        union
+    }
+
+    /// Returns the number of cells in the union.
+    pub fn num_cells(&self) -> usize {
+        self.cell_ids.len()
+    }
+
+    /// Returns the cell at the given index.
+    pub fn cell_id(&self, i: usize) -> S2CellId {
+        self.cell_ids[i]
+    }
+
+    /// Returns the cells in the union.
+    pub fn cell_ids(&self) -> &[S2CellId] {
+        &self.cell_ids
+    }
+
+    /// Returns true if the cell union is empty.
+    pub fn is_empty(&self) -> bool {
+        self.cell_ids.is_empty()
+    }
+
+    /// Consumes the union and returns the underlying vector of cells.
+    pub fn release(self) -> Vec<S2CellId> {
+        self.cell_ids
+    }
+
+    /// Returns true if the cell union is valid, meaning that the S2CellIds are
+    /// valid, non-overlapping, and sorted in increasing order.
+    pub fn is_valid(&self) -> bool {
+        if self.cell_ids.iter().any(|id| !id.is_valid()) {
+            return false;
+        }
+        self.cell_ids
+            .windows(2)
+            .all(|w| w[0].range_max() < w[1].range_min())
+    }
+
+    /// Returns true if the cell union is normalized, meaning that it satisfies
+    /// `is_valid()` and that no four cells have a common parent.
+    pub fn is_normalized(&self) -> bool {
+        self.is_valid()
+            && !self
+                .cell_ids
+                .windows(4)
+                .any(|w| are_siblings(w[0], w[1], w[2], w[3]))
+    }
+
+    /// Normalizes the cell union by discarding cells that are contained by
+    /// other cells, replacing groups of 4 child cells by their parent cell
+    /// whenever possible, and sorting all the cell ids in increasing order.
+    ///
+    /// Returns true if the number of cells was reduced.
+    pub fn normalize(&mut self) -> bool {
+        normalize(&mut self.cell_ids)
+    }
+
+    /// Returns true if the cell union contains the given cell id. Containment
+    /// is defined with respect to regions, e.g. a cell contains its 4
+    /// children. This is a fast operation (logarithmic in the size of the cell
+    /// union).
+    ///
+    /// CAVEAT: If you have constructed a non-normalized S2CellUnion using
+    /// `from_verbatim`, note that groups of 4 child cells are *not* considered
+    /// to contain their parent cell. To get this behavior you must use one of
+    /// the other constructors or call `normalize()` explicitly.
+    pub fn contains(&self, id: &S2CellId) -> bool {
+        // This is an exact test. Each cell occupies a linear span of the S2
+        // space-filling curve, and the cell id is simply the position at the
+        // center of this span. The cell union ids are sorted in increasing order
+        // along the space-filling curve. So we simply find the pair of cell ids
+        // that surround the given cell id (using binary search). There is
+        // containment if and only if one of these two cell ids contains this
+        // cell.
+        debug_assert!(id.is_valid());
+        let i = self.cell_ids.partition_point(|x| x < id);
+        if i < self.cell_ids.len() && self.cell_ids[i].range_min() <= *id {
+            return true;
+        }
+        i != 0 && self.cell_ids[i - 1].range_max() >= *id
+    }
+
+    /// Returns true if the cell union intersects the given cell id. This is a
+    /// fast operation (logarithmic in the size of the cell union).
+    pub fn intersects(&self, id: &S2CellId) -> bool {
+        // This is an exact test; see the comments for contains() above.
+        debug_assert!(id.is_valid());
+        let i = self.cell_ids.partition_point(|x| x < id);
+        if i < self.cell_ids.len() && self.cell_ids[i].range_min() <= id.range_max() {
+            return true;
+        }
+        i != 0 && self.cell_ids[i - 1].range_max() >= id.range_min()
+    }
+
+    /// Returns true if the cell union contains the given point. This is a
+    /// fast operation (logarithmic in the size of the cell union).
+    ///
+    /// The cell union must be valid (see is_valid()). Since cells do not
+    /// overlap, a point on the boundary between two cells is contained by
+    /// exactly one of them (the one containing its leaf cell).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{S2CellId, S2CellUnion, S2LatLng};
+    ///
+    /// let p = S2LatLng::from_degrees(40.0, -74.0).to_point();
+    /// let union = S2CellUnion::new(vec![S2CellId::from_point(&p).parent_at_level(10)]);
+    /// assert!(union.contains_point(&p));
+    /// assert!(!union.contains_point(&-p));
+    /// ```
+    pub fn contains_point(&self, p: &S2Point) -> bool {
+        self.contains(&S2CellId::from_point(p))
+    }
+
+    /// Returns the number of leaf cells covered by the union. This will be no
+    /// more than 6*2^60 for the whole sphere. Each cell at level k covers
+    /// 4^(30-k) leaf cells, so the result is proportional to the area covered
+    /// (up to the variation in cell sizes). Cells that overlap are counted
+    /// more than once unless the union is normalized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{S2CellId, S2CellUnion};
+    ///
+    /// let cell = S2CellId::from_face(2).child_begin_at_level(28);
+    /// let union = S2CellUnion::new(vec![cell, cell.next().child(0)]);
+    /// assert_eq!(union.leaf_cell_covering(), 16 + 4);
+    /// ```
+    pub fn leaf_cell_covering(&self) -> u64 {
+        self.cell_ids
+            .iter()
+            .map(|id| {
+                let inverted_level = S2CellId::MAX_LEVEL - id.level();
+                1u64 << (inverted_level << 1)
+            })
+            .sum()
+    }
+}
+
+impl IntoIterator for S2CellUnion {
+    type Item = S2CellId;
+    type IntoIter = alloc::vec::IntoIter<S2CellId>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.cell_ids.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a S2CellUnion {
+    type Item = &'a S2CellId;
+    type IntoIter = core::slice::Iter<'a, S2CellId>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.cell_ids.iter()
+    }
+}
+
+/// Like `S2CellUnion::normalize()`, but works with a vector of S2CellIds.
+/// Returns true if the number of cells was reduced.
+pub fn normalize(ids: &mut Vec<S2CellId>) -> bool {
+    // Optimize the representation by discarding cells contained by other cells,
+    // and looking for cases where all subcells of a parent cell are present.
+    ids.sort();
+    let mut out = 0;
+    for i in 0..ids.len() {
+        let mut id = ids[i];
+
+        // Check whether this cell is contained by the previous cell.
+        if out > 0 && ids[out - 1].contains(&id) {
+            continue;
+        }
+
+        // Discard any previous cells contained by this cell.
+        while out > 0 && id.contains(&ids[out - 1]) {
+            out -= 1;
+        }
+
+        // Check whether the last 3 elements plus "id" can be collapsed into a
+        // single parent cell.
+        while out >= 3 && are_siblings(ids[out - 3], ids[out - 2], ids[out - 1], id) {
+            // Replace four children by their parent cell.
+            id = id.parent();
+            out -= 3;
+        }
+        ids[out] = id;
+        out += 1;
+    }
+    if ids.len() == out {
+        return false;
+    }
+    ids.truncate(out);
+    true
+}
+
+/// Returns true if the given four cells have a common parent.
+/// REQUIRES: The four cells are distinct.
+fn are_siblings(a: S2CellId, b: S2CellId, c: S2CellId, d: S2CellId) -> bool {
+    // A necessary (but not sufficient) condition is that the XOR of the
+    // four cell IDs must be zero. This is also very fast to test.
+    if (a.id() ^ b.id() ^ c.id()) != d.id() {
+        return false;
+    }
+
+    // Now we do a slightly more expensive but exact test. First, compute a
+    // mask that blocks out the two bits that encode the child position of
+    // "id" with respect to its parent, then check that the other three
+    // children all agree with "mask".
+    let mut mask = d.lsb() << 1;
+    mask = !(mask + (mask << 1));
+    let id_masked = d.id() & mask;
+    (a.id() & mask) == id_masked
+        && (b.id() & mask) == id_masked
+        && (c.id() & mask) == id_masked
+        && !d.is_face()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::s2::s2testing::random_point;
+
+    #[test]
+    fn test_normalize_siblings() {
+        let parent = S2CellId::from_face(3).child_begin_at_level(29);
+        let leaves: Vec<S2CellId> = (0..4).rev().map(|k| parent.child(k)).collect();
+        let union = S2CellUnion::new(leaves);
+        assert_eq!(union.num_cells(), 1);
+        assert_eq!(union.cell_id(0), parent);
+        assert!(union.is_normalized());
+
+        // Three siblings are not enough to be replaced by their parent.
+        let union = S2CellUnion::new((0..3).map(|k| parent.child(k)).collect());
+        assert_eq!(union.num_cells(), 3);
+    }
+
+    #[test]
+    fn test_normalize_cascades_and_removes_contained() {
+        // All 16 grandchildren collapse all the way up to the grandparent, and
+        // duplicates and contained cells are discarded.
+        let grandparent = S2CellId::from_face(0).child(1);
+        let mut ids: Vec<S2CellId> = Vec::new();
+        for i in 0..4 {
+            for j in 0..4 {
+                ids.push(grandparent.child(i).child(j));
+            }
+        }
+        ids.push(grandparent.child(2).child(3).child(1));
+        ids.push(grandparent.child(0).child(0));
+        let union = S2CellUnion::new(ids);
+        assert_eq!(union.cell_ids(), &[grandparent]);
+
+        // Four face cells are never merged.
+        let union = S2CellUnion::new((0..4).map(S2CellId::from_face).collect());
+        assert_eq!(union.num_cells(), 4);
+    }
+
+    #[test]
+    fn test_normalize_random() {
+        // Normalizing preserves the set of leaf cells covered by the input.
+        // Cells are chosen from a small neighborhood so that many of them
+        // overlap and sibling groups are frequent.
+        let mut rng = StdRng::seed_from_u64(60);
+        for _ in 0..200 {
+            let base = S2CellId::from_face(rng.gen_range(0..6)).child(rng.gen_range(0..4));
+            let mut ids = Vec::new();
+            for _ in 0..rng.gen_range(0..60) {
+                let mut id = base;
+                for _ in 0..rng.gen_range(0..5) {
+                    id = id.child(rng.gen_range(0..4));
+                }
+                ids.push(id);
+            }
+            let mut union = S2CellUnion {
+                cell_ids: ids.clone(),
+            };
+            let reduced = union.normalize();
+            assert!(union.is_normalized());
+            assert_eq!(reduced, union.num_cells() < ids.len());
+            assert!(ids.iter().all(|id| union.contains(id)));
+
+            // Compare the total number of leaf cells against the union of the
+            // input leaf ranges. Cell ranges are either nested or disjoint, so
+            // after sorting (larger ranges first when they start at the same
+            // leaf) we only need to skip nested ranges.
+            let mut ranges: Vec<(u64, u64)> = ids
+                .iter()
+                .map(|id| (id.range_min().id(), id.range_max().id()))
+                .collect();
+            ranges.sort_by_key(|&(lo, hi)| (lo, core::cmp::Reverse(hi)));
+            let mut expected = 0;
+            let mut end = None;
+            for (lo, hi) in ranges {
+                if end.is_none_or(|end| lo > end) {
+                    expected += (hi - lo) / 2 + 1;
+                    end = Some(hi);
+                }
+            }
+            assert_eq!(union.leaf_cell_covering(), expected);
+        }
+    }
+
+    #[test]
+    fn test_leaf_cell_covering() {
+        assert_eq!(S2CellUnion::default().leaf_cell_covering(), 0);
+
+        // One leaf cell on face 1, one level-29 cell (4 leaves) on face 2, and
+        // one face cell (4^30 leaves) on face 3.
+        let leaf = S2CellId::from_face(1).child_begin_at_level(S2CellId::MAX_LEVEL);
+        let level29 = S2CellId::from_face(2).child_begin_at_level(S2CellId::MAX_LEVEL - 1);
+        let union = S2CellUnion::new(vec![leaf, level29, S2CellId::from_face(3)]);
+        assert_eq!(union.leaf_cell_covering(), 1 + 4 + (1 << 60));
+
+        // The whole sphere.
+        let union = S2CellUnion::new((0..6).map(S2CellId::from_face).collect());
+        assert_eq!(union.leaf_cell_covering(), 6 << 60);
+    }
+
+    #[test]
+    fn test_contains_point() {
+        assert!(!S2CellUnion::default().contains_point(&S2Point::new(1.0, 0.0, 0.0)));
+
+        // Compare against a linear scan over cells at random levels.
+        let mut rng = StdRng::seed_from_u64(63);
+        for _ in 0..100 {
+            let ids: Vec<S2CellId> = (0..rng.gen_range(0..20))
+                .map(|_| {
+                    let level = rng.gen_range(0..6);
+                    S2CellId::from_point(&random_point(&mut rng)).parent_at_level(level)
+                })
+                .collect();
+            let union = S2CellUnion::new(ids.clone());
+            for _ in 0..100 {
+                let p = random_point(&mut rng);
+                let leaf = S2CellId::from_point(&p);
+                let expected = ids.iter().any(|id| id.contains(&leaf));
+                assert_eq!(union.contains_point(&p), expected);
+            }
+            for id in &ids {
+                assert!(union.contains_point(&S2Point::from(*id)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_contains_and_intersects() {
+        let a = S2CellId::from_face(1).child(0).child(3);
+        let b = S2CellId::from_face(1).child(2);
+        let c = S2CellId::from_face(4).child_begin_at_level(20);
+        let union = S2CellUnion::new(vec![c, b, a]);
+        assert_eq!(union.num_cells(), 3);
+
+        for id in [a, b, c] {
+            assert!(union.contains(&id));
+            assert!(union.contains(&id.range_min()));
+            assert!(union.contains(&id.range_max()));
+            assert!(union.contains(&id.child_begin_at_level(25)));
+            assert!(union.intersects(&id));
+            assert!(union.intersects(&id.parent()));
+            assert!(!union.contains(&id.parent()));
+        }
+
+        // Neighbors along the curve are not contained.
+        assert!(!union.contains(&a.next()));
+        assert!(!union.contains(&b.prev().range_max()));
+        assert!(!union.contains(&c.next().child_begin_at_level(30)));
+        assert!(!union.intersects(&S2CellId::from_face(2)));
+        assert!(!union.intersects(&S2CellId::from_face(1).child(3)));
+        assert!(union.intersects(&S2CellId::from_face(1)));
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let parent = S2CellId::from_face(2).child(1);
+        let ids = vec![parent.child(0), parent.child(3), S2CellId::from_face(5)];
+        let union = S2CellUnion::new(ids.clone());
+        let borrowed: Vec<S2CellId> = (&union).into_iter().copied().collect();
+        assert_eq!(borrowed, ids);
+        let mut count = 0;
+        for id in &union {
+            assert!(union.contains(id));
+            count += 1;
+        }
+        assert_eq!(count, union.num_cells());
+        assert_eq!(union.into_iter().collect::<Vec<_>>(), ids);
+    }
+}
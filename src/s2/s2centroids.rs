@@ -0,0 +1,201 @@
+// Copyright 2005 Google Inc. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS-IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+// Original Author: ericv@google.com (Eric Veach)
+//
+// There are several notions of the "centroid" of a triangle. First, there
+// is the planar centroid, which is simply the centroid of the ordinary
+// (non-spherical) triangle defined by the three vertices. Second, there is
+// the surface centroid, which is defined as the intersection of the three
+// medians of the spherical triangle. It is possible to show that this
+// point is simply the planar centroid projected to the surface of the
+// sphere. Finally, there is the true centroid (mass centroid), which is
+// defined as the surface integral over the spherical triangle of (x,y,z)
+// divided by the triangle area. This is the point that the triangle would
+// rotate around if it was spinning in empty space.
+//
+// The best centroid for most purposes is the true centroid. Unlike the
+// planar and surface centroids, the true centroid behaves linearly as
+// regions are added or subtracted. That is, if you split a triangle into
+// pieces and compute the average of their centroids (weighted by triangle
+// area), the result equals the centroid of the original triangle. This is
+// not true of the other centroids.
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+use crate::s2::S2Point;
+
+/// Returns the centroid of the planar triangle ABC. This can be normalized
+/// to unit length to obtain the "surface centroid" of the corresponding
+/// spherical triangle, i.e. the intersection of the three medians. However,
+/// note that for large spherical triangles the surface centroid may be
+/// nowhere near the intuitive "center".
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{s2centroids::planar_centroid, S2Point};
+///
+/// let a = S2Point::new(1.0, 0.0, 0.0);
+/// let b = S2Point::new(0.0, 1.0, 0.0);
+/// let c = S2Point::new(0.0, 0.0, 1.0);
+/// assert_eq!(
+///     planar_centroid(&a, &b, &c),
+///     S2Point::new(1.0, 1.0, 1.0) * (1.0 / 3.0)
+/// );
+/// ```
+pub fn planar_centroid(a: &S2Point, b: &S2Point, c: &S2Point) -> S2Point {
+    (*a + *b + *c) * (1.0 / 3.0)
+}
+
+/// Returns the true centroid of the spherical triangle ABC multiplied by the
+/// signed area of spherical triangle ABC. The reasons for multiplying by the
+/// signed area are (1) this is the quantity that needs to be summed to
+/// compute the centroid of a union or difference of triangles, and (2) it's
+/// actually easier to calculate this way. All points must have unit length.
+///
+/// Note that the result of this function is defined to be S2Point(0, 0, 0)
+/// if the triangle is degenerate (and that this is intended behavior).
+///
+/// # Examples
+///
+/// ```
+/// use core::f64::consts::FRAC_PI_4;
+/// use s2shell::s2::{s2centroids::true_centroid, S2Point};
+///
+/// // The integral of (x, y, z) over one octant of the sphere.
+/// let a = S2Point::new(1.0, 0.0, 0.0);
+/// let b = S2Point::new(0.0, 1.0, 0.0);
+/// let c = S2Point::new(0.0, 0.0, 1.0);
+/// let centroid = true_centroid(&a, &b, &c);
+/// assert!((centroid - S2Point::new(1.0, 1.0, 1.0) * FRAC_PI_4).norm() < 1e-15);
+/// assert_eq!(true_centroid(&a, &c, &b), -centroid);
+/// ```
+pub fn true_centroid(a: &S2Point, b: &S2Point, c: &S2Point) -> S2Point {
+    debug_assert!(a.is_unit_length());
+    debug_assert!(b.is_unit_length());
+    debug_assert!(c.is_unit_length());
+
+    // I couldn't find any references for computing the true centroid of a
+    // spherical triangle... I have a truly marvellous demonstration of this
+    // formula which this margin is too narrow to contain :)
+
+    // Use angle() in order to get accurate results for small triangles.
+    let angle_a = b.angle(c);
+    let angle_b = c.angle(a);
+    let angle_c = a.angle(b);
+    let ra = if angle_a == 0.0 {
+        1.0
+    } else {
+        angle_a / angle_a.sin()
+    };
+    let rb = if angle_b == 0.0 {
+        1.0
+    } else {
+        angle_b / angle_b.sin()
+    };
+    let rc = if angle_c == 0.0 {
+        1.0
+    } else {
+        angle_c / angle_c.sin()
+    };
+
+    // Now compute a point M such that:
+    //
+    //  [Ax Ay Az] [Mx]                       [ra]
+    //  [Bx By Bz] [My] = 0.5 * det(A,B,C) * [rb]
+    //  [Cx Cy Cz] [Mz]                       [rc]
+    //
+    // To improve the numerical stability we subtract the first row (A) from
+    // the other two rows; this reduces the cancellation error when A, B, and
+    // C are very close together. Then we solve it using Cramer's rule.
+    //
+    // The result is the true centroid of the triangle multiplied by the
+    // triangle's area.
+    let x = S2Point::new(a.x(), b.x() - a.x(), c.x() - a.x());
+    let y = S2Point::new(a.y(), b.y() - a.y(), c.y() - a.y());
+    let z = S2Point::new(a.z(), b.z() - a.z(), c.z() - a.z());
+    let r = S2Point::new(ra, rb - ra, rc - ra);
+    S2Point::new(
+        y.cross_prod(&z).dot_prod(&r),
+        z.cross_prod(&x).dot_prod(&r),
+        x.cross_prod(&y).dot_prod(&r),
+    ) * 0.5
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::s2::area;
+    use crate::s2::s2testing::random_point;
+
+    #[test]
+    fn test_true_centroid_symmetry() {
+        // The centroid changes sign with the orientation of the triangle, and
+        // its norm is at most the triangle area. (The rounding errors are
+        // largest for nearly antipodal edges, where angle / sin(angle) is
+        // ill-conditioned.)
+        let mut rng = StdRng::seed_from_u64(51);
+        for _ in 0..1000 {
+            let a = random_point(&mut rng);
+            let b = random_point(&mut rng);
+            let c = random_point(&mut rng);
+            let centroid = true_centroid(&a, &b, &c);
+            assert!((centroid + true_centroid(&a, &c, &b)).norm() <= 1e-13);
+            assert!((centroid - true_centroid(&b, &c, &a)).norm() <= 1e-13);
+            assert!(centroid.norm() <= area(&a, &b, &c) + 1e-14);
+        }
+    }
+
+    #[test]
+    fn test_true_centroid_small_triangles() {
+        // For small triangles the true centroid is very close to the planar
+        // centroid, and its norm is very close to the area.
+        let mut rng = StdRng::seed_from_u64(52);
+        for _ in 0..100 {
+            let p = random_point(&mut rng);
+            let x = p.ortho();
+            let y = p.cross_prod(&x);
+            // The direction of the centroid has an error of about
+            // DBL_EPSILON / d, so the triangles can't be arbitrarily small.
+            let d = 1e-4 * rng.gen_range(0.1..1.0);
+            let a = (p + x * d).normalize();
+            let b = (p + y * d).normalize();
+            let c = (p - x * d - y * d).normalize();
+            let centroid = true_centroid(&a, &b, &c);
+            let planar = planar_centroid(&a, &b, &c).normalize();
+            assert!((centroid.normalize() - planar).norm() <= 1e-10);
+            assert!((centroid.norm() - area(&a, &b, &c)).abs() <= 1e-3 * area(&a, &b, &c));
+        }
+    }
+
+    #[test]
+    fn test_true_centroid_additive() {
+        // Splitting a triangle at the midpoint of an edge preserves the sum of
+        // the area-weighted centroids.
+        let mut rng = StdRng::seed_from_u64(53);
+        for _ in 0..100 {
+            let a = random_point(&mut rng);
+            let b = random_point(&mut rng);
+            let c = random_point(&mut rng);
+            let m = (b + c).normalize();
+            let sum = true_centroid(&a, &b, &m) + true_centroid(&a, &m, &c);
+            assert!((sum - true_centroid(&a, &b, &c)).norm() <= 1e-13);
+        }
+    }
+}
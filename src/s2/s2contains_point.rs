@@ -0,0 +1,268 @@
+// Copyright 2005 Google Inc. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS-IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+// Original Author: ericv@google.com (Eric Veach)
+//
+// This file contains the point containment test shared by loops and
+// polygons. A point is contained by a loop if the number of loop edges
+// crossed by the geodesic from origin() to the point is odd (or even, if the
+// loop contains origin() itself).
+//
+// Containment is defined using the "semi-open" boundary model: every point
+// of the sphere is contained by exactly one of a loop and its reverse, and
+// more generally by exactly one loop of any set of loops that tile the
+// sphere. In particular:
+//
+//  - A point that is a loop vertex is contained if and only if the loop
+//    contains the reference direction ref_dir(vertex), which is determined
+//    by angle_contains_vertex().
+//
+//  - A point that lies exactly on the interior of an edge is classified as
+//    though it were moved an infinitesimal distance along the geodesic from
+//    origin() towards the point, so it belongs to the loop on the side of
+//    the edge that faces origin().
+
+use crate::s2::{edge_or_vertex_crossing, origin, ref_dir, s2predicates::ordered_ccw, S2Point};
+
+/// Returns true if the loop defined by "vertices" contains "point". The loop
+/// is implicitly closed (the last vertex is connected to the first) and its
+/// interior is on the left of its edges. "origin_inside" specifies whether
+/// the loop contains origin(); see contains_origin().
+///
+/// Loops with fewer than three vertices are considered to be empty or full
+/// according to "origin_inside".
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{contains_origin, contains_point, S2LatLng, S2Point};
+///
+/// let vertices: Vec<S2Point> = [(0.0, 0.0), (0.0, 10.0), (10.0, 5.0)]
+///     .iter()
+///     .map(|&(lat, lng)| S2LatLng::from_degrees(lat, lng).to_point())
+///     .collect();
+/// let origin_inside = contains_origin(&vertices);
+/// let inside = S2LatLng::from_degrees(3.0, 5.0).to_point();
+/// let outside = S2LatLng::from_degrees(-3.0, 5.0).to_point();
+/// assert!(contains_point(&vertices, &inside, origin_inside));
+/// assert!(!contains_point(&vertices, &outside, origin_inside));
+/// ```
+pub fn contains_point(vertices: &[S2Point], point: &S2Point, origin_inside: bool) -> bool {
+    if vertices.len() < 3 {
+        return origin_inside;
+    }
+    let origin = origin();
+    let mut inside = origin_inside;
+    for (i, a) in vertices.iter().enumerate() {
+        let b = &vertices[(i + 1) % vertices.len()];
+        inside ^= edge_or_vertex_crossing(&origin, point, a, b);
+    }
+    inside
+}
+
+/// Returns true if the loop defined by "vertices" contains origin(). The loop
+/// must have at least three vertices, and its first three vertices must be
+/// distinct consecutive vertices (i.e. vertices[0] != vertices[1] and
+/// vertices[1] != vertices[2]).
+///
+/// This is computed the same way as S2Loop does: the containment of vertex 1
+/// is known from angle_contains_vertex(), so origin() is inside the loop
+/// exactly when the crossing test starting from "outside" gets vertex 1
+/// wrong.
+pub fn contains_origin(vertices: &[S2Point]) -> bool {
+    debug_assert!(vertices.len() >= 3);
+    debug_assert!(vertices[0] != vertices[1] && vertices[1] != vertices[2]);
+    let v1_inside = angle_contains_vertex(&vertices[0], &vertices[1], &vertices[2]);
+    v1_inside != contains_point(vertices, &vertices[1], false)
+}
+
+/// Given the vertices of a loop A, B, C (with A != B and B != C), returns
+/// true if the loop contains vertex B, i.e. if the wedge formed by the edges
+/// AB and BC contains the fixed reference direction ref_dir(B).
+///
+/// The wedge is closed at A and open at C, so the point B is inside the loop
+/// if A == ref_dir(B) but not if C == ref_dir(B). This ensures that when a
+/// set of loops share the vertex B, exactly one of them contains it.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{angle_contains_vertex, S2Point};
+///
+/// let a = S2Point::new(1.0, 0.0, 0.0);
+/// let b = S2Point::new(0.0, 0.0, 1.0);
+/// let c = S2Point::new(0.0, 1.0, 0.0);
+/// // Exactly one of the two complementary wedges contains the vertex.
+/// assert_ne!(
+///     angle_contains_vertex(&a, &b, &c),
+///     angle_contains_vertex(&c, &b, &a)
+/// );
+/// ```
+pub fn angle_contains_vertex(a: &S2Point, b: &S2Point, c: &S2Point) -> bool {
+    // Note that the test below is written so as to get correct results when
+    // the angle ABC is degenerate. If A == C or C == ref_dir(B) it returns
+    // false, and otherwise if A == ref_dir(B) it returns true.
+    debug_assert!(a != b && b != c);
+    !ordered_ccw(&ref_dir(b), c, a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use core::f64::consts::PI;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::s2::s2testing::random_point;
+
+    /// Returns a random star-shaped CCW loop around a random center, with
+    /// every vertex within 0.6 radians of the center. The angular gap between
+    /// consecutive vertices is always less than Pi, so the center is inside
+    /// the loop.
+    fn random_loop(rng: &mut StdRng, num_vertices: usize) -> Vec<S2Point> {
+        let center = random_point(rng);
+        let u = center.ortho();
+        let v = center.cross_prod(&u);
+        (0..num_vertices)
+            .map(|k| {
+                let theta = 2.0 * PI * (k as f64 + rng.gen_range(0.0..0.4)) / num_vertices as f64;
+                let r = rng.gen_range(0.05..0.6_f64);
+                let dir = u * theta.cos() + v * theta.sin();
+                (center * r.cos() + dir * r.sin()).normalize()
+            })
+            .collect()
+    }
+
+    /// Returns the winding number of the loop around "p", computed by summing
+    /// the signed angles subtended at "p" by each edge. The result is close to
+    /// an integer: 1 if a small CCW loop surrounds "p" and 0 otherwise.
+    fn winding_number(vertices: &[S2Point], p: &S2Point) -> f64 {
+        let mut total = 0.0;
+        for (i, a) in vertices.iter().enumerate() {
+            let b = &vertices[(i + 1) % vertices.len()];
+            let ta = p.cross_prod(a);
+            let tb = p.cross_prod(b);
+            total += ta.cross_prod(&tb).dot_prod(p).atan2(ta.dot_prod(&tb));
+        }
+        total / (2.0 * PI)
+    }
+
+    #[test]
+    fn test_origin() {
+        let o = origin();
+        assert!(crate::s2::is_unit_length(&o));
+        // The origin is not one of the poles.
+        assert!(o.z() < 1.0);
+    }
+
+    #[test]
+    fn test_angle_contains_vertex() {
+        let a = S2Point::new(1.0, 0.0, 0.0);
+        let b = S2Point::new(0.0, 1.0, 0.0);
+        let ref_b = ref_dir(&b);
+
+        // Degenerate angle ABA.
+        assert!(!angle_contains_vertex(&a, &b, &a));
+
+        // An angle where A == ref_dir(B).
+        assert!(angle_contains_vertex(&ref_b, &b, &a));
+
+        // An angle where C == ref_dir(B).
+        assert!(!angle_contains_vertex(&a, &b, &ref_b));
+
+        // Verify that when a set of polygons tile the region around the vertex,
+        // exactly one of those polygons contains that vertex.
+        let mut rng = StdRng::seed_from_u64(1);
+        let center = random_point(&mut rng);
+        let u = center.ortho();
+        let v = center.cross_prod(&u);
+        let spokes: Vec<S2Point> = (0..10)
+            .map(|k| {
+                let theta = 2.0 * PI * k as f64 / 10.0 + 0.01;
+                (center * 0.9 + (u * theta.cos() + v * theta.sin()) * 0.1).normalize()
+            })
+            .collect();
+        let count = (0..spokes.len())
+            .filter(|&k| {
+                angle_contains_vertex(&spokes[(k + 1) % spokes.len()], &center, &spokes[k])
+            })
+            .count();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_contains_point_matches_winding_number() {
+        let mut rng = StdRng::seed_from_u64(2);
+        for _ in 0..50 {
+            let num_vertices = rng.gen_range(3..12);
+            let vertices = random_loop(&mut rng, num_vertices);
+            let origin_inside = winding_number(&vertices, &origin()).round() == 1.0;
+            assert_eq!(contains_origin(&vertices), origin_inside);
+
+            let reversed: Vec<S2Point> = vertices.iter().rev().copied().collect();
+            assert_eq!(contains_origin(&reversed), !origin_inside);
+
+            for _ in 0..100 {
+                // Sample points both near the loop and elsewhere on the sphere.
+                let p = if rng.gen_bool(0.5) {
+                    (vertices[0] + random_point(&mut rng) * 0.5).normalize()
+                } else {
+                    random_point(&mut rng)
+                };
+                let w = winding_number(&vertices, &p);
+                let k = w.round();
+                if (w - k).abs() > 1e-6 || (k != 0.0 && k != 1.0) {
+                    continue;
+                }
+                let expected = k == 1.0;
+                assert_eq!(contains_point(&vertices, &p, origin_inside), expected);
+                assert_eq!(contains_point(&reversed, &p, !origin_inside), !expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_contains_point_at_vertices() {
+        // Every vertex is contained by exactly one of the loop and its reverse,
+        // consistently with angle_contains_vertex().
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..50 {
+            let num_vertices = rng.gen_range(3..12);
+            let vertices = random_loop(&mut rng, num_vertices);
+            let reversed: Vec<S2Point> = vertices.iter().rev().copied().collect();
+            let origin_inside = contains_origin(&vertices);
+            let n = vertices.len();
+            for i in 0..n {
+                let (prev, v, next) = (
+                    &vertices[(i + n - 1) % n],
+                    &vertices[i],
+                    &vertices[(i + 1) % n],
+                );
+                let inside = contains_point(&vertices, v, origin_inside);
+                assert_eq!(inside, angle_contains_vertex(prev, v, next));
+                assert_ne!(inside, contains_point(&reversed, v, !origin_inside));
+            }
+        }
+    }
+
+    #[test]
+    fn test_degenerate_loops() {
+        let p = S2Point::new(0.0, 0.0, 1.0);
+        assert!(!contains_point(&[], &p, false));
+        assert!(contains_point(&[], &p, true));
+        assert!(!contains_point(&[p, -p], &p, false));
+    }
+}
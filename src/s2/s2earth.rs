@@ -0,0 +1,205 @@
+//! Functions for converting between distances on the unit sphere and
+//! distances on the Earth's surface, modeling the Earth as a sphere of
+//! radius [`RADIUS_METERS`].
+//!
+//! The level pickers answer questions such as "what level should I index at
+//! for roughly 100 meter resolution?". For reference, the average cell edge
+//! length at each level (the AVG_EDGE metric scaled by the Earth's radius)
+//! is approximately:
+//!
+//! | level | avg edge   |
+//! |------:|-----------:|
+//! |     0 |    9297 km |
+//! |     1 |    4648 km |
+//! |     2 |    2324 km |
+//! |     3 |    1162 km |
+//! |     4 |     581 km |
+//! |     5 |     291 km |
+//! |     6 |     145 km |
+//! |     7 |    72.6 km |
+//! |     8 |    36.3 km |
+//! |     9 |    18.2 km |
+//! |    10 |    9.08 km |
+//! |    11 |    4.54 km |
+//! |    12 |    2.27 km |
+//! |    13 |    1.13 km |
+//! |    14 |      567 m |
+//! |    15 |      284 m |
+//! |    16 |      142 m |
+//! |    17 |     70.9 m |
+//! |    18 |     35.5 m |
+//! |    19 |     17.7 m |
+//! |    20 |     8.87 m |
+//! |    21 |     4.43 m |
+//! |    22 |     2.22 m |
+//! |    23 |     1.11 m |
+//! |    24 |    55.4 cm |
+//! |    25 |    27.7 cm |
+//! |    26 |    13.9 cm |
+//! |    27 |    6.93 cm |
+//! |    28 |    3.46 cm |
+//! |    29 |    1.73 cm |
+//! |    30 |    8.66 mm |
+//!
+//! Individual cells vary in size by up to a factor of about 1.8 (see
+//! MAX_EDGE and MIN_EDGE), so [`level_for_meters`] uses the MAX_EDGE metric
+//! to guarantee its bound for every cell rather than the typical one.
+
+use crate::{
+    s1::S1Angle,
+    s2::{S2CellId, S2LatLng},
+};
+
+/// The mean radius of the Earth in meters.
+pub const RADIUS_METERS: f64 = 6371010.0;
+
+/// The mean radius of the Earth in kilometers.
+pub const RADIUS_KM: f64 = 0.001 * RADIUS_METERS;
+
+/// Converts a distance on the Earth's surface to an angle on the unit
+/// sphere.
+pub fn meters_to_angle(meters: f64) -> S1Angle {
+    S1Angle::from_radians(meters / RADIUS_METERS)
+}
+
+/// Converts an angle on the unit sphere to a distance on the Earth's
+/// surface.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::{s1::S1Angle, s2::s2earth};
+///
+/// let quarter = s2earth::angle_to_meters(S1Angle::from_degrees(90.0));
+/// assert!((quarter - 10_007_559.1).abs() < 0.1);
+/// ```
+pub fn angle_to_meters(angle: S1Angle) -> f64 {
+    angle.radians() * RADIUS_METERS
+}
+
+/// Converts a distance in kilometers to an angle on the unit sphere.
+pub fn km_to_angle(km: f64) -> S1Angle {
+    S1Angle::from_radians(km / RADIUS_KM)
+}
+
+/// Converts an angle on the unit sphere to a distance in kilometers.
+pub fn angle_to_km(angle: S1Angle) -> f64 {
+    angle.radians() * RADIUS_KM
+}
+
+/// Converts an area in square kilometers to steradians (i.e. an area on the
+/// unit sphere).
+pub fn square_km_to_steradians(km2: f64) -> f64 {
+    km2 / (RADIUS_KM * RADIUS_KM)
+}
+
+/// Converts an area in steradians to square kilometers.
+pub fn steradians_to_square_km(steradians: f64) -> f64 {
+    steradians * RADIUS_KM * RADIUS_KM
+}
+
+/// Returns the distance in meters between two points on the Earth's surface.
+pub fn get_distance_meters(a: &S2LatLng, b: &S2LatLng) -> f64 {
+    angle_to_meters(a.get_distance(b))
+}
+
+/// Returns the minimum level such that every cell at that level has edges
+/// no longer than "meters" (see [`S2CellId::level_for_max_edge_length`]).
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::s2earth::level_for_meters;
+///
+/// // Every level-14 cell has edges of at most about 1 km.
+/// assert_eq!(level_for_meters(1000.0), 14);
+/// ```
+pub fn level_for_meters(meters: f64) -> i32 {
+    S2CellId::level_for_max_edge_length(meters_to_angle(meters))
+}
+
+/// Returns the minimum level such that every cell at that level has an area
+/// of at most "km2" square kilometers (see [`S2CellId::level_for_max_area`]).
+pub fn level_for_square_km(km2: f64) -> i32 {
+    S2CellId::level_for_max_area(square_km_to_steradians(km2))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, string::String, vec::Vec};
+
+    use super::*;
+    use crate::s2::s2metrics::{AVG_EDGE, MAX_AREA, MAX_EDGE};
+
+    /// Formats a length with three significant digits in a readable unit.
+    fn format_km(km: f64) -> String {
+        let (v, unit) = if km >= 1.0 {
+            (km, "km")
+        } else if km >= 1e-3 {
+            (km * 1e3, "m")
+        } else if km >= 1e-5 {
+            (km * 1e5, "cm")
+        } else {
+            (km * 1e6, "mm")
+        };
+        if v >= 100.0 {
+            format!("{v:.0} {unit}")
+        } else if v >= 10.0 {
+            format!("{v:.1} {unit}")
+        } else {
+            format!("{v:.2} {unit}")
+        }
+    }
+
+    #[test]
+    fn test_doc_table() {
+        // The table in the module documentation must match the metric.
+        let expected: Vec<String> = (0..=S2CellId::MAX_LEVEL)
+            .map(|level| {
+                let km = angle_to_km(S1Angle::from_radians(AVG_EDGE.get_value(level)));
+                format!("//! | {:>5} | {:>10} |", level, format_km(km))
+            })
+            .collect();
+        let actual: Vec<&str> = include_str!("s2earth.rs")
+            .lines()
+            .filter(|line| line.starts_with("//! | ") && !line.contains("level"))
+            .collect();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_conversions() {
+        let angle = S1Angle::from_degrees(1.0);
+        assert!((angle_to_km(angle) - 111.195).abs() < 1e-3);
+        assert!(
+            (meters_to_angle(angle_to_meters(angle)).radians() - angle.radians()).abs() < 1e-15
+        );
+        assert!((km_to_angle(angle_to_km(angle)).radians() - angle.radians()).abs() < 1e-15);
+        assert!((steradians_to_square_km(square_km_to_steradians(5.0)) - 5.0).abs() < 1e-12);
+        let sphere = steradians_to_square_km(4.0 * core::f64::consts::PI);
+        assert!((sphere - 510_066_073.1).abs() < 0.1);
+        let a = S2LatLng::from_degrees(0.0, 0.0);
+        let b = S2LatLng::from_degrees(0.0, 1.0);
+        assert!((get_distance_meters(&a, &b) - 111_195.1).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_level_for_meters() {
+        let mut prev = 0;
+        for i in 0..100 {
+            let meters = 1e7 * 0.75_f64.powi(i);
+            let level = level_for_meters(meters);
+            assert!(level >= prev);
+            prev = level;
+            let max_edge = angle_to_meters(S1Angle::from_radians(MAX_EDGE.get_value(level)));
+            assert!(level == S2CellId::MAX_LEVEL || max_edge <= meters);
+
+            let km2 = 1e-6 * meters * meters;
+            let area_level = level_for_square_km(km2);
+            let max_area = steradians_to_square_km(MAX_AREA.get_value(area_level));
+            assert!(area_level == S2CellId::MAX_LEVEL || max_area <= km2 * (1.0 + 1e-12));
+        }
+        assert_eq!(level_for_meters(1000.0), 14);
+        assert_eq!(level_for_meters(0.0), S2CellId::MAX_LEVEL);
+    }
+}
@@ -0,0 +1,400 @@
+// Copyright 2005 Google Inc. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS-IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+// Original Author: ericv@google.com (Eric Veach)
+//
+// Defines a collection of functions for:
+//
+//   (1) Robustly clipping geodesic edges to the faces of the S2 biunit cube
+//       (see s2coords.h), and
+//
+//   (2) Robustly clipping 2D edges against 2D rectangles.
+//
+// These functions can be used to efficiently find the set of S2CellIds that
+// are intersected by a geodesic edge (e.g., see S2CrossingEdgeQuery). The
+// 2D clipping functions live on R2Edge and R2Rect.
+
+use core::f64::consts::FRAC_1_SQRT_2;
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+use crate::{
+    r2::R2Point,
+    s2::{face_xyz_to_uvw, get_face, robust_cross_prod, valid_face_xyz_to_uv, S2Point},
+};
+
+/// The maximum error in the vertices returned by [`clip_to_face`] and
+/// [`clip_to_padded_face`] (compared to an exact calculation), measured as
+/// the angle between the returned point and the exact result.
+pub const FACE_CLIP_ERROR_RADIANS: f64 = 3.0 * f64::EPSILON;
+
+/// The same error as [`FACE_CLIP_ERROR_RADIANS`], expressed as the maximum
+/// distance in (u,v)-space between the returned vertex and the exact result.
+pub const FACE_CLIP_ERROR_UV_DIST: f64 = 9.0 * f64::EPSILON;
+
+/// The same error expressed as the maximum error in an individual u- or
+/// v-coordinate. In other words, for each returned vertex there is a point
+/// on the exact edge AB whose u- and v-coordinates differ from the vertex
+/// by at most this amount.
+pub const FACE_CLIP_ERROR_UV_COORD: f64 = 9.0 * FRAC_1_SQRT_2 * f64::EPSILON;
+
+/// The maximum error in the u- or v-coordinate of the endpoints returned by
+/// [`R2Edge::clip_to_rect`](crate::r2::R2Edge::clip_to_rect), compared to an
+/// exact calculation.
+pub const EDGE_CLIP_ERROR_UV_COORD: f64 = 2.25 * f64::EPSILON;
+
+/// The maximum distance in (u,v)-space between the endpoints returned by
+/// [`R2Edge::clip_to_rect`](crate::r2::R2Edge::clip_to_rect) and the exact
+/// result.
+pub const EDGE_CLIP_ERROR_UV_DIST: f64 = 2.25 * f64::EPSILON;
+
+/// The maximum angle between a returned vertex and the nearest point on the
+/// exact edge AB, expressed as the distance in (u,v)-space. This is the
+/// padding needed by callers of
+/// [`R2Rect::intersects_edge`](crate::r2::R2Rect::intersects_edge) that want
+/// to guarantee no false negatives.
+pub const INTERSECTS_RECT_ERROR_UV_DIST: f64 = 3.0 * core::f64::consts::SQRT_2 * f64::EPSILON;
+
+/// Given an edge AB and a face, return the (u,v) coordinates for the portion
+/// of AB that intersects that face, or `None` if AB does not intersect the
+/// face. This method guarantees that the clipped vertices lie within the
+/// [-1,1]x[-1,1] cube face rectangle and are within
+/// [`FACE_CLIP_ERROR_UV_DIST`] of the line AB, but the results may differ
+/// from those produced by a full face-by-face walk along the edge.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{clip_to_face, S2Point};
+///
+/// // An edge from the center of face 0 to the center of face 1 exits face 0
+/// // through its u=1 edge.
+/// let a = S2Point::new(1.0, 0.0, 0.0);
+/// let b = S2Point::new(0.0, 1.0, 0.0);
+/// let (a_uv, b_uv) = clip_to_face(&a, &b, 0).unwrap();
+/// assert_eq!(a_uv.x(), 0.0);
+/// assert_eq!(b_uv.x(), 1.0);
+/// assert!(clip_to_face(&a, &b, 3).is_none());
+/// ```
+pub fn clip_to_face(a: &S2Point, b: &S2Point, face: i32) -> Option<(R2Point, R2Point)> {
+    clip_to_padded_face(a, b, face, 0.0)
+}
+
+/// Like [`clip_to_face`], but rather than clipping to the square [-1,1]x[-1,1]
+/// in (u,v) space, this method clips to [-R,R]x[-R,R] where R=(1+padding).
+pub fn clip_to_padded_face(
+    a_xyz: &S2Point,
+    b_xyz: &S2Point,
+    face: i32,
+    padding: f64,
+) -> Option<(R2Point, R2Point)> {
+    debug_assert!(padding >= 0.0);
+    // Fast path: both endpoints are on the given face.
+    if get_face(a_xyz) == face && get_face(b_xyz) == face {
+        return Some((
+            valid_face_xyz_to_uv(face, a_xyz),
+            valid_face_xyz_to_uv(face, b_xyz),
+        ));
+    }
+    // Convert everything into the (u,v,w) coordinates of the given face. Note
+    // that the cross product *must* be computed in the original (x,y,z)
+    // coordinate system because robust_cross_prod (unlike the mathematical
+    // cross product) can produce different results in different coordinate
+    // systems when one argument is a linear multiple of the other, due to the
+    // use of symbolic perturbations.
+    let mut n = face_xyz_to_uvw(face, &robust_cross_prod(a_xyz, b_xyz));
+    let a = face_xyz_to_uvw(face, a_xyz);
+    let b = face_xyz_to_uvw(face, b_xyz);
+
+    // Padding is handled by scaling the u- and v-components of the normal.
+    // Letting R=1+padding, this means that when we compute the dot product of
+    // the normal with a cube face vertex (such as (-1,-1,1)), we will actually
+    // compute the dot product with the scaled vertex (-R,-R,1). This allows
+    // methods such as intersects_face(), get_exit_axis(), etc, to handle
+    // padding with no further modifications.
+    let scale_uv = 1.0 + padding;
+    let scaled_n = S2Point::new(scale_uv * n[0], scale_uv * n[1], n[2]);
+    if !intersects_face(&scaled_n) {
+        return None;
+    }
+
+    // Avoid loss of precision in normalize() when the vector is so small that
+    // it underflows.
+    if n[0].abs().max(n[1].abs()).max(n[2].abs()) < 2f64.powi(-511) {
+        n = n * 2f64.powi(563);
+    }
+    let n = n.normalize();
+    let a_tangent = n.cross_prod(&a);
+    let b_tangent = b.cross_prod(&n);
+    // As described in clip_destination(), if the sum of the scores from
+    // clipping the two endpoints is 3 or more, then the segment does not
+    // intersect this face.
+    let (a_uv, a_score) = clip_destination(&b, &a, &-scaled_n, &b_tangent, &a_tangent, scale_uv);
+    let (b_uv, b_score) = clip_destination(&a, &b, &scaled_n, &a_tangent, &b_tangent, scale_uv);
+    (a_score + b_score < 3).then_some((a_uv, b_uv))
+}
+
+// The two functions below compare a sum (u + v) to a third value w. They are
+// implemented in such a way that they produce an exact result even though all
+// calculations are done with ordinary floating-point operations.
+
+// Return true if a given directed line L intersects the cube face F. The line
+// L is defined by its normal N in the (u,v,w) coordinates of F.
+fn intersects_face(n: &S2Point) -> bool {
+    // L intersects the [-1,1]x[-1,1] square in (u,v) if and only if the dot
+    // products of N with the four corner vertices (-1,-1,1), (1,-1,1), (1,1,1),
+    // and (-1,1,1) do not all have the same sign. This is true exactly when
+    // |Nu| + |Nv| >= |Nw|. The code below evaluates this expression exactly.
+    let (u, v, w) = (n[0].abs(), n[1].abs(), n[2].abs());
+    // We only need to consider the cases where u or v is the smallest value,
+    // since if w is the smallest then both expressions below will have a
+    // positive LHS and a negative RHS.
+    v >= w - u && u >= w - v
+}
+
+// Given a directed line L intersecting a cube face F, return true if L
+// intersects two opposite edges of F (including the case where L passes
+// exactly through a corner vertex of F). The line L is defined by its normal
+// N in the (u,v,w) coordinates of F.
+fn intersects_opposite_edges(n: &S2Point) -> bool {
+    // The line L intersects opposite edges of the [-1,1]x[-1,1] (u,v) square if
+    // and only exactly two of the corner vertices lie on each side of L. This
+    // is true exactly when ||Nu| - |Nv|| >= |Nw|. The code below evaluates this
+    // expression exactly.
+    let (u, v, w) = (n[0].abs(), n[1].abs(), n[2].abs());
+    // If w is the smallest, the following line returns an exact result.
+    if (u - v).abs() != w {
+        return (u - v).abs() >= w;
+    }
+    // Otherwise u - v = w exactly, or w is not the smallest value. In either
+    // case the following line returns the correct result.
+    if u >= v {
+        u - w >= v
+    } else {
+        v - w >= u
+    }
+}
+
+// Given cube face F and a directed line L (represented by its CCW normal N in
+// the (u,v,w) coordinates of F), compute the axis of the cube face edge where
+// L exits the face: return 0 if L exits through the u=-1 or u=+1 edge, and 1
+// if L exits through the v=-1 or v=+1 edge. Either result is acceptable if L
+// exits exactly through a corner vertex of the cube face.
+fn get_exit_axis(n: &S2Point) -> usize {
+    debug_assert!(intersects_face(n));
+    if intersects_opposite_edges(n) {
+        // The line passes through through opposite edges of the face.
+        // It exits through the v=+1 or v=-1 edge if the u-component of N has a
+        // larger absolute magnitude than the v-component.
+        if n[0].abs() >= n[1].abs() {
+            1
+        } else {
+            0
+        }
+    } else {
+        // The line passes through two adjacent edges of the face.
+        // It exits the v=+1 or v=-1 edge if an even number of the components of
+        // N are negative. We test this using sign bits rather than
+        // multiplication to avoid the possibility of underflow.
+        debug_assert!(n[0] != 0.0 && n[1] != 0.0 && n[2] != 0.0);
+        if n[0].is_sign_negative() ^ n[1].is_sign_negative() ^ n[2].is_sign_negative() {
+            0
+        } else {
+            1
+        }
+    }
+}
+
+// Given a cube face F, a directed line L (represented by its CCW normal N in
+// the (u,v,w) coordinates of F), and result of get_exit_axis(N), return the
+// (u,v) coordinates of the point where L exits the cube face.
+fn get_exit_point(n: &S2Point, axis: usize) -> R2Point {
+    if axis == 0 {
+        let u = if n[1] > 0.0 { 1.0 } else { -1.0 };
+        R2Point::new(u, (-u * n[0] - n[2]) / n[1])
+    } else {
+        let v = if n[0] < 0.0 { 1.0 } else { -1.0 };
+        R2Point::new((-v * n[1] - n[2]) / n[0], v)
+    }
+}
+
+// This helper function does two things. First, it clips the line segment AB
+// to find the clipped destination B' on a given face. (The face is specified
+// implicitly by expressing *all arguments* in the (u,v,w) coordinates of that
+// face.) Second, it partially computes whether the segment AB intersects this
+// face at all. The actual condition is fairly complicated, but it turns out
+// that it can be expressed as a "score" that can be computed independently
+// when clipping the two endpoints A and B. This function returns the clipped
+// point together with the score for the given endpoint, which is an integer
+// ranging from 0 to 3. If the sum of the two scores is 3 or more, then AB
+// does not intersect this face.
+fn clip_destination(
+    a: &S2Point,
+    b: &S2Point,
+    scaled_n: &S2Point,
+    a_tangent: &S2Point,
+    b_tangent: &S2Point,
+    scale_uv: f64,
+) -> (R2Point, i32) {
+    debug_assert!(intersects_face(scaled_n));
+
+    // Optimization: if B is within the safe region of the face, use it.
+    const MAX_SAFE_UV_COORD: f64 = 1.0 - FACE_CLIP_ERROR_UV_COORD;
+    if b[2] > 0.0 {
+        let uv = R2Point::new(b[0] / b[2], b[1] / b[2]);
+        if uv[0].abs().max(uv[1].abs()) <= MAX_SAFE_UV_COORD {
+            return (uv, 0);
+        }
+    }
+    // Otherwise find the point B' where the line AB exits the face.
+    let mut uv = get_exit_point(scaled_n, get_exit_axis(scaled_n)) * scale_uv;
+    let p = S2Point::new(uv[0], uv[1], 1.0);
+
+    // Determine if the exit point B' is contained within the segment. We do
+    // this by computing the dot products with two inward-facing tangent
+    // vectors at A and B. If either dot product is negative, we say that B' is
+    // on the "wrong side" of that point. As the point B' moves around the great
+    // circle AB past the segment endpoint B, it is initially on the wrong side
+    // of B only; as it moves further it is on the wrong side of both endpoints;
+    // and then it is on the wrong side of A only. If the exit point B' is on
+    // the wrong side of either endpoint, we can't use it; instead the segment
+    // is clipped at the original endpoint B.
+    //
+    // We reject the segment if the sum of the scores of the two endpoints is 3
+    // or more. Here is what that rule encodes:
+    //  - If B' is on the wrong side of A, then the other clipped endpoint A'
+    //    must be in the interior of AB (otherwise AB' would go the wrong way
+    //    around the circle). There is a similar rule for A'.
+    //  - If B' is on the wrong side of either endpoint (and therefore we must
+    //    use the original endpoint B instead), then it must be possible to
+    //    project B onto this face (i.e., its w-coordinate must be positive).
+    //    This rule is only necessary to handle certain zero-length edges (A=B).
+    let mut score = 0;
+    if (p - *a).dot_prod(a_tangent) < 0.0 {
+        score = 2; // B' is on wrong side of A.
+    } else if (p - *b).dot_prod(b_tangent) < 0.0 {
+        score = 1; // B' is on wrong side of B.
+    }
+    if score > 0 {
+        // B' is not in the interior of AB.
+        if b[2] <= 0.0 {
+            score = 3; // B cannot be projected onto this face.
+        } else {
+            uv = R2Point::new(b[0] / b[2], b[1] / b[2]);
+        }
+    }
+    (uv, score)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::s2::s2testing::random_point;
+    use crate::s2::{face_uv_to_xyz_from_r2point, get_uvw_axis};
+
+    // Check that a clipped (u,v) point lies inside the padded face and within
+    // the documented tolerance of the great circle through A and B.
+    fn check_clipped_point(a: &S2Point, b: &S2Point, face: i32, padding: f64, uv: &R2Point) {
+        let max_uv = 1.0 + padding;
+        assert!(uv[0].abs() <= max_uv && uv[1].abs() <= max_uv, "{uv:?}");
+        let p = face_uv_to_xyz_from_r2point(face, uv).normalize();
+        let n = robust_cross_prod(a, b).normalize();
+        assert!(p.dot_prod(&n).abs() <= FACE_CLIP_ERROR_RADIANS + 1e-15);
+    }
+
+    #[test]
+    fn test_clip_to_face_same_face() {
+        let a = face_uv_to_xyz_from_r2point(2, &R2Point::new(-0.5, 0.25)).normalize();
+        let b = face_uv_to_xyz_from_r2point(2, &R2Point::new(0.75, -0.1)).normalize();
+        let (a_uv, b_uv) = clip_to_face(&a, &b, 2).unwrap();
+        assert!((a_uv - R2Point::new(-0.5, 0.25)).norm() < 1e-15);
+        assert!((b_uv - R2Point::new(0.75, -0.1)).norm() < 1e-15);
+        // The edge is far from the opposite face.
+        assert!(clip_to_face(&a, &b, 5).is_none());
+    }
+
+    #[test]
+    fn test_clip_to_face_crossing_faces() {
+        // An edge along the equator from face 0 into face 1 exits face 0 at
+        // u=1 and enters face 1 at u=-1.
+        let a = S2Point::new(1.0, -0.5, 0.0).normalize();
+        let b = S2Point::new(-0.5, 1.0, 0.0).normalize();
+        let (a0, b0) = clip_to_face(&a, &b, 0).unwrap();
+        assert!((a0 - R2Point::new(-0.5, 0.0)).norm() < 1e-15);
+        assert!((b0 - R2Point::new(1.0, 0.0)).norm() < 1e-15);
+        let (a1, b1) = clip_to_face(&a, &b, 1).unwrap();
+        assert!((a1 - R2Point::new(-1.0, 0.0)).norm() < 1e-15);
+        assert!((b1 - R2Point::new(0.5, 0.0)).norm() < 1e-15);
+        for face in [2, 3, 4, 5] {
+            assert!(clip_to_face(&a, &b, face).is_none());
+        }
+    }
+
+    #[test]
+    fn test_clip_to_face_through_corner() {
+        // The diagonal of face 2 extended to the neighboring faces passes
+        // exactly through two corners of the face.
+        let a = S2Point::new(-2.0, -2.0, 1.0).normalize();
+        let b = S2Point::new(2.0, 2.0, 1.0).normalize();
+        let (a_uv, b_uv) = clip_to_face(&a, &b, 2).unwrap();
+        assert!((a_uv - R2Point::new(1.0, 1.0)).norm() <= FACE_CLIP_ERROR_UV_DIST);
+        assert!((b_uv - R2Point::new(-1.0, -1.0)).norm() <= FACE_CLIP_ERROR_UV_DIST);
+    }
+
+    #[test]
+    fn test_clip_to_face_random() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..1000 {
+            let a = random_point(&mut rng);
+            // Keep edges shorter than a hemisphere so that the expected set of
+            // faces is easy to reason about.
+            let b = (a + random_point(&mut rng) * rng.gen_range(0.0..1.0)).normalize();
+            let padding = if rng.gen_bool(0.5) {
+                0.0
+            } else {
+                rng.gen_range(0.0..0.5)
+            };
+            let mut hit = false;
+            for face in 0..6 {
+                if let Some((a_uv, b_uv)) = clip_to_padded_face(&a, &b, face, padding) {
+                    hit = true;
+                    check_clipped_point(&a, &b, face, padding, &a_uv);
+                    check_clipped_point(&a, &b, face, padding, &b_uv);
+                }
+            }
+            // Every edge intersects at least the faces of its endpoints.
+            assert!(hit);
+            assert!(clip_to_padded_face(&a, &b, get_face(&a), padding).is_some());
+            assert!(clip_to_padded_face(&a, &b, get_face(&b), padding).is_some());
+        }
+    }
+
+    #[test]
+    fn test_face_xyz_to_uvw() {
+        for face in 0..6 {
+            assert_eq!(
+                face_xyz_to_uvw(face, &get_uvw_axis(face, 2)),
+                S2Point::new(0.0, 0.0, 1.0)
+            );
+            assert_eq!(
+                face_xyz_to_uvw(face, &get_uvw_axis(face, 0)),
+                S2Point::new(1.0, 0.0, 0.0)
+            );
+        }
+    }
+}
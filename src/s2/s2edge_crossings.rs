@@ -0,0 +1,258 @@
+// Copyright 2005 Google Inc. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS-IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+// Original Author: ericv@google.com (Eric Veach)
+//
+// Defines functions related to determining whether two geodesic edges cross.
+
+use crate::s2::{ref_dir, s2predicates::ordered_ccw, sign, S2Point};
+
+/// This function determines whether the edge AB intersects the edge CD.
+/// Returns +1 if AB crosses CD at a point that is interior to both edges.
+/// Returns 0 if any two vertices from different edges are the same.
+/// Returns -1 otherwise.
+///
+/// Note that if an edge is degenerate (A == B or C == D), the return value
+/// is 0 if two vertices from different edges are the same and -1 otherwise.
+///
+/// Properties of crossing_sign:
+///
+///  (1) crossing_sign(b,a,c,d) == crossing_sign(a,b,c,d)
+///  (2) crossing_sign(c,d,a,b) == crossing_sign(a,b,c,d)
+///  (3) crossing_sign(a,b,c,d) == 0 if a==c, a==d, b==c, b==d
+///  (4) crossing_sign(a,b,c,d) <= 0 if a==b or c==d (see above)
+///
+/// Since sign() does not apply symbolic perturbations, edges where three of
+/// the four points are exactly collinear (without sharing a vertex) are
+/// reported as not crossing.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{crossing_sign, S2Point};
+///
+/// let a = S2Point::new(1.0, 2.0, 1.0).normalize();
+/// let b = S2Point::new(1.0, -3.0, 0.5).normalize();
+/// let c = S2Point::new(1.0, -0.5, -3.0).normalize();
+/// let d = S2Point::new(0.1, 0.5, 3.0).normalize();
+/// assert_eq!(crossing_sign(&a, &b, &c, &d), 1);
+/// assert_eq!(crossing_sign(&a, &b, &c, &-d), -1);
+/// assert_eq!(crossing_sign(&a, &b, &b, &d), 0);
+/// ```
+pub fn crossing_sign(a: &S2Point, b: &S2Point, c: &S2Point, d: &S2Point) -> i32 {
+    if a == c || a == d || b == c || b == d {
+        return 0;
+    }
+    if a == b || c == d {
+        return -1;
+    }
+    // The edges cross if and only if the vertices of each edge are strictly
+    // on opposite sides of the other edge, with consistent orientations.
+    let acb = -sign(a, b, c);
+    let bda = sign(a, b, d);
+    if acb == 0 || bda != acb {
+        return -1;
+    }
+    let cbd = -sign(c, d, b);
+    if cbd != acb {
+        return -1;
+    }
+    let dac = sign(c, d, a);
+    if dac == acb {
+        1
+    } else {
+        -1
+    }
+}
+
+/// Given two edges AB and CD where at least two vertices are identical
+/// (i.e. crossing_sign(a,b,c,d) == 0), this function defines whether the two
+/// edges "cross" in such a way that point-in-polygon containment tests can
+/// be implemented by counting the number of edge crossings. The basic rule
+/// is that a "crossing" occurs if AB is encountered after CD during a CCW
+/// sweep around the shared vertex starting from a fixed reference point.
+///
+/// Note that according to this rule, if AB crosses CD then in general CD
+/// does not cross AB. However, this leads to the correct result when
+/// counting polygon edge crossings. For example, suppose that A,B,C are
+/// three consecutive vertices of a CCW polygon. If we now consider the edge
+/// crossings of a segment BP as P sweeps around B, the crossing number
+/// changes parity exactly when BP crosses BA or BC.
+pub fn vertex_crossing(a: &S2Point, b: &S2Point, c: &S2Point, d: &S2Point) -> bool {
+    // If A == B or C == D there is no intersection. We need to check this
+    // case first in case 3 or more input points are identical.
+    if a == b || c == d {
+        return false;
+    }
+
+    // If any other pair of vertices is equal, there is a crossing if and only
+    // if ordered_ccw() indicates that the edge AB is further CCW around the
+    // shared vertex O (either A or B) than the edge CD, starting from an
+    // arbitrary fixed reference point.
+    if a == c {
+        return b == d || ordered_ccw(&ref_dir(a), d, b, a);
+    }
+    if b == d {
+        return ordered_ccw(&ref_dir(b), c, a, b);
+    }
+    if a == d {
+        return b == c || ordered_ccw(&ref_dir(a), c, b, a);
+    }
+    if b == c {
+        return ordered_ccw(&ref_dir(b), d, a, b);
+    }
+    false
+}
+
+/// A convenience function that calls crossing_sign() to handle cases where
+/// all four vertices are distinct, and vertex_crossing() to handle cases
+/// where two or more vertices are the same. This defines a crossing
+/// function such that point-in-polygon containment tests can be implemented
+/// by simply counting edge crossings.
+pub fn edge_or_vertex_crossing(a: &S2Point, b: &S2Point, c: &S2Point, d: &S2Point) -> bool {
+    match crossing_sign(a, b, c, d) {
+        crossing if crossing < 0 => false,
+        0 => vertex_crossing(a, b, c, d),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::s2::s2testing::random_point;
+
+    // Check crossing_sign() and its symmetries, along with the consistency
+    // of edge_or_vertex_crossing().
+    fn check_crossing(a: &S2Point, b: &S2Point, c: &S2Point, d: &S2Point, expected: i32) {
+        assert_eq!(crossing_sign(a, b, c, d), expected);
+        assert_eq!(crossing_sign(b, a, c, d), expected);
+        assert_eq!(crossing_sign(a, b, d, c), expected);
+        assert_eq!(crossing_sign(c, d, a, b), expected);
+        if expected != 0 {
+            assert_eq!(edge_or_vertex_crossing(a, b, c, d), expected > 0);
+        }
+    }
+
+    #[test]
+    fn test_crossings() {
+        // Two regular edges that cross.
+        let a = S2Point::new(1.0, 2.0, 1.0).normalize();
+        let b = S2Point::new(1.0, -3.0, 0.5).normalize();
+        let c = S2Point::new(1.0, -0.5, -3.0).normalize();
+        let d = S2Point::new(0.1, 0.5, 3.0).normalize();
+        check_crossing(&a, &b, &c, &d, 1);
+
+        // Two regular edges that intersect antipodal points.
+        let c = S2Point::new(-1.0, 0.5, 3.0).normalize();
+        let d = S2Point::new(-0.1, -0.5, -3.0).normalize();
+        check_crossing(&a, &b, &c, &d, -1);
+
+        // Two edges on the same great circle that start at antipodal points.
+        let a = S2Point::new(0.0, 0.0, -1.0);
+        let b = S2Point::new(0.0, 1.0, 0.0);
+        let c = S2Point::new(0.0, 0.0, 1.0);
+        let d = S2Point::new(0.0, 1.0, 1.0).normalize();
+        check_crossing(&a, &b, &c, &d, -1);
+
+        // An edge between two coordinate axes crossed by an edge from the third.
+        let a = S2Point::new(1.0, 0.0, 0.0);
+        let b = S2Point::new(0.0, 1.0, 0.0);
+        let c = S2Point::new(0.0, 0.0, 1.0);
+        let d = S2Point::new(1.0, 1.0, -1.0).normalize();
+        check_crossing(&a, &b, &c, &d, 1);
+    }
+
+    #[test]
+    fn test_shared_and_degenerate_vertices() {
+        let a = S2Point::new(1.0, 0.0, 0.0);
+        let b = S2Point::new(0.0, 1.0, 0.0);
+        let c = S2Point::new(0.0, 0.0, 1.0);
+        // Shared vertices.
+        check_crossing(&a, &b, &b, &c, 0);
+        check_crossing(&a, &b, &c, &a, 0);
+        check_crossing(&a, &b, &a, &b, 0);
+        // Degenerate edges that share no vertex with the other edge.
+        assert_eq!(crossing_sign(&a, &a, &b, &c), -1);
+        assert_eq!(crossing_sign(&b, &c, &a, &a), -1);
+        assert!(!edge_or_vertex_crossing(&a, &a, &b, &c));
+        // Degenerate edges that do share a vertex.
+        assert_eq!(crossing_sign(&a, &a, &a, &b), 0);
+        assert!(!vertex_crossing(&a, &a, &a, &b));
+    }
+
+    #[test]
+    fn test_touching_and_parallel_edges() {
+        let a = S2Point::new(1.0, 0.0, 0.0);
+        let b = S2Point::new(0.0, 1.0, 0.0);
+        let mid = S2Point::new(1.0, 1.0, 0.0).normalize();
+
+        // A vertex that lies on the interior of the other edge ("T"
+        // intersection) is not counted as a crossing.
+        check_crossing(&a, &b, &mid, &S2Point::new(1.0, 1.0, 1.0).normalize(), -1);
+
+        // Overlapping edges on the same great circle do not cross.
+        check_crossing(&a, &mid, &S2Point::new(2.0, 1.0, 0.0).normalize(), &b, -1);
+
+        // Disjoint edges on the same great circle.
+        check_crossing(&a, &S2Point::new(3.0, 1.0, 0.0).normalize(), &mid, &b, -1);
+
+        // Edges along two different lines of latitude never meet.
+        let north = |lng: f64| S2Point::new(lng.cos(), lng.sin(), 0.5).normalize();
+        let south = |lng: f64| S2Point::new(lng.cos(), lng.sin(), -0.5).normalize();
+        check_crossing(&north(0.0), &north(0.5), &south(0.0), &south(0.5), -1);
+    }
+
+    #[test]
+    fn test_vertex_crossing_antisymmetry() {
+        // When two edges share their first vertex, exactly one of them is
+        // considered to cross the other, so that crossings are counted once.
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..1000 {
+            let a = random_point(&mut rng);
+            let b = random_point(&mut rng);
+            let d = random_point(&mut rng);
+            assert_ne!(
+                vertex_crossing(&a, &b, &a, &d),
+                vertex_crossing(&a, &d, &a, &b)
+            );
+            assert!(vertex_crossing(&a, &b, &a, &b));
+            assert!(
+                edge_or_vertex_crossing(&a, &b, &a, &d) != edge_or_vertex_crossing(&a, &d, &a, &b)
+            );
+        }
+    }
+
+    #[test]
+    fn test_random_symmetry() {
+        let mut rng = StdRng::seed_from_u64(2);
+        for _ in 0..1000 {
+            let a = random_point(&mut rng);
+            let b = random_point(&mut rng);
+            let c = random_point(&mut rng);
+            let d = random_point(&mut rng);
+            let expected = crossing_sign(&a, &b, &c, &d);
+            // Random edges have distinct vertices and are never degenerate.
+            assert!(expected == 1 || expected == -1);
+            check_crossing(&a, &b, &c, &d, expected);
+            // If AB crosses CD, it cannot also cross the antipodal edge.
+            if expected == 1 {
+                assert_eq!(crossing_sign(&a, &b, &-c, &-d), -1);
+            }
+        }
+    }
+}
@@ -0,0 +1,618 @@
+// Copyright 2005 Google Inc. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS-IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+// Original Author: ericv@google.com (Eric Veach)
+//
+// Defines functions related to determining the distance between points and
+// edges, and interpolating along edges.
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+use crate::{
+    s1::{S1Angle, S1ChordAngle},
+    s2::{is_unit_length, robust_cross_prod, sign, S2Point},
+};
+
+/// Return the point X along the line segment AB whose distance from A is the
+/// given fraction "t" of the distance AB. Does NOT require that "t" be
+/// between 0 and 1. Note that all distances are measured on the surface of
+/// the sphere, so this is more complicated than just computing (1-t)*a + t*b
+/// and normalizing the result.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{interpolate, S2Point};
+///
+/// let a = S2Point::new(1.0, 0.0, 0.0);
+/// let b = S2Point::new(0.0, 1.0, 0.0);
+/// let mid = interpolate(0.5, &a, &b);
+/// assert!((mid - S2Point::new(1.0, 1.0, 0.0).normalize()).norm() < 1e-15);
+/// ```
+pub fn interpolate(t: f64, a: &S2Point, b: &S2Point) -> S2Point {
+    if t == 0.0 {
+        return *a;
+    }
+    if t == 1.0 {
+        return *b;
+    }
+    let ab = S1Angle::from_points(a, b);
+    interpolate_at_distance(S1Angle::from_radians(t * ab.radians()), a, b)
+}
+
+/// Like interpolate(), except that the parameter "ax" represents the
+/// desired distance from A to the result X rather than a fraction between A
+/// and B.
+///
+/// If you know the length of AB and you want to call this function repeatedly
+/// with various distances along the edge, it's more efficient to do this
+/// rather than calling interpolate() with a fraction.
+pub fn interpolate_at_distance(ax: S1Angle, a: &S2Point, b: &S2Point) -> S2Point {
+    let ax = ax.radians();
+    debug_assert!(is_unit_length(a));
+    debug_assert!(is_unit_length(b));
+
+    // Use robust_cross_prod() to compute the tangent vector at A towards B.
+    // The result is always perpendicular to A, even if A=B or A=-B, but it is
+    // not necessarily unit length. (We effectively normalize it below.)
+    let normal = robust_cross_prod(a, b);
+    let tangent = normal.cross_prod(a);
+    debug_assert!(tangent != S2Point::zero());
+
+    // Now compute the appropriate linear combination of A and "tangent". With
+    // infinite precision the result would always be unit length, but we
+    // normalize it anyway to ensure that the error is within acceptable
+    // bounds. (Otherwise errors can build up when the result of one
+    // interpolation is fed into another interpolation.)
+    (a * ax.cos() + tangent * (ax.sin() / tangent.norm())).normalize()
+}
+
+/// Returns the point along the edge AB that is closest to the point X.
+/// Requires that all vectors have unit length.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{project, S2Point};
+///
+/// let a = S2Point::new(1.0, 0.0, 0.0);
+/// let b = S2Point::new(0.0, 1.0, 0.0);
+/// // A point above the middle of the edge projects onto the edge...
+/// let x = S2Point::new(1.0, 1.0, 1.0).normalize();
+/// let p = project(&x, &a, &b);
+/// assert!((p - S2Point::new(1.0, 1.0, 0.0).normalize()).norm() < 1e-15);
+/// // ...while a point beyond B projects onto B.
+/// assert_eq!(
+///     project(&S2Point::new(-1.0, 1.0, 0.0).normalize(), &a, &b),
+///     b
+/// );
+/// ```
+pub fn project(x: &S2Point, a: &S2Point, b: &S2Point) -> S2Point {
+    project_with_cross(x, a, b, &robust_cross_prod(a, b))
+}
+
+/// A slightly more efficient version of project() where the cross product of
+/// the two endpoints has been precomputed. The cross product does not need
+/// to be normalized, but should be computed using robust_cross_prod() for
+/// the most accurate results.
+pub fn project_with_cross(x: &S2Point, a: &S2Point, b: &S2Point, a_cross_b: &S2Point) -> S2Point {
+    debug_assert!(is_unit_length(a));
+    debug_assert!(is_unit_length(b));
+    debug_assert!(is_unit_length(x));
+
+    // Find the closest point to X along the great circle through AB.
+    let p = *x - a_cross_b * (x.dot_prod(a_cross_b) / a_cross_b.norm2());
+
+    // If this point is on the edge AB, then it's the closest point.
+    if sign(a_cross_b, a, &p) > 0 && sign(&p, b, a_cross_b) > 0 {
+        return p.normalize();
+    }
+
+    // Otherwise, the closest point is either A or B.
+    if (x - a).norm2() <= (x - b).norm2() {
+        *a
+    } else {
+        *b
+    }
+}
+
+/// Returns the minimum distance from X to any point on the edge AB. All
+/// arguments should be unit length. The result is very accurate for small
+/// distances but may have some numerical error if the distance is large
+/// (approximately Pi/2 or greater). The case A == B is handled correctly.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{get_distance, S2Point};
+///
+/// let a = S2Point::new(1.0, 0.0, 0.0);
+/// let b = S2Point::new(0.0, 1.0, 0.0);
+/// let x = S2Point::new(1.0, 1.0, 0.0).normalize();
+/// assert!(get_distance(&x, &a, &b).radians() < 1e-15);
+/// let z = S2Point::new(0.0, 0.0, 1.0);
+/// assert!((get_distance(&z, &a, &b).degrees() - 90.0).abs() < 1e-13);
+/// ```
+pub fn get_distance(x: &S2Point, a: &S2Point, b: &S2Point) -> S1Angle {
+    let mut min_dist = S1ChordAngle::zero();
+    always_update_min_distance(x, a, b, &mut min_dist, true);
+    S1Angle::from(min_dist)
+}
+
+/// If the distance from X to the edge AB is less than "min_dist", this
+/// method updates "min_dist" and returns true. Otherwise it returns false.
+/// The case A == B is handled correctly.
+///
+/// Use this method when you want to compute many distances and keep track
+/// of the minimum. It is significantly faster than using get_distance(),
+/// because (1) using S1ChordAngle is much faster than S1Angle, and (2) it
+/// can save a lot of work by not actually computing the distance when it is
+/// obviously larger than the current minimum.
+pub fn update_min_distance(
+    x: &S2Point,
+    a: &S2Point,
+    b: &S2Point,
+    min_dist: &mut S1ChordAngle,
+) -> bool {
+    always_update_min_distance(x, a, b, min_dist, false)
+}
+
+/// If the maximum distance from X to the edge AB is greater than "max_dist",
+/// this method updates "max_dist" and returns true. Otherwise it returns
+/// false. The case A == B is handled correctly.
+pub fn update_max_distance(
+    x: &S2Point,
+    a: &S2Point,
+    b: &S2Point,
+    max_dist: &mut S1ChordAngle,
+) -> bool {
+    let (xa, xb) = (
+        S1ChordAngle::from_points(x, a),
+        S1ChordAngle::from_points(x, b),
+    );
+    let mut dist = if xa < xb { xb } else { xa };
+    if dist > S1ChordAngle::right() {
+        // The farthest point from X on the edge is the closest point to -X.
+        always_update_min_distance(&-x, a, b, &mut dist, true);
+        dist = S1ChordAngle::straight() - dist;
+    }
+    if *max_dist < dist {
+        *max_dist = dist;
+        return true;
+    }
+    false
+}
+
+/// Returns the maximum error in the result of update_min_distance (and
+/// the associated functions such as get_distance), assuming that all input
+/// points are normalized to within the bounds guaranteed by
+/// S2Point::normalize(). The error can be added or subtracted from an
+/// S1ChordAngle "x" using x.plus_error(error).
+///
+/// Note that accuracy goes down as the distance approaches 0 degrees or 180
+/// degrees (for different reasons). Near 0 degrees the error is acceptable
+/// for all practical purposes (about 1.2e-15 radians ~= 8 nanometers). For
+/// exactly antipodal points the maximum error is quite high (0.5 meters), but
+/// this error drops rapidly as the points move away from antipodality
+/// (approximately 1 millimeter for points that are 50 meters from antipodal,
+/// and approximately 1 nanometer for points that are 1km from antipodal).
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::{s1::S1ChordAngle, s2::get_update_min_distance_max_error};
+///
+/// let error = get_update_min_distance_max_error(S1ChordAngle::from_degrees(10.0));
+/// assert!(error > 0.0 && error < 1e-14);
+/// ```
+pub fn get_update_min_distance_max_error(dist: S1ChordAngle) -> f64 {
+    // There are two cases for the maximum error in update_min_distance(),
+    // depending on whether the closest point is interior to the edge.
+    get_update_min_interior_distance_max_error(dist).max(dist.get_s2point_constructor_max_error())
+}
+
+// Returns the maximum error in the distance computed when the closest point
+// is interior to the edge.
+fn get_update_min_interior_distance_max_error(dist: S1ChordAngle) -> f64 {
+    // If a point is more than 90 degrees from an edge, then the minimum
+    // distance is always to one of the endpoints, not to the edge interior.
+    if dist >= S1ChordAngle::right() {
+        return 0.0;
+    }
+
+    // This bound includes all source of error, assuming that the input points
+    // are normalized to within the bounds guaranteed to S2Point::normalize().
+    // "a" and "b" are components of chord length that are perpendicular and
+    // parallel to the plane containing the edge respectively.
+    let b = (0.5 * dist.length2()).min(1.0);
+    let a = (b * (2.0 - b)).sqrt();
+    ((2.5 + 2.0 * 3f64.sqrt() + 8.5 * a) * a
+        + (2.0 + 2.0 * 3f64.sqrt() / 3.0 + 6.5 * (1.0 - b)) * b
+        + (23.0 + 16.0 / 3f64.sqrt()) * f64::EPSILON)
+        * f64::EPSILON
+}
+
+// If the minimum distance from X to AB is attained at an interior point of
+// AB (i.e., not an endpoint), and that distance is less than "min_dist" (or
+// "always_update" is true), then update "min_dist" and return true.
+// Otherwise return false.
+//
+// The "always_update" parameter controls whether "min_dist" is updated even
+// when the new distance is not smaller, which lets get_distance() reuse this
+// code without needing a sentinel initial value.
+fn always_update_min_interior_distance(
+    x: &S2Point,
+    a: &S2Point,
+    b: &S2Point,
+    xa2: f64,
+    xb2: f64,
+    min_dist: &mut S1ChordAngle,
+    always_update: bool,
+) -> bool {
+    debug_assert!(is_unit_length(x) && is_unit_length(a) && is_unit_length(b));
+
+    // The closest point on AB could either be one of the two vertices (the
+    // "vertex case") or in the interior (the "interior case"). Let C = A x B.
+    // If X is in the spherical wedge extending from A to B around the axis
+    // through C, then we are in the interior case. Otherwise we are in the
+    // vertex case.
+    //
+    // Check whether we might be in the interior case. For this to be true,
+    // XAB and XBA must both be acute angles. Checking this condition exactly
+    // is expensive, so instead we consider the planar triangle ABX (which
+    // passes through the sphere's interior). The planar angles XAB and XBA
+    // are always less than the corresponding spherical angles, so if we are
+    // in the interior case then both of these angles must be acute.
+    //
+    // We check this by computing the squared edge lengths of the planar
+    // triangle ABX, and testing acuteness using the law of cosines:
+    //
+    //             max(XA^2, XB^2) < min(XA^2, XB^2) + AB^2
+    if xa2.max(xb2) >= xa2.min(xb2) + (a - b).norm2() {
+        return false;
+    }
+
+    // The minimum distance might be to a point on the edge interior. Let R be
+    // the closest point to X that lies on the great circle through AB. Rather
+    // than computing the geodesic distance along the surface of the sphere,
+    // instead we compute the "chord length" through the sphere's interior.
+    //
+    // The squared chord length XR^2 can be expressed as XQ^2 + QR^2, where Q
+    // is the point X projected onto the plane through the great circle AB.
+    // The distance XQ^2 can be written as (X.C)^2 / |C|^2 where C = A x B.
+    // We use XQ^2 as a lower bound first, since it is cheap to compute.
+    let c = robust_cross_prod(a, b);
+    let c2 = c.norm2();
+    let x_dot_c = x.dot_prod(&c);
+    let x_dot_c2 = x_dot_c * x_dot_c;
+    if !always_update && x_dot_c2 > c2 * min_dist.length2() {
+        // The closest point on the great circle AB is too far away. We need to
+        // test this using ">" rather than ">=" because the actual minimum
+        // bound on the distance is (x_dot_c2 / c2), which can be rounded
+        // differently than the (more efficient) multiplicative test above.
+        return false;
+    }
+
+    // Otherwise we do the exact, more expensive test for the interior case.
+    // This test is very likely to succeed because of the conservative planar
+    // test we did initially.
+    let cx = c.cross_prod(x);
+    if a.dot_prod(&cx) >= 0.0 || b.dot_prod(&cx) <= 0.0 {
+        return false;
+    }
+
+    // Compute the squared chord length XR^2 = XQ^2 + QR^2 (see above). This
+    // calculation has good accuracy for all chord lengths since it is based
+    // on both the dot product and cross product (rather than deriving one
+    // from the other). However, note that the chord length representation
+    // itself loses accuracy as the angle approaches Pi.
+    let qr = 1.0 - (cx.norm2() / c2).sqrt();
+    let dist2 = (x_dot_c2 / c2) + (qr * qr);
+    if !always_update && dist2 >= min_dist.length2() {
+        return false;
+    }
+    *min_dist = S1ChordAngle::from_length2(dist2);
+    true
+}
+
+// Like update_min_distance(), except that if "always_update" is true then
+// "min_dist" is updated even when the new distance is not smaller.
+fn always_update_min_distance(
+    x: &S2Point,
+    a: &S2Point,
+    b: &S2Point,
+    min_dist: &mut S1ChordAngle,
+    always_update: bool,
+) -> bool {
+    let xa2 = (x - a).norm2();
+    let xb2 = (x - b).norm2();
+    if always_update_min_interior_distance(x, a, b, xa2, xb2, min_dist, always_update) {
+        // Minimum distance is attained along the edge interior.
+        return true;
+    }
+    // Otherwise the minimum distance is to one of the endpoints.
+    let dist2 = xa2.min(xb2);
+    if !always_update && dist2 >= min_dist.length2() {
+        return false;
+    }
+    *min_dist = S1ChordAngle::from_length2(dist2);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::s2::s2testing::random_point;
+
+    fn check_interpolate(t: f64, a: S2Point, b: S2Point, expected: S2Point) {
+        let a = a.normalize();
+        let b = b.normalize();
+        let expected = expected.normalize();
+        let actual = interpolate(t, &a, &b);
+        // We allow a bit more than the usual 1e-15 error tolerance because
+        // interpolate() uses trig functions.
+        assert!(
+            S1Angle::from_points(&expected, &actual).radians() <= 3e-15,
+            "interpolate({t}, {a:?}, {b:?}) = {actual:?}, expected {expected:?}"
+        );
+    }
+
+    #[test]
+    fn test_interpolate() {
+        // A zero-length edge.
+        let p1 = S2Point::new(0.1, 1e-30, 0.3);
+        let p2 = S2Point::new(-0.7, -0.55, -1e30);
+        check_interpolate(0.0, p1, p1, p1);
+        check_interpolate(1.0, p1, p1, p1);
+
+        // Start, end, and middle of a medium-length edge.
+        check_interpolate(0.0, p1, p2, p1);
+        check_interpolate(1.0, p1, p2, p2);
+        check_interpolate(0.5, p1, p2, (p1.normalize() + p2.normalize()) * 0.5);
+
+        // Test that interpolation is done using distances on the sphere rather
+        // than linear distances.
+        let i = S2Point::new(1.0, 0.0, 0.0);
+        let j = S2Point::new(0.0, 1.0, 0.0);
+        check_interpolate(1.0 / 3.0, i, j, S2Point::new(3f64.sqrt(), 1.0, 0.0));
+        check_interpolate(2.0 / 3.0, i, j, S2Point::new(1.0, 3f64.sqrt(), 0.0));
+
+        // Values of "t" outside [0, 1] extrapolate along the great circle.
+        check_interpolate(-1.0, i, j, S2Point::new(0.0, -1.0, 0.0));
+        check_interpolate(2.0, i, j, S2Point::new(-1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_interpolate_endpoints_are_exact() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..100 {
+            let a = random_point(&mut rng);
+            let b = random_point(&mut rng);
+            assert_eq!(interpolate(0.0, &a, &b), a);
+            assert_eq!(interpolate(1.0, &a, &b), b);
+        }
+    }
+
+    #[test]
+    fn test_midpoint_is_equidistant() {
+        let mut rng = StdRng::seed_from_u64(8);
+        for _ in 0..1000 {
+            let a = random_point(&mut rng);
+            let b = random_point(&mut rng);
+            let mid = interpolate(0.5, &a, &b);
+            let da = S1Angle::from_points(&a, &mid).radians();
+            let db = S1Angle::from_points(&mid, &b).radians();
+            assert!((da - db).abs() <= 1e-14, "{da} != {db}");
+            assert!((da + db - S1Angle::from_points(&a, &b).radians()).abs() <= 1e-14);
+        }
+    }
+
+    #[test]
+    fn test_interpolate_at_distance() {
+        let mut rng = StdRng::seed_from_u64(9);
+        for _ in 0..1000 {
+            let a = random_point(&mut rng);
+            let b = random_point(&mut rng);
+            let r = S1Angle::from_radians(rng.gen_range(0.0..3.0));
+            let x = interpolate_at_distance(r, &a, &b);
+            assert!(is_unit_length(&x));
+            assert!((S1Angle::from_points(&a, &x).radians() - r.radians()).abs() <= 1e-14);
+            // The result lies on the great circle through A and B.
+            assert!(robust_cross_prod(&a, &b).normalize().dot_prod(&x).abs() <= 1e-15);
+        }
+
+        // The distance can exceed the length of the edge.
+        let i = S2Point::new(1.0, 0.0, 0.0);
+        let j = S2Point::new(0.0, 1.0, 0.0);
+        let x = interpolate_at_distance(S1Angle::from_degrees(180.0), &i, &j);
+        assert!((x - S2Point::new(-1.0, 0.0, 0.0)).norm() <= 1e-15);
+    }
+
+    fn check_project(x: S2Point, a: S2Point, b: S2Point, expected: S2Point) {
+        let (x, a, b) = (x.normalize(), a.normalize(), b.normalize());
+        let expected = expected.normalize();
+        let actual = project(&x, &a, &b);
+        assert!(
+            (actual - expected).norm() <= 1e-15,
+            "project({x:?}, {a:?}, {b:?}) = {actual:?}, expected {expected:?}"
+        );
+    }
+
+    #[test]
+    fn test_project() {
+        let a = S2Point::new(1.0, 0.0, 0.0);
+        let b = S2Point::new(0.0, 1.0, 0.0);
+
+        // Points above and below the edge project onto its interior.
+        check_project(
+            S2Point::new(1.0, 1.0, 1.0),
+            a,
+            b,
+            S2Point::new(1.0, 1.0, 0.0),
+        );
+        check_project(
+            S2Point::new(1.0, 3.0, -2.0),
+            a,
+            b,
+            S2Point::new(1.0, 3.0, 0.0),
+        );
+        // Points on the edge project to themselves.
+        check_project(
+            S2Point::new(2.0, 1.0, 0.0),
+            a,
+            b,
+            S2Point::new(2.0, 1.0, 0.0),
+        );
+        // Points beside or beyond the endpoints are clamped to the endpoints.
+        check_project(S2Point::new(1.0, -1.0, 0.0), a, b, a);
+        check_project(S2Point::new(1.0, -1.0, 5.0), a, b, a);
+        check_project(S2Point::new(-1.0, 1.0, 0.0), a, b, b);
+        check_project(S2Point::new(-1.0, 0.1, 0.3), a, b, b);
+        // Degenerate edges project everything onto their single point.
+        check_project(S2Point::new(0.0, 0.0, 1.0), a, a, a);
+    }
+
+    #[test]
+    fn test_project_lies_on_edge() {
+        let mut rng = StdRng::seed_from_u64(10);
+        for _ in 0..1000 {
+            let a = random_point(&mut rng);
+            let b = random_point(&mut rng);
+            let x = random_point(&mut rng);
+            let p = project(&x, &a, &b);
+            assert!(is_unit_length(&p));
+            // The result lies on the great circle through A and B. (The error
+            // grows when X is close to the normal of AB, since the component
+            // of X along the great circle is then small.)
+            let n = robust_cross_prod(&a, &b).normalize();
+            assert!(n.dot_prod(&p).abs() <= 1e-14);
+            // It lies within the edge AB...
+            let ab = S1Angle::from_points(&a, &b).radians();
+            let ap = S1Angle::from_points(&a, &p).radians();
+            let pb = S1Angle::from_points(&p, &b).radians();
+            assert!((ap + pb - ab).abs() <= 1e-14);
+            // ...and is at least as close to X as the endpoints and midpoint.
+            let xp = S1Angle::from_points(&x, &p).radians();
+            for q in [a, b, interpolate(0.5, &a, &b)] {
+                assert!(xp <= S1Angle::from_points(&x, &q).radians() + 1e-15);
+            }
+        }
+    }
+
+    fn check_distance(x: S2Point, a: S2Point, b: S2Point, distance_radians: f64) {
+        let (x, a, b) = (x.normalize(), a.normalize(), b.normalize());
+        let distance = get_distance(&x, &a, &b).radians();
+        assert!(
+            (distance - distance_radians).abs() <= 1e-15,
+            "get_distance({x:?}, {a:?}, {b:?}) = {distance}, expected {distance_radians}"
+        );
+        let mut min_dist = S1ChordAngle::straight();
+        update_min_distance(&x, &a, &b, &mut min_dist);
+        assert!((min_dist.radians() - distance_radians).abs() <= 1e-15);
+        // A second update with the same edge never succeeds.
+        assert!(!update_min_distance(&x, &a, &b, &mut min_dist));
+    }
+
+    #[test]
+    fn test_distance() {
+        use core::f64::consts::{FRAC_PI_2, FRAC_PI_4};
+
+        let a = S2Point::new(1.0, 0.0, 0.0);
+        let b = S2Point::new(0.0, 1.0, 0.0);
+        check_distance(S2Point::new(1.0, 0.0, 0.0), a, b, 0.0);
+        check_distance(S2Point::new(0.0, 1.0, 0.0), a, b, 0.0);
+        check_distance(S2Point::new(1.0, 3.0, 0.0), a, b, 0.0);
+        check_distance(S2Point::new(0.0, 0.0, 1.0), a, b, FRAC_PI_2);
+        check_distance(S2Point::new(0.0, 0.0, -1.0), a, b, FRAC_PI_2);
+        check_distance(
+            S2Point::new(-1.0, -1.0, 0.0),
+            a,
+            b,
+            0.75 * core::f64::consts::PI,
+        );
+        check_distance(S2Point::new(0.0, 1.0, 1.0), a, b, FRAC_PI_4);
+        check_distance(S2Point::new(1.0, 1.0, 0.0), a, a, FRAC_PI_4);
+        check_distance(S2Point::new(-1.0, 0.0, 0.0), a, a, core::f64::consts::PI);
+    }
+
+    #[test]
+    fn test_update_max_distance() {
+        let a = S2Point::new(1.0, 0.0, 0.0);
+        let b = S2Point::new(0.0, 1.0, 0.0);
+        let check = |x: S2Point, expected_degrees: f64| {
+            let mut max_dist = S1ChordAngle::negative();
+            assert!(update_max_distance(&x.normalize(), &a, &b, &mut max_dist));
+            let actual = S1Angle::from(max_dist).degrees();
+            assert!(
+                (actual - expected_degrees).abs() <= 1e-12,
+                "{x:?}: {actual} != {expected_degrees}"
+            );
+        };
+        check(S2Point::new(1.0, 0.0, 1.0), 90.0);
+        check(S2Point::new(1.0, 1.0, 1.0), 54.735610317245346);
+        check(S2Point::new(0.0, 0.0, 1.0), 90.0);
+        // The antipode of X lies on the edge interior.
+        check(S2Point::new(-1.0, -1.0, 0.0), 180.0);
+        check(S2Point::new(-1.0, -1.0, 1.0), 144.73561031724535);
+    }
+
+    #[test]
+    fn test_update_min_distance_max_error() {
+        // The error bound covers the difference between the computed minimum
+        // distance and the distance to a point computed on the edge.
+        let mut rng = StdRng::seed_from_u64(56);
+        for _ in 0..1000 {
+            let a = random_point(&mut rng);
+            let b = random_point(&mut rng);
+            let x = random_point(&mut rng);
+            let mut min_dist = S1ChordAngle::infinity();
+            update_min_distance(&x, &a, &b, &mut min_dist);
+            let error = get_update_min_distance_max_error(min_dist);
+            assert!(error > 0.0 && error < 1e-14);
+            let p = project(&x, &a, &b);
+            let dist = S1ChordAngle::from_points(&x, &p);
+            assert!(min_dist.plus_error(-error) <= dist.plus_error(error));
+        }
+        assert_eq!(
+            get_update_min_interior_distance_max_error(S1ChordAngle::straight()),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_min_and_max_distance_bound_endpoints() {
+        let mut rng = StdRng::seed_from_u64(11);
+        for _ in 0..1000 {
+            let a = random_point(&mut rng);
+            let b = random_point(&mut rng);
+            let x = random_point(&mut rng);
+            let mut min_dist = S1ChordAngle::infinity();
+            assert!(update_min_distance(&x, &a, &b, &mut min_dist));
+            let mut max_dist = S1ChordAngle::negative();
+            assert!(update_max_distance(&x, &a, &b, &mut max_dist));
+            // The minimum is attained at the projection of X onto AB.
+            let p = project(&x, &a, &b);
+            assert!((min_dist.length2() - (x - p).norm2()).abs() <= 1e-14);
+            for q in [a, b, interpolate(0.5, &a, &b)] {
+                let d = S1ChordAngle::from_points(&x, &q).length2();
+                assert!(min_dist.length2() <= d + 1e-15);
+                assert!(max_dist.length2() >= d - 1e-14);
+            }
+        }
+    }
+}
@@ -0,0 +1,127 @@
+// Copyright 2012 Google Inc. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS-IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+// Original Author: ericv@google.com (Eric Veach)
+
+use alloc::{vec, vec::Vec};
+
+use crate::s2::{S2Point, S2Shape};
+
+/// S2EdgeVectorShape is an S2Shape representing an arbitrary set of edges.
+/// It is mainly used for testing, but it can also be useful if you have, say,
+/// a collection of polylines and don't care about memory efficiency (since
+/// this type would store most of the vertices twice).
+///
+/// Note that if you already have data stored in an S2Loop, S2Polyline, or
+/// S2Polygon, then you would be better off using the "Shape" type defined
+/// within those types. Similarly, if the vertex data is stored in your own
+/// data structures, you can easily write your own subtype of S2Shape that
+/// points to the existing vertex data rather than copying it.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{S2EdgeVectorShape, S2Point, S2Shape};
+///
+/// let a = S2Point::new(1.0, 0.0, 0.0);
+/// let b = S2Point::new(0.0, 1.0, 0.0);
+/// let c = S2Point::new(0.0, 0.0, 1.0);
+/// let mut shape = S2EdgeVectorShape::from_edge(&a, &b);
+/// shape.add(&b, &c);
+/// assert_eq!(shape.num_edges(), 2);
+/// assert_eq!(shape.edge(1), (b, c));
+/// assert_eq!(shape.dimension(), 1);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct S2EdgeVectorShape {
+    edges: Vec<(S2Point, S2Point)>,
+}
+
+impl S2EdgeVectorShape {
+    /// Constructs an empty edge vector.
+    pub fn new() -> S2EdgeVectorShape {
+        S2EdgeVectorShape::default()
+    }
+
+    /// Constructs an S2EdgeVectorShape from a vector of edges.
+    pub fn from_edges(edges: Vec<(S2Point, S2Point)>) -> S2EdgeVectorShape {
+        S2EdgeVectorShape { edges }
+    }
+
+    /// Creates an S2EdgeVectorShape containing a single edge.
+    pub fn from_edge(a: &S2Point, b: &S2Point) -> S2EdgeVectorShape {
+        S2EdgeVectorShape::from_edges(vec![(*a, *b)])
+    }
+
+    /// Adds an edge to the vector.
+    pub fn add(&mut self, a: &S2Point, b: &S2Point) {
+        self.edges.push((*a, *b));
+    }
+}
+
+impl S2Shape for S2EdgeVectorShape {
+    fn num_edges(&self) -> i32 {
+        self.edges.len() as i32
+    }
+
+    fn edge(&self, id: i32) -> (S2Point, S2Point) {
+        self.edges[id as usize]
+    }
+
+    fn dimension(&self) -> i32 {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::s2::s2testing::random_point;
+
+    #[test]
+    fn test_empty() {
+        let shape = S2EdgeVectorShape::new();
+        assert_eq!(shape.num_edges(), 0);
+        assert_eq!(shape.dimension(), 1);
+    }
+
+    #[test]
+    fn test_edge_access() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let edges: Vec<(S2Point, S2Point)> = (0..100)
+            .map(|_| (random_point(&mut rng), random_point(&mut rng)))
+            .collect();
+        let mut shape = S2EdgeVectorShape::new();
+        for (a, b) in &edges {
+            shape.add(a, b);
+        }
+        assert_eq!(shape, S2EdgeVectorShape::from_edges(edges.clone()));
+        assert_eq!(shape.num_edges(), edges.len() as i32);
+        for (i, edge) in edges.iter().enumerate() {
+            assert_eq!(shape.edge(i as i32), *edge);
+        }
+    }
+
+    #[test]
+    fn test_dyn_shape() {
+        let a = S2Point::new(1.0, 0.0, 0.0);
+        let b = S2Point::new(0.0, 1.0, 0.0);
+        let shape: &dyn S2Shape = &S2EdgeVectorShape::from_edge(&a, &b);
+        assert_eq!(shape.num_edges(), 1);
+        assert_eq!(shape.edge(0), (a, b));
+    }
+}
@@ -0,0 +1,891 @@
+// Copyright 2005 Google Inc. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS-IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+// Original Author: ericv@google.com (Eric Veach)
+
+use core::{
+    f64::consts::{FRAC_PI_2, FRAC_PI_4, PI},
+    fmt,
+    ops::{Add, Mul, Sub},
+};
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+use crate::{
+    r2::R2Point,
+    s1::S1Angle,
+    s2::{distance::angle_between_points, S2Point},
+    util::math::remainder,
+};
+
+/// This class represents a point on the unit sphere as a pair
+/// of latitude-longitude coordinates. Like the rest of the "geometry"
+/// package, the intent is to represent spherical geometry as a mathematical
+/// abstraction, so functions that are specifically related to the Earth's
+/// geometry (e.g. easting/northing conversions) should be put elsewhere.
+///
+/// This class is intended to be copied by value as desired.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct S2LatLng {
+    coords: R2Point,
+}
+
+impl S2LatLng {
+    /// Constructs an S2LatLng from the given latitude and longitude angles.
+    pub fn new(lat: S1Angle, lng: S1Angle) -> S2LatLng {
+        S2LatLng::from_radians(lat.radians(), lng.radians())
+    }
+
+    /// Constructs an S2LatLng from latitude and longitude in radians.
+    pub fn from_radians(lat_radians: f64, lng_radians: f64) -> S2LatLng {
+        S2LatLng {
+            coords: R2Point::new(lat_radians, lng_radians),
+        }
+    }
+
+    /// Constructs an S2LatLng from latitude and longitude in degrees.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::S2LatLng;
+    ///
+    /// let ll = S2LatLng::from_degrees(45.0, -120.0);
+    /// assert!(ll.is_valid());
+    /// assert!((ll.lat().degrees() - 45.0).abs() < 1e-13);
+    /// assert!((ll.lng().degrees() + 120.0).abs() < 1e-13);
+    /// ```
+    pub fn from_degrees(lat_degrees: f64, lng_degrees: f64) -> S2LatLng {
+        S2LatLng::new(
+            S1Angle::from_degrees(lat_degrees),
+            S1Angle::from_degrees(lng_degrees),
+        )
+    }
+
+    /// Converts a direction vector (not necessarily unit length) to an
+    /// S2LatLng.
+    pub fn from_point(p: &S2Point) -> S2LatLng {
+        let ll = S2LatLng::new(S2LatLng::latitude(p), S2LatLng::longitude(p));
+        debug_assert!(ll.is_valid());
+        ll
+    }
+
+    /// Returns an S2LatLng for which is_valid() will return false.
+    pub fn invalid() -> S2LatLng {
+        // These coordinates are outside the bounds allowed by is_valid().
+        S2LatLng::from_radians(PI, 2.0 * PI)
+    }
+
+    /// Returns the latitude of the given point (not necessarily unit length).
+    pub fn latitude(p: &S2Point) -> S1Angle {
+        // We use atan2 rather than asin because the input vector is not
+        // necessarily unit length, and atan2 is much more accurate than asin
+        // near the poles.
+        S1Angle::from_radians(p.z().atan2((p.x() * p.x() + p.y() * p.y()).sqrt()))
+    }
+
+    /// Returns the longitude of the given point (not necessarily unit length).
+    pub fn longitude(p: &S2Point) -> S1Angle {
+        // Note that atan2(0, 0) is defined to be zero.
+        S1Angle::from_radians(p.y().atan2(p.x()))
+    }
+
+    /// Returns the latitude of this point as an S1Angle.
+    pub fn lat(&self) -> S1Angle {
+        S1Angle::from_radians(self.coords[0])
+    }
+
+    /// Returns the longitude of this point as an S1Angle.
+    pub fn lng(&self) -> S1Angle {
+        S1Angle::from_radians(self.coords[1])
+    }
+
+    /// Returns the latitude and longitude in radians as an R2Point.
+    pub fn coords(&self) -> &R2Point {
+        &self.coords
+    }
+
+    /// Return true if the latitude is between -90 and 90 degrees inclusive
+    /// and the longitude is between -180 and 180 degrees inclusive.
+    pub fn is_valid(&self) -> bool {
+        self.lat().radians().abs() <= FRAC_PI_2 && self.lng().radians().abs() <= PI
+    }
+
+    /// Clamps the latitude to the range [-90, 90] degrees, and adds or
+    /// subtracts a multiple of 360 degrees to the longitude if necessary to
+    /// reduce it to the range [-180, 180].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::S2LatLng;
+    ///
+    /// let ll = S2LatLng::from_degrees(100.0, 270.0).normalized();
+    /// assert!(ll.is_valid());
+    /// assert_eq!(ll.lat().degrees(), 90.0);
+    /// assert_eq!(ll.lng().degrees(), -90.0);
+    /// ```
+    pub fn normalized(&self) -> S2LatLng {
+        // remainder(x, 2 * PI) reduces its argument to the range
+        // [-PI, PI] inclusive, which is what we want here.
+        S2LatLng::from_radians(
+            self.lat().radians().clamp(-FRAC_PI_2, FRAC_PI_2),
+            remainder(self.lng().radians(), 2.0 * PI),
+        )
+    }
+
+    /// Converts a normalized S2LatLng to the equivalent unit-length vector.
+    /// The maximum error in the result is 1.5 * DBL_EPSILON. (This does not
+    /// include the error of converting degrees, E5, E6, or E7 to radians.)
+    pub fn to_point(&self) -> S2Point {
+        debug_assert!(self.is_valid());
+        let (sin_phi, cos_phi) = self.lat().sin_cos();
+        let (sin_theta, cos_theta) = self.lng().sin_cos();
+        S2Point::new(cos_theta * cos_phi, sin_theta * cos_phi, sin_phi)
+    }
+
+    /// Returns the distance (measured along the surface of the sphere) to the
+    /// given S2LatLng. This converts both arguments to points and measures
+    /// the angle between them with distance::angle_between_points(), which
+    /// has an absolute error of about 1e-15 radians (a few nanometers on the
+    /// Earth) at all distances. (The Haversine formula used previously is
+    /// slightly faster and has better relative accuracy for tiny distances,
+    /// but only gets about 8 digits of precision for nearly antipodal
+    /// points.) Both S2LatLngs must be normalized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use core::f64::consts::PI;
+    /// use s2shell::s2::S2LatLng;
+    ///
+    /// // Nearly antipodal points on the equator.
+    /// let a = S2LatLng::from_radians(0.0, 0.0);
+    /// let b = S2LatLng::from_radians(0.0, PI - 1e-7);
+    /// assert!((a.get_distance(&b).radians() - (PI - 1e-7)).abs() < 1e-15);
+    /// ```
+    pub fn get_distance(&self, o: &S2LatLng) -> S1Angle {
+        debug_assert!(self.is_valid());
+        debug_assert!(o.is_valid());
+        angle_between_points(&self.to_point(), &o.to_point())
+    }
+
+    /// Returns the initial bearing of the great circle path from this point to
+    /// "o", measured clockwise from north in the range (-180, 180] degrees.
+    /// The bearing generally changes along the path; see rhumb_bearing_to()
+    /// for paths of constant bearing.
+    ///
+    /// At the poles "north" is taken to be the direction of the meridian
+    /// through this point's longitude, so that destination() always inverts
+    /// this method. If the two points are equal or exactly antipodal every
+    /// direction is equally valid and zero is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::S2LatLng;
+    ///
+    /// let a = S2LatLng::from_degrees(0.0, 0.0);
+    /// let b = S2LatLng::from_degrees(0.0, 10.0);
+    /// assert!((a.bearing_to(&b).degrees() - 90.0).abs() < 1e-13);
+    /// ```
+    pub fn bearing_to(&self, o: &S2LatLng) -> S1Angle {
+        let p = self.to_point();
+        let (north, east) = self.north_east();
+        // The tangent at this point in the direction of "o". As in
+        // robust_cross_prod(), (b + p) x (b - p) is used rather than p x b
+        // since it stays accurate when the two points are nearly equal or
+        // nearly antipodal. It is exactly zero for equal or antipodal points.
+        let b = o.to_point();
+        let tangent = (b + p).cross_prod(&(b - p)).cross_prod(&p);
+        let (y, x) = (tangent.dot_prod(&east), tangent.dot_prod(&north));
+        if y == 0.0 && x == 0.0 {
+            return S1Angle::zero();
+        }
+        S1Angle::from_radians(y.atan2(x))
+    }
+
+    /// Returns the point reached by starting at this point and travelling the
+    /// given distance along the great circle with the given initial bearing
+    /// (measured clockwise from north). Distances may exceed 180 degrees, in
+    /// which case the path continues past the antipodal point. The result is
+    /// normalized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::{s1::S1Angle, s2::S2LatLng};
+    ///
+    /// // Travelling due north from the equator for 90 degrees reaches the pole.
+    /// let ll =
+    ///     S2LatLng::from_degrees(0.0, 30.0).destination(S1Angle::zero(), S1Angle::from_degrees(90.0));
+    /// assert!((ll.lat().degrees() - 90.0).abs() < 1e-13);
+    /// ```
+    pub fn destination(&self, bearing: S1Angle, distance: S1Angle) -> S2LatLng {
+        let p = self.to_point();
+        let (north, east) = self.north_east();
+        let (theta, delta) = (bearing.radians(), distance.radians());
+        let dir = north * theta.cos() + east * theta.sin();
+        S2LatLng::from_point(&(p * delta.cos() + dir * delta.sin()))
+    }
+
+    /// Returns the bearing of the rhumb line (loxodrome) from this point to
+    /// "o", i.e. the path that crosses every meridian at the same angle. This
+    /// is the path followed when steering a constant compass course, and it is
+    /// a straight line on a Mercator chart. The result is measured clockwise
+    /// from north in the range (-180, 180] degrees, and the rhumb line always
+    /// takes the shorter way around in longitude.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::S2LatLng;
+    ///
+    /// // A rhumb line along a parallel heads due east or west.
+    /// let a = S2LatLng::from_degrees(40.0, 170.0);
+    /// let b = S2LatLng::from_degrees(40.0, -170.0);
+    /// assert!((a.rhumb_bearing_to(&b).degrees() - 90.0).abs() < 1e-13);
+    /// ```
+    pub fn rhumb_bearing_to(&self, o: &S2LatLng) -> S1Angle {
+        let dpsi = mercator_y(o.lat().radians()) - mercator_y(self.lat().radians());
+        let dlng = remainder(o.lng().radians() - self.lng().radians(), 2.0 * PI);
+        S1Angle::from_radians(dlng.atan2(dpsi))
+    }
+
+    /// Returns the point reached by travelling the given distance from this
+    /// point along the rhumb line with the given constant bearing.
+    ///
+    /// Every rhumb line except the east-west ones spirals into a pole after a
+    /// finite distance. If the given distance would take the path beyond the
+    /// pole, the pole itself is returned (with this point's longitude, since
+    /// the path winds around the pole infinitely often).
+    pub fn rhumb_destination(&self, bearing: S1Angle, distance: S1Angle) -> S2LatLng {
+        let (theta, delta) = (bearing.radians(), distance.radians());
+        let lat1 = self.lat().radians();
+        let lat2 = lat1 + delta * theta.cos();
+        if lat2.abs() >= FRAC_PI_2 {
+            return S2LatLng::from_radians(FRAC_PI_2.copysign(lat2), self.lng().radians());
+        }
+        // "q" is the ratio between the change in latitude and the change in
+        // Mercator y-coordinate along the path. For east-west paths the
+        // latitude is constant and the usual formula divides zero by zero, so
+        // we use the limiting value cos(lat) instead.
+        let dpsi = mercator_y(lat2) - mercator_y(lat1);
+        let q = if dpsi.abs() > 1e-12 {
+            (lat2 - lat1) / dpsi
+        } else {
+            lat1.cos()
+        };
+        let dlng = delta * theta.sin() / q;
+        S2LatLng::from_radians(lat2, self.lng().radians() + dlng).normalized()
+    }
+
+    /// Returns unit vectors pointing north and east in the tangent plane at
+    /// this point. At the poles these are oriented relative to the meridian
+    /// through this point's longitude.
+    fn north_east(&self) -> (S2Point, S2Point) {
+        let (lat, lng) = (self.lat().radians(), self.lng().radians());
+        let north = S2Point::new(-lat.sin() * lng.cos(), -lat.sin() * lng.sin(), lat.cos());
+        let east = S2Point::new(-lng.sin(), lng.cos(), 0.0);
+        (north, east)
+    }
+}
+
+/// Returns the Mercator projection y-coordinate of the given latitude.
+fn mercator_y(lat: f64) -> f64 {
+    (FRAC_PI_4 + 0.5 * lat).tan().ln()
+}
+
+/// Returns the latitude and longitude of the given direction vector, as in
+/// [`S2LatLng::from_point`]. The vector need not be unit length, but it must
+/// be non-zero and have finite components, since otherwise it does not have
+/// a direction.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{InvalidPointError, S2LatLng, S2Point};
+///
+/// let ll = S2LatLng::try_from(S2Point::new(0.0, 2.0, 0.0)).unwrap();
+/// assert_eq!(ll, S2LatLng::from_degrees(0.0, 90.0));
+/// assert_eq!(S2LatLng::try_from(S2Point::zero()), Err(InvalidPointError));
+/// assert!(S2LatLng::try_from(S2Point::new(f64::NAN, 0.0, 1.0)).is_err());
+/// ```
+impl TryFrom<S2Point> for S2LatLng {
+    type Error = InvalidPointError;
+
+    fn try_from(p: S2Point) -> Result<S2LatLng, InvalidPointError> {
+        let finite = p.x().is_finite() && p.y().is_finite() && p.z().is_finite();
+        if !finite || p == S2Point::zero() {
+            return Err(InvalidPointError);
+        }
+        Ok(S2LatLng::from_point(&p))
+    }
+}
+
+/// Returns the unit-length point with the given latitude and longitude, as
+/// in [`S2LatLng::to_point`].
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{S2LatLng, S2Point};
+///
+/// let p = S2Point::from(S2LatLng::from_degrees(0.0, 0.0));
+/// assert_eq!(p, S2Point::new(1.0, 0.0, 0.0));
+/// ```
+impl From<S2LatLng> for S2Point {
+    fn from(ll: S2LatLng) -> S2Point {
+        ll.to_point()
+    }
+}
+
+/// The error returned when converting an [`S2Point`] that does not represent
+/// a direction (a zero vector, or one with a NaN or infinite component) to an
+/// [`S2LatLng`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidPointError;
+
+impl fmt::Display for InvalidPointError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("point must be a non-zero vector with finite components")
+    }
+}
+
+impl core::error::Error for InvalidPointError {}
+
+/// Formats the point as "lat,lng" in degrees with six decimal places (about
+/// 10cm of precision on the Earth's surface).
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::S2LatLng;
+///
+/// assert_eq!(
+///     S2LatLng::from_degrees(-45.0, 170.25).to_string(),
+///     "-45.000000,170.250000"
+/// );
+/// ```
+impl fmt::Display for S2LatLng {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.6},{:.6}", self.lat().degrees(), self.lng().degrees())
+    }
+}
+
+// Simple arithmetic operations for manipulating latitude-longitude pairs.
+// The results are not normalized.
+
+impl Add for S2LatLng {
+    type Output = S2LatLng;
+
+    fn add(self, rhs: S2LatLng) -> S2LatLng {
+        S2LatLng {
+            coords: self.coords + rhs.coords,
+        }
+    }
+}
+
+impl Sub for S2LatLng {
+    type Output = S2LatLng;
+
+    fn sub(self, rhs: S2LatLng) -> S2LatLng {
+        S2LatLng {
+            coords: self.coords - rhs.coords,
+        }
+    }
+}
+
+impl Mul<f64> for S2LatLng {
+    type Output = S2LatLng;
+
+    fn mul(self, m: f64) -> S2LatLng {
+        S2LatLng {
+            coords: self.coords * m,
+        }
+    }
+}
+
+/// With the `serde` feature, an S2LatLng serializes as a two-element array
+/// `[lat, lng]` in degrees. Note that this is latitude-first, unlike GeoJSON
+/// positions, which are `[lng, lat]`.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "serde")] {
+/// use s2shell::s2::S2LatLng;
+///
+/// let ll = S2LatLng::from_degrees(37.5, -122.25);
+/// assert_eq!(serde_json::to_string(&ll).unwrap(), "[37.5,-122.25]");
+/// # }
+/// ```
+#[cfg(feature = "serde")]
+impl serde::Serialize for S2LatLng {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeTuple;
+
+        let mut tuple = serializer.serialize_tuple(2)?;
+        tuple.serialize_element(&self.lat().degrees())?;
+        tuple.serialize_element(&self.lng().degrees())?;
+        tuple.end()
+    }
+}
+
+/// With the `serde` feature, an S2LatLng deserializes from either a
+/// two-element array `[lat, lng]` or an object `{"lat": lat, "lng": lng}`,
+/// both in degrees. Since the two forms are distinguished by the input
+/// itself, this requires a self-describing format such as JSON.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "serde")] {
+/// use s2shell::s2::S2LatLng;
+///
+/// let a: S2LatLng = serde_json::from_str("[37.5, -122.25]").unwrap();
+/// let b: S2LatLng = serde_json::from_str(r#"{"lng": -122.25, "lat": 37.5}"#).unwrap();
+/// assert_eq!(a, b);
+/// assert_eq!(a, S2LatLng::from_degrees(37.5, -122.25));
+/// # }
+/// ```
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for S2LatLng {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<S2LatLng, D::Error> {
+        use core::fmt;
+
+        use serde::de::{self, MapAccess, SeqAccess, Visitor};
+
+        enum Field {
+            Lat,
+            Lng,
+        }
+
+        impl<'de> serde::Deserialize<'de> for Field {
+            fn deserialize<D: serde::Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<Field, D::Error> {
+                struct FieldVisitor;
+
+                impl Visitor<'_> for FieldVisitor {
+                    type Value = Field;
+
+                    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                        f.write_str("`lat` or `lng`")
+                    }
+
+                    fn visit_str<E: de::Error>(self, value: &str) -> Result<Field, E> {
+                        match value {
+                            "lat" => Ok(Field::Lat),
+                            "lng" => Ok(Field::Lng),
+                            _ => Err(de::Error::unknown_field(value, &["lat", "lng"])),
+                        }
+                    }
+                }
+
+                deserializer.deserialize_identifier(FieldVisitor)
+            }
+        }
+
+        struct S2LatLngVisitor;
+
+        impl<'de> Visitor<'de> for S2LatLngVisitor {
+            type Value = S2LatLng;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a [lat, lng] array or a {lat, lng} object in degrees")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<S2LatLng, A::Error> {
+                let lat: f64 = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let lng: f64 = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                if seq.next_element::<de::IgnoredAny>()?.is_some() {
+                    return Err(de::Error::invalid_length(3, &self));
+                }
+                Ok(S2LatLng::from_degrees(lat, lng))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<S2LatLng, A::Error> {
+                let mut lat = None;
+                let mut lng = None;
+                while let Some(key) = map.next_key()? {
+                    match key {
+                        Field::Lat if lat.is_some() => {
+                            return Err(de::Error::duplicate_field("lat"))
+                        }
+                        Field::Lng if lng.is_some() => {
+                            return Err(de::Error::duplicate_field("lng"))
+                        }
+                        Field::Lat => lat = Some(map.next_value::<f64>()?),
+                        Field::Lng => lng = Some(map.next_value::<f64>()?),
+                    }
+                }
+                let lat = lat.ok_or_else(|| de::Error::missing_field("lat"))?;
+                let lng = lng.ok_or_else(|| de::Error::missing_field("lng"))?;
+                Ok(S2LatLng::from_degrees(lat, lng))
+            }
+        }
+
+        deserializer.deserialize_any(S2LatLngVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+
+    /// The mean radius of the Earth used by navigation references.
+    const EARTH_RADIUS_KM: f64 = 6371.0;
+
+    fn random_latlng(rng: &mut StdRng) -> S2LatLng {
+        S2LatLng::from_point(
+            &S2Point::new(
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+            )
+            .normalize(),
+        )
+    }
+
+    fn angle_between(a: &S2LatLng, b: &S2LatLng) -> f64 {
+        S1Angle::from_points(&a.to_point(), &b.to_point()).radians()
+    }
+
+    #[test]
+    fn test_basic() {
+        let ll = S2LatLng::from_radians(FRAC_PI_2 / 2.0, PI / 2.0);
+        assert_eq!(ll.lat().radians(), FRAC_PI_2 / 2.0);
+        assert_eq!(ll.lng().radians(), PI / 2.0);
+        assert!(ll.is_valid());
+        assert!(!S2LatLng::invalid().is_valid());
+        assert!(!S2LatLng::from_degrees(-91.0, 0.0).is_valid());
+        assert!(!S2LatLng::from_degrees(0.0, 181.0).is_valid());
+
+        let bad = S2LatLng::from_degrees(120.0, 200.0).normalized();
+        assert!(bad.is_valid());
+        assert_relative_eq!(bad.lat().degrees(), 90.0);
+        assert_relative_eq!(bad.lng().degrees(), -160.0);
+
+        let bad = S2LatLng::from_degrees(-100.0, -360.0).normalized();
+        assert_relative_eq!(bad.lat().degrees(), -90.0);
+        assert_relative_eq!(bad.lng().degrees(), 0.0);
+
+        let sum = S2LatLng::from_degrees(10.0, 20.0) + S2LatLng::from_degrees(20.0, 30.0);
+        assert_relative_eq!(sum.coords(), S2LatLng::from_degrees(30.0, 50.0).coords());
+        let diff = S2LatLng::from_degrees(10.0, 20.0) - S2LatLng::from_degrees(20.0, 30.0);
+        assert_relative_eq!(diff.coords(), S2LatLng::from_degrees(-10.0, -10.0).coords());
+        let scaled = S2LatLng::from_degrees(10.0, 20.0) * 0.5;
+        assert_relative_eq!(scaled.coords(), S2LatLng::from_degrees(5.0, 10.0).coords());
+    }
+
+    #[test]
+    fn test_conversion() {
+        // Test special cases.
+        assert_relative_eq!(
+            S2LatLng::from_point(&S2LatLng::from_degrees(90.0, 65.0).to_point())
+                .lat()
+                .radians(),
+            FRAC_PI_2
+        );
+        assert_eq!(
+            S2LatLng::from_point(&S2LatLng::from_radians(-FRAC_PI_2, 1.0).to_point())
+                .lat()
+                .radians(),
+            -FRAC_PI_2
+        );
+        assert_relative_eq!(
+            S2LatLng::from_point(&S2LatLng::from_degrees(12.2, 180.0).to_point())
+                .lng()
+                .radians()
+                .abs(),
+            PI
+        );
+        assert_relative_eq!(
+            S2LatLng::from_point(&S2LatLng::from_radians(0.1, -PI).to_point())
+                .lng()
+                .radians()
+                .abs(),
+            PI
+        );
+
+        // Test a bunch of random points.
+        for lat in (-90_i32..=90).step_by(15) {
+            for lng in (-180..180).step_by(20) {
+                let ll = S2LatLng::from_degrees(lat as f64, lng as f64);
+                let p: S2Point = ll.into();
+                assert_relative_eq!(p.norm(), 1.0, epsilon = 1e-15);
+                let back = S2LatLng::try_from(p).unwrap();
+                assert!((back.lat().radians() - ll.lat().radians()).abs() < 1e-15);
+                if lat.abs() != 90 {
+                    assert!((back.lng().radians() - ll.lng().radians()).abs() < 1e-14);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(
+            S2LatLng::from_degrees(90.0, 0.0)
+                .get_distance(&S2LatLng::from_degrees(90.0, 0.0))
+                .radians(),
+            0.0
+        );
+        assert!(
+            (S2LatLng::from_degrees(-37.0, 25.0)
+                .get_distance(&S2LatLng::from_degrees(-66.0, -155.0))
+                .degrees()
+                - 77.0)
+                .abs()
+                < 1e-13
+        );
+        assert!(
+            (S2LatLng::from_degrees(0.0, 165.0)
+                .get_distance(&S2LatLng::from_degrees(0.0, -80.0))
+                .degrees()
+                - 115.0)
+                .abs()
+                < 1e-13
+        );
+        assert!(
+            (S2LatLng::from_degrees(47.0, -127.0)
+                .get_distance(&S2LatLng::from_degrees(-47.0, 53.0))
+                .degrees()
+                - 180.0)
+                .abs()
+                < 2e-6
+        );
+    }
+
+    #[test]
+    fn test_bearing_and_destination_round_trip() {
+        let mut rng = StdRng::seed_from_u64(12);
+        for _ in 0..5000 {
+            let a = random_latlng(&mut rng);
+            let b = random_latlng(&mut rng);
+            let distance = S1Angle::from_radians(angle_between(&a, &b));
+            let c = a.destination(a.bearing_to(&b), distance);
+            assert!(angle_between(&b, &c) <= 1e-9, "{a:?} {b:?} {c:?}");
+        }
+    }
+
+    #[test]
+    fn test_bearing_to() {
+        // From Land's End to John o' Groats.
+        let a = S2LatLng::from_degrees(
+            50.0 + 3.0 / 60.0 + 59.0 / 3600.0,
+            -5.0 - 42.0 / 60.0 - 53.0 / 3600.0,
+        );
+        let b = S2LatLng::from_degrees(
+            58.0 + 38.0 / 60.0 + 38.0 / 3600.0,
+            -3.0 - 4.0 / 60.0 - 12.0 / 3600.0,
+        );
+        assert!((a.bearing_to(&b).degrees() - (9.0 + 7.0 / 60.0 + 11.0 / 3600.0)).abs() < 1e-3);
+        assert!((a.get_distance(&b).radians() * EARTH_RADIUS_KM - 968.9).abs() < 0.1);
+
+        let origin = S2LatLng::from_degrees(0.0, 0.0);
+        assert_relative_eq!(
+            origin
+                .bearing_to(&S2LatLng::from_degrees(10.0, 0.0))
+                .degrees(),
+            0.0
+        );
+        assert_relative_eq!(
+            origin
+                .bearing_to(&S2LatLng::from_degrees(0.0, -10.0))
+                .degrees(),
+            -90.0
+        );
+        assert_relative_eq!(
+            origin
+                .bearing_to(&S2LatLng::from_degrees(-10.0, 0.0))
+                .degrees(),
+            180.0
+        );
+        // Equal points have no well-defined bearing.
+        assert_eq!(origin.bearing_to(&origin).radians(), 0.0);
+    }
+
+    #[test]
+    fn test_destination_over_pole_and_past_antipode() {
+        // Heading north from 80N continues over the pole and down the other
+        // side of the globe.
+        let a = S2LatLng::from_degrees(80.0, 10.0);
+        let b = a.destination(S1Angle::zero(), S1Angle::from_degrees(30.0));
+        assert!(angle_between(&b, &S2LatLng::from_degrees(70.0, -170.0)) < 1e-14);
+
+        // Starting at a pole, the bearing is relative to the point's meridian.
+        let pole = S2LatLng::from_degrees(90.0, 45.0);
+        let b = pole.destination(S1Angle::from_degrees(180.0), S1Angle::from_degrees(90.0));
+        assert!(angle_between(&b, &S2LatLng::from_degrees(0.0, 45.0)) < 1e-14);
+        let c = S2LatLng::from_degrees(30.0, -100.0);
+        let d = pole.destination(pole.bearing_to(&c), S1Angle::from_degrees(60.0));
+        assert!(angle_between(&c, &d) < 1e-14);
+
+        // Distances of half and a full circumference.
+        let a = S2LatLng::from_degrees(20.0, 30.0);
+        let bearing = S1Angle::from_degrees(37.0);
+        let b = a.destination(bearing, S1Angle::from_degrees(180.0));
+        assert!(angle_between(&b, &S2LatLng::from_degrees(-20.0, -150.0)) < 1e-14);
+        let c = a.destination(bearing, S1Angle::from_degrees(360.0));
+        assert!(angle_between(&a, &c) < 1e-14);
+        let d = a.destination(bearing, S1Angle::from_degrees(270.0));
+        let e = a.destination(
+            S1Angle::from_degrees(37.0 + 180.0),
+            S1Angle::from_degrees(90.0),
+        );
+        assert!(angle_between(&d, &e) < 1e-14);
+    }
+
+    #[test]
+    fn test_rhumb_lines() {
+        // Example from the Movable Type navigation reference: from Dover to
+        // Calais, a rhumb line bearing of 116.7 degrees over 40.31 km.
+        let dover = S2LatLng::from_degrees(51.127, 1.338);
+        let calais = S2LatLng::from_degrees(50.964, 1.853);
+        assert!((dover.rhumb_bearing_to(&calais).degrees() - 116.7).abs() < 0.05);
+        let dest = dover.rhumb_destination(
+            S1Angle::from_degrees(116.7),
+            S1Angle::from_radians(40.31 / EARTH_RADIUS_KM),
+        );
+        assert!((dest.lat().degrees() - 50.9642).abs() < 1e-3);
+        assert!((dest.lng().degrees() - 1.8530).abs() < 1e-3);
+
+        // East-west rhumb lines follow a parallel of latitude.
+        let a = S2LatLng::from_degrees(60.0, 170.0);
+        let b = a.rhumb_destination(S1Angle::from_degrees(90.0), S1Angle::from_degrees(10.0));
+        assert_relative_eq!(b.lat().degrees(), 60.0, epsilon = 1e-12);
+        assert_relative_eq!(b.lng().degrees(), -170.0, epsilon = 1e-12);
+        let c = a.rhumb_destination(S1Angle::from_degrees(-90.0), S1Angle::from_degrees(10.0));
+        assert_relative_eq!(c.lng().degrees(), 150.0, epsilon = 1e-12);
+
+        // A rhumb line that reaches the pole stops there.
+        let p = a.rhumb_destination(S1Angle::from_degrees(10.0), S1Angle::from_degrees(90.0));
+        assert_eq!(p.lat().degrees(), 90.0);
+
+        // Rhumb bearings invert rhumb destinations.
+        let mut rng = StdRng::seed_from_u64(13);
+        for _ in 0..2000 {
+            let a =
+                S2LatLng::from_degrees(rng.gen_range(-80.0..80.0), rng.gen_range(-180.0..180.0));
+            let b =
+                S2LatLng::from_degrees(rng.gen_range(-80.0..80.0), rng.gen_range(-180.0..180.0));
+            let bearing = a.rhumb_bearing_to(&b);
+            let lat_span = b.lat().radians() - a.lat().radians();
+            let distance = if bearing.cos().abs() > 1e-6 {
+                lat_span / bearing.cos()
+            } else {
+                let dlng = remainder(b.lng().radians() - a.lng().radians(), 2.0 * PI);
+                (dlng * a.lat().cos()).abs()
+            };
+            let c = a.rhumb_destination(bearing, S1Angle::from_radians(distance));
+            assert!(angle_between(&b, &c) <= 1e-9, "{a:?} {b:?} {c:?}");
+        }
+    }
+
+    #[test]
+    fn test_point_conversions() {
+        let mut rng = StdRng::seed_from_u64(64);
+        for _ in 0..1000 {
+            let p = S2Point::new(
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+            )
+            .normalize();
+            let ll = S2LatLng::try_from(p).unwrap();
+            assert_eq!(ll, S2LatLng::from_point(&p));
+            assert!(S2Point::from(ll).angle(&p) <= 1e-15);
+
+            // The length of the vector does not matter (scaling by a power of
+            // two is exact).
+            assert_eq!(S2LatLng::try_from(p * 1024.0), Ok(ll));
+        }
+        let invalid = [
+            S2Point::zero(),
+            S2Point::new(f64::NAN, 1.0, 0.0),
+            S2Point::new(0.0, f64::INFINITY, 0.0),
+            S2Point::new(0.0, 0.0, f64::NEG_INFINITY),
+        ];
+        for p in invalid {
+            assert_eq!(S2LatLng::try_from(p), Err(InvalidPointError), "{p:?}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde_round_trip() {
+        let mut rng = StdRng::seed_from_u64(15);
+        for _ in 0..100 {
+            let ll =
+                S2LatLng::from_degrees(rng.gen_range(-90.0..90.0), rng.gen_range(-180.0..180.0));
+            let json = serde_json::to_string(&ll).unwrap();
+            let decoded: S2LatLng = serde_json::from_str(&json).unwrap();
+            assert_relative_eq!(
+                decoded.lat().radians(),
+                ll.lat().radians(),
+                max_relative = 1e-15
+            );
+            assert_relative_eq!(
+                decoded.lng().radians(),
+                ll.lng().radians(),
+                max_relative = 1e-15
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde_formats() {
+        let ll = S2LatLng::from_degrees(-45.0, 90.0);
+        assert_eq!(serde_json::to_string(&ll).unwrap(), "[-45.0,90.0]");
+        assert_eq!(
+            serde_json::to_value(ll).unwrap(),
+            serde_json::json!([-45.0, 90.0])
+        );
+
+        for json in [
+            "[-45, 90]",
+            r#"{"lat": -45, "lng": 90.0}"#,
+            r#"{"lng": 90, "lat": -45}"#,
+        ] {
+            assert_eq!(
+                serde_json::from_str::<S2LatLng>(json).unwrap(),
+                ll,
+                "{json}"
+            );
+        }
+
+        for json in [
+            "[-45]",
+            "[-45, 90, 0]",
+            r#"{"lat": -45}"#,
+            r#"{"lat": -45, "lng": 90, "alt": 0}"#,
+            r#"{"lat": -45, "lat": -45, "lng": 90}"#,
+            r#""-45,90""#,
+        ] {
+            assert!(serde_json::from_str::<S2LatLng>(json).is_err(), "{json}");
+        }
+    }
+}
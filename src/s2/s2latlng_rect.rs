@@ -15,6 +15,28 @@
 
 // Original Author: ericv@google.com (Eric Veach)
 
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::{
+    f64::consts::{FRAC_PI_2, PI},
+    fmt,
+};
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+#[cfg(feature = "alloc")]
+use crate::s2::S2CellId;
+use crate::{
+    r1::R1Interval,
+    s1::{S1Angle, S1Interval},
+    s2::{
+        robust_cross_prod, s2edge_distances::get_distance, s2predicates::sign, S2Cap, S2Cell,
+        S2LatLng, S2Point, S2Region,
+    },
+    util::math::remainder,
+};
+
 /// An S2LatLngRect represents a closed latitude-longitude rectangle. It is
 /// capable of representing the empty and full rectangles as well as single
 /// points. Note that the latitude-longitude space is considered to have a
@@ -33,5 +55,1530 @@
 /// flip can be surprising, especially when working with map projections where
 /// -180 and +180 are at opposite ends of the flattened map. See the comments
 /// on S1Interval for more details.
-#[derive(Debug)]
-pub struct S2LatLngRect {}
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct S2LatLngRect {
+    lat: R1Interval,
+    lng: S1Interval,
+}
+
+impl S2LatLngRect {
+    /// Construct a rectangle from minimum and maximum latitudes and
+    /// longitudes. If lo.lng() > hi.lng(), the rectangle spans the 180 degree
+    /// longitude line. Both points must be normalized, with lo.lat() <=
+    /// hi.lat(). The rectangle contains all the points p such that 'lo' <= p
+    /// <= 'hi', where '<=' is defined in the obvious way.
+    pub fn new(lo: &S2LatLng, hi: &S2LatLng) -> S2LatLngRect {
+        let rect = S2LatLngRect {
+            lat: R1Interval::new(lo.lat().radians(), hi.lat().radians()),
+            lng: S1Interval::new(lo.lng().radians(), hi.lng().radians()),
+        };
+        debug_assert!(rect.is_valid(), "{:?}, {:?}", lo, hi);
+        rect
+    }
+
+    /// Construct a rectangle from latitude and longitude intervals. The two
+    /// intervals must either be both empty or both non-empty, and the
+    /// latitude interval must not extend outside [-90, +90] degrees.
+    /// Note that both intervals (and hence the rectangle) are closed.
+    pub fn from_lat_lng(lat: R1Interval, lng: S1Interval) -> S2LatLngRect {
+        let rect = S2LatLngRect { lat, lng };
+        debug_assert!(rect.is_valid(), "{:?}, {:?}", lat, lng);
+        rect
+    }
+
+    /// The canonical empty rectangle. (Use is_empty() to test for empty
+    /// rectangles, since they have more than one representation.)
+    pub fn empty() -> S2LatLngRect {
+        S2LatLngRect {
+            lat: R1Interval::empty(),
+            lng: S1Interval::empty(),
+        }
+    }
+
+    /// The full rectangle.
+    pub fn full() -> S2LatLngRect {
+        S2LatLngRect {
+            lat: S2LatLngRect::full_lat(),
+            lng: S2LatLngRect::full_lng(),
+        }
+    }
+
+    /// The full allowable range of latitudes.
+    pub fn full_lat() -> R1Interval {
+        R1Interval::new(-FRAC_PI_2, FRAC_PI_2)
+    }
+
+    /// The full allowable range of longitudes.
+    pub fn full_lng() -> S1Interval {
+        S1Interval::full()
+    }
+
+    /// Construct a rectangle of the given size centered around the given
+    /// point. "center" needs to be normalized, but "size" does not. The
+    /// latitude interval of the result is clamped to [-90,90] degrees, and the
+    /// longitude interval of the result is full() if and only if the
+    /// longitude size is 360 degrees or more. Otherwise the longitude interval
+    /// wraps around the 180 degree meridian as necessary.
+    ///
+    /// Note that "size" is the full height and width of the rectangle; see
+    /// from_center_half_size() to build a rectangle from half-sizes instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{S2LatLng, S2LatLngRect};
+    ///
+    /// let rect = S2LatLngRect::from_center_size(
+    ///     &S2LatLng::from_degrees(80.0, 170.0),
+    ///     &S2LatLng::from_degrees(40.0, 60.0),
+    /// );
+    /// assert_eq!(rect.lat_hi().degrees(), 90.0);
+    /// assert!((rect.lat_lo().degrees() - 60.0).abs() < 1e-13);
+    /// assert!(rect.is_inverted());
+    /// assert!((rect.lng_hi().degrees() + 160.0).abs() < 1e-13);
+    /// ```
+    pub fn from_center_size(center: &S2LatLng, size: &S2LatLng) -> S2LatLngRect {
+        S2LatLngRect::from_center_half_size(center, &(*size * 0.5))
+    }
+
+    /// Construct a rectangle that extends "half_size.lat()" north and south
+    /// of the given point and "half_size.lng()" east and west of it, e.g. a
+    /// search margin around a point. "center" needs to be normalized, but
+    /// "half_size" does not. Latitudes are clamped and longitudes wrap as
+    /// described for from_center_size(); the longitude interval is full() if
+    /// and only if the longitude half-size is 180 degrees or more.
+    ///
+    /// REQUIRES: half_size.lat() >= 0 and half_size.lng() >= 0
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{S2LatLng, S2LatLngRect};
+    ///
+    /// let rect = S2LatLngRect::from_center_half_size(
+    ///     &S2LatLng::from_degrees(0.0, 175.0),
+    ///     &S2LatLng::from_degrees(5.0, 10.0),
+    /// );
+    /// assert!((rect.lat_lo().degrees() + 5.0).abs() < 1e-13);
+    /// assert!((rect.lat_hi().degrees() - 5.0).abs() < 1e-13);
+    /// assert!(rect.is_inverted());
+    /// assert!(rect.contains_latlng(&S2LatLng::from_degrees(0.0, -176.0)));
+    /// ```
+    pub fn from_center_half_size(center: &S2LatLng, half_size: &S2LatLng) -> S2LatLngRect {
+        S2LatLngRect::from_point(center).expanded(half_size)
+    }
+
+    /// Construct a rectangle containing a single (normalized) point.
+    pub fn from_point(p: &S2LatLng) -> S2LatLngRect {
+        debug_assert!(p.is_valid(), "Invalid S2LatLng: {:?}", p);
+        S2LatLngRect::new(p, p)
+    }
+
+    /// Construct the minimal bounding rectangle containing the two given
+    /// normalized points. This is equivalent to starting with an empty
+    /// rectangle and adding both points. Note that it is different than the
+    /// new(lo, hi) constructor, where the first point is always used as the
+    /// lower-left corner of the resulting rectangle.
+    pub fn from_point_pair(p1: &S2LatLng, p2: &S2LatLng) -> S2LatLngRect {
+        debug_assert!(p1.is_valid(), "Invalid S2LatLng: {:?}", p1);
+        debug_assert!(p2.is_valid(), "Invalid S2LatLng: {:?}", p2);
+        S2LatLngRect::from_lat_lng(
+            R1Interval::from_point_pair(p1.lat().radians(), p2.lat().radians()),
+            S1Interval::from_point_pair(p1.lng().radians(), p2.lng().radians()),
+        )
+    }
+
+    pub fn lat_lo(&self) -> S1Angle {
+        S1Angle::from_radians(self.lat.lo())
+    }
+
+    pub fn lat_hi(&self) -> S1Angle {
+        S1Angle::from_radians(self.lat.hi())
+    }
+
+    pub fn lng_lo(&self) -> S1Angle {
+        S1Angle::from_radians(self.lng.lo())
+    }
+
+    pub fn lng_hi(&self) -> S1Angle {
+        S1Angle::from_radians(self.lng.hi())
+    }
+
+    pub fn lat(&self) -> &R1Interval {
+        &self.lat
+    }
+
+    pub fn lng(&self) -> &S1Interval {
+        &self.lng
+    }
+
+    pub fn lo(&self) -> S2LatLng {
+        S2LatLng::new(self.lat_lo(), self.lng_lo())
+    }
+
+    pub fn hi(&self) -> S2LatLng {
+        S2LatLng::new(self.lat_hi(), self.lng_hi())
+    }
+
+    /// Return true if the rectangle is valid, which essentially just means
+    /// that the latitude bounds do not exceed Pi/2 in absolute value and
+    /// the longitude bounds do not exceed Pi in absolute value. Also, if
+    /// either the latitude or longitude bound is empty then both must be.
+    pub fn is_valid(&self) -> bool {
+        // The lat/lng ranges must either be both empty or both non-empty.
+        self.lat.lo().abs() <= FRAC_PI_2
+            && self.lat.hi().abs() <= FRAC_PI_2
+            && self.lng.is_valid()
+            && self.lat.is_empty() == self.lng.is_empty()
+    }
+
+    /// Return true if the rectangle is empty, i.e. it contains no points at
+    /// all.
+    pub fn is_empty(&self) -> bool {
+        self.lat.is_empty()
+    }
+
+    /// Return true if the rectangle is full, i.e. it contains all points.
+    pub fn is_full(&self) -> bool {
+        self.lat == S2LatLngRect::full_lat() && self.lng.is_full()
+    }
+
+    /// Return true if the rectangle is a point, i.e. lo() == hi().
+    pub fn is_point(&self) -> bool {
+        self.lat.lo() == self.lat.hi() && self.lng.lo() == self.lng.hi()
+    }
+
+    /// Return true if lng().lo() > lng().hi(), i.e. the rectangle crosses
+    /// the 180 degree longitude line.
+    pub fn is_inverted(&self) -> bool {
+        self.lng.is_inverted()
+    }
+
+    /// Return the k-th vertex of the rectangle (k = 0,1,2,3) in CCW order
+    /// (lower left, lower right, upper right, upper left). For convenience,
+    /// the argument is reduced modulo 4 to the range [0..3].
+    pub fn get_vertex(&self, k: i32) -> S2LatLng {
+        // Twiddle bits to return the points in CCW order (lower left, lower
+        // right, upper right, upper left).
+        let i = ((k >> 1) & 1) as usize;
+        S2LatLng::from_radians(self.lat[i], self.lng[i ^ (k & 1) as usize])
+    }
+
+    /// Return the center of the rectangle in latitude-longitude space (in
+    /// general this is not the center of the region on the sphere). For
+    /// rectangles that cross the 180 degree meridian the longitude of the
+    /// center is measured the short way around, i.e. inside the rectangle.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{S2LatLng, S2LatLngRect};
+    ///
+    /// let rect = S2LatLngRect::new(
+    ///     &S2LatLng::from_degrees(10.0, 170.0),
+    ///     &S2LatLng::from_degrees(20.0, -150.0),
+    /// );
+    /// let center = rect.get_center();
+    /// assert!((center.lat().degrees() - 15.0).abs() < 1e-13);
+    /// assert!((center.lng().degrees() + 170.0).abs() < 1e-13);
+    /// ```
+    pub fn get_center(&self) -> S2LatLng {
+        S2LatLng::from_radians(self.lat.get_center(), self.lng.get_center())
+    }
+
+    /// Return the width and height of this rectangle in latitude-longitude
+    /// space. Empty rectangles have a negative width and height.
+    pub fn get_size(&self) -> S2LatLng {
+        S2LatLng::from_radians(self.lat.get_length(), self.lng.get_length())
+    }
+
+    /// Return the surface area of this rectangle on the unit sphere, in
+    /// steradians. Empty rectangles have zero area, and the full rectangle
+    /// has area 4*Pi.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{S2LatLng, S2LatLngRect};
+    ///
+    /// assert_eq!(S2LatLngRect::full().area(), 4.0 * std::f64::consts::PI);
+    /// let north = S2LatLngRect::new(
+    ///     &S2LatLng::from_degrees(0.0, -180.0),
+    ///     &S2LatLng::from_degrees(90.0, 180.0),
+    /// );
+    /// assert!((north.area() - 2.0 * std::f64::consts::PI).abs() < 1e-15);
+    /// ```
+    pub fn area(&self) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        // This is the size difference of the two spherical caps, multiplied by
+        // the longitude ratio.
+        self.lng.get_length() * (self.lat_hi().sin() - self.lat_lo().sin())
+    }
+
+    /// Return the length of the boundary of this rectangle on the unit
+    /// sphere. Note that the edges along lines of constant latitude are not
+    /// geodesics, so their length is the longitude span scaled by the cosine
+    /// of the latitude. Edges that degenerate to a point (such as a latitude
+    /// edge at a pole) or that do not exist (the meridian edges of a
+    /// rectangle spanning all longitudes) contribute nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{S2LatLng, S2LatLngRect};
+    ///
+    /// // The northern hemisphere is bounded by the equator.
+    /// let north = S2LatLngRect::new(
+    ///     &S2LatLng::from_degrees(0.0, -180.0),
+    ///     &S2LatLng::from_degrees(90.0, 180.0),
+    /// );
+    /// assert!((north.get_perimeter().radians() - 2.0 * std::f64::consts::PI).abs() < 1e-15);
+    /// ```
+    pub fn get_perimeter(&self) -> S1Angle {
+        if self.is_empty() {
+            return S1Angle::zero();
+        }
+        let lng_span = self.lng.get_length();
+        let mut perimeter = lng_span * (self.lat_lo().cos() + self.lat_hi().cos());
+        if !self.lng.is_full() {
+            perimeter += 2.0 * self.lat.get_length();
+        }
+        S1Angle::from_radians(perimeter)
+    }
+
+    /// Return the true centroid of the rectangle multiplied by its surface
+    /// area (see s2centroids.h in the C++ library for details on centroids).
+    /// The result is not unit length, so you may want to normalize it. Note
+    /// that in general the centroid is *not* at the center of the rectangle,
+    /// and in fact it may not even be contained by the rectangle. (It is the
+    /// "center of mass" of the rectangle viewed as subset of the unit
+    /// sphere, i.e. it is the point in space about which this curved shape
+    /// would rotate.)
+    ///
+    /// The reason for multiplying the result by the rectangle area is to
+    /// make it easier to compute the centroid of more complicated shapes.
+    /// The centroid of a union of disjoint regions can be computed simply by
+    /// adding their centroids together.
+    pub fn get_centroid(&self) -> S2Point {
+        // When a sphere is divided into slices of equal thickness by a series
+        // of parallel planes, all slices have the same surface area. This
+        // implies that the z-component of the centroid is simply the midpoint
+        // of the z-interval spanned by the S2LatLngRect.
+        //
+        // Similarly, it is easy to see that the (x,y) of the centroid lies in
+        // the plane through the midpoint of the rectangle's longitude
+        // interval. We only need to determine the distance "d" of this point
+        // from the z-axis.
+        //
+        // Let's restrict our attention to a particular z-value. In this
+        // z-plane, the S2LatLngRect is a circular arc. The centroid of this
+        // arc lies on a radial line through the midpoint of the arc, and at a
+        // distance from the z-axis of
+        //
+        //     r * (sin(alpha) / alpha)
+        //
+        // where r = sqrt(1-z^2) is the radius of the arc, and "alpha" is half
+        // of the arc length (i.e., the arc covers longitudes [-alpha, alpha]).
+        //
+        // To find the centroid distance from the z-axis for the entire
+        // rectangle, we just need to integrate over the z-interval. This gives
+        //
+        //    d = Integrate[sqrt(1-z^2)*sin(alpha)/alpha, z1..z2] / (z2 - z1)
+        //
+        // where [z1, z2] is the range of z-values covered by the rectangle.
+        // This simplifies to
+        //
+        //    d = sin(alpha)/(2*alpha*(z2-z1))*(z2*r2 - z1*r1 + theta2 - theta1)
+        //
+        // where [theta1, theta2] is the latitude interval, z1=sin(theta1),
+        // z2=sin(theta2), r1=cos(theta1), and r2=cos(theta2).
+        //
+        // Finally, we want to return not the centroid itself, but the centroid
+        // scaled by the area of the rectangle. The area of the rectangle is
+        //
+        //    A = 2 * alpha * (z2 - z1)
+        //
+        // which fortunately appears in the denominator of "d".
+        if self.is_empty() {
+            return S2Point::zero();
+        }
+        let (z1, r1) = self.lat_lo().sin_cos();
+        let (z2, r2) = self.lat_hi().sin_cos();
+        let alpha = 0.5 * self.lng.get_length();
+        let r = alpha.sin() * (r2 * z2 - r1 * z1 + self.lat.get_length());
+        let (sin_lng, cos_lng) = self.lng.get_center().sin_cos();
+        let z = alpha * (z2 + z1) * (z2 - z1); // scaled by the area
+        S2Point::new(r * cos_lng, r * sin_lng, z)
+    }
+
+    /// Return true if the rectangle contains the given (normalized) point.
+    pub fn contains_latlng(&self, ll: &S2LatLng) -> bool {
+        debug_assert!(ll.is_valid(), "Invalid S2LatLng: {:?}", ll);
+        self.lat.contains(ll.lat().radians()) && self.lng.contains(ll.lng().radians())
+    }
+
+    /// Return true if the rectangle contains the given point. The point does
+    /// not need to be normalized.
+    pub fn contains_point(&self, p: &S2Point) -> bool {
+        self.contains_latlng(&S2LatLng::from_point(p))
+    }
+
+    /// Return true if and only if the rectangle contains the given other
+    /// rectangle.
+    pub fn contains(&self, other: &S2LatLngRect) -> bool {
+        self.lat.contains_interval(&other.lat) && self.lng.contains_interval(&other.lng)
+    }
+
+    /// Return true if this rectangle and the given other rectangle have any
+    /// points in common.
+    pub fn intersects(&self, other: &S2LatLngRect) -> bool {
+        self.lat.intersects(&other.lat) && self.lng.intersects(&other.lng)
+    }
+
+    /// Return the smallest rectangle containing the union of this rectangle
+    /// and the given rectangle.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{S2LatLng, S2LatLngRect};
+    ///
+    /// let a = S2LatLngRect::from_point(&S2LatLng::from_degrees(10.0, 170.0));
+    /// let b = S2LatLngRect::from_point(&S2LatLng::from_degrees(20.0, -170.0));
+    /// let u = a.union(&b);
+    /// assert!(u.is_inverted());
+    /// assert!(u.contains_latlng(&S2LatLng::from_degrees(15.0, 180.0)));
+    /// assert_eq!(a.union(&S2LatLngRect::empty()), a);
+    /// ```
+    pub fn union(&self, other: &S2LatLngRect) -> S2LatLngRect {
+        S2LatLngRect::from_lat_lng(self.lat.union(&other.lat), self.lng.union(&other.lng))
+    }
+
+    /// Return true if the rectangle contains the given cell. This test is
+    /// conservative, since it compares the cell's bounding rectangle rather
+    /// than the cell itself.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{S2Cell, S2LatLngRect};
+    ///
+    /// let cell = S2Cell::from_face_pos_level(2, 0, 5);
+    /// assert!(S2LatLngRect::full().contains_cell(&cell));
+    /// assert!(!S2LatLngRect::empty().contains_cell(&cell));
+    /// ```
+    pub fn contains_cell(&self, cell: &S2Cell) -> bool {
+        // A latitude-longitude rectangle contains a cell if and only if it
+        // contains the cell's bounding rectangle. This test is exact from a
+        // mathematical point of view, assuming that the bounds returned by
+        // S2Cell::get_rect_bound() are tight. However, note that there can be
+        // a loss of precision when converting between representations -- for
+        // example, if an S2Cell is converted to a polygon, the polygon's
+        // bounding rectangle may not contain the cell's bounding rectangle.
+        // This has some slightly unexpected side effects; for instance, if one
+        // creates an S2Polygon from an S2Cell, the polygon will contain the
+        // cell, but the polygon's bounding box will not.
+        self.contains(&cell.get_rect_bound())
+    }
+
+    /// Return true if the rectangle may intersect the given cell. This test
+    /// is cheap but is conservative: it compares the cell's bounding
+    /// rectangle rather than the cell itself.
+    pub fn may_intersect_cell(&self, cell: &S2Cell) -> bool {
+        self.intersects(&cell.get_rect_bound())
+    }
+
+    /// Return true if this rectangle and the given cell have any points in
+    /// common. Unlike may_intersect_cell(), this test is exact.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{S2Cell, S2LatLng, S2LatLngRect};
+    ///
+    /// // This rectangle intersects the bounding rectangle of face 2 but not
+    /// // the face itself.
+    /// let rect = S2LatLngRect::new(
+    ///     &S2LatLng::from_degrees(41.0, -87.0),
+    ///     &S2LatLng::from_degrees(42.0, -79.0),
+    /// );
+    /// let face = S2Cell::from_face(2);
+    /// assert!(rect.may_intersect_cell(&face));
+    /// assert!(!rect.intersects_cell(&face));
+    /// ```
+    pub fn intersects_cell(&self, cell: &S2Cell) -> bool {
+        // First we eliminate the cases where one region completely contains
+        // the other. Once these are disposed of, then the regions will
+        // intersect if and only if their boundaries intersect.
+        if self.is_empty() {
+            return false;
+        }
+        if self.contains_point(&cell.get_center_raw()) {
+            return true;
+        }
+        if cell.contains_point(&self.get_center().to_point()) {
+            return true;
+        }
+
+        // Quick rejection test (not required for correctness).
+        if !self.intersects(&cell.get_rect_bound()) {
+            return false;
+        }
+
+        // Precompute the cell vertices as points and latitude-longitudes. We
+        // also check whether the S2Cell contains any corner of the rectangle,
+        // or vice-versa, since the edge-crossing tests only check the edge
+        // interiors.
+        let mut cell_v = [S2Point::default(); 4];
+        let mut cell_lng = [0.0; 4];
+        for i in 0..4 {
+            cell_v[i] = cell.get_vertex(i as i32); // Must be normalized.
+            let cell_ll = S2LatLng::from_point(&cell_v[i]);
+            if self.contains_latlng(&cell_ll) {
+                return true;
+            }
+            cell_lng[i] = cell_ll.lng().radians();
+            if cell.contains_point(&self.get_vertex(i as i32).to_point()) {
+                return true;
+            }
+        }
+
+        // Now check whether the boundaries intersect. Unfortunately, a
+        // latitude-longitude rectangle does not have straight edges -- two
+        // edges are curved, and at least one of them is concave.
+        for i in 0..4 {
+            let edge_lng = S1Interval::from_point_pair(cell_lng[i], cell_lng[(i + 1) & 3]);
+            if !self.lng.intersects(&edge_lng) {
+                continue;
+            }
+
+            let a = &cell_v[i];
+            let b = &cell_v[(i + 1) & 3];
+            if edge_lng.contains(self.lng.lo())
+                && S2LatLngRect::intersects_lng_edge(a, b, &self.lat, self.lng.lo())
+            {
+                return true;
+            }
+            if edge_lng.contains(self.lng.hi())
+                && S2LatLngRect::intersects_lng_edge(a, b, &self.lat, self.lng.hi())
+            {
+                return true;
+            }
+            if S2LatLngRect::intersects_lat_edge(a, b, self.lat.lo(), &self.lng) {
+                return true;
+            }
+            if S2LatLngRect::intersects_lat_edge(a, b, self.lat.hi(), &self.lng) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// If the rectangle does not include either pole, return it unmodified.
+    /// Otherwise expand the longitude range to Full() so that the rectangle
+    /// contains all possible representations of the contained pole(s).
+    pub fn polar_closure(&self) -> S2LatLngRect {
+        if self.lat.lo() == -FRAC_PI_2 || self.lat.hi() == FRAC_PI_2 {
+            return S2LatLngRect::from_lat_lng(self.lat, S1Interval::full());
+        }
+        *self
+    }
+
+    /// Return true if the edge AB intersects the given edge of constant
+    /// longitude.
+    fn intersects_lng_edge(a: &S2Point, b: &S2Point, lat: &R1Interval, lng: f64) -> bool {
+        // The nice thing about edges of constant longitude is that they are
+        // straight lines on the sphere (geodesics).
+        let c = S2LatLng::from_radians(lat.lo(), lng).to_point();
+        let d = S2LatLng::from_radians(lat.hi(), lng).to_point();
+
+        // The edges cross at a point interior to both edges if and only if
+        // the vertices of each edge are strictly on opposite sides of the
+        // other edge (with consistent orientation).
+        let acb = -sign(a, b, &c);
+        let bda = sign(a, b, &d);
+        if acb == 0 || acb != bda {
+            return false;
+        }
+        let cbd = -sign(&c, &d, b);
+        let dac = sign(&c, &d, a);
+        cbd == acb && dac == acb
+    }
+
+    /// Return true if the edge AB intersects the given edge of constant
+    /// latitude. Requires the vectors to have unit length.
+    fn intersects_lat_edge(a: &S2Point, b: &S2Point, lat: f64, lng: &S1Interval) -> bool {
+        // Unfortunately, lines of constant latitude are curves on the sphere.
+        // They can intersect a straight edge in 0, 1, or 2 points.
+
+        // First, compute the normal to the plane AB that points vaguely north.
+        let mut z = robust_cross_prod(a, b).normalize();
+        if z.z() < 0.0 {
+            z = -z;
+        }
+
+        // Extend this to an orthonormal frame (x,y,z) where x is the direction
+        // where the great circle through AB achieves its maximum latitude.
+        let y = robust_cross_prod(&z, &S2Point::new(0.0, 0.0, 1.0)).normalize();
+        let x = y.cross_prod(&z);
+        debug_assert!(x.z() >= 0.0);
+
+        // Compute the angle "theta" from the x-axis (in the x-y plane defined
+        // above) where the great circle intersects the given line of latitude.
+        let sin_lat = lat.sin();
+        if sin_lat.abs() >= x.z() {
+            return false; // The great circle does not reach the given latitude.
+        }
+        let cos_theta = sin_lat / x.z();
+        let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+        let theta = sin_theta.atan2(cos_theta);
+
+        // The candidate intersection points are located +/- theta in the x-y
+        // plane. For an intersection to be valid, we need to check that the
+        // intersection point is contained in the interior of the edge AB and
+        // also that it is contained within the given longitude interval "lng".
+
+        // Compute the range of theta values spanned by the edge AB.
+        let ab_theta = S1Interval::from_point_pair(
+            a.dot_prod(&y).atan2(a.dot_prod(&x)),
+            b.dot_prod(&y).atan2(b.dot_prod(&x)),
+        );
+
+        if ab_theta.contains(theta) {
+            // Check if the intersection point is also in the given "lng"
+            // interval.
+            let isect = x * cos_theta + y * sin_theta;
+            if lng.contains(isect.y().atan2(isect.x())) {
+                return true;
+            }
+        }
+        if ab_theta.contains(-theta) {
+            // Check if the intersection point is also in the given "lng"
+            // interval.
+            let isect = x * cos_theta - y * sin_theta;
+            if lng.contains(isect.y().atan2(isect.x())) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Return the minimum distance (measured along the surface of the sphere)
+    /// from the given point to the rectangle (both its boundary and its
+    /// interior). The result is zero if the point is contained by the
+    /// rectangle. The rectangle must be non-empty and the point must be
+    /// valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{S2LatLng, S2LatLngRect};
+    ///
+    /// let rect = S2LatLngRect::new(
+    ///     &S2LatLng::from_degrees(0.0, 170.0),
+    ///     &S2LatLng::from_degrees(10.0, -170.0),
+    /// );
+    /// assert_eq!(
+    ///     rect.get_distance(&S2LatLng::from_degrees(5.0, 180.0))
+    ///         .radians(),
+    ///     0.0
+    /// );
+    /// let north = rect.get_distance(&S2LatLng::from_degrees(30.0, 175.0));
+    /// assert!((north.degrees() - 20.0).abs() < 1e-13);
+    /// ```
+    pub fn get_distance(&self, p: &S2LatLng) -> S1Angle {
+        // The closest point is either directly north or south of "p" (if the
+        // longitude interval contains p's longitude) or on one of the two
+        // longitude edges.
+        debug_assert!(!self.is_empty());
+        debug_assert!(p.is_valid());
+
+        let p_lat = p.lat().radians();
+        let p_lng = p.lng().radians();
+        if self.lng.contains(p_lng) {
+            return S1Angle::from_radians(
+                0f64.max(p_lat - self.lat.hi()).max(self.lat.lo() - p_lat),
+            );
+        }
+
+        // Otherwise the closest edge is the one on the same side of the
+        // complement's center as "p".
+        let interval = S1Interval::new(self.lng.hi(), self.lng.get_complement_center());
+        let lng = if interval.contains(p_lng) {
+            self.lng.hi()
+        } else {
+            self.lng.lo()
+        };
+        let lo = S2LatLng::from_radians(self.lat.lo(), lng).to_point();
+        let hi = S2LatLng::from_radians(self.lat.hi(), lng).to_point();
+        get_distance(&p.to_point(), &lo, &hi)
+    }
+
+    /// Return the point of the rectangle that is closest to "p" in
+    /// latitude-longitude space, i.e. "p" itself if it is contained by the
+    /// rectangle, and otherwise the point obtained by clamping its latitude
+    /// and longitude to the corresponding intervals (longitudes wrap around
+    /// at +/-180 degrees). When the longitude interval contains p's longitude
+    /// this is also the closest point measured along the sphere, so that its
+    /// distance from "p" equals get_distance(p). The rectangle must be
+    /// non-empty and the point must be valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{S2LatLng, S2LatLngRect};
+    ///
+    /// let rect = S2LatLngRect::new(
+    ///     &S2LatLng::from_degrees(0.0, 170.0),
+    ///     &S2LatLng::from_degrees(10.0, -170.0),
+    /// );
+    /// let inside = S2LatLng::from_degrees(5.0, 180.0);
+    /// assert_eq!(rect.project(&inside), inside);
+    /// assert_eq!(
+    ///     rect.project(&S2LatLng::from_degrees(30.0, -160.0)),
+    ///     S2LatLng::from_degrees(10.0, -170.0)
+    /// );
+    /// ```
+    pub fn project(&self, p: &S2LatLng) -> S2LatLng {
+        debug_assert!(!self.is_empty());
+        debug_assert!(p.is_valid());
+        S2LatLng::from_radians(
+            self.lat.project(p.lat().radians()),
+            self.lng.project(p.lng().radians()),
+        )
+    }
+
+    /// Return a rectangle that has been expanded by margin.lat() on each
+    /// side in the latitude direction, and by margin.lng() on each side in
+    /// the longitude direction. If either margin is negative, then shrink
+    /// the rectangle on the corresponding sides instead. The resulting
+    /// rectangle may be empty.
+    ///
+    /// As noted above, the latitude-longitude space has the topology of a
+    /// cylinder. Longitudes "wrap around" at +/-180 degrees, while
+    /// latitudes are clamped to range [-90, 90]. This means that any
+    /// expansion (positive or negative) of the full rectangle remains full
+    /// (since the "rectangle" is actually a continuous band around the
+    /// cylinder), while any expansion of the empty rectangle remains empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{S2LatLng, S2LatLngRect};
+    ///
+    /// let rect = S2LatLngRect::new(
+    ///     &S2LatLng::from_degrees(70.0, 170.0),
+    ///     &S2LatLng::from_degrees(80.0, 175.0),
+    /// );
+    /// let expanded = rect.expanded(&S2LatLng::from_degrees(20.0, 10.0));
+    /// assert_eq!(expanded.lat_hi().degrees(), 90.0);
+    /// assert!(expanded.is_inverted());
+    /// assert!(expanded.contains_latlng(&S2LatLng::from_degrees(85.0, -178.0)));
+    /// ```
+    pub fn expanded(&self, margin: &S2LatLng) -> S2LatLngRect {
+        let lat = self.lat.expanded(margin.lat().radians());
+        let lng = self.lng.expanded(margin.lng().radians());
+        if lat.is_empty() || lng.is_empty() {
+            return S2LatLngRect::empty();
+        }
+        S2LatLngRect {
+            lat: lat.intersection(&S2LatLngRect::full_lat()),
+            lng,
+        }
+    }
+}
+
+impl S2Region for S2LatLngRect {
+    fn get_cap_bound(&self) -> S2Cap {
+        // We consider two possible bounding caps, one whose axis passes
+        // through the center of the lat-long rectangle and one whose axis
+        // passes through the north or south pole. We return the smaller of
+        // the two caps.
+        if self.is_empty() {
+            return S2Cap::empty();
+        }
+
+        let (pole_z, pole_angle) = if self.lat.lo() + self.lat.hi() < 0.0 {
+            // South pole axis yields smaller cap.
+            (-1.0, FRAC_PI_2 + self.lat.hi())
+        } else {
+            (1.0, FRAC_PI_2 - self.lat.lo())
+        };
+        let pole_cap = S2Cap::from_center_angle(
+            S2Point::new(0.0, 0.0, pole_z),
+            S1Angle::from_radians(pole_angle),
+        );
+
+        // For bounding rectangles that span 180 degrees or less in longitude,
+        // the maximum cap size is achieved at one of the rectangle vertices.
+        // For rectangles that are larger than 180 degrees, we punt and always
+        // return a bounding cap centered at one of the two poles.
+        let lng_span = self.lng.hi() - self.lng.lo();
+        if remainder(lng_span, 2.0 * PI) >= 0.0 && lng_span < 2.0 * PI {
+            let mut mid_cap = S2Cap::from_point(self.get_center().to_point());
+            for k in 0..4 {
+                mid_cap.add_point(&self.get_vertex(k).to_point());
+            }
+            if mid_cap.height() < pole_cap.height() {
+                return mid_cap;
+            }
+        }
+        pole_cap
+    }
+
+    fn get_rect_bound(&self) -> S2LatLngRect {
+        *self
+    }
+
+    #[cfg(feature = "alloc")]
+    fn get_cell_union_bound(&self, cell_ids: &mut Vec<S2CellId>) {
+        self.get_cap_bound().get_cell_union_bound(cell_ids)
+    }
+
+    fn contains_cell(&self, cell: &S2Cell) -> bool {
+        S2LatLngRect::contains_cell(self, cell)
+    }
+
+    fn may_intersect_cell(&self, cell: &S2Cell) -> bool {
+        S2LatLngRect::may_intersect_cell(self, cell)
+    }
+
+    fn contains_point(&self, point: &S2Point) -> bool {
+        S2LatLngRect::contains_point(self, point)
+    }
+}
+
+impl Default for S2LatLngRect {
+    /// The default constructor creates an empty S2LatLngRect.
+    fn default() -> S2LatLngRect {
+        S2LatLngRect::empty()
+    }
+}
+
+/// Formats the rectangle as "[lat_lo:lat_hi, lng_lo:lng_hi] deg", with all
+/// bounds in degrees.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{S2LatLng, S2LatLngRect};
+///
+/// let rect = S2LatLngRect::new(
+///     &S2LatLng::from_degrees(-10.0, 170.0),
+///     &S2LatLng::from_degrees(20.5, -170.0),
+/// );
+/// assert_eq!(
+///     rect.to_string(),
+///     "[-10.000000:20.500000, 170.000000:-170.000000] deg"
+/// );
+/// ```
+impl fmt::Display for S2LatLngRect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[{:.6}:{:.6}, {:.6}:{:.6}] deg",
+            self.lat_lo().degrees(),
+            self.lat_hi().degrees(),
+            self.lng_lo().degrees(),
+            self.lng_hi().degrees()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use core::f64::consts::FRAC_PI_4;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::s2::s2testing::random_point;
+
+    fn rect_from_degrees(lat_lo: f64, lng_lo: f64, lat_hi: f64, lng_hi: f64) -> S2LatLngRect {
+        // Convenience method to construct a rectangle. This method is
+        // intentionally *not* in the S2LatLngRect interface because the
+        // argument order is ambiguous, but hopefully it's not too confusing
+        // within the context of this unit test.
+        S2LatLngRect::new(
+            &S2LatLng::from_degrees(lat_lo, lng_lo).normalized(),
+            &S2LatLng::from_degrees(lat_hi, lng_hi).normalized(),
+        )
+    }
+
+    fn assert_approx_eq(a: &S2LatLngRect, b: &S2LatLngRect) {
+        const MAX_ERROR: f64 = 1e-15;
+        assert!(
+            (a.lat.lo() - b.lat.lo()).abs() <= MAX_ERROR
+                && (a.lat.hi() - b.lat.hi()).abs() <= MAX_ERROR
+                && (a.lng.lo() - b.lng.lo()).abs() <= MAX_ERROR
+                && (a.lng.hi() - b.lng.hi()).abs() <= MAX_ERROR,
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn test_empty_and_full() {
+        let empty = S2LatLngRect::empty();
+        let full = S2LatLngRect::full();
+        assert!(empty.is_valid() && empty.is_empty() && !empty.is_point());
+        assert!(full.is_valid() && full.is_full() && !full.is_point());
+        assert_eq!(S2LatLngRect::default(), empty);
+    }
+
+    #[test]
+    fn test_accessors() {
+        let d1 = rect_from_degrees(-90.0, 0.0, -45.0, 180.0);
+        assert_eq!(d1.lat_lo().degrees(), -90.0);
+        assert_eq!(d1.lat_hi().degrees(), -45.0);
+        assert_eq!(d1.lng_lo().degrees(), 0.0);
+        assert_eq!(d1.lng_hi().degrees(), 180.0);
+        assert!(!d1.is_inverted());
+        assert!(d1.contains_latlng(&S2LatLng::from_degrees(-60.0, 90.0)));
+        assert!(!d1.contains_latlng(&S2LatLng::from_degrees(-30.0, 90.0)));
+        assert!(rect_from_degrees(0.0, 170.0, 10.0, -170.0).is_inverted());
+    }
+
+    #[test]
+    fn test_expanded() {
+        let margin = |lat, lng| S2LatLng::from_degrees(lat, lng);
+        assert_approx_eq(
+            &rect_from_degrees(70.0, 150.0, 80.0, 170.0).expanded(&margin(20.0, 30.0)),
+            &rect_from_degrees(50.0, 120.0, 90.0, -160.0),
+        );
+        assert!(S2LatLngRect::empty()
+            .expanded(&margin(20.0, 30.0))
+            .is_empty());
+        assert!(S2LatLngRect::full()
+            .expanded(&margin(500.0, 500.0))
+            .is_full());
+        assert_approx_eq(
+            &rect_from_degrees(-90.0, 170.0, 10.0, 20.0).expanded(&margin(30.0, 80.0)),
+            &rect_from_degrees(-90.0, -180.0, 40.0, 180.0),
+        );
+
+        // Negative margins.
+        assert_approx_eq(
+            &rect_from_degrees(10.0, -50.0, 60.0, 70.0).expanded(&margin(-10.0, -10.0)),
+            &rect_from_degrees(20.0, -40.0, 50.0, 60.0),
+        );
+        assert_approx_eq(
+            &rect_from_degrees(-20.0, -180.0, 20.0, 180.0).expanded(&margin(-10.0, -10.0)),
+            &rect_from_degrees(-10.0, -180.0, 10.0, 180.0),
+        );
+        assert!(rect_from_degrees(10.0, -50.0, 60.0, 70.0)
+            .expanded(&margin(-30.0, 0.0))
+            .is_empty());
+        assert!(rect_from_degrees(-20.0, -180.0, 20.0, 180.0)
+            .expanded(&margin(-50.0, 0.0))
+            .is_empty());
+        assert!(rect_from_degrees(10.0, -50.0, 60.0, 70.0)
+            .expanded(&margin(0.0, -70.0))
+            .is_empty());
+
+        // Mixed margins.
+        assert_approx_eq(
+            &rect_from_degrees(10.0, -50.0, 60.0, 70.0).expanded(&margin(-10.0, 30.0)),
+            &rect_from_degrees(20.0, -80.0, 50.0, 100.0),
+        );
+        assert_approx_eq(
+            &rect_from_degrees(-20.0, -180.0, 20.0, 180.0).expanded(&margin(10.0, -500.0)),
+            &rect_from_degrees(-30.0, -180.0, 30.0, 180.0),
+        );
+
+        // Expanding longitude by more than 180 degrees on each side yields
+        // the full longitude range.
+        let wide = rect_from_degrees(-10.0, -10.0, 10.0, 10.0).expanded(&margin(0.0, 175.0));
+        assert!(wide.lng().is_full());
+
+        // Large latitude margins saturate at the poles. The result contains
+        // the pole only at the longitudes of the original rectangle, so
+        // polar_closure() is needed to contain all representations of it.
+        let near_pole = rect_from_degrees(80.0, 10.0, 85.0, 20.0);
+        let expanded = near_pole.expanded(&margin(30.0, 0.0));
+        assert_eq!(expanded.lat_hi().degrees(), 90.0);
+        assert!((expanded.lat_lo().degrees() - 50.0).abs() <= 1e-13);
+        assert!(expanded.contains_latlng(&S2LatLng::from_degrees(90.0, 15.0)));
+        assert!(!expanded.contains_latlng(&S2LatLng::from_degrees(90.0, -100.0)));
+        let closed = expanded.polar_closure();
+        assert!(closed.lng().is_full());
+        assert_eq!(closed.lat(), expanded.lat());
+        assert!(closed.contains_latlng(&S2LatLng::from_degrees(90.0, -100.0)));
+
+        let south = rect_from_degrees(-85.0, 10.0, -80.0, 20.0).expanded(&margin(200.0, 0.0));
+        assert_eq!(*south.lat(), S2LatLngRect::full_lat());
+        assert!(south.polar_closure().is_full());
+    }
+
+    #[test]
+    fn test_center_and_size() {
+        let r = rect_from_degrees(-40.0, -60.0, 20.0, 80.0);
+        assert!((r.get_center().lat().degrees() + 10.0).abs() < 1e-13);
+        assert!((r.get_center().lng().degrees() - 10.0).abs() < 1e-13);
+        assert!((r.get_size().lat().degrees() - 60.0).abs() < 1e-13);
+        assert!((r.get_size().lng().degrees() - 140.0).abs() < 1e-13);
+
+        // Rectangles that wrap around the 180 degree meridian.
+        let r = rect_from_degrees(0.0, 170.0, 10.0, -170.0);
+        assert!((r.get_center().lng().degrees() - 180.0).abs() < 1e-13);
+        assert!((r.get_size().lng().degrees() - 20.0).abs() < 1e-13);
+        let r = rect_from_degrees(0.0, 100.0, 10.0, -170.0);
+        assert!((r.get_center().lng().degrees() - 145.0).abs() < 1e-13);
+        assert!((r.get_size().lng().degrees() - 90.0).abs() < 1e-13);
+        let r = rect_from_degrees(0.0, 170.0, 10.0, -100.0);
+        assert!((r.get_center().lng().degrees() + 145.0).abs() < 1e-13);
+        assert!(r.contains_latlng(&r.get_center()));
+
+        let full = S2LatLngRect::full();
+        assert_eq!(full.get_center(), S2LatLng::from_radians(0.0, 0.0));
+        assert_eq!(full.get_size(), S2LatLng::from_degrees(180.0, 360.0));
+        let size = S2LatLngRect::empty().get_size();
+        assert!(size.lat().radians() < 0.0 && size.lng().radians() < 0.0);
+        let point = S2LatLngRect::from_point(&S2LatLng::from_degrees(30.0, 40.0));
+        assert_eq!(point.get_size(), S2LatLng::from_radians(0.0, 0.0));
+    }
+
+    #[test]
+    fn test_from_center_half_size() {
+        let from_degrees = |lat, lng, lat_half, lng_half| {
+            S2LatLngRect::from_center_half_size(
+                &S2LatLng::from_degrees(lat, lng),
+                &S2LatLng::from_degrees(lat_half, lng_half),
+            )
+        };
+        let rect = from_degrees(10.0, -30.0, 10.0, 20.0);
+        assert_approx_eq(&rect, &rect_from_degrees(0.0, -50.0, 20.0, -10.0));
+        assert!((rect.get_size().lat().degrees() - 20.0).abs() < 1e-13);
+        assert!((rect.get_size().lng().degrees() - 40.0).abs() < 1e-13);
+        assert_eq!(
+            rect,
+            S2LatLngRect::from_center_size(
+                &S2LatLng::from_degrees(10.0, -30.0),
+                &S2LatLng::from_degrees(20.0, 40.0)
+            )
+        );
+        assert_eq!(
+            from_degrees(10.0, -30.0, 0.0, 0.0),
+            S2LatLngRect::from_point(&S2LatLng::from_degrees(10.0, -30.0))
+        );
+
+        // Latitudes are clamped at the poles.
+        let rect = from_degrees(85.0, 0.0, 10.0, 5.0);
+        assert_eq!(rect.lat_hi().degrees(), 90.0);
+        assert!((rect.lat_lo().degrees() - 75.0).abs() < 1e-13);
+        let rect = from_degrees(-85.0, 0.0, 10.0, 5.0);
+        assert_eq!(rect.lat_lo().degrees(), -90.0);
+        assert!((rect.lat_hi().degrees() + 75.0).abs() < 1e-13);
+
+        // Longitudes wrap around the date line, and a half-size of 180
+        // degrees or more covers all longitudes.
+        let rect = from_degrees(0.0, -175.0, 5.0, 10.0);
+        assert!(rect.is_inverted());
+        assert!(rect.contains_latlng(&S2LatLng::from_degrees(0.0, 175.5)));
+        assert!(!rect.contains_latlng(&S2LatLng::from_degrees(0.0, 170.0)));
+        assert!(from_degrees(0.0, 0.0, 5.0, 180.0).lng().is_full());
+        assert!(!from_degrees(0.0, 0.0, 5.0, 179.9).lng().is_full());
+        assert!(from_degrees(0.0, 0.0, 90.0, 180.0).is_full());
+    }
+
+    #[test]
+    fn test_from_center_size() {
+        let from_degrees = |lat, lng, lat_size, lng_size| {
+            S2LatLngRect::from_center_size(
+                &S2LatLng::from_degrees(lat, lng),
+                &S2LatLng::from_degrees(lat_size, lng_size),
+            )
+        };
+        assert_approx_eq(
+            &from_degrees(80.0, 170.0, 40.0, 60.0),
+            &rect_from_degrees(60.0, 140.0, 90.0, -160.0),
+        );
+        assert!(from_degrees(10.0, 40.0, 210.0, 400.0).is_full());
+        assert_approx_eq(
+            &from_degrees(-90.0, 180.0, 20.0, 50.0),
+            &rect_from_degrees(-90.0, 155.0, -80.0, -155.0),
+        );
+
+        // The center and size round trip for rectangles away from the poles.
+        let rect = from_degrees(10.0, -30.0, 20.0, 40.0);
+        assert_approx_eq(&rect, &rect_from_degrees(0.0, -50.0, 20.0, -10.0));
+        assert!((rect.get_center().lat().degrees() - 10.0).abs() < 1e-13);
+        assert!((rect.get_center().lng().degrees() + 30.0).abs() < 1e-13);
+        assert!((rect.get_size().lat().degrees() - 20.0).abs() < 1e-13);
+        assert!((rect.get_size().lng().degrees() - 40.0).abs() < 1e-13);
+
+        // Latitudes are clamped at both poles.
+        let rect = from_degrees(85.0, 0.0, 20.0, 10.0);
+        assert_eq!(rect.lat_hi().degrees(), 90.0);
+        assert!((rect.lat_lo().degrees() - 75.0).abs() < 1e-13);
+        let rect = from_degrees(-85.0, 0.0, 20.0, 10.0);
+        assert_eq!(rect.lat_lo().degrees(), -90.0);
+        assert!((rect.lat_hi().degrees() + 75.0).abs() < 1e-13);
+        let rect = from_degrees(0.0, 0.0, 400.0, 10.0);
+        assert_eq!(*rect.lat(), S2LatLngRect::full_lat());
+
+        // Longitudes wrap around the date line in both directions.
+        let rect = from_degrees(0.0, 175.0, 10.0, 20.0);
+        assert!(rect.is_inverted());
+        assert!(rect.contains_latlng(&S2LatLng::from_degrees(0.0, 180.0)));
+        assert!(rect.contains_latlng(&S2LatLng::from_degrees(0.0, -175.5)));
+        assert!(!rect.contains_latlng(&S2LatLng::from_degrees(0.0, -170.0)));
+        let rect = from_degrees(0.0, -175.0, 10.0, 20.0);
+        assert!(rect.is_inverted());
+        assert!(rect.contains_latlng(&S2LatLng::from_degrees(0.0, 176.0)));
+        assert!(!rect.contains_latlng(&S2LatLng::from_degrees(0.0, 170.0)));
+        assert!((rect.get_size().lng().degrees() - 20.0).abs() < 1e-13);
+
+        // A longitude size of 360 degrees or more is full.
+        assert!(from_degrees(0.0, 123.0, 10.0, 360.0).lng().is_full());
+        assert!(!from_degrees(0.0, 123.0, 10.0, 359.0).lng().is_full());
+    }
+
+    #[test]
+    fn test_get_vertex() {
+        let r1 =
+            S2LatLngRect::from_lat_lng(R1Interval::new(0.0, FRAC_PI_2), S1Interval::new(-PI, 0.0));
+        assert_eq!(r1.get_vertex(0), S2LatLng::from_radians(0.0, PI));
+        assert_eq!(r1.get_vertex(1), S2LatLng::from_radians(0.0, 0.0));
+        assert_eq!(r1.get_vertex(2), S2LatLng::from_radians(FRAC_PI_2, 0.0));
+        assert_eq!(r1.get_vertex(3), S2LatLng::from_radians(FRAC_PI_2, PI));
+
+        // Make sure that get_vertex() returns vertices in CCW order.
+        for i in 0..4 {
+            let lat = FRAC_PI_4 * (i - 2) as f64;
+            let lng = FRAC_PI_2 * (i - 2) as f64 + 0.2;
+            let r = S2LatLngRect::from_lat_lng(
+                R1Interval::new(lat, lat + FRAC_PI_4),
+                S1Interval::new(
+                    remainder(lng, 2.0 * PI),
+                    remainder(lng + FRAC_PI_2, 2.0 * PI),
+                ),
+            );
+            for k in 0..4 {
+                assert!(
+                    sign(
+                        &r.get_vertex(k - 1).to_point(),
+                        &r.get_vertex(k).to_point(),
+                        &r.get_vertex(k + 1).to_point()
+                    ) > 0
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_contains_and_intersects() {
+        let r1 = rect_from_degrees(0.0, -180.0, 30.0, 0.0);
+        assert!(r1.contains(&rect_from_degrees(10.0, -170.0, 20.0, -10.0)));
+        assert!(!r1.contains(&rect_from_degrees(10.0, -10.0, 20.0, 10.0)));
+        assert!(r1.intersects(&rect_from_degrees(10.0, -10.0, 20.0, 10.0)));
+        assert!(!r1.intersects(&rect_from_degrees(40.0, -10.0, 50.0, 10.0)));
+        assert!(r1.contains(&S2LatLngRect::empty()));
+        assert!(!r1.intersects(&S2LatLngRect::empty()));
+        assert!(S2LatLngRect::full().contains(&r1));
+        assert!(r1.contains_point(&S2LatLng::from_degrees(15.0, -90.0).to_point()));
+        assert!(!r1.contains_point(&S2LatLng::from_degrees(15.0, 90.0).to_point()));
+    }
+
+    #[test]
+    fn test_union_and_from_point_pair() {
+        let r1 = rect_from_degrees(0.0, -180.0, 30.0, 0.0);
+        let r2 = rect_from_degrees(-10.0, 10.0, 20.0, 20.0);
+        assert_eq!(r1.union(&r2), rect_from_degrees(-10.0, -180.0, 30.0, 20.0));
+        assert_eq!(r1.union(&S2LatLngRect::empty()), r1);
+        assert_eq!(S2LatLngRect::empty().union(&r2), r2);
+        assert!(r1.union(&S2LatLngRect::full()).is_full());
+
+        assert_eq!(
+            S2LatLngRect::from_point_pair(
+                &S2LatLng::from_degrees(10.0, 20.0),
+                &S2LatLng::from_degrees(-10.0, -20.0)
+            ),
+            rect_from_degrees(-10.0, -20.0, 10.0, 20.0)
+        );
+        // The pair spans the shorter way around, across the antimeridian.
+        assert!(S2LatLngRect::from_point_pair(
+            &S2LatLng::from_degrees(0.0, 170.0),
+            &S2LatLng::from_degrees(0.0, -170.0)
+        )
+        .is_inverted());
+    }
+
+    #[test]
+    fn test_polar_closure() {
+        assert_eq!(
+            rect_from_degrees(-89.0, 0.0, 89.0, 1.0).polar_closure(),
+            rect_from_degrees(-89.0, 0.0, 89.0, 1.0)
+        );
+        assert_eq!(
+            rect_from_degrees(-90.0, -30.0, -45.0, 100.0).polar_closure(),
+            rect_from_degrees(-90.0, -180.0, -45.0, 180.0)
+        );
+        assert_eq!(
+            rect_from_degrees(89.0, 145.0, 90.0, 146.0).polar_closure(),
+            rect_from_degrees(89.0, -180.0, 90.0, 180.0)
+        );
+        assert_eq!(
+            rect_from_degrees(-90.0, -145.0, 90.0, -144.0).polar_closure(),
+            S2LatLngRect::full()
+        );
+    }
+
+    fn check_cell_ops(r: &S2LatLngRect, cell: &S2Cell, level: i32) {
+        // Test the relationship between the given rectangle and cell:
+        // 0 == no intersection, 1 == may_intersect, 2 == intersects,
+        // 3 == vertex containment, 4 == contains
+        let mut vertex_contained = false;
+        for i in 0..4 {
+            if r.contains_point(&cell.get_vertex_raw(i))
+                || (!r.is_empty() && cell.contains_point(&r.get_vertex(i).to_point()))
+            {
+                vertex_contained = true;
+            }
+        }
+        assert_eq!(r.may_intersect_cell(cell), level >= 1, "{:?} {:?}", r, cell);
+        assert_eq!(r.intersects_cell(cell), level >= 2, "{:?} {:?}", r, cell);
+        assert_eq!(vertex_contained, level >= 3, "{:?} {:?}", r, cell);
+        assert_eq!(r.contains_cell(cell), level >= 4, "{:?} {:?}", r, cell);
+    }
+
+    #[test]
+    fn test_cell_ops() {
+        // Special cases.
+        check_cell_ops(
+            &S2LatLngRect::empty(),
+            &S2Cell::from_face_pos_level(3, 0, 0),
+            0,
+        );
+        check_cell_ops(
+            &S2LatLngRect::full(),
+            &S2Cell::from_face_pos_level(2, 0, 0),
+            4,
+        );
+        check_cell_ops(
+            &S2LatLngRect::full(),
+            &S2Cell::from_face_pos_level(5, 0, 25),
+            4,
+        );
+
+        // This rectangle includes the first quadrant of face 0. It's expanded
+        // slightly because cell bounding rectangles are slightly conservative.
+        let r4 = rect_from_degrees(-45.1, -45.1, 0.1, 0.1);
+        check_cell_ops(&r4, &S2Cell::from_face_pos_level(0, 0, 0), 3);
+        check_cell_ops(&r4, &S2Cell::from_face_pos_level(0, 0, 1), 4);
+        check_cell_ops(&r4, &S2Cell::from_face_pos_level(1, 0, 1), 0);
+
+        // This rectangle intersects the first quadrant of face 0.
+        let r5 = rect_from_degrees(-10.0, -45.0, 10.0, 0.0);
+        check_cell_ops(&r5, &S2Cell::from_face_pos_level(0, 0, 0), 3);
+        check_cell_ops(&r5, &S2Cell::from_face_pos_level(0, 0, 1), 3);
+        check_cell_ops(&r5, &S2Cell::from_face_pos_level(1, 0, 1), 0);
+
+        // Rectangle consisting of a single point.
+        check_cell_ops(
+            &rect_from_degrees(4.0, 4.0, 4.0, 4.0),
+            &S2Cell::from_face(0),
+            3,
+        );
+
+        // Rectangles that intersect the bounding rectangle of a face but not
+        // the face itself.
+        check_cell_ops(
+            &rect_from_degrees(41.0, -87.0, 42.0, -79.0),
+            &S2Cell::from_face(2),
+            1,
+        );
+        check_cell_ops(
+            &rect_from_degrees(-41.0, 160.0, -40.0, -160.0),
+            &S2Cell::from_face(5),
+            1,
+        );
+
+        // This is the leaf cell at the top right hand corner of face 0. It has
+        // two angles of 60 degrees and two of 120 degrees.
+        let cell0tr = S2Cell::new(S2CellId::from_point(&S2Point::new(1.0 + 1e-12, 1.0, 1.0)));
+        let v0 = S2LatLng::from_point(&cell0tr.get_vertex_raw(0));
+        check_cell_ops(
+            &rect_from_degrees(
+                v0.lat().degrees() - 1e-8,
+                v0.lng().degrees() - 1e-8,
+                v0.lat().degrees() - 2e-10,
+                v0.lng().degrees() + 1e-10,
+            ),
+            &cell0tr,
+            1,
+        );
+
+        // Rectangles that intersect a face but where no vertex of one region
+        // is contained by the other region. The first one passes through a
+        // corner of one of the face cells.
+        check_cell_ops(
+            &rect_from_degrees(-37.0, -70.0, -36.0, -20.0),
+            &S2Cell::from_face(5),
+            2,
+        );
+
+        // These two intersect like a diamond and a square.
+        let cell202 = S2Cell::from_face_pos_level(2, 0, 2);
+        let bound202 = cell202.get_rect_bound();
+        check_cell_ops(
+            &rect_from_degrees(
+                bound202.lo().lat().degrees() + 3.0,
+                bound202.lo().lng().degrees() + 3.0,
+                bound202.hi().lat().degrees() - 3.0,
+                bound202.hi().lng().degrees() - 3.0,
+            ),
+            &cell202,
+            2,
+        );
+    }
+
+    #[test]
+    fn test_cell_rect_bound_contains_cell() {
+        // The bound of every cell must contain its vertices and center, and
+        // must be contained by the bound of its parent.
+        let mut id = S2CellId::from_face(0).child_begin_at_level(3);
+        let end = S2CellId::from_face(5).child_end_at_level(3);
+        while id != end {
+            let cell = S2Cell::new(id);
+            let bound = cell.get_rect_bound();
+            assert!(bound.contains_point(&cell.get_center()));
+            for k in 0..4 {
+                assert!(bound.contains_point(&cell.get_vertex(k)));
+            }
+            assert!(S2Cell::new(id.parent()).get_rect_bound().contains(&bound));
+            assert!(bound.contains_cell(&cell) && bound.intersects_cell(&cell));
+            id = id.next();
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            S2LatLngRect::full().to_string(),
+            "[-90.000000:90.000000, -180.000000:180.000000] deg"
+        );
+        assert_eq!(
+            S2LatLngRect::empty().to_string(),
+            "[57.295780:0.000000, 180.000000:-180.000000] deg"
+        );
+        let p = S2LatLngRect::from_point(&S2LatLng::from_degrees(1.5, -2.25));
+        assert_eq!(
+            p.to_string(),
+            "[1.500000:1.500000, -2.250000:-2.250000] deg"
+        );
+    }
+
+    #[test]
+    fn test_area() {
+        assert_eq!(S2LatLngRect::empty().area(), 0.0);
+        assert_eq!(S2LatLngRect::full().area(), 4.0 * PI);
+        assert_eq!(rect_from_degrees(0.0, 0.0, 90.0, 90.0).area(), FRAC_PI_2);
+        assert_eq!(
+            S2LatLngRect::from_point(&S2LatLng::from_degrees(1.0, 2.0)).area(),
+            0.0
+        );
+
+        // A band around the equator spanning all longitudes has area
+        // 2 * PI * (sin(lat_hi) - sin(lat_lo)).
+        let band =
+            S2LatLngRect::from_lat_lng(R1Interval::new(-PI / 6.0, PI / 6.0), S1Interval::full());
+        assert!((band.area() - 2.0 * PI).abs() <= 1e-14);
+        let band = rect_from_degrees(-10.0, -180.0, 20.0, 180.0);
+        let expected = 2.0 * PI * (20f64.to_radians().sin() + 10f64.to_radians().sin());
+        assert!((band.area() - expected).abs() <= 1e-14);
+
+        // Splitting a rectangle at a meridian preserves the total area and
+        // centroid, including across the 180 degree meridian.
+        for (lat_lo, lng_lo, lat_hi, lng_hi, split) in [
+            (-30.0, -20.0, 45.0, 60.0, 10.0),
+            (10.0, 150.0, 80.0, -170.0, 180.0),
+            (-90.0, -100.0, -60.0, 100.0, -1e-3),
+        ] {
+            let rect = rect_from_degrees(lat_lo, lng_lo, lat_hi, lng_hi);
+            let west = rect_from_degrees(lat_lo, lng_lo, lat_hi, split);
+            let east = rect_from_degrees(lat_lo, split, lat_hi, lng_hi);
+            assert!((west.area() + east.area() - rect.area()).abs() <= 1e-15);
+            let centroid = west.get_centroid() + east.get_centroid();
+            assert!((centroid - rect.get_centroid()).norm() <= 1e-15);
+        }
+    }
+
+    #[test]
+    fn test_get_perimeter() {
+        assert_eq!(S2LatLngRect::empty().get_perimeter(), S1Angle::zero());
+        assert!(S2LatLngRect::full().get_perimeter().radians().abs() <= 1e-15);
+        // A quarter of the northern hemisphere: two meridians from the equator
+        // to the pole plus a quarter of the equator.
+        let rect = rect_from_degrees(0.0, 0.0, 90.0, 90.0);
+        assert!((rect.get_perimeter().radians() - 1.5 * PI).abs() <= 1e-15);
+        // A band spanning all longitudes is bounded by two latitude circles.
+        let rect = rect_from_degrees(-30.0, -180.0, 60.0, 180.0);
+        let expected = 2.0 * PI * (30f64.to_radians().cos() + 60f64.to_radians().cos());
+        assert!((rect.get_perimeter().radians() - expected).abs() <= 1e-14);
+    }
+
+    #[test]
+    fn test_get_centroid() {
+        assert_eq!(S2LatLngRect::empty().get_centroid(), S2Point::zero());
+        assert!(S2LatLngRect::full().get_centroid().norm() <= 1e-15);
+
+        // A polar cap is also a rectangle spanning all longitudes.
+        let rect = rect_from_degrees(60.0, -180.0, 90.0, 180.0);
+        let cap =
+            S2Cap::from_center_height(S2Point::new(0.0, 0.0, 1.0), 1.0 - 60f64.to_radians().sin());
+        assert!((rect.area() - cap.get_area()).abs() <= 1e-15);
+        assert!((rect.get_centroid() - cap.get_centroid()).norm() <= 1e-15);
+
+        // The centroid of a rectangle symmetric about the equator lies in the
+        // equatorial plane, along the central meridian.
+        let rect = rect_from_degrees(-20.0, 10.0, 20.0, 50.0);
+        let centroid = rect.get_centroid();
+        assert!(centroid.z().abs() <= 1e-15);
+        let center = S2LatLng::from_point(&centroid);
+        assert!((center.lng().degrees() - 30.0).abs() <= 1e-13);
+        // The centroid lies inside the sphere, at a distance less than 1.
+        assert!(centroid.norm() < rect.area());
+    }
+
+    // Returns the minimum distance from "x" to the line of latitude "lat"
+    // restricted to the longitude interval "lng".
+    fn distance_to_lat_edge(x: &S2LatLng, lat: f64, lng: &S1Interval) -> f64 {
+        if lng.contains(x.lng().radians()) {
+            return (x.lat().radians() - lat).abs();
+        }
+        // Otherwise the closest point is one of the endpoints.
+        let lo = x.get_distance(&S2LatLng::from_radians(lat, lng.lo()));
+        let hi = x.get_distance(&S2LatLng::from_radians(lat, lng.hi()));
+        lo.radians().min(hi.radians())
+    }
+
+    fn brute_force_distance(a: &S2LatLngRect, p: &S2LatLng) -> f64 {
+        if a.contains_latlng(p) {
+            return 0.0;
+        }
+        let lng_edge = |lng: f64| {
+            get_distance(
+                &p.to_point(),
+                &S2LatLng::from_radians(a.lat.lo(), lng).to_point(),
+                &S2LatLng::from_radians(a.lat.hi(), lng).to_point(),
+            )
+            .radians()
+        };
+        distance_to_lat_edge(p, a.lat.lo(), &a.lng)
+            .min(distance_to_lat_edge(p, a.lat.hi(), &a.lng))
+            .min(lng_edge(a.lng.lo()))
+            .min(lng_edge(a.lng.hi()))
+    }
+
+    #[test]
+    fn test_get_distance() {
+        let rect = rect_from_degrees(10.0, 20.0, 30.0, 40.0);
+        let dist = |lat, lng| {
+            rect.get_distance(&S2LatLng::from_degrees(lat, lng))
+                .degrees()
+        };
+
+        // Points inside the rectangle (including its boundary).
+        assert_eq!(dist(20.0, 30.0), 0.0);
+        assert_eq!(dist(10.0, 20.0), 0.0);
+        assert_eq!(dist(30.0, 35.0), 0.0);
+
+        // Points directly north or south of the rectangle.
+        assert!((dist(50.0, 30.0) - 20.0).abs() <= 1e-13);
+        assert!((dist(90.0, 0.0) - 60.0).abs() <= 1e-13);
+        assert!((dist(-5.0, 25.0) - 15.0).abs() <= 1e-13);
+
+        // Points east or west of the rectangle are closest to a longitude
+        // edge. At the latitude of a corner the distance is less than the
+        // longitude difference, since lines of latitude are not geodesics.
+        let east = dist(20.0, 50.0);
+        assert!(east > 0.0 && east < 10.0);
+        assert!((east - 10.0 * 20f64.to_radians().cos()).abs() <= 0.1);
+
+        // A rectangle that crosses the date line.
+        let rect = rect_from_degrees(-10.0, 170.0, 10.0, -170.0);
+        let dist = |lat, lng| {
+            rect.get_distance(&S2LatLng::from_degrees(lat, lng))
+                .degrees()
+        };
+        assert_eq!(dist(0.0, 180.0), 0.0);
+        assert_eq!(dist(5.0, -175.0), 0.0);
+        assert!((dist(20.0, 175.0) - 10.0).abs() <= 1e-13);
+        assert!((dist(0.0, -160.0) - 10.0).abs() <= 1e-13);
+        assert!((dist(0.0, 160.0) - 10.0).abs() <= 1e-13);
+        // For a point on the far side of the sphere, the closest points are
+        // the corners.
+        let corner = S2LatLng::from_degrees(10.0, 170.0);
+        let expected = S2LatLng::from_degrees(0.0, 0.0)
+            .get_distance(&corner)
+            .degrees();
+        assert!(expected < 170.0);
+        assert!((dist(0.0, 0.0) - expected).abs() <= 1e-12);
+    }
+
+    #[test]
+    fn test_project() {
+        let rect = rect_from_degrees(0.0, 170.0, 10.0, -170.0);
+        let project = |lat, lng| rect.project(&S2LatLng::from_degrees(lat, lng));
+        // Points inside the rectangle are unchanged.
+        assert_eq!(project(5.0, 175.0), S2LatLng::from_degrees(5.0, 175.0));
+        assert_eq!(project(0.0, -170.0), S2LatLng::from_degrees(0.0, -170.0));
+        // Latitudes are clamped, and longitudes snap to the nearer edge
+        // across the 180 degree meridian.
+        assert_eq!(project(-20.0, 180.0), S2LatLng::from_degrees(0.0, 180.0));
+        assert_eq!(project(50.0, 160.0), S2LatLng::from_degrees(10.0, 170.0));
+        assert_eq!(project(5.0, -150.0), S2LatLng::from_degrees(5.0, -170.0));
+        assert_eq!(project(5.0, 10.0), S2LatLng::from_degrees(5.0, 170.0));
+        assert_eq!(project(5.0, -10.0), S2LatLng::from_degrees(5.0, -170.0));
+
+        let full = S2LatLngRect::full();
+        let p = S2LatLng::from_degrees(-45.0, 120.0);
+        assert_eq!(full.project(&p), p);
+        let point = S2LatLngRect::from_point(&p);
+        assert_eq!(point.project(&S2LatLng::from_degrees(10.0, -20.0)), p);
+    }
+
+    #[test]
+    fn test_project_random() {
+        let mut rng = StdRng::seed_from_u64(73);
+        for _ in 0..1000 {
+            let rect = S2LatLngRect::from_point_pair(
+                &S2LatLng::from_point(&random_point(&mut rng)),
+                &S2LatLng::from_point(&random_point(&mut rng)),
+            );
+            let p = S2LatLng::from_point(&random_point(&mut rng));
+            let q = rect.project(&p);
+            assert!(rect.contains_latlng(&q));
+            assert_eq!(rect.project(&q), q);
+            if rect.contains_latlng(&p) {
+                assert_eq!(q, p);
+            }
+            if rect.lng().contains(p.lng().radians()) {
+                let d = p.get_distance(&q).radians();
+                assert!((d - rect.get_distance(&p).radians()).abs() < 1e-14);
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_distance_random() {
+        let mut rng = StdRng::seed_from_u64(66);
+        for _ in 0..1000 {
+            let rect = S2LatLngRect::from_point_pair(
+                &S2LatLng::from_point(&random_point(&mut rng)),
+                &S2LatLng::from_point(&random_point(&mut rng)),
+            );
+            for _ in 0..10 {
+                let p = S2LatLng::from_point(&random_point(&mut rng));
+                let expected = brute_force_distance(&rect, &p);
+                let actual = rect.get_distance(&p).radians();
+                assert!((actual - expected).abs() <= 1e-10, "{rect:?} {p:?}");
+            }
+        }
+    }
+}
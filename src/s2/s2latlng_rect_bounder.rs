@@ -0,0 +1,352 @@
+// Copyright 2005 Google Inc. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS-IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+// Original Author: ericv@google.com (Eric Veach)
+
+use core::f64::consts::{FRAC_PI_2, PI};
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+use crate::{
+    r1::R1Interval,
+    s1::{S1Angle, S1Interval},
+    s2::{S2LatLng, S2LatLngRect, S2Point},
+};
+
+/// This class computes a bounding rectangle that contains all edges defined
+/// by a vertex chain v0, v1, v2, ... All vertices must be unit length.
+/// Note that the bounding rectangle of an edge can be larger than the
+/// bounding rectangle of its endpoints, e.g. consider an edge that passes
+/// through the north pole.
+///
+/// The bounds are calculated conservatively to account for numerical errors
+/// when S2Points are converted to S2LatLngs. More precisely, this function
+/// guarantees the following: let L be a closed edge chain (loop) such that
+/// the interior of the loop does not contain either pole. Now if P is any
+/// point such that L.contains(P), then rect_bound(L).contains(S2LatLng(P)).
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::s2latlng_rect_bounder::S2LatLngRectBounder;
+/// use s2shell::s2::{S2LatLng, S2Point};
+///
+/// // An edge between two points at latitude 45 degrees passes much closer
+/// // to the north pole than either endpoint.
+/// let mut bounder = S2LatLngRectBounder::new();
+/// bounder.add_point(&S2LatLng::from_degrees(45.0, -80.0).to_point());
+/// bounder.add_point(&S2LatLng::from_degrees(45.0, 80.0).to_point());
+/// assert!(bounder.get_bound().lat_hi().degrees() > 80.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct S2LatLngRectBounder {
+    // The previous vertex in the chain.
+    a: S2Point,
+    // The corresponding latitude-longitude.
+    a_latlng: S2LatLng,
+    // The current bounding rectangle.
+    bound: S2LatLngRect,
+}
+
+impl S2LatLngRectBounder {
+    pub fn new() -> S2LatLngRectBounder {
+        S2LatLngRectBounder {
+            a: S2Point::zero(),
+            a_latlng: S2LatLng::default(),
+            bound: S2LatLngRect::empty(),
+        }
+    }
+
+    /// This method is called to add a vertex to the chain when the vertex is
+    /// represented as an S2Point. Requires that 'b' has unit length.
+    /// Repeated vertices are ignored.
+    pub fn add_point(&mut self, b: &S2Point) {
+        debug_assert!(b.is_unit_length());
+        self.add_internal(b, &S2LatLng::from_point(b));
+    }
+
+    /// This method is called to add a vertex to the chain when the vertex is
+    /// represented as an S2LatLng. Repeated vertices are ignored.
+    pub fn add_latlng(&mut self, b_latlng: &S2LatLng) {
+        self.add_internal(&b_latlng.to_point(), b_latlng);
+    }
+
+    fn add_internal(&mut self, b: &S2Point, b_latlng: &S2LatLng) {
+        debug_assert!(self.bound.is_valid());
+        if self.bound.is_empty() {
+            self.bound = S2LatLngRect::from_point(b_latlng);
+        } else {
+            // First compute the cross product N = A x B robustly. This is the
+            // normal to the great circle through A and B. We don't use
+            // robust_cross_prod() since that method returns an arbitrary vector
+            // orthogonal to A if the two vectors are proportional, and we want
+            // the zero vector in that case.
+            let a = self.a;
+            let n = (a - *b).cross_prod(&(a + *b)); // N = 2 * (A x B)
+
+            // The relative error in N gets large as its norm gets very small
+            // (i.e., when the two points are nearly identical or antipodal).
+            // We handle this by choosing a maximum allowable error, and if the
+            // error is greater than this we fall back to a different technique.
+            // Since it turns out that the other sources of error in converting
+            // the normal to a maximum latitude add up to at most 1.16 *
+            // DBL_EPSILON, and it is desirable to have the total error be a
+            // multiple of DBL_EPSILON, we have chosen to limit the maximum error
+            // in the normal to be 3.84 * DBL_EPSILON. It is possible to show
+            // that the error is less than this when
+            //
+            //   n.norm() >= 8 * sqrt(3) / (3.84 - 0.5 - sqrt(3)) * DBL_EPSILON
+            //            = 1.91346e-15 (about 8.618 * DBL_EPSILON)
+            let n_norm = n.norm();
+            if n_norm < 1.91346e-15 {
+                // A and B are either nearly identical or nearly antipodal (to
+                // within 4.309 * DBL_EPSILON, or about 6 nanometers on the
+                // earth's surface).
+                if a.dot_prod(b) < 0.0 {
+                    // The two points are nearly antipodal. The easiest solution
+                    // is to assume that the edge between A and B could go in any
+                    // direction around the sphere.
+                    self.bound = S2LatLngRect::full();
+                } else {
+                    // The two points are nearly identical (to within 4.309 *
+                    // DBL_EPSILON). In this case we can just use the bounding
+                    // rectangle of the points, since after the expansion done by
+                    // get_bound() this rectangle is guaranteed to include the
+                    // (lat,lng) values of all points along AB.
+                    self.bound = self
+                        .bound
+                        .union(&S2LatLngRect::from_point_pair(&self.a_latlng, b_latlng));
+                }
+            } else {
+                // Compute the longitude range spanned by AB.
+                let mut lng_ab = S1Interval::from_point_pair(
+                    self.a_latlng.lng().radians(),
+                    b_latlng.lng().radians(),
+                );
+                if lng_ab.get_length() >= PI - 2.0 * f64::EPSILON {
+                    // The points lie on nearly opposite lines of longitude to
+                    // within the maximum error of the calculation. (Note that
+                    // this test relies on the fact that PI - 2 * DBL_EPSILON
+                    // is exactly representable.) It is safest to assume that
+                    // AB could go on either side of the pole.
+                    lng_ab = S1Interval::full();
+                }
+
+                // Next we compute the latitude range spanned by the edge AB.
+                // We start with the range spanning the two endpoints of the
+                // edge:
+                let mut lat_ab = R1Interval::from_point_pair(
+                    self.a_latlng.lat().radians(),
+                    b_latlng.lat().radians(),
+                );
+
+                // This is the desired range unless the edge AB crosses the
+                // plane through N and the Z-axis (which is where the great
+                // circle through A and B attains its minimum and maximum
+                // latitudes). To test whether AB crosses this plane, we compute
+                // a vector M perpendicular to this plane and then project A and B
+                // onto it.
+                let m = n.cross_prod(&S2Point::new(0.0, 0.0, 1.0));
+                let m_a = m.dot_prod(&a);
+                let m_b = m.dot_prod(b);
+
+                // We want to test the signs of "m_a" and "m_b", so we need to
+                // bound the error in these calculations. It is possible to show
+                // that the total error is bounded by
+                //
+                //  (1 + sqrt(3)) * DBL_EPSILON * n_norm + 8 * sqrt(3) *
+                //  (DBL_EPSILON**2) = 6.06638e-16 * n_norm + 6.83174e-31
+                let m_error = 6.06638e-16 * n_norm + 6.83174e-31;
+                if m_a * m_b < 0.0 || m_a.abs() <= m_error || m_b.abs() <= m_error {
+                    // Minimum/maximum latitude *may* occur in the edge interior.
+                    //
+                    // The maximum latitude is 90 degrees minus the latitude of N.
+                    // We compute this directly using atan2 in order to get
+                    // maximum accuracy near the poles.
+                    //
+                    // Our goal is compute a bound that contains the computed
+                    // latitudes of all S2Points P that pass the point-in-polygon
+                    // containment test. There are three sources of error we need
+                    // to consider:
+                    //  - the directional error in N (at most 3.84 * DBL_EPSILON)
+                    //  - converting N to a maximum latitude
+                    //  - computing the latitude of the test point P
+                    // The latter two sources of error are at most 0.955 *
+                    // DBL_EPSILON individually, but it is possible to show by a
+                    // more complex analysis that together they can add up to at
+                    // most 1.16 * DBL_EPSILON, for a total error of 5 *
+                    // DBL_EPSILON.
+                    //
+                    // We add 3 * DBL_EPSILON to the bound here, and get_bound()
+                    // will pad the bound by another 2 * DBL_EPSILON.
+                    let max_lat = ((n.x() * n.x() + n.y() * n.y()).sqrt().atan2(n.z().abs())
+                        + 3.0 * f64::EPSILON)
+                        .min(FRAC_PI_2);
+
+                    // In order to get tight bounds when the two points are close
+                    // together, we also bound the min/max latitude relative to the
+                    // latitudes of the endpoints A and B. First we compute the
+                    // distance between A and B, and then we compute the maximum
+                    // change in latitude between any two points along the great
+                    // circle that are separated by this distance. This gives us a
+                    // latitude change "budget". Some of this budget must be spent
+                    // getting from A to B; the remainder bounds the round-trip
+                    // distance (in latitude) from A or B to the min or max
+                    // latitude attained along the edge AB.
+                    let lat_budget = 2.0 * (0.5 * (a - *b).norm() * max_lat.sin()).asin();
+                    let max_delta = 0.5 * (lat_budget - lat_ab.get_length()) + f64::EPSILON;
+
+                    // Test whether AB passes through the point of maximum latitude
+                    // or minimum latitude. If the dot product(s) are small enough
+                    // then the result may be ambiguous.
+                    if m_a <= m_error && m_b >= -m_error {
+                        lat_ab = R1Interval::new(lat_ab.lo(), max_lat.min(lat_ab.hi() + max_delta));
+                    }
+                    if m_b <= m_error && m_a >= -m_error {
+                        lat_ab =
+                            R1Interval::new((-max_lat).max(lat_ab.lo() - max_delta), lat_ab.hi());
+                    }
+                }
+                self.bound = self
+                    .bound
+                    .union(&S2LatLngRect::from_lat_lng(lat_ab, lng_ab));
+            }
+        }
+        self.a = *b;
+        self.a_latlng = *b_latlng;
+    }
+
+    /// Returns the bounding rectangle of the edge chain that connects the
+    /// vertices defined so far. This bound satisfies the guarantee made
+    /// above, i.e. if the edge chain defines a loop, then the bound contains
+    /// the S2LatLng coordinates of all S2Points contained by the loop.
+    pub fn get_bound(&self) -> S2LatLngRect {
+        // To save time, we ignore numerical errors in the computed S2LatLngs
+        // while accumulating the bounds and then account for them here.
+        //
+        // S2LatLng(S2Point) has a maximum error of 0.955 * DBL_EPSILON in
+        // latitude. In the worst case, we might have rounded "inwards" when
+        // computing the bound and "outwards" when computing the latitude of a
+        // contained point P, therefore we expand the latitude bounds by 2 *
+        // DBL_EPSILON in each direction. (A more complex analysis shows that
+        // 1.5 * DBL_EPSILON is enough, but the expansion amount should be a
+        // multiple of DBL_EPSILON in order to avoid rounding errors during
+        // the expansion itself.)
+        //
+        // S2LatLng(S2Point) has a maximum error of DBL_EPSILON in longitude,
+        // which is simply the maximum rounding error for results in the range
+        // [-Pi, Pi]. This is true because the Gnu implementation of atan2()
+        // comes from the IBM Accurate Mathematical Library, which implements
+        // correct rounding for this intrinsic (i.e., it returns the infinite
+        // precision result rounded to the nearest representable value, with
+        // ties rounded to even values). This implies that we don't need to
+        // expand the longitude bounds at all, since we only guarantee that
+        // the bound contains the *rounded* latitudes of contained points.
+        // The *true* latitudes of contained points may lie up to DBL_EPSILON
+        // outside of the returned bound.
+        let expansion = S2LatLng::new(
+            S1Angle::from_radians(2.0 * f64::EPSILON),
+            S1Angle::from_radians(0.0),
+        );
+        self.bound.expanded(&expansion).polar_closure()
+    }
+}
+
+impl Default for S2LatLngRectBounder {
+    fn default() -> Self {
+        S2LatLngRectBounder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::s2::interpolate;
+    use crate::s2::s2testing::random_point;
+
+    fn get_edge_bound(a: &S2Point, b: &S2Point) -> S2LatLngRect {
+        let mut bounder = S2LatLngRectBounder::new();
+        bounder.add_point(a);
+        bounder.add_point(b);
+        bounder.get_bound()
+    }
+
+    #[test]
+    fn test_max_latitude_simple() {
+        // Check cases where the min/max latitude is attained at a vertex.
+        let cube_lat = (1.0_f64 / 3.0).sqrt().asin(); // 35.26 degrees
+        let cube_lat_rect = S2LatLngRect::from_lat_lng(
+            R1Interval::new(-cube_lat, cube_lat),
+            S1Interval::new(-PI / 4.0, PI / 4.0),
+        );
+        let bound = get_edge_bound(
+            &S2Point::new(1.0, 1.0, 1.0).normalize(),
+            &S2Point::new(1.0, -1.0, -1.0).normalize(),
+        );
+        assert!(bound.contains(&cube_lat_rect));
+        assert!(cube_lat_rect
+            .expanded(&S2LatLng::from_radians(1e-14, 1e-14))
+            .contains(&bound));
+
+        // Check cases where the min/max latitude occurs in the edge interior.
+        // The maximum latitude of the great circle through these two points is
+        // attained at (1, 0, 0.1), which lies in the edge interior.
+        let bound = get_edge_bound(
+            &S2Point::new(1.0, 0.9, 0.1).normalize(),
+            &S2Point::new(1.0, -0.9, 0.1).normalize(),
+        );
+        let max_lat = S2LatLng::from_point(&S2Point::new(1.0, 0.0, 0.1).normalize()).lat();
+        assert!(bound.lat_hi().radians() >= max_lat.radians());
+        assert!(bound.lat_hi().radians() <= max_lat.radians() + 6.0 * f64::EPSILON);
+        assert!(bound.lat_lo().radians() < max_lat.radians());
+    }
+
+    #[test]
+    fn test_max_latitude_random() {
+        // Check that the bound of a random edge contains the latitude and
+        // longitude of points sampled along the edge, including the interior
+        // points where the maximum and minimum latitudes are attained.
+        let mut rng = StdRng::seed_from_u64(41);
+        for _ in 0..1000 {
+            let a = random_point(&mut rng);
+            let b = random_point(&mut rng);
+            let bound = get_edge_bound(&a, &b);
+            // Every point along the edge must be contained by the bound.
+            for k in 0..=10 {
+                let t = k as f64 / 10.0;
+                let p = interpolate(t, &a, &b);
+                assert!(
+                    bound.contains_latlng(&S2LatLng::from_point(&p)),
+                    "{a:?} {b:?} {t}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_nearly_identical_or_antipodal_points() {
+        let a = S2Point::new(1.0, 0.0, 0.0);
+        let b = S2Point::new(1.0, 1e-17, 0.0).normalize();
+        let bound = get_edge_bound(&a, &b);
+        assert!(bound.contains_latlng(&S2LatLng::from_point(&a)));
+        assert!(bound.lat().get_length() <= 4.0 * f64::EPSILON + 1e-16);
+
+        let bound = get_edge_bound(&a, &S2Point::new(-1.0, 1e-17, 0.0));
+        assert!(bound.is_full());
+    }
+}
@@ -0,0 +1,616 @@
+// Copyright 2005 Google Inc. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS-IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+// Original Author: ericv@google.com (Eric Veach)
+
+use alloc::{vec, vec::Vec};
+use core::f64::consts::{FRAC_PI_2, PI};
+
+use crate::{
+    r1::R1Interval,
+    s1::S1Interval,
+    s2::{
+        s2centroids::true_centroid,
+        s2contains_point::{angle_contains_vertex, contains_point},
+        s2edge_crossings::crossing_sign,
+        s2latlng_rect_bounder::S2LatLngRectBounder,
+        s2measures::turn_angle,
+        S2Cap, S2Cell, S2CellId, S2LatLngRect, S2Point, S2Region,
+    },
+};
+
+/// An S2Loop represents a simple spherical polygon. It consists of a single
+/// chain of vertices where the first vertex is implicitly connected to the
+/// last. All loops are defined to have a CCW orientation, i.e. the interior
+/// of the loop is on the left side of the edges. This implies that a
+/// clockwise loop enclosing a small area is interpreted to be a CCW loop
+/// enclosing a very large area.
+///
+/// Loops are not allowed to have any duplicate vertices (whether adjacent or
+/// not). Non-adjacent edges are not allowed to intersect, and furthermore
+/// edges of length 180 degrees are not allowed (i.e., adjacent vertices
+/// cannot be antipodal). Loops must have at least 3 vertices (except for the
+/// empty and full loops discussed below).
+///
+/// There are two special loops: the "empty loop" contains no points, while
+/// the "full loop" contains all points. These loops do not have any edges,
+/// but to preserve the invariant that every loop can be represented as a
+/// vertex chain, they are defined as having exactly one vertex each (see
+/// [`S2Loop::empty`] and [`S2Loop::full`]).
+///
+/// Point containment follows the "semi-open" boundary model, so if a set of
+/// loops partitions the sphere then every point is contained by exactly one
+/// of them (see [`contains_point`]).
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{S2Cell, S2CellId, S2LatLng, S2Loop, S2Region};
+///
+/// // A loop around a 20x20 degree region centered on (0, 0).
+/// let vertices = [(-10.0, -10.0), (-10.0, 10.0), (10.0, 10.0), (10.0, -10.0)]
+///     .iter()
+///     .map(|&(lat, lng)| S2LatLng::from_degrees(lat, lng).to_point())
+///     .collect();
+/// let l = S2Loop::new(vertices);
+/// let center = S2LatLng::from_degrees(0.0, 0.0).to_point();
+/// assert!(l.contains_point(&center));
+///
+/// let cell = S2Cell::new(S2CellId::from_point(&center).parent_at_level(10));
+/// assert!(l.contains_cell(&cell));
+/// assert!(l.intersects_cell(&cell));
+/// assert!(!l.contains_cell(&S2Cell::from_face(0)));
+/// assert!(l.intersects_cell(&S2Cell::from_face(0)));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct S2Loop {
+    vertices: Vec<S2Point>,
+    // "origin_inside" is true if the loop contains S2::Origin().
+    origin_inside: bool,
+    // A conservative bounding rectangle of the loop.
+    bound: S2LatLngRect,
+}
+
+impl S2Loop {
+    /// Constructs a loop with the given vertices. The last vertex is
+    /// implicitly connected to the first. The vertices must be unit length
+    /// and satisfy the requirements listed in the type documentation.
+    pub fn new(vertices: Vec<S2Point>) -> S2Loop {
+        debug_assert!(!vertices.is_empty());
+        debug_assert!(vertices.iter().all(|v| v.is_unit_length()));
+        let mut l = S2Loop {
+            vertices,
+            origin_inside: false,
+            bound: S2LatLngRect::full(),
+        };
+        l.init_origin_and_bound();
+        l
+    }
+
+    /// Returns the empty loop, which contains no points.
+    pub fn empty() -> S2Loop {
+        S2Loop::new(vec![S2Point::new(0.0, 0.0, 1.0)])
+    }
+
+    /// Returns the full loop, which contains all points.
+    pub fn full() -> S2Loop {
+        S2Loop::new(vec![S2Point::new(0.0, 0.0, -1.0)])
+    }
+
+    pub fn num_vertices(&self) -> usize {
+        self.vertices.len()
+    }
+
+    /// For convenience, we make two entire copies of the vertex list
+    /// available: vertex(n..2*n-1) is mapped to vertex(0..n-1).
+    pub fn vertex(&self, i: usize) -> &S2Point {
+        debug_assert!(i < 2 * self.vertices.len());
+        &self.vertices[i % self.vertices.len()]
+    }
+
+    pub fn vertices(&self) -> &[S2Point] {
+        &self.vertices
+    }
+
+    /// Returns true if this is the special empty loop that contains no points.
+    pub fn is_empty(&self) -> bool {
+        self.is_empty_or_full() && !self.origin_inside
+    }
+
+    /// Returns true if this is the special full loop that contains all points.
+    pub fn is_full(&self) -> bool {
+        self.is_empty_or_full() && self.origin_inside
+    }
+
+    /// Returns true if this loop is either empty or full.
+    pub fn is_empty_or_full(&self) -> bool {
+        self.vertices.len() == 1
+    }
+
+    /// Returns the area of the loop interior, i.e. the region on the left side
+    /// of the loop. The return value is between 0 and 4*Pi. (Note that the
+    /// return value is not affected by whether this loop is a "hole" or a
+    /// "shell".)
+    ///
+    /// The area is computed from the Gauss-Bonnet theorem: the area of the
+    /// loop interior is 2*Pi minus the sum of the turning angles at each
+    /// vertex (the spherical excess formula). This is correct for loops of
+    /// any size, including those that contain one or both poles. The
+    /// absolute error is about 1e-15 per vertex, so small loops have poor
+    /// *relative* accuracy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use core::f64::consts::{FRAC_PI_2, PI};
+    /// use s2shell::s2::{S2Loop, S2Point};
+    ///
+    /// // One octant of the sphere, and its complement.
+    /// let a = S2Point::new(1.0, 0.0, 0.0);
+    /// let b = S2Point::new(0.0, 1.0, 0.0);
+    /// let c = S2Point::new(0.0, 0.0, 1.0);
+    /// assert!((S2Loop::new(vec![a, b, c]).get_area() - FRAC_PI_2).abs() < 1e-15);
+    /// assert!((S2Loop::new(vec![c, b, a]).get_area() - 3.5 * PI).abs() < 1e-14);
+    /// ```
+    pub fn get_area(&self) -> f64 {
+        if self.is_empty_or_full() {
+            return if self.origin_inside { 4.0 * PI } else { 0.0 };
+        }
+        // Sum the turning angles using compensated (Kahan) summation so that
+        // the error does not grow with the number of vertices.
+        let n = self.num_vertices();
+        let mut sum = 0.0;
+        let mut compensation = 0.0;
+        for i in 0..n {
+            let angle = turn_angle(self.vertex(i + n - 1), self.vertex(i), self.vertex(i + 1))
+                - compensation;
+            let new_sum = sum + angle;
+            compensation = (new_sum - sum) - angle;
+            sum = new_sum;
+        }
+        (2.0 * PI - sum).clamp(0.0, 4.0 * PI)
+    }
+
+    /// Returns the true centroid of the loop, normalized to unit length. The
+    /// true centroid is the surface integral over the loop interior of (x,y,z)
+    /// divided by the loop area (see s2centroids), so unlike the average of
+    /// the vertices it does not depend on how the boundary is subdivided.
+    ///
+    /// The centroid is computed by summing the area-weighted true centroids of
+    /// the triangles obtained by fanning out from vertex 0. These triangles
+    /// have signed areas, so this works for loops of any shape and size,
+    /// including loops that contain a pole or more than a hemisphere.
+    ///
+    /// Returns the zero vector if the centroid is undefined, e.g. for the empty
+    /// and full loops (or any loop symmetric about the origin).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{S2LatLng, S2Loop};
+    ///
+    /// let vertices = [(0.0, 0.0), (0.0, 10.0), (10.0, 10.0), (10.0, 0.0)]
+    ///     .iter()
+    ///     .map(|&(lat, lng)| S2LatLng::from_degrees(lat, lng).to_point())
+    ///     .collect();
+    /// let centroid = S2LatLng::from_point(&S2Loop::new(vertices).get_centroid());
+    /// assert!((centroid.lat().degrees() - 5.0).abs() < 0.1);
+    /// assert!((centroid.lng().degrees() - 5.0).abs() < 1e-13);
+    /// ```
+    pub fn get_centroid(&self) -> S2Point {
+        if self.is_empty_or_full() {
+            return S2Point::zero();
+        }
+        let v0 = self.vertex(0);
+        (1..self.num_vertices() - 1)
+            .map(|i| true_centroid(v0, self.vertex(i), self.vertex(i + 1)))
+            .sum::<S2Point>()
+            .normalize()
+    }
+
+    /// Returns true if the loop contains the given point. Point containment
+    /// is defined such that if the sphere is subdivided into faces (loops),
+    /// every point is contained by exactly one face.
+    pub fn contains_point(&self, p: &S2Point) -> bool {
+        if !self.bound.contains_point(p) {
+            return false;
+        }
+        self.brute_force_contains(p)
+    }
+
+    /// Returns true if the loop contains the given cell. This method is
+    /// conservative: it may return false for a cell that touches the loop
+    /// boundary (e.g. a cell that shares a vertex with the loop).
+    ///
+    /// The cell is contained if its center is inside the loop, no loop edge
+    /// crosses or touches any cell edge, and no loop vertex lies in the cell.
+    pub fn contains_cell(&self, cell: &S2Cell) -> bool {
+        if self.is_empty_or_full() {
+            return self.origin_inside;
+        }
+        if !self.bound.intersects(&cell.get_rect_bound()) {
+            return false;
+        }
+        if self.vertices.iter().any(|v| cell.contains_point(v)) {
+            return false;
+        }
+        if self.boundary_crosses_cell(cell) {
+            return false;
+        }
+        self.brute_force_contains(&cell.get_center())
+    }
+
+    /// Returns true if the loop intersects the given cell. This method is
+    /// conservative: it may return true for a cell that only touches the loop
+    /// boundary.
+    ///
+    /// The cell intersects the loop if any loop vertex lies in the cell, any
+    /// loop edge crosses a cell edge, or (otherwise) the cell center is inside
+    /// the loop.
+    pub fn intersects_cell(&self, cell: &S2Cell) -> bool {
+        if self.is_empty_or_full() {
+            return self.origin_inside;
+        }
+        if !self.bound.intersects(&cell.get_rect_bound()) {
+            return false;
+        }
+        if self.vertices.iter().any(|v| cell.contains_point(v)) {
+            return true;
+        }
+        if self.boundary_crosses_cell(cell) {
+            return true;
+        }
+        self.brute_force_contains(&cell.get_center())
+    }
+
+    // Returns true if any loop edge crosses or shares a vertex with any cell
+    // edge.
+    fn boundary_crosses_cell(&self, cell: &S2Cell) -> bool {
+        let cell_vertices = [
+            cell.get_vertex(0),
+            cell.get_vertex(1),
+            cell.get_vertex(2),
+            cell.get_vertex(3),
+        ];
+        let n = self.num_vertices();
+        (0..n).any(|i| {
+            let (a, b) = (self.vertex(i), self.vertex(i + 1));
+            (0..4).any(|k| crossing_sign(a, b, &cell_vertices[k], &cell_vertices[(k + 1) & 3]) >= 0)
+        })
+    }
+
+    fn brute_force_contains(&self, p: &S2Point) -> bool {
+        // Empty and full loops don't need a special case, but invalid loops
+        // with zero vertices do, so we might as well handle them all at once.
+        if self.is_empty_or_full() {
+            return self.origin_inside;
+        }
+        contains_point(&self.vertices, p, self.origin_inside)
+    }
+
+    fn init_origin_and_bound(&mut self) {
+        if self.num_vertices() < 3 {
+            // Check for the special empty and full loops (which have one vertex).
+            debug_assert!(self.is_empty_or_full());
+            self.origin_inside = self.vertex(0).z() < 0.0;
+            self.bound = if self.origin_inside {
+                S2LatLngRect::full()
+            } else {
+                S2LatLngRect::empty()
+            };
+            return;
+        }
+
+        // The brute force point containment algorithm works by counting edge
+        // crossings starting at a fixed reference point (S2::Origin()) for
+        // which origin_inside is known. We first assume that the origin is
+        // outside the loop, and then test whether vertex(1) (which the loop
+        // contains if and only if angle_contains_vertex() says so) agrees.
+        self.origin_inside = false;
+        let v1_inside = self.vertex(1) != self.vertex(0)
+            && self.vertex(2) != self.vertex(1)
+            && angle_contains_vertex(self.vertex(0), self.vertex(1), self.vertex(2));
+        if v1_inside != self.brute_force_contains(self.vertex(1)) {
+            self.origin_inside = true;
+        }
+
+        // The bounding rectangle of a loop is not necessarily the same as the
+        // bounding rectangle of its vertices. First, the maximal latitude may
+        // be attained along the interior of an edge. Second, the loop may wrap
+        // entirely around the sphere (e.g. a loop that defines two revolutions
+        // of a candy-cane stripe). Third, the loop may include one or both
+        // poles. Note that a small clockwise loop near the equator contains
+        // both poles.
+        let mut bounder = S2LatLngRectBounder::new();
+        for i in 0..=self.num_vertices() {
+            bounder.add_point(self.vertex(i));
+        }
+        let mut b = bounder.get_bound();
+        if self.brute_force_contains(&S2Point::new(0.0, 0.0, 1.0)) {
+            b = S2LatLngRect::from_lat_lng(
+                R1Interval::new(b.lat().lo(), FRAC_PI_2),
+                S1Interval::full(),
+            );
+        }
+        // If a loop contains the south pole, then either it wraps entirely
+        // around the sphere (full longitude range), or it also contains the
+        // north pole in which case b.lng().is_full() due to the test above.
+        // Either way, we only need to do the south pole containment test if
+        // b.lng().is_full().
+        if b.lng().is_full() && self.brute_force_contains(&S2Point::new(0.0, 0.0, -1.0)) {
+            b = S2LatLngRect::from_lat_lng(R1Interval::new(-FRAC_PI_2, b.lat().hi()), *b.lng());
+        }
+        self.bound = b;
+    }
+}
+
+impl S2Region for S2Loop {
+    fn get_cap_bound(&self) -> S2Cap {
+        self.bound.get_cap_bound()
+    }
+
+    fn get_rect_bound(&self) -> S2LatLngRect {
+        self.bound
+    }
+
+    fn get_cell_union_bound(&self, cell_ids: &mut Vec<S2CellId>) {
+        self.get_cap_bound().get_cell_union_bound(cell_ids)
+    }
+
+    fn contains_cell(&self, cell: &S2Cell) -> bool {
+        S2Loop::contains_cell(self, cell)
+    }
+
+    fn may_intersect_cell(&self, cell: &S2Cell) -> bool {
+        self.intersects_cell(cell)
+    }
+
+    fn contains_point(&self, point: &S2Point) -> bool {
+        S2Loop::contains_point(self, point)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::boxed::Box;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::s2::s2testing::random_point;
+    use crate::s2::{area, sign, S2LatLng, S2RegionClone};
+
+    fn make_loop(latlngs: &[(f64, f64)]) -> S2Loop {
+        S2Loop::new(
+            latlngs
+                .iter()
+                .map(|&(lat, lng)| S2LatLng::from_degrees(lat, lng).to_point())
+                .collect(),
+        )
+    }
+
+    // A small square centered on (0, 0), and the same square traversed
+    // clockwise (i.e. its complement).
+    fn square() -> S2Loop {
+        make_loop(&[(-10.0, -10.0), (-10.0, 10.0), (10.0, 10.0), (10.0, -10.0)])
+    }
+
+    fn square_complement() -> S2Loop {
+        make_loop(&[(10.0, -10.0), (10.0, 10.0), (-10.0, 10.0), (-10.0, -10.0)])
+    }
+
+    #[test]
+    fn test_empty_and_full() {
+        let empty = S2Loop::empty();
+        let full = S2Loop::full();
+        assert!(empty.is_empty() && !empty.is_full());
+        assert!(full.is_full() && !full.is_empty());
+        assert!(empty.get_rect_bound().is_empty());
+        assert!(full.get_rect_bound().is_full());
+        let cell = S2Cell::from_face(3);
+        assert!(!empty.contains_cell(&cell) && !empty.intersects_cell(&cell));
+        assert!(full.contains_cell(&cell) && full.intersects_cell(&cell));
+        assert!(full.contains_point(&S2Point::new(0.0, 1.0, 0.0)));
+        assert!(!empty.contains_point(&S2Point::new(0.0, 1.0, 0.0)));
+    }
+
+    #[test]
+    fn test_origin_and_bound() {
+        let l = square();
+        assert!(l.contains_point(&S2Point::new(1.0, 0.0, 0.0)));
+        assert!(!l.contains_point(&S2Point::new(-1.0, 0.0, 0.0)));
+        let bound = l.get_rect_bound();
+        assert!(bound.lat_hi().degrees() > 10.0 && bound.lat_hi().degrees() < 10.2);
+        assert!(!bound.lng().is_full());
+
+        // The complement contains both poles.
+        let c = square_complement();
+        assert!(!c.contains_point(&S2Point::new(1.0, 0.0, 0.0)));
+        assert!(c.contains_point(&S2Point::new(0.0, 0.0, 1.0)));
+        assert!(c.contains_point(&S2Point::new(0.0, 0.0, -1.0)));
+        assert!(c.get_rect_bound().is_full());
+
+        // A loop around the north pole.
+        let arctic = make_loop(&[(80.0, 0.0), (80.0, 120.0), (80.0, -120.0)]);
+        assert!(arctic.contains_point(&S2Point::new(0.0, 0.0, 1.0)));
+        assert_eq!(arctic.get_rect_bound().lat_hi().radians(), FRAC_PI_2);
+        assert!(arctic.get_rect_bound().lng().is_full());
+        assert!(arctic.get_rect_bound().lat_lo().degrees() < 80.0);
+    }
+
+    #[test]
+    fn test_contains_and_intersects_cell() {
+        let l = square();
+        let c = square_complement();
+        let inside =
+            S2Cell::new(S2CellId::from_point(&S2Point::new(1.0, 0.0, 0.0)).parent_at_level(8));
+        let outside =
+            S2Cell::new(S2CellId::from_point(&S2Point::new(-1.0, 0.0, 0.0)).parent_at_level(8));
+        // A cell straddling the loop edge at latitude 10 degrees.
+        let straddle = S2Cell::new(
+            S2CellId::from_point(&S2LatLng::from_degrees(10.0, 0.0).to_point()).parent_at_level(8),
+        );
+
+        assert!(l.contains_cell(&inside) && l.intersects_cell(&inside));
+        assert!(!l.contains_cell(&outside) && !l.intersects_cell(&outside));
+        assert!(!l.contains_cell(&straddle) && l.intersects_cell(&straddle));
+
+        assert!(!c.contains_cell(&inside) && !c.intersects_cell(&inside));
+        assert!(c.contains_cell(&outside) && c.intersects_cell(&outside));
+        assert!(!c.contains_cell(&straddle) && c.intersects_cell(&straddle));
+
+        // Face 0 contains the whole loop without containing any cell vertex.
+        let face = S2Cell::from_face(0);
+        assert!(!l.contains_cell(&face) && l.intersects_cell(&face));
+        assert!(!c.contains_cell(&face) && c.intersects_cell(&face));
+    }
+
+    #[test]
+    fn test_cell_predicates_consistent_with_points() {
+        // For random cells, containment implies that every sampled point of the
+        // cell is in the loop, and non-intersection implies that none is.
+        let loops = [square(), square_complement()];
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..200 {
+            let p = random_point(&mut rng);
+            let level = rng.gen_range(1..=8);
+            let cell = S2Cell::new(S2CellId::from_point(&p).parent_at_level(level));
+            for l in &loops {
+                let contains = l.contains_cell(&cell);
+                let intersects = l.intersects_cell(&cell);
+                assert!(!contains || intersects);
+                for k in 0..4 {
+                    let v = cell.get_vertex(k);
+                    let q = (v + cell.get_center()).normalize();
+                    if contains {
+                        assert!(l.contains_point(&q));
+                    }
+                    if !intersects {
+                        assert!(!l.contains_point(&q));
+                    }
+                }
+                // Exactly one of a loop and its complement contains a point.
+                assert_ne!(
+                    loops[0].contains_point(&cell.get_center()),
+                    loops[1].contains_point(&cell.get_center())
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_area() {
+        assert_eq!(S2Loop::empty().get_area(), 0.0);
+        assert_eq!(S2Loop::full().get_area(), 4.0 * PI);
+
+        // A loop around the equator is a hemisphere containing the north pole,
+        // and its reverse contains the south pole.
+        let equator = make_loop(&[(0.0, 0.0), (0.0, 90.0), (0.0, 180.0), (0.0, -90.0)]);
+        assert!(equator.contains_point(&S2Point::new(0.0, 0.0, 1.0)));
+        assert!((equator.get_area() - 2.0 * PI).abs() <= 1e-14);
+        let reversed = make_loop(&[(0.0, -90.0), (0.0, 180.0), (0.0, 90.0), (0.0, 0.0)]);
+        assert!(reversed.contains_point(&S2Point::new(0.0, 0.0, -1.0)));
+        assert!((reversed.get_area() - 2.0 * PI).abs() <= 1e-14);
+
+        // A loop around the north pole is the union of triangles fanning out
+        // from the pole.
+        let arctic = make_loop(&[(60.0, 0.0), (70.0, 100.0), (65.0, -150.0), (80.0, -60.0)]);
+        let pole = S2Point::new(0.0, 0.0, 1.0);
+        let n = arctic.num_vertices();
+        let expected: f64 = (0..n)
+            .map(|i| area(arctic.vertex(i), arctic.vertex(i + 1), &pole))
+            .sum();
+        assert!((arctic.get_area() - expected).abs() <= 1e-14);
+
+        // Loops and their complements partition the sphere.
+        let l = square();
+        let c = square_complement();
+        assert!((l.get_area() + c.get_area() - 4.0 * PI).abs() <= 1e-14);
+
+        let mut rng = StdRng::seed_from_u64(45);
+        for _ in 0..100 {
+            let mut vertices = [
+                random_point(&mut rng),
+                random_point(&mut rng),
+                random_point(&mut rng),
+            ];
+            let triangle_area = area(&vertices[0], &vertices[1], &vertices[2]);
+            if sign(&vertices[0], &vertices[1], &vertices[2]) < 0 {
+                vertices.swap(0, 1);
+            }
+            let l = S2Loop::new(vertices.to_vec());
+            assert!((l.get_area() - triangle_area).abs() <= 1e-14);
+            vertices.reverse();
+            let c = S2Loop::new(vertices.to_vec());
+            assert!((c.get_area() - (4.0 * PI - triangle_area)).abs() <= 1e-14);
+        }
+    }
+
+    #[test]
+    fn test_get_centroid() {
+        assert_eq!(S2Loop::empty().get_centroid(), S2Point::zero());
+        assert_eq!(S2Loop::full().get_centroid(), S2Point::zero());
+
+        // Symmetric loops have their centroid at the center of symmetry.
+        let l = square();
+        assert!((l.get_centroid() - S2Point::new(1.0, 0.0, 0.0)).norm() <= 1e-15);
+        assert!((l.get_centroid().norm() - 1.0).abs() <= 1e-15);
+        let c = square_complement();
+        assert!((c.get_centroid() - S2Point::new(-1.0, 0.0, 0.0)).norm() <= 1e-15);
+        let arctic = make_loop(&[(80.0, 0.0), (80.0, 120.0), (80.0, -120.0)]);
+        assert!((arctic.get_centroid() - S2Point::new(0.0, 0.0, 1.0)).norm() <= 1e-15);
+
+        // The centroid of a loop is the same wherever its fan starts.
+        let vertices = [(60.0, 0.0), (70.0, 100.0), (65.0, -150.0), (80.0, -60.0)]
+            .iter()
+            .map(|&(lat, lng)| S2LatLng::from_degrees(lat, lng).to_point())
+            .collect::<Vec<_>>();
+        let centroid = S2Loop::new(vertices.clone()).get_centroid();
+        for k in 1..vertices.len() {
+            let mut rotated = vertices.clone();
+            rotated.rotate_left(k);
+            assert!((S2Loop::new(rotated).get_centroid() - centroid).norm() <= 1e-15);
+        }
+
+        // A random triangle and its complement have opposite centroids, since
+        // the centroid of the whole sphere is the origin.
+        let mut rng = StdRng::seed_from_u64(54);
+        for _ in 0..100 {
+            let v = [
+                random_point(&mut rng),
+                random_point(&mut rng),
+                random_point(&mut rng),
+            ];
+            let a = S2Loop::new(v.to_vec()).get_centroid();
+            let b = S2Loop::new(vec![v[2], v[1], v[0]]).get_centroid();
+            assert!((a + b).norm() <= 1e-13);
+            assert!((a.norm() - 1.0).abs() <= 1e-15);
+        }
+    }
+
+    #[test]
+    fn test_region() {
+        let l = square();
+        let region: Box<dyn S2Region> = l.clone_region();
+        assert!(region.contains_point(&S2Point::new(1.0, 0.0, 0.0)));
+        assert!(region
+            .get_cap_bound()
+            .contains_point(&S2Point::new(1.0, 0.0, 0.0)));
+        for v in l.vertices() {
+            assert!(region.get_cap_bound().contains_point(v));
+            assert!(region.get_rect_bound().contains_point(v));
+        }
+    }
+}
@@ -0,0 +1,252 @@
+// Copyright 2005 Google Inc. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS-IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+// Original Author: ericv@google.com (Eric Veach)
+//
+// Defines various angle and area measures on the sphere.
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+use crate::s2::{robust_cross_prod, sign, S2Point};
+
+/// Returns the area of triangle ABC.  This method combines two different
+/// algorithms to get accurate results for both large and small triangles.
+/// The maximum error is about 5e-15 (about 0.25 square meters on the Earth's
+/// surface), the same as girard_area() below, but unlike that method it is
+/// also accurate for small triangles.  Example: when the true area is 100
+/// square meters, area() yields an error about 1 trillion times smaller than
+/// girard_area().
+///
+/// All points should be unit length, and no two points should be antipodal.
+/// The area is always positive.
+///
+/// # Examples
+///
+/// ```
+/// use core::f64::consts::FRAC_PI_2;
+/// use s2shell::s2::{s2measures::area, S2Point};
+///
+/// let a = S2Point::new(1.0, 0.0, 0.0);
+/// let b = S2Point::new(0.0, 1.0, 0.0);
+/// let c = S2Point::new(0.0, 0.0, 1.0);
+/// assert!((area(&a, &b, &c) - FRAC_PI_2).abs() < 1e-15);
+/// ```
+pub fn area(a: &S2Point, b: &S2Point, c: &S2Point) -> f64 {
+    debug_assert!(a.is_unit_length());
+    debug_assert!(b.is_unit_length());
+    debug_assert!(c.is_unit_length());
+    // This method is based on l'Huilier's theorem,
+    //
+    //   tan(E/4) = sqrt(tan(s/2) tan((s-a)/2) tan((s-b)/2) tan((s-c)/2))
+    //
+    // where E is the spherical excess of the triangle (i.e. its area),
+    //       a, b, c, are the side lengths, and
+    //       s is the semiperimeter (a + b + c) / 2 .
+    //
+    // The only significant source of error using l'Huilier's method is the
+    // cancellation error of the terms (s-a), (s-b), (s-c).  This leads to a
+    // *relative* error of about 1e-16 * s / min(s-a, s-b, s-c).  This compares
+    // to a relative error of about 1e-15 / E using Girard's formula, where E is
+    // the true area of the triangle.  Girard's formula can be even worse than
+    // this for very small triangles, e.g. a triangle with a true area of 1e-30
+    // might evaluate to 1e-5.
+    //
+    // So, we prefer l'Huilier's formula unless dmin < s * (0.1 * E), where
+    // dmin = min(s-a, s-b, s-c).  This basically includes all triangles
+    // except for extremely long and skinny ones.
+    //
+    // Since we don't know E, we would like a conservative upper bound on
+    // the triangle area in terms of s and dmin.  It's possible to show that
+    // E <= k1 * s * sqrt(s * dmin), where k1 = 2*sqrt(3)/Pi (about 1).
+    // Using this, it's easy to show that we should always use l'Huilier's
+    // method if dmin >= k2 * s^5, where k2 is about 1e-2.  Furthermore,
+    // if dmin < k2 * s^5, the triangle area is at most k3 * s^4, where
+    // k3 is about 0.1.  Since the best case error using Girard's formula
+    // is about 1e-15, this means that we shouldn't even consider it unless
+    // s >= 3e-4 or so.
+    let sa = b.angle(c);
+    let sb = c.angle(a);
+    let sc = a.angle(b);
+    let s = 0.5 * (sa + sb + sc);
+    if s >= 3e-4 {
+        // Consider whether Girard's formula might be more accurate.
+        let s2 = s * s;
+        let dmin = s - sa.max(sb).max(sc);
+        if dmin < 1e-2 * s * s2 * s2 {
+            // This triangle is skinny enough to consider using Girard's formula.
+            // We increase the area by the approximate maximum error in the
+            // Girard calculation in order to ensure that this test is
+            // conservative.
+            let area = girard_area(a, b, c);
+            if dmin < s * (0.1 * (area + 5e-15)) {
+                return area;
+            }
+        }
+    }
+    // Use l'Huilier's formula.
+    4.0 * (0.0_f64)
+        .max(
+            (0.5 * s).tan()
+                * (0.5 * (s - sa)).tan()
+                * (0.5 * (s - sb)).tan()
+                * (0.5 * (s - sc)).tan(),
+        )
+        .sqrt()
+        .atan()
+}
+
+/// Returns the area of the triangle computed using Girard's formula.  All
+/// points should be unit length, and no two points should be antipodal.
+///
+/// This method is about twice as fast as area() but has poor relative
+/// accuracy for small triangles.  The maximum error is about 5e-15 (about
+/// 0.25 square meters on the Earth's surface) and the average error is about
+/// 1e-15.  These bounds apply to triangles of any size, even as the maximum
+/// edge length of the triangle approaches 180 degrees.  But note that for
+/// such triangles, tiny perturbations of the input points can change the
+/// true mathematical area dramatically.
+pub fn girard_area(a: &S2Point, b: &S2Point, c: &S2Point) -> f64 {
+    // This is equivalent to the usual Girard's formula but is slightly more
+    // accurate, faster to compute, and handles a == b == c without a special
+    // case.  robust_cross_prod() is necessary to get good accuracy when two
+    // of the input points are very close together.
+    let ab = robust_cross_prod(a, b);
+    let bc = robust_cross_prod(b, c);
+    let ac = robust_cross_prod(a, c);
+    (ab.angle(&ac) - ab.angle(&bc) + bc.angle(&ac)).max(0.0)
+}
+
+/// Returns the exterior angle at vertex B in the triangle ABC.  The return
+/// value is positive if ABC is counterclockwise and negative otherwise.  If
+/// you imagine an ant walking from A to B to C, this is the angle that the
+/// ant turns at vertex B (positive = left = CCW, negative = right = CW).
+/// This quantity is also known as the "geodesic curvature" at B.
+///
+/// Ensures that turn_angle(a,b,c) == -turn_angle(c,b,a) for all distinct
+/// a,b,c.  The result is undefined if (a == b || b == c), but is either
+/// -Pi or Pi if (a == c).  All points should be normalized.
+///
+/// # Examples
+///
+/// ```
+/// use core::f64::consts::FRAC_PI_2;
+/// use s2shell::s2::{s2measures::turn_angle, S2Point};
+///
+/// let a = S2Point::new(1.0, 0.0, 0.0);
+/// let b = S2Point::new(0.0, 1.0, 0.0);
+/// let c = S2Point::new(0.0, 0.0, 1.0);
+/// assert!((turn_angle(&a, &b, &c) - FRAC_PI_2).abs() < 1e-15);
+/// assert_eq!(turn_angle(&c, &b, &a), -turn_angle(&a, &b, &c));
+/// ```
+pub fn turn_angle(a: &S2Point, b: &S2Point, c: &S2Point) -> f64 {
+    // We use robust_cross_prod() to get good accuracy when two points are very
+    // close together, and sign() to ensure that the sign is correct for
+    // turns that are close to 180 degrees.
+    //
+    // Unfortunately we can't save robust_cross_prod(a, b) and pass it as the
+    // next call's first argument, because that would break the symmetry
+    // property above.
+    let angle = robust_cross_prod(a, b).angle(&robust_cross_prod(b, c));
+
+    // Don't return sign() * angle because it is legal to have (a == c).
+    if sign(a, b, c) > 0 {
+        angle
+    } else {
+        -angle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+    use super::*;
+
+    #[test]
+    fn test_area() {
+        let pz = S2Point::new(0.0, 0.0, 1.0);
+        let p000 = S2Point::new(1.0, 0.0, 0.0);
+        let p045 = S2Point::new(1.0, 1.0, 0.0).normalize();
+        let p090 = S2Point::new(0.0, 1.0, 0.0);
+        let p180 = S2Point::new(-1.0, 0.0, 0.0);
+
+        assert!((area(&p000, &p090, &pz) - FRAC_PI_2).abs() <= 4.0 * f64::EPSILON);
+        assert!((area(&p045, &pz, &p180) - 3.0 * FRAC_PI_4).abs() <= 4.0 * f64::EPSILON);
+
+        // Make sure that area() has good *relative* accuracy even for very
+        // small areas.
+        let eps = 1e-10;
+        let pepsx = S2Point::new(eps, 0.0, 1.0).normalize();
+        let pepsy = S2Point::new(0.0, eps, 1.0).normalize();
+        let expected1 = 0.5 * eps * eps;
+        assert!((area(&pepsx, &pepsy, &pz) - expected1).abs() <= 1e-14 * expected1);
+
+        // Make sure that it can handle degenerate triangles.
+        let pr = S2Point::new(0.257, -0.5723, 0.112).normalize();
+        let pq = S2Point::new(-0.747, 0.401, 0.2235).normalize();
+        assert_eq!(area(&pr, &pr, &pr), 0.0);
+        // The following test is not exact due to rounding error.
+        assert!(area(&pr, &pq, &pr).abs() <= 1e-15);
+        assert_eq!(area(&p000, &p045, &p090), 0.0);
+
+        // A triangle with a very long, skinny shape uses Girard's formula.
+        let eps2 = 1e-14;
+        let p000eps2 = S2Point::new(1.0, 0.1 * eps2, eps2).normalize();
+        let expected2 = 5.857_864_376_269_05e-15; // Mathematica.
+        assert!((area(&p000eps2, &p045, &p090) - expected2).abs() <= 1e-9 * expected2);
+
+        // The four triangles around the north pole of an octahedron cover a
+        // hemisphere.
+        let p270 = S2Point::new(0.0, -1.0, 0.0);
+        let sum = area(&p000, &p090, &pz)
+            + area(&p090, &p180, &pz)
+            + area(&p180, &p270, &pz)
+            + area(&p270, &p000, &pz);
+        assert!((sum - 2.0 * PI).abs() <= 1e-14);
+    }
+
+    #[test]
+    fn test_girard_area() {
+        let pz = S2Point::new(0.0, 0.0, 1.0);
+        let p000 = S2Point::new(1.0, 0.0, 0.0);
+        let p090 = S2Point::new(0.0, 1.0, 0.0);
+        assert!((girard_area(&p000, &p090, &pz) - FRAC_PI_2).abs() <= 1e-15);
+        assert_eq!(girard_area(&pz, &pz, &pz), 0.0);
+    }
+
+    #[test]
+    fn test_turn_angle() {
+        let pz = S2Point::new(0.0, 0.0, 1.0);
+        let p000 = S2Point::new(1.0, 0.0, 0.0);
+        let p045 = S2Point::new(1.0, 1.0, 0.0).normalize();
+        let p090 = S2Point::new(0.0, 1.0, 0.0);
+        let p180 = S2Point::new(-1.0, 0.0, 0.0);
+
+        assert!((turn_angle(&p000, &p090, &pz) - FRAC_PI_2).abs() <= 1e-15);
+        assert!((turn_angle(&pz, &p090, &p000) + FRAC_PI_2).abs() <= 1e-15);
+        assert!(turn_angle(&p000, &p045, &p090).abs() <= 1e-15);
+        assert!((turn_angle(&p045, &pz, &p180) + FRAC_PI_4).abs() <= 1e-15);
+
+        // Turning back along the same edge is a turn of +/- Pi.
+        assert_eq!(turn_angle(&p000, &p090, &p000).abs(), PI);
+
+        // The turning angles around a triangle sum to 2 * Pi minus its area.
+        let sum = turn_angle(&pz, &p000, &p090)
+            + turn_angle(&p000, &p090, &pz)
+            + turn_angle(&p090, &pz, &p000);
+        assert!((sum - (2.0 * PI - area(&p000, &p090, &pz))).abs() <= 1e-14);
+    }
+}
@@ -0,0 +1,260 @@
+// Copyright 2005 Google Inc. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS-IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+// Original Author: ericv@google.com (Eric Veach)
+//
+// The following are various constants that describe the shapes and sizes of
+// S2Cells (see s2coords.h and s2cell_id.h). They are useful for deciding
+// which cell level to use in order to satisfy a given condition (e.g. that
+// cell vertices must be no further than "x" apart). All of the raw constants
+// are differential quantities; you can use the get_value(level) method to
+// compute the corresponding length or area on the unit sphere for cells at a
+// given level. The minimum and maximum bounds are valid for cells at all
+// levels, but they may be somewhat conservative for very large cells (e.g.
+// face cells).
+//
+// All of the values below assume the quadratic projection used by st_to_uv().
+
+// The constants are given to full precision to match the reference
+// implementation exactly.
+#![allow(clippy::excessive_precision)]
+
+use core::f64::consts::{FRAC_PI_2, PI, SQRT_2};
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+use crate::s2::MAX_CELL_LEVEL;
+
+/// Defines a cell metric of the given dimension (1 == length, 2 == area).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Metric<const DIM: i32> {
+    deriv: f64,
+}
+
+pub type LengthMetric = Metric<1>;
+pub type AreaMetric = Metric<2>;
+
+impl<const DIM: i32> Metric<DIM> {
+    pub const fn new(deriv: f64) -> Metric<DIM> {
+        Metric { deriv }
+    }
+
+    /// The "deriv" value of a metric is a derivative, and must be multiplied
+    /// by a length or area in (s,t)-space to get a useful value.
+    pub fn deriv(&self) -> f64 {
+        self.deriv
+    }
+
+    /// Return the value of a metric for cells at the given level. The value
+    /// is either a length or an area on the unit sphere, depending on the
+    /// particular metric.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::s2metrics::{AVG_AREA, MIN_EDGE};
+    ///
+    /// assert_eq!(MIN_EDGE.get_value(1), 0.5 * MIN_EDGE.deriv());
+    /// assert_eq!(AVG_AREA.get_value(1), 0.25 * AVG_AREA.deriv());
+    /// ```
+    pub fn get_value(&self, level: i32) -> f64 {
+        self.deriv * 2_f64.powi(-DIM * level)
+    }
+
+    /// Return the level at which the metric has approximately the given value.
+    /// For example, AVG_EDGE.get_closest_level(0.1) returns the level at which
+    /// the average cell edge length is approximately 0.1. The return value is
+    /// always a valid level.
+    pub fn get_closest_level(&self, value: f64) -> i32 {
+        self.get_level_for_max_value(if DIM == 1 { SQRT_2 } else { 2.0 } * value)
+    }
+
+    /// Return the minimum level such that the metric is at most the given
+    /// value, or MAX_CELL_LEVEL if there is no such level. For example,
+    /// MAX_DIAG.get_level_for_max_value(0.1) returns the minimum level such
+    /// that all cell diagonal lengths are 0.1 or smaller. The return value is
+    /// always a valid level.
+    pub fn get_level_for_max_value(&self, value: f64) -> i32 {
+        if value <= 0.0 {
+            return MAX_CELL_LEVEL;
+        }
+        // This code is equivalent to computing a floating-point "level" value
+        // and rounding up. ilogb() returns the exponent corresponding to a
+        // fraction in the range [1,2).
+        let level = ilogb(value / self.deriv);
+        let level = (-(level >> (DIM - 1))).clamp(0, MAX_CELL_LEVEL);
+        debug_assert!(level == MAX_CELL_LEVEL || self.get_value(level) <= value);
+        debug_assert!(level == 0 || self.get_value(level - 1) > value);
+        level
+    }
+
+    /// Return the maximum level such that the metric is at least the given
+    /// value, or zero if there is no such level. For example,
+    /// MIN_WIDTH.get_level_for_min_value(0.1) returns the maximum level such
+    /// that all cells have a minimum width of 0.1 or larger. The return value
+    /// is always a valid level.
+    pub fn get_level_for_min_value(&self, value: f64) -> i32 {
+        if value <= 0.0 {
+            return MAX_CELL_LEVEL;
+        }
+        // This code is equivalent to computing a floating-point "level" value
+        // and rounding down.
+        let level = ilogb(self.deriv / value);
+        let level = (level >> (DIM - 1)).clamp(0, MAX_CELL_LEVEL);
+        debug_assert!(level == 0 || self.get_value(level) >= value);
+        debug_assert!(level == MAX_CELL_LEVEL || self.get_value(level + 1) < value);
+        level
+    }
+}
+
+/// Returns the unbiased binary exponent of "x", i.e. floor(log2(|x|)), for
+/// finite non-zero values.
+fn ilogb(x: f64) -> i32 {
+    debug_assert!(x.is_finite() && x != 0.0);
+    let exponent = ((x.to_bits() >> 52) & 0x7ff) as i32;
+    if exponent == 0 {
+        // Subnormal numbers are scaled into the normal range first.
+        return ilogb(x * 2_f64.powi(64)) - 64;
+    }
+    exponent - 1023
+}
+
+// Each cell is bounded by four planes passing through its four edges and
+// the center of the sphere. These metrics relate to the angle between each
+// pair of opposite bounding planes, or equivalently, between the planes
+// corresponding to two different s-values or two different t-values. For
+// example, the maximum angle between opposite bounding planes for a cell at
+// level k is MAX_ANGLE_SPAN.get_value(k), and the average angle span for all
+// cells at level k is approximately AVG_ANGLE_SPAN.get_value(k).
+pub const MIN_ANGLE_SPAN: LengthMetric = LengthMetric::new(4.0 / 3.0); // 1.333
+pub const MAX_ANGLE_SPAN: LengthMetric = LengthMetric::new(1.704897179199218452); // 1.705
+pub const AVG_ANGLE_SPAN: LengthMetric = LengthMetric::new(FRAC_PI_2); // 1.571
+
+// The width of geometric figure is defined as the distance between two
+// parallel bounding lines in a given direction. For cells, the minimum width
+// is always attained between two opposite edges, and the maximum width is
+// attained between two opposite vertices. However, for our purposes we
+// redefine the width of a cell as the perpendicular distance between a pair
+// of opposite edges. A cell therefore has two widths, one in each direction.
+// The minimum width according to this definition agrees with the classic
+// geometric one, but the maximum width is different. (The maximum geometric
+// width corresponds to MAX_DIAG defined below.)
+//
+// For a cell at level k, the distance between opposite edges is at least
+// MIN_WIDTH.get_value(k) and at most MAX_WIDTH.get_value(k). The average
+// width in both directions for all cells at level k is approximately
+// AVG_WIDTH.get_value(k).
+//
+// The width is useful for bounding the minimum or maximum distance from a
+// point on one edge of a cell to the closest point on the opposite edge.
+// For example, this is useful when "growing" regions by a fixed distance.
+pub const MIN_WIDTH: LengthMetric = LengthMetric::new(2.0 * SQRT_2 / 3.0); // 0.943
+pub const MAX_WIDTH: LengthMetric = LengthMetric::new(1.704897179199218452); // 1.705
+pub const AVG_WIDTH: LengthMetric = LengthMetric::new(1.434523672886099389); // 1.435
+
+// The minimum edge length of any cell at level k is at least
+// MIN_EDGE.get_value(k), and the maximum is at most MAX_EDGE.get_value(k).
+// The average edge length is approximately AVG_EDGE.get_value(k).
+//
+// The edge length metrics can also be used to bound the minimum, maximum,
+// or average distance from the center of one cell to the center of one of
+// its edge neighbors. In particular, it can be used to bound the distance
+// between adjacent cell centers along the space-filling Hilbert curve for
+// cells at any given level.
+pub const MIN_EDGE: LengthMetric = LengthMetric::new(2.0 * SQRT_2 / 3.0); // 0.943
+pub const MAX_EDGE: LengthMetric = LengthMetric::new(1.704897179199218452); // 1.705
+pub const AVG_EDGE: LengthMetric = LengthMetric::new(1.459213746386106062); // 1.459
+
+// The minimum diagonal length of any cell at level k is at least
+// MIN_DIAG.get_value(k), and the maximum is at most MAX_DIAG.get_value(k).
+// The average diagonal length is approximately AVG_DIAG.get_value(k).
+//
+// The maximum diagonal also happens to be the maximum diameter of any cell,
+// and also the maximum geometric width (see the discussion above). So for
+// example, the distance from an arbitrary point to the closest cell center
+// at a given level is at most half the maximum diagonal length.
+pub const MIN_DIAG: LengthMetric = LengthMetric::new(8.0 * SQRT_2 / 9.0); // 1.257
+pub const MAX_DIAG: LengthMetric = LengthMetric::new(2.438654594434021032); // 2.439
+pub const AVG_DIAG: LengthMetric = LengthMetric::new(2.060422738998471683); // 2.060
+
+// The minimum area of any cell at level k is at least MIN_AREA.get_value(k),
+// and the maximum is at most MAX_AREA.get_value(k). The average area of all
+// cells at level k is exactly AVG_AREA.get_value(k).
+pub const MIN_AREA: AreaMetric = AreaMetric::new(8.0 * SQRT_2 / 9.0); // 1.257
+pub const MAX_AREA: AreaMetric = AreaMetric::new(2.635799256963161491); // 2.636
+pub const AVG_AREA: AreaMetric = AreaMetric::new(4.0 * PI / 6.0); // 2.094
+
+/// This is the maximum edge aspect ratio over all cells at any level, where
+/// the edge aspect ratio of a cell is defined as the ratio of its longest
+/// edge length to its shortest edge length.
+pub const MAX_EDGE_ASPECT: f64 = 1.442615274452682920;
+
+/// This is the maximum diagonal aspect ratio over all cells at any level,
+/// where the diagonal aspect ratio of a cell is defined as the ratio of its
+/// longest diagonal length to its shortest diagonal length.
+pub const MAX_DIAG_ASPECT: f64 = 1.7320508075688772; // sqrt(3)
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics() {
+        assert!(MIN_ANGLE_SPAN.deriv() <= AVG_ANGLE_SPAN.deriv());
+        assert!(AVG_ANGLE_SPAN.deriv() <= MAX_ANGLE_SPAN.deriv());
+        assert!(MIN_WIDTH.deriv() <= AVG_WIDTH.deriv());
+        assert!(AVG_WIDTH.deriv() <= MAX_WIDTH.deriv());
+        assert!(MIN_EDGE.deriv() <= AVG_EDGE.deriv());
+        assert!(AVG_EDGE.deriv() <= MAX_EDGE.deriv());
+        assert!(MIN_DIAG.deriv() <= AVG_DIAG.deriv());
+        assert!(AVG_DIAG.deriv() <= MAX_DIAG.deriv());
+        assert!(MIN_AREA.deriv() <= AVG_AREA.deriv());
+        assert!(AVG_AREA.deriv() <= MAX_AREA.deriv());
+
+        // Check that the maximum aspect ratio of an individual cell is
+        // consistent with the global minimums and maximums.
+        assert!(MAX_EDGE_ASPECT <= MAX_EDGE.deriv() / MIN_EDGE.deriv());
+        assert!(MAX_DIAG_ASPECT <= MAX_DIAG.deriv() / MIN_DIAG.deriv());
+    }
+
+    #[test]
+    fn test_level_for_value() {
+        // Check that the min/max level for a given value is consistent with
+        // the value at that level.
+        for level in -2..=MAX_CELL_LEVEL + 3 {
+            let width = MIN_WIDTH.deriv() * 2_f64.powi(-level);
+            let expected = level.clamp(0, MAX_CELL_LEVEL);
+            assert_eq!(MIN_WIDTH.get_level_for_max_value(width), expected);
+            assert_eq!(MIN_WIDTH.get_level_for_min_value(width), expected);
+            assert_eq!(MIN_WIDTH.get_closest_level(width), expected);
+            assert_eq!(MIN_WIDTH.get_level_for_max_value(1.2 * width), expected);
+            assert_eq!(
+                MIN_WIDTH.get_level_for_min_value(0.8 * width),
+                expected,
+                "{level}"
+            );
+
+            let area = MIN_AREA.deriv() * 2_f64.powi(-2 * level);
+            assert_eq!(MIN_AREA.get_level_for_max_value(area), expected);
+            assert_eq!(MIN_AREA.get_level_for_min_value(area), expected);
+            assert_eq!(MIN_AREA.get_closest_level(area), expected);
+            assert_eq!(MIN_AREA.get_level_for_max_value(1.2 * area), expected);
+            assert_eq!(MIN_AREA.get_level_for_min_value(0.8 * area), expected);
+        }
+        assert_eq!(MAX_DIAG.get_level_for_max_value(0.0), MAX_CELL_LEVEL);
+        assert_eq!(MAX_DIAG.get_level_for_max_value(1e-300), MAX_CELL_LEVEL);
+    }
+}
@@ -15,17 +15,289 @@
 
 // Original Author: ericv@google.com (Eric Veach)
 
-use crate::util::math::vector::Vector3;
+use crate::{s2::s2predicates, util::math::vector::Vector3};
 
 /// An S2Point represents a point on the unit sphere as a 3D vector. Usually
 /// points are normalized to be unit length, but some methods do not require
 /// this. See util/math/vector.h for the methods available.
 pub type S2Point = Vector3<f64>;
 
+/// Return a unique "origin" on the sphere for operations that need a fixed
+/// reference point. In particular, this is the "point at infinity" used for
+/// point-in-polygon testing (by counting the number of edge crossings).
+///
+/// It should *not* be a point that is commonly used in edge tests in order
+/// to avoid triggering code to handle degenerate cases. (This rules out the
+/// north and south poles.) It should also not be on the boundary of any
+/// low-level S2Cell for the same reason.
+#[allow(clippy::excessive_precision)]
 pub fn origin() -> S2Point {
-    todo!()
+    // The origin was chosen to be at a point that is about 0.6 degrees from
+    // the north pole, near the middle of a level 20 cell boundary, and not on
+    // the boundary of any cell at level 20 or below.
+    S2Point::new(
+        -0.0099994664350250197,
+        0.0025924542609324121,
+        0.99994664350250195,
+    )
 }
 
+/// Return a unique reference direction orthogonal to the given point,
+/// used to define a consistent ordering of edges around a vertex.
+pub fn ref_dir(a: &S2Point) -> S2Point {
+    a.ortho()
+}
+
+/// Return true if the given point is approximately unit length (this is
+/// mainly useful for assertions).
 pub fn is_unit_length(p: &S2Point) -> bool {
-    (p.norm2() - 1.0).abs() <= 5.0 * f64::EPSILON
+    // normalize() is guaranteed to return a vector whose L2-norm differs from
+    // 1 by less than 2 * f64::EPSILON. Thus the squared L2-norm differs by
+    // less than 4 * f64::EPSILON. The actual calculated norm2() can have up to
+    // 1.5 * f64::EPSILON of additional error. The total error of 5.5 *
+    // f64::EPSILON can then be rounded down since the result must be a
+    // representable double-precision value.
+    (p.norm2() - 1.0).abs() <= 5.0 * f64::EPSILON // About 1.11e-15
+}
+
+impl S2Point {
+    /// Return true if this point is approximately unit length. See
+    /// [`is_unit_length`] for the tolerance used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::S2Point;
+    ///
+    /// assert!(S2Point::new(1.0, 1.0, 1.0).normalize().is_unit_length());
+    /// assert!(!S2Point::new(1.0, 1.0, 1.0).is_unit_length());
+    /// ```
+    pub fn is_unit_length(&self) -> bool {
+        is_unit_length(self)
+    }
+
+    /// Panic if this point is not approximately unit length. Like
+    /// `debug_assert!`, the check is only performed in debug builds.
+    #[track_caller]
+    pub fn assert_unit_length(&self) {
+        debug_assert!(self.is_unit_length(), "point is not unit length: {self:?}");
+    }
+
+    /// Return true if the edges OA, OB, and OC are encountered in that order
+    /// while sweeping CCW around the point O, with OB strictly between OA and
+    /// OC. Unlike the inclusive [`s2predicates::ordered_ccw`], this is false
+    /// if B is in the same direction from O as A or C, and it is always false
+    /// if A and C are in the same direction.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{ordered_ccw, S2Point};
+    ///
+    /// // Three directions around the north pole, in CCW order.
+    /// let o = S2Point::new(0.0, 0.0, 1.0);
+    /// let a = S2Point::new(1.0, 0.0, 0.0);
+    /// let b = S2Point::new(0.0, 1.0, 0.0);
+    /// let c = S2Point::new(-1.0, 0.0, 0.0);
+    /// assert!(S2Point::ordered_ccw(&a, &b, &c, &o));
+    /// assert!(!S2Point::ordered_ccw(&c, &b, &a, &o));
+    /// // The endpoints of the range are excluded, unlike the free function.
+    /// assert!(!S2Point::ordered_ccw(&a, &a, &c, &o));
+    /// assert!(ordered_ccw(&a, &a, &c, &o));
+    /// ```
+    pub fn ordered_ccw(a: &S2Point, b: &S2Point, c: &S2Point, o: &S2Point) -> bool {
+        // B is strictly inside the CCW range from A to C exactly when it is in
+        // the closed range from A to C but not in the closed range from C to A.
+        s2predicates::ordered_ccw(a, b, c, o) && !s2predicates::ordered_ccw(c, b, a, o)
+    }
+}
+
+/// Return a vector "c" that is orthogonal to the given unit-length vectors
+/// "a" and "b". This function is similar to a.cross_prod(b) except that it
+/// does a better job of ensuring orthogonality when "a" is nearly parallel
+/// to "b", and it returns a non-zero result even when a == b or a == -b.
+///
+/// It satisfies the following properties (RCP == robust_cross_prod):
+///
+///   (1) RCP(a, b) != 0 for all a, b
+///   (2) RCP(b, a) == -RCP(a, b) unless a == b or a == -b
+///   (3) RCP(-a, b) == -RCP(a, b) unless a == b or a == -b
+///   (4) RCP(a, -b) == -RCP(a, b) unless a == b or a == -b
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{robust_cross_prod, S2Point};
+///
+/// let a = S2Point::new(1.0, 0.0, 0.0);
+/// let b = S2Point::new(1.0, 1e-20, 0.0);
+/// // The naive cross product has the right direction here too, but it is
+/// // only accurate to within roughly DBL_EPSILON of "a" and "b".
+/// assert!(robust_cross_prod(&a, &b).normalize() == S2Point::new(0.0, 0.0, 1.0));
+/// // Identical points still yield a non-zero vector orthogonal to both.
+/// let c = robust_cross_prod(&a, &a);
+/// assert!(c != S2Point::zero() && c.dot_prod(&a) == 0.0);
+/// ```
+pub fn robust_cross_prod(a: &S2Point, b: &S2Point) -> S2Point {
+    // The direction of a.cross_prod(b) becomes unstable as (a + b) or (a - b)
+    // approaches zero. This leads to situations where a.cross_prod(b) is not
+    // very orthogonal to "a" and/or "b". We could fix this using Gram-Schmidt,
+    // but we also want b.robust_cross_prod(a) == -a.robust_cross_prod(b).
+    //
+    // The easiest fix is to just compute the cross product of (b+a) and
+    // (b-a). Mathematically, this cross product is exactly twice the cross
+    // product of "a" and "b", but it has the numerical advantage that (b+a)
+    // and (b-a) are always perpendicular (since "a" and "b" are unit
+    // length). This yields a result that is nearly orthogonal to both "a"
+    // and "b" even if these two values differ only in the lowest bit of one
+    // component.
+    debug_assert!(is_unit_length(a));
+    debug_assert!(is_unit_length(b));
+    let x = (b + a).cross_prod(&(b - a));
+    if x != S2Point::zero() {
+        return x;
+    }
+
+    // The only result that makes sense mathematically is to return zero, but
+    // we find it more convenient to return an arbitrary orthogonal vector.
+    a.ortho()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::s2::s2testing::random_point;
+
+    #[test]
+    fn test_is_unit_length() {
+        // Exact unit vectors.
+        for p in [
+            S2Point::new(1.0, 0.0, 0.0),
+            S2Point::new(0.0, -1.0, 0.0),
+            S2Point::new(0.0, 0.0, 1.0),
+            S2Point::new(0.6, 0.8, 0.0),
+        ] {
+            assert!(p.is_unit_length());
+            p.assert_unit_length();
+        }
+
+        // Normalized vectors are within the tolerance, including after a
+        // perturbation by one ulp in one component.
+        let p = S2Point::new(1.0, 2.0, 3.0).normalize();
+        assert!(p.is_unit_length());
+        assert!(S2Point::new(p.x().next_up(), p.y(), p.z()).is_unit_length());
+        assert!(origin().is_unit_length());
+
+        // Perturbations well beyond rounding error are not.
+        assert!(!(p * (1.0 + 1e-14)).is_unit_length());
+        assert!(!(p * (1.0 - 1e-14)).is_unit_length());
+
+        // Clearly non-unit vectors.
+        assert!(!S2Point::zero().is_unit_length());
+        assert!(!S2Point::new(1.0, 1.0, 0.0).is_unit_length());
+        assert!(!S2Point::new(0.0, 0.0, 2.0).is_unit_length());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "not unit length")]
+    fn test_assert_unit_length() {
+        S2Point::new(1.0, 1.0, 0.0).assert_unit_length();
+    }
+
+    #[test]
+    fn test_ordered_ccw_strict() {
+        // Three directions around the south pole. Seen from below, increasing
+        // longitude is clockwise, so the CCW order is by decreasing longitude.
+        let o = S2Point::new(0.0, 0.0, -1.0);
+        let a = S2Point::new(0.0, 1.0, 0.0);
+        let b = S2Point::new(1.0, 0.0, 0.0);
+        let c = S2Point::new(0.0, -1.0, 0.0);
+        assert!(S2Point::ordered_ccw(&a, &b, &c, &o));
+        assert!(!S2Point::ordered_ccw(&c, &b, &a, &o));
+
+        // The result is unchanged by rotating the arguments, and exactly one
+        // of the two cyclic orders holds for distinct directions.
+        let mut rng = StdRng::seed_from_u64(77);
+        for _ in 0..1000 {
+            let o = random_point(&mut rng);
+            let (a, b, c) = (
+                random_point(&mut rng),
+                random_point(&mut rng),
+                random_point(&mut rng),
+            );
+            let ccw = S2Point::ordered_ccw(&a, &b, &c, &o);
+            assert_eq!(ccw, S2Point::ordered_ccw(&b, &c, &a, &o));
+            assert_eq!(ccw, S2Point::ordered_ccw(&c, &a, &b, &o));
+            assert_ne!(ccw, S2Point::ordered_ccw(&c, &b, &a, &o));
+            // Repeated directions are never strictly between.
+            assert!(!S2Point::ordered_ccw(&a, &a, &c, &o));
+            assert!(!S2Point::ordered_ccw(&a, &c, &c, &o));
+            assert!(!S2Point::ordered_ccw(&a, &b, &a, &o));
+        }
+    }
+
+    // Checks that robust_cross_prod(a, b) is non-zero, orthogonal to both
+    // inputs, antisymmetric, and points in the direction "expected".
+    fn check_robust_cross_prod(a: &S2Point, b: &S2Point, expected: &S2Point) {
+        let c = robust_cross_prod(a, b);
+        assert_ne!(c, S2Point::zero());
+        let n = c.normalize();
+        assert!(n.dot_prod(a).abs() <= 1e-15, "{a:?} {b:?} {n:?}");
+        assert!(n.dot_prod(b).abs() <= 1e-15, "{a:?} {b:?} {n:?}");
+        assert!(
+            n.dot_prod(&expected.normalize()) > 0.99,
+            "{a:?} {b:?} {n:?}"
+        );
+        assert_eq!(robust_cross_prod(b, a), -c);
+    }
+
+    #[test]
+    fn test_robust_cross_prod_coordinate_axes() {
+        let x = S2Point::new(1.0, 0.0, 0.0);
+        let y = S2Point::new(0.0, 1.0, 0.0);
+        let z = S2Point::new(0.0, 0.0, 1.0);
+        check_robust_cross_prod(&x, &y, &z);
+        check_robust_cross_prod(&y, &z, &x);
+        check_robust_cross_prod(&z, &x, &y);
+
+        // Points that differ only by a tiny amount in one component, for
+        // which the naive cross product would be much less accurate.
+        check_robust_cross_prod(&x, &S2Point::new(1.0, 1e-20, 0.0), &z);
+        check_robust_cross_prod(&x, &S2Point::new(-1.0, 1e-20, 0.0), &z);
+        check_robust_cross_prod(&x, &S2Point::new(1.0, 0.0, -1e-100), &y);
+    }
+
+    #[test]
+    fn test_robust_cross_prod_nearly_equal_and_antipodal() {
+        let mut rng = StdRng::seed_from_u64(13);
+        for _ in 0..1000 {
+            let a = random_point(&mut rng);
+            // A direction "d" perpendicular to "a", so that the points
+            // a + eps * d and -a + eps * d lie on the great circle through
+            // "a" perpendicular to a x d.
+            let d = a.cross_prod(&random_point(&mut rng)).normalize();
+            let expected = a.cross_prod(&d);
+            let eps = 10f64.powi(-rng.gen_range(5..12));
+            let b = (a + d * eps).normalize();
+            check_robust_cross_prod(&a, &b, &expected);
+            let b = (-a + d * eps).normalize();
+            check_robust_cross_prod(&a, &b, &expected);
+        }
+    }
+
+    #[test]
+    fn test_robust_cross_prod_identical_and_antipodal() {
+        let mut rng = StdRng::seed_from_u64(14);
+        for _ in 0..100 {
+            let a = random_point(&mut rng);
+            for b in [a, -a] {
+                let c = robust_cross_prod(&a, &b);
+                assert_ne!(c, S2Point::zero());
+                assert!(c.normalize().dot_prod(&a).abs() <= 1e-15);
+            }
+        }
+    }
 }
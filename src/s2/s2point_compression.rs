@@ -0,0 +1,636 @@
+// Copyright 2011 Google Inc. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS-IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! Given a sequence of S2Points assumed to be the center of level-k cells,
+//! compresses it into a stream using the following method:
+//!
+//!  - decompose the points into (face, si, ti) tuples (see s2/mod.rs);
+//!  - run-length encode the faces, combining face number and count into a
+//!    varint64 (see `FaceRun`);
+//!  - right shift the (si, ti) to remove the part that's constant for all
+//!    cells of level-k.  The result is called the (pi, qi) space;
+//!  - 2nd derivative encode the pi and qi sequences (linear prediction);
+//!  - zig-zag encode all derivative values but the first, which cannot be
+//!    negative;
+//!  - interleave the zig-zag encoded values;
+//!  - encode the first interleaved value in a fixed length encoding (varint
+//!    would make this value larger);
+//!  - encode the remaining interleaved values as varint64s, as the
+//!    derivative encoding should make the values small.
+//!
+//! In addition, provides a lossless method to compress a sequence of points
+//! even if some points are not the center of level-k cells.  These points are
+//! stored exactly, using 3 double precision values, after the above encoded
+//! string, together with their index in the sequence (this leads to some
+//! redundancy - it is expected that only a small fraction of the points are
+//! not cell centers).
+//!
+//! The encoding is byte-for-byte identical to the one produced by
+//! `S2EncodePointsCompressed` in the C++ library, so it can be exchanged with
+//! it (doubles are stored in little-endian order).
+
+use alloc::vec::Vec;
+
+use crate::s2::{
+    face_uv_to_xyz, st_to_uv, xyz_to_face_siti, S2CellId, S2Point, SiTiCoord, MAX_CELL_LEVEL,
+    MAX_SITI,
+};
+
+const DERIVATIVE_ENCODING_ORDER: usize = 2;
+
+/// A point together with its (face, si, ti) coordinates and the level of the
+/// cell whose center it is (-1 if the point is not a cell center).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct S2XYZFaceSiTi {
+    pub xyz: S2Point,
+    pub face: i32,
+    pub si: SiTiCoord,
+    pub ti: SiTiCoord,
+    pub cell_level: i32,
+}
+
+impl S2XYZFaceSiTi {
+    pub fn new(xyz: S2Point) -> S2XYZFaceSiTi {
+        let (face, si, ti, cell_level) = xyz_to_face_siti(&xyz);
+        S2XYZFaceSiTi {
+            xyz,
+            face,
+            si,
+            ti,
+            cell_level,
+        }
+    }
+}
+
+/// Pair of face number and count for run-length encoding.
+#[derive(Debug, Copy, Clone)]
+struct FaceRun {
+    face: i32,
+    count: u32,
+}
+
+impl FaceRun {
+    /// Encodes each face as a varint64 with value NUM_FACES * count + face.
+    /// 21 faces can fit in a single byte.  Varint64 is used so that 4G faces
+    /// can be encoded instead of just 4G / 6 = ~700M.
+    fn encode(&self, out: &mut Vec<u8>) {
+        // It isn't necessary to encode the number of faces left for the last
+        // run, but since this would only help if there were more than 21
+        // faces, it will be a small overall savings, much smaller than the
+        // bound encoding.
+        encode_varint(
+            S2CellId::NUM_FACES as u64 * self.count as u64 + self.face as u64,
+            out,
+        );
+    }
+
+    fn decode(data: &[u8], pos: &mut usize) -> Option<FaceRun> {
+        let face_and_count = decode_varint(data, pos)?;
+        let face = (face_and_count % S2CellId::NUM_FACES as u64) as i32;
+        // Make sure large counts don't wrap on malicious or random input.
+        let count = u32::try_from(face_and_count / S2CellId::NUM_FACES as u64).ok()?;
+        (count > 0).then_some(FaceRun { face, count })
+    }
+}
+
+/// Encodes and decodes a sequence of integers as their n-th differences
+/// (wrapping on overflow), ramping up from 0-th differences for the first
+/// value.  Don't mix `encode` and `decode` calls on the same coder.
+#[derive(Debug)]
+struct NthDerivativeCoder {
+    // The derivative order of the coder (the N in NthDerivative).
+    n: usize,
+    // The derivative order in which to code the next value (ramps to n).
+    m: usize,
+    // Value memory.  [0] is oldest.
+    memory: [i32; DERIVATIVE_ENCODING_ORDER],
+}
+
+impl NthDerivativeCoder {
+    fn new(n: usize) -> NthDerivativeCoder {
+        debug_assert!(n <= DERIVATIVE_ENCODING_ORDER);
+        NthDerivativeCoder {
+            n,
+            m: 0,
+            memory: [0; DERIVATIVE_ENCODING_ORDER],
+        }
+    }
+
+    /// Encode the next value in the sequence.
+    fn encode(&mut self, mut k: i32) -> i32 {
+        for i in 0..self.m {
+            let delta = k.wrapping_sub(self.memory[i]);
+            self.memory[i] = k;
+            k = delta;
+        }
+        if self.m < self.n {
+            self.memory[self.m] = k;
+            self.m += 1;
+        }
+        k
+    }
+
+    /// Decode the next value in the sequence.
+    fn decode(&mut self, mut k: i32) -> i32 {
+        if self.m < self.n {
+            self.m += 1;
+        }
+        for i in (0..self.m).rev() {
+            k = self.memory[i].wrapping_add(k);
+            self.memory[i] = k;
+        }
+        k
+    }
+}
+
+fn encode_varint(mut value: u64, out: &mut Vec<u8>) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn decode_varint(data: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut value = 0_u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *data.get(*pos)?;
+        *pos += 1;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+fn decode_varint32(data: &[u8], pos: &mut usize) -> Option<u32> {
+    u32::try_from(decode_varint(data, pos)?).ok()
+}
+
+fn zigzag_encode(value: i32) -> u32 {
+    ((value << 1) ^ (value >> 31)) as u32
+}
+
+fn zigzag_decode(value: u32) -> i32 {
+    ((value >> 1) as i32) ^ -((value & 1) as i32)
+}
+
+/// Spreads the bits of "x" out to the even bit positions of the result.
+fn spread_bits(x: u32) -> u64 {
+    let mut x = x as u64;
+    x = (x | (x << 16)) & 0x0000_ffff_0000_ffff;
+    x = (x | (x << 8)) & 0x00ff_00ff_00ff_00ff;
+    x = (x | (x << 4)) & 0x0f0f_0f0f_0f0f_0f0f;
+    x = (x | (x << 2)) & 0x3333_3333_3333_3333;
+    (x | (x << 1)) & 0x5555_5555_5555_5555
+}
+
+/// The inverse of `spread_bits`, ignoring the odd bit positions of "x".
+fn compact_bits(x: u64) -> u32 {
+    let mut x = x & 0x5555_5555_5555_5555;
+    x = (x | (x >> 1)) & 0x3333_3333_3333_3333;
+    x = (x | (x >> 2)) & 0x0f0f_0f0f_0f0f_0f0f;
+    x = (x | (x >> 4)) & 0x00ff_00ff_00ff_00ff;
+    x = (x | (x >> 8)) & 0x0000_ffff_0000_ffff;
+    ((x | (x >> 16)) & 0xffff_ffff) as u32
+}
+
+/// Interleaves the bits of "a" (even positions) and "b" (odd positions).
+fn interleave(a: u32, b: u32) -> u64 {
+    spread_bits(a) | (spread_bits(b) << 1)
+}
+
+fn deinterleave(x: u64) -> (u32, u32) {
+    (compact_bits(x), compact_bits(x >> 1))
+}
+
+/// Returns the (pi, qi) coordinate of the level-"level" cell containing the
+/// given si- or ti-coordinate.
+fn siti_to_piqi(si: u32, level: i32) -> u32 {
+    si.min(MAX_SITI - 1) >> (MAX_CELL_LEVEL + 1 - level)
+}
+
+fn piqi_to_st(pi: u32, level: i32) -> f64 {
+    // We want to recover the position at the center of the cell.  If the
+    // point was snapped to the center of the cell, then modf(s * 2^level) ==
+    // 0.5.  Inverting siti_to_piqi() gives s = (pi + 0.5) / 2^level.
+    (pi as f64 + 0.5) / (1_u64 << level) as f64
+}
+
+fn face_piqi_to_xyz(face: i32, pi: u32, qi: u32, level: i32) -> S2Point {
+    face_uv_to_xyz(
+        face,
+        st_to_uv(piqi_to_st(pi, level)),
+        st_to_uv(piqi_to_st(qi, level)),
+    )
+    .normalize()
+}
+
+/// The number of bytes used to encode the first point.
+fn first_point_bytes(level: i32) -> usize {
+    (level as usize).div_ceil(8) * 2
+}
+
+/// Encode the given points in the compressed format described above,
+/// appending the result to "out".  Points that are the center of a cell at
+/// the given level usually take only a byte or two each; all other points
+/// additionally take 24 bytes plus the varint encoding of their index.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{decode_points_compressed, encode_points_compressed, S2CellId, S2Point};
+///
+/// let id = S2CellId::from_point(&S2Point::new(0.5, 0.5, 0.7)).parent_at_level(20);
+/// let points = vec![
+///     S2Point::from(id),
+///     S2Point::from(id.next()),
+///     S2Point::new(0.0, 0.6, 0.8),
+/// ];
+///
+/// let mut encoded = Vec::new();
+/// encode_points_compressed(&points, 20, &mut encoded);
+/// assert!(encoded.len() < 3 * 24);
+/// assert_eq!(decode_points_compressed(&encoded, 20), Some(points));
+/// ```
+pub fn encode_points_compressed(points: &[S2Point], level: i32, out: &mut Vec<u8>) {
+    debug_assert!((0..=S2CellId::MAX_LEVEL).contains(&level));
+
+    let mut faces: Vec<FaceRun> = Vec::new();
+    let mut vertices_pi_qi = Vec::with_capacity(points.len());
+    let mut off_center = Vec::new();
+    for (i, p) in points.iter().enumerate() {
+        let point = S2XYZFaceSiTi::new(*p);
+        match faces.last_mut() {
+            Some(run) if run.face == point.face => run.count += 1,
+            _ => faces.push(FaceRun {
+                face: point.face,
+                count: 1,
+            }),
+        }
+        vertices_pi_qi.push((
+            siti_to_piqi(*point.si, level),
+            siti_to_piqi(*point.ti, level),
+        ));
+        if point.cell_level != level {
+            off_center.push(i);
+        }
+    }
+    encode_varint(points.len() as u64, out);
+    for run in &faces {
+        run.encode(out);
+    }
+
+    let mut pi_coder = NthDerivativeCoder::new(DERIVATIVE_ENCODING_ORDER);
+    let mut qi_coder = NthDerivativeCoder::new(DERIVATIVE_ENCODING_ORDER);
+    for (i, &(pi, qi)) in vertices_pi_qi.iter().enumerate() {
+        let pi = pi_coder.encode(pi as i32);
+        let qi = qi_coder.encode(qi as i32);
+        if i == 0 {
+            // The first point will be just the (pi, qi) coordinates of the
+            // S2Point.  NthDerivativeCoder will not save anything in that
+            // case, so we encode in fixed format rather than varint to avoid
+            // the varint overhead.  Do not zigzag encode it, since it cannot
+            // be negative.
+            let bytes = interleave(pi as u32, qi as u32).to_le_bytes();
+            out.extend_from_slice(&bytes[..first_point_bytes(level)]);
+        } else {
+            // Zigzag encode, as varint requires the maximum number of bytes
+            // for negative numbers.
+            encode_varint(interleave(zigzag_encode(pi), zigzag_encode(qi)), out);
+        }
+    }
+
+    encode_varint(off_center.len() as u64, out);
+    for index in off_center {
+        encode_varint(index as u64, out);
+        let p = &points[index];
+        for k in 0..3 {
+            out.extend_from_slice(&p[k].to_le_bytes());
+        }
+    }
+}
+
+/// Decode points encoded with `encode_points_compressed` (or the C++
+/// `S2EncodePointsCompressed`) using the same level, starting at the
+/// beginning of "data".  Bytes following the encoded points are ignored.
+/// The decoded points are bit-for-bit identical to the encoded ones.
+/// Returns None if the level is out of range or the data is truncated or
+/// otherwise invalid.
+pub fn decode_points_compressed(data: &[u8], level: i32) -> Option<Vec<S2Point>> {
+    if !(0..=S2CellId::MAX_LEVEL).contains(&level) {
+        return None;
+    }
+
+    let mut pos = 0;
+    let num_points = decode_varint32(data, &mut pos)? as usize;
+
+    let mut faces = Vec::new();
+    let mut num_faces_parsed = 0_usize;
+    while num_faces_parsed < num_points {
+        let run = FaceRun::decode(data, &mut pos)?;
+        num_faces_parsed += run.count as usize;
+        faces.push(run);
+    }
+
+    let limit = 1_u32 << level;
+    let mut points = Vec::with_capacity(num_points.min(data.len()));
+    let mut pi_coder = NthDerivativeCoder::new(DERIVATIVE_ENCODING_ORDER);
+    let mut qi_coder = NthDerivativeCoder::new(DERIVATIVE_ENCODING_ORDER);
+    for face in faces
+        .iter()
+        .flat_map(|run| core::iter::repeat_n(run.face, run.count as usize))
+        .take(num_points)
+    {
+        let (pi, qi) = if points.is_empty() {
+            let n = first_point_bytes(level);
+            let mut bytes = [0_u8; 8];
+            bytes[..n].copy_from_slice(data.get(pos..pos + n)?);
+            pos += n;
+            let (pi, qi) = deinterleave(u64::from_le_bytes(bytes));
+            (pi_coder.decode(pi as i32), qi_coder.decode(qi as i32))
+        } else {
+            let (pi, qi) = deinterleave(decode_varint(data, &mut pos)?);
+            (
+                pi_coder.decode(zigzag_decode(pi)),
+                qi_coder.decode(zigzag_decode(qi)),
+            )
+        };
+        // (pi, qi) are in the range [0, 2^level).
+        let (pi, qi) = (pi as u32, qi as u32);
+        if pi >= limit || qi >= limit {
+            return None;
+        }
+        points.push(face_piqi_to_xyz(face, pi, qi, level));
+    }
+
+    let num_off_center = decode_varint32(data, &mut pos)? as usize;
+    if num_off_center > num_points {
+        return None;
+    }
+    for _ in 0..num_off_center {
+        let index = decode_varint32(data, &mut pos)? as usize;
+        let bytes = data.get(pos..pos + 24)?;
+        pos += 24;
+        let coord = |k: usize| f64::from_le_bytes(bytes[8 * k..8 * k + 8].try_into().unwrap());
+        *points.get_mut(index)? = S2Point::new(coord(0), coord(1), coord(2));
+    }
+    Some(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::s2::{s2testing::random_point, IjCoord};
+
+    fn assert_bit_exact(a: &[S2Point], b: &[S2Point]) {
+        assert_eq!(a.len(), b.len());
+        for (p, q) in a.iter().zip(b) {
+            for k in 0..3 {
+                assert_eq!(p[k].to_bits(), q[k].to_bits());
+            }
+        }
+    }
+
+    fn encode(points: &[S2Point], level: i32) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        encode_points_compressed(points, level, &mut encoded);
+        encoded
+    }
+
+    /// Returns the center of the level-"level" cell with the given (pi, qi)
+    /// coordinates.
+    fn piqi_center(face: i32, pi: i32, qi: i32, level: i32) -> S2Point {
+        let shift = S2CellId::MAX_LEVEL - level;
+        S2Point::from(
+            S2CellId::from_face_ij(face, IjCoord(pi << shift), IjCoord(qi << shift))
+                .parent_at_level(level),
+        )
+    }
+
+    /// Returns a polyline of "n" vertices that wanders across the sphere in
+    /// small steps, with every vertex snapped to the center of a cell at the
+    /// given level.
+    fn snapped_polyline(rng: &mut StdRng, n: usize, step: f64, level: i32) -> Vec<S2Point> {
+        let mut p = random_point(rng);
+        let mut dir = p.ortho();
+        (0..n)
+            .map(|_| {
+                let id = S2CellId::from_point(&p).parent_at_level(level);
+                let jitter = random_point(rng) * 0.2;
+                dir = (dir + jitter - p * dir.dot_prod(&p)).normalize();
+                p = (p + dir * step).normalize();
+                S2Point::from(id)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_coding_primitives() {
+        for value in [0, 1, 127, 128, 300, u32::MAX as u64, u64::MAX] {
+            let mut out = Vec::new();
+            encode_varint(value, &mut out);
+            let mut pos = 0;
+            assert_eq!(decode_varint(&out, &mut pos), Some(value));
+            assert_eq!(pos, out.len());
+        }
+        for value in [0, 1, -1, i32::MAX, i32::MIN] {
+            assert_eq!(zigzag_decode(zigzag_encode(value)), value);
+        }
+        assert_eq!(zigzag_encode(-5), 9);
+        assert_eq!(interleave(0b101, 0b11), 0b11011);
+        assert_eq!(interleave(u32::MAX, 0), 0x5555_5555_5555_5555);
+        let mut rng = StdRng::seed_from_u64(78);
+        for _ in 0..100 {
+            let (a, b) = (rng.gen::<u32>(), rng.gen::<u32>());
+            assert_eq!(deinterleave(interleave(a, b)), (a, b));
+        }
+
+        let values = [7, -3, 12, i32::MAX, i32::MIN, 0, 5];
+        let mut encoder = NthDerivativeCoder::new(2);
+        let mut decoder = NthDerivativeCoder::new(2);
+        let encoded: Vec<i32> = values.iter().map(|&k| encoder.encode(k)).collect();
+        assert_eq!(&encoded[..3], [7, -10, 25]);
+        let decoded: Vec<i32> = encoded.iter().map(|&k| decoder.decode(k)).collect();
+        assert_eq!(decoded, values);
+    }
+
+    // The expected encodings below were derived by hand by following the C++
+    // S2EncodePointsCompressed step by step.
+
+    #[test]
+    fn test_golden_single_face_center() {
+        // num_points = 1, face run (face 0, count 1) = 6, no (pi, qi) bytes
+        // at level 0, no off-center points.
+        let points = [S2Point::new(1.0, 0.0, 0.0)];
+        let encoded = encode(&points, 0);
+        assert_eq!(encoded, [0x01, 0x06, 0x00]);
+        assert_eq!(decode_points_compressed(&encoded, 0), Some(points.to_vec()));
+    }
+
+    #[test]
+    fn test_golden_face_runs() {
+        // Face runs (0, 2) and (5, 1), followed by the zero derivatives of the
+        // second and third points.
+        let points = [
+            S2Point::new(1.0, 0.0, 0.0),
+            S2Point::new(1.0, 0.0, 0.0),
+            S2Point::new(0.0, 0.0, -1.0),
+        ];
+        let encoded = encode(&points, 0);
+        assert_eq!(encoded, [0x03, 0x0c, 0x0b, 0x00, 0x00, 0x00]);
+        assert_eq!(decode_points_compressed(&encoded, 0), Some(points.to_vec()));
+    }
+
+    #[test]
+    fn test_golden_derivatives_and_off_center() {
+        // Level 3 cells (pi, qi) = (1, 2), (2, 2), (4, 3) on face 2, followed
+        // by the center of the level 2 cell containing (1, 3), which is
+        // stored as an off-center point.
+        let off_center = piqi_center(2, 0, 1, 2);
+        let points = [
+            piqi_center(2, 1, 2, 3),
+            piqi_center(2, 2, 2, 3),
+            piqi_center(2, 4, 3, 3),
+            off_center,
+        ];
+        let mut expected = vec![
+            0x04, // num_points
+            0x1a, // face run (face 2, count 4)
+            0x09, 0x00, // first point: interleave(1, 2), 2 bytes at level 3
+            0x04, // deltas (1, 0), zigzag (2, 0)
+            0x0c, // 2nd derivatives (1, 1), zigzag (2, 2)
+            0x43, // 2nd derivatives (-5, -1), zigzag (9, 1)
+            0x01, // num_off_center
+            0x03, // off-center index
+        ];
+        for k in 0..3 {
+            expected.extend_from_slice(&off_center[k].to_le_bytes());
+        }
+        let encoded = encode(&points, 3);
+        assert_eq!(encoded, expected);
+        assert_bit_exact(&decode_points_compressed(&encoded, 3).unwrap(), &points);
+    }
+
+    #[test]
+    fn test_golden_leaf_level() {
+        // At level 30 the first point takes 8 bytes.
+        let points = [piqi_center(1, 5, 3, 30)];
+        let encoded = encode(&points, 30);
+        assert_eq!(encoded, [0x01, 0x07, 27, 0, 0, 0, 0, 0, 0, 0, 0x00]);
+        assert_eq!(
+            decode_points_compressed(&encoded, 30),
+            Some(points.to_vec())
+        );
+    }
+
+    #[test]
+    fn test_first_point_on_face_edge() {
+        // The si-coordinate of a point on the edge of a face is MAX_SITI,
+        // which must be clamped so that the (pi, qi) value fits in "level"
+        // bits.
+        let p = S2Point::new(-1.0, 1.0, 0.0).normalize();
+        let (face, si, _, _) = xyz_to_face_siti(&p);
+        assert_eq!((face, *si), (1, MAX_SITI));
+        for level in [1, 14, 30] {
+            let encoded = encode(&[p], level);
+            let n = first_point_bytes(level);
+            let mut bytes = [0_u8; 8];
+            bytes[..n].copy_from_slice(&encoded[2..2 + n]);
+            let (pi, qi) = deinterleave(u64::from_le_bytes(bytes));
+            assert_eq!((pi, qi), ((1 << level) - 1, 1 << (level - 1)));
+            assert_bit_exact(&decode_points_compressed(&encoded, level).unwrap(), &[p]);
+        }
+    }
+
+    #[test]
+    fn test_snapped_polyline() {
+        let mut rng = StdRng::seed_from_u64(2);
+        // Vertices about 100m apart on the Earth's surface, snapped to ~10m
+        // cells.
+        let points = snapped_polyline(&mut rng, 1000, 1.5e-5, 20);
+        let encoded = encode(&points, 20);
+        let bytes_per_point = encoded.len() as f64 / points.len() as f64;
+        assert!(bytes_per_point < 3.0, "{bytes_per_point} bytes per point");
+
+        let decoded = decode_points_compressed(&encoded, 20).unwrap();
+        assert_bit_exact(&points, &decoded);
+    }
+
+    #[test]
+    fn test_straight_line_compresses_well() {
+        // Equally spaced cells along a line have zero second derivatives, so
+        // all points after the second take one byte each.
+        let points: Vec<S2Point> = (0..100)
+            .map(|k| piqi_center(3, 1000 + 3 * k, 2000 + 7 * k, 20))
+            .collect();
+        let encoded = encode(&points, 20);
+        assert_eq!(encoded.len(), 1 + 2 + 6 + 2 + 98 + 1);
+        assert_bit_exact(&decode_points_compressed(&encoded, 20).unwrap(), &points);
+    }
+
+    #[test]
+    fn test_mixed_points() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut points = snapped_polyline(&mut rng, 50, 1e-3, 12);
+        for i in (0..points.len()).step_by(3) {
+            points[i] = random_point(&mut rng);
+        }
+        // Cell centers at other levels are stored exactly.
+        points.push(S2Point::from(S2CellId::from_face(4).child(2)));
+        points.push(S2Point::from(S2CellId::from_point(&points[1])));
+
+        for level in [0, 12, 30] {
+            let encoded = encode(&points, level);
+            let decoded = decode_points_compressed(&encoded, level).unwrap();
+            assert_bit_exact(&points, &decoded);
+        }
+    }
+
+    #[test]
+    fn test_empty_and_invalid() {
+        let encoded = encode(&[], 10);
+        assert_eq!(encoded, [0x00, 0x00]);
+        assert_eq!(decode_points_compressed(&encoded, 10), Some(vec![]));
+        assert_eq!(decode_points_compressed(&encoded, 31), None);
+
+        let points = [S2Point::new(1.0, 0.0, 0.0), S2Point::new(0.6, 0.8, 0.0)];
+        let encoded = encode(&points, 10);
+        for len in 0..encoded.len() {
+            assert_eq!(decode_points_compressed(&encoded[..len], 10), None);
+        }
+
+        // A face run with a count of zero.
+        assert_eq!(
+            decode_points_compressed(&[0x01, 0x02, 0, 0, 0, 0, 0], 10),
+            None
+        );
+        // A (pi, qi) value past the last cell at the level.
+        assert_eq!(
+            decode_points_compressed(&[0x01, 0x06, 0, 0, 0x10, 0, 0], 10),
+            None
+        );
+        // An off-center index past the last point.
+        let mut encoded = encode(&[S2Point::new(0.6, 0.8, 0.0)], 10);
+        let n = encoded.len();
+        encoded[n - 25] = 1;
+        assert_eq!(decode_points_compressed(&encoded, 10), None);
+    }
+}
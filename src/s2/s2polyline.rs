@@ -0,0 +1,258 @@
+// Copyright 2005 Google Inc. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS-IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+// Original Author: ericv@google.com (Eric Veach)
+
+use alloc::vec::Vec;
+
+use crate::{
+    s1::{S1Angle, S1ChordAngle},
+    s2::{
+        s2edge_distances::update_min_distance, s2latlng_rect_bounder::S2LatLngRectBounder,
+        CovererOptions, S2BufferedPolyline, S2CellUnion, S2LatLngRect, S2Point, S2RegionCoverer,
+    },
+};
+
+/// An S2Polyline represents a sequence of zero or more vertices connected by
+/// straight edges (geodesics). Edges of length 0 and 180 degrees are not
+/// allowed, i.e. adjacent vertices should not be identical or antipodal.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{S2LatLng, S2Polyline};
+///
+/// let line = S2Polyline::new(vec![
+///     S2LatLng::from_degrees(0.0, 0.0).to_point(),
+///     S2LatLng::from_degrees(0.0, 10.0).to_point(),
+/// ]);
+/// assert_eq!(line.num_vertices(), 2);
+/// assert_eq!(
+///     *line.vertex(1),
+///     S2LatLng::from_degrees(0.0, 10.0).to_point()
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct S2Polyline {
+    vertices: Vec<S2Point>,
+}
+
+impl S2Polyline {
+    /// Creates a polyline that connects the given vertices. Empty polylines
+    /// are allowed. Adjacent vertices should not be identical or antipodal.
+    /// All vertices should be unit length.
+    pub fn new(vertices: Vec<S2Point>) -> S2Polyline {
+        debug_assert!(vertices.iter().all(|v| v.is_unit_length()));
+        S2Polyline { vertices }
+    }
+
+    /// Returns the number of vertices in the polyline.
+    pub fn num_vertices(&self) -> usize {
+        self.vertices.len()
+    }
+
+    /// Returns the vertex with index "i", where 0 <= i < num_vertices().
+    pub fn vertex(&self, i: usize) -> &S2Point {
+        &self.vertices[i]
+    }
+
+    /// Returns all the vertices of the polyline.
+    pub fn vertices(&self) -> &[S2Point] {
+        &self.vertices
+    }
+
+    /// Returns a bounding latitude-longitude rectangle for the polyline,
+    /// including the latitude extremes attained along the interior of its
+    /// edges.
+    pub fn get_rect_bound(&self) -> S2LatLngRect {
+        let mut bounder = S2LatLngRectBounder::new();
+        for v in &self.vertices {
+            bounder.add_point(v);
+        }
+        bounder.get_bound()
+    }
+
+    /// Returns the minimum distance from the given point to the polyline, or
+    /// S1ChordAngle::infinity() if the polyline has no vertices. A polyline
+    /// with a single vertex is treated as that point.
+    pub fn get_distance(&self, point: &S2Point) -> S1ChordAngle {
+        let mut min_dist = S1ChordAngle::infinity();
+        match self.vertices.as_slice() {
+            [] => {}
+            [v] => min_dist = S1ChordAngle::from_points(point, v),
+            vertices => {
+                for edge in vertices.windows(2) {
+                    update_min_distance(point, &edge[0], &edge[1], &mut min_dist);
+                }
+            }
+        }
+        min_dist
+    }
+
+    /// Returns a covering of all points within the given distance of the
+    /// polyline, subject to the restrictions in "options". This is the
+    /// covering of the corresponding [`S2BufferedPolyline`] region.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s1::S1Angle;
+    /// use s2shell::s2::{CovererOptions, S2LatLng, S2Polyline};
+    ///
+    /// let line = S2Polyline::new(vec![
+    ///     S2LatLng::from_degrees(0.0, 0.0).to_point(),
+    ///     S2LatLng::from_degrees(0.0, 10.0).to_point(),
+    /// ]);
+    /// let mut options = CovererOptions::default();
+    /// options.set_max_cells(20);
+    /// let covering = line.get_covering(S1Angle::from_degrees(1.0), &options);
+    /// let near = S2LatLng::from_degrees(0.9, 5.0).to_point();
+    /// let far = S2LatLng::from_degrees(0.0, 30.0).to_point();
+    /// assert!(covering.contains_point(&near));
+    /// assert!(!covering.contains_point(&far));
+    /// ```
+    pub fn get_covering(&self, distance: S1Angle, options: &CovererOptions) -> S2CellUnion {
+        let region = S2BufferedPolyline::new(self.clone(), distance);
+        S2CellUnion::from_verbatim(S2RegionCoverer::new(options.clone()).get_covering(&region))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::s2::s2testing::{make_polyline, random_point, sample_near_polyline};
+    use crate::s2::{s2metrics::MAX_DIAG, S2Cell, S2LatLng, S2Region};
+
+    fn check_covering(line: &S2Polyline, distance: S1Angle, max_cells: i32, rng: &mut StdRng) {
+        let mut options = CovererOptions::default();
+        options.set_max_cells(max_cells);
+        let covering = line.get_covering(distance, &options);
+        assert!(covering.is_valid());
+
+        // Every point within "distance" of the polyline is covered.
+        for _ in 0..1000 {
+            let p = sample_near_polyline(line, distance, rng);
+            assert!(covering.contains_point(&p));
+        }
+
+        // No covered point is farther than "distance" plus the diameter of the
+        // largest covering cell.
+        let min_level = covering
+            .cell_ids()
+            .iter()
+            .map(|id| id.level())
+            .min()
+            .unwrap();
+        let max_distance =
+            S1Angle::from_radians(distance.radians() + MAX_DIAG.get_value(min_level));
+        let region = S2BufferedPolyline::new(line.clone(), max_distance);
+        for _ in 0..1000 {
+            let p = random_point(rng);
+            if covering.contains_point(&p) {
+                assert!(region.contains_point(&p));
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_distance() {
+        let line = make_polyline(&[(0.0, 0.0), (0.0, 10.0), (10.0, 10.0)]);
+        let d = S1Angle::from(line.get_distance(&S2LatLng::from_degrees(1.0, 5.0).to_point()));
+        assert!((d.degrees() - 1.0).abs() < 1e-13);
+        let d = S1Angle::from(line.get_distance(&S2LatLng::from_degrees(5.0, 12.0).to_point()));
+        assert!((d.degrees() - 2.0).abs() < 1e-2);
+        assert!(S2Polyline::default()
+            .get_distance(&S2Point::new(1.0, 0.0, 0.0))
+            .is_infinity());
+    }
+
+    #[test]
+    fn test_get_covering() {
+        let mut rng = StdRng::seed_from_u64(57);
+        let line = make_polyline(&[(0.0, 0.0), (3.0, 4.0), (2.0, 8.0), (-1.0, 9.0)]);
+        check_covering(&line, S1Angle::from_degrees(0.5), 8, &mut rng);
+        check_covering(&line, S1Angle::from_degrees(0.5), 100, &mut rng);
+        check_covering(&line, S1Angle::from_degrees(0.01), 50, &mut rng);
+        let long = make_polyline(&[(-60.0, -170.0), (70.0, 20.0), (0.0, 150.0)]);
+        check_covering(&long, S1Angle::from_degrees(5.0), 30, &mut rng);
+    }
+
+    #[test]
+    fn test_get_interior_covering() {
+        // Interior coverings keep subdividing cells that straddle the buffer
+        // boundary, so limit the level to keep the test fast.
+        let mut options = CovererOptions::default();
+        options.set_max_cells(100);
+        options.set_max_level(12);
+        let coverer = S2RegionCoverer::new(options);
+
+        let mut rng = StdRng::seed_from_u64(76);
+        let line = make_polyline(&[(0.0, 0.0), (3.0, 4.0), (2.0, 8.0), (-1.0, 9.0)]);
+        for (line, degrees) in [
+            (line.clone(), 0.5),
+            (line.clone(), 0.1),
+            (make_polyline(&[(45.0, 45.0)]), 1.0),
+        ] {
+            let distance = S1Angle::from_degrees(degrees);
+            let region = S2BufferedPolyline::new(line.clone(), distance);
+            let interior = coverer.get_interior_covering(&region);
+            assert!(interior.is_valid());
+            assert!(!interior.is_empty());
+            for id in interior.cell_ids() {
+                let cell = S2Cell::new(*id);
+                assert!(line.get_distance(&cell.get_center()) <= region.radius());
+                for k in 0..4 {
+                    assert!(line.get_distance(&cell.get_vertex(k)) <= region.radius());
+                }
+            }
+
+            // Points farther than "distance" from the polyline are never in
+            // the interior covering. Sample out to twice the distance so that
+            // both sides of the boundary are tested.
+            let twice = S1Angle::from_radians(2.0 * distance.radians());
+            for _ in 0..1000 {
+                let p = sample_near_polyline(&line, twice, &mut rng);
+                if interior.contains_point(&p) {
+                    assert!(region.contains_point(&p));
+                }
+            }
+        }
+
+        // A zero buffer distance has no interior.
+        let region = S2BufferedPolyline::new(line, S1Angle::zero());
+        assert!(coverer.get_interior_covering(&region).is_empty());
+    }
+
+    #[test]
+    fn test_get_covering_single_point() {
+        // A single-vertex polyline buffers to a cap.
+        let mut rng = StdRng::seed_from_u64(58);
+        let line = make_polyline(&[(45.0, 45.0)]);
+        check_covering(&line, S1Angle::from_degrees(1.0), 8, &mut rng);
+
+        // With a zero buffer distance the covering still contains the vertex.
+        let covering = line.get_covering(S1Angle::zero(), &CovererOptions::default());
+        assert!(covering.contains_point(line.vertex(0)));
+    }
+
+    #[test]
+    fn test_get_covering_empty() {
+        let covering = S2Polyline::default()
+            .get_covering(S1Angle::from_degrees(1.0), &CovererOptions::default());
+        assert!(covering.is_empty());
+    }
+}
@@ -0,0 +1,370 @@
+// Copyright 2016 Google Inc. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS-IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+// Original Author: ericv@google.com (Eric Veach)
+//
+// This file contains various predicates on points of the unit sphere, such
+// as the orientation of three points. Such predicates are the building blocks
+// of point-in-polygon tests, edge crossing tests, and ordering of edges.
+
+use crate::{s2::S2Point, util::math::exact_float::Expansion};
+
+/// Returns +1 if the points A, B, C are counterclockwise, -1 if the points
+/// are clockwise, and 0 if any two points are the same or the three points
+/// are collinear (i.e. they lie on a common great circle). This is the sign
+/// of the determinant of ABC, computed as (A x B) . C.
+///
+/// The result is exact: when the floating-point determinant is too close to
+/// zero for its sign to be trusted, it is recomputed using exact arithmetic.
+/// This means that 0 is returned only when the points are exactly collinear,
+/// and that the properties below hold even for nearly collinear points.
+///
+/// sign(a, b, c) == -sign(b, a, c) for all a, b, c, and the result is
+/// invariant under rotations of the arguments, i.e.
+/// sign(a, b, c) == sign(b, c, a) == sign(c, a, b).
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{sign, S2Point};
+///
+/// let a = S2Point::new(1.0, 0.0, 0.0);
+/// let b = S2Point::new(0.0, 1.0, 0.0);
+/// let c = S2Point::new(0.0, 0.0, 1.0);
+/// assert_eq!(sign(&a, &b, &c), 1);
+/// assert_eq!(sign(&b, &a, &c), -1);
+/// assert_eq!(sign(&a, &a, &c), 0);
+/// ```
+pub fn sign(a: &S2Point, b: &S2Point, c: &S2Point) -> i32 {
+    let sign = triage_sign(a, b, c);
+    if sign != 0 {
+        return sign;
+    }
+    expensive_sign(a, b, c)
+}
+
+/// Computes the sign of the determinant of ABC using ordinary floating-point
+/// arithmetic, and returns +1 or -1 only if that sign is certain to be
+/// correct. Returns 0 if the determinant is too close to zero for its sign
+/// to be trusted, in which case expensive_sign() should be called instead.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{triage_sign, S2Point};
+///
+/// let a = S2Point::new(0.1, 0.2, 0.3).normalize();
+/// let b = S2Point::new(0.3, -0.2, 0.7).normalize();
+/// assert_eq!(triage_sign(&a, &b, &S2Point::new(0.0, 0.0, 1.0)), -1);
+/// // The determinant of a nearly collinear triple is dominated by rounding
+/// // error, so its sign is uncertain.
+/// let c = (a + b).normalize();
+/// assert_ne!(a.cross_prod(&b).dot_prod(&c), 0.0);
+/// assert_eq!(triage_sign(&a, &b, &c), 0);
+/// ```
+pub fn triage_sign(a: &S2Point, b: &S2Point, c: &S2Point) -> i32 {
+    let det = a.cross_prod(b).dot_prod(c);
+
+    // The absolute error in the determinant is bounded by a small multiple of
+    // the "permanent" (the determinant with every term replaced by its
+    // absolute value). If the computed determinant is larger than this bound,
+    // its sign is correct.
+    let permanent = c[0].abs() * ((a[1] * b[2]).abs() + (a[2] * b[1]).abs())
+        + c[1].abs() * ((a[2] * b[0]).abs() + (a[0] * b[2]).abs())
+        + c[2].abs() * ((a[0] * b[1]).abs() + (a[1] * b[0]).abs());
+    let max_error = 8.0 * f64::EPSILON * permanent;
+    if det > max_error {
+        1
+    } else if det < -max_error {
+        -1
+    } else {
+        0
+    }
+}
+
+/// Returns the sign of the determinant of ABC for points whose orientation
+/// could not be determined by triage_sign(). Returns 0 if any two points are
+/// the same, and otherwise calls exact_sign().
+pub fn expensive_sign(a: &S2Point, b: &S2Point, c: &S2Point) -> i32 {
+    // Return zero if and only if two points are the same. This ensures (1)
+    // that the exact calculation is skipped for the most common degenerate
+    // case, and (2) that sign(a, b, c) == 0 whenever two arguments are equal.
+    if a == b || b == c || c == a {
+        return 0;
+    }
+    exact_sign(a, b, c)
+}
+
+/// Computes the sign of the determinant of ABC exactly, using floating-point
+/// expansions (sums of non-overlapping doubles) so that no rounding occurs.
+/// Returns 0 only if the points are exactly collinear. This is much slower
+/// than the plain floating-point calculation and is only used when the sign
+/// of that calculation cannot be trusted.
+///
+/// The calculation is exact provided that the products of coordinates do not
+/// underflow, which holds for all points of unit length whose coordinates are
+/// not subnormal.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{exact_sign, S2Point};
+///
+/// // A determinant of 2^-60 * (2^-30 - 2^-60), which is far below the
+/// // rounding error of the plain floating-point calculation.
+/// let a = S2Point::new(1.0, 2f64.powi(-30), 0.0);
+/// let b = S2Point::new(1.0 + 2f64.powi(-30), 2f64.powi(-29), 0.0);
+/// let c = S2Point::new(1.0, 0.0, 2f64.powi(-60));
+/// assert_eq!(exact_sign(&a, &b, &c), 1);
+/// assert_eq!(
+///     exact_sign(&a, &b, &S2Point::new(2.0, 2f64.powi(-29), 0.0)),
+///     0
+/// );
+/// ```
+pub fn exact_sign(a: &S2Point, b: &S2Point, c: &S2Point) -> i32 {
+    let cross_x = Expansion::difference_of_products(a[1], b[2], a[2], b[1]);
+    let cross_y = Expansion::difference_of_products(a[2], b[0], a[0], b[2]);
+    let cross_z = Expansion::difference_of_products(a[0], b[1], a[1], b[0]);
+    let mut det = cross_x.scale(c[0]);
+    for &x in cross_y
+        .scale(c[1])
+        .terms()
+        .iter()
+        .chain(cross_z.scale(c[2]).terms())
+    {
+        det.grow(x);
+    }
+    det.sign()
+}
+
+/// Given 4 points on the unit sphere, return true if the edges OA, OB, and
+/// OC are encountered in that order while sweeping CCW around the point O.
+/// You can think of this as testing whether A <= B <= C with respect to the
+/// CCW ordering around O that starts at A, or equivalently, whether B is
+/// contained in the range of angles (inclusive) that starts at A and extends
+/// CCW to C. Properties:
+///
+///  (1) If ordered_ccw(a,b,c,o) && ordered_ccw(b,a,c,o), then a == b
+///  (2) If ordered_ccw(a,b,c,o) && ordered_ccw(a,c,b,o), then b == c
+///  (3) If ordered_ccw(a,b,c,o) && ordered_ccw(c,b,a,o), then a == b == c
+///  (4) If a == b or b == c, then ordered_ccw(a,b,c,o) is true
+///  (5) Otherwise if a == c, then ordered_ccw(a,b,c,o) is false
+///
+/// These are the inclusive semantics of the C++ S2 library, which polygon
+/// vertex traversal relies on. See [`S2Point::ordered_ccw`] for a version
+/// that requires B to be strictly between A and C.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::s2::{ordered_ccw, S2Point};
+///
+/// // Three directions around the north pole, in CCW order.
+/// let o = S2Point::new(0.0, 0.0, 1.0);
+/// let a = S2Point::new(1.0, 0.0, 0.0);
+/// let b = S2Point::new(0.0, 1.0, 0.0);
+/// let c = S2Point::new(-1.0, 0.0, 0.0);
+/// assert!(ordered_ccw(&a, &b, &c, &o));
+/// assert!(!ordered_ccw(&c, &b, &a, &o));
+/// assert!(ordered_ccw(&a, &a, &c, &o));
+/// ```
+pub fn ordered_ccw(a: &S2Point, b: &S2Point, c: &S2Point, o: &S2Point) -> bool {
+    // The last inequality below is ">" rather than ">=" so that we return
+    // true if A == B or B == C, and otherwise false if A == C. Recall that
+    // sign(x,y,z) == -sign(z,y,x) for all x,y,z.
+    let mut sum = 0;
+    if sign(b, o, a) >= 0 {
+        sum += 1;
+    }
+    if sign(c, o, b) >= 0 {
+        sum += 1;
+    }
+    if sign(a, o, c) > 0 {
+        sum += 1;
+    }
+    sum >= 2
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::s2::s2testing::random_point;
+
+    #[test]
+    fn test_counterclockwise_and_clockwise() {
+        let a = S2Point::new(1.0, 0.0, 0.0);
+        let b = S2Point::new(0.0, 1.0, 0.0);
+        let c = S2Point::new(0.0, 0.0, 1.0);
+        assert_eq!(sign(&a, &b, &c), 1);
+        assert_eq!(sign(&b, &c, &a), 1);
+        assert_eq!(sign(&c, &a, &b), 1);
+        assert_eq!(sign(&c, &b, &a), -1);
+        assert_eq!(sign(&a, &c, &b), -1);
+        assert_eq!(sign(&b, &a, &c), -1);
+    }
+
+    #[test]
+    fn test_degenerate() {
+        let a = S2Point::new(1.0, 0.0, 0.0);
+        let b = S2Point::new(0.0, 1.0, 0.0);
+        // Duplicate points.
+        assert_eq!(sign(&a, &a, &b), 0);
+        assert_eq!(sign(&a, &b, &b), 0);
+        // Three distinct points on the equator.
+        let c = S2Point::new(1.0, 1.0, 0.0).normalize();
+        assert_eq!(sign(&a, &b, &c), 0);
+        // Antipodal points are collinear with everything.
+        assert_eq!(sign(&a, &-a, &b), 0);
+    }
+
+    #[test]
+    fn test_swapping_arguments_flips_sign() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..1000 {
+            let a = random_point(&mut rng);
+            let b = random_point(&mut rng);
+            let c = random_point(&mut rng);
+            let s = sign(&a, &b, &c);
+            assert_ne!(s, 0);
+            assert_eq!(sign(&b, &a, &c), -s);
+            assert_eq!(sign(&a, &c, &b), -s);
+            assert_eq!(sign(&c, &b, &a), -s);
+            assert_eq!(sign(&b, &c, &a), s);
+        }
+    }
+
+    #[test]
+    fn test_exactly_collinear() {
+        // A tiny but nonzero determinant: 2^-60 * (2^-30 - 2^-60).
+        let a = S2Point::new(1.0, 2f64.powi(-30), 0.0);
+        let b = S2Point::new(1.0 + 2f64.powi(-30), 2f64.powi(-29), 0.0);
+        let c = S2Point::new(1.0, 0.0, 2f64.powi(-60));
+        assert_eq!(sign(&a, &b, &c), 1);
+        assert_eq!(exact_sign(&a, &b, &c), 1);
+        assert_eq!(exact_sign(&b, &a, &c), -1);
+
+        // Exactly collinear points whose coordinates are not exactly
+        // representable in binary, so the floating-point determinant is not
+        // trusted.
+        let a = S2Point::new(0.1, 0.2, 0.3);
+        let b = S2Point::new(0.3, -0.2, 0.7);
+        assert_eq!(sign(&a, &b, &(a * 2.0)), 0);
+        assert_eq!(sign(&a, &b, &-b), 0);
+        assert_eq!(exact_sign(&a, &(a * 0.5), &b), 0);
+        // Scaling by 3 rounds the coordinates, so the points are no longer
+        // exactly collinear.
+        assert_ne!(exact_sign(&a, &(a * 3.0), &b), 0);
+    }
+
+    #[test]
+    fn test_nearly_collinear_consistency() {
+        // Points on a common great circle up to rounding error. The plain
+        // floating-point determinant often gives inconsistent signs for such
+        // points under permutation; the exact fallback must not.
+        let mut rng = StdRng::seed_from_u64(2);
+        for _ in 0..1000 {
+            let a = random_point(&mut rng);
+            let b = random_point(&mut rng);
+            let t = rng.gen_range(-2.0..2.0);
+            let c = (a + (b - a) * t).normalize();
+            let s = sign(&a, &b, &c);
+            assert_eq!(s, exact_sign(&a, &b, &c));
+            assert_eq!(sign(&b, &c, &a), s);
+            assert_eq!(sign(&c, &a, &b), s);
+            assert_eq!(sign(&b, &a, &c), -s);
+            assert_eq!(sign(&a, &c, &b), -s);
+            assert_eq!(sign(&c, &b, &a), -s);
+        }
+    }
+
+    #[test]
+    fn test_ordered_ccw() {
+        let o = S2Point::new(0.0, 0.0, 1.0);
+        let a = S2Point::new(1.0, 0.0, 0.0);
+        let b = S2Point::new(0.0, 1.0, 0.0);
+        let c = S2Point::new(-1.0, 0.0, 0.0);
+        assert!(ordered_ccw(&a, &b, &c, &o));
+        assert!(!ordered_ccw(&a, &c, &b, &o));
+        assert!(ordered_ccw(&b, &c, &a, &o));
+        // Properties (4) and (5).
+        assert!(ordered_ccw(&a, &a, &c, &o) && ordered_ccw(&a, &c, &c, &o));
+        assert!(!ordered_ccw(&a, &b, &a, &o));
+
+        // Directions around the north pole at 0, 90, 180 and 270 degrees.
+        let p = [a, b, c, S2Point::new(0.0, -1.0, 0.0)];
+        for i in 0..4 {
+            let (a, b, c) = (&p[i], &p[(i + 1) % 4], &p[(i + 2) % 4]);
+            // Rotating the starting direction preserves the CCW order.
+            assert!(ordered_ccw(a, b, c, &o));
+            assert!(ordered_ccw(b, c, a, &o));
+            assert!(ordered_ccw(c, a, b, &o));
+            assert!(!ordered_ccw(c, b, a, &o));
+            assert!(!ordered_ccw(a, c, b, &o));
+            assert!(!ordered_ccw(b, a, c, &o));
+            assert!(ordered_ccw(a, a, a, &o));
+        }
+    }
+
+    #[test]
+    fn test_ordered_ccw_properties() {
+        let mut rng = StdRng::seed_from_u64(70);
+        for _ in 0..1000 {
+            let o = random_point(&mut rng);
+            let a = random_point(&mut rng);
+            let b = random_point(&mut rng);
+            let c = random_point(&mut rng);
+            // Exactly one of the two cyclic orders holds for distinct
+            // directions, and each is invariant under rotation.
+            let ccw = ordered_ccw(&a, &b, &c, &o);
+            assert_eq!(ordered_ccw(&b, &c, &a, &o), ccw);
+            assert_eq!(ordered_ccw(&c, &a, &b, &o), ccw);
+            assert_ne!(ordered_ccw(&c, &b, &a, &o), ccw);
+            // Properties (1) and (2).
+            assert!(!(ccw && ordered_ccw(&b, &a, &c, &o)));
+            assert!(!(ccw && ordered_ccw(&a, &c, &b, &o)));
+        }
+    }
+
+    #[test]
+    fn test_naive_sign_is_unreliable() {
+        // Nearly collinear triples for which the plain floating-point
+        // determinant frequently has the wrong sign. triage_sign() must never
+        // report a wrong sign, and sign() must always agree with exact_sign().
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut naive_errors = 0;
+        let mut triaged = 0;
+        for _ in 0..1000 {
+            let a = random_point(&mut rng);
+            let b = random_point(&mut rng);
+            let c = (a * rng.gen_range(-1.0..1.0) + b * rng.gen_range(-1.0..1.0)).normalize();
+            let exact = exact_sign(&a, &b, &c);
+            let naive = a.cross_prod(&b).dot_prod(&c);
+            if (naive > 0.0 && exact <= 0) || (naive < 0.0 && exact >= 0) {
+                naive_errors += 1;
+            }
+            let triage = triage_sign(&a, &b, &c);
+            assert!(triage == 0 || triage == exact);
+            if triage == 0 {
+                triaged += 1;
+                assert_eq!(expensive_sign(&a, &b, &c), exact);
+            }
+            assert_eq!(sign(&a, &b, &c), exact);
+        }
+        assert!(naive_errors > 0);
+        assert!(triaged >= naive_errors);
+    }
+}
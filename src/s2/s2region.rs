@@ -15,10 +15,12 @@
 
 // Original Author: ericv@google.com (Eric Veach)
 
-use crate::s2::{
-    s2cap::S2Cap, s2cell::S2Cell, s2cell_id::S2CellId, s2latlng_rect::S2LatLngRect,
-    s2point::S2Point,
-};
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, vec::Vec};
+
+#[cfg(feature = "alloc")]
+use crate::s2::s2cell_id::S2CellId;
+use crate::s2::{s2cap::S2Cap, s2cell::S2Cell, s2latlng_rect::S2LatLngRect, s2point::S2Point};
 
 /// An S2Region represents a two-dimensional region over the unit sphere.
 /// It is an abstract interface with various concrete subtypes.
@@ -27,7 +29,31 @@ use crate::s2::{
 /// approximated as simpler regions.  So rather than having a wide variety
 /// of virtual methods that are implemented by all subtypes, the interface
 /// is restricted to methods that are useful for computing approximations.
-pub trait S2Region: Clone {
+///
+/// The trait is object safe, so heterogeneous regions can be stored as
+/// `Box<dyn S2Region>` and copied with `clone_region()`.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::{
+///     s1::S1Angle,
+///     s2::{S2Cap, S2Cell, S2Point, S2Region},
+/// };
+///
+/// let regions: Vec<Box<dyn S2Region>> = vec![
+///     Box::new(S2Cap::from_center_angle(
+///         S2Point::new(0.0, 0.0, 1.0),
+///         S1Angle::from_degrees(10.0),
+///     )),
+///     Box::new(S2Cell::from_face(2)),
+/// ];
+/// let copies = regions.clone();
+/// for region in &copies {
+///     assert!(region.contains_point(&S2Point::new(0.0, 0.0, 1.0)));
+/// }
+/// ```
+pub trait S2Region: S2RegionClone {
     /// Returns a bounding spherical cap that contains the region.  The bound may
     /// not be tight.
     fn get_cap_bound(&self) -> S2Cap;
@@ -55,15 +81,99 @@ pub trait S2Region: Clone {
     /// `GetCapBound().GetCellUnionBound(cell_ids)` and
     /// `GetRectBound().GetCellUnionBound(cell_ids)` are always valid
     /// implementations, but something better should be done if possible.
-    fn get_cell_union_bound(&self, cell_ids: &[S2CellId]);
+    ///
+    /// Any previous contents of "cell_ids" are discarded.
+    #[cfg(feature = "alloc")]
+    fn get_cell_union_bound(&self, cell_ids: &mut Vec<S2CellId>);
 
-    /// Returns true if the region completely contains the given cell, otherwise
-    /// either the region does not contain the cell or the containment relationship
-    /// could not be determined.
-    fn contains_cell(&self, cell: &S2Cell) -> Option<bool>;
+    /// Returns true if the region completely contains the given cell. If this
+    /// method returns false, either the region does not contain the cell or the
+    /// containment relationship could not be determined.
+    fn contains_cell(&self, cell: &S2Cell) -> bool;
+
+    /// If this method returns false, the region does not intersect the given
+    /// cell. Otherwise, either region intersects the cell, or the intersection
+    /// relationship could not be determined.
+    fn may_intersect_cell(&self, cell: &S2Cell) -> bool;
 
     /// Returns true if and only if the given point is contained by the region.
     /// The point 'p' is generally required to be unit length, although some
     /// subtypes may relax this restriction.
     fn contains_point(&self, point: &S2Point) -> bool;
 }
+
+/// Helper trait that allows `Box<dyn S2Region>` to be cloned. It is
+/// implemented automatically for every region type that implements `Clone`,
+/// so region implementations never need to implement it by hand.
+pub trait S2RegionClone {
+    /// Returns a deep copy of the region.
+    #[cfg(feature = "alloc")]
+    fn clone_region(&self) -> Box<dyn S2Region>;
+}
+
+impl<T: S2Region + Clone + 'static> S2RegionClone for T {
+    #[cfg(feature = "alloc")]
+    fn clone_region(&self) -> Box<dyn S2Region> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Clone for Box<dyn S2Region> {
+    fn clone(&self) -> Self {
+        self.clone_region()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::{
+        s1::S1Angle,
+        s2::{S2LatLng, S2RegionCoverer},
+    };
+
+    #[test]
+    fn test_dyn_regions() {
+        let north_pole = S2Point::new(0.0, 0.0, 1.0);
+        let cap = S2Cap::from_center_angle(north_pole, S1Angle::from_degrees(10.0));
+        let rect = S2LatLngRect::new(
+            &S2LatLng::from_degrees(60.0, -180.0),
+            &S2LatLng::from_degrees(90.0, 180.0),
+        );
+        let cell = S2Cell::new(S2CellId::from_point(&north_pole).parent_at_level(5));
+        let regions: Vec<Box<dyn S2Region>> =
+            vec![Box::new(cap), Box::new(rect), Box::new(cell.clone())];
+
+        for region in &regions {
+            // Every region contains the north pole, and so must its bounds.
+            assert!(region.contains_point(&north_pole));
+            assert!(region.get_cap_bound().contains_point(&north_pole));
+            assert!(region.may_intersect_cell(&cell));
+
+            // Copies made through the trait object behave like the original.
+            let copy = region.clone_region();
+            assert_eq!(
+                copy.get_cap_bound().center(),
+                region.get_cap_bound().center()
+            );
+            assert_eq!(
+                copy.contains_point(&-north_pole),
+                region.contains_point(&-north_pole)
+            );
+        }
+        assert!(!regions[0].contains_cell(&S2Cell::from_face(0)));
+        assert!(regions[1].contains_cell(&cell));
+        assert!(regions[1].get_rect_bound().contains(&rect));
+
+        let mut cell_ids = vec![S2CellId::from_face(0)];
+        regions[2].get_cell_union_bound(&mut cell_ids);
+        assert_eq!(cell_ids, vec![cell.id()]);
+
+        // Trait objects can be passed directly to the coverer.
+        let covering = S2RegionCoverer::default().get_covering(regions[0].as_ref());
+        assert!(!covering.is_empty());
+    }
+}
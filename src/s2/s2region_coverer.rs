@@ -0,0 +1,844 @@
+// Copyright 2005 Google Inc. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS-IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+// Original Author: ericv@google.com (Eric Veach)
+
+use alloc::{collections::BinaryHeap, vec, vec::Vec};
+use core::{cmp::Ordering, ops::ControlFlow};
+
+use crate::s2::{S2Cell, S2CellId, S2CellUnion, S2Region};
+
+/// An S2RegionCoverer is a class that allows arbitrary regions to be
+/// approximated as unions of cells (S2CellUnion). This is useful for
+/// implementing various sorts of search and precomputation operations.
+///
+/// Typical usage:
+///
+/// ```
+/// use s2shell::{
+///     s1::S1Angle,
+///     s2::{S2Cap, S2Point, S2RegionCoverer},
+/// };
+///
+/// let mut coverer = S2RegionCoverer::default();
+/// coverer.options_mut().set_max_cells(5);
+/// let cap = S2Cap::from_center_angle(S2Point::new(1.0, 0.0, 0.0), S1Angle::from_degrees(10.0));
+/// let covering = coverer.get_covering(&cap);
+/// assert!(covering.len() <= 5);
+/// ```
+///
+/// This yields a vector of at most 5 cells that is guaranteed to cover the
+/// given cap (a disc-shaped region on the sphere).
+///
+/// The approximation algorithm is not optimal but does a pretty good job in
+/// practice. The output does not always use the maximum number of cells
+/// allowed, both because this would not always yield a better approximation,
+/// and because max_cells() is a limit on how much work is done exploring the
+/// possible covering as well as a limit on the final output size.
+///
+/// Because it is an approximation algorithm, one should not rely on the
+/// stability of the output. In particular, the output of the covering
+/// algorithm may change across different versions of the library.
+#[derive(Debug, Clone, Default)]
+pub struct S2RegionCoverer {
+    options: CovererOptions,
+}
+
+/// Options that control the size and accuracy of the covering produced by
+/// an S2RegionCoverer.
+#[derive(Debug, Clone)]
+pub struct CovererOptions {
+    max_cells: i32,
+    min_level: i32,
+    max_level: i32,
+}
+
+impl CovererOptions {
+    /// By default, the covering uses at most 8 cells at any level. This gives
+    /// a reasonable tradeoff between the number of cells used and the accuracy
+    /// of the approximation.
+    pub const DEFAULT_MAX_CELLS: i32 = 8;
+
+    /// Sets the desired maximum number of cells in the approximation. Note the
+    /// following:
+    ///
+    ///  - For any setting of max_cells(), up to 6 cells may be returned if that
+    ///    is the minimum number required (e.g. if the region intersects all six
+    ///    face cells). Up to 3 cells may be returned even for very tiny convex
+    ///    regions if they happen to be located at the intersection of three
+    ///    cube faces.
+    ///
+    ///  - If the region is very small, then the covering may use fewer cells
+    ///    than requested, since a single cell at `max_level` may already cover
+    ///    it.
+    ///
+    /// DEFAULT: `CovererOptions::DEFAULT_MAX_CELLS`
+    pub fn set_max_cells(&mut self, max_cells: i32) {
+        self.max_cells = max_cells;
+    }
+
+    pub fn max_cells(&self) -> i32 {
+        self.max_cells
+    }
+
+    /// Sets the minimum cell level to be used. Cells at lower levels (i.e.
+    /// larger cells) are never used, even if this causes more than max_cells()
+    /// cells to be returned.
+    ///
+    /// DEFAULT: 0
+    pub fn set_min_level(&mut self, min_level: i32) {
+        debug_assert!((0..=S2CellId::MAX_LEVEL).contains(&min_level));
+        self.min_level = min_level.clamp(0, S2CellId::MAX_LEVEL);
+    }
+
+    pub fn min_level(&self) -> i32 {
+        self.min_level
+    }
+
+    /// Sets the maximum cell level to be used. Cells at higher levels (i.e.
+    /// smaller cells) are never used.
+    ///
+    /// DEFAULT: S2CellId::MAX_LEVEL
+    pub fn set_max_level(&mut self, max_level: i32) {
+        debug_assert!((0..=S2CellId::MAX_LEVEL).contains(&max_level));
+        self.max_level = max_level.clamp(0, S2CellId::MAX_LEVEL);
+    }
+
+    pub fn max_level(&self) -> i32 {
+        self.max_level
+    }
+
+    /// Convenience function that sets both the maximum and minimum cell levels.
+    pub fn set_fixed_level(&mut self, level: i32) {
+        self.set_min_level(level);
+        self.set_max_level(level);
+    }
+}
+
+impl Default for CovererOptions {
+    fn default() -> Self {
+        CovererOptions {
+            max_cells: CovererOptions::DEFAULT_MAX_CELLS,
+            min_level: 0,
+            max_level: S2CellId::MAX_LEVEL,
+        }
+    }
+}
+
+impl S2RegionCoverer {
+    /// Constructs an S2RegionCoverer with the given options.
+    pub fn new(options: CovererOptions) -> S2RegionCoverer {
+        S2RegionCoverer { options }
+    }
+
+    pub fn options(&self) -> &CovererOptions {
+        &self.options
+    }
+
+    pub fn options_mut(&mut self) -> &mut CovererOptions {
+        &mut self.options
+    }
+
+    /// Returns a vector of cell ids that covers the given region and satisfies
+    /// the various restrictions specified above. The cells are returned in
+    /// increasing order of cell id.
+    pub fn get_covering<R: S2Region + ?Sized>(&self, region: &R) -> Vec<S2CellId> {
+        self.get_cell_ids(region, false)
+    }
+
+    /// Returns a cell union that is contained within the given region and
+    /// satisfies the various restrictions specified above. Unlike
+    /// get_covering(), cells that only partially overlap the region are never
+    /// returned, so every point of the result is a point of the region. The
+    /// result may be empty if the region is too small to contain any cell at
+    /// max_level().
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::{
+    ///     s1::S1Angle,
+    ///     s2::{S2Cap, S2Cell, S2Point, S2Region, S2RegionCoverer},
+    /// };
+    ///
+    /// let cap = S2Cap::from_center_angle(S2Point::new(1.0, 0.0, 0.0), S1Angle::from_degrees(10.0));
+    /// let mut coverer = S2RegionCoverer::default();
+    /// coverer.options_mut().set_max_cells(20);
+    /// let interior = coverer.get_interior_covering(&cap);
+    /// assert!(!interior.is_empty());
+    /// assert!(interior
+    ///     .cell_ids()
+    ///     .iter()
+    ///     .all(|id| cap.contains_cell(&S2Cell::new(*id))));
+    /// ```
+    pub fn get_interior_covering<R: S2Region + ?Sized>(&self, region: &R) -> S2CellUnion {
+        S2CellUnion::from_verbatim(self.get_cell_ids(region, true))
+    }
+
+    /// Returns an iterator over the same cells as get_covering(), in
+    /// increasing order of cell id.
+    ///
+    /// If max_cells() is i32::MAX, so that the number of cells is unlimited,
+    /// the covering is computed incrementally by a depth-first traversal of
+    /// the cell hierarchy. Only a small amount of state (proportional to the
+    /// maximum level) is kept in memory no matter how many cells are produced,
+    /// which makes it possible to cover large regions at fine levels and to
+    /// stop as soon as enough cells have been seen. In this case every cell
+    /// that intersects the boundary of the region is subdivided until
+    /// max_level() is reached, and since the number of cells grows by a
+    /// factor of about 2 per level, max_level() should normally be set well
+    /// below the default of S2CellId::MAX_LEVEL.
+    ///
+    /// Otherwise the choice of which cells to subdivide depends on the whole
+    /// region, so the covering is computed up front by get_covering() and the
+    /// iterator holds its (at most about max_cells()) cells.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::{
+    ///     s1::S1Angle,
+    ///     s2::{S2Cap, S2Point, S2RegionCoverer},
+    /// };
+    ///
+    /// let cap = S2Cap::from_center_angle(S2Point::new(1.0, 0.0, 0.0), S1Angle::from_degrees(1.0));
+    /// let mut coverer = S2RegionCoverer::default();
+    /// let cells: Vec<_> = coverer.coverings_iter(&cap).collect();
+    /// assert_eq!(cells, coverer.get_covering(&cap));
+    ///
+    /// // Stream a covering with an unlimited number of cells.
+    /// coverer.options_mut().set_max_cells(i32::MAX);
+    /// coverer.options_mut().set_max_level(10);
+    /// let cells: Vec<_> = coverer.coverings_iter(&cap).collect();
+    /// assert!(cells.len() > 100);
+    /// assert!(cells.windows(2).all(|w| w[0] < w[1]));
+    /// assert_eq!(cells, coverer.get_covering(&cap));
+    /// ```
+    pub fn coverings_iter<'a, R: S2Region + ?Sized>(
+        &'a self,
+        region: &'a R,
+    ) -> CoveringIter<'a, R> {
+        if self.options.max_cells() != i32::MAX {
+            return CoveringIter {
+                state: CoveringIterState::Computed(self.get_covering(region).into_iter()),
+            };
+        }
+        let covering = Covering::new(&self.options, region, false);
+        let mut stack = Vec::new();
+        for face in (0..S2CellId::NUM_FACES).rev() {
+            stack.extend(covering.new_candidate(S2Cell::new(S2CellId::from_face(face))));
+        }
+        CoveringIter {
+            state: CoveringIterState::Streaming { covering, stack },
+        }
+    }
+
+    /// Calls "f" for each cell of the covering returned by coverings_iter(),
+    /// in increasing order of cell id. If "f" returns ControlFlow::Break, the
+    /// traversal stops immediately and the break value is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::ControlFlow;
+    ///
+    /// use s2shell::{
+    ///     s1::S1Angle,
+    ///     s2::{S2Cap, S2Point, S2RegionCoverer},
+    /// };
+    ///
+    /// // Stop after the first 10 cells rather than computing all of them.
+    /// let cap = S2Cap::from_center_angle(
+    ///     S2Point::new(1.0, 1.0, 0.0).normalize(),
+    ///     S1Angle::from_degrees(10.0),
+    /// );
+    /// let mut coverer = S2RegionCoverer::default();
+    /// coverer.options_mut().set_max_cells(i32::MAX);
+    /// coverer.options_mut().set_max_level(20);
+    /// let mut cells = Vec::new();
+    /// let result = coverer.get_covering_with(&cap, |id| {
+    ///     cells.push(id);
+    ///     if cells.len() == 10 {
+    ///         ControlFlow::Break(())
+    ///     } else {
+    ///         ControlFlow::Continue(())
+    ///     }
+    /// });
+    /// assert_eq!(result, ControlFlow::Break(()));
+    /// assert_eq!(cells.len(), 10);
+    /// ```
+    pub fn get_covering_with<R, B, F>(&self, region: &R, mut f: F) -> ControlFlow<B>
+    where
+        R: S2Region + ?Sized,
+        F: FnMut(S2CellId) -> ControlFlow<B>,
+    {
+        for id in self.coverings_iter(region) {
+            f(id)?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn get_cell_ids<R: S2Region + ?Sized>(
+        &self,
+        region: &R,
+        interior_covering: bool,
+    ) -> Vec<S2CellId> {
+        let mut covering = Covering::new(&self.options, region, interior_covering);
+        covering.run();
+        let mut result = covering.result;
+        result.sort();
+        result
+    }
+}
+
+/// A candidate cell for the covering, along with the children that
+/// intersect the region (if it has been expanded).
+struct Candidate {
+    cell: S2Cell,
+    /// Cell should not be expanded further.
+    is_terminal: bool,
+    children: Vec<Candidate>,
+}
+
+/// We define our own ordering so that the priority queue only compares the
+/// priorities of candidates, which are computed once when they are queued.
+struct QueueEntry {
+    priority: i32,
+    candidate: Candidate,
+}
+
+impl PartialEq for QueueEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl Eq for QueueEntry {}
+
+impl PartialOrd for QueueEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QueueEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority.cmp(&other.priority)
+    }
+}
+
+/// The working state of a single covering computation.
+struct Covering<'a, R: S2Region + ?Sized> {
+    options: &'a CovererOptions,
+    region: &'a R,
+    interior_covering: bool,
+    result: Vec<S2CellId>,
+    pq: BinaryHeap<QueueEntry>,
+}
+
+impl<'a, R: S2Region + ?Sized> Covering<'a, R> {
+    /// The number of bits used to encode the number of children and the
+    /// number of terminal children in a candidate's priority.
+    const MAX_CHILDREN_SHIFT: i32 = 2;
+
+    fn new(options: &'a CovererOptions, region: &'a R, interior_covering: bool) -> Self {
+        Covering {
+            options,
+            region,
+            interior_covering,
+            result: Vec::new(),
+            pq: BinaryHeap::new(),
+        }
+    }
+
+    fn max_level(&self) -> i32 {
+        self.options.max_level().max(self.options.min_level())
+    }
+
+    /// If the cell intersects the given region, return a new candidate with no
+    /// children, otherwise return `None`. Also marks the candidate as
+    /// "terminal" if it should not be expanded further.
+    fn new_candidate(&self, cell: S2Cell) -> Option<Candidate> {
+        if !self.region.may_intersect_cell(&cell) {
+            return None;
+        }
+        let mut is_terminal = false;
+        if cell.level() >= self.options.min_level() {
+            if self.interior_covering {
+                if self.region.contains_cell(&cell) {
+                    is_terminal = true;
+                } else if cell.level() >= self.max_level() {
+                    // The cell cannot be subdivided any further and is not
+                    // contained by the region, so it is discarded.
+                    return None;
+                }
+            } else {
+                is_terminal = cell.level() >= self.max_level() || self.region.contains_cell(&cell);
+            }
+        }
+        Some(Candidate {
+            cell,
+            is_terminal,
+            children: Vec::new(),
+        })
+    }
+
+    /// Populates the children of "candidate" by expanding the given number of
+    /// levels from the given cell. Returns the number of children that were
+    /// marked "terminal".
+    fn expand_children(&self, candidate: &mut Candidate, cell: &S2Cell, num_levels: i32) -> i32 {
+        let num_levels = num_levels - 1;
+        let mut num_terminals = 0;
+        for pos in 0..4 {
+            let child = S2Cell::new(cell.id().child(pos));
+            if num_levels > 0 {
+                if self.region.may_intersect_cell(&child) {
+                    num_terminals += self.expand_children(candidate, &child, num_levels);
+                }
+                continue;
+            }
+            if let Some(child) = self.new_candidate(child) {
+                if child.is_terminal {
+                    num_terminals += 1;
+                }
+                candidate.children.push(child);
+            }
+        }
+        num_terminals
+    }
+
+    /// Processes a candidate by either adding it to the result vector or
+    /// expanding its children and inserting it into the priority queue.
+    fn add_candidate(&mut self, mut candidate: Candidate) {
+        if candidate.is_terminal {
+            self.result.push(candidate.cell.id());
+            return;
+        }
+
+        // Expand one level at a time until we hit min_level() to ensure that we
+        // don't skip over it.
+        let cell = candidate.cell.clone();
+        let num_terminals = self.expand_children(&mut candidate, &cell, 1);
+        let num_children = candidate.children.len() as i32;
+        if candidate.children.is_empty() {
+            // Not needed.
+        } else if num_terminals == 4 && cell.level() >= self.options.min_level() {
+            // Optimization: add the parent cell rather than all of its children.
+            candidate.is_terminal = true;
+            self.add_candidate(candidate);
+        } else {
+            // We negate the priority so that smaller absolute priorities are
+            // returned first. The heuristic is designed to refine the largest
+            // cells first, since those are where we have the largest potential
+            // gain. Among cells of the same size, we prefer the cells with the
+            // fewest children. Finally, among cells with equal numbers of
+            // children we prefer those with the smallest number of children that
+            // cannot be refined further.
+            let priority = -((((cell.level() << Self::MAX_CHILDREN_SHIFT) + num_children)
+                << Self::MAX_CHILDREN_SHIFT)
+                + num_terminals);
+            self.pq.push(QueueEntry {
+                priority,
+                candidate,
+            });
+        }
+    }
+
+    /// Computes a set of initial candidates that cover the given region.
+    fn get_initial_candidates(&mut self) {
+        for face in 0..S2CellId::NUM_FACES {
+            if let Some(candidate) = self.new_candidate(S2Cell::new(S2CellId::from_face(face))) {
+                self.add_candidate(candidate);
+            }
+        }
+    }
+
+    /// Generates a covering and stores it in `result`. Strategy: Start with the
+    /// 6 faces of the cube. Discard any that do not intersect the shape. Then
+    /// repeatedly choose the largest cell that intersects the shape and
+    /// subdivide it.
+    ///
+    /// `result` contains the cells that will be part of the output, while `pq`
+    /// contains cells that we may still subdivide further. Cells that are
+    /// entirely contained within the region are immediately added to the
+    /// output, while cells that do not intersect the region are immediately
+    /// discarded. Therefore `pq` only contains cells that partially intersect
+    /// the region. Candidates are prioritized first according to cell size
+    /// (larger cells first), then by the number of intersecting children they
+    /// have (fewest children first), and then by the number of fully contained
+    /// children (fewest children first).
+    ///
+    /// For interior coverings, cells that only partially intersect the region
+    /// are never added to the result, and the search stops once max_cells()
+    /// cells have been found.
+    fn run(&mut self) {
+        self.get_initial_candidates();
+        let max_cells = self.options.max_cells() as usize;
+        while !self.interior_covering || self.result.len() < max_cells {
+            let Some(QueueEntry { mut candidate, .. }) = self.pq.pop() else {
+                break;
+            };
+            // Candidates in the queue are never part of an interior covering,
+            // so they do not count against the budget.
+            let queued = if self.interior_covering {
+                0
+            } else {
+                self.pq.len()
+            };
+            if candidate.cell.level() < self.options.min_level()
+                || candidate.children.len() == 1
+                || self.result.len() + queued + candidate.children.len() <= max_cells
+            {
+                // Expand this candidate into its children.
+                for child in core::mem::take(&mut candidate.children) {
+                    self.add_candidate(child);
+                }
+            } else if self.interior_covering {
+                // The candidate partially intersects the region and cannot be
+                // expanded, so it is discarded.
+            } else {
+                candidate.is_terminal = true;
+                self.add_candidate(candidate);
+            }
+        }
+    }
+}
+
+/// An iterator over the cells of a covering, returned by
+/// S2RegionCoverer::coverings_iter().
+pub struct CoveringIter<'a, R: S2Region + ?Sized> {
+    state: CoveringIterState<'a, R>,
+}
+
+enum CoveringIterState<'a, R: S2Region + ?Sized> {
+    /// The covering was computed up front because max_cells() is limited.
+    Computed(vec::IntoIter<S2CellId>),
+    /// The cells are found by a depth-first traversal that makes the same
+    /// decisions as get_covering() does when max_cells() is unlimited. Since
+    /// the children of a cell are visited in order and the cells of a
+    /// covering are disjoint, the traversal produces cells in increasing
+    /// order of cell id.
+    Streaming {
+        covering: Covering<'a, R>,
+        // Candidates that remain to be visited, with the next one at the end.
+        stack: Vec<Candidate>,
+    },
+}
+
+impl<R: S2Region + ?Sized> Iterator for CoveringIter<'_, R> {
+    type Item = S2CellId;
+
+    fn next(&mut self) -> Option<S2CellId> {
+        let (covering, stack) = match &mut self.state {
+            CoveringIterState::Computed(cells) => return cells.next(),
+            CoveringIterState::Streaming { covering, stack } => (covering, stack),
+        };
+        // This is the depth-first analogue of Covering::add_candidate().
+        while let Some(mut candidate) = stack.pop() {
+            if candidate.is_terminal {
+                return Some(candidate.cell.id());
+            }
+            let cell = candidate.cell.clone();
+            let num_terminals = covering.expand_children(&mut candidate, &cell, 1);
+            if candidate.children.is_empty() {
+                continue;
+            }
+            if num_terminals == 4 && cell.level() >= covering.options.min_level() {
+                // Optimization: return the parent cell rather than all of its
+                // children.
+                return Some(cell.id());
+            }
+            // Push the children in reverse so that they are visited in order.
+            stack.extend(candidate.children.into_iter().rev());
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::s2::s2testing::random_point;
+    use crate::{
+        s1::S1Angle,
+        s2::{S2Cap, S2LatLng, S2LatLngRect, S2Point},
+    };
+
+    // A region that counts how many cells it has been asked about.
+    #[derive(Clone)]
+    struct CountingRegion {
+        cap: S2Cap,
+        num_calls: Cell<usize>,
+    }
+
+    impl S2Region for CountingRegion {
+        fn get_cap_bound(&self) -> S2Cap {
+            self.cap.get_cap_bound()
+        }
+
+        fn get_rect_bound(&self) -> S2LatLngRect {
+            self.cap.get_rect_bound()
+        }
+
+        fn get_cell_union_bound(&self, cell_ids: &mut Vec<S2CellId>) {
+            self.cap.get_cell_union_bound(cell_ids)
+        }
+
+        fn contains_cell(&self, cell: &S2Cell) -> bool {
+            self.num_calls.set(self.num_calls.get() + 1);
+            self.cap.contains_cell(cell)
+        }
+
+        fn may_intersect_cell(&self, cell: &S2Cell) -> bool {
+            self.num_calls.set(self.num_calls.get() + 1);
+            self.cap.may_intersect_cell(cell)
+        }
+
+        fn contains_point(&self, point: &S2Point) -> bool {
+            self.cap.contains_point(point)
+        }
+    }
+
+    fn covering_contains(covering: &[S2CellId], p: &S2Point) -> bool {
+        covering.iter().any(|id| S2Cell::new(*id).contains_point(p))
+    }
+
+    #[test]
+    fn test_cap_covering_contains_center() {
+        let centers = [
+            S2Point::new(1.0, 0.0, 0.0),
+            S2Point::new(0.0, 0.0, -1.0),
+            S2Point::new(1.0, 2.0, 3.0).normalize(),
+            S2Point::new(-1.0, -1.0, 1.0).normalize(),
+        ];
+        for center in centers {
+            for degrees in [0.001, 0.1, 5.0, 45.0, 120.0] {
+                let cap = S2Cap::from_center_angle(center, S1Angle::from_degrees(degrees));
+                let covering = S2RegionCoverer::default().get_covering(&cap);
+                assert!(!covering.is_empty());
+                assert!(covering_contains(&covering, &center));
+            }
+        }
+    }
+
+    #[test]
+    fn test_covering_respects_max_cells() {
+        let center = S2Point::new(0.3, -0.4, 0.5).normalize();
+        let cap = S2Cap::from_center_angle(center, S1Angle::from_degrees(3.0));
+        for max_cells in [4, 6, 8, 20, 100] {
+            let mut coverer = S2RegionCoverer::default();
+            coverer.options_mut().set_max_cells(max_cells);
+            let covering = coverer.get_covering(&cap);
+            assert!(covering.len() <= max_cells as usize);
+            assert!(covering_contains(&covering, &center));
+            // Every cell in the covering should intersect the cap.
+            for id in &covering {
+                assert!(cap.may_intersect_cell(&S2Cell::new(*id)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_covering_respects_levels() {
+        let center = S2Point::new(-0.2, 0.9, 0.1).normalize();
+        let cap = S2Cap::from_center_angle(center, S1Angle::from_degrees(1.0));
+        let mut coverer = S2RegionCoverer::default();
+        coverer.options_mut().set_min_level(6);
+        coverer.options_mut().set_max_level(9);
+        let covering = coverer.get_covering(&cap);
+        assert!(covering_contains(&covering, &center));
+        for id in &covering {
+            assert!((6..=9).contains(&id.level()));
+        }
+        // The cells are returned in sorted order.
+        assert!(covering.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_full_and_empty_caps() {
+        let coverer = S2RegionCoverer::default();
+        let full = coverer.get_covering(&S2Cap::full());
+        assert_eq!(
+            full,
+            (0..6).map(S2CellId::from_face).collect::<Vec<S2CellId>>()
+        );
+        assert!(coverer.get_covering(&S2Cap::empty()).is_empty());
+    }
+
+    #[test]
+    fn test_interior_covering() {
+        let center = S2Point::new(1.0, 2.0, 3.0).normalize();
+        let cap = S2Cap::from_center_angle(center, S1Angle::from_degrees(5.0));
+        for max_cells in [1, 4, 8, 20, 100] {
+            let mut coverer = S2RegionCoverer::default();
+            coverer.options_mut().set_max_cells(max_cells);
+            coverer.options_mut().set_max_level(12);
+            let interior = coverer.get_interior_covering(&cap);
+            // With a very small budget no candidate can be subdivided far
+            // enough to find a contained cell, so the result may be empty.
+            assert!(max_cells < 8 || !interior.is_empty());
+            assert!(interior.is_valid());
+            assert!(interior.num_cells() <= max_cells as usize);
+            for id in interior.cell_ids() {
+                assert!(id.level() <= 12);
+                assert!(cap.contains_cell(&S2Cell::new(*id)));
+            }
+
+            // Every interior cell is also covered by the regular covering.
+            let covering = S2CellUnion::new(coverer.get_covering(&cap));
+            for id in interior.cell_ids() {
+                assert!(covering.contains(id));
+            }
+        }
+    }
+
+    #[test]
+    fn test_interior_covering_respects_levels() {
+        let cap = S2Cap::from_center_angle(
+            S2Point::new(0.0, -1.0, 1.0).normalize(),
+            S1Angle::from_degrees(10.0),
+        );
+        let mut coverer = S2RegionCoverer::default();
+        coverer.options_mut().set_max_cells(1000);
+        coverer.options_mut().set_min_level(3);
+        coverer.options_mut().set_max_level(7);
+        let interior = coverer.get_interior_covering(&cap);
+        assert!(!interior.is_empty());
+        for id in interior.cell_ids() {
+            assert!((3..=7).contains(&id.level()));
+            assert!(cap.contains_cell(&S2Cell::new(*id)));
+        }
+    }
+
+    #[test]
+    fn test_interior_covering_tiny_region() {
+        // A cap much smaller than any cell at max_level has no interior.
+        let cap = S2Cap::from_center_angle(
+            S2Point::new(1.0, 2.0, 3.0).normalize(),
+            S1Angle::from_degrees(1e-3),
+        );
+        let mut coverer = S2RegionCoverer::default();
+        coverer.options_mut().set_max_level(5);
+        assert!(coverer.get_interior_covering(&cap).is_empty());
+        assert!(S2RegionCoverer::default()
+            .get_interior_covering(&S2Cap::empty())
+            .is_empty());
+    }
+
+    #[test]
+    fn test_full_cap_interior_covering() {
+        let interior = S2RegionCoverer::default().get_interior_covering(&S2Cap::full());
+        assert_eq!(
+            interior.cell_ids(),
+            &(0..6).map(S2CellId::from_face).collect::<Vec<S2CellId>>()[..]
+        );
+    }
+
+    // Checks that coverings_iter() and get_covering_with() produce the same
+    // cells as get_covering(), both with the given max_cells() and with an
+    // unlimited number of cells.
+    fn check_coverings_iter<R: S2Region + ?Sized>(region: &R, coverer: &S2RegionCoverer) {
+        let mut unlimited = coverer.clone();
+        unlimited.options_mut().set_max_cells(i32::MAX);
+        for coverer in [coverer, &unlimited] {
+            let cells: Vec<S2CellId> = coverer.coverings_iter(region).collect();
+            assert!(cells.windows(2).all(|w| w[0] < w[1]));
+            assert_eq!(cells, coverer.get_covering(region));
+
+            let mut visited = Vec::new();
+            let result = coverer.get_covering_with(region, |id| {
+                visited.push(id);
+                ControlFlow::<()>::Continue(())
+            });
+            assert_eq!(result, ControlFlow::Continue(()));
+            assert_eq!(visited, cells);
+        }
+    }
+
+    #[test]
+    fn test_coverings_iter() {
+        let mut rng = StdRng::seed_from_u64(74);
+        for _ in 0..30 {
+            let mut coverer = S2RegionCoverer::default();
+            let max_level = rng.gen_range(3..11);
+            coverer.options_mut().set_max_level(max_level);
+            coverer
+                .options_mut()
+                .set_min_level(rng.gen_range(0..=max_level));
+            let radius = S1Angle::from_radians(0.3 * rng.gen_range(0.0f64..1.0).powi(2));
+            let cap = S2Cap::from_center_angle(random_point(&mut rng), radius);
+            check_coverings_iter(&cap, &coverer);
+
+            let center = S2LatLng::from_point(&random_point(&mut rng));
+            let rect = S2LatLngRect::from_center_size(&center, &S2LatLng::from_degrees(10.0, 20.0));
+            check_coverings_iter(&rect, &coverer);
+
+            let cell =
+                S2Cell::new(S2CellId::from_point(&random_point(&mut rng)).parent_at_level(2));
+            check_coverings_iter(&cell, &coverer);
+        }
+        check_coverings_iter(&S2Cap::full(), &S2RegionCoverer::default());
+        check_coverings_iter(&S2Cap::empty(), &S2RegionCoverer::default());
+    }
+
+    #[test]
+    fn test_coverings_iter_early_termination() {
+        // Covering a cap with a 10 degree radius at level 20 produces about a
+        // million cells along its boundary (its perimeter is about 1.1 radians
+        // while level 20 cells are about 1.4e-6 radians wide).
+        let region = CountingRegion {
+            cap: S2Cap::from_center_angle(
+                S2Point::new(1.0, 2.0, 3.0).normalize(),
+                S1Angle::from_degrees(10.0),
+            ),
+            num_calls: Cell::new(0),
+        };
+        let mut coverer = S2RegionCoverer::default();
+        coverer.options_mut().set_max_cells(i32::MAX);
+        coverer.options_mut().set_max_level(20);
+
+        let mut count = 0;
+        let result = coverer.get_covering_with(&region, |_| {
+            count += 1;
+            if count == 100 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        assert_eq!(result, ControlFlow::Break(()));
+        assert_eq!(count, 100);
+        // The traversal only explored a few paths down to level 20.
+        assert!(
+            region.num_calls.get() < 10_000,
+            "{}",
+            region.num_calls.get()
+        );
+
+        region.num_calls.set(0);
+        let first: Vec<S2CellId> = coverer.coverings_iter(&region).take(100).collect();
+        assert_eq!(first.len(), 100);
+        assert!(first.windows(2).all(|w| w[0] < w[1]));
+        assert!(
+            region.num_calls.get() < 10_000,
+            "{}",
+            region.num_calls.get()
+        );
+    }
+}
@@ -0,0 +1,47 @@
+// Copyright 2012 Google Inc. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS-IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+// Original Author: ericv@google.com (Eric Veach)
+
+use crate::s2::S2Point;
+
+/// The purpose of S2Shape is to represent polygonal geometry in a flexible
+/// way. It is organized as a collection of edges that optionally defines an
+/// interior. All geometry represented by a given S2Shape must have the same
+/// dimension, which means that an S2Shape can represent either a set of
+/// points, a set of polylines, or a set of polygons.
+///
+/// S2Shape is defined as a trait in order to give clients control over the
+/// underlying data representation. Sometimes an S2Shape does not have any
+/// data of its own, but instead "wraps" some other type.
+///
+/// This is the basic building block of the S2ShapeIndex framework, which
+/// indexes collections of shapes so that spatial queries can be answered in
+/// sublinear time.
+pub trait S2Shape {
+    /// Returns the number of edges in this shape. Edges have a well-defined
+    /// ordering starting from 0, and edge ids are always in the range
+    /// [0, num_edges()).
+    fn num_edges(&self) -> i32;
+
+    /// Returns the endpoints of the given edge id.
+    ///
+    /// Requires: 0 <= id < num_edges()
+    fn edge(&self, id: i32) -> (S2Point, S2Point);
+
+    /// Returns the dimension of the geometry represented by this shape: 0 for
+    /// points, 1 for polylines, and 2 for polygons.
+    fn dimension(&self) -> i32;
+}
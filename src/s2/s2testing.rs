@@ -0,0 +1,87 @@
+// Copyright 2005 Google Inc. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS-IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+// Original Author: ericv@google.com (Eric Veach)
+
+//! Utility functions for testing. Randomized tests pass a seeded `StdRng` so
+//! that failures are reproducible.
+
+use rand::{rngs::StdRng, Rng};
+
+use crate::s2::{S2CellId, S2Point};
+#[cfg(feature = "alloc")]
+use crate::{
+    s1::S1Angle,
+    s2::{interpolate, S2LatLng, S2Polyline},
+};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// Return a random unit-length vector.
+pub fn random_point(rng: &mut StdRng) -> S2Point {
+    S2Point::new(
+        rng.gen_range(-1.0..1.0),
+        rng.gen_range(-1.0..1.0),
+        rng.gen_range(-1.0..1.0),
+    )
+    .normalize()
+}
+
+/// Return a random cell id at the given level.
+pub fn random_cell_id_at_level(rng: &mut StdRng, level: i32) -> S2CellId {
+    let face = rng.gen_range(0..S2CellId::NUM_FACES);
+    let pos = rng.gen::<u64>() & ((1 << S2CellId::POS_BITS) - 1);
+    S2CellId::from_face_pos_level(face, pos, level)
+}
+
+/// Return a random cell id at a random level.
+pub fn random_cell_id(rng: &mut StdRng) -> S2CellId {
+    let level = rng.gen_range(0..=S2CellId::MAX_LEVEL);
+    random_cell_id_at_level(rng, level)
+}
+
+/// Return "n" random unit-length vectors.
+#[cfg(feature = "alloc")]
+pub fn random_points(rng: &mut StdRng, n: usize) -> Vec<S2Point> {
+    (0..n).map(|_| random_point(rng)).collect()
+}
+
+/// Return a polyline with the given vertices, specified as (lat, lng) pairs
+/// in degrees.
+#[cfg(feature = "alloc")]
+pub fn make_polyline(latlngs: &[(f64, f64)]) -> S2Polyline {
+    S2Polyline::new(
+        latlngs
+            .iter()
+            .map(|&(lat, lng)| S2LatLng::from_degrees(lat, lng).to_point())
+            .collect(),
+    )
+}
+
+/// Return a random point within "distance" of the given polyline.
+#[cfg(feature = "alloc")]
+pub fn sample_near_polyline(line: &S2Polyline, distance: S1Angle, rng: &mut StdRng) -> S2Point {
+    let n = line.num_vertices();
+    let base = if n == 1 {
+        *line.vertex(0)
+    } else {
+        let i = rng.gen_range(0..n - 1);
+        interpolate(rng.gen_range(0.0..1.0), line.vertex(i), line.vertex(i + 1))
+    };
+    // Move away from "base" along a random direction.
+    let dir = base.cross_prod(&random_point(rng)).normalize();
+    let angle = distance.radians() * rng.gen_range(0.0..0.999);
+    (base * angle.cos() + dir * angle.sin()).normalize()
+}
@@ -0,0 +1,599 @@
+// Copyright 2016 Google Inc. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS-IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+// Original Author: ericv@google.com (Eric Veach)
+//
+// This file defines various snapping functions. A snap function maps input
+// vertices to candidate output vertices ("snap sites") in a deterministic
+// way, so that equal inputs always produce bit-identical outputs.
+
+use core::f64::consts::{FRAC_1_SQRT_2, SQRT_2};
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+use crate::{
+    s1::S1Angle,
+    s2::{s2metrics, S2CellId, S2LatLng, S2Point},
+};
+
+/// The maximum supported snap radius (equivalent to about 7800km).
+pub fn max_snap_radius() -> S1Angle {
+    // This value can't be larger than 85.7 degrees without changing the code
+    // in S2Builder's site selection, and it can't be larger than 90 degrees
+    // without changing the code for snapping points to cell centers.
+    S1Angle::from_degrees(70.0)
+}
+
+/// A SnapFunction restricts the locations of the output vertices. For
+/// example, there are predefined snap functions that require vertices to be
+/// located at S2CellId centers or at E5/E6/E7 coordinates. The SnapFunction
+/// can also specify a minimum spacing between vertices (the "snap radius").
+///
+/// A SnapFunction defines the following methods:
+///
+/// 1. The snap_point() method, which snaps a point P to a nearby point (the
+///    "candidate snap site"). Any point may be returned, including P
+///    itself (this is the "identity snap function").
+///
+/// 2. "snap_radius", the maximum distance that vertices can move when
+///    snapped. The snap_radius must be at least as large as the maximum
+///    distance between P and snap_point(P) for any point P.
+///
+/// 3. "min_vertex_separation", the guaranteed minimum distance between
+///    snap sites selected from inputs that are more than snap_radius apart
+///    (i.e. the way that a geometry builder selects its output vertices).
+pub trait SnapFunction {
+    /// The maximum distance that vertices can move when snapped.
+    fn snap_radius(&self) -> S1Angle;
+
+    /// Returns the guaranteed minimum distance between vertices in the output.
+    /// This is generally some fraction of "snap_radius".
+    fn min_vertex_separation(&self) -> S1Angle;
+
+    /// Returns a candidate snap site for the given point. The final vertex
+    /// locations are a subset of the snap sites returned by this function
+    /// (i.e., every output vertex is a point that was returned by this
+    /// function).
+    ///
+    /// Snapping must be idempotent: snap_point(snap_point(x)) == snap_point(x)
+    /// for every point x.
+    fn snap_point(&self, point: &S2Point) -> S2Point;
+}
+
+/// A SnapFunction that snaps every vertex to itself. It should be used when
+/// vertices do not need to be snapped to a discrete set of locations (such
+/// as E7 lat/lngs), or when maximum accuracy is desired.
+///
+/// If the given "snap_radius" is zero, then all input vertices are
+/// preserved exactly. Otherwise, a geometry builder merges nearby vertices
+/// so that the output vertices are separated by at least the snap radius.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct IdentitySnapFunction {
+    snap_radius: S1Angle,
+}
+
+impl IdentitySnapFunction {
+    pub fn new(snap_radius: S1Angle) -> IdentitySnapFunction {
+        let mut result = IdentitySnapFunction::default();
+        result.set_snap_radius(snap_radius);
+        result
+    }
+
+    pub fn set_snap_radius(&mut self, snap_radius: S1Angle) {
+        debug_assert!(snap_radius <= max_snap_radius());
+        self.snap_radius = snap_radius;
+    }
+}
+
+impl Default for IdentitySnapFunction {
+    /// The default snap radius is zero, i.e. vertices are preserved exactly.
+    fn default() -> IdentitySnapFunction {
+        IdentitySnapFunction {
+            snap_radius: S1Angle::zero(),
+        }
+    }
+}
+
+impl SnapFunction for IdentitySnapFunction {
+    fn snap_radius(&self) -> S1Angle {
+        self.snap_radius
+    }
+
+    fn min_vertex_separation(&self) -> S1Angle {
+        // Since snapping does not move the input point, output vertices are
+        // separated by the full snap_radius().
+        self.snap_radius
+    }
+
+    fn snap_point(&self, point: &S2Point) -> S2Point {
+        *point
+    }
+}
+
+/// A SnapFunction that snaps vertices to S2CellId centers. This can be
+/// useful if you want to encode your geometry compactly using
+/// encode_points_compressed(), for example.
+///
+/// If the snap radius is the minimum value for the given level (which is the
+/// default), then the output vertices are simply the centers of the cells
+/// containing the input vertices.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct S2CellIdSnapFunction {
+    level: i32,
+    snap_radius: S1Angle,
+}
+
+impl S2CellIdSnapFunction {
+    /// Snaps vertices to S2Cell centers at the given level, using the minimum
+    /// snap radius for that level.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{S2CellId, S2CellIdSnapFunction, S2Point, SnapFunction};
+    ///
+    /// let snap = S2CellIdSnapFunction::new(20);
+    /// let p = S2Point::new(1.0, 2.0, 3.0).normalize();
+    /// let snapped = snap.snap_point(&p);
+    /// assert_eq!(
+    ///     snapped,
+    ///     S2Point::from(S2CellId::from_point(&p).parent_at_level(20))
+    /// );
+    /// ```
+    pub fn new(level: i32) -> S2CellIdSnapFunction {
+        let mut result = S2CellIdSnapFunction {
+            level: 0,
+            snap_radius: S1Angle::zero(),
+        };
+        result.set_level(level);
+        result
+    }
+
+    /// Snaps vertices to S2Cell centers at the given level, and sets the snap
+    /// radius (see set_snap_radius).
+    pub fn with_snap_radius(level: i32, snap_radius: S1Angle) -> S2CellIdSnapFunction {
+        let mut result = S2CellIdSnapFunction::new(level);
+        result.set_snap_radius(snap_radius);
+        result
+    }
+
+    /// Snaps vertices to S2Cell centers at the given level. As a side effect,
+    /// this method also resets "snap_radius" to the minimum value allowed at
+    /// this level:
+    ///
+    ///   set_snap_radius(min_snap_radius_for_level(level))
+    ///
+    /// This means that if you want to use a larger snap radius than the
+    /// minimum, you must call set_snap_radius() *after* calling set_level().
+    pub fn set_level(&mut self, level: i32) {
+        debug_assert!((0..=S2CellId::MAX_LEVEL).contains(&level));
+        self.level = level;
+        self.set_snap_radius(S2CellIdSnapFunction::min_snap_radius_for_level(level));
+    }
+
+    pub fn level(&self) -> i32 {
+        self.level
+    }
+
+    /// Defines the snap radius to be used (see the SnapFunction trait). The
+    /// snap radius must be at least the minimum value for the current
+    /// level(), but larger values can also be used (e.g., to simplify the
+    /// geometry).
+    pub fn set_snap_radius(&mut self, snap_radius: S1Angle) {
+        debug_assert!(snap_radius >= S2CellIdSnapFunction::min_snap_radius_for_level(self.level));
+        debug_assert!(snap_radius <= max_snap_radius());
+        self.snap_radius = snap_radius;
+    }
+
+    /// Returns the minimum allowable snap radius for the given S2Cell level
+    /// (approximately equal to half of the maximum cell diagonal length).
+    pub fn min_snap_radius_for_level(level: i32) -> S1Angle {
+        // snap_radius() needs to be an upper bound on the true distance that
+        // a point can move when snapped, taking into account numerical errors.
+        //
+        // The maximum error when converting from an S2Point to an S2CellId is
+        // MAX_DIAG.deriv() * DBL_EPSILON. The maximum error when converting
+        // an S2CellId center back to an S2Point is 1.5 * DBL_EPSILON. These
+        // add up to just slightly less than 4 * DBL_EPSILON.
+        S1Angle::from_radians(0.5 * s2metrics::MAX_DIAG.get_value(level) + 4.0 * f64::EPSILON)
+    }
+
+    /// Returns the minimum S2Cell level (i.e., largest S2Cells) such that
+    /// vertices will not move by more than "snap_radius". This can be
+    /// useful when choosing an appropriate level to snap to. The return
+    /// value is always a valid level (out of range values are silently
+    /// clamped).
+    ///
+    /// If you want to choose the snap level based on a distance, and then
+    /// use the minimum possible snap radius for the chosen level, do this:
+    ///
+    ///   S2CellIdSnapFunction::new(
+    ///       S2CellIdSnapFunction::level_for_max_snap_radius(distance));
+    pub fn level_for_max_snap_radius(snap_radius: S1Angle) -> i32 {
+        // When choosing a level, we need to account for the error bound of
+        // 4 * DBL_EPSILON that is added by min_snap_radius_for_level().
+        s2metrics::MAX_DIAG
+            .get_level_for_max_value(2.0 * (snap_radius.radians() - 4.0 * f64::EPSILON))
+    }
+}
+
+impl SnapFunction for S2CellIdSnapFunction {
+    fn snap_radius(&self) -> S1Angle {
+        self.snap_radius
+    }
+
+    fn min_vertex_separation(&self) -> S1Angle {
+        // We have three different bounds for the minimum vertex separation:
+        // one is a constant bound, one is proportional to snap_radius, and
+        // one is equal to snap_radius minus a constant. These bounds give the
+        // best results for small, medium, and large snap radii respectively.
+        // We return the maximum of the three bounds.
+        //
+        // 1. Constant bound: Vertices are always separated by at least
+        //    MIN_EDGE(level), the minimum edge length for the chosen snap
+        //    level.
+        //
+        // 2. Proportional bound: It can be shown that in the plane, the
+        //    worst-case configuration has a vertex separation of
+        //    2 / sqrt(13) * snap_radius. On the sphere the ratio is slightly
+        //    smaller at cell level 2 (0.54849 vs. 0.55470), so we reduce that
+        //    value a bit more.
+        //
+        // 3. Best asymptotic bound: This bound is derived by observing we
+        //    only select a new site when it is at least snap_radius() away
+        //    from all existing sites, and the site can move by at most
+        //    0.5 * MAX_DIAG(level) when snapped.
+        let min_edge = s2metrics::MIN_EDGE.get_value(self.level);
+        let max_diag = s2metrics::MAX_DIAG.get_value(self.level);
+        let r = self.snap_radius.radians();
+        S1Angle::from_radians(min_edge.max(0.548 * r).max(r - 0.5 * max_diag))
+    }
+
+    fn snap_point(&self, point: &S2Point) -> S2Point {
+        S2Point::from(S2CellId::from_point(point).parent_at_level(self.level))
+    }
+}
+
+/// A SnapFunction that snaps vertices to S2LatLng E5, E6, or E7 coordinates.
+/// These coordinates are expressed in degrees multiplied by a power of 10 and
+/// then rounded to the nearest integer. For example, in E6 coordinates the
+/// point (23.12345651, -45.65432149) would become (23123457, -45654321).
+///
+/// The main argument of the snap function is the exponent for the power of
+/// 10 that coordinates should be multiplied by before rounding. For example,
+/// IntLatLngSnapFunction::new(7) is a function that snaps to E7 coordinates.
+/// The exponent can range from 0 to 10.
+///
+/// Each exponent has a corresponding minimum snap radius, which is simply the
+/// maximum distance that a vertex can move when snapped. It is approximately
+/// equal to 1/sqrt(2) times the nominal point spacing; for example, for
+/// snapping to E7 the minimum snap radius is (1e-7 / sqrt(2)) degrees.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct IntLatLngSnapFunction {
+    exponent: i32,
+    snap_radius: S1Angle,
+    // The multiplier that converts degrees to integer coordinates, and its
+    // inverse.
+    from_degrees: f64,
+    to_degrees: f64,
+}
+
+impl IntLatLngSnapFunction {
+    /// The minimum exponent supported for snapping.
+    pub const MIN_EXPONENT: i32 = 0;
+
+    /// The maximum exponent supported for snapping.
+    pub const MAX_EXPONENT: i32 = 10;
+
+    /// Snaps vertices to points whose (lat, lng) coordinates are integers
+    /// after converting to degrees and multiplying by 10 raised to the given
+    /// exponent, using the minimum snap radius for that exponent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::s2::{IntLatLngSnapFunction, S2LatLng, SnapFunction};
+    ///
+    /// let snap = IntLatLngSnapFunction::new(6);
+    /// let p = S2LatLng::from_degrees(23.12345651, -45.65432149).to_point();
+    /// let snapped = S2LatLng::from_point(&snap.snap_point(&p));
+    /// assert!((snapped.lat().degrees() - 23.123457).abs() < 1e-12);
+    /// assert!((snapped.lng().degrees() + 45.654321).abs() < 1e-12);
+    /// ```
+    pub fn new(exponent: i32) -> IntLatLngSnapFunction {
+        let mut result = IntLatLngSnapFunction {
+            exponent: 0,
+            snap_radius: S1Angle::zero(),
+            from_degrees: 1.0,
+            to_degrees: 1.0,
+        };
+        result.set_exponent(exponent);
+        result
+    }
+
+    /// Like new(), but also sets the snap radius (see set_snap_radius).
+    pub fn with_snap_radius(exponent: i32, snap_radius: S1Angle) -> IntLatLngSnapFunction {
+        let mut result = IntLatLngSnapFunction::new(exponent);
+        result.set_snap_radius(snap_radius);
+        result
+    }
+
+    /// Snaps vertices to points whose (lat, lng) coordinates are integers
+    /// after converting to degrees and multiplying by 10 raised to the given
+    /// exponent. For example, (exponent == 7) yields E7 coordinates. As a
+    /// side effect, this method also resets "snap_radius" to the minimum
+    /// value allowed for this exponent:
+    ///
+    ///   set_snap_radius(min_snap_radius_for_exponent(exponent))
+    ///
+    /// This means that if you want to use a larger snap radius than the
+    /// minimum, you must call set_snap_radius() *after* calling
+    /// set_exponent().
+    pub fn set_exponent(&mut self, exponent: i32) {
+        debug_assert!((Self::MIN_EXPONENT..=Self::MAX_EXPONENT).contains(&exponent));
+        self.exponent = exponent;
+        self.set_snap_radius(IntLatLngSnapFunction::min_snap_radius_for_exponent(
+            exponent,
+        ));
+
+        // Precompute the scale factors needed for snapping. Note that these
+        // calculations need to exactly match the ones in
+        // min_snap_radius_for_exponent().
+        let power = 10_f64.powi(exponent);
+        self.from_degrees = power;
+        self.to_degrees = 1.0 / power;
+    }
+
+    pub fn exponent(&self) -> i32 {
+        self.exponent
+    }
+
+    /// Defines the snap radius to be used (see the SnapFunction trait). The
+    /// snap radius must be at least the minimum value for the current
+    /// exponent(), but larger values can also be used (e.g., to simplify
+    /// the geometry).
+    pub fn set_snap_radius(&mut self, snap_radius: S1Angle) {
+        debug_assert!(
+            snap_radius >= IntLatLngSnapFunction::min_snap_radius_for_exponent(self.exponent)
+        );
+        debug_assert!(snap_radius <= max_snap_radius());
+        self.snap_radius = snap_radius;
+    }
+
+    /// Returns the minimum allowable snap radius for the given exponent
+    /// (approximately equal to (10**(-exponent) / sqrt(2)) degrees).
+    pub fn min_snap_radius_for_exponent(exponent: i32) -> S1Angle {
+        // snap_radius() needs to be an upper bound on the true distance that
+        // a point can move when snapped, taking into account numerical errors.
+        //
+        // The maximum errors in latitude and longitude can be bounded as
+        // follows (as absolute errors in terms of DBL_EPSILON):
+        //
+        //                                      Latitude      Longitude
+        // Convert to S2LatLng:                    1.000            1.000
+        // Convert to degrees:                     1.032            2.063
+        // Scale by 10**exp:                       0.786            1.571
+        // Round to integer: 0.5 * S1Angle::from_degrees(to_degrees)
+        // Scale by 10**(-exp):                    1.375            2.750
+        // Convert to radians:                     1.252            1.503
+        // ------------------------------------------------------------
+        // Total (except for rounding)             5.445            8.886
+        //
+        // The maximum error when converting the S2LatLng back to an S2Point is
+        //
+        //   sqrt(2) * (maximum error in latitude or longitude) + 1.5 * DBL_EPSILON
+        //
+        // which works out to (9 * sqrt(2) + 1.5) * DBL_EPSILON radians.
+        // Finally we need to consider the effect of rounding to integer
+        // coordinates (much larger than the errors above), which can change
+        // the position by up to (sqrt(2) * 0.5 * to_degrees) radians.
+        let power = 10_f64.powi(exponent);
+        S1Angle::from_radians(
+            S1Angle::from_degrees(FRAC_1_SQRT_2 / power).radians()
+                + (9.0 * SQRT_2 + 1.5) * f64::EPSILON,
+        )
+    }
+
+    /// Returns the minimum exponent such that vertices will not move by more
+    /// than "snap_radius". This can be useful when choosing an appropriate
+    /// exponent for snapping. The return value is always a valid exponent
+    /// (out of range values are silently clamped).
+    pub fn exponent_for_max_snap_radius(snap_radius: S1Angle) -> i32 {
+        // When choosing an exponent, we need to account for the error bound
+        // of (9 * sqrt(2) + 1.5) * DBL_EPSILON added by
+        // min_snap_radius_for_exponent().
+        let radians = (snap_radius.radians() - (9.0 * SQRT_2 + 1.5) * f64::EPSILON).max(1e-30);
+        let exponent = (FRAC_1_SQRT_2 / S1Angle::from_radians(radians).degrees()).log10();
+
+        // There can be small errors in the calculation above, so to ensure
+        // that this function is the inverse of min_snap_radius_for_exponent()
+        // we subtract a small error tolerance.
+        ((exponent - 2.0 * f64::EPSILON).ceil() as i32)
+            .clamp(Self::MIN_EXPONENT, Self::MAX_EXPONENT)
+    }
+}
+
+impl SnapFunction for IntLatLngSnapFunction {
+    fn snap_radius(&self) -> S1Angle {
+        self.snap_radius
+    }
+
+    fn min_vertex_separation(&self) -> S1Angle {
+        // We have two bounds for the minimum vertex separation: one is
+        // proportional to snap_radius, and one is equal to snap_radius minus
+        // a constant. These bounds give the best results for small and large
+        // snap radii respectively. We return the maximum of the two bounds.
+        //
+        // 1. Proportional bound: It can be shown that in the plane, the
+        //    worst-case configuration has a vertex separation of
+        //    (sqrt(2) / 3) * snap_radius. This is approximately 0.471, which
+        //    is used below.
+        //
+        // 2. Best asymptotic bound: This bound is derived by observing we
+        //    only select a new site when it is at least snap_radius() away
+        //    from all existing sites, and snapping a vertex can move it by
+        //    up to ((1 / sqrt(2)) * to_degrees) degrees.
+        let r = self.snap_radius.radians();
+        let max_move = S1Angle::from_degrees(FRAC_1_SQRT_2 * self.to_degrees).radians();
+        S1Angle::from_radians((0.471 * r).max(r - max_move))
+    }
+
+    fn snap_point(&self, point: &S2Point) -> S2Point {
+        debug_assert!(self.exponent >= 0); // Make sure the snap function was initialized.
+        let input = S2LatLng::from_point(point);
+        let lat = (input.lat().degrees() * self.from_degrees).round();
+        let lng = (input.lng().degrees() * self.from_degrees).round();
+        S2LatLng::from_degrees(lat * self.to_degrees, lng * self.to_degrees)
+            .normalized()
+            .to_point()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+
+    /// Returns points clustered around "center" within roughly "radius".
+    fn clustered_points(rng: &mut StdRng, center: &S2Point, radius: f64, n: usize) -> Vec<S2Point> {
+        let u = center.ortho();
+        let v = center.cross_prod(&u);
+        (0..n)
+            .map(|_| {
+                let (a, b) = (
+                    rng.gen_range(-radius..radius),
+                    rng.gen_range(-radius..radius),
+                );
+                (*center + u * a + v * b).normalize()
+            })
+            .collect()
+    }
+
+    fn distance(a: &S2Point, b: &S2Point) -> S1Angle {
+        S1Angle::from_points(a, b)
+    }
+
+    /// Checks the guarantees documented on SnapFunction for the given input
+    /// points: snapping is idempotent, moves points by at most snap_radius,
+    /// and sites selected the way a geometry builder does (only creating a
+    /// new site for inputs farther than snap_radius from all existing
+    /// sites) are separated by at least min_vertex_separation.
+    fn check_snap_function(snap: &impl SnapFunction, points: &[S2Point]) {
+        let mut sites: Vec<S2Point> = Vec::new();
+        for p in points {
+            let site = snap.snap_point(p);
+            assert_eq!(snap.snap_point(&site), site);
+            assert!(distance(p, &site) <= snap.snap_radius());
+            if sites.iter().all(|s| distance(p, s) > snap.snap_radius()) {
+                sites.push(site);
+            }
+        }
+        assert!(sites.len() > 1);
+        for (i, a) in sites.iter().enumerate() {
+            for b in &sites[..i] {
+                assert!(
+                    distance(a, b) >= snap.min_vertex_separation(),
+                    "{:?} < {:?}",
+                    distance(a, b),
+                    snap.min_vertex_separation()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_identity_snap_function() {
+        let snap = IdentitySnapFunction::new(S1Angle::from_degrees(1.0));
+        let p = S2Point::new(0.3, -0.2, 0.9).normalize();
+        assert_eq!(snap.snap_point(&p), p);
+        assert_eq!(snap.min_vertex_separation(), snap.snap_radius());
+        assert_eq!(
+            IdentitySnapFunction::default().snap_radius(),
+            S1Angle::zero()
+        );
+
+        let mut rng = StdRng::seed_from_u64(15);
+        let points = clustered_points(&mut rng, &p, 0.1, 200);
+        check_snap_function(&snap, &points);
+    }
+
+    #[test]
+    fn test_s2cell_id_snap_function() {
+        let mut rng = StdRng::seed_from_u64(16);
+        for level in [0, 1, 2, 5, 10, 20, 30] {
+            let snap = S2CellIdSnapFunction::new(level);
+            assert_eq!(snap.level(), level);
+            assert_eq!(
+                S2CellIdSnapFunction::level_for_max_snap_radius(snap.snap_radius()),
+                level
+            );
+            let center = S2Point::new(
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+            )
+            .normalize();
+            let radius = 3.0 * s2metrics::MAX_DIAG.get_value(level);
+            check_snap_function(&snap, &clustered_points(&mut rng, &center, radius, 300));
+
+            // Larger snap radii are allowed as well (up to max_snap_radius).
+            if level >= 2 {
+                let snap = S2CellIdSnapFunction::with_snap_radius(
+                    level,
+                    S1Angle::from_radians(3.0 * snap.snap_radius().radians()),
+                );
+                let radius = 10.0 * s2metrics::MAX_DIAG.get_value(level);
+                check_snap_function(&snap, &clustered_points(&mut rng, &center, radius, 300));
+            }
+        }
+
+        // Snapped points are exactly the cell centers.
+        let snap = S2CellIdSnapFunction::new(12);
+        let id = S2CellId::from_point(&S2Point::new(-0.1, 0.5, 0.3)).parent_at_level(12);
+        assert_eq!(snap.snap_point(&S2Point::from(id)), S2Point::from(id));
+    }
+
+    #[test]
+    fn test_int_latlng_snap_function() {
+        let mut rng = StdRng::seed_from_u64(17);
+        for exponent in IntLatLngSnapFunction::MIN_EXPONENT..=IntLatLngSnapFunction::MAX_EXPONENT {
+            let snap = IntLatLngSnapFunction::new(exponent);
+            assert_eq!(snap.exponent(), exponent);
+            assert_eq!(
+                IntLatLngSnapFunction::exponent_for_max_snap_radius(snap.snap_radius()),
+                exponent
+            );
+            let spacing = S1Angle::from_degrees(10_f64.powi(-exponent)).radians();
+            // Include clusters near the poles, where the longitude spacing
+            // of the grid becomes very small.
+            for lat in [0.0, 45.0, -70.0, 89.9] {
+                let center = S2LatLng::from_degrees(lat, rng.gen_range(-180.0..180.0)).to_point();
+                let points = clustered_points(&mut rng, &center, 3.0 * spacing, 300);
+                check_snap_function(&snap, &points);
+            }
+        }
+
+        // Points exactly on the poles or the antimeridian stay there.
+        let snap = IntLatLngSnapFunction::new(7);
+        let pole = S2Point::new(0.0, 0.0, 1.0);
+        assert!(distance(&snap.snap_point(&pole), &pole).radians() < 1e-15);
+        let p = S2LatLng::from_degrees(10.0, 180.0).to_point();
+        assert!(distance(&snap.snap_point(&p), &p).radians() < 1e-15);
+    }
+}
@@ -0,0 +1,520 @@
+//! Extended precision floating-point arithmetic built from pairs or
+//! sequences of doubles, for predicates whose double-precision result is
+//! too close to zero to be trusted.
+//!
+//! - [`two_sum`] and [`two_product`] are the error-free transformations
+//!   (Knuth's TwoSum and Dekker's TwoProduct, the latter via a fused
+//!   multiply-add) that everything else is built on.
+//! - [`DoubleDouble`] represents a value as the unevaluated sum of two
+//!   doubles, giving about 106 bits of precision. Sums and products of two
+//!   doubles are represented exactly, and further operations have a relative
+//!   error of about 2**-104, which is enough to decide the sign of most
+//!   determinants that double precision cannot.
+//! - [`Expansion`] represents a value exactly as the sum of up to 24
+//!   nonoverlapping doubles (enough for a 3x3 determinant), and its sign is
+//!   always exact. See J. R. Shewchuk, "Adaptive Precision Floating-Point
+//!   Arithmetic and Fast Robust Geometric Predicates".
+//!
+//! These guarantees hold only as long as no intermediate result overflows
+//! or underflows, which holds for the coordinates of points on the unit
+//! sphere whose nonzero components exceed about 1e-100 in magnitude.
+
+use core::{
+    cmp::Ordering,
+    ops::{Add, Mul, Neg, Sub},
+};
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+/// Returns (x, y) such that x = fl(a + b) and x + y = a + b exactly.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::util::math::exact_float::two_sum;
+///
+/// assert_eq!(two_sum(1.0, 1e-20), (1.0, 1e-20));
+/// ```
+pub fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    let b_virtual = x - a;
+    let a_virtual = x - b_virtual;
+    let b_roundoff = b - b_virtual;
+    let a_roundoff = a - a_virtual;
+    (x, a_roundoff + b_roundoff)
+}
+
+/// Returns (x, y) such that x = fl(a * b) and x + y = a * b exactly (unless
+/// the product underflows).
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::util::math::exact_float::two_product;
+///
+/// let a = 1.0 + f64::EPSILON;
+/// assert_eq!(
+///     two_product(a, a),
+///     (1.0 + 2.0 * f64::EPSILON, f64::EPSILON * f64::EPSILON)
+/// );
+/// ```
+pub fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let x = a * b;
+    (x, a.mul_add(b, -x))
+}
+
+// Like two_sum(), but requires |a| >= |b| (or a == 0).
+fn quick_two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    (x, b - (x - a))
+}
+
+/// A value represented as the unevaluated sum of two doubles "hi" and "lo",
+/// where hi = fl(hi + lo). Values are always stored in this normalized
+/// form, so comparisons and the sign only need to look at "hi" unless it is
+/// zero or equal.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::util::math::exact_float::DoubleDouble;
+///
+/// // 1 + 2**-60 is not representable as an f64, but survives as a
+/// // DoubleDouble.
+/// let tiny = DoubleDouble::from_f64(2f64.powi(-60));
+/// let x = DoubleDouble::from_f64(1.0) + tiny;
+/// assert_eq!(x.to_f64_rounded(), 1.0);
+/// assert_eq!(x - DoubleDouble::from_f64(1.0), tiny);
+/// ```
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct DoubleDouble {
+    hi: f64,
+    lo: f64,
+}
+
+impl DoubleDouble {
+    /// Returns the value x, which is always exactly representable.
+    pub fn from_f64(x: f64) -> DoubleDouble {
+        DoubleDouble { hi: x, lo: 0.0 }
+    }
+
+    /// Returns the exact sum a + b.
+    pub fn from_sum(a: f64, b: f64) -> DoubleDouble {
+        let (hi, lo) = two_sum(a, b);
+        DoubleDouble { hi, lo }
+    }
+
+    /// Returns the exact product a * b.
+    pub fn from_product(a: f64, b: f64) -> DoubleDouble {
+        let (hi, lo) = two_product(a, b);
+        DoubleDouble { hi, lo }
+    }
+
+    /// The high-order component, which is the value rounded to the nearest
+    /// double.
+    pub fn hi(&self) -> f64 {
+        self.hi
+    }
+
+    /// The low-order component, i.e. the rounding error of hi().
+    pub fn lo(&self) -> f64 {
+        self.lo
+    }
+
+    /// Returns the value rounded to a double. This is the nearest double
+    /// except possibly when the value is halfway between two doubles.
+    pub fn to_f64_rounded(&self) -> f64 {
+        self.hi
+    }
+
+    /// Returns +1, -1 or 0 according to the sign of the value.
+    pub fn sign(&self) -> i32 {
+        match self.partial_cmp(&DoubleDouble::default()) {
+            Some(Ordering::Greater) => 1,
+            Some(Ordering::Less) => -1,
+            _ => 0,
+        }
+    }
+
+    /// Returns true if the value is zero. Since values are normalized, this
+    /// only needs to check the high-order component.
+    pub fn is_zero(&self) -> bool {
+        self.hi == 0.0
+    }
+
+    /// Returns true if neither component is infinite or NaN.
+    pub fn is_finite(&self) -> bool {
+        self.hi.is_finite() && self.lo.is_finite()
+    }
+}
+
+impl From<f64> for DoubleDouble {
+    fn from(x: f64) -> DoubleDouble {
+        DoubleDouble::from_f64(x)
+    }
+}
+
+impl PartialOrd for DoubleDouble {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        // Since both values are normalized, the high components determine the
+        // order unless they are equal.
+        match self.hi.partial_cmp(&other.hi)? {
+            Ordering::Equal => self.lo.partial_cmp(&other.lo),
+            order => Some(order),
+        }
+    }
+}
+
+impl Neg for DoubleDouble {
+    type Output = DoubleDouble;
+
+    fn neg(self) -> DoubleDouble {
+        DoubleDouble {
+            hi: -self.hi,
+            lo: -self.lo,
+        }
+    }
+}
+
+impl Add for DoubleDouble {
+    type Output = DoubleDouble;
+
+    fn add(self, other: DoubleDouble) -> DoubleDouble {
+        // This is the accurate ("IEEE") variant of double-double addition,
+        // which sums the high and low components separately so that the
+        // relative error stays small even when the high components cancel.
+        let (s, e) = two_sum(self.hi, other.hi);
+        let (t, f) = two_sum(self.lo, other.lo);
+        let (s, e) = quick_two_sum(s, e + t);
+        let (hi, lo) = quick_two_sum(s, e + f);
+        DoubleDouble { hi, lo }
+    }
+}
+
+impl Sub for DoubleDouble {
+    type Output = DoubleDouble;
+
+    fn sub(self, other: DoubleDouble) -> DoubleDouble {
+        self + -other
+    }
+}
+
+impl Mul for DoubleDouble {
+    type Output = DoubleDouble;
+
+    fn mul(self, other: DoubleDouble) -> DoubleDouble {
+        let (p, e) = two_product(self.hi, other.hi);
+        let e = e + (self.hi * other.lo + self.lo * other.hi);
+        let (hi, lo) = quick_two_sum(p, e);
+        DoubleDouble { hi, lo }
+    }
+}
+
+impl Mul<f64> for DoubleDouble {
+    type Output = DoubleDouble;
+
+    fn mul(self, other: f64) -> DoubleDouble {
+        let (p, e) = two_product(self.hi, other);
+        let (hi, lo) = quick_two_sum(p, e + self.lo * other);
+        DoubleDouble { hi, lo }
+    }
+}
+
+/// An exact sum of nonoverlapping doubles sorted by increasing magnitude,
+/// with zero components eliminated. The capacity is enough for the
+/// determinant computed by s2::exact_sign(): each cross product component
+/// has at most 4 terms, scaling doubles that, and the sum of three scaled
+/// components has at most 24.
+///
+/// # Examples
+///
+/// ```
+/// use s2shell::util::math::exact_float::Expansion;
+///
+/// let mut sum = Expansion::new();
+/// for x in [1e30, 1.0, -1e30] {
+///     sum.grow(x);
+/// }
+/// assert_eq!(sum.terms(), [1.0]);
+/// assert_eq!(sum.sign(), 1);
+/// assert_eq!(sum.estimate(), 1.0);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Expansion {
+    terms: [f64; Expansion::CAPACITY],
+    len: usize,
+}
+
+impl Expansion {
+    /// The maximum number of nonzero components.
+    pub const CAPACITY: usize = 24;
+
+    /// Returns an expansion with no components, whose value is zero.
+    pub fn new() -> Expansion {
+        Expansion {
+            terms: [0.0; Expansion::CAPACITY],
+            len: 0,
+        }
+    }
+
+    /// Returns the exact value of a * b - c * d.
+    pub fn difference_of_products(a: f64, b: f64, c: f64, d: f64) -> Expansion {
+        let (ab, ab_err) = two_product(a, b);
+        let (cd, cd_err) = two_product(c, d);
+        let mut result = Expansion::new();
+        for x in [ab_err, -cd_err, ab, -cd] {
+            result.grow(x);
+        }
+        result
+    }
+
+    /// The nonzero components, in order of increasing magnitude.
+    pub fn terms(&self) -> &[f64] {
+        &self.terms[..self.len]
+    }
+
+    /// Adds "b" to the expansion exactly (Shewchuk's GROW-EXPANSION).
+    ///
+    /// REQUIRES: the result has at most CAPACITY nonzero components.
+    pub fn grow(&mut self, b: f64) {
+        let mut q = b;
+        let mut len = 0;
+        for k in 0..self.len {
+            let (sum, err) = two_sum(q, self.terms[k]);
+            if err != 0.0 {
+                self.terms[len] = err;
+                len += 1;
+            }
+            q = sum;
+        }
+        if q != 0.0 {
+            assert!(len < Expansion::CAPACITY, "expansion is full");
+            self.terms[len] = q;
+            len += 1;
+        }
+        self.len = len;
+    }
+
+    /// Returns the exact product of the expansion and the scalar "b".
+    pub fn scale(&self, b: f64) -> Expansion {
+        let mut result = Expansion::new();
+        for &x in self.terms() {
+            let (product, err) = two_product(x, b);
+            result.grow(err);
+            result.grow(product);
+        }
+        result
+    }
+
+    /// Returns +1, -1 or 0 according to the sign of the exact sum. The
+    /// components do not overlap, so this is the sign of the largest one.
+    pub fn sign(&self) -> i32 {
+        match self.terms().last() {
+            Some(&x) if x > 0.0 => 1,
+            Some(&x) if x < 0.0 => -1,
+            _ => 0,
+        }
+    }
+
+    /// Returns an approximation of the exact sum, accurate to within a few
+    /// units in the last place.
+    pub fn estimate(&self) -> f64 {
+        self.terms().iter().sum()
+    }
+}
+
+impl Default for Expansion {
+    fn default() -> Self {
+        Expansion::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+
+    type Point = [f64; 3];
+
+    fn point(bits: [u64; 3]) -> Point {
+        bits.map(f64::from_bits)
+    }
+
+    // Computes a.(b x c) in double precision.
+    fn det_f64(a: &Point, b: &Point, c: &Point) -> f64 {
+        let x = b[1] * c[2] - b[2] * c[1];
+        let y = b[2] * c[0] - b[0] * c[2];
+        let z = b[0] * c[1] - b[1] * c[0];
+        a[0] * x + a[1] * y + a[2] * z
+    }
+
+    // Computes a.(b x c) as a DoubleDouble.
+    fn det_double_double(a: &Point, b: &Point, c: &Point) -> DoubleDouble {
+        let minor = |p: f64, q: f64, r: f64, s: f64| {
+            DoubleDouble::from_product(p, q) - DoubleDouble::from_product(r, s)
+        };
+        minor(b[1], c[2], b[2], c[1]) * a[0]
+            + minor(b[2], c[0], b[0], c[2]) * a[1]
+            + minor(b[0], c[1], b[1], c[0]) * a[2]
+    }
+
+    // Computes a.(b x c) exactly.
+    fn det_expansion(a: &Point, b: &Point, c: &Point) -> Expansion {
+        let terms = [
+            (a[0], b[1], c[2], 1.0),
+            (a[0], b[2], c[1], -1.0),
+            (a[1], b[2], c[0], 1.0),
+            (a[1], b[0], c[2], -1.0),
+            (a[2], b[0], c[1], 1.0),
+            (a[2], b[1], c[0], -1.0),
+        ];
+        let mut expansion = Expansion::new();
+        for (p, q, r, s) in terms {
+            let (pq, pq_err) = two_product(p, q);
+            let (x0, x1) = two_product(pq, r);
+            let (x2, x3) = two_product(pq_err, r);
+            for x in [x0, x1, x2, x3] {
+                expansion.grow(s * x);
+            }
+        }
+        expansion
+    }
+
+    #[test]
+    fn test_error_free_transformations() {
+        let mut rng = StdRng::seed_from_u64(34);
+        for _ in 0..1000 {
+            let a = rng.gen_range(-1.0..1.0);
+            let b = rng.gen_range(-1.0..1.0) * 2f64.powi(-(rng.gen_range(0..60)));
+            let (x, y) = two_sum(a, b);
+            assert_eq!(x, a + b);
+            assert!(y.abs() <= 0.5 * f64::EPSILON * x.abs());
+            // Undo the sum: both operations below are exact.
+            assert_eq!((x - a) - (b - y), 0.0);
+
+            let (x, y) = two_product(a, b);
+            assert_eq!(x, a * b);
+            assert!(y.abs() <= 0.5 * f64::EPSILON * x.abs());
+            assert_eq!(a.mul_add(b, -x), y);
+        }
+    }
+
+    #[test]
+    fn test_double_double_arithmetic() {
+        let one = DoubleDouble::from_f64(1.0);
+        let eps = DoubleDouble::from_f64(f64::EPSILON);
+        let x = one + eps * eps;
+        assert_eq!(x.hi(), 1.0);
+        assert_eq!(x.lo(), f64::EPSILON * f64::EPSILON);
+        assert_eq!(x - one, eps * eps);
+        assert_eq!((x - one) * DoubleDouble::from_f64(4.0), eps * eps * 4.0);
+        assert_eq!(-x + x, DoubleDouble::default());
+
+        // (1 + e)^2 = 1 + 2e + e^2 is represented exactly.
+        let y = one + eps;
+        let square = y * y;
+        assert_eq!(square.hi(), 1.0 + 2.0 * f64::EPSILON);
+        assert_eq!(square.lo(), f64::EPSILON * f64::EPSILON);
+        assert_eq!(square, DoubleDouble::from_product(y.hi(), y.hi()));
+
+        // Sums of doubles are exact.
+        let sum = DoubleDouble::from_sum(1e20, 1.0);
+        assert_eq!(sum - DoubleDouble::from_f64(1e20), one);
+        assert_eq!(sum.to_f64_rounded(), 1e20);
+        assert!(sum.is_finite());
+        assert!(!DoubleDouble::from_f64(f64::INFINITY).is_finite());
+    }
+
+    #[test]
+    fn test_double_double_ordering() {
+        let one = DoubleDouble::from_f64(1.0);
+        let tiny = DoubleDouble::from_f64(1e-30);
+        assert!(one + tiny > one);
+        assert!(one - tiny < one);
+        assert!(one - tiny > DoubleDouble::from_f64(1.0 - f64::EPSILON));
+        assert_eq!((one + tiny).sign(), 1);
+        assert_eq!((tiny - one).sign(), -1);
+        assert_eq!((one - one).sign(), 0);
+        assert!((one - one).is_zero());
+        assert_eq!(DoubleDouble::from_f64(f64::NAN).sign(), 0);
+        assert_eq!(DoubleDouble::from(2.0), DoubleDouble::from_f64(2.0));
+    }
+
+    #[test]
+    fn test_expansion() {
+        let mut sum = Expansion::new();
+        assert_eq!(sum.sign(), 0);
+        assert_eq!(sum.estimate(), 0.0);
+
+        // 1 + 2^-60 is not representable, but the expansion keeps both terms.
+        sum.grow(1.0);
+        sum.grow(2f64.powi(-60));
+        assert_eq!(sum.terms(), [2f64.powi(-60), 1.0]);
+        sum.grow(-1.0);
+        assert_eq!(sum.terms(), [2f64.powi(-60)]);
+        assert_eq!(sum.estimate(), 2f64.powi(-60));
+        assert_eq!(sum.scale(-4.0).terms(), [-(2f64.powi(-58))]);
+        assert_eq!(sum.scale(-4.0).sign(), -1);
+
+        // (1 + 2^-30)^2 - 1 - 2^-29 == 2^-60 exactly.
+        let x = 1.0 + 2f64.powi(-30);
+        let mut d = Expansion::difference_of_products(x, x, 1.0, 1.0);
+        d.grow(-(2f64.powi(-29)));
+        assert_eq!(d.terms(), [2f64.powi(-60)]);
+        d.grow(-(2f64.powi(-60)));
+        assert_eq!(d.sign(), 0);
+    }
+
+    #[test]
+    fn test_nearly_degenerate_determinants() {
+        // Nearly collinear points for which the double-precision determinant
+        // has the wrong sign. The expected values are the exact rational
+        // determinants rounded to the nearest double.
+        let cases = [
+            (
+                [0x3f8e74ee6deceb80, 0xbfed99abcf4ffae6, 0xbfc0fc98b29e5570],
+                [0xbfeb877d1e131f48, 0xbfea31c20b97748a, 0xbfc352b5de1bc450],
+                [0xbff211b2dc303cb2, 0xbfe925b97777cc55, 0xbfc40a93630dba29],
+                -1.5127175616987808e-18,
+            ),
+            (
+                [0xbfe2d1928c0757d8, 0x3fd71758a881ed14, 0xbfc2894f8720f120],
+                [0xbfd7ca06888ce510, 0x3fc5e761dd8cd4f0, 0xbfb7f837a8dbd5b0],
+                [0xbfe85d45b20a5e0a, 0x3fe0682ef4846ba4, 0xbfc7c8a2a89c4aae],
+                7.635456752942809e-20,
+            ),
+            (
+                [0xbfdb24daf641b434, 0x3feebb2f4895ea56, 0xbfe8719c3be78fc4],
+                [0xbfc4f5e71ab8a168, 0x3fe074ff3abc87c0, 0xbfe645e2a888a9a2],
+                [0xbfdbe12f4aa84f2a, 0x3fef5c8220b85405, 0xbfe88a24eadea29d],
+                -3.1106524955292795e-18,
+            ),
+            (
+                [0xbfed7da0d84dc8f2, 0x3fd58818e13deef8, 0x3fe0eebaa476f06a],
+                [0x3fc2b1d3f9404268, 0x3fe807d4168bfcd6, 0xbfd7d71f1a8edbdc],
+                [0xbfb66ac9dda9c630, 0x3fe520c4d617d8c4, 0xbfc66d36eb67010c],
+                9.998428207852491e-18,
+            ),
+        ];
+        for (a, b, c, expected) in cases {
+            let (a, b, c) = (point(a), point(b), point(c));
+            let expected_sign = if expected > 0.0 { 1 } else { -1 };
+            assert!(det_f64(&a, &b, &c) * expected <= 0.0);
+
+            let exact = det_expansion(&a, &b, &c);
+            assert_eq!(exact.sign(), expected_sign);
+            assert!((exact.estimate() - expected).abs() <= 4.0 * f64::EPSILON * expected.abs());
+
+            let dd = det_double_double(&a, &b, &c);
+            assert_eq!(dd.sign(), expected_sign);
+            assert!((dd.to_f64_rounded() - expected).abs() <= 1e-12 * expected.abs());
+        }
+
+        // Exactly collinear points.
+        let (a, b, c) = ([1.0, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.0]);
+        assert_eq!(det_expansion(&a, &b, &c).sign(), 0);
+        assert_eq!(det_double_double(&a, &b, &c).sign(), 0);
+    }
+}
@@ -1,8 +1,20 @@
 use num_traits::{Num, NumCast};
 
+pub mod exact_float;
 pub mod vector;
 pub use vector::Vector2;
 pub use vector::Vector3;
 
 pub trait Scalar: Copy + Clone + Num + NumCast + PartialOrd {}
 impl<T> Scalar for T where T: Copy + Clone + Num + NumCast + PartialOrd {}
+
+/// The IEEE 754 remainder of x / y, i.e. x - n * y where n is the integer
+/// nearest to x / y (with ties rounded to even). This matches the C++
+/// std::remainder() function, and the result lies in the range [-y/2, y/2].
+pub fn remainder(x: f64, y: f64) -> f64 {
+    #[cfg(feature = "std")]
+    let n = (x / y).round_ties_even();
+    #[cfg(not(feature = "std"))]
+    let n = libm::rint(x / y);
+    x - n * y
+}
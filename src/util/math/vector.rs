@@ -13,12 +13,18 @@
 // limitations under the License.
 //
 
-use std::ops::{Add, Index, IndexMut, Mul, Sub};
+use core::{
+    iter::Sum,
+    ops::{Add, Index, IndexMut, Mul, Neg, Sub},
+};
 
-use approx::{AbsDiffEq, RelativeEq};
+use approx::{AbsDiffEq, RelativeEq, UlpsEq};
 use num_traits::{Float, Signed};
 
-use crate::util::math::Scalar;
+use crate::util::math::{
+    exact_float::{two_product, two_sum, DoubleDouble},
+    Scalar,
+};
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Vector2<T: Scalar> {
@@ -309,6 +315,29 @@ impl<T: Scalar> Vector3<T> {
             self.dot_prod(other).to_f64().unwrap(),
         )
     }
+
+    /// Returns the angle between two vectors that are both known to be unit
+    /// length. This avoids computing a cross product by using the chord
+    /// length between the two points, i.e. 2 * asin(0.5 * |a - b|).
+    ///
+    /// The result is very accurate for small angles, but loses precision as
+    /// the vectors approach being antipodal (the error is roughly
+    /// sqrt(DBL_EPSILON) radians at an angle of Pi). Use angle() if the
+    /// vectors may not be unit length or may be nearly antipodal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::util::math::Vector3;
+    ///
+    /// let a = Vector3::new(1.0, 0.0, 0.0);
+    /// let b = Vector3::new(0.0, 1.0, 0.0);
+    /// assert!((a.angle_unit(&b) - std::f64::consts::FRAC_PI_2).abs() < 1e-15);
+    /// ```
+    pub fn angle_unit(&self, other: &Vector3<T>) -> f64 {
+        // The chord length can slightly exceed 2 due to rounding errors.
+        2.0 * (0.5 * (*self - *other).norm()).min(1.0).asin()
+    }
 }
 
 impl<T: Scalar> Vector2<T>
@@ -357,22 +386,41 @@ where
     /// # Examples
     ///
     /// ```
+    /// use approx::assert_relative_eq;
     /// use s2shell::util::math::Vector3;
     ///
     /// let v = Vector3::new(3.0, -4.0, 3.0);
     ///
     /// // Check properties of orthogonal unit vectors
-    /// assert_eq!(v.ortho().norm(), 1.0);
-    /// assert_eq!(v.dot_prod(&v.ortho()), 0.0);
+    /// assert_relative_eq!(v.ortho().norm(), 1.0);
+    /// assert_relative_eq!(v.dot_prod(&v.ortho()), 0.0);
     /// ```
     pub fn ortho(&self) -> Vector3<f64> {
-        let k = self.largest_abs_component() - 1;
-        let k = if k == 0 { 2 } else { k - 1 };
+        self.ortho_raw().normalize()
+    }
 
-        let mut temp: Vector3<T> = Vector3::zero();
-        temp[k as usize] = T::one();
+    /// Vector orthogonal to this vector, without normalization. The result is
+    /// the cross product of this vector with a coordinate axis chosen so that
+    /// the two are not close to parallel: the axis "before" the largest
+    /// component (x -> z, y -> x, z -> y).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::util::math::Vector3;
+    ///
+    /// let v = Vector3::new(1, 2, 3);
+    /// assert_eq!(v.ortho_raw(), Vector3::new(-3, 0, 1));
+    /// assert_eq!(v.dot_prod(&v.ortho_raw()), 0);
+    /// ```
+    pub fn ortho_raw(&self) -> Vector3<T> {
+        // Maps the index of the largest component to the axis that is crossed
+        // with this vector.
+        const ORTHO_AXIS: [usize; 3] = [2, 0, 1];
 
-        self.cross_prod(&temp).normalize()
+        let mut temp: Vector3<T> = Vector3::zero();
+        temp[ORTHO_AXIS[self.largest_abs_component() as usize]] = T::one();
+        self.cross_prod(&temp)
     }
 
     /// Vector with the absolute values of each component.
@@ -394,7 +442,9 @@ where
 
     /// Index of the component with the largest absolute value.
     ///
-    /// 0 for x, 1 for y, 2 for z.
+    /// 0 for x, 1 for y, 2 for z. When several components tie for the largest
+    /// absolute value, the one with the highest index is returned (so a vector
+    /// with |x| == |y| returns 1, and one with |x| == |y| == |z| returns 2).
     ///
     /// # Examples
     /// ```
@@ -408,6 +458,9 @@ where
     ///
     /// let v3 = Vector3::new(0.0, 1.0, -2.0);
     /// assert_eq!(v3.largest_abs_component(), 2);
+    ///
+    /// let v4 = Vector3::new(-2.0, 2.0, 1.0);
+    /// assert_eq!(v4.largest_abs_component(), 1);
     /// ```
     // Could collapse into else if, but I think this makes more sense logically
     #[allow(clippy::collapsible_else_if)]
@@ -422,6 +475,105 @@ where
     }
 }
 
+// Returns a * b - c * d with an error of at most 1.5 ulps, using Kahan's
+// algorithm: the rounding error of c * d is recovered exactly with an FMA.
+fn diff_of_products(a: f64, b: f64, c: f64, d: f64) -> f64 {
+    let cd = c * d;
+    let err = (-c).mul_add(d, cd);
+    a.mul_add(b, -cd) + err
+}
+
+/// Variants of the basic vector operations that are more accurate than the
+/// default ones, at the cost of a few extra floating-point operations. These
+/// matter in the cancellation-heavy cases that geometric predicates care
+/// about (e.g. nearly parallel vectors); the default versions remain the
+/// right choice everywhere else.
+impl Vector3<f64> {
+    /// Dot product computed as if in twice the working precision (Ogita,
+    /// Rump and Oishi's Dot2), and then rounded. The result is accurate to
+    /// within about one ulp unless the dot product is extremely ill
+    /// conditioned, in which case the relative error is at most about
+    /// DBL_EPSILON**2 times the condition number.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::util::math::Vector3;
+    ///
+    /// // The exact result is -2**-60, but the fast version cancels to zero.
+    /// let a = Vector3::new(1.0 + 2f64.powi(-30), 1.0, 0.0);
+    /// let b = Vector3::new(1.0 - 2f64.powi(-30), -1.0, 0.0);
+    /// assert_eq!(a.dot_prod(&b), 0.0);
+    /// assert_eq!(a.dot_prod_accurate(&b), -2f64.powi(-60));
+    /// ```
+    pub fn dot_prod_accurate(&self, other: &Vector3<f64>) -> f64 {
+        let (mut p, mut s) = two_product(self.x, other.x);
+        for (a, b) in [(self.y, other.y), (self.z, other.z)] {
+            let (h, r) = two_product(a, b);
+            let (sum, q) = two_sum(p, h);
+            p = sum;
+            s += q + r;
+        }
+        p + s
+    }
+
+    /// Cross product where each component is computed with an error of at
+    /// most 1.5 ulps (using fused multiply-adds), rather than the error
+    /// relative to the magnitude of the inputs that the default cross_prod()
+    /// has. This makes the direction of the result accurate even when the
+    /// two vectors are nearly parallel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::util::math::Vector3;
+    ///
+    /// // The z component is (1 + t)**2 - 1, which the fast version rounds.
+    /// let t = 2f64.powi(-27);
+    /// let a = Vector3::new(1.0 + t, 1.0, 0.0);
+    /// let b = Vector3::new(1.0, 1.0 + t, 0.0);
+    /// let exact = 2.0 * t + t * t;
+    /// assert_ne!(a.cross_prod(&b).z(), exact);
+    /// assert_eq!(a.cross_prod_accurate(&b).z(), exact);
+    /// ```
+    pub fn cross_prod_accurate(&self, other: &Vector3<f64>) -> Vector3<f64> {
+        Vector3::new(
+            diff_of_products(self.y, other.z, self.z, other.y),
+            diff_of_products(self.z, other.x, self.x, other.z),
+            diff_of_products(self.x, other.y, self.y, other.x),
+        )
+    }
+
+    /// Returns the scalar triple product a . (b x c), i.e. the determinant
+    /// of the matrix whose rows are a, b, c. Its sign is the orientation of
+    /// the three vectors, which is what s2predicates::sign() computes.
+    ///
+    /// The cross product is evaluated exactly in double-double arithmetic and
+    /// the dot product is accumulated in double-double, so the result is as
+    /// accurate as if it were computed with about 106 bits of precision. This
+    /// is considerably more expensive than the naive
+    /// a.dot_prod(&b.cross_prod(&c)), so it should only be used when that
+    /// value is too close to zero to trust its sign.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use s2shell::util::math::Vector3;
+    ///
+    /// let a = Vector3::new(1.0, 0.0, 0.0);
+    /// let b = Vector3::new(0.0, 1.0, 0.0);
+    /// let c = Vector3::new(0.0, 0.0, 1.0);
+    /// assert_eq!(Vector3::scalar_triple_product(&a, &b, &c), 1.0);
+    /// assert_eq!(Vector3::scalar_triple_product(&b, &a, &c), -1.0);
+    /// ```
+    pub fn scalar_triple_product(a: &Vector3<f64>, b: &Vector3<f64>, c: &Vector3<f64>) -> f64 {
+        let bc_x = DoubleDouble::from_product(b.y, c.z) - DoubleDouble::from_product(b.z, c.y);
+        let bc_y = DoubleDouble::from_product(b.z, c.x) - DoubleDouble::from_product(b.x, c.z);
+        let bc_z = DoubleDouble::from_product(b.x, c.y) - DoubleDouble::from_product(b.y, c.x);
+        (bc_x * a.x + bc_y * a.y + bc_z * a.z).to_f64_rounded()
+    }
+}
+
 macro_rules! impl_vector_ops {
     ($vector:ident, $($field:ident),+) => {
         impl<T: Scalar> Add for $vector<T> {
@@ -452,6 +604,20 @@ macro_rules! impl_vector_ops {
             }
         }
 
+        impl<T: Scalar + Neg<Output = T>> Neg for $vector<T> {
+            type Output = Self;
+            fn neg(self) -> Self::Output {
+                $vector::new($(-self.$field),+)
+            }
+        }
+
+        impl<T: Scalar + Neg<Output = T>> Neg for &$vector<T> {
+            type Output = $vector<T>;
+            fn neg(self) -> Self::Output {
+                $vector::new($(-self.$field),+)
+            }
+        }
+
         impl<T: Scalar> Mul<T> for $vector<T> {
             type Output = Self;
             fn mul(self, scalar: T) -> Self::Output {
@@ -465,6 +631,30 @@ macro_rules! impl_vector_ops {
                 $vector::new($(self.$field * scalar),+)
             }
         }
+
+        impl<T: Scalar> Sum for $vector<T> {
+            fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+                iter.fold($vector::zero(), |acc, v| acc + v)
+            }
+        }
+
+        impl<'a, T: Scalar> Sum<&'a $vector<T>> for $vector<T> {
+            fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+                iter.fold($vector::zero(), |acc, v| acc + *v)
+            }
+        }
+
+        impl<T: Scalar + Float> $vector<T> {
+            /// Returns the componentwise arithmetic mean of the given vectors,
+            /// or None if there are none.
+            pub fn mean(vectors: &[$vector<T>]) -> Option<$vector<T>> {
+                if vectors.is_empty() {
+                    return None;
+                }
+                let n = T::from(vectors.len())?;
+                Some(vectors.iter().sum::<$vector<T>>() * (T::one() / n))
+            }
+        }
     }
 }
 
@@ -601,6 +791,21 @@ where
     }
 }
 
+impl<T> UlpsEq for Vector2<T>
+where
+    T: Scalar + Float + UlpsEq,
+    T::Epsilon: Copy,
+{
+    fn default_max_ulps() -> u32 {
+        T::default_max_ulps()
+    }
+
+    fn ulps_eq(&self, other: &Self, epsilon: Self::Epsilon, max_ulps: u32) -> bool {
+        T::ulps_eq(&self.x, &other.x, epsilon, max_ulps)
+            && T::ulps_eq(&self.y, &other.y, epsilon, max_ulps)
+    }
+}
+
 impl<T> AbsDiffEq for Vector3<T>
 where
     T: Scalar + AbsDiffEq,
@@ -640,9 +845,31 @@ where
     }
 }
 
+impl<T> UlpsEq for Vector3<T>
+where
+    T: Scalar + Float + UlpsEq,
+    T::Epsilon: Copy,
+{
+    fn default_max_ulps() -> u32 {
+        T::default_max_ulps()
+    }
+
+    fn ulps_eq(&self, other: &Self, epsilon: Self::Epsilon, max_ulps: u32) -> bool {
+        T::ulps_eq(&self.x, &other.x, epsilon, max_ulps)
+            && T::ulps_eq(&self.y, &other.y, epsilon, max_ulps)
+            && T::ulps_eq(&self.z, &other.z, epsilon, max_ulps)
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use approx::{assert_relative_eq, assert_ulps_eq, assert_ulps_ne};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
     use super::*;
+    use crate::util::math::exact_float::Expansion;
 
     #[test]
     fn test_vector3_add() {
@@ -659,6 +886,173 @@ mod tests {
         assert_eq!(v1 - v2, Vector3::new(3, 3, 3));
     }
 
+    fn assert_ortho(v: Vector3<f64>) {
+        let ortho = v.ortho();
+        assert!(
+            v.dot_prod(&ortho).abs() <= 1e-15 * v.norm(),
+            "{v:?} is not orthogonal to {ortho:?}"
+        );
+        assert_relative_eq!(ortho.norm(), 1.0, epsilon = 1e-15);
+    }
+
+    #[test]
+    fn test_ulps_eq() {
+        let v = Vector3::new(0.1, 0.2, 0.3);
+        let w = Vector3::new(0.1, 0.2_f64.next_up(), 0.3);
+        assert_ne!(v, w);
+        assert_ulps_eq!(v, w);
+        assert_ulps_eq!(v, w, epsilon = 0.0, max_ulps = 1);
+        assert_ulps_ne!(v, w, epsilon = 0.0, max_ulps = 0);
+        assert_ulps_ne!(v, Vector3::new(0.1, 0.2, 0.3000001));
+
+        let v = Vector2::new(1.0, -2.0);
+        let w = Vector2::new(1.0_f64.next_down(), -2.0);
+        assert_ulps_eq!(v, w);
+        assert_ulps_ne!(v, w, epsilon = 0.0, max_ulps = 0);
+    }
+
+    #[test]
+    fn test_vector3_sum() {
+        let vectors = vec![
+            Vector3::new(1, 2, 3),
+            Vector3::new(4, 5, 6),
+            Vector3::new(-7, 0, 1),
+        ];
+        assert_eq!(
+            vectors.iter().sum::<Vector3<i32>>(),
+            Vector3::new(-2, 7, 10)
+        );
+        assert_eq!(
+            vectors.into_iter().sum::<Vector3<i32>>(),
+            Vector3::new(-2, 7, 10)
+        );
+        assert_eq!(
+            core::iter::empty::<Vector3<f64>>().sum::<Vector3<f64>>(),
+            Vector3::zero()
+        );
+        assert_eq!(
+            [Vector2::new(1.5, 2.0), Vector2::new(0.5, -1.0)]
+                .iter()
+                .sum::<Vector2<f64>>(),
+            Vector2::new(2.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn test_vector3_mean() {
+        // The centroid of points that are symmetric about the origin is the
+        // origin.
+        let mut rng = StdRng::seed_from_u64(30);
+        let mut points = Vec::new();
+        for _ in 0..100 {
+            let p = Vector3::new(
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+            );
+            points.push(p);
+            points.push(-p);
+        }
+        assert!(Vector3::mean(&points).unwrap().norm() <= 1e-15);
+
+        let points = [Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 3.0, 2.0)];
+        assert_eq!(Vector3::mean(&points), Some(Vector3::new(0.5, 1.5, 1.0)));
+        assert_eq!(Vector3::<f64>::mean(&[]), None);
+        assert_eq!(
+            Vector2::mean(&[Vector2::new(1.0, 2.0)]),
+            Some(Vector2::new(1.0, 2.0))
+        );
+    }
+
+    #[test]
+    fn test_vector3_ortho_axes() {
+        let expected = [
+            (Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, -1.0, 0.0)),
+            (Vector3::new(-1.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0)),
+            (Vector3::new(0.0, 1.0, 0.0), Vector3::new(0.0, 0.0, -1.0)),
+            (Vector3::new(0.0, -1.0, 0.0), Vector3::new(0.0, 0.0, 1.0)),
+            (Vector3::new(0.0, 0.0, 1.0), Vector3::new(-1.0, 0.0, 0.0)),
+            (Vector3::new(0.0, 0.0, -1.0), Vector3::new(1.0, 0.0, 0.0)),
+        ];
+        for (v, ortho) in expected {
+            assert_eq!(v.ortho(), ortho);
+            assert_ortho(v);
+        }
+    }
+
+    #[test]
+    fn test_vector3_ortho_ties() {
+        let ties = [
+            Vector3::new(1.0, 1.0, 0.0),
+            Vector3::new(1.0, 0.0, -1.0),
+            Vector3::new(0.0, -1.0, 1.0),
+            Vector3::new(1.0, 1.0, 1.0),
+            Vector3::new(-2.0, 2.0, -2.0),
+        ];
+        for v in ties {
+            assert_ortho(v);
+        }
+        assert_eq!(Vector3::new(1.0, 1.0, 0.0).largest_abs_component(), 1);
+        assert_eq!(Vector3::new(1.0, 0.0, -1.0).largest_abs_component(), 2);
+        assert_eq!(Vector3::new(1.0, 1.0, 1.0).largest_abs_component(), 2);
+    }
+
+    #[test]
+    fn test_vector3_ortho_random() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..1000 {
+            let v = Vector3::new(
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+            );
+            assert_ortho(v);
+        }
+    }
+
+    #[test]
+    fn test_vector3_ortho_raw() {
+        let v = Vector3::new(3, -4, 2);
+        assert_eq!(v.ortho_raw(), Vector3::new(0, 2, 4));
+        assert_eq!(v.dot_prod(&v.ortho_raw()), 0);
+        assert_eq!(v.ortho_raw().normalize(), v.ortho());
+    }
+
+    #[test]
+    fn test_vector3_angle_unit() {
+        // Compare both methods against the exact angle for angles ranging
+        // from 1e-9 to Pi, using random rotations of the x-y plane.
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut theta = 1e-9_f64;
+        while theta < core::f64::consts::PI {
+            let phi = rng.gen_range(-core::f64::consts::PI..core::f64::consts::PI);
+            let a = Vector3::new(phi.cos(), phi.sin(), 0.0);
+            let b = Vector3::new((phi + theta).cos(), (phi + theta).sin(), 0.0);
+            let unit_error = (a.angle_unit(&b) - theta).abs();
+            let atan2_error = (a.angle(&b) - theta).abs();
+            // The exact points have rounding errors of their own, so the
+            // error bound is relative to the angle plus a few ulps of Pi.
+            assert!(unit_error <= 1e-15 * theta + 2e-15, "{theta}: {unit_error}");
+            assert!(
+                atan2_error <= 1e-15 * theta + 2e-15,
+                "{theta}: {atan2_error}"
+            );
+            theta *= 1.5;
+        }
+
+        // Identical and antipodal vectors.
+        let v = Vector3::new(0.0, 0.6, 0.8);
+        assert_eq!(v.angle_unit(&v), 0.0);
+        assert_relative_eq!(v.angle_unit(&-v), core::f64::consts::PI);
+    }
+
+    #[test]
+    fn test_vector3_neg() {
+        let v = Vector3::new(1, -2, 3);
+        assert_eq!(-v, Vector3::new(-1, 2, -3));
+        assert_eq!(-&v, Vector3::new(-1, 2, -3));
+    }
+
     #[test]
     fn test_vector3_mul() {
         let v = Vector3::new(2, 3, 4);
@@ -666,4 +1060,153 @@ mod tests {
         assert_eq!(v * s, Vector3::new(6, 9, 12));
         assert_eq!(s * v, Vector3::new(6, 9, 12));
     }
+
+    fn random_vector(rng: &mut StdRng) -> Vector3<f64> {
+        Vector3::new(
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+        )
+    }
+
+    // Adds the exact product a * b to "sum".
+    fn grow_product(sum: &mut Expansion, a: f64, b: f64) {
+        let (hi, lo) = two_product(a, b);
+        sum.grow(hi);
+        sum.grow(lo);
+    }
+
+    fn exact_dot_prod(a: &Vector3<f64>, b: &Vector3<f64>) -> f64 {
+        let mut sum = Expansion::new();
+        for i in 0..3 {
+            grow_product(&mut sum, a[i], b[i]);
+        }
+        sum.estimate()
+    }
+
+    fn exact_cross_prod(a: &Vector3<f64>, b: &Vector3<f64>) -> Vector3<f64> {
+        let component = |i: usize, j: usize| {
+            let mut sum = Expansion::new();
+            grow_product(&mut sum, a[i], b[j]);
+            grow_product(&mut sum, -a[j], b[i]);
+            sum.estimate()
+        };
+        Vector3::new(component(1, 2), component(2, 0), component(0, 1))
+    }
+
+    fn exact_scalar_triple_product(
+        a: &Vector3<f64>,
+        b: &Vector3<f64>,
+        c: &Vector3<f64>,
+    ) -> Expansion {
+        let mut sum = Expansion::new();
+        for (i, j, k, s) in [
+            (0, 1, 2, 1.0),
+            (1, 2, 0, 1.0),
+            (2, 0, 1, 1.0),
+            (0, 2, 1, -1.0),
+            (1, 0, 2, -1.0),
+            (2, 1, 0, -1.0),
+        ] {
+            let (hi, lo) = two_product(b[j], c[k]);
+            grow_product(&mut sum, s * a[i], hi);
+            grow_product(&mut sum, s * a[i], lo);
+        }
+        sum
+    }
+
+    fn assert_within_ulps(actual: f64, expected: f64, ulps: f64) {
+        assert!(
+            (actual - expected).abs() <= ulps * f64::EPSILON * expected.abs(),
+            "{actual:e} != {expected:e}"
+        );
+    }
+
+    #[test]
+    fn test_dot_prod_accurate() {
+        let mut rng = StdRng::seed_from_u64(47);
+        let mut fast_max_error: f64 = 0.0;
+        for _ in 0..1000 {
+            // Nearly orthogonal vectors, so that the dot product cancels almost
+            // completely. Perturb one component of "b" by a single ulp.
+            let a = random_vector(&mut rng);
+            let mut b = a.ortho_raw();
+            let k = rng.gen_range(0..3);
+            if b[k] == 0.0 {
+                continue;
+            }
+            b[k] = b[k].next_up();
+            let exact = exact_dot_prod(&a, &b);
+            if exact == 0.0 {
+                continue;
+            }
+            assert_within_ulps(a.dot_prod_accurate(&b), exact, 2.0);
+            fast_max_error = fast_max_error.max((a.dot_prod(&b) - exact).abs() / exact.abs());
+
+            // Well-conditioned inputs are correctly rounded (up to an ulp).
+            let c = random_vector(&mut rng);
+            assert_within_ulps(a.dot_prod_accurate(&c), exact_dot_prod(&a, &c), 1.0);
+        }
+        // The default version has no relative accuracy for these inputs.
+        assert!(fast_max_error > 1e-3);
+    }
+
+    #[test]
+    fn test_cross_prod_accurate() {
+        let mut rng = StdRng::seed_from_u64(48);
+        let mut fast_max_error: f64 = 0.0;
+        for _ in 0..1000 {
+            // Nearly parallel vectors that differ by one ulp in one component.
+            let a = random_vector(&mut rng);
+            let mut b = a;
+            let k = rng.gen_range(0..3);
+            b[k] = b[k].next_up();
+            let exact = exact_cross_prod(&a, &b);
+            let accurate = a.cross_prod_accurate(&b);
+            let fast = a.cross_prod(&b);
+            for i in 0..3 {
+                assert_within_ulps(accurate[i], exact[i], 1.5);
+                if exact[i] != 0.0 {
+                    fast_max_error =
+                        fast_max_error.max((fast[i] - exact[i]).abs() / exact[i].abs());
+                }
+            }
+        }
+        assert!(fast_max_error > 1e-3);
+    }
+
+    #[test]
+    fn test_scalar_triple_product() {
+        let mut rng = StdRng::seed_from_u64(49);
+        let mut fast_wrong_sign = 0;
+        for _ in 0..1000 {
+            // Three nearly coplanar vectors: c is a + b perturbed by one ulp.
+            let a = random_vector(&mut rng);
+            let b = random_vector(&mut rng);
+            let mut c = a + b;
+            let k = rng.gen_range(0..3);
+            c[k] = c[k].next_up();
+            let exact = exact_scalar_triple_product(&a, &b, &c);
+            let result = Vector3::scalar_triple_product(&a, &b, &c);
+            assert_eq!(
+                result.partial_cmp(&0.0).map(|o| o as i32),
+                Some(exact.sign())
+            );
+            // The error is relative to the size of the terms (at most about 8),
+            // not to the result.
+            assert!((result - exact.estimate()).abs() <= 8.0 * f64::EPSILON * f64::EPSILON);
+            let fast = a.dot_prod(&b.cross_prod(&c));
+            if (fast > 0.0) != (exact.sign() > 0) {
+                fast_wrong_sign += 1;
+            }
+        }
+        assert!(fast_wrong_sign > 0);
+
+        // Exactly coplanar vectors.
+        let a = Vector3::new(0.1, 0.2, 0.3);
+        assert_eq!(
+            Vector3::scalar_triple_product(&a, &(a * 2.0), &(a * 4.0)),
+            0.0
+        );
+    }
 }
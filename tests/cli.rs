@@ -0,0 +1,87 @@
+// Runs the s2shell binary with sample arguments and checks its output.
+
+use std::process::{Command, Output};
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_s2shell"))
+        .args(args)
+        .output()
+        .expect("failed to run s2shell")
+}
+
+fn stdout(args: &[&str]) -> String {
+    let output = run(args);
+    assert!(
+        output.status.success(),
+        "s2shell {args:?} failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn assert_fails(args: &[&str], message: &str) {
+    let output = run(args);
+    assert_eq!(output.status.code(), Some(2), "s2shell {args:?}");
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains(message), "s2shell {args:?}: {stderr}");
+}
+
+#[test]
+fn test_cell() {
+    assert_eq!(
+        stdout(&["cell", "89c259"]),
+        "\
+id:       0x89c2590000000000 (9926594385212866560)
+token:    89c259
+cell:     4/1032010230
+level:    10
+center:   40.755098,-73.983003
+vertex 0: 40.801268,-74.030012
+vertex 1: 40.722226,-74.030012
+vertex 2: 40.708880,-73.935982
+vertex 3: 40.787917,-73.935982
+"
+    );
+}
+
+#[test]
+fn test_commands_agree() {
+    // The same cell given by id (decimal and hex), token and a point in it.
+    let expected = stdout(&["cell", "89c259"]);
+    assert_eq!(stdout(&["token", "9926594385212866560"]), expected);
+    assert_eq!(stdout(&["token", "0x89c2590000000000"]), expected);
+    assert_eq!(stdout(&["token", "0X89C2590000000000"]), expected);
+    assert_eq!(stdout(&["cell", "89C259"]), expected);
+    assert_eq!(
+        stdout(&["latlng", "40.7550976", "-73.9830029", "10"]),
+        expected
+    );
+}
+
+#[test]
+fn test_latlng() {
+    // The default level is a leaf cell.
+    let out = stdout(&["latlng", "-33.8688", "151.2093"]);
+    assert!(out.contains("level:    30\n"), "{out}");
+    assert!(out.contains("center:   -33.868800,151.209300\n"), "{out}");
+
+    let out = stdout(&["latlng", "90", "0", "0"]);
+    assert!(out.starts_with("id:       0x5000000000000000"), "{out}");
+    assert!(out.contains("cell:     2/\n"), "{out}");
+}
+
+#[test]
+fn test_errors() {
+    assert_fails(&[], "usage:");
+    assert_fails(&["frobnicate"], "usage:");
+    assert_fails(&["cell"], "usage:");
+    assert_fails(&["cell", "89c259", "extra"], "usage:");
+    assert_fails(&["cell", "xyz"], "invalid cell token");
+    assert_fails(&["cell", "X"], "invalid cell token");
+    assert_fails(&["token", "12ab"], "invalid cell id");
+    assert_fails(&["token", "0"], "not a valid cell");
+    assert_fails(&["latlng", "91", "0"], "invalid latitude");
+    assert_fails(&["latlng", "0", "east"], "invalid longitude");
+    assert_fails(&["latlng", "0", "0", "31"], "invalid level");
+}
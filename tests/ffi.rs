@@ -0,0 +1,122 @@
+//! Exercises the C interface through raw pointers, as a C caller would.
+#![cfg(feature = "ffi")]
+
+use std::{ffi::c_char, path::Path, ptr};
+
+use s2shell::{
+    ffi::*,
+    s2::{S2CellId, S2LatLng},
+};
+
+const EXPORTED: [&str; 7] = [
+    "s2_cellid_from_latlng_degrees",
+    "s2_cellid_to_latlng_degrees",
+    "s2_cellid_parent",
+    "s2_cellid_contains",
+    "s2_cellid_level",
+    "s2_cellid_to_token",
+    "s2_cellid_from_token",
+];
+
+#[test]
+fn header_declares_all_functions() {
+    let header =
+        std::fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("include/s2shell.h"))
+            .unwrap();
+    for name in EXPORTED {
+        assert!(
+            header.contains(&format!(" {name}(")),
+            "{name} is not declared"
+        );
+    }
+    assert_eq!(header.matches("s2_cellid_").count(), EXPORTED.len());
+}
+
+#[test]
+fn cell_id_round_trip() {
+    let id = s2_cellid_from_latlng_degrees(37.5, -122.25, 12);
+    let expected =
+        S2CellId::from_lat_lng(&S2LatLng::from_degrees(37.5, -122.25)).parent_at_level(12);
+    assert_eq!(id, expected.id());
+    assert_eq!(s2_cellid_level(id), 12);
+
+    let (mut lat, mut lng) = (0.0, 0.0);
+    assert!(unsafe { s2_cellid_to_latlng_degrees(id, &mut lat, &mut lng) });
+    // The center of a level 12 cell is within a few kilometers of the point.
+    assert!((lat - 37.5).abs() < 0.05 && (lng + 122.25).abs() < 0.05);
+
+    let parent = s2_cellid_parent(id, 5);
+    assert_eq!(s2_cellid_level(parent), 5);
+    assert!(s2_cellid_contains(parent, id));
+    assert!(!s2_cellid_contains(id, parent));
+    assert_eq!(s2_cellid_parent(id, 12), id);
+}
+
+#[test]
+fn tokens_through_buffers() {
+    let id = s2_cellid_from_latlng_degrees(-10.0, 20.0, 30);
+    let mut buf = [0x7f as c_char; 17];
+    let len = unsafe { s2_cellid_to_token(id, buf.as_mut_ptr(), buf.len()) };
+    assert_eq!(len, 16);
+    assert_eq!(buf[len], 0);
+    assert_eq!(unsafe { s2_cellid_from_token(buf.as_ptr(), len) }, id);
+
+    // A buffer without room for the terminator is left untouched, but the
+    // required length is still returned.
+    let face = S2CellId::from_face(3).id();
+    let mut small = [0x7f as c_char; 1];
+    assert_eq!(
+        unsafe { s2_cellid_to_token(face, small.as_mut_ptr(), 1) },
+        1
+    );
+    assert_eq!(small[0], 0x7f);
+    assert_eq!(unsafe { s2_cellid_to_token(face, ptr::null_mut(), 0) }, 1);
+    let mut exact = [0 as c_char; 2];
+    assert_eq!(
+        unsafe { s2_cellid_to_token(face, exact.as_mut_ptr(), 2) },
+        1
+    );
+    assert_eq!(exact, [b'7' as c_char, 0]);
+
+    // Tokens need not be NUL-terminated.
+    let token = b"89c25";
+    let decoded = unsafe { s2_cellid_from_token(token.as_ptr().cast(), token.len()) };
+    assert_eq!(decoded, S2CellId::from_token("89c25").id());
+    assert_ne!(decoded, 0);
+}
+
+#[test]
+fn errors_return_sentinels() {
+    assert_eq!(s2_cellid_from_latlng_degrees(91.0, 0.0, 10), 0);
+    assert_eq!(s2_cellid_from_latlng_degrees(f64::NAN, 0.0, 10), 0);
+    assert_eq!(s2_cellid_from_latlng_degrees(0.0, f64::INFINITY, 10), 0);
+    assert_eq!(s2_cellid_from_latlng_degrees(0.0, 0.0, 31), 0);
+    assert_eq!(s2_cellid_from_latlng_degrees(0.0, 0.0, -1), 0);
+    // Longitudes are wrapped.
+    assert_eq!(
+        s2_cellid_from_latlng_degrees(0.0, 190.0, 10),
+        s2_cellid_from_latlng_degrees(0.0, -170.0, 10)
+    );
+
+    let id = s2_cellid_from_latlng_degrees(1.0, 2.0, 10);
+    assert_eq!(s2_cellid_parent(id, 11), 0);
+    assert_eq!(s2_cellid_parent(id, -1), 0);
+    assert_eq!(s2_cellid_parent(0, 0), 0);
+    assert_eq!(s2_cellid_level(0), -1);
+    assert_eq!(s2_cellid_level(u64::MAX), -1);
+    assert!(!s2_cellid_contains(0, id));
+    assert!(!s2_cellid_contains(id, 0));
+
+    let mut lat = 1.5;
+    assert!(!unsafe { s2_cellid_to_latlng_degrees(0, &mut lat, &mut 0.0) });
+    assert!(!unsafe { s2_cellid_to_latlng_degrees(id, ptr::null_mut(), &mut lat) });
+    assert_eq!(lat, 1.5);
+
+    assert_eq!(unsafe { s2_cellid_from_token(ptr::null(), 3) }, 0);
+    for token in [&b"xyz"[..], b"12345678901234567", b"\xff\xfe"] {
+        assert_eq!(
+            unsafe { s2_cellid_from_token(token.as_ptr().cast(), token.len()) },
+            0
+        );
+    }
+}
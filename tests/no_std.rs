@@ -0,0 +1,89 @@
+//! Checks that the crate builds without the standard library.
+//!
+//! With the "std" feature disabled the crate is `#![no_std]`, so most uses of
+//! `std` fail to compile regardless of the target. The default run is only a
+//! `cargo check` for the host, though, which still has `std` available to
+//! dependencies and never links anything. The embedded tests additionally
+//! build for a target without `std` (thumbv7em-none-eabihf, or the target
+//! named by S2SHELL_NO_STD_TARGET) when it has been installed with
+//! `rustup target add`, and print a message and pass otherwise.
+
+use std::{
+    env,
+    io::{self, Write},
+    path::Path,
+    process::Command,
+};
+
+const EMBEDDED_TARGET: &str = "thumbv7em-none-eabihf";
+
+fn run_cargo(subcommand: &str, features: &[&str], target: Option<&str>) {
+    let manifest_dir = env!("CARGO_MANIFEST_DIR");
+    let mut cmd = Command::new(env!("CARGO"));
+    cmd.current_dir(manifest_dir)
+        .args([subcommand, "--lib", "--no-default-features"])
+        .arg("--target-dir")
+        .arg(Path::new(manifest_dir).join("target").join("no_std"));
+    if !features.is_empty() {
+        cmd.args(["--features", &features.join(",")]);
+    }
+    if let Some(target) = target {
+        cmd.args(["--target", target]);
+    }
+    let output = cmd.output().expect("failed to run cargo");
+    assert!(
+        output.status.success(),
+        "no_std {subcommand} for {} with features {features:?} failed:\n{}",
+        target.unwrap_or("the host"),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// Returns true if the standard library for "target" has been installed.
+fn target_installed(target: &str) -> bool {
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| String::from("rustc"));
+    let Ok(output) = Command::new(rustc)
+        .args(["--print", "target-libdir", "--target", target])
+        .output()
+    else {
+        return false;
+    };
+    let libdir = String::from_utf8_lossy(&output.stdout);
+    output.status.success() && Path::new(libdir.trim()).is_dir()
+}
+
+fn build_embedded(features: &[&str]) {
+    let target =
+        env::var("S2SHELL_NO_STD_TARGET").unwrap_or_else(|_| String::from(EMBEDDED_TARGET));
+    if !target_installed(&target) {
+        // Written to stderr directly so that the test harness does not
+        // capture it.
+        let _ = writeln!(
+            io::stderr(),
+            "skipping no_std build for {target}: target not installed \
+             (run `rustup target add {target}`)"
+        );
+        return;
+    }
+    run_cargo("build", features, Some(&target));
+}
+
+#[test]
+fn test_no_std() {
+    run_cargo("check", &[], None);
+}
+
+#[test]
+fn test_no_std_alloc() {
+    run_cargo("check", &["alloc"], None);
+}
+
+#[test]
+fn test_no_std_embedded() {
+    build_embedded(&[]);
+}
+
+#[test]
+fn test_no_std_alloc_embedded() {
+    build_embedded(&["alloc"]);
+}